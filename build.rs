@@ -1,3 +1,6 @@
 fn main() {
+    // Icon shown for the executable in Explorer and the taskbar (Windows only)
+    println!("cargo:rerun-if-changed=app-icon.rc");
+    println!("cargo:rerun-if-changed=assets/icon.ico");
     embed_resource::compile("app-icon.rc", embed_resource::NONE);
 }
@@ -0,0 +1,46 @@
+//! Loading a large banned password list and analyzing a vault against it.
+//!
+//! Run with `cargo bench -p passman-core --bench banned_list`. The list is
+//! read into hash sets once, so analysis time should barely move with its
+//! size.
+
+use std::fmt::Write;
+use std::time::Instant;
+use passman_core::health::{AnalysisScope, BannedList, PasswordHealthAnalyzer};
+use passman_core::{Entry, Vault};
+
+const LIST_LINES: usize = 200_000;
+const ENTRIES: usize = 1_000;
+
+fn main() {
+    // Half plaintext passwords, half SHA-1 hashes with breach counts
+    let mut text = String::with_capacity(LIST_LINES * 48);
+    for i in 0..LIST_LINES / 2 {
+        writeln!(text, "Company{}!", i).unwrap();
+        writeln!(text, "{:040X}:{}", (i as u128).wrapping_mul(0x9E37_79B9_7F4A_7C15_F39C_C060_5CED_C835), i).unwrap();
+    }
+
+    let start = Instant::now();
+    let list = BannedList::parse(&text);
+    println!("parse {} lines: {:?} ({} kept)", LIST_LINES, start.elapsed(), list.len());
+
+    let mut vault = Vault::new();
+    for i in 0..ENTRIES {
+        let password = if i % 10 == 0 { format!("company{}!", i) } else { format!("Unlisted-{}-Passphrase", i) };
+        vault.add_entry(format!("site-{}", i), Entry::new("me".to_string(), password, None));
+    }
+
+    let plain = PasswordHealthAnalyzer::new().with_banned_list(BannedList::default());
+    let start = Instant::now();
+    plain.analyze_vault(&vault, AnalysisScope::ActiveOnly);
+    let baseline = start.elapsed();
+
+    let banned = PasswordHealthAnalyzer::new().with_banned_list(list);
+    let start = Instant::now();
+    let reports = banned.analyze_vault(&vault, AnalysisScope::ActiveOnly);
+    let with_list = start.elapsed();
+
+    let matched = reports.iter().filter(|report| report.recommendations.iter().any(|r| r.contains("banned"))).count();
+    println!("analyze {} entries: {:?} without a list, {:?} with it ({} banned)", ENTRIES, baseline, with_list, matched);
+    assert_eq!(matched, ENTRIES / 10);
+}
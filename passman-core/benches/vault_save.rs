@@ -0,0 +1,79 @@
+//! Edit-save round trips on a large vault, on a simulated slow share.
+//!
+//! Run with `cargo bench -p passman-core --bench vault_save`. Saves with a
+//! cached key write the file and take its fingerprint from memory; a cold
+//! save reads only the header and salt. Before, both read the whole file.
+
+use std::fs::File;
+use std::io::Read;
+use std::thread;
+use std::time::{Duration, Instant};
+use passman_core::sample::sample_vault;
+use passman_core::{Vault, VaultManager, Zeroizing};
+
+/// Roughly the size of the vault to save
+const VAULT_BYTES: usize = 5 * 1024 * 1024;
+/// Throughput of the simulated share
+const SHARE_BYTES_PER_SEC: u64 = 20 * 1024 * 1024;
+const ROUNDS: u32 = 5;
+
+/// A reader that takes as long as a slow network share would
+struct Throttled<R>(R);
+
+impl<R: Read> Read for Throttled<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.0.read(buf)?;
+        thread::sleep(Duration::from_secs_f64(read as f64 / SHARE_BYTES_PER_SEC as f64));
+        Ok(read)
+    }
+}
+
+fn main() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("large.dat");
+    let path = path.to_str().unwrap();
+    let password = Zeroizing::new("correct horse battery staple".to_string());
+    let key = VaultManager::init(&password, Some(path)).unwrap();
+
+    let mut vault = Vault::new();
+    let note = "n".repeat(64 * 1024);
+    for (id, entry) in sample_vault(1, VAULT_BYTES / note.len(), chrono::Utc::now()).iter() {
+        let mut entry = entry.clone();
+        entry.note = Some(note.clone());
+        vault.add_entry(id.to_string(), entry);
+    }
+    VaultManager::save_with_key(&vault, &key, Some(path)).unwrap();
+    let size = std::fs::metadata(path).unwrap().len();
+    println!("vault: {} entries, {} bytes", vault.len(), size);
+
+    // Key parameters for a cold save, the old way and the new
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        let mut buffer = Vec::new();
+        Throttled(File::open(path).unwrap()).read_to_end(&mut buffer).unwrap();
+        VaultManager::key_params_from(buffer.as_slice()).unwrap();
+    }
+    let whole_file = start.elapsed() / ROUNDS;
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        VaultManager::key_params_from(Throttled(File::open(path).unwrap())).unwrap();
+    }
+    let key_section = start.elapsed() / ROUNDS;
+    println!("key parameters over the share: {:?} reading the file, {:?} reading the key section", whole_file, key_section);
+
+    // Edit-save with a cached key: before, the fingerprint read the file back
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        VaultManager::save_with_key(&vault, &key, Some(path)).unwrap();
+        let mut buffer = Vec::new();
+        Throttled(File::open(path).unwrap()).read_to_end(&mut buffer).unwrap();
+    }
+    let read_back = start.elapsed() / ROUNDS;
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        VaultManager::save_and_fingerprint(&vault, &key, Some(path)).unwrap();
+    }
+    let in_memory = start.elapsed() / ROUNDS;
+    println!("edit-save: {:?} reading the file back, {:?} fingerprinting in memory", read_back, in_memory);
+    assert!(key_section < whole_file);
+}
@@ -0,0 +1,27 @@
+//! Feed arbitrary bytes to the vault loader. It must return a vault or an
+//! error for every input, never panic.
+//!
+//! Loads with a fixed key rather than a password, so inputs don't spend
+//! their time in Argon2 with whatever costs their header claims.
+
+#![no_main]
+
+use std::sync::OnceLock;
+use argon2::password_hash::SaltString;
+use libfuzzer_sys::fuzz_target;
+use passman_core::crypto::KdfParams;
+use passman_core::vault::VaultKey;
+use passman_core::VaultManager;
+
+fn key() -> &'static VaultKey {
+    static KEY: OnceLock<VaultKey> = OnceLock::new();
+    KEY.get_or_init(|| {
+        let salt = SaltString::from_b64("c29tZXNhbHRzb21lc2FsdA").unwrap();
+        let kdf = KdfParams { memory_kb: 1024, time_cost: 1, parallelism: 1 };
+        VaultKey::derive("fuzz", salt, kdf).unwrap()
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    let _ = VaultManager::load_bytes_with_vault_key(key(), data);
+});
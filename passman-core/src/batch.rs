@@ -0,0 +1,312 @@
+//! Batch Edit Module
+//!
+//! Several entry changes applied as one: every operation runs in order
+//! against the vault in memory, and if any of them can't be applied the
+//! vault is put back as it was. `PassmanCore::apply_batch` saves the result
+//! once; the importers build their changes as operations too.
+
+use std::collections::HashMap;
+use crate::config::identity;
+use crate::import_export::ConflictPolicy;
+use crate::model::{Entry, Vault};
+
+/// One change to the vault
+#[derive(Debug, Clone)]
+pub enum EntryOp {
+    /// Add `entry` under `id`, settling a taken id by `on_conflict`.
+    /// Recorded as created by `general.identity` unless it names a creator.
+    Add { id: String, entry: Entry, on_conflict: ConflictPolicy },
+    /// Replace the entry under `id`, keeping who created it and recording
+    /// `general.identity` as the editor. Fails if there is no such entry.
+    Update { id: String, entry: Entry },
+    /// Delete the entry under `id` for good (not to the trash). Fails if
+    /// there is no such entry.
+    Remove { id: String },
+    /// Move the entry under `from` to `to`, settling a taken `to` by
+    /// `on_conflict`. Fails if there is no entry under `from`.
+    Rename { from: String, to: String, on_conflict: ConflictPolicy },
+}
+
+impl EntryOp {
+    /// The id the operation is about (`from` for a rename)
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Add { id, .. } | Self::Update { id, .. } | Self::Remove { id } => id,
+            Self::Rename { from, .. } => from,
+        }
+    }
+}
+
+/// What became of one operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpOutcome {
+    Applied,
+    /// Applied over the entry that already had the target id
+    Replaced,
+    /// Applied under a free id because the target was taken
+    Renamed { to: String },
+    /// Left out because the target id was taken and the policy is `Skip`
+    Skipped,
+    /// Couldn't be applied; the whole batch is rolled back
+    Failed { reason: String },
+}
+
+/// Outcomes of a batch, in the order the operations were given
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchResult {
+    /// Each operation's id (see [`EntryOp::id`]) and its outcome
+    pub outcomes: Vec<(String, OpOutcome)>,
+    /// False if an operation failed and the vault was left untouched
+    pub committed: bool,
+}
+
+impl BatchResult {
+    /// Whether the vault was changed and needs saving
+    pub fn changed(&self) -> bool {
+        self.committed && self.outcomes.iter().any(|(_, outcome)| !matches!(outcome, OpOutcome::Skipped))
+    }
+
+    /// Operations that failed, with the reason
+    pub fn failures(&self) -> impl Iterator<Item = (&str, &str)> + '_ {
+        self.outcomes.iter().filter_map(|(id, outcome)| match outcome {
+            OpOutcome::Failed { reason } => Some((id.as_str(), reason.as_str())),
+            _ => None,
+        })
+    }
+}
+
+/// Apply `ops` to `vault` in order. Later operations see the effect of
+/// earlier ones. If any fails, every change is undone, but the outcomes
+/// still report each operation up to and including the failed one.
+pub fn apply_ops(vault: &mut Vault, ops: Vec<EntryOp>) -> BatchResult {
+    let mut undo = Undo::default();
+    let mut result = BatchResult { outcomes: Vec::with_capacity(ops.len()), committed: true };
+    let editor = identity();
+
+    for op in ops {
+        let id = op.id().to_string();
+        let outcome = apply_op(vault, op, editor.as_deref(), &mut undo);
+        let failed = matches!(outcome, OpOutcome::Failed { .. });
+        result.outcomes.push((id, outcome));
+        if failed {
+            undo.restore(vault);
+            result.committed = false;
+            break;
+        }
+    }
+    result
+}
+
+fn apply_op(vault: &mut Vault, op: EntryOp, editor: Option<&str>, undo: &mut Undo) -> OpOutcome {
+    match op {
+        EntryOp::Add { id, mut entry, on_conflict } => {
+            if entry.created_by.is_none() {
+                entry.created_by = editor.map(str::to_string);
+            }
+            let Some((id, outcome)) = settle(vault, id, on_conflict) else {
+                return OpOutcome::Skipped;
+            };
+            undo.record(vault, &id);
+            vault.add_entry(id, entry);
+            outcome
+        }
+        EntryOp::Update { id, mut entry } => {
+            let Some(existing) = vault.get_entry(&id) else {
+                return OpOutcome::Failed { reason: format!("No entry '{}' to update", id) };
+            };
+            entry.created_by = entry.created_by.or_else(|| existing.created_by.clone());
+            entry.generation = entry.generation.or_else(|| existing.generation.clone());
+            entry.modified_by = editor.map(str::to_string);
+            undo.record(vault, &id);
+            vault.add_entry(id, entry);
+            OpOutcome::Applied
+        }
+        EntryOp::Remove { id } => {
+            if !vault.contains(&id) {
+                return OpOutcome::Failed { reason: format!("No entry '{}' to remove", id) };
+            }
+            undo.record(vault, &id);
+            vault.remove_entry(&id);
+            OpOutcome::Applied
+        }
+        EntryOp::Rename { from, to, on_conflict } => {
+            if !vault.contains(&from) {
+                return OpOutcome::Failed { reason: format!("No entry '{}' to rename", from) };
+            }
+            if from == to {
+                return OpOutcome::Applied;
+            }
+            let Some((to, outcome)) = settle(vault, to, on_conflict) else {
+                return OpOutcome::Skipped;
+            };
+            undo.record(vault, &from);
+            undo.record(vault, &to);
+            // Links to it follow the rename
+            for linking in vault.linked_from(&from) {
+                undo.record(vault, linking);
+            }
+            vault.rename_entry(&from, to);
+            outcome
+        }
+    }
+}
+
+/// Where an entry headed for `id` goes, settling a taken id by
+/// `on_conflict`; `None` if it is skipped
+fn settle(vault: &Vault, id: String, on_conflict: ConflictPolicy) -> Option<(String, OpOutcome)> {
+    match on_conflict {
+        _ if !vault.contains(&id) => Some((id, OpOutcome::Applied)),
+        ConflictPolicy::Skip => None,
+        ConflictPolicy::Overwrite => Some((id, OpOutcome::Replaced)),
+        ConflictPolicy::Rename => {
+            let new_id = vault.copy_id(&id);
+            Some((new_id.clone(), OpOutcome::Renamed { to: new_id }))
+        }
+    }
+}
+
+/// Each touched id's entry from before the batch (`None` if it had none)
+#[derive(Default)]
+struct Undo(HashMap<String, Option<Entry>>);
+
+impl Undo {
+    /// Remember `id` as it is now, unless it was already touched
+    fn record(&mut self, vault: &Vault, id: &str) {
+        if !self.0.contains_key(id) {
+            self.0.insert(id.to_string(), vault.get_entry(id).cloned());
+        }
+    }
+
+    fn restore(self, vault: &mut Vault) {
+        for (id, entry) in self.0 {
+            match entry {
+                Some(entry) => vault.add_entry(id, entry),
+                None => vault.remove_entry(&id).is_some(),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const IDS: [&str; 4] = ["a", "b", "c", "a (copy)"];
+
+    fn entry(username: &str) -> Entry {
+        let mut entry = Entry::new(username.to_string(), "pw".to_string(), None);
+        entry.created_by = Some("test".to_string());
+        entry
+    }
+
+    fn snapshot(vault: &Vault) -> serde_json::Value {
+        serde_json::to_value(vault).unwrap()
+    }
+
+    fn random_op(rng: &mut StdRng, n: usize) -> EntryOp {
+        let id = IDS[rng.gen_range(0..IDS.len())].to_string();
+        let other = IDS[rng.gen_range(0..IDS.len())].to_string();
+        let policy = ConflictPolicy::ALL[rng.gen_range(0..3)];
+        match rng.gen_range(0..4) {
+            0 => EntryOp::Add { id, entry: entry(&format!("added-{}", n)), on_conflict: policy },
+            1 => EntryOp::Update { id, entry: entry(&format!("updated-{}", n)) },
+            2 => EntryOp::Remove { id },
+            _ => EntryOp::Rename { from: id, to: other, on_conflict: policy },
+        }
+    }
+
+    /// The obvious one-at-a-time meaning of an operation; false if it
+    /// can't be applied
+    fn apply_naively(vault: &mut Vault, op: EntryOp) -> bool {
+        let free = |vault: &Vault, id: String, policy: ConflictPolicy| match policy {
+            _ if !vault.contains(&id) => Some(id),
+            ConflictPolicy::Skip => None,
+            ConflictPolicy::Overwrite => Some(id),
+            ConflictPolicy::Rename => Some(vault.copy_id(&id)),
+        };
+        match op {
+            EntryOp::Add { id, entry, on_conflict } => {
+                if let Some(id) = free(vault, id, on_conflict) {
+                    vault.add_entry(id, entry);
+                }
+            }
+            EntryOp::Update { id, mut entry } => {
+                if !vault.contains(&id) {
+                    return false;
+                }
+                entry.modified_by = identity();
+                vault.add_entry(id, entry);
+            }
+            EntryOp::Remove { id } => return vault.remove_entry(&id).is_some(),
+            EntryOp::Rename { from, to, on_conflict } => {
+                if !vault.contains(&from) {
+                    return false;
+                }
+                if from != to {
+                    if let Some(to) = free(vault, to, on_conflict) {
+                        vault.rename_entry(&from, to);
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    #[test]
+    fn test_batches_match_naive_application_or_change_nothing() {
+        let mut rng = StdRng::seed_from_u64(4189);
+        for _ in 0..500 {
+            let mut vault = Vault::new();
+            for id in &IDS[..rng.gen_range(0..3)] {
+                vault.add_entry(id.to_string(), entry(id));
+            }
+            let ops: Vec<EntryOp> = (0..rng.gen_range(1..8)).map(|n| random_op(&mut rng, n)).collect();
+
+            let before = snapshot(&vault);
+            let mut naive: Vault = serde_json::from_value(before.clone()).unwrap();
+            let naive_ok = ops.iter().cloned().all(|op| apply_naively(&mut naive, op));
+
+            let result = apply_ops(&mut vault, ops.clone());
+            assert_eq!(result.committed, naive_ok, "{:?}", ops);
+            if naive_ok {
+                assert_eq!(snapshot(&vault)["entries"], snapshot(&naive)["entries"], "{:?}", ops);
+                assert_eq!(result.outcomes.len(), ops.len());
+            } else {
+                assert_eq!(snapshot(&vault), before, "{:?}", ops);
+                assert_eq!(result.failures().count(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_outcomes_follow_conflict_policies() {
+        let mut vault = Vault::new();
+        vault.add_entry("github".into(), entry("old"));
+        let result = apply_ops(&mut vault, vec![
+            EntryOp::Add { id: "github".into(), entry: entry("skipped"), on_conflict: ConflictPolicy::Skip },
+            EntryOp::Add { id: "github".into(), entry: entry("copy"), on_conflict: ConflictPolicy::Rename },
+            EntryOp::Rename { from: "github (copy)".into(), to: "gitlab".into(), on_conflict: ConflictPolicy::Skip },
+            EntryOp::Update { id: "github".into(), entry: Entry::new("new".into(), "pw".into(), None) },
+        ]);
+        assert!(result.committed && result.changed());
+        assert_eq!(result.outcomes.iter().map(|(_, outcome)| outcome.clone()).collect::<Vec<_>>(), vec![
+            OpOutcome::Skipped,
+            OpOutcome::Renamed { to: "github (copy)".into() },
+            OpOutcome::Applied,
+            OpOutcome::Applied,
+        ]);
+        let github = vault.get_entry("github").unwrap();
+        assert_eq!((github.username.as_str(), github.created_by.as_deref()), ("new", Some("test")));
+        assert_eq!(vault.get_entry("gitlab").unwrap().username, "copy");
+
+        let result = apply_ops(&mut vault, vec![
+            EntryOp::Remove { id: "gitlab".into() },
+            EntryOp::Remove { id: "missing".into() },
+        ]);
+        assert!(!result.committed && !result.changed());
+        assert_eq!(result.failures().collect::<Vec<_>>(), vec![("missing", "No entry 'missing' to remove")]);
+        assert!(vault.contains("gitlab"));
+    }
+}
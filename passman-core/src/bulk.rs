@@ -0,0 +1,191 @@
+//! Bulk Edit Module
+//!
+//! Vault-wide find-and-replace on entry text fields, shared by
+//! `passman replace` and the GUI bulk edit preview. Passwords are never
+//! touched.
+
+use regex::Regex;
+use serde::Serialize;
+use crate::model::{Entry, Vault};
+
+/// Entry fields that can be bulk edited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BulkField {
+    #[default]
+    Username,
+    Url,
+    Note,
+}
+
+impl BulkField {
+    pub const ALL: [BulkField; 3] = [Self::Username, Self::Url, Self::Note];
+
+    /// Parse a field name; secret fields are refused with an explanation
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_lowercase().as_str() {
+            "username" | "user" => Ok(Self::Username),
+            "url" => Ok(Self::Url),
+            "note" | "notes" => Ok(Self::Note),
+            "password" | "totp" | "totp_secret" => {
+                Err(format!("Refusing to bulk edit {}: change secrets one entry at a time", name.trim()))
+            }
+            other => Err(format!("Unknown field '{}'. Use: username, url, note", other)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Username => "username",
+            Self::Url => "url",
+            Self::Note => "note",
+        }
+    }
+
+    fn value<'a>(&self, entry: &'a Entry) -> Option<&'a str> {
+        match self {
+            Self::Username => Some(&entry.username),
+            Self::Url => entry.url.as_deref(),
+            Self::Note => entry.note.as_deref(),
+        }
+    }
+
+    /// Store a new value; an emptied URL or note is removed
+    fn set(&self, entry: &mut Entry, value: String) {
+        let optional = (!value.is_empty()).then(|| value.clone());
+        match self {
+            Self::Username => entry.username = value,
+            Self::Url => entry.url = optional,
+            Self::Note => entry.note = optional,
+        }
+    }
+}
+
+/// One entry's field before and after a replacement
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BulkChange {
+    pub id: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// A find-and-replace over one field of every entry outside the trash
+#[derive(Debug, Clone)]
+pub struct BulkReplace {
+    field: BulkField,
+    pattern: Regex,
+    replacement: String,
+}
+
+impl BulkReplace {
+    /// Plain text `find` is matched literally; with `regex` it is a regular
+    /// expression and `replacement` may use `$1`-style groups
+    pub fn new(field: BulkField, find: &str, replacement: &str, regex: bool) -> Result<Self, String> {
+        if find.is_empty() {
+            return Err("Nothing to find".into());
+        }
+        let pattern = if regex {
+            Regex::new(find).map_err(|e| format!("Invalid regex: {}", e))?
+        } else {
+            Regex::new(&regex::escape(find)).map_err(|e| e.to_string())?
+        };
+        // Literal replacements must not expand `$` groups
+        let replacement = if regex { replacement.to_string() } else { replacement.replace('$', "$$") };
+        Ok(Self { field, pattern, replacement })
+    }
+
+    pub fn field(&self) -> BulkField {
+        self.field
+    }
+
+    /// Entries the replacement would change, sorted by id
+    pub fn preview(&self, vault: &Vault) -> Vec<BulkChange> {
+        let mut changes: Vec<BulkChange> = vault.iter()
+            .filter(|(_, entry)| !entry.is_trashed())
+            .filter_map(|(id, entry)| {
+                let before = self.field.value(entry)?;
+                let after = self.pattern.replace_all(before, self.replacement.as_str());
+                (after != before).then(|| BulkChange {
+                    id: id.to_string(),
+                    before: before.to_string(),
+                    after: after.into_owned(),
+                })
+            })
+            .collect();
+        changes.sort_by(|a, b| a.id.cmp(&b.id));
+        changes
+    }
+
+    /// Apply the replacement, bumping `modified_at` and `modified_by` on
+    /// each changed entry. Returns the changes made; saving is left to the caller.
+    pub fn apply(&self, vault: &mut Vault) -> Vec<BulkChange> {
+        let changes = self.preview(vault);
+        let now = chrono::Utc::now();
+        let editor = crate::config::identity();
+        for change in &changes {
+            if let Some(mut entry) = vault.get_entry(&change.id).cloned() {
+                self.field.set(&mut entry, change.after.clone());
+                entry.modified_at = now;
+                entry.modified_by = editor.clone();
+                vault.add_entry(change.id.clone(), entry);
+            }
+        }
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault() -> Vault {
+        let mut vault = Vault::new();
+        vault.add_entry("mail".to_string(), Entry::new("me@oldcorp.com".to_string(), "pw@oldcorp.com".to_string(), None));
+        vault.add_entry("jira".to_string(), Entry::new("ann@oldcorp.com".to_string(), "pw".to_string(), Some("$HOME".to_string())));
+        vault.add_entry("forum".to_string(), Entry::new("ann".to_string(), "pw".to_string(), None));
+        vault
+    }
+
+    #[test]
+    fn test_literal_replace_previews_then_applies() {
+        let mut vault = vault();
+        let before = vault.get_entry("mail").unwrap().modified_at;
+        let replace = BulkReplace::new(BulkField::Username, "@oldcorp.com", "@newcorp.com", false).unwrap();
+
+        let preview = replace.preview(&vault);
+        assert_eq!(preview.iter().map(|c| c.id.as_str()).collect::<Vec<_>>(), vec!["jira", "mail"]);
+        assert_eq!(preview[1].after, "me@newcorp.com");
+        assert_eq!(vault.get_entry("mail").unwrap().username, "me@oldcorp.com");
+
+        assert_eq!(replace.apply(&mut vault), preview);
+        let mail = vault.get_entry("mail").unwrap();
+        assert_eq!(mail.username, "me@newcorp.com");
+        assert_eq!(mail.password_str(), "pw@oldcorp.com");
+        assert!(mail.modified_at >= before);
+        assert!(replace.preview(&vault).is_empty());
+    }
+
+    #[test]
+    fn test_regex_groups_and_literal_dollars() {
+        let mut vault = vault();
+        let replace = BulkReplace::new(BulkField::Username, r"^(\w+)@oldcorp\.com$", "$1@newcorp.com", true).unwrap();
+        replace.apply(&mut vault);
+        assert_eq!(vault.get_entry("jira").unwrap().username, "ann@newcorp.com");
+
+        let replace = BulkReplace::new(BulkField::Note, "$HOME", "$1", false).unwrap();
+        replace.apply(&mut vault);
+        assert_eq!(vault.get_entry("jira").unwrap().note.as_deref(), Some("$1"));
+
+        let clear = BulkReplace::new(BulkField::Note, "$1", "", false).unwrap();
+        clear.apply(&mut vault);
+        assert_eq!(vault.get_entry("jira").unwrap().note, None);
+    }
+
+    #[test]
+    fn test_secret_fields_and_bad_input_are_refused() {
+        assert!(BulkField::parse("password").unwrap_err().starts_with("Refusing"));
+        assert!(BulkField::parse("totp").is_err());
+        assert_eq!(BulkField::parse(" URL "), Ok(BulkField::Url));
+        assert!(BulkReplace::new(BulkField::Url, "", "x", false).is_err());
+        assert!(BulkReplace::new(BulkField::Url, "(", "x", true).is_err());
+    }
+}
@@ -0,0 +1,271 @@
+//! Change Markers
+//!
+//! Rotation scripts push new secrets out to services and need to know what
+//! changed since their last run. A marker, kept in a sidecar next to the
+//! vault, records when it was taken and a keyed digest of every field of
+//! every entry. Listing changes since the marker then names the fields that
+//! differ from those digests. The digests are HMACs under the vault key
+//! that cover the entry id, so the sidecar shows neither the values nor
+//! which entries share one.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use crate::diff::entry_fields;
+use crate::model::{Entry, Vault};
+use crate::vault::VaultKey;
+
+/// Sidecar holding the marker: `<vault>.changes.json`
+pub const MARKER_SUFFIX: &str = ".changes.json";
+
+/// Keeps these digests apart from any other use of the vault key
+const DIGEST_CONTEXT: &str = "changes";
+
+/// Where a listing of changes starts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Since {
+    At(DateTime<Utc>),
+    /// The time of the last marker
+    LastMark,
+}
+
+impl Since {
+    /// Parse `last-export` (the last marker), an RFC 3339 timestamp or a
+    /// `YYYY-MM-DD` date, taken as midnight UTC
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        if input == "last-export" {
+            return Ok(Since::LastMark);
+        }
+        if let Ok(at) = DateTime::parse_from_rfc3339(input) {
+            return Ok(Since::At(at.with_timezone(&Utc)));
+        }
+        chrono::NaiveDate::parse_from_str(input, "%Y-%m-%d")
+            .map(|date| Since::At(date.and_time(chrono::NaiveTime::MIN).and_utc()))
+            .map_err(|_| format!("Invalid marker '{}': use last-export, YYYY-MM-DD or an RFC 3339 timestamp", input))
+    }
+}
+
+/// A point to list changes from, with what every field held then
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChangeMarker {
+    pub marked_at: DateTime<Utc>,
+    /// Digest of nothing in particular, to tell whether `fields` were taken
+    /// under the current key; after a master password change they weren't
+    key_check: String,
+    /// Entry id → field → digest; unset fields are left out
+    fields: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// An entry modified since the marker
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct EntryChange {
+    pub id: String,
+    pub modified_at: DateTime<Utc>,
+    /// Created since the marker
+    pub added: bool,
+    /// Fields that differ from the last marker, in the order `passman diff`
+    /// lists them. `None` when there is no marker taken under the current
+    /// key to compare with.
+    pub fields: Option<Vec<&'static str>>,
+    /// Current values of those fields (of every field when `fields` is
+    /// `None`), secrets included; only filled in when asked for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub values: Option<BTreeMap<&'static str, Option<String>>>,
+}
+
+impl ChangeMarker {
+    /// Mark `vault` as of `at`
+    pub fn take(vault: &Vault, key: &VaultKey, at: DateTime<Utc>) -> Self {
+        let fields = vault
+            .iter()
+            .map(|(id, entry)| {
+                let digests = entry_fields(entry)
+                    .into_iter()
+                    .filter_map(|(field, value, _)| value.map(|value| (field.to_string(), field_digest(key, id, field, &value))))
+                    .collect();
+                (id.to_string(), digests)
+            })
+            .collect();
+        ChangeMarker { marked_at: at, key_check: key.keyed_digest(DIGEST_CONTEXT, b""), fields }
+    }
+
+    /// The sidecar of `vault_path`
+    pub fn path(vault_path: &str) -> PathBuf {
+        PathBuf::from(format!("{}{}", vault_path, MARKER_SUFFIX))
+    }
+
+    /// The marker of `vault_path`, if one was taken
+    pub fn load(vault_path: &str) -> Result<Option<Self>, String> {
+        let path = Self::path(vault_path);
+        match fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data)
+                .map(Some)
+                .map_err(|e| format!("Unreadable change marker {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Could not read change marker {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Replace the marker of `vault_path` with this one
+    pub fn store(&self, vault_path: &str) -> Result<(), String> {
+        let path = Self::path(vault_path);
+        let data = serde_json::to_vec_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(&path, data).map_err(|e| format!("Could not write change marker {}: {}", path.display(), e))
+    }
+
+    /// Whether the digests were taken under `key`
+    pub fn matches_key(&self, key: &VaultKey) -> bool {
+        self.key_check == key.keyed_digest(DIGEST_CONTEXT, b"")
+    }
+
+    /// Fields of `entry` that differ from the marker; every set field of an
+    /// entry the marker doesn't know
+    fn changed_fields(&self, key: &VaultKey, id: &str, entry: &Entry) -> Vec<&'static str> {
+        let marked = self.fields.get(id);
+        entry_fields(entry)
+            .into_iter()
+            .filter(|(field, value, _)| {
+                let digest = value.as_ref().map(|value| field_digest(key, id, field, value));
+                marked.and_then(|digests| digests.get(*field)) != digest.as_ref()
+            })
+            .map(|(field, _, _)| field)
+            .collect()
+    }
+}
+
+fn field_digest(key: &VaultKey, id: &str, field: &str, value: &str) -> String {
+    key.keyed_digest(DIGEST_CONTEXT, format!("{}\0{}\0{}", id, field, value).as_bytes())
+}
+
+/// Entries of `vault` modified after `since`, oldest change first. Fields
+/// are compared with `marker` if it was taken under `key`; with
+/// `include_values` their current values are filled in too.
+pub fn changes_since(
+    vault: &Vault,
+    key: &VaultKey,
+    since: DateTime<Utc>,
+    marker: Option<&ChangeMarker>,
+    include_values: bool,
+) -> Vec<EntryChange> {
+    let marker = marker.filter(|marker| marker.matches_key(key));
+    let mut changes: Vec<EntryChange> = vault
+        .iter()
+        .filter(|(_, entry)| entry.modified_at > since)
+        .map(|(id, entry)| {
+            let fields = marker.map(|marker| marker.changed_fields(key, id, entry));
+            let values = include_values.then(|| {
+                entry_fields(entry)
+                    .into_iter()
+                    .filter(|(field, _, _)| fields.as_ref().is_none_or(|fields| fields.contains(field)))
+                    .map(|(field, value, _)| (field, value))
+                    .collect()
+            });
+            EntryChange {
+                id: id.to_string(),
+                modified_at: entry.modified_at,
+                added: entry.created_at > since,
+                fields,
+                values,
+            }
+        })
+        .collect();
+    changes.sort_by(|a, b| a.modified_at.cmp(&b.modified_at).then_with(|| a.id.cmp(&b.id)));
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::VaultManager;
+    use zeroize::Zeroizing;
+
+    fn vault_key(dir: &tempfile::TempDir) -> VaultKey {
+        let path = dir.path().join("vault.dat");
+        VaultManager::init(&Zeroizing::new("correct horse".to_string()), Some(path.to_str().unwrap())).unwrap()
+    }
+
+    fn entry(username: &str, password: &str, at: DateTime<Utc>) -> Entry {
+        let mut entry = Entry::new(username.to_string(), password.to_string(), None);
+        entry.created_at = at;
+        entry.modified_at = at;
+        entry
+    }
+
+    #[test]
+    fn test_since_parses_markers_dates_and_timestamps() {
+        assert_eq!(Since::parse("last-export"), Ok(Since::LastMark));
+        let date = Since::parse("2024-06-01").unwrap();
+        assert_eq!(date, Since::parse("2024-06-01T00:00:00Z").unwrap());
+        assert_eq!(Since::parse("2024-06-01T02:00:00+02:00").unwrap(), date);
+        assert!(Since::parse("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_changed_fields_are_found_without_storing_values() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = vault_key(&dir);
+        let marked_at = Utc::now() - chrono::Duration::hours(1);
+        let before = marked_at - chrono::Duration::days(1);
+        let mut vault = Vault::new();
+        vault.add_entry("github".to_string(), entry("octo", "old-secret", before));
+        vault.add_entry("mail".to_string(), entry("me", "pw", before));
+        let marker = ChangeMarker::take(&vault, &key, marked_at);
+
+        // Rotate one password, add an entry, touch another without changing a field
+        let after = marked_at + chrono::Duration::minutes(5);
+        let mut github = vault.get_entry("github").unwrap().clone();
+        github.password = "new-secret".to_string().into();
+        github.modified_at = after;
+        vault.add_entry("github".to_string(), github);
+        vault.add_entry("bank".to_string(), entry("ann", "pw", after));
+        let mut mail = vault.get_entry("mail").unwrap().clone();
+        mail.modified_at = after + chrono::Duration::seconds(1);
+        vault.add_entry("mail".to_string(), mail);
+
+        let changes = changes_since(&vault, &key, marker.marked_at, Some(&marker), false);
+        let summary: Vec<_> = changes.iter().map(|c| (c.id.as_str(), c.added, c.fields.clone().unwrap())).collect();
+        assert_eq!(summary, vec![
+            ("bank", true, vec!["username", "password"]),
+            ("github", false, vec!["password"]),
+            ("mail", false, vec![]),
+        ]);
+        assert!(changes.iter().all(|change| change.values.is_none()));
+
+        // Values only on request, and only of the changed fields
+        let changes = changes_since(&vault, &key, marker.marked_at, Some(&marker), true);
+        let github = changes.iter().find(|change| change.id == "github").unwrap();
+        assert_eq!(github.values, Some(BTreeMap::from([("password", Some("new-secret".to_string()))])));
+
+        // A marker from another key can't say which fields changed
+        let other_dir = tempfile::tempdir().unwrap();
+        let changes = changes_since(&vault, &vault_key(&other_dir), marker.marked_at, Some(&marker), false);
+        assert_eq!(changes.len(), 3);
+        assert!(changes.iter().all(|change| change.fields.is_none()));
+    }
+
+    #[test]
+    fn test_marker_persists_beside_the_vault() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = vault_key(&dir);
+        let vault_path = dir.path().join("vault.dat").to_string_lossy().into_owned();
+        assert!(ChangeMarker::load(&vault_path).unwrap().is_none());
+
+        let mut vault = Vault::new();
+        vault.add_entry("github".to_string(), entry("octo", "hunter2", Utc::now()));
+        let marker = ChangeMarker::take(&vault, &key, Utc::now());
+        marker.store(&vault_path).unwrap();
+
+        let sidecar = fs::read_to_string(ChangeMarker::path(&vault_path)).unwrap();
+        assert!(!sidecar.contains("hunter2") && !sidecar.contains("octo"));
+        let loaded = ChangeMarker::load(&vault_path).unwrap().unwrap();
+        assert_eq!(loaded.marked_at, marker.marked_at);
+        assert!(loaded.matches_key(&key));
+        assert_eq!(loaded.changed_fields(&key, "github", vault.get_entry("github").unwrap()), Vec::<&str>::new());
+
+        fs::write(ChangeMarker::path(&vault_path), b"{").unwrap();
+        assert!(ChangeMarker::load(&vault_path).is_err());
+    }
+}
@@ -0,0 +1,1128 @@
+//! Configuration Module
+//! 
+//! Handles application configuration loading, saving, and defaults.
+//! Configuration is stored in TOML format.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use crate::crypto::CipherKind;
+use crate::import_export::ImportProfile;
+use crate::model::{EntryTemplate, HousekeepingLimits};
+use crate::utils::StrengthSuggestion;
+use std::path::PathBuf;
+
+/// Default config filename
+const CONFIG_FILE: &str = "passman.toml";
+
+/// The configuration as it was before the last save: `passman.toml.bak`
+pub const CONFIG_BACKUP_SUFFIX: &str = ".bak";
+
+/// Where a configuration file that no longer parses is moved, for a look
+/// at what went wrong: `passman.toml.broken`
+pub const CONFIG_BROKEN_SUFFIX: &str = ".broken";
+
+/// Environment variable that overrides the configured vault path
+pub const VAULT_ENV_VAR: &str = "PASSMAN_VAULT";
+
+/// Vault file used when nothing else names one
+const FALLBACK_VAULT_FILE: &str = "vault.dat";
+
+/// Where the effective vault path came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaultPathSource {
+    Flag,
+    Env,
+    Config,
+    Default,
+}
+
+impl std::fmt::Display for VaultPathSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VaultPathSource::Flag => write!(f, "--vault"),
+            VaultPathSource::Env => write!(f, "{}", VAULT_ENV_VAR),
+            VaultPathSource::Config => write!(f, "general.default_vault"),
+            VaultPathSource::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// Pick the vault path: `--vault` flag > PASSMAN_VAULT > configured
+/// default vault > built-in default. Empty values are skipped.
+pub fn resolve_vault_path_from(
+    flag: Option<&str>,
+    env: Option<&str>,
+    configured: Option<&str>,
+) -> (String, VaultPathSource) {
+    let candidates = [
+        (flag, VaultPathSource::Flag),
+        (env, VaultPathSource::Env),
+        (configured, VaultPathSource::Config),
+    ];
+    candidates
+        .into_iter()
+        .find_map(|(value, source)| {
+            value
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(|value| (value.to_string(), source))
+        })
+        .unwrap_or_else(|| (FALLBACK_VAULT_FILE.to_string(), VaultPathSource::Default))
+}
+
+/// Application configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Config {
+    /// General settings
+    #[serde(default)]
+    pub general: GeneralConfig,
+    
+    /// Security settings
+    #[serde(default)]
+    pub security: SecurityConfig,
+    
+    /// UI settings
+    #[serde(default)]
+    pub ui: UiConfig,
+    
+    /// Password generation settings
+    #[serde(default)]
+    pub password: PasswordConfig,
+    
+    /// Backup settings
+    #[serde(default)]
+    pub backup: BackupConfig,
+    
+    /// Trash settings
+    #[serde(default)]
+    pub trash: TrashConfig,
+    
+    /// Password health settings
+    #[serde(default)]
+    pub health: HealthConfig,
+    
+    /// Network settings
+    #[serde(default)]
+    pub network: NetworkConfig,
+    
+    /// Entry templates by name
+    #[serde(default)]
+    pub templates: BTreeMap<String, EntryTemplate>,
+    
+    /// Saved import settings by name
+    #[serde(default)]
+    pub import_profiles: BTreeMap<String, ImportProfile>,
+}
+
+/// General application settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneralConfig {
+    /// Default vault file path
+    #[serde(default = "default_vault_file")]
+    pub default_vault: String,
+    
+    /// Enable logging
+    #[serde(default = "default_true")]
+    pub enable_logging: bool,
+    
+    /// Log level (error, warn, info, debug, trace)
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    
+    /// Look for a newer release about once a week (signed manifest only,
+    /// never downloads anything)
+    #[serde(default)]
+    pub check_updates: bool,
+    
+    /// Save the GUI vault after every change; when off, changes are kept
+    /// until saved explicitly, on lock or on exit
+    #[serde(default = "default_true")]
+    pub autosave: bool,
+    
+    /// Name recorded as who added or changed entries, e.g. "alice", for
+    /// vaults shared by a team. Empty records nothing.
+    #[serde(default)]
+    pub identity: String,
+}
+
+/// Security settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityConfig {
+    /// Auto-lock timeout in seconds (0 = disabled)
+    #[serde(default = "default_lock_timeout")]
+    pub lock_timeout_secs: u64,
+    
+    /// Clipboard auto-clear timeout in seconds (0 = disabled)
+    #[serde(default = "default_clipboard_timeout")]
+    pub clipboard_timeout_secs: u64,
+    
+    /// Clear clipboard on lock
+    #[serde(default = "default_true")]
+    pub clear_clipboard_on_lock: bool,
+    
+    /// Lock on window minimize
+    #[serde(default)]
+    pub lock_on_minimize: bool,
+    
+    /// Maximum failed login attempts before lockout
+    #[serde(default = "default_max_attempts")]
+    pub max_failed_attempts: u32,
+    
+    /// Minimum master password length
+    #[serde(default = "default_min_password_length")]
+    pub min_password_length: usize,
+    
+    /// Require uppercase in master password
+    #[serde(default = "default_true")]
+    pub require_uppercase: bool,
+    
+    /// Require lowercase in master password
+    #[serde(default = "default_true")]
+    pub require_lowercase: bool,
+    
+    /// Require numbers in master password
+    #[serde(default = "default_true")]
+    pub require_numbers: bool,
+    
+    /// Require symbols in master password
+    #[serde(default)]
+    pub require_symbols: bool,
+    
+    /// Argon2 memory cost in KB
+    #[serde(default = "default_argon2_memory")]
+    pub argon2_memory_kb: u32,
+    
+    /// Argon2 time cost (iterations)
+    #[serde(default = "default_argon2_time")]
+    pub argon2_time_cost: u32,
+    
+    /// Argon2 parallelism
+    #[serde(default = "default_argon2_parallelism")]
+    pub argon2_parallelism: u32,
+    
+    /// Cipher for new vaults (aes-256-gcm, xchacha20-poly1305); existing
+    /// vaults keep theirs until `passman vault rekey --cipher`
+    #[serde(default)]
+    pub cipher: CipherKind,
+    
+    /// What keeps the auto-lock timer from expiring (strict, lenient)
+    #[serde(default)]
+    pub lock_timeout_behavior: LockTimeoutBehavior,
+    
+    /// Entries with this tag need their id retyped before deletion
+    #[serde(default = "default_protected_tag")]
+    pub protected_tag: String,
+    
+    /// Don't warn after unlocking that the master password is weak
+    #[serde(default)]
+    pub suppress_master_strength_warning: bool,
+    
+    /// Ask for the master password before showing a password on screen
+    #[serde(default)]
+    pub reveal_requires_password: bool,
+    
+    /// Refuse to unlock a vault file that users other than its owner can
+    /// read, instead of warning (Unix)
+    #[serde(default)]
+    pub strict_permissions: bool,
+    
+    /// Keep entry counts and the last save time unencrypted next to each
+    /// vault, for badges on the vault list. Never includes entry names.
+    #[serde(default)]
+    pub store_plain_metadata: bool,
+    
+    /// Vaults (canonical paths) not to warn about sitting in a cloud sync folder
+    #[serde(default)]
+    pub quiet_sync_folder_vaults: Vec<String>,
+}
+
+/// How the auto-lock timer treats activity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LockTimeoutBehavior {
+    /// Only pointer movement, clicks, scrolling and key presses reset the timer
+    #[default]
+    Strict,
+    /// Also pause the timer while typing in an entry form
+    Lenient,
+}
+
+impl std::fmt::Display for LockTimeoutBehavior {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LockTimeoutBehavior::Strict => write!(f, "strict"),
+            LockTimeoutBehavior::Lenient => write!(f, "lenient"),
+        }
+    }
+}
+
+impl std::str::FromStr for LockTimeoutBehavior {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "strict" => Ok(LockTimeoutBehavior::Strict),
+            "lenient" => Ok(LockTimeoutBehavior::Lenient),
+            other => Err(format!("Invalid lock timeout behavior: {} (expected strict or lenient)", other)),
+        }
+    }
+}
+
+/// How many password strength suggestions to show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionDetail {
+    /// Every suggestion
+    #[default]
+    Full,
+    /// Only the most important one
+    Brief,
+    /// None; the strength rating alone
+    Off,
+}
+
+impl SuggestionDetail {
+    /// The suggestions to show, most important first
+    pub fn select(self, suggestions: &[StrengthSuggestion]) -> Vec<&StrengthSuggestion> {
+        let mut selected: Vec<&StrengthSuggestion> = suggestions.iter().collect();
+        selected.sort_by_key(|suggestion| suggestion.rank());
+        selected.truncate(match self {
+            SuggestionDetail::Full => selected.len(),
+            SuggestionDetail::Brief => 1,
+            SuggestionDetail::Off => 0,
+        });
+        selected
+    }
+}
+
+impl std::fmt::Display for SuggestionDetail {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SuggestionDetail::Full => write!(f, "full"),
+            SuggestionDetail::Brief => write!(f, "brief"),
+            SuggestionDetail::Off => write!(f, "off"),
+        }
+    }
+}
+
+impl std::str::FromStr for SuggestionDetail {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "full" => Ok(SuggestionDetail::Full),
+            "brief" => Ok(SuggestionDetail::Brief),
+            "off" => Ok(SuggestionDetail::Off),
+            other => Err(format!("Invalid suggestion detail: {} (expected full, brief or off)", other)),
+        }
+    }
+}
+
+/// UI settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UiConfig {
+    /// Theme (dark, light, system)
+    #[serde(default = "default_theme")]
+    pub theme: String,
+    
+    /// Show password strength indicator
+    #[serde(default = "default_true")]
+    pub show_password_strength: bool,
+    
+    /// Show password health warnings
+    #[serde(default = "default_true")]
+    pub show_health_warnings: bool,
+    
+    /// Entry sort order (name, created, modified, recent, frequent)
+    #[serde(default = "default_sort_order")]
+    pub default_sort: String,
+    
+    /// Show entry icons
+    #[serde(default = "default_true")]
+    pub show_icons: bool,
+    
+    /// Compact list view
+    #[serde(default)]
+    pub compact_view: bool,
+    
+    /// Window width
+    #[serde(default = "default_window_width")]
+    pub window_width: f32,
+    
+    /// Window height
+    #[serde(default = "default_window_height")]
+    pub window_height: f32,
+    
+    /// Remember window position
+    #[serde(default = "default_true")]
+    pub remember_window_position: bool,
+    
+    /// Include entry notes when searching
+    #[serde(default = "default_true")]
+    pub search_notes: bool,
+    
+    /// Group the GUI entry list under site headers
+    #[serde(default)]
+    pub group_by_site: bool,
+    
+    /// Command the TUI pipes copied text into (empty = OSC 52 escape)
+    #[serde(default)]
+    pub clipboard_command: String,
+    
+    /// Glyphs shown for a hidden password, whatever its real length
+    #[serde(default = "default_mask_length")]
+    pub mask_length: usize,
+    
+    /// Reveal a password while its mask is held down (GUI)
+    #[serde(default)]
+    pub hold_to_reveal: bool,
+    
+    /// Hide revealed passwords again after this many seconds (0 = never)
+    #[serde(default = "default_reveal_timeout")]
+    pub reveal_timeout_secs: u64,
+    
+    /// "Open & copy": seconds the username stays on the clipboard before
+    /// the password replaces it (0 = only on a second click)
+    #[serde(default = "default_open_copy_delay")]
+    pub open_copy_delay_secs: u64,
+    
+    /// Pinned search queries by name (GUI chips, `passman list --saved`)
+    #[serde(default)]
+    pub saved_searches: BTreeMap<String, String>,
+    
+    /// GUI animations (full, reduced, off, system = the desktop's
+    /// reduce-motion setting)
+    #[serde(default = "default_animations")]
+    pub animations: String,
+    
+    /// "Getting started" checklist items done, by name (GUI)
+    #[serde(default)]
+    pub getting_started_done: Vec<String>,
+    
+    /// Hide the "Getting started" checklist for good
+    #[serde(default)]
+    pub getting_started_dismissed: bool,
+    
+    /// Password strength suggestions shown (GUI, CLI and health reports)
+    #[serde(default)]
+    pub suggestion_detail: SuggestionDetail,
+}
+
+/// Password generation settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordConfig {
+    /// Default password length
+    #[serde(default = "default_password_length")]
+    pub default_length: usize,
+    
+    /// Include uppercase letters
+    #[serde(default = "default_true")]
+    pub include_uppercase: bool,
+    
+    /// Include lowercase letters
+    #[serde(default = "default_true")]
+    pub include_lowercase: bool,
+    
+    /// Include numbers
+    #[serde(default = "default_true")]
+    pub include_numbers: bool,
+    
+    /// Include symbols
+    #[serde(default = "default_true")]
+    pub include_symbols: bool,
+    
+    /// Exclude ambiguous characters (0, O, l, I)
+    #[serde(default)]
+    pub exclude_ambiguous: bool,
+    
+    /// Custom symbol set (if empty, use default)
+    #[serde(default)]
+    pub custom_symbols: String,
+    
+    /// Number of words for memorable passwords
+    #[serde(default = "default_word_count")]
+    pub memorable_word_count: usize,
+    
+    /// Number of syllables for pronounceable passwords
+    #[serde(default = "default_syllable_count")]
+    pub pronounceable_syllables: usize,
+}
+
+/// Backup settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    /// Enable automatic backups
+    #[serde(default = "default_true")]
+    pub auto_backup: bool,
+    
+    /// Backup directory (empty = same as vault)
+    #[serde(default)]
+    pub backup_directory: String,
+    
+    /// Maximum number of backups to keep
+    #[serde(default = "default_max_backups")]
+    pub max_backups: usize,
+    
+    /// Create backup before each save
+    #[serde(default = "default_true")]
+    pub backup_on_save: bool,
+}
+
+/// Trash settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashConfig {
+    /// Days an entry stays in the trash before a save deletes it for good
+    /// (0 = keep it until emptied by hand)
+    #[serde(default = "default_trash_retention_days")]
+    pub retention_days: u32,
+}
+
+/// Password health settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HealthConfig {
+    /// File of passwords entries must not use, one per line, in plaintext
+    /// or as SHA-1 hashes (empty = none)
+    #[serde(default)]
+    pub banned_password_file: String,
+}
+
+/// Network settings, shared by every feature that goes online
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Never open a connection
+    #[serde(default)]
+    pub offline: bool,
+    
+    /// Proxy URL (empty = HTTPS_PROXY / HTTP_PROXY from the environment)
+    #[serde(default)]
+    pub proxy: String,
+    
+    /// Connect and read timeout in seconds
+    #[serde(default = "default_network_timeout")]
+    pub timeout_secs: u64,
+    
+    /// Extra attempts after a failed connection
+    #[serde(default = "default_network_retries")]
+    pub retries: u32,
+}
+
+// Default value functions
+fn default_vault_file() -> String { FALLBACK_VAULT_FILE.to_string() }
+fn default_true() -> bool { true }
+fn default_log_level() -> String { "info".to_string() }
+fn default_lock_timeout() -> u64 { 300 } // 5 minutes
+fn default_clipboard_timeout() -> u64 { 30 }
+fn default_max_attempts() -> u32 { 5 }
+fn default_protected_tag() -> String { "critical".to_string() }
+fn default_min_password_length() -> usize { 12 }
+fn default_argon2_memory() -> u32 { 65536 } // 64 MB
+fn default_argon2_time() -> u32 { 3 }
+fn default_argon2_parallelism() -> u32 { 4 }
+fn default_theme() -> String { "dark".to_string() }
+fn default_animations() -> String { "system".to_string() }
+fn default_sort_order() -> String { "name".to_string() }
+fn default_mask_length() -> usize { crate::utils::DEFAULT_MASK_LENGTH }
+fn default_reveal_timeout() -> u64 { 30 }
+fn default_open_copy_delay() -> u64 { 10 }
+fn default_window_width() -> f32 { 900.0 }
+fn default_window_height() -> f32 { 650.0 }
+fn default_password_length() -> usize { 20 }
+fn default_word_count() -> usize { 4 }
+fn default_syllable_count() -> usize { 6 }
+fn default_max_backups() -> usize { 10 }
+fn default_trash_retention_days() -> u32 { 90 }
+fn default_network_timeout() -> u64 { 10 }
+fn default_network_retries() -> u32 { 2 }
+
+impl Default for GeneralConfig {
+    fn default() -> Self {
+        Self {
+            default_vault: default_vault_file(),
+            enable_logging: true,
+            log_level: default_log_level(),
+            check_updates: false,
+            autosave: true,
+            identity: String::new(),
+        }
+    }
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        Self {
+            lock_timeout_secs: default_lock_timeout(),
+            clipboard_timeout_secs: default_clipboard_timeout(),
+            clear_clipboard_on_lock: true,
+            lock_on_minimize: false,
+            max_failed_attempts: default_max_attempts(),
+            min_password_length: default_min_password_length(),
+            require_uppercase: true,
+            require_lowercase: true,
+            require_numbers: true,
+            require_symbols: false,
+            argon2_memory_kb: default_argon2_memory(),
+            argon2_time_cost: default_argon2_time(),
+            argon2_parallelism: default_argon2_parallelism(),
+            cipher: CipherKind::default(),
+            lock_timeout_behavior: LockTimeoutBehavior::default(),
+            protected_tag: default_protected_tag(),
+            suppress_master_strength_warning: false,
+            reveal_requires_password: false,
+            strict_permissions: false,
+            store_plain_metadata: false,
+            quiet_sync_folder_vaults: Vec::new(),
+        }
+    }
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            theme: default_theme(),
+            show_password_strength: true,
+            show_health_warnings: true,
+            default_sort: default_sort_order(),
+            show_icons: true,
+            compact_view: false,
+            window_width: default_window_width(),
+            window_height: default_window_height(),
+            remember_window_position: true,
+            search_notes: true,
+            group_by_site: false,
+            clipboard_command: String::new(),
+            mask_length: default_mask_length(),
+            hold_to_reveal: false,
+            reveal_timeout_secs: default_reveal_timeout(),
+            open_copy_delay_secs: default_open_copy_delay(),
+            saved_searches: BTreeMap::new(),
+            animations: default_animations(),
+            getting_started_done: Vec::new(),
+            getting_started_dismissed: false,
+            suggestion_detail: SuggestionDetail::Full,
+        }
+    }
+}
+
+impl Default for PasswordConfig {
+    fn default() -> Self {
+        Self {
+            default_length: default_password_length(),
+            include_uppercase: true,
+            include_lowercase: true,
+            include_numbers: true,
+            include_symbols: true,
+            exclude_ambiguous: false,
+            custom_symbols: String::new(),
+            memorable_word_count: default_word_count(),
+            pronounceable_syllables: default_syllable_count(),
+        }
+    }
+}
+
+impl PasswordConfig {
+    /// Random passwords as configured; an entry's own
+    /// [`GenerationPrefs`](crate::model::GenerationPrefs) take precedence
+    pub fn generation_defaults(&self) -> crate::model::GenerationPrefs {
+        crate::model::GenerationPrefs::random(self.default_length, crate::utils::PasswordConfig {
+            include_uppercase: self.include_uppercase,
+            include_lowercase: self.include_lowercase,
+            include_numbers: self.include_numbers,
+            include_symbols: self.include_symbols,
+            exclude_ambiguous: self.exclude_ambiguous,
+            ..Default::default()
+        })
+    }
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            auto_backup: true,
+            backup_directory: String::new(),
+            max_backups: default_max_backups(),
+            backup_on_save: true,
+        }
+    }
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self {
+            retention_days: default_trash_retention_days(),
+        }
+    }
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            offline: false,
+            proxy: String::new(),
+            timeout_secs: default_network_timeout(),
+            retries: default_network_retries(),
+        }
+    }
+}
+
+/// What loading did about a configuration file it couldn't use
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigRecovery {
+    /// Settings came from the copy kept by the previous save
+    FromBackup { broken: Option<PathBuf> },
+    /// There was no usable copy either; defaults are in use
+    Defaults { broken: Option<PathBuf> },
+}
+
+impl ConfigRecovery {
+    /// A line to show the user
+    pub fn message(&self) -> String {
+        let (summary, broken) = match self {
+            ConfigRecovery::FromBackup { broken } => ("Settings were restored from backup", broken),
+            ConfigRecovery::Defaults { broken } => ("Settings could not be read and were reset to defaults", broken),
+        };
+        match broken {
+            Some(broken) => format!("{}; the damaged file is kept as {}", summary, broken.display()),
+            None => summary.to_string(),
+        }
+    }
+}
+
+/// `path` with `suffix` added to its file name
+fn with_suffix(path: &std::path::Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+impl Config {
+    /// What housekeeping enforces before each save
+    pub fn housekeeping_limits(&self) -> HousekeepingLimits {
+        HousekeepingLimits { trash_retention_days: self.trash.retention_days }
+    }
+
+    /// Load configuration from file
+    pub fn load() -> Self {
+        Self::load_from(Self::config_path())
+    }
+
+    /// Load configuration from specific path
+    pub fn load_from(path: PathBuf) -> Self {
+        Self::load_recovering(path).0
+    }
+
+    /// Load configuration from `path`, falling back to the copy kept by the
+    /// last save if the file doesn't parse (a crash or a full disk during a
+    /// write by an older version) or is missing while the copy isn't (a
+    /// crash between the two renames of a save). Failing that, defaults.
+    /// A file that doesn't parse is kept as `<path>.broken`.
+    pub fn load_recovering(path: PathBuf) -> (Self, Option<ConfigRecovery>) {
+        let backup = with_suffix(&path, CONFIG_BACKUP_SUFFIX);
+        let error = match fs::read_to_string(&path) {
+            Ok(contents) => match toml::from_str(&contents) {
+                Ok(config) => {
+                    log::info!("Configuration loaded from {:?}", path);
+                    return (config, None);
+                }
+                Err(e) => e.to_string(),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && backup.exists() => {
+                "missing, but its backup is there".to_string()
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                log::info!("No config file found. Using defaults.");
+                return (Self::default(), None);
+            }
+            // Unreadable is not the same as damaged: leave it alone
+            Err(e) => {
+                log::warn!("Failed to read config file: {}. Using defaults.", e);
+                return (Self::default(), None);
+            }
+        };
+        log::warn!("Config file {:?} is unusable ({})", path, error);
+
+        let broken = path.exists().then(|| {
+            let broken = with_suffix(&path, CONFIG_BROKEN_SUFFIX);
+            match fs::rename(&path, &broken) {
+                Ok(()) => broken,
+                Err(e) => {
+                    log::warn!("Could not move the damaged config file aside: {}", e);
+                    path.clone()
+                }
+            }
+        });
+        let restored = fs::read_to_string(&backup)
+            .ok()
+            .and_then(|contents| toml::from_str::<Config>(&contents).ok());
+        match restored {
+            Some(config) => {
+                log::warn!("Settings restored from {:?}", backup);
+                // Back in place, so the next save keeps it as the backup
+                if broken.as_ref() != Some(&path) {
+                    if let Err(e) = fs::copy(&backup, &path) {
+                        log::warn!("Could not put the restored config file back: {}", e);
+                    }
+                }
+                (config, Some(ConfigRecovery::FromBackup { broken }))
+            }
+            None => {
+                log::warn!("No usable config backup at {:?}. Using defaults.", backup);
+                (Self::default(), Some(ConfigRecovery::Defaults { broken }))
+            }
+        }
+    }
+
+    /// Save configuration to file
+    pub fn save(&self) -> Result<(), String> {
+        self.save_to(Self::config_path())
+    }
+
+    /// Save configuration to specific path. Written to a temporary file and
+    /// renamed over the old one, which is kept as `<path>.bak`, so a crash
+    /// or a full disk never leaves a half-written file behind.
+    pub fn save_to(&self, path: PathBuf) -> Result<(), String> {
+        let contents = toml::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        
+        crate::vault::VaultManager::atomic_write(&path.to_string_lossy(), contents.as_bytes())
+            .map_err(|e| format!("Failed to write config file: {}", e))?;
+        
+        log::info!("Configuration saved to {:?}", path);
+        Ok(())
+    }
+
+    /// Get default config file path
+    pub fn config_path() -> PathBuf {
+        // Try to use the app data directory, fallback to current directory
+        if let Some(config_dir) = dirs::config_dir() {
+            let app_dir = config_dir.join("passman");
+            if !app_dir.exists() {
+                let _ = fs::create_dir_all(&app_dir);
+            }
+            app_dir.join(CONFIG_FILE)
+        } else {
+            PathBuf::from(CONFIG_FILE)
+        }
+    }
+
+    /// Validate master password against security requirements
+    pub fn validate_master_password(&self, password: &str) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        
+        if password.len() < self.security.min_password_length {
+            errors.push(format!(
+                "Password must be at least {} characters long",
+                self.security.min_password_length
+            ));
+        }
+        
+        if self.security.require_uppercase && !password.chars().any(|c| c.is_uppercase()) {
+            errors.push("Password must contain at least one uppercase letter".to_string());
+        }
+        
+        if self.security.require_lowercase && !password.chars().any(|c| c.is_lowercase()) {
+            errors.push("Password must contain at least one lowercase letter".to_string());
+        }
+        
+        if self.security.require_numbers && !password.chars().any(|c| c.is_numeric()) {
+            errors.push("Password must contain at least one number".to_string());
+        }
+        
+        if self.security.require_symbols && !password.chars().any(|c| !c.is_alphanumeric()) {
+            errors.push("Password must contain at least one symbol".to_string());
+        }
+        
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Global configuration instance
+static CONFIG: std::sync::OnceLock<std::sync::RwLock<Config>> = std::sync::OnceLock::new();
+
+/// What loading the global configuration had to recover from, until a
+/// frontend reports it
+static CONFIG_RECOVERY: std::sync::Mutex<Option<ConfigRecovery>> = std::sync::Mutex::new(None);
+
+/// Load the global configuration, noting any recovery
+fn load_global() -> Config {
+    let (config, recovery) = Config::load_recovering(Config::config_path());
+    if recovery.is_some() {
+        *CONFIG_RECOVERY.lock().unwrap_or_else(|e| e.into_inner()) = recovery;
+    }
+    config
+}
+
+/// What loading the configuration had to recover from, once: for the
+/// GUI's toast or the CLI's warning
+pub fn take_config_recovery() -> Option<ConfigRecovery> {
+    drop(get_config());
+    CONFIG_RECOVERY.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+/// Get the global configuration (read-only)
+pub fn get_config() -> std::sync::RwLockReadGuard<'static, Config> {
+    CONFIG
+        .get_or_init(|| std::sync::RwLock::new(load_global()))
+        .read()
+        .expect("Config lock poisoned")
+}
+
+/// Get the global configuration (mutable)
+pub fn get_config_mut() -> std::sync::RwLockWriteGuard<'static, Config> {
+    CONFIG
+        .get_or_init(|| std::sync::RwLock::new(load_global()))
+        .write()
+        .expect("Config lock poisoned")
+}
+
+/// Resolve the vault path both frontends should use, given an optional
+/// `--vault` flag
+pub fn resolve_vault_path(flag: Option<&str>) -> (String, VaultPathSource) {
+    let env = std::env::var(VAULT_ENV_VAR).ok();
+    let configured = get_config().general.default_vault.clone();
+    resolve_vault_path_from(flag, env.as_deref(), Some(&configured))
+}
+
+/// Reload configuration from file
+pub fn reload_config() {
+    let mut config = get_config_mut();
+    *config = load_global();
+}
+
+/// Save current configuration
+pub fn save_config() -> Result<(), String> {
+    get_config().save()
+}
+
+/// The configured `general.identity`, if set
+pub fn identity() -> Option<String> {
+    Some(get_config().general.identity.trim().to_string()).filter(|name| !name.is_empty())
+}
+
+/// Stop pointing `general.default_vault` at a vault that was deleted.
+/// Returns whether the configuration changed.
+pub fn forget_vault(vault_path: &str) -> Result<bool, String> {
+    {
+        let mut config = get_config_mut();
+        if config.general.default_vault != vault_path || vault_path == default_vault_file() {
+            return Ok(false);
+        }
+        config.general.default_vault = default_vault_file();
+    }
+    save_config()?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vault_path_precedence() {
+        assert_eq!(
+            resolve_vault_path_from(Some("flag.dat"), Some("env.dat"), Some("config.dat")),
+            ("flag.dat".to_string(), VaultPathSource::Flag)
+        );
+        assert_eq!(
+            resolve_vault_path_from(None, Some("env.dat"), Some("config.dat")),
+            ("env.dat".to_string(), VaultPathSource::Env)
+        );
+        assert_eq!(
+            resolve_vault_path_from(None, Some("  "), Some("config.dat")),
+            ("config.dat".to_string(), VaultPathSource::Config)
+        );
+        assert_eq!(
+            resolve_vault_path_from(None, None, Some("")),
+            ("vault.dat".to_string(), VaultPathSource::Default)
+        );
+    }
+
+    #[test]
+    fn test_save_keeps_the_previous_config_as_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        let mut config = Config::default();
+        config.save_to(path.clone()).unwrap();
+        config.password.default_length = 32;
+        config.save_to(path.clone()).unwrap();
+
+        assert_eq!(Config::load_from(path.clone()).password.default_length, 32);
+        let backup = Config::load_from(with_suffix(&path, CONFIG_BACKUP_SUFFIX));
+        assert_eq!(backup.password.default_length, 20);
+        assert!(!with_suffix(&path, ".tmp").exists());
+    }
+
+    #[test]
+    fn test_truncated_config_is_restored_from_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        let mut config = Config::default();
+        config.password.default_length = 28;
+        config.save_to(path.clone()).unwrap();
+        config.save_to(path.clone()).unwrap();
+        // Cut off mid-line, as a write interrupted by a crash would
+        let full = fs::read_to_string(&path).unwrap();
+        let truncated = &full[..full.find("default_length = ").unwrap() + "default_length = ".len()];
+        fs::write(&path, truncated).unwrap();
+
+        let (loaded, recovery) = Config::load_recovering(path.clone());
+        assert_eq!(loaded.password.default_length, 28);
+        let broken = with_suffix(&path, CONFIG_BROKEN_SUFFIX);
+        assert_eq!(recovery, Some(ConfigRecovery::FromBackup { broken: Some(broken.clone()) }));
+        assert!(recovery.unwrap().message().starts_with("Settings were restored from backup"));
+        assert_eq!(fs::read_to_string(&broken).unwrap(), truncated);
+        // The restored settings are back in place for the next start
+        assert_eq!(Config::load_recovering(path).1, None);
+    }
+
+    #[test]
+    fn test_unusable_config_without_backup_falls_back_to_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        fs::write(&path, "[security\nlock_timeout_secs = ").unwrap();
+        let (loaded, recovery) = Config::load_recovering(path.clone());
+        assert_eq!(loaded.security.lock_timeout_secs, 300);
+        assert_eq!(recovery, Some(ConfigRecovery::Defaults { broken: Some(with_suffix(&path, CONFIG_BROKEN_SUFFIX)) }));
+        assert!(!path.exists());
+
+        // A crash between the renames of a save leaves only the backup
+        Config::default().save_to(with_suffix(&path, CONFIG_BACKUP_SUFFIX)).unwrap();
+        let (_, recovery) = Config::load_recovering(path.clone());
+        assert_eq!(recovery, Some(ConfigRecovery::FromBackup { broken: None }));
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn test_config_io_errors_leave_files_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        // Unreadable: the path is a directory. Defaults, and nothing is moved.
+        let unreadable = dir.path().join("unreadable.toml");
+        fs::create_dir(&unreadable).unwrap();
+        assert_eq!(Config::load_recovering(unreadable.clone()).1, None);
+        assert!(unreadable.is_dir() && !with_suffix(&unreadable, CONFIG_BROKEN_SUFFIX).exists());
+
+        // Unwritable: the parent is a file. The save fails without touching it.
+        let parent = dir.path().join("not-a-dir");
+        fs::write(&parent, "keep").unwrap();
+        let err = Config::default().save_to(parent.join(CONFIG_FILE)).unwrap_err();
+        assert!(err.starts_with("Failed to write config file"), "{}", err);
+        assert_eq!(fs::read_to_string(&parent).unwrap(), "keep");
+    }
+
+    #[test]
+    fn test_suggestion_detail_picks_how_many_to_show() {
+        let (_, suggestions) = crate::utils::analyze_password_strength("qwerty");
+        assert_eq!(SuggestionDetail::Full.select(&suggestions).len(), suggestions.len());
+        assert_eq!(
+            SuggestionDetail::Brief.select(&suggestions),
+            [&StrengthSuggestion::CommonPassword { which: "qwerty" }]
+        );
+        assert!(SuggestionDetail::Off.select(&suggestions).is_empty());
+        assert_eq!("Brief".parse::<SuggestionDetail>(), Ok(SuggestionDetail::Brief));
+        assert!("some".parse::<SuggestionDetail>().is_err());
+        assert_eq!(Config::default().ui.suggestion_detail, SuggestionDetail::Full);
+    }
+
+    #[test]
+    fn test_default_config() {
+        let config = Config::default();
+        assert_eq!(config.security.lock_timeout_secs, 300);
+        assert_eq!(config.security.clipboard_timeout_secs, 30);
+        assert_eq!(config.password.default_length, 20);
+    }
+
+    #[test]
+    fn test_password_validation() {
+        let config = Config::default();
+        
+        // Too short
+        assert!(config.validate_master_password("short").is_err());
+        
+        // Missing uppercase
+        assert!(config.validate_master_password("lowercaseonly123").is_err());
+        
+        // Valid password
+        assert!(config.validate_master_password("ValidPassword123").is_ok());
+    }
+
+    #[test]
+    fn test_config_serialization() {
+        let config = Config::default();
+        let toml_str = toml::to_string_pretty(&config).unwrap();
+        let parsed: Config = toml::from_str(&toml_str).unwrap();
+        
+        assert_eq!(config.security.lock_timeout_secs, parsed.security.lock_timeout_secs);
+    }
+
+    #[test]
+    fn test_autosave_defaults_on_for_older_configs() {
+        let parsed: Config = toml::from_str("[general]\ndefault_vault = \"vault.dat\"\n").unwrap();
+        assert!(parsed.general.autosave);
+    }
+
+    #[test]
+    fn test_saved_searches_roundtrip() {
+        let parsed: Config = toml::from_str("[ui.saved_searches]\nwork = \"corp.example\"\n").unwrap();
+        assert_eq!(parsed.ui.saved_searches.get("work").map(String::as_str), Some("corp.example"));
+        let again: Config = toml::from_str(&toml::to_string_pretty(&parsed).unwrap()).unwrap();
+        assert_eq!(again.ui.saved_searches, parsed.ui.saved_searches);
+        assert!(Config::default().ui.saved_searches.is_empty());
+    }
+
+    #[test]
+    fn test_cipher_parsing() {
+        let parsed: Config = toml::from_str("[security]\ncipher = \"xchacha20\"\n").unwrap();
+        assert_eq!(parsed.security.cipher, CipherKind::XChaCha20Poly1305);
+        assert_eq!(Config::default().security.cipher, CipherKind::Aes256Gcm);
+        let again: Config = toml::from_str(&toml::to_string_pretty(&parsed).unwrap()).unwrap();
+        assert_eq!(again.security.cipher, CipherKind::XChaCha20Poly1305);
+    }
+
+    #[test]
+    fn test_lock_timeout_behavior_parsing() {
+        let parsed: Config = toml::from_str("[security]\nlock_timeout_behavior = \"lenient\"\n").unwrap();
+        assert_eq!(parsed.security.lock_timeout_behavior, LockTimeoutBehavior::Lenient);
+        assert_eq!(Config::default().security.lock_timeout_behavior, LockTimeoutBehavior::Strict);
+        
+        assert_eq!("STRICT".parse::<LockTimeoutBehavior>().unwrap(), LockTimeoutBehavior::Strict);
+        assert!("sometimes".parse::<LockTimeoutBehavior>().is_err());
+    }
+
+    #[test]
+    fn test_templates_roundtrip() {
+        let mut config = Config::default();
+        config.templates.insert("corp".to_string(), EntryTemplate {
+            username: "{id}@corp.example".to_string(),
+            tags: vec!["work".to_string()],
+            generation: Some(crate::model::GenerationPrefs::random(20, crate::utils::PasswordConfig::default())),
+            ..Default::default()
+        });
+        
+        let toml_str = toml::to_string_pretty(&config).unwrap();
+        let parsed: Config = toml::from_str(&toml_str).unwrap();
+        
+        assert_eq!(parsed.templates, config.templates);
+    }
+
+    #[test]
+    fn test_import_profiles_roundtrip() {
+        let mut profile = ImportProfile::new("csv");
+        profile.delimiter = ';';
+        profile.columns.insert("username".to_string(), "Login Name".to_string());
+        profile.id_template = "{System}/{Login Name}".to_string();
+        profile.tags = vec!["import:{month}".to_string()];
+        profile.conflict = crate::import_export::ConflictPolicy::Rename;
+        let mut config = Config::default();
+        config.import_profiles.insert("it-monthly".to_string(), profile);
+
+        let parsed: Config = toml::from_str(&toml::to_string_pretty(&config).unwrap()).unwrap();
+        assert_eq!(parsed.import_profiles, config.import_profiles);
+
+        let minimal: Config = toml::from_str("[import_profiles.plain]\nformat = \"json\"\n").unwrap();
+        assert_eq!(minimal.import_profiles["plain"], ImportProfile::new("json"));
+    }
+}
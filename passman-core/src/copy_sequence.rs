@@ -0,0 +1,210 @@
+//! Copy Sequences
+//!
+//! Some logins take more than a username and password: a company id
+//! first, say, each in a field of its own. An entry can list what to copy,
+//! in order, and each "copy next" puts the next item on the clipboard. The
+//! position starts over after the last item, or once the sequence has sat
+//! idle for [`SEQUENCE_TIMEOUT_SECS`].
+//!
+//! The GUI keeps the position in memory; the CLI, which exits between
+//! steps, keeps it in a small state file next to the configuration.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use zeroize::Zeroizing;
+use crate::config::Config;
+use crate::model::Entry;
+
+/// Seconds without a step after which a sequence starts over
+pub const SEQUENCE_TIMEOUT_SECS: i64 = 60;
+
+/// Where the CLI keeps its position, beside the configuration file
+const CURSOR_FILE: &str = "copy_sequence.json";
+
+/// One item of an entry's copy sequence
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopyStep {
+    Username,
+    Password,
+    Url,
+    /// A value the entry has no field for, such as a company id
+    Custom { name: String, value: String },
+}
+
+impl CopyStep {
+    /// Parse `username`, `password`, `url` or `<name>=<value>`
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        match spec.trim() {
+            "username" => Ok(CopyStep::Username),
+            "password" => Ok(CopyStep::Password),
+            "url" => Ok(CopyStep::Url),
+            spec => match spec.split_once('=') {
+                Some((name, value)) if !name.trim().is_empty() => Ok(CopyStep::Custom {
+                    name: name.trim().to_string(),
+                    value: value.trim().to_string(),
+                }),
+                _ => Err(format!("Unknown copy step '{}'. Use username, password, url or <name>=<value>", spec)),
+            },
+        }
+    }
+
+    /// Parse one step per line, skipping blank lines
+    pub fn parse_lines(text: &str) -> Result<Vec<Self>, String> {
+        text.lines().filter(|line| !line.trim().is_empty()).map(Self::parse).collect()
+    }
+
+    /// The form [`parse`](Self::parse) reads
+    pub fn spec(&self) -> String {
+        match self {
+            CopyStep::Custom { name, value } => format!("{}={}", name, value),
+            step => step.label().to_string(),
+        }
+    }
+
+    /// What the step copies, for messages such as "Copied 2/3: username"
+    pub fn label(&self) -> &str {
+        match self {
+            CopyStep::Username => "username",
+            CopyStep::Password => "password",
+            CopyStep::Url => "url",
+            CopyStep::Custom { name, .. } => name,
+        }
+    }
+
+    /// Whether the value is cleared from the clipboard after the timeout
+    pub fn is_secret(&self) -> bool {
+        matches!(self, CopyStep::Password)
+    }
+
+    /// The value to copy from `entry`. `None` for the password of a
+    /// high-security entry, which takes its PIN to open.
+    pub fn value(&self, entry: &Entry) -> Option<Zeroizing<String>> {
+        match self {
+            CopyStep::Username => Some(Zeroizing::new(entry.username.clone())),
+            CopyStep::Password if entry.is_sealed() => None,
+            CopyStep::Password => Some(Zeroizing::new(entry.password_str().reveal().to_string())),
+            CopyStep::Url => Some(Zeroizing::new(entry.url.clone().unwrap_or_default())),
+            CopyStep::Custom { value, .. } => Some(Zeroizing::new(value.clone())),
+        }
+    }
+}
+
+/// How far a copy sequence has got
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SequenceCursor {
+    /// Whatever identifies the entry to the caller; the vault path and
+    /// entry id, so the same id in another vault starts over
+    pub entry: String,
+    /// Index of the step the next "copy next" copies
+    pub next: usize,
+    /// When the last step was copied (Unix seconds)
+    pub updated_at: i64,
+}
+
+impl SequenceCursor {
+    /// The key [`entry`](Self::entry) holds for an entry of a vault file
+    pub fn key(vault_path: &str, id: &str) -> String {
+        format!("{}#{}", vault_path, id)
+    }
+
+    /// Whether the sequence has sat idle for too long to be continued
+    pub fn is_expired(&self, now: i64) -> bool {
+        now - self.updated_at >= SEQUENCE_TIMEOUT_SECS
+    }
+
+    /// Take a "copy next" for `entry`, whose sequence has `len` steps, at
+    /// `now`. Returns the index of the step to copy and the cursor to keep
+    /// for the one after: `None` once the last step is copied. `cursor`
+    /// only continues if it is for the same entry and hasn't expired.
+    pub fn step(cursor: Option<&Self>, entry: &str, len: usize, now: i64) -> Option<(usize, Option<Self>)> {
+        if len == 0 {
+            return None;
+        }
+        let index = cursor
+            .filter(|cursor| cursor.entry == entry && !cursor.is_expired(now) && cursor.next < len)
+            .map_or(0, |cursor| cursor.next);
+        let next = (index + 1 < len).then(|| SequenceCursor { entry: entry.to_string(), next: index + 1, updated_at: now });
+        Some((index, next))
+    }
+
+    fn path() -> PathBuf {
+        Config::config_path().with_file_name(CURSOR_FILE)
+    }
+
+    /// The CLI's position, if it has one that hasn't expired
+    pub fn load(now: i64) -> Option<Self> {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|contents| serde_json::from_str::<Self>(&contents).ok())
+            .filter(|cursor| !cursor.is_expired(now))
+    }
+
+    /// Keep the CLI's position for the next step, or forget it
+    pub fn store(cursor: Option<&Self>) -> std::io::Result<()> {
+        match cursor {
+            Some(cursor) => fs::write(Self::path(), serde_json::to_string(cursor)?),
+            None => match fs::remove_file(Self::path()) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps_parse_and_label() {
+        let steps = CopyStep::parse_lines("company = ACME-42\n\nusername\npassword\n").unwrap();
+        assert_eq!(steps, vec![
+            CopyStep::Custom { name: "company".to_string(), value: "ACME-42".to_string() },
+            CopyStep::Username,
+            CopyStep::Password,
+        ]);
+        assert_eq!(steps.iter().map(CopyStep::label).collect::<Vec<_>>(), ["company", "username", "password"]);
+        assert_eq!(steps[0].spec(), "company=ACME-42");
+        assert!(steps[2].is_secret() && !steps[0].is_secret());
+        assert!(CopyStep::parse("pin").is_err());
+        assert!(CopyStep::parse("=value").is_err());
+    }
+
+    #[test]
+    fn test_sequence_progresses_and_starts_over_after_the_last_step() {
+        let mut cursor = None;
+        let mut copied = Vec::new();
+        for second in 0..7 {
+            let (index, next) = SequenceCursor::step(cursor.as_ref(), "v#work", 3, second).unwrap();
+            copied.push(index);
+            cursor = next;
+        }
+        assert_eq!(copied, [0, 1, 2, 0, 1, 2, 0]);
+        assert_eq!(SequenceCursor::step(None, "v#work", 0, 0), None);
+    }
+
+    #[test]
+    fn test_sequence_expires_and_is_per_entry() {
+        let (_, cursor) = SequenceCursor::step(None, "v#work", 3, 1_000).unwrap();
+        let cursor = cursor.unwrap();
+        assert_eq!(cursor.next, 1);
+
+        // Within the minute it continues; after it, or for another entry, it starts over
+        assert_eq!(SequenceCursor::step(Some(&cursor), "v#work", 3, 1_059).unwrap().0, 1);
+        assert_eq!(SequenceCursor::step(Some(&cursor), "v#work", 3, 1_000 + SEQUENCE_TIMEOUT_SECS).unwrap().0, 0);
+        assert_eq!(SequenceCursor::step(Some(&cursor), "v#home", 3, 1_001).unwrap().0, 0);
+        // A sequence shortened meanwhile starts over too
+        assert_eq!(SequenceCursor::step(Some(&cursor), "v#work", 1, 1_001), Some((0, None)));
+    }
+
+    #[test]
+    fn test_sealed_password_needs_the_pin() {
+        let mut entry = Entry::new("octo".to_string(), "pw".to_string(), None);
+        assert_eq!(CopyStep::Password.value(&entry).unwrap().as_str(), "pw");
+        entry.seal("4711").unwrap();
+        assert!(CopyStep::Password.value(&entry).is_none());
+        assert_eq!(CopyStep::Username.value(&entry).unwrap().as_str(), "octo");
+    }
+}
@@ -0,0 +1,1173 @@
+//! Core Library Module
+//!
+//! Provides a unified API for both CLI and GUI interfaces.
+//! This module abstracts vault operations, authentication, and common functionality
+//! to ensure consistent behavior across different frontends.
+
+use crate::batch::{apply_ops, BatchResult, EntryOp};
+use crate::changes::{changes_since, ChangeMarker, EntryChange, Since};
+use crate::diff::{merge_newer, VaultDiff};
+use crate::crypto::CipherKind;
+use crate::model::{Entry, HousekeepingReport, Vault};
+use crate::vault::{BackupInfo, ConflictResolution, SyncFolderRisk, VaultKey, VaultManager};
+use crate::health::{PasswordHealthAnalyzer, PasswordHealth, HealthSummary, HealthReport, AnalysisScope};
+use crate::import_export::{ImportExportManager, ImportProfile, ImportReport};
+use crate::sealed::OpenedEntries;
+use crate::search::{search_vault, SearchScope};
+use crate::utils::{generate_password, generate_password_with_config, generate_memorable_password, generate_pronounceable_password, analyze_password_strength, PasswordStrength, PasswordConfig, StrengthSuggestion};
+use crate::error::{PassmanError, PassmanResult, VaultError, AuthError, TransferError};
+use crate::config::{Config, get_config, identity, resolve_vault_path};
+
+use zeroize::Zeroizing;
+use std::path::Path;
+
+/// Core password manager operations
+/// 
+/// This struct provides a unified interface for all password manager operations,
+/// abstracting the underlying vault, crypto, and storage mechanisms.
+///
+/// ```
+/// use passman_core::{EntryBuilder, PassmanCore, Zeroizing};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let dir = tempfile::tempdir()?;
+/// # let path = dir.path().join("vault.dat");
+/// # let path = path.to_str().unwrap();
+/// let password = Zeroizing::new("Correct-Horse-Battery-42!".to_string());
+///
+/// let mut core = PassmanCore::with_vault_path(path);
+/// core.init_vault(&password)?;
+/// core.add_entry("mail", EntryBuilder::new("me@example.com").generate_password(20).build())?;
+/// core.save()?;
+/// core.lock();
+///
+/// let mut reopened = PassmanCore::with_vault_path(path);
+/// reopened.unlock(&password)?;
+/// assert_eq!(reopened.get_entry("mail").unwrap().password_str().len(), 20);
+/// assert!(reopened.unlock(&Zeroizing::new("wrong".to_string())).is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub struct PassmanCore {
+    /// Currently loaded vault (if any)
+    vault: Option<Vault>,
+    /// Key the vault was unlocked with, so saves skip the KDF
+    key: Option<VaultKey>,
+    /// Path to the vault file
+    vault_path: String,
+    /// Application configuration
+    config: Config,
+}
+
+impl PassmanCore {
+    /// Create a new core instance with default configuration
+    pub fn new() -> Self {
+        let (vault_path, _) = resolve_vault_path(None);
+        let config = get_config();
+        Self {
+            vault: None,
+            key: None,
+            vault_path,
+            config: config.clone(),
+        }
+    }
+
+    /// Create a new core instance with a specific vault path
+    pub fn with_vault_path(vault_path: impl Into<String>) -> Self {
+        let config = get_config();
+        Self {
+            vault: None,
+            key: None,
+            vault_path: vault_path.into(),
+            config: config.clone(),
+        }
+    }
+
+    /// Wrap a vault that is already decrypted (for example one read with
+    /// [`VaultManager::load_from_bytes`]). There is no key, so it can't be
+    /// saved until it is unlocked.
+    pub fn with_vault(vault_path: impl Into<String>, vault: Vault) -> Self {
+        let mut core = Self::with_vault_path(vault_path);
+        core.vault = Some(vault);
+        core
+    }
+
+    /// Get the current configuration
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Check if a vault exists at the current path
+    pub fn vault_exists(&self) -> bool {
+        Path::new(&self.vault_path).exists()
+    }
+
+    /// Get the vault file path
+    pub fn vault_path(&self) -> &str {
+        &self.vault_path
+    }
+
+    /// Set the vault file path
+    pub fn set_vault_path(&mut self, path: impl Into<String>) {
+        self.vault_path = path.into();
+    }
+
+    /// Check if currently authenticated (vault is unlocked)
+    pub fn is_authenticated(&self) -> bool {
+        self.vault.is_some()
+    }
+
+    // ============ Vault Operations ============
+
+    /// Initialize a new vault with the given master password
+    /// 
+    /// # Errors
+    /// Returns error if vault already exists or password is too weak
+    pub fn init_vault(&mut self, master_password: &Zeroizing<String>) -> PassmanResult<()> {
+        self.init_vault_with_cipher(master_password, self.config.security.cipher)
+    }
+
+    /// [`init_vault`](Self::init_vault), encrypted with `cipher` rather
+    /// than the configured one
+    pub fn init_vault_with_cipher(&mut self, master_password: &Zeroizing<String>, cipher: CipherKind) -> PassmanResult<()> {
+        if self.vault_exists() {
+            return Err(PassmanError::Vault(VaultError::AlreadyExists(
+                self.vault_path.clone()
+            )));
+        }
+
+        // Validate password strength
+        self.validate_master_password(master_password)?;
+
+        let key = VaultManager::init_with_cipher(master_password, cipher, Some(&self.vault_path))
+            .map_err(|e| PassmanError::Vault(VaultError::WriteError(e.to_string())))?;
+
+        // Auto-login after init, reusing the key init just derived
+        self.vault = Some(Vault::new());
+        self.key = Some(key);
+
+        log::info!("Vault initialized at {}", self.vault_path);
+        Ok(())
+    }
+
+    /// Unlock the vault with the master password
+    /// 
+    /// # Errors
+    /// Returns error if vault doesn't exist or password is incorrect
+    pub fn unlock(&mut self, master_password: &Zeroizing<String>) -> PassmanResult<()> {
+        if !self.vault_exists() {
+            return Err(PassmanError::Vault(VaultError::NotFound(
+                self.vault_path.clone()
+            )));
+        }
+
+        let (vault, key) = VaultManager::load_with_key(master_password, Some(&self.vault_path))
+            .map_err(|e| {
+                let msg = e.to_string();
+                if msg.contains("decryption") || msg.contains("authentication") || msg.contains("HMAC") {
+                    PassmanError::Auth(AuthError::InvalidPassword)
+                } else {
+                    PassmanError::Vault(VaultError::ReadError(msg))
+                }
+            })?;
+
+        // The key comes from the file's own salt, so later saves can reuse it
+        self.vault = Some(vault);
+        self.key = Some(key);
+
+        log::info!("Vault unlocked successfully");
+        Ok(())
+    }
+
+    /// Lock the vault (clear sensitive data from memory)
+    pub fn lock(&mut self) {
+        self.vault = None;
+        self.key = None;
+        log::info!("Vault locked");
+    }
+
+    /// Save the current vault state
+    /// 
+    /// The vault is encrypted with the key derived when it was unlocked, so
+    /// neither the master password nor another KDF run is needed. Entries
+    /// trashed longer than `trash.retention_days` are deleted first.
+    /// 
+    /// # Errors
+    /// Returns error if vault is not unlocked
+    pub fn save(&mut self) -> PassmanResult<()> {
+        let (Some(vault), Some(key)) = (self.vault.as_mut(), &self.key) else {
+            return Err(PassmanError::Vault(VaultError::ReadError("Vault is not unlocked".to_string())));
+        };
+
+        let housekeeping = vault.housekeep(&self.config.housekeeping_limits(), chrono::Utc::now());
+        VaultManager::save_with_key(vault, key, Some(&self.vault_path))
+            .map_err(|e| PassmanError::Vault(VaultError::WriteError(e.to_string())))?;
+
+        if housekeeping.is_empty() {
+            log::debug!("Vault saved");
+        } else {
+            log::info!("Vault saved; housekeeping {}", housekeeping.summary());
+        }
+        Ok(())
+    }
+
+    /// What housekeeping would remove on the next save, without removing it
+    pub fn housekeeping_plan(&self) -> Option<HousekeepingReport> {
+        self.vault.as_ref().map(|vault| vault.housekeeping_plan(&self.config.housekeeping_limits(), chrono::Utc::now()))
+    }
+
+    /// Change the master password
+    /// 
+    /// # Errors
+    /// Returns error if current password is incorrect or new password is too weak
+    pub fn change_password(
+        &mut self,
+        current_password: &Zeroizing<String>,
+        new_password: &Zeroizing<String>,
+    ) -> PassmanResult<()> {
+        // Verify current password by loading vault
+        let vault = VaultManager::load(current_password, Some(&self.vault_path))
+            .map_err(|_| PassmanError::Auth(AuthError::InvalidPassword))?;
+
+        // Validate new password
+        self.validate_master_password(new_password)?;
+
+        // Re-key with a fresh salt and the configured KDF parameters
+        let key = VaultManager::change_password(current_password, new_password, Some(&self.vault_path))
+            .map_err(|e| PassmanError::Vault(VaultError::WriteError(e.to_string())))?;
+
+        // Update internal state; the old key no longer matches the file
+        self.vault = Some(vault);
+        self.key = Some(key);
+
+        log::info!("Master password changed successfully");
+        Ok(())
+    }
+
+    // ============ Entry Operations ============
+
+    /// Get a reference to the current vault
+    pub fn vault(&self) -> Option<&Vault> {
+        self.vault.as_ref()
+    }
+
+    /// Get a mutable reference to the current vault
+    pub fn vault_mut(&mut self) -> Option<&mut Vault> {
+        self.vault.as_mut()
+    }
+
+    /// Get an entry by ID
+    pub fn get_entry(&self, id: &str) -> Option<&Entry> {
+        self.vault.as_ref()?.get_entry(id)
+    }
+
+    /// List all entry IDs
+    pub fn list_entries(&self) -> Vec<String> {
+        self.vault.as_ref()
+            .map(|v| v.list_entries().into_iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// List all entries with their data
+    pub fn list_entries_with_data(&self) -> Vec<(String, Entry)> {
+        self.vault.as_ref()
+            .map(|v| {
+                v.list_entries()
+                    .into_iter()
+                    .filter_map(|id| v.get_entry(id).map(|e| (id.clone(), e.clone())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Add a new entry, recorded as created by `general.identity` unless
+    /// it already names a creator
+    /// 
+    /// # Errors
+    /// Returns error if vault is locked or entry already exists
+    pub fn add_entry(&mut self, id: impl Into<String>, mut entry: Entry) -> PassmanResult<()> {
+        let id = id.into();
+        let vault = self.vault.as_mut()
+            .ok_or_else(|| PassmanError::Vault(VaultError::ReadError("Vault is locked".to_string())))?;
+
+        if vault.get_entry(&id).is_some() {
+            return Err(PassmanError::Vault(VaultError::EntryExists(id)));
+        }
+
+        if entry.created_by.is_none() {
+            entry.created_by = identity();
+        }
+        vault.add_entry(id.clone(), entry);
+        log::debug!("Entry added: {}", id);
+        Ok(())
+    }
+
+    /// Copy an entry under `new_id`, or "<id> (copy)" when none is given,
+    /// with an empty password (see [`Entry::duplicate`]). Returns the new id.
+    ///
+    /// # Errors
+    /// Returns error if vault is locked, `id` doesn't exist or `new_id` is taken
+    pub fn duplicate_entry(&mut self, id: &str, new_id: Option<&str>) -> PassmanResult<String> {
+        let vault = self.vault.as_ref()
+            .ok_or_else(|| PassmanError::Vault(VaultError::ReadError("Vault is locked".to_string())))?;
+        let copy = vault.get_entry(id)
+            .ok_or_else(|| PassmanError::Vault(VaultError::EntryNotFound(id.to_string())))?
+            .duplicate();
+        let new_id = new_id.map_or_else(|| vault.copy_id(id), str::to_string);
+
+        self.add_entry(new_id.clone(), copy)?;
+        Ok(new_id)
+    }
+
+    /// Update an existing entry
+    /// 
+    /// # Errors
+    /// Returns error if vault is locked or entry doesn't exist
+    pub fn update_entry(&mut self, id: &str, mut entry: Entry) -> PassmanResult<()> {
+        let vault = self.vault.as_mut()
+            .ok_or_else(|| PassmanError::Vault(VaultError::ReadError("Vault is locked".to_string())))?;
+
+        let Some(existing) = vault.get_entry(id) else {
+            return Err(PassmanError::Vault(VaultError::EntryNotFound(id.to_string())));
+        };
+
+        // Who added it and how its password was generated survive edits;
+        // the editor is `general.identity`
+        entry.created_by = entry.created_by.or_else(|| existing.created_by.clone());
+        entry.generation = entry.generation.or_else(|| existing.generation.clone());
+        entry.modified_by = identity();
+        vault.add_entry(id.to_string(), entry);
+        log::debug!("Entry updated: {}", id);
+        Ok(())
+    }
+
+    /// Give an entry a new id; entries linking to it follow
+    ///
+    /// # Errors
+    /// Returns error if vault is locked, `from` doesn't exist or `to` is taken
+    pub fn rename_entry(&mut self, from: &str, to: impl Into<String>) -> PassmanResult<()> {
+        let to = to.into();
+        let vault = self.vault.as_mut()
+            .ok_or_else(|| PassmanError::Vault(VaultError::ReadError("Vault is locked".to_string())))?;
+
+        if from == to {
+            return Ok(());
+        }
+        if vault.contains(&to) {
+            return Err(PassmanError::Vault(VaultError::EntryExists(to)));
+        }
+        if !vault.rename_entry(from, to.clone()) {
+            return Err(PassmanError::Vault(VaultError::EntryNotFound(from.to_string())));
+        }
+        log::debug!("Entry renamed: {} -> {}", from, to);
+        Ok(())
+    }
+
+    /// Link `id` to `other` (the account that recovers it, say), or with
+    /// `linked` false remove the link. Returns false if nothing changed.
+    /// A link to an entry that no longer exists can still be removed.
+    ///
+    /// # Errors
+    /// Returns error if vault is locked, either entry doesn't exist or
+    /// they are the same entry
+    pub fn set_link(&mut self, id: &str, other: &str, linked: bool) -> PassmanResult<bool> {
+        let vault = self.vault.as_mut()
+            .ok_or_else(|| PassmanError::Vault(VaultError::ReadError("Vault is locked".to_string())))?;
+
+        if id == other {
+            return Err(PassmanError::Other("An entry can't be linked to itself".to_string()));
+        }
+        if linked && !vault.contains(other) {
+            return Err(PassmanError::Vault(VaultError::EntryNotFound(other.to_string())));
+        }
+        let mut entry = vault.get_entry(id)
+            .ok_or_else(|| PassmanError::Vault(VaultError::EntryNotFound(id.to_string())))?
+            .clone();
+        let changed = if linked { entry.link(other) } else { entry.unlink(other) };
+        if changed {
+            entry.update();
+            entry.modified_by = identity();
+            vault.add_entry(id.to_string(), entry);
+        }
+        Ok(changed)
+    }
+
+    /// Archive or unarchive an entry, recording `general.identity` as the
+    /// editor. Returns false if it was already in that state.
+    ///
+    /// # Errors
+    /// Returns error if vault is locked or entry doesn't exist
+    pub fn set_archived(&mut self, id: &str, archived: bool) -> PassmanResult<bool> {
+        let vault = self.vault.as_mut()
+            .ok_or_else(|| PassmanError::Vault(VaultError::ReadError("Vault is locked".to_string())))?;
+
+        if !vault.contains(id) {
+            return Err(PassmanError::Vault(VaultError::EntryNotFound(id.to_string())));
+        }
+        Ok(vault.set_archived(id, archived, chrono::Utc::now(), identity()))
+    }
+
+    /// Count a copy or reveal of an entry's password. Only changes the
+    /// in-memory vault; it is written with the next save.
+    ///
+    /// # Errors
+    /// Returns error if vault is locked or entry doesn't exist
+    pub fn record_use(&mut self, id: &str) -> PassmanResult<()> {
+        let vault = self.vault.as_mut()
+            .ok_or_else(|| PassmanError::Vault(VaultError::ReadError("Vault is locked".to_string())))?;
+
+        if !vault.record_use(id, chrono::Utc::now()) {
+            return Err(PassmanError::Vault(VaultError::EntryNotFound(id.to_string())));
+        }
+        Ok(())
+    }
+
+    /// Remove an entry
+    /// 
+    /// # Errors
+    /// Returns error if vault is locked or entry doesn't exist
+    pub fn remove_entry(&mut self, id: &str) -> PassmanResult<Entry> {
+        let vault = self.vault.as_mut()
+            .ok_or_else(|| PassmanError::Vault(VaultError::ReadError("Vault is locked".to_string())))?;
+
+        vault.remove_entry(id)
+            .ok_or_else(|| PassmanError::Vault(VaultError::EntryNotFound(id.to_string())))
+    }
+
+    /// Apply several entry changes and save once, with the key from
+    /// unlocking. The operations run in order; if any of them fails, none
+    /// is kept and nothing is written. The result has each one's outcome.
+    ///
+    /// # Errors
+    /// Returns error if vault is locked or the save fails
+    pub fn apply_batch(&mut self, ops: Vec<EntryOp>) -> PassmanResult<BatchResult> {
+        let vault = self.vault.as_mut()
+            .ok_or_else(|| PassmanError::Vault(VaultError::ReadError("Vault is locked".to_string())))?;
+
+        let result = apply_ops(vault, ops);
+        if result.changed() {
+            self.save()?;
+        }
+        match result.failures().next() {
+            Some((id, reason)) => log::debug!("Batch rolled back at '{}': {}", id, reason),
+            None => log::debug!("Batch of {} operation(s) applied", result.outcomes.len()),
+        }
+        Ok(result)
+    }
+
+    /// Search entries by pattern (matches ID, username, URL, tags and,
+    /// unless disabled in the config, notes)
+    pub fn search_entries(&self, pattern: &str) -> Vec<(String, Entry)> {
+        let include_notes = get_config().ui.search_notes;
+        self.search_entries_with(pattern, include_notes, SearchScope::ActiveOnly)
+    }
+
+    /// Search entries, choosing explicitly whether notes and the trash are matched
+    pub fn search_entries_with(&self, pattern: &str, include_notes: bool, scope: SearchScope) -> Vec<(String, Entry)> {
+        let Some(vault) = self.vault.as_ref() else {
+            return Vec::new();
+        };
+        search_vault(vault, pattern, include_notes, scope)
+            .into_iter()
+            .filter_map(|id| vault.get_entry(&id).cloned().map(|entry| (id, entry)))
+            .collect()
+    }
+
+    /// Check if vault is empty
+    pub fn is_empty(&self) -> bool {
+        self.vault.as_ref().is_none_or(|v| v.is_empty())
+    }
+
+    /// Get entry count
+    pub fn entry_count(&self) -> usize {
+        self.vault.as_ref().map_or(0, |v| v.len())
+    }
+
+    // ============ Password Generation ============
+
+    /// Generate a random password with default settings
+    pub fn generate_password(&self, length: usize) -> String {
+        generate_password(length)
+    }
+
+    /// Generate a random password with custom configuration
+    pub fn generate_password_configured(&self, length: usize, config: &PasswordConfig) -> PassmanResult<String> {
+        generate_password_with_config(length, config).map_err(PassmanError::Other)
+    }
+
+    /// Generate a memorable password (diceware-style)
+    pub fn generate_memorable_password(&self, word_count: usize) -> String {
+        generate_memorable_password(word_count)
+    }
+
+    /// Generate a pronounceable password of consonant/vowel syllables
+    pub fn generate_pronounceable_password(&self, syllables: usize) -> String {
+        generate_pronounceable_password(syllables)
+    }
+
+    /// Analyze password strength
+    pub fn analyze_password(&self, password: &str) -> (PasswordStrength, Vec<StrengthSuggestion>) {
+        analyze_password_strength(password)
+    }
+
+    /// Strength of the master password used to unlock, if it is weak enough
+    /// to warn about and the warning hasn't been shown recently
+    pub fn master_password_warning(&self, master_password: &str) -> Option<PasswordStrength> {
+        VaultManager::master_password_warning(master_password, Some(&self.vault_path))
+    }
+
+    /// Signs that the vault is in a cloud sync folder, if not already
+    /// warned about (see [`VaultManager::sync_folder_warning`])
+    pub fn sync_folder_warning(&self) -> Option<SyncFolderRisk> {
+        VaultManager::sync_folder_warning(Some(&self.vault_path))
+    }
+
+    // ============ Health Analysis ============
+
+    /// Analyze the health of all passwords in the vault, leaving out the trash
+    pub fn analyze_health(&self) -> Option<(Vec<HealthReport>, HealthSummary)> {
+        self.analyze_health_with(AnalysisScope::ActiveOnly)
+    }
+
+    /// Analyze the health of the passwords in `scope`
+    pub fn analyze_health_with(&self, scope: AnalysisScope) -> Option<(Vec<HealthReport>, HealthSummary)> {
+        let vault = self.vault.as_ref()?;
+        let analyzer = PasswordHealthAnalyzer::new();
+        let reports = analyzer.analyze_vault(vault, scope);
+        let summary = analyzer.generate_summary(&reports);
+        Some((reports, summary))
+    }
+
+    /// Get detailed health analysis for a specific entry
+    pub fn analyze_entry_health(&self, id: &str) -> Option<HealthReport> {
+        let entry = self.get_entry(id)?;
+        let analyzer = PasswordHealthAnalyzer::new();
+        Some(analyzer.analyze_entry(id, entry))
+    }
+
+    /// Get entries with weak passwords
+    pub fn get_weak_passwords(&self) -> Vec<HealthReport> {
+        let vault = match self.vault.as_ref() {
+            Some(v) => v,
+            None => return Vec::new(),
+        };
+
+        let analyzer = PasswordHealthAnalyzer::new();
+        analyzer.analyze_vault(vault, AnalysisScope::ActiveOnly)
+            .into_iter()
+            .filter(|r| matches!(r.health, PasswordHealth::Critical { .. } | PasswordHealth::Warning { .. }))
+            .collect()
+    }
+
+    /// Get entries with reused passwords (same password across entries)
+    pub fn get_reused_passwords(&self) -> Vec<Vec<String>> {
+        let vault = match self.vault.as_ref() {
+            Some(v) => v,
+            None => return Vec::new(),
+        };
+
+        use std::collections::HashMap;
+        let mut password_map: HashMap<&str, Vec<String>> = HashMap::new();
+
+        for id in vault.list_entries() {
+            if let Some(entry) = vault.get_entry(id).filter(|entry| !entry.is_trashed() && !entry.archived && !entry.is_sealed()) {
+                password_map
+                    .entry(entry.password_str().reveal())
+                    .or_default()
+                    .push(id.clone());
+            }
+        }
+
+        password_map
+            .into_values()
+            .filter(|ids| ids.len() > 1)
+            .collect()
+    }
+
+    // ============ Import/Export ============
+
+    /// Export vault to JSON format. High-security entries are left out
+    /// unless `opened` (see [`sealed::open_all`](crate::sealed::open_all))
+    /// holds them.
+    pub fn export_json(&self, file_path: &str, opened: &OpenedEntries) -> PassmanResult<()> {
+        let vault = self.vault.as_ref()
+            .ok_or_else(|| PassmanError::Transfer(TransferError::InvalidData("Vault is locked".to_string())))?;
+
+        ImportExportManager::export_json(vault, file_path, opened)
+            .map_err(|e| PassmanError::Transfer(TransferError::InvalidData(e.to_string())))
+    }
+
+    /// Export vault to CSV format (WARNING: plaintext). High-security
+    /// entries are left out unless `opened` holds them.
+    pub fn export_csv(&self, file_path: &str, opened: &OpenedEntries) -> PassmanResult<()> {
+        let vault = self.vault.as_ref()
+            .ok_or_else(|| PassmanError::Transfer(TransferError::InvalidData("Vault is locked".to_string())))?;
+
+        ImportExportManager::export_csv(vault, file_path, opened)
+            .map_err(|e| PassmanError::Transfer(TransferError::InvalidData(e.to_string())))
+    }
+
+    /// Export vault to KeePass 2.x XML (WARNING: plaintext), grouped by
+    /// `/`-path ids or first tags. High-security entries are left out
+    /// unless `opened` holds them.
+    pub fn export_keepass_xml(&self, file_path: &str, opened: &OpenedEntries) -> PassmanResult<()> {
+        let vault = self.vault.as_ref()
+            .ok_or_else(|| PassmanError::Transfer(TransferError::InvalidData("Vault is locked".to_string())))?;
+
+        ImportExportManager::export_keepass_xml(vault, file_path, opened)
+            .map_err(|e| PassmanError::Transfer(TransferError::InvalidData(e.to_string())))
+    }
+
+    /// Export the vault as an encrypted vault file that opens with the
+    /// same master password
+    pub fn export_encrypted(&self, file_path: &str) -> PassmanResult<()> {
+        let (Some(vault), Some(key)) = (self.vault.as_ref(), self.key.as_ref()) else {
+            return Err(PassmanError::Transfer(TransferError::InvalidData("Vault is locked".to_string())));
+        };
+
+        ImportExportManager::export_encrypted(vault, key, file_path)
+            .map_err(|e| PassmanError::Transfer(TransferError::InvalidData(e.to_string())))
+    }
+
+    /// Import entries from JSON file
+    pub fn import_json(&mut self, file_path: &str, master_password: &Zeroizing<String>, merge: bool, strict: bool) -> PassmanResult<ImportReport> {
+        self.import_profile(file_path, &ImportProfile::new("json"), master_password, merge, strict, &mut |_, _| {})
+    }
+
+    /// Import entries from CSV file
+    pub fn import_csv(&mut self, file_path: &str, master_password: &Zeroizing<String>, merge: bool, strict: bool) -> PassmanResult<ImportReport> {
+        self.import_profile(file_path, &ImportProfile::new("csv"), master_password, merge, strict, &mut |_, _| {})
+    }
+
+    /// Import a KeePass 2.x XML export
+    pub fn import_keepass_xml(&mut self, file_path: &str, master_password: &Zeroizing<String>, merge: bool, strict: bool) -> PassmanResult<ImportReport> {
+        self.import_profile(file_path, &ImportProfile::new("keepass"), master_password, merge, strict, &mut |_, _| {})
+    }
+
+    /// Import entries exported by a browser (`chrome` or `firefox` CSV)
+    pub fn import_browser(&mut self, file_path: &str, browser: &str, master_password: &Zeroizing<String>, merge: bool, strict: bool) -> PassmanResult<ImportReport> {
+        self.import_profile(file_path, &ImportProfile::new(browser), master_password, merge, strict, &mut |_, _| {})
+    }
+
+    /// Import a `pass` password store, decrypting each file with `gpg`;
+    /// `progress` gets (files done, total)
+    pub fn import_pass(
+        &mut self,
+        store_dir: &str,
+        master_password: &Zeroizing<String>,
+        merge: bool,
+        strict: bool,
+        progress: &mut dyn FnMut(usize, usize),
+    ) -> PassmanResult<ImportReport> {
+        self.import_profile(store_dir, &ImportProfile::new("pass"), master_password, merge, strict, progress)
+    }
+
+    /// Import a file the way a saved profile describes; `progress` gets
+    /// (files done, total) for password stores. The vault is created if it
+    /// doesn't exist yet, and the rows are added as one batch.
+    pub fn import_profile(
+        &mut self,
+        file_path: &str,
+        profile: &ImportProfile,
+        master_password: &Zeroizing<String>,
+        merge: bool,
+        strict: bool,
+        progress: &mut dyn FnMut(usize, usize),
+    ) -> PassmanResult<ImportReport> {
+        let exists = self.vault_exists();
+        if exists && !merge {
+            return Err(PassmanError::Transfer(TransferError::ParseError(
+                "Vault already exists! Use --merge flag to merge with existing vault or choose a different vault file.".to_string()
+            )));
+        }
+
+        // Check the rows before creating or decrypting anything
+        let (mut rows, errors) = ImportExportManager::parse(file_path, profile, progress)
+            .and_then(|(rows, errors)| ImportExportManager::check_strict(&errors, strict).map(|_| (rows, errors)))
+            .map_err(|e| PassmanError::Transfer(TransferError::ParseError(e.to_string())))?;
+        let moved = ImportExportManager::attach_oversize_fields(&mut rows, profile);
+
+        if !exists {
+            // Unlike `init_vault`, an import doesn't second-guess the password's strength
+            let key = VaultManager::init(master_password, Some(&self.vault_path))
+                .map_err(|e| PassmanError::Vault(VaultError::WriteError(e.to_string())))?;
+            self.vault = Some(Vault::new());
+            self.key = Some(key);
+        } else if !self.is_authenticated() {
+            self.unlock(master_password)?;
+        }
+
+        let result = self.apply_batch(ImportExportManager::import_ops(rows, profile.conflict))?;
+        let mut report = ImportExportManager::import_report(&result, errors);
+        report.record_attached(moved);
+        Ok(report)
+    }
+
+    /// Create a backup of the current vault and verify that it decrypts
+    /// with `master_password`
+    pub fn create_backup(&self, master_password: &Zeroizing<String>) -> PassmanResult<String> {
+        VaultManager::create_backup(master_password, Some(&self.vault_path))
+            .map_err(|e| PassmanError::Transfer(TransferError::InvalidData(e.to_string())))
+    }
+
+    /// Backups of the current vault with their recorded verification, newest first
+    pub fn backups(&self) -> Vec<BackupInfo> {
+        VaultManager::backup_infos(Some(&self.vault_path))
+    }
+
+    /// Replace the vault file with a backup and reload it.
+    ///
+    /// # Errors
+    /// `VaultError::Corrupted` if the backup doesn't decrypt with
+    /// `master_password` and `force` is not set; nothing is changed then.
+    pub fn restore_backup(&mut self, master_password: &Zeroizing<String>, backup_path: &str, force: bool) -> PassmanResult<String> {
+        let safety = VaultManager::restore_backup(master_password, backup_path, Some(&self.vault_path), force)
+            .map_err(|e| PassmanError::Vault(VaultError::Corrupted(e.to_string())))?;
+        // A forced restore may leave a vault this password can't open
+        (self.vault, self.key) = VaultManager::load_with_key(master_password, Some(&self.vault_path)).ok().unzip();
+        Ok(safety)
+    }
+
+    /// Compare the open vault (A) against a backup (B) decrypted with
+    /// `master_password`, to show what a restore would roll back.
+    /// Secrets are redacted.
+    pub fn compare_backup(&self, master_password: &Zeroizing<String>, backup_path: &str) -> PassmanResult<VaultDiff> {
+        let vault = self.vault.as_ref()
+            .ok_or_else(|| PassmanError::Vault(VaultError::ReadError("Vault is not unlocked".to_string())))?;
+        let backup = VaultManager::load(master_password, Some(backup_path))
+            .map_err(|e| PassmanError::Vault(VaultError::Corrupted(e.to_string())))?;
+        let mut diff = VaultDiff::compare(vault, &backup);
+        diff.redact_secrets();
+        Ok(diff)
+    }
+
+    /// Conflicted sync copies beside the vault file, by path
+    pub fn conflicted_copies(&self) -> Vec<String> {
+        VaultManager::conflicted_copies(Some(&self.vault_path))
+    }
+
+    /// Merge a conflicted sync copy, already decrypted as `copy`, into the
+    /// open vault, keeping the newer version of each entry, and save. The
+    /// vault file is backed up first and the copy moved aside with a
+    /// `.resolved` suffix.
+    pub fn resolve_conflicted_copy(&mut self, copy_path: &str, copy: &Vault) -> PassmanResult<ConflictResolution> {
+        let (Some(vault), Some(key)) = (self.vault.as_mut(), self.key.as_ref()) else {
+            return Err(PassmanError::Vault(VaultError::ReadError("Vault is not unlocked".to_string())));
+        };
+        VaultManager::resolve_conflicted_copy(vault, key, copy_path, copy, Some(&self.vault_path))
+            .map_err(|e| PassmanError::Vault(VaultError::WriteError(e.to_string())))
+    }
+
+    /// Record the open vault as it is now in the change marker beside the
+    /// vault file, replacing the previous marker
+    pub fn mark_changes(&self) -> PassmanResult<ChangeMarker> {
+        let (Some(vault), Some(key)) = (self.vault.as_ref(), self.key.as_ref()) else {
+            return Err(PassmanError::Vault(VaultError::ReadError("Vault is not unlocked".to_string())));
+        };
+        let marker = ChangeMarker::take(vault, key, chrono::Utc::now());
+        marker.store(&self.vault_path)?;
+        Ok(marker)
+    }
+
+    /// Entries modified after `since`, with the fields that differ from the
+    /// change marker, and the time `since` stands for. Values, secrets
+    /// included, are only filled in with `include_values`.
+    pub fn changes(&self, since: Since, include_values: bool) -> PassmanResult<(chrono::DateTime<chrono::Utc>, Vec<EntryChange>)> {
+        let (Some(vault), Some(key)) = (self.vault.as_ref(), self.key.as_ref()) else {
+            return Err(PassmanError::Vault(VaultError::ReadError("Vault is not unlocked".to_string())));
+        };
+        let marker = ChangeMarker::load(&self.vault_path)?;
+        let since = match (since, &marker) {
+            (Since::At(at), _) => at,
+            (Since::LastMark, Some(marker)) => marker.marked_at,
+            (Since::LastMark, None) => return Err("No change marker yet; record one with 'passman changes mark'".to_string().into()),
+        };
+        Ok((since, changes_since(vault, key, since, marker.as_ref(), include_values)))
+    }
+
+    /// Merge a backup into the open vault, keeping the newer copy of each
+    /// entry, and save. The vault file is backed up first; that safety
+    /// backup's path is returned.
+    pub fn merge_backup(&mut self, master_password: &Zeroizing<String>, backup_path: &str) -> PassmanResult<String> {
+        let backup = VaultManager::load(master_password, Some(backup_path))
+            .map_err(|e| PassmanError::Vault(VaultError::Corrupted(e.to_string())))?;
+        let safety = self.create_backup(master_password)?;
+        let vault = self.vault.as_mut()
+            .ok_or_else(|| PassmanError::Vault(VaultError::ReadError("Vault is not unlocked".to_string())))?;
+        merge_newer(vault, &backup);
+        self.save()?;
+        log::info!("Merged backup {} into vault", backup_path);
+        Ok(safety)
+    }
+
+
+    // ============ Validation Helpers ============
+
+    /// Validate master password meets minimum requirements
+    fn validate_master_password(&self, password: &Zeroizing<String>) -> PassmanResult<()> {
+        if password.len() < self.config.security.min_password_length {
+            return Err(PassmanError::Auth(AuthError::WeakPassword(format!(
+                "Password must be at least {} characters",
+                self.config.security.min_password_length
+            ))));
+        }
+
+        let (strength, _) = analyze_password_strength(password.as_str());
+        match strength {
+            PasswordStrength::VeryWeak | PasswordStrength::Weak => {
+                Err(PassmanError::Auth(AuthError::WeakPassword(
+                    "Master password is too weak. Use a stronger password.".to_string()
+                )))
+            }
+            _ => Ok(())
+        }
+    }
+
+    /// Create a new entry with default values
+    pub fn create_entry(
+        &self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+        note: Option<String>,
+    ) -> Entry {
+        Entry::new(username.into(), password.into(), note)
+    }
+}
+
+impl Default for PassmanCore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============ Builder Pattern for Entry Creation ============
+
+/// Builder for creating entries with optional fields
+///
+/// ```
+/// use passman_core::EntryBuilder;
+///
+/// let entry = EntryBuilder::new("octocat")
+///     .password("hunter2")
+///     .url("https://github.com")
+///     .tag("work")
+///     .build();
+/// assert!(entry.has_tag("Work"));
+/// ```
+pub struct EntryBuilder {
+    username: String,
+    password: String,
+    note: Option<String>,
+    url: Option<String>,
+    tags: Vec<String>,
+}
+
+impl EntryBuilder {
+    pub fn new(username: impl Into<String>) -> Self {
+        Self {
+            username: username.into(),
+            password: String::new(),
+            note: None,
+            url: None,
+            tags: Vec::new(),
+        }
+    }
+
+    pub fn password(mut self, password: impl Into<String>) -> Self {
+        self.password = password.into();
+        self
+    }
+
+    pub fn generate_password(mut self, length: usize) -> Self {
+        self.password = generate_password(length);
+        self
+    }
+
+    pub fn note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn build(self) -> Entry {
+        let mut entry = Entry::new(self.username, self.password, self.note);
+        if let Some(url) = self.url {
+            entry.url = Some(url);
+        }
+        entry.tags = self.tags;
+        entry
+    }
+}
+
+// ============ Unit Tests ============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_core_creation() {
+        let core = PassmanCore::new();
+        assert!(!core.is_authenticated());
+        assert!(core.vault().is_none());
+    }
+
+    #[test]
+    fn test_core_with_vault_path() {
+        let core = PassmanCore::with_vault_path("/tmp/test_vault.dat");
+        assert_eq!(core.vault_path(), "/tmp/test_vault.dat");
+    }
+
+    #[test]
+    fn test_entry_builder() {
+        let entry = EntryBuilder::new("user@example.com")
+            .password("SecurePass123!")
+            .note("Test note")
+            .url("https://example.com")
+            .tag("work")
+            .tag("important")
+            .build();
+
+        assert_eq!(entry.username, "user@example.com");
+        assert_eq!(entry.password_str(), "SecurePass123!");
+        assert_eq!(entry.note, Some("Test note".to_string()));
+        assert_eq!(entry.url, Some("https://example.com".to_string()));
+        assert_eq!(entry.tags.len(), 2);
+    }
+
+    #[test]
+    fn test_entry_builder_generate_password() {
+        let entry = EntryBuilder::new("user@example.com")
+            .generate_password(20)
+            .build();
+
+        assert_eq!(entry.password_str().len(), 20);
+    }
+
+    #[test]
+    fn test_password_generation() {
+        let core = PassmanCore::new();
+        
+        let password = core.generate_password(16);
+        assert_eq!(password.len(), 16);
+        
+        let memorable = core.generate_memorable_password(4);
+        assert!(!memorable.is_empty());
+    }
+
+    #[test]
+    fn test_password_analysis() {
+        let core = PassmanCore::new();
+        
+        let (strength, _) = core.analyze_password("weak");
+        assert!(matches!(strength, PasswordStrength::VeryWeak | PasswordStrength::Weak));
+        
+        let (strength, _) = core.analyze_password("Str0ng!P@ssw0rd#2024");
+        assert!(matches!(strength, PasswordStrength::Good | PasswordStrength::Strong));
+    }
+
+    #[test]
+    fn test_create_entry() {
+        let core = PassmanCore::new();
+        let entry = core.create_entry("user", "pass", Some("note".to_string()));
+        
+        assert_eq!(entry.username, "user");
+        assert_eq!(entry.password_str(), "pass");
+        assert_eq!(entry.note, Some("note".to_string()));
+    }
+
+    #[test]
+    fn test_merge_backup_keeps_entries_added_after_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("merge.dat");
+        let path = path.to_str().unwrap();
+        let password = Zeroizing::new("Correct-Horse-Battery-42!".to_string());
+
+        let mut core = PassmanCore::with_vault_path(path);
+        core.init_vault(&password).unwrap();
+        core.add_entry("mail", EntryBuilder::new("me@example.com").password("pw").build()).unwrap();
+        core.save().unwrap();
+        let backup = core.create_backup(&password).unwrap();
+
+        core.remove_entry("mail").unwrap();
+        core.add_entry("bank", EntryBuilder::new("me").password("pw").build()).unwrap();
+        core.save().unwrap();
+
+        let diff = core.compare_backup(&password, &backup).unwrap();
+        assert_eq!(diff.only_in_a, vec!["bank"]);
+        assert_eq!(diff.only_in_b, vec!["mail"]);
+        assert!(diff.restore_warning().is_some());
+
+        core.merge_backup(&password, &backup).unwrap();
+        let mut reopened = PassmanCore::with_vault_path(path);
+        reopened.unlock(&password).unwrap();
+        assert!(reopened.get_entry("bank").is_some());
+        assert!(reopened.get_entry("mail").is_some());
+    }
+
+    #[test]
+    fn test_trash_retention_holds_across_save_cycles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("housekeeping.dat");
+        let path = path.to_str().unwrap();
+        let password = Zeroizing::new("Correct-Horse-Battery-42!".to_string());
+
+        let mut core = PassmanCore::with_vault_path(path);
+        core.config.trash.retention_days = 30;
+        core.init_vault(&password).unwrap();
+        let now = chrono::Utc::now();
+        for cycle in 0..5i64 {
+            // Each cycle trashes one entry long ago and one just now
+            for (id, days_ago) in [(format!("old-{}", cycle), 31 + cycle), (format!("new-{}", cycle), 1)] {
+                core.add_entry(id.as_str(), EntryBuilder::new("me").password("pw").build()).unwrap();
+                core.vault_mut().unwrap().trash_entry(&id, now - chrono::Duration::days(days_ago));
+            }
+            assert_eq!(core.housekeeping_plan().unwrap().purged_trash.len(), 1);
+            core.save().unwrap();
+
+            let mut reopened = PassmanCore::with_vault_path(path);
+            reopened.unlock(&password).unwrap();
+            let mut ids = reopened.list_entries();
+            ids.sort();
+            let expected: Vec<String> = (0..=cycle).map(|n| format!("new-{}", n)).collect();
+            assert_eq!(ids, expected);
+        }
+        assert!(core.housekeeping_plan().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_saves_with_cached_key_open_with_current_password() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cached.dat");
+        let path = path.to_str().unwrap();
+        let password = Zeroizing::new("Correct-Horse-Battery-42!".to_string());
+        let new_password = Zeroizing::new("Staple-Paper-Clip-Tree-7?".to_string());
+
+        let mut core = PassmanCore::with_vault_path(path);
+        core.init_vault(&password).unwrap();
+        core.add_entry("mail", EntryBuilder::new("me@example.com").password("pw").build()).unwrap();
+        core.save().unwrap();
+        let salt = VaultManager::key_params(Some(path)).unwrap().0;
+        assert_eq!(core.key.as_ref().unwrap().salt().as_str(), salt.as_str());
+
+        let mut reopened = PassmanCore::with_vault_path(path);
+        reopened.unlock(&password).unwrap();
+        assert!(reopened.get_entry("mail").is_some());
+
+        // After re-keying, saves must use the new password's key
+        core.change_password(&password, &new_password).unwrap();
+        core.add_entry("bank", EntryBuilder::new("me").password("pw").build()).unwrap();
+        core.save().unwrap();
+        let mut reopened = PassmanCore::with_vault_path(path);
+        assert!(reopened.unlock(&password).is_err());
+        reopened.unlock(&new_password).unwrap();
+        assert!(reopened.get_entry("bank").is_some());
+    }
+
+    #[test]
+    fn test_apply_batch_saves_once_or_not_at_all() {
+        use crate::batch::{EntryOp, OpOutcome};
+        use crate::import_export::ConflictPolicy;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("batch.dat");
+        let path = path.to_str().unwrap();
+        let password = Zeroizing::new("Correct-Horse-Battery-42!".to_string());
+
+        let mut core = PassmanCore::with_vault_path(path);
+        core.init_vault(&password).unwrap();
+        let result = core.apply_batch(vec![
+            EntryOp::Add { id: "mail".into(), entry: EntryBuilder::new("me").password("pw").build(), on_conflict: ConflictPolicy::Skip },
+            EntryOp::Rename { from: "mail".into(), to: "work mail".into(), on_conflict: ConflictPolicy::Skip },
+        ]).unwrap();
+        assert!(result.committed);
+        assert_eq!(result.outcomes[1], ("mail".to_string(), OpOutcome::Applied));
+
+        let result = core.apply_batch(vec![
+            EntryOp::Remove { id: "work mail".into() },
+            EntryOp::Update { id: "missing".into(), entry: EntryBuilder::new("me").build() },
+        ]).unwrap();
+        assert!(!result.committed);
+        assert!(core.get_entry("work mail").is_some());
+
+        let mut reopened = PassmanCore::with_vault_path(path);
+        reopened.unlock(&password).unwrap();
+        assert!(reopened.get_entry("work mail").is_some());
+        assert!(reopened.get_entry("mail").is_none());
+
+        core.lock();
+        assert!(core.apply_batch(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_generation_prefs_survive_edits_duplicates_and_renames() {
+        use crate::batch::EntryOp;
+        use crate::import_export::ConflictPolicy;
+        use crate::model::{GenerationMode, GenerationPrefs};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prefs.dat");
+        let mut core = PassmanCore::with_vault_path(path.to_str().unwrap());
+        core.init_vault(&Zeroizing::new("Correct-Horse-Battery-42!".to_string())).unwrap();
+
+        let prefs = GenerationPrefs {
+            length: 5,
+            rules: PasswordConfig { include_symbols: false, ..Default::default() },
+            mode: GenerationMode::Pronounceable,
+        };
+        let mut entry = EntryBuilder::new("me").build();
+        entry.password = prefs.generate().unwrap().into();
+        entry.generation = Some(prefs.clone());
+        core.add_entry("bank", entry).unwrap();
+
+        // An edit that doesn't generate keeps them
+        core.update_entry("bank", EntryBuilder::new("me2").password("typed").build()).unwrap();
+        assert_eq!(core.get_entry("bank").unwrap().generation, Some(prefs.clone()));
+
+        let copy = core.duplicate_entry("bank", None).unwrap();
+        assert_eq!(core.get_entry(&copy).unwrap().generation, Some(prefs.clone()));
+
+        core.apply_batch(vec![
+            EntryOp::Rename { from: "bank".into(), to: "bank (old)".into(), on_conflict: ConflictPolicy::Skip },
+            EntryOp::Update { id: copy.clone(), entry: EntryBuilder::new("me3").password("pw").build() },
+        ]).unwrap();
+        assert_eq!(core.get_entry("bank (old)").unwrap().generation, Some(prefs.clone()));
+        assert_eq!(core.get_entry(&copy).unwrap().generation, Some(prefs));
+    }
+
+    #[test]
+    fn test_links_are_checked_and_follow_renames() {
+        let mut core = PassmanCore::with_vault("unused.dat", Vault::new());
+        for id in ["mail", "shop"] {
+            core.add_entry(id, EntryBuilder::new("me").password("pw").build()).unwrap();
+        }
+        assert!(core.set_link("shop", "mail", true).unwrap());
+        assert!(!core.set_link("shop", "mail", true).unwrap());
+        assert!(core.set_link("shop", "shop", true).is_err());
+        assert!(core.set_link("shop", "nowhere", true).is_err());
+        assert!(core.set_link("nowhere", "mail", true).is_err());
+
+        assert!(core.rename_entry("mail", "shop").is_err());
+        core.rename_entry("mail", "email").unwrap();
+        assert_eq!(core.get_entry("shop").unwrap().linked_ids, vec!["email"]);
+
+        // A dangling link can still be removed
+        core.remove_entry("email").unwrap();
+        assert!(core.set_link("shop", "email", false).unwrap());
+        assert!(core.get_entry("shop").unwrap().linked_ids.is_empty());
+    }
+}
@@ -0,0 +1,608 @@
+use aes_gcm::{Aes256Gcm, KeyInit};
+use aes_gcm::aead::{Aead, generic_array::GenericArray};
+use argon2::{Argon2, password_hash::SaltString, PasswordHasher};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+
+use std::time::Instant;
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Lowest memory cost the calibration routine will suggest (OWASP minimum for Argon2id)
+const MIN_CALIBRATION_MEMORY_KB: u32 = 19456;
+
+/// Upper bound on suggested iterations to keep calibration output sane
+const MAX_CALIBRATION_TIME_COST: u32 = 64;
+
+/// Argon2id cost of the in-memory master password check: cheap enough to
+/// run on every entry save, yet at the OWASP minimum so a memory dump
+/// doesn't make the master password easy to guess
+const MASTER_CHECK_KDF: KdfParams = KdfParams {
+    memory_kb: MIN_CALIBRATION_MEMORY_KB,
+    time_cost: 2,
+    parallelism: 1,
+};
+
+/// Secure key wrapper that automatically zeroizes on drop
+#[derive(Clone, ZeroizeOnDrop)]
+pub struct Key(GenericArray<u8, typenum::U32>);
+
+impl Key {
+    pub fn new(data: GenericArray<u8, typenum::U32>) -> Self {
+        Self(data)
+    }
+    
+    pub fn as_array(&self) -> &GenericArray<u8, typenum::U32> {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Key {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl std::ops::Deref for Key {
+    type Target = GenericArray<u8, typenum::U32>;
+    
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[derive(Debug)]
+pub enum CryptoError {
+    KeyDerivation(String),
+    Encryption(String),
+    Decryption(String),
+}
+
+impl std::fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CryptoError::KeyDerivation(msg) => write!(f, "Key derivation error: {}", msg),
+            CryptoError::Encryption(msg) => write!(f, "Encryption error: {}", msg),
+            CryptoError::Decryption(msg) => write!(f, "Decryption error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
+/// Argon2id cost parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory cost in KB
+    pub memory_kb: u32,
+    /// Time cost (iterations)
+    pub time_cost: u32,
+    /// Degree of parallelism
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory_kb: 65536,
+            time_cost: 3,
+            parallelism: 4,
+        }
+    }
+}
+
+impl KdfParams {
+    /// Build parameters from the security section of the configuration
+    pub fn from_config(security: &crate::config::SecurityConfig) -> Self {
+        Self {
+            memory_kb: security.argon2_memory_kb,
+            time_cost: security.argon2_time_cost,
+            parallelism: security.argon2_parallelism,
+        }
+    }
+}
+
+impl std::fmt::Display for KdfParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "m={} KB, t={}, p={}", self.memory_kb, self.time_cost, self.parallelism)
+    }
+}
+
+/// Result of benchmarking key derivation on this machine
+#[derive(Debug, Clone, Copy)]
+pub struct KdfCalibration {
+    pub params: KdfParams,
+    pub measured_ms: u64,
+}
+
+/// Derive a key using the default Argon2id parameters
+pub fn derive_key(password: &str, salt: &SaltString) -> Result<Key, CryptoError> {
+    derive_key_with_params(password, salt, &KdfParams::default())
+}
+
+/// Derive a key using explicit Argon2id parameters
+pub fn derive_key_with_params(password: &str, salt: &SaltString, kdf: &KdfParams) -> Result<Key, CryptoError> {
+    let params = argon2::Params::new(kdf.memory_kb, kdf.time_cost, kdf.parallelism, None)
+        .map_err(|e| CryptoError::KeyDerivation(e.to_string()))?;
+    let argon2 = Argon2::new(
+        argon2::Algorithm::Argon2id,
+        argon2::Version::V0x13,
+        params
+    );
+    
+    let hash = argon2.hash_password(password.as_bytes(), salt)
+        .map_err(|e| CryptoError::KeyDerivation(e.to_string()))?;
+    
+    let hash_output = hash.hash
+        .ok_or_else(|| CryptoError::KeyDerivation("No hash output".to_string()))?;
+    let key_bytes = hash_output.as_bytes();
+    
+    // Ensure we have exactly 32 bytes for AES-256
+    let mut key_array = [0u8; 32];
+    let len = std::cmp::min(key_bytes.len(), 32);
+    key_array[..len].copy_from_slice(&key_bytes[..len]);
+    
+    let key = Key::new(*GenericArray::from_slice(&key_array));
+    
+    // Zeroize the temporary key_array
+    key_array.zeroize();
+    
+    Ok(key)
+}
+
+/// Time a single key derivation with the given parameters
+fn measure_derive_ms(kdf: &KdfParams) -> Result<u64, CryptoError> {
+    let salt = SaltString::generate(&mut rand::thread_rng());
+    let start = Instant::now();
+    derive_key_with_params("passman-calibration", &salt, kdf)?;
+    Ok(start.elapsed().as_millis() as u64)
+}
+
+/// Benchmark Argon2id on this machine and find parameters that take
+/// roughly `target_ms` to derive a key.
+///
+/// Memory cost is only lowered when a single iteration already exceeds
+/// the target; otherwise the iteration count is scaled to fit.
+pub fn calibrate_kdf(target_ms: u64, memory_kb: u32, parallelism: u32) -> Result<KdfCalibration, CryptoError> {
+    let mut params = KdfParams {
+        memory_kb,
+        time_cost: 1,
+        parallelism,
+    };
+
+    let mut single_pass_ms = measure_derive_ms(&params)?;
+    while single_pass_ms > target_ms && params.memory_kb / 2 >= MIN_CALIBRATION_MEMORY_KB {
+        params.memory_kb /= 2;
+        single_pass_ms = measure_derive_ms(&params)?;
+    }
+
+    let iterations = (target_ms as f64 / single_pass_ms.max(1) as f64).round() as u32;
+    params.time_cost = iterations.clamp(1, MAX_CALIBRATION_TIME_COST);
+
+    let measured_ms = if params.time_cost == 1 {
+        single_pass_ms
+    } else {
+        measure_derive_ms(&params)?
+    };
+
+    Ok(KdfCalibration { params, measured_ms })
+}
+
+/// Salted hash of an open vault's master password, to notice it being
+/// stored as an entry password without keeping the password itself
+#[derive(Clone)]
+pub struct MasterPasswordCheck {
+    salt: SaltString,
+    hash: Key,
+}
+
+impl MasterPasswordCheck {
+    /// Hash `master_password` under a fresh random salt
+    pub fn new(master_password: &str) -> Result<Self, CryptoError> {
+        let salt = SaltString::generate(&mut rand::thread_rng());
+        let hash = derive_key_with_params(master_password, &salt, &MASTER_CHECK_KDF)?;
+        Ok(Self { salt, hash })
+    }
+
+    /// Whether `password` is the master password. The full hash and a
+    /// constant-time comparison run whatever `password` is, so the time
+    /// taken says nothing about how close it came.
+    pub fn matches(&self, password: &str) -> bool {
+        derive_key_with_params(password, &self.salt, &MASTER_CHECK_KDF)
+            .is_ok_and(|hash| bool::from(hash.as_ref().ct_eq(self.hash.as_ref())))
+    }
+}
+
+impl std::fmt::Debug for MasterPasswordCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[REDACTED]")
+    }
+}
+
+pub fn encrypt_data(key: &Key, plaintext: &[u8]) -> Result<(Vec<u8>, [u8; 12]), CryptoError> {
+    let cipher = Aes256Gcm::new(key.as_array());
+    let nonce_bytes = rand::random::<[u8; 12]>();
+    let nonce = GenericArray::from_slice(&nonce_bytes);
+    let ciphertext = cipher.encrypt(nonce, plaintext)
+        .map_err(|e| CryptoError::Encryption(e.to_string()))?;
+    Ok((ciphertext, nonce_bytes))
+}
+
+/// Encrypt with a caller-supplied nonce.
+///
+/// Only for deterministic output (e.g. sync files) where the nonce is derived
+/// from the plaintext itself, so a nonce is never reused for different data.
+pub fn encrypt_data_with_nonce(key: &Key, plaintext: &[u8], nonce_bytes: &[u8; 12]) -> Result<Vec<u8>, CryptoError> {
+    let cipher = Aes256Gcm::new(key.as_array());
+    let nonce = GenericArray::from_slice(nonce_bytes);
+    cipher.encrypt(nonce, plaintext)
+        .map_err(|e| CryptoError::Encryption(e.to_string()))
+}
+
+pub fn decrypt_data(key: &Key, ciphertext: &[u8], nonce: &[u8; 12]) -> Result<Vec<u8>, CryptoError> {
+    let cipher = Aes256Gcm::new(key.as_array());
+    let nonce = GenericArray::from_slice(nonce);
+    cipher.decrypt(nonce, ciphertext)
+        .map_err(|_e| CryptoError::Decryption("Invalid password or corrupted data".to_string()))
+}
+
+/// An AEAD cipher a vault body can be encrypted with. The key is always
+/// 256 bits; only the nonce length differs.
+pub trait Cipher {
+    /// Bytes of nonce stored in front of the ciphertext
+    fn nonce_len(&self) -> usize;
+
+    /// Encrypt under a fresh random nonce, returning the ciphertext and the nonce
+    fn encrypt(&self, key: &Key, plaintext: &[u8]) -> Result<(Vec<u8>, Vec<u8>), CryptoError>;
+
+    /// Decrypt and authenticate; fails on a wrong key, nonce or tampered data
+    fn decrypt(&self, key: &Key, ciphertext: &[u8], nonce: &[u8]) -> Result<Vec<u8>, CryptoError>;
+}
+
+/// AES-256-GCM with 96-bit random nonces, the cipher of every vault
+/// written before the cipher could be chosen
+pub struct Aes256GcmCipher;
+
+impl Cipher for Aes256GcmCipher {
+    fn nonce_len(&self) -> usize {
+        12
+    }
+
+    fn encrypt(&self, key: &Key, plaintext: &[u8]) -> Result<(Vec<u8>, Vec<u8>), CryptoError> {
+        encrypt_data(key, plaintext).map(|(ciphertext, nonce)| (ciphertext, nonce.to_vec()))
+    }
+
+    fn decrypt(&self, key: &Key, ciphertext: &[u8], nonce: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let nonce: &[u8; 12] = nonce.try_into()
+            .map_err(|_| CryptoError::Decryption(format!("nonce must be 12 bytes, not {}", nonce.len())))?;
+        decrypt_data(key, ciphertext, nonce)
+    }
+}
+
+/// XChaCha20-Poly1305: 192-bit nonces that are safe to pick at random for
+/// any number of saves, and fast without AES hardware
+pub struct XChaCha20Poly1305Cipher;
+
+impl Cipher for XChaCha20Poly1305Cipher {
+    fn nonce_len(&self) -> usize {
+        24
+    }
+
+    fn encrypt(&self, key: &Key, plaintext: &[u8]) -> Result<(Vec<u8>, Vec<u8>), CryptoError> {
+        let cipher = XChaCha20Poly1305::new(key.as_array());
+        let nonce_bytes = rand::random::<[u8; 24]>();
+        let ciphertext = cipher.encrypt(XNonce::from_slice(&nonce_bytes), plaintext)
+            .map_err(|e| CryptoError::Encryption(e.to_string()))?;
+        Ok((ciphertext, nonce_bytes.to_vec()))
+    }
+
+    fn decrypt(&self, key: &Key, ciphertext: &[u8], nonce: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if nonce.len() != self.nonce_len() {
+            return Err(CryptoError::Decryption(format!("nonce must be 24 bytes, not {}", nonce.len())));
+        }
+        let cipher = XChaCha20Poly1305::new(key.as_array());
+        cipher.decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_e| CryptoError::Decryption("Invalid password or corrupted data".to_string()))
+    }
+}
+
+/// Which [`Cipher`] a vault is encrypted with, as chosen in
+/// `security.cipher` and stored in the vault header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CipherKind {
+    #[default]
+    #[serde(rename = "aes-256-gcm", alias = "aes")]
+    Aes256Gcm,
+    #[serde(rename = "xchacha20-poly1305", alias = "xchacha20")]
+    XChaCha20Poly1305,
+}
+
+impl CipherKind {
+    pub const ALL: [CipherKind; 2] = [CipherKind::Aes256Gcm, CipherKind::XChaCha20Poly1305];
+
+    /// Identifier byte in the vault header
+    pub fn id(self) -> u8 {
+        match self {
+            CipherKind::Aes256Gcm => 0,
+            CipherKind::XChaCha20Poly1305 => 1,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.id() == id)
+    }
+
+    pub fn cipher(self) -> &'static dyn Cipher {
+        match self {
+            CipherKind::Aes256Gcm => &Aes256GcmCipher,
+            CipherKind::XChaCha20Poly1305 => &XChaCha20Poly1305Cipher,
+        }
+    }
+}
+
+impl std::fmt::Display for CipherKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CipherKind::Aes256Gcm => write!(f, "aes-256-gcm"),
+            CipherKind::XChaCha20Poly1305 => write!(f, "xchacha20-poly1305"),
+        }
+    }
+}
+
+impl std::str::FromStr for CipherKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "aes" | "aes-256-gcm" | "aes256gcm" => Ok(CipherKind::Aes256Gcm),
+            "xchacha20" | "xchacha20-poly1305" => Ok(CipherKind::XChaCha20Poly1305),
+            other => Err(format!("Unknown cipher: {} (expected aes-256-gcm or xchacha20)", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argon2::password_hash::rand_core::OsRng;
+    
+    #[test]
+    fn test_key_derivation() {
+        let salt = SaltString::generate(&mut OsRng);
+        let password = "test_password_123";
+        
+        let key = derive_key(password, &salt).expect("Key derivation should succeed");
+        
+        // Key should be 32 bytes for AES-256
+        assert_eq!(key.as_ref().len(), 32);
+    }
+    
+    #[test]
+    fn test_key_derivation_deterministic() {
+        let salt = SaltString::generate(&mut OsRng);
+        let password = "deterministic_test";
+        
+        let key1 = derive_key(password, &salt).expect("Key derivation should succeed");
+        let key2 = derive_key(password, &salt).expect("Key derivation should succeed");
+        
+        // Same password and salt should produce same key
+        assert_eq!(key1.as_ref(), key2.as_ref());
+    }
+    
+    #[test]
+    fn test_different_passwords_different_keys() {
+        let salt = SaltString::generate(&mut OsRng);
+        
+        let key1 = derive_key("password1", &salt).expect("Key derivation should succeed");
+        let key2 = derive_key("password2", &salt).expect("Key derivation should succeed");
+        
+        // Different passwords should produce different keys
+        assert_ne!(key1.as_ref(), key2.as_ref());
+    }
+    
+    #[test]
+    fn test_different_salts_different_keys() {
+        let salt1 = SaltString::generate(&mut OsRng);
+        let salt2 = SaltString::generate(&mut OsRng);
+        let password = "same_password";
+        
+        let key1 = derive_key(password, &salt1).expect("Key derivation should succeed");
+        let key2 = derive_key(password, &salt2).expect("Key derivation should succeed");
+        
+        // Different salts should produce different keys
+        assert_ne!(key1.as_ref(), key2.as_ref());
+    }
+    
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let salt = SaltString::generate(&mut OsRng);
+        let key = derive_key("test_password", &salt).expect("Key derivation should succeed");
+        let plaintext = b"Hello, World! This is a secret message.";
+        
+        let (ciphertext, nonce) = encrypt_data(&key, plaintext).expect("Encryption should succeed");
+        let decrypted = decrypt_data(&key, &ciphertext, &nonce).expect("Decryption should succeed");
+        
+        assert_eq!(decrypted, plaintext);
+    }
+    
+    #[test]
+    fn test_ciphertext_different_from_plaintext() {
+        let salt = SaltString::generate(&mut OsRng);
+        let key = derive_key("test_password", &salt).expect("Key derivation should succeed");
+        let plaintext = b"Secret data";
+        
+        let (ciphertext, _nonce) = encrypt_data(&key, plaintext).expect("Encryption should succeed");
+        
+        // Ciphertext should be different from plaintext
+        assert_ne!(&ciphertext[..], &plaintext[..]);
+        
+        // Ciphertext should be longer (includes authentication tag)
+        assert!(ciphertext.len() > plaintext.len());
+    }
+    
+    #[test]
+    fn test_wrong_key_fails_decryption() {
+        let salt1 = SaltString::generate(&mut OsRng);
+        let salt2 = SaltString::generate(&mut OsRng);
+        let key1 = derive_key("password1", &salt1).expect("Key derivation should succeed");
+        let key2 = derive_key("password2", &salt2).expect("Key derivation should succeed");
+        let plaintext = b"Secret message";
+        
+        let (ciphertext, nonce) = encrypt_data(&key1, plaintext).expect("Encryption should succeed");
+        
+        // Decryption with wrong key should fail
+        let result = decrypt_data(&key2, &ciphertext, &nonce);
+        assert!(result.is_err());
+    }
+    
+    #[test]
+    fn test_wrong_nonce_fails_decryption() {
+        let salt = SaltString::generate(&mut OsRng);
+        let key = derive_key("test_password", &salt).expect("Key derivation should succeed");
+        let plaintext = b"Secret message";
+        
+        let (ciphertext, _nonce) = encrypt_data(&key, plaintext).expect("Encryption should succeed");
+        let wrong_nonce = rand::random::<[u8; 12]>();
+        
+        // Decryption with wrong nonce should fail
+        let result = decrypt_data(&key, &ciphertext, &wrong_nonce);
+        assert!(result.is_err());
+    }
+    
+    #[test]
+    fn test_tampered_ciphertext_fails() {
+        let salt = SaltString::generate(&mut OsRng);
+        let key = derive_key("test_password", &salt).expect("Key derivation should succeed");
+        let plaintext = b"Secret message";
+        
+        let (mut ciphertext, nonce) = encrypt_data(&key, plaintext).expect("Encryption should succeed");
+        
+        // Tamper with ciphertext
+        if !ciphertext.is_empty() {
+            ciphertext[0] ^= 0xFF;
+        }
+        
+        // Decryption of tampered ciphertext should fail
+        let result = decrypt_data(&key, &ciphertext, &nonce);
+        assert!(result.is_err());
+    }
+    
+    #[test]
+    fn test_empty_plaintext() {
+        let salt = SaltString::generate(&mut OsRng);
+        let key = derive_key("test_password", &salt).expect("Key derivation should succeed");
+        let plaintext = b"";
+        
+        let (ciphertext, nonce) = encrypt_data(&key, plaintext).expect("Encryption should succeed");
+        let decrypted = decrypt_data(&key, &ciphertext, &nonce).expect("Decryption should succeed");
+        
+        assert_eq!(decrypted, plaintext);
+    }
+    
+    #[test]
+    fn test_large_plaintext() {
+        let salt = SaltString::generate(&mut OsRng);
+        let key = derive_key("test_password", &salt).expect("Key derivation should succeed");
+        let plaintext: Vec<u8> = (0..10000).map(|i| (i % 256) as u8).collect();
+        
+        let (ciphertext, nonce) = encrypt_data(&key, &plaintext).expect("Encryption should succeed");
+        let decrypted = decrypt_data(&key, &ciphertext, &nonce).expect("Decryption should succeed");
+        
+        assert_eq!(decrypted, plaintext);
+    }
+    
+    #[test]
+    fn test_every_cipher_roundtrips_and_rejects_tampering() {
+        let key = Key::new(GenericArray::clone_from_slice(&[7u8; 32]));
+        let plaintext = b"Hello, World! This is a secret message.";
+
+        for kind in CipherKind::ALL {
+            let cipher = kind.cipher();
+            let (mut ciphertext, nonce) = cipher.encrypt(&key, plaintext).expect("Encryption should succeed");
+            assert_eq!(nonce.len(), cipher.nonce_len(), "{}", kind);
+            assert_eq!(cipher.decrypt(&key, &ciphertext, &nonce).unwrap(), plaintext, "{}", kind);
+
+            // A second encryption picks a fresh nonce
+            let (_, other_nonce) = cipher.encrypt(&key, plaintext).unwrap();
+            assert_ne!(nonce, other_nonce, "{}", kind);
+
+            assert!(cipher.decrypt(&key, &ciphertext, &nonce[1..]).is_err(), "{}", kind);
+            let wrong_key = Key::new(GenericArray::clone_from_slice(&[8u8; 32]));
+            assert!(cipher.decrypt(&wrong_key, &ciphertext, &nonce).is_err(), "{}", kind);
+            ciphertext[0] ^= 0xFF;
+            assert!(cipher.decrypt(&key, &ciphertext, &nonce).is_err(), "{}", kind);
+        }
+
+        // Each cipher only opens its own output
+        let (ciphertext, nonce) = CipherKind::XChaCha20Poly1305.cipher().encrypt(&key, plaintext).unwrap();
+        assert!(CipherKind::Aes256Gcm.cipher().decrypt(&key, &ciphertext, &nonce[..12]).is_err());
+    }
+
+    #[test]
+    fn test_cipher_kind_ids_and_names() {
+        for kind in CipherKind::ALL {
+            assert_eq!(CipherKind::from_id(kind.id()), Some(kind));
+            assert_eq!(kind.to_string().parse::<CipherKind>(), Ok(kind));
+        }
+        assert_eq!(CipherKind::from_id(0), Some(CipherKind::Aes256Gcm));
+        assert_eq!(CipherKind::from_id(9), None);
+        assert_eq!("XChaCha20".parse::<CipherKind>(), Ok(CipherKind::XChaCha20Poly1305));
+        assert!("rot13".parse::<CipherKind>().is_err());
+    }
+
+    #[test]
+    fn test_kdf_params_affect_key() {
+        let salt = SaltString::generate(&mut OsRng);
+        let light = KdfParams { memory_kb: 1024, time_cost: 1, parallelism: 1 };
+        
+        let key1 = derive_key_with_params("password", &salt, &light).expect("Key derivation should succeed");
+        let key2 = derive_key("password", &salt).expect("Key derivation should succeed");
+        
+        // Same password and salt with different costs should produce different keys
+        assert_ne!(key1.as_ref(), key2.as_ref());
+    }
+    
+    #[test]
+    fn test_invalid_kdf_params_rejected() {
+        let salt = SaltString::generate(&mut OsRng);
+        let invalid = KdfParams { memory_kb: 1, time_cost: 0, parallelism: 1 };
+        
+        assert!(derive_key_with_params("password", &salt, &invalid).is_err());
+    }
+    
+    #[test]
+    fn test_calibrate_kdf_small_target() {
+        let calibration = calibrate_kdf(1, 1024, 1).expect("Calibration should succeed");
+        
+        // Memory is never lowered below the starting point when already small
+        assert_eq!(calibration.params.memory_kb, 1024);
+        assert!(calibration.params.time_cost >= 1);
+        assert_eq!(calibration.params.parallelism, 1);
+    }
+    
+    #[test]
+    fn test_crypto_error_display() {
+        let err = CryptoError::KeyDerivation("test error".to_string());
+        assert!(err.to_string().contains("Key derivation"));
+        
+        let err = CryptoError::Encryption("enc error".to_string());
+        assert!(err.to_string().contains("Encryption"));
+        
+        let err = CryptoError::Decryption("dec error".to_string());
+        assert!(err.to_string().contains("Decryption"));
+    }
+    
+    #[test]
+    fn test_master_password_check() {
+        let check = MasterPasswordCheck::new("correct horse battery").unwrap();
+        assert!(check.matches("correct horse battery"));
+        assert!(!check.matches("correct horse battery "));
+        assert!(!check.matches("Correct horse battery"));
+        assert!(!check.matches(""));
+        assert_eq!(format!("{:?}", check), "[REDACTED]");
+        
+        // Salted per check, so two checks of one password don't share a hash
+        let other = MasterPasswordCheck::new("correct horse battery").unwrap();
+        assert_ne!(check.hash.as_ref(), other.hash.as_ref());
+    }
+}
\ No newline at end of file
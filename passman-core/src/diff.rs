@@ -0,0 +1,310 @@
+//! Vault Diff Module
+//!
+//! Field-level comparison of two decrypted vaults, used by `passman diff`
+//! and, with the live vault as A and a backup as B, to warn before a backup
+//! restore rolls back newer data.
+
+use serde::Serialize;
+use crate::model::{Entry, Vault};
+
+/// One field that differs between the two copies of an entry
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    /// Value in vault A (`None` when unset, or redacted for secrets)
+    pub a: Option<String>,
+    /// Value in vault B (`None` when unset, or redacted for secrets)
+    pub b: Option<String>,
+    /// Password-like field; values are hidden unless explicitly requested
+    pub secret: bool,
+}
+
+/// An entry present in both vaults with differing fields
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryDiff {
+    pub id: String,
+    pub a_modified_at: chrono::DateTime<chrono::Utc>,
+    pub b_modified_at: chrono::DateTime<chrono::Utc>,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Result of comparing vault A against vault B
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VaultDiff {
+    pub only_in_a: Vec<String>,
+    pub only_in_b: Vec<String>,
+    pub modified: Vec<EntryDiff>,
+    pub unchanged: usize,
+}
+
+impl VaultDiff {
+    /// Compare every entry of `a` against `b`. Ids are sorted.
+    pub fn compare(a: &Vault, b: &Vault) -> Self {
+        let mut diff = VaultDiff::default();
+
+        for (id, entry_a) in a.iter() {
+            match b.get_entry(id) {
+                None => diff.only_in_a.push(id.to_string()),
+                Some(entry_b) => {
+                    let changes = compare_entries(entry_a, entry_b);
+                    if changes.is_empty() {
+                        diff.unchanged += 1;
+                    } else {
+                        diff.modified.push(EntryDiff {
+                            id: id.to_string(),
+                            a_modified_at: entry_a.modified_at,
+                            b_modified_at: entry_b.modified_at,
+                            changes,
+                        });
+                    }
+                }
+            }
+        }
+        diff.only_in_b = b.iter()
+            .filter(|(id, _)| !a.contains(id))
+            .map(|(id, _)| id.to_string())
+            .collect();
+
+        diff.only_in_a.sort();
+        diff.only_in_b.sort();
+        diff.modified.sort_by(|x, y| x.id.cmp(&y.id));
+        diff
+    }
+
+    /// Whether the vaults hold identical entries
+    pub fn is_empty(&self) -> bool {
+        self.only_in_a.is_empty() && self.only_in_b.is_empty() && self.modified.is_empty()
+    }
+
+    /// Entries whose copy in A was modified after the copy in B. Restoring
+    /// backup B over live vault A reverts these.
+    pub fn newer_in_a(&self) -> impl Iterator<Item = &EntryDiff> {
+        self.modified.iter().filter(|entry| entry.a_modified_at > entry.b_modified_at)
+    }
+
+    /// Entries whose copy in B is the more recently modified one
+    pub fn newer_in_b(&self) -> impl Iterator<Item = &EntryDiff> {
+        self.modified.iter().filter(|entry| entry.a_modified_at <= entry.b_modified_at)
+    }
+
+    /// What restoring backup B over live vault A would lose, e.g. "Restoring
+    /// will remove 3 entries added after the backup and revert 2 modified
+    /// entries". `None` when nothing newer would be lost.
+    pub fn restore_warning(&self) -> Option<String> {
+        let entries = |n: usize| if n == 1 { "entry" } else { "entries" };
+        let mut losses = Vec::new();
+        if !self.only_in_a.is_empty() {
+            let n = self.only_in_a.len();
+            losses.push(format!("remove {} {} added after the backup", n, entries(n)));
+        }
+        let reverted = self.newer_in_a().count();
+        if reverted > 0 {
+            losses.push(format!("revert {} modified {}", reverted, entries(reverted)));
+        }
+        (!losses.is_empty()).then(|| format!("Restoring will {}", losses.join(" and ")))
+    }
+
+    /// Entries [`merge_newer`] of B into A replaces with B's copy: those
+    /// modified later in B (on equal timestamps A's copy is kept)
+    pub fn merge_updates(&self) -> impl Iterator<Item = &EntryDiff> {
+        self.modified.iter().filter(|entry| entry.b_modified_at > entry.a_modified_at)
+    }
+
+    /// What [`merge_newer`] of B into A would do, e.g. "Merging will add 1
+    /// entry and take 2 newer entries from the other file". `None` when
+    /// B has nothing A doesn't.
+    pub fn merge_summary(&self) -> Option<String> {
+        let entries = |n: usize| if n == 1 { "entry" } else { "entries" };
+        let added = self.only_in_b.len();
+        let updated = self.merge_updates().count();
+        if added == 0 && updated == 0 {
+            return None;
+        }
+        let mut parts = Vec::new();
+        if added > 0 {
+            parts.push(format!("add {} {}", added, entries(added)));
+        }
+        if updated > 0 {
+            parts.push(format!("take {} newer {} from the other file", updated, entries(updated)));
+        }
+        let kept = self.modified.len() - updated;
+        if kept > 0 {
+            parts.push(format!("keep {} {} changed more recently here", kept, entries(kept)));
+        }
+        let last = parts.pop().unwrap_or_default();
+        Some(match parts.is_empty() {
+            true => format!("Merging will {}", last),
+            false => format!("Merging will {} and {}", parts.join(", "), last),
+        })
+    }
+
+    /// Drop secret values so the diff can be printed or serialized safely
+    pub fn redact_secrets(&mut self) {
+        for change in self.modified.iter_mut().flat_map(|entry| entry.changes.iter_mut()) {
+            if change.secret {
+                change.a = None;
+                change.b = None;
+            }
+        }
+    }
+}
+
+/// Merge `b` into `a`, keeping the more recently modified copy of each
+/// entry. Entries found in only one vault are kept, so entries deleted from
+/// `a` after `b` was taken come back; on equal timestamps `a` wins.
+pub fn merge_newer(a: &mut Vault, b: &Vault) {
+    for (id, entry_b) in b.iter() {
+        let keep_b = a.get_entry(id).is_none_or(|entry_a| entry_b.modified_at > entry_a.modified_at);
+        if keep_b {
+            a.add_entry(id.to_string(), entry_b.clone());
+        }
+    }
+}
+
+/// The fields compared between two copies of an entry, as (name, value,
+/// secret); `None` when unset
+pub(crate) fn entry_fields(entry: &Entry) -> [(&'static str, Option<String>, bool); 8] {
+    [
+        ("username", Some(entry.username.clone()), false),
+        ("password", Some(entry.password_str().reveal().to_string()), true),
+        ("note", entry.note.clone(), false),
+        ("url", entry.url.clone(), false),
+        ("tags", (!entry.tags.is_empty()).then(|| entry.tags.join(", ")), false),
+        ("expires", entry.expires_at.map(|at| at.format("%Y-%m-%d").to_string()), false),
+        ("totp_secret", entry.totp_secret_str().map(str::to_string), true),
+        ("links", (!entry.linked_ids.is_empty()).then(|| entry.linked_ids.join(", ")), false),
+    ]
+}
+
+fn compare_entries(a: &Entry, b: &Entry) -> Vec<FieldChange> {
+    entry_fields(a)
+        .into_iter()
+        .zip(entry_fields(b))
+        .filter(|((_, a, _), (_, b, _))| a != b)
+        .map(|((field, a, secret), (_, b, _))| FieldChange { field, a, b, secret })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_reports_added_removed_and_modified() {
+        let mut a = Vault::new();
+        a.add_entry("github".to_string(), Entry::new("octo".to_string(), "old".to_string(), None));
+        a.add_entry("bank".to_string(), Entry::new("alice".to_string(), "pw".to_string(), None));
+        a.add_entry("mail".to_string(), Entry::new("me".to_string(), "pw".to_string(), None));
+
+        let mut b = Vault::new();
+        let mut github = Entry::new("octo".to_string(), "new".to_string(), Some("rotated".to_string()));
+        github.tags.push("work".to_string());
+        b.add_entry("github".to_string(), github);
+        b.add_entry("bank".to_string(), a.get_entry("bank").cloned().unwrap());
+        b.add_entry("forum".to_string(), Entry::new("bob".to_string(), "pw".to_string(), None));
+
+        let mut diff = VaultDiff::compare(&a, &b);
+        assert_eq!(diff.only_in_a, vec!["mail"]);
+        assert_eq!(diff.only_in_b, vec!["forum"]);
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.modified.len(), 1);
+
+        let fields: Vec<_> = diff.modified[0].changes.iter().map(|c| c.field).collect();
+        assert_eq!(fields, vec!["password", "note", "tags"]);
+
+        diff.redact_secrets();
+        let password = &diff.modified[0].changes[0];
+        assert!(password.secret && password.a.is_none() && password.b.is_none());
+        assert_eq!(diff.modified[0].changes[1].b.as_deref(), Some("rotated"));
+    }
+
+    /// Live vault and a backup taken at `backup_at`, covering each way an
+    /// entry can differ: added after the backup, deleted after it, changed
+    /// in the live vault, newer in the backup, and untouched
+    fn live_and_backup() -> (Vault, Vault) {
+        let backup_at = chrono::Utc::now() - chrono::Duration::days(7);
+        let entry = |username: &str, password: &str, modified_at| {
+            let mut entry = Entry::new(username.to_string(), password.to_string(), None);
+            entry.modified_at = modified_at;
+            entry
+        };
+
+        let mut backup = Vault::new();
+        backup.add_entry("deleted".to_string(), entry("gone", "pw", backup_at));
+        backup.add_entry("rotated".to_string(), entry("me", "old", backup_at));
+        backup.add_entry("stale".to_string(), entry("me", "backup-copy", backup_at + chrono::Duration::days(1)));
+        backup.add_entry("same".to_string(), entry("me", "pw", backup_at));
+
+        let mut live = Vault::new();
+        live.add_entry("added".to_string(), entry("new", "pw", backup_at + chrono::Duration::days(2)));
+        live.add_entry("rotated".to_string(), entry("me", "new", backup_at + chrono::Duration::days(3)));
+        live.add_entry("stale".to_string(), entry("me", "live-copy", backup_at));
+        live.add_entry("same".to_string(), backup.get_entry("same").cloned().unwrap());
+        (live, backup)
+    }
+
+    #[test]
+    fn test_restore_warning_counts_only_newer_live_data() {
+        let (live, backup) = live_and_backup();
+        let diff = VaultDiff::compare(&live, &backup);
+
+        assert_eq!(diff.only_in_a, vec!["added"]);
+        assert_eq!(diff.only_in_b, vec!["deleted"]);
+        assert_eq!(diff.newer_in_a().map(|entry| entry.id.as_str()).collect::<Vec<_>>(), vec!["rotated"]);
+        assert_eq!(diff.newer_in_b().map(|entry| entry.id.as_str()).collect::<Vec<_>>(), vec!["stale"]);
+        assert_eq!(
+            diff.restore_warning().as_deref(),
+            Some("Restoring will remove 1 entry added after the backup and revert 1 modified entry")
+        );
+
+        // Restoring a backup that is newer everywhere loses nothing
+        let reverse = VaultDiff::compare(&backup, &backup);
+        assert_eq!(reverse.restore_warning(), None);
+    }
+
+    #[test]
+    fn test_merge_keeps_newer_copy_of_each_entry() {
+        let (mut live, backup) = live_and_backup();
+        merge_newer(&mut live, &backup);
+
+        let password = |id: &str| live.get_entry(id).map(|entry| entry.password_str().reveal().to_string());
+        assert_eq!(password("added").as_deref(), Some("pw"));
+        assert_eq!(password("deleted").as_deref(), Some("pw"));
+        assert_eq!(password("rotated").as_deref(), Some("new"));
+        assert_eq!(password("stale").as_deref(), Some("backup-copy"));
+        assert_eq!(live.len(), 5);
+
+        // Merging again changes nothing
+        let merged = VaultDiff::compare(&live, &backup);
+        assert!(merged.newer_in_b().next().is_none());
+        assert_eq!(merged.merge_summary(), None);
+    }
+
+    #[test]
+    fn test_merge_summary_matches_what_merge_does() {
+        let (live, backup) = live_and_backup();
+        let diff = VaultDiff::compare(&live, &backup);
+        assert_eq!(diff.merge_updates().map(|entry| entry.id.as_str()).collect::<Vec<_>>(), vec!["stale"]);
+        assert_eq!(
+            diff.merge_summary().as_deref(),
+            Some("Merging will add 1 entry, take 1 newer entry from the other file and keep 1 entry changed more recently here")
+        );
+        assert_eq!(
+            VaultDiff::compare(&Vault::new(), &live).merge_summary().as_deref(),
+            Some("Merging will add 4 entries")
+        );
+    }
+
+    #[test]
+    fn test_identical_vaults_have_empty_diff() {
+        let mut a = Vault::new();
+        a.add_entry("x".to_string(), Entry::new("u".to_string(), "p".to_string(), None));
+        let mut b = Vault::new();
+        for (id, entry) in a.iter() {
+            b.add_entry(id.to_string(), entry.clone());
+        }
+        let diff = VaultDiff::compare(&a, &b);
+        assert!(diff.is_empty());
+        assert_eq!(diff.unchanged, 1);
+    }
+}
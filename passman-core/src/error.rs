@@ -25,6 +25,8 @@ pub enum PassmanError {
     Transfer(TransferError),
     /// Configuration errors
     Config(ConfigError),
+    /// Network errors
+    Network(NetworkError),
     /// IO errors
     Io(io::Error),
     /// Other errors
@@ -52,6 +54,10 @@ pub enum VaultError {
     EntryExists(String),
     /// Invalid vault format
     InvalidFormat(String),
+    /// The file was re-keyed since the key at hand was derived
+    Rekeyed,
+    /// Other users can read the file and `security.strict_permissions` is on
+    LoosePermissions(String),
 }
 
 /// Cryptographic errors
@@ -123,6 +129,19 @@ pub enum TransferError {
     InvalidData(String),
 }
 
+/// Network errors
+#[derive(Debug, Clone)]
+pub enum NetworkError {
+    /// Offline mode is on; the named feature did not try to connect
+    Offline(String),
+    /// Proxy URL could not be used
+    InvalidProxy(String),
+    /// Proxy refused to open a tunnel
+    ProxyRefused(String),
+    /// Connection failed or timed out after all retries
+    ConnectFailed(String),
+}
+
 /// Configuration errors
 #[derive(Debug, Clone)]
 pub enum ConfigError {
@@ -144,6 +163,7 @@ impl fmt::Display for PassmanError {
             PassmanError::Clipboard(e) => write!(f, "{}", e),
             PassmanError::Transfer(e) => write!(f, "{}", e),
             PassmanError::Config(e) => write!(f, "{}", e),
+            PassmanError::Network(e) => write!(f, "{}", e),
             PassmanError::Io(e) => write!(f, "IO error: {}", e),
             PassmanError::Other(msg) => write!(f, "{}", msg),
         }
@@ -156,16 +176,20 @@ impl fmt::Display for VaultError {
             VaultError::NotFound(path) => write!(f, "Vault not found: '{}'. Run 'passman init' to create one.", path),
             VaultError::AlreadyExists(path) => write!(f, "Vault '{}' already exists. Remove it first or choose a different name.", path),
             VaultError::Corrupted(msg) => write!(f, "Vault file is corrupted: {}", msg),
-            VaultError::IntegrityFailed => write!(f, "Vault integrity check failed. The file may have been tampered with."),
+            VaultError::IntegrityFailed => write!(f, "Vault integrity check failed. Wrong password or tampered file."),
             VaultError::ReadError(msg) => write!(f, "Failed to read vault: {}", msg),
             VaultError::WriteError(msg) => write!(f, "Failed to write vault: {}", msg),
             VaultError::EntryNotFound(id) => write!(f, "Entry '{}' not found.", id),
             VaultError::EntryExists(id) => write!(f, "Entry '{}' already exists. Use 'edit' to modify it.", id),
             VaultError::InvalidFormat(msg) => write!(f, "Invalid vault format: {}", msg),
+            VaultError::Rekeyed => write!(f, "Vault was re-keyed since it was unlocked; unlock it again with its master password"),
+            VaultError::LoosePermissions(msg) => write!(f, "Refusing to open the vault: {}", msg),
         }
     }
 }
 
+impl std::error::Error for VaultError {}
+
 impl fmt::Display for CryptoError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -231,6 +255,17 @@ impl fmt::Display for TransferError {
     }
 }
 
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkError::Offline(feature) => write!(f, "{} is unavailable in offline mode (--offline or network.offline).", feature),
+            NetworkError::InvalidProxy(msg) => write!(f, "Invalid proxy setting: {}", msg),
+            NetworkError::ProxyRefused(msg) => write!(f, "Proxy refused the connection: {}", msg),
+            NetworkError::ConnectFailed(msg) => write!(f, "Network request failed: {}", msg),
+        }
+    }
+}
+
 impl fmt::Display for ConfigError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -286,6 +321,12 @@ impl From<TransferError> for PassmanError {
     }
 }
 
+impl From<NetworkError> for PassmanError {
+    fn from(err: NetworkError) -> Self {
+        PassmanError::Network(err)
+    }
+}
+
 impl From<ConfigError> for PassmanError {
     fn from(err: ConfigError) -> Self {
         PassmanError::Config(err)
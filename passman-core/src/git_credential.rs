@@ -0,0 +1,211 @@
+//! Git Credential Helper Module
+//!
+//! The protocol side of `passman git-credential get|store|erase`: parsing
+//! the `key=value` lines git writes to a helper, finding the entry for a
+//! host and formatting the reply. Unlocking and saving the vault is left
+//! to the frontend.
+
+use zeroize::Zeroizing;
+use crate::model::{Entry, Vault};
+
+/// Entries with this tag are preferred when several match a host, and
+/// entries created by `store` carry it
+pub const GIT_TAG: &str = "git";
+
+/// What git asks for or hands over: one credential for a host
+#[derive(Debug, Clone, Default)]
+pub struct CredentialRequest {
+    pub protocol: Option<String>,
+    /// Lowercased host, with the port if git sent one
+    pub host: String,
+    pub path: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<Zeroizing<String>>,
+}
+
+impl CredentialRequest {
+    /// Parse git's `key=value` lines, up to a blank line or the end of the
+    /// input. Keys this helper has no use for (`capability[]`, `wwwauth[]`…)
+    /// are ignored.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut request = Self::default();
+        for line in input.lines() {
+            if line.is_empty() {
+                break;
+            }
+            // Not echoed: the line could hold a password
+            let (key, value) = line.split_once('=').ok_or("Malformed credential line (expected key=value)")?;
+            match key {
+                "protocol" => request.protocol = Some(value.to_string()),
+                "host" => request.host = value.trim_end_matches('.').to_lowercase(),
+                "path" => request.path = Some(value.to_string()),
+                "username" => request.username = Some(value.to_string()),
+                "password" => request.password = Some(Zeroizing::new(value.to_string())),
+                "url" => request.apply_url(value),
+                _ => {}
+            }
+        }
+        if request.host.is_empty() {
+            return Err("Credential request has no host".to_string());
+        }
+        Ok(request)
+    }
+
+    /// Fill in the fields a `url=` line carries; git sends it instead of
+    /// (or as well as) the separate ones
+    fn apply_url(&mut self, url: &str) {
+        if let Some((protocol, _)) = url.split_once("://") {
+            self.protocol = Some(protocol.to_string());
+        }
+        if let Some((username, _)) = authority(url).split_once('@') {
+            self.username = Some(username.to_string());
+        }
+        if let Some(host) = url_host(url) {
+            self.host = host;
+        }
+        let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+        if let Some((_, path)) = rest.split_once('/') {
+            if !path.is_empty() {
+                self.path = Some(path.to_string());
+            }
+        }
+    }
+
+    /// URL stored on entries created for this host
+    pub fn url(&self) -> String {
+        format!("{}://{}", self.protocol.as_deref().unwrap_or("https"), self.host)
+    }
+
+    /// Whether `entry` holds a credential for this host (and username, if
+    /// git named one). Trashed and archived entries never match, nor do
+    /// high-security ones, since git can't be asked for a PIN.
+    pub fn matches(&self, entry: &Entry) -> bool {
+        if entry.is_trashed() || entry.archived || entry.is_sealed() || self.username.as_ref().is_some_and(|username| *username != entry.username) {
+            return false;
+        }
+        let Some(entry_host) = entry.url.as_deref().and_then(url_host) else {
+            return false;
+        };
+        // An entry without a port covers the host on any port
+        entry_host == self.host || (!entry_host.contains(':') && entry_host == strip_port(&self.host))
+    }
+
+    /// The entry to answer with: entries tagged `git` first, then the most
+    /// recently modified
+    pub fn find<'a>(&self, vault: &'a Vault) -> Option<(&'a str, &'a Entry)> {
+        vault.iter()
+            .filter(|(_, entry)| self.matches(entry))
+            .min_by(|(a_id, a), (b_id, b)| {
+                b.has_tag(GIT_TAG).cmp(&a.has_tag(GIT_TAG))
+                    .then(b.modified_at.cmp(&a.modified_at))
+                    .then(a_id.cmp(b_id))
+            })
+    }
+
+    /// Free id for a new entry: the host, then "user@host", then numbered
+    pub fn new_entry_id(&self, vault: &Vault) -> String {
+        let base = match &self.username {
+            Some(username) if vault.contains(&self.host) => format!("{}@{}", username, self.host),
+            _ => self.host.clone(),
+        };
+        std::iter::once(base.clone())
+            .chain((2..).map(|n| format!("{} ({})", base, n)))
+            .find(|candidate| !vault.contains(candidate))
+            .unwrap_or_default()
+    }
+}
+
+/// Reply to `get`. Refuses values git would misread as extra lines.
+pub fn reply(entry: &Entry) -> Result<Zeroizing<String>, String> {
+    let password = entry.password_str().reveal();
+    if [entry.username.as_str(), password].iter().any(|value| value.contains(['\n', '\0'])) {
+        return Err("Entry contains a line break, which git credentials can't hold".to_string());
+    }
+    Ok(Zeroizing::new(format!("username={}\npassword={}\n", entry.username, password)))
+}
+
+/// `host[:port]` of an entry URL, lowercased. Bare hosts
+/// ("github.com/login") and user info ("git@github.com") are accepted.
+pub fn url_host(url: &str) -> Option<String> {
+    let authority = authority(url.trim());
+    let host = authority.rsplit('@').next().unwrap_or(authority).trim_end_matches('.');
+    (!host.is_empty()).then(|| host.to_lowercase())
+}
+
+/// The part of a URL between the scheme and the path
+fn authority(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    rest.split(['/', '?', '#']).next().unwrap_or_default()
+}
+
+fn strip_port(host: &str) -> &str {
+    host.rsplit_once(':')
+        .filter(|(_, port)| port.chars().all(|c| c.is_ascii_digit()))
+        .map_or(host, |(host, _)| host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(username: &str, url: &str, tags: &[&str]) -> Entry {
+        let mut entry = Entry::new(username.to_string(), format!("{}-token", username), None);
+        entry.url = Some(url.to_string());
+        entry.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        entry
+    }
+
+    #[test]
+    fn test_parse_fields_and_url() {
+        let request = CredentialRequest::parse("protocol=https\nhost=GitHub.com\nusername=ann\ncapability[]=authtype\n\nignored=after blank").unwrap();
+        assert_eq!(request.protocol.as_deref(), Some("https"));
+        assert_eq!(request.host, "github.com");
+        assert_eq!(request.username.as_deref(), Some("ann"));
+        assert_eq!(request.url(), "https://github.com");
+
+        let request = CredentialRequest::parse("url=https://bob@gitlab.example.com:8443/team/repo.git\n").unwrap();
+        assert_eq!(request.host, "gitlab.example.com:8443");
+        assert_eq!(request.username.as_deref(), Some("bob"));
+        assert_eq!(request.path.as_deref(), Some("team/repo.git"));
+
+        assert!(CredentialRequest::parse("protocol=https\n").is_err());
+        assert!(CredentialRequest::parse("host\n").is_err());
+    }
+
+    #[test]
+    fn test_find_prefers_git_tag_and_respects_username() {
+        let mut vault = Vault::new();
+        vault.add_entry("github web".to_string(), entry("ann", "https://github.com/login", &[]));
+        vault.add_entry("github token".to_string(), entry("ann", "github.com", &[GIT_TAG]));
+        vault.add_entry("github bot".to_string(), entry("bot", "https://github.com", &[]));
+        vault.add_entry("gitlab".to_string(), entry("ann", "https://gitlab.com", &[GIT_TAG]));
+
+        let request = CredentialRequest::parse("protocol=https\nhost=github.com\n").unwrap();
+        assert_eq!(request.find(&vault).map(|(id, _)| id), Some("github token"));
+
+        let request = CredentialRequest::parse("protocol=https\nhost=github.com\nusername=bot\n").unwrap();
+        assert_eq!(request.find(&vault).map(|(id, _)| id), Some("github bot"));
+
+        let request = CredentialRequest::parse("protocol=https\nhost=github.com:443\n").unwrap();
+        assert_eq!(request.find(&vault).map(|(id, _)| id), Some("github token"));
+
+        assert!(vault.trash_entry("github token", chrono::Utc::now()));
+        let request = CredentialRequest::parse("protocol=https\nhost=github.com\nusername=ann\n").unwrap();
+        assert_eq!(request.find(&vault).map(|(id, _)| id), Some("github web"));
+        assert!(CredentialRequest::parse("host=example.com\n").unwrap().find(&vault).is_none());
+    }
+
+    #[test]
+    fn test_new_entry_ids_and_reply() {
+        let mut vault = Vault::new();
+        let request = CredentialRequest::parse("protocol=https\nhost=github.com\nusername=ann\n").unwrap();
+        assert_eq!(request.new_entry_id(&vault), "github.com");
+        vault.add_entry("github.com".to_string(), entry("bot", "https://github.com", &[]));
+        assert_eq!(request.new_entry_id(&vault), "ann@github.com");
+        vault.add_entry("ann@github.com".to_string(), entry("ann", "https://github.com", &[]));
+        assert_eq!(request.new_entry_id(&vault), "ann@github.com (2)");
+
+        assert_eq!(reply(&entry("ann", "github.com", &[])).unwrap().as_str(), "username=ann\npassword=ann-token\n");
+        assert!(reply(&entry("ann\nhost=evil.com", "github.com", &[])).is_err());
+    }
+}
@@ -0,0 +1,836 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use chrono::{DateTime, Utc, Duration};
+use sha1::{Digest, Sha1};
+use crate::config::get_config;
+use crate::model::{Entry, Vault};
+use crate::utils::{analyze_password_strength, PasswordStrength, StrengthSuggestion};
+use serde::{Serialize, Deserialize};
+
+/// Maximum number of health records kept in a vault's history
+pub const HEALTH_HISTORY_LIMIT: usize = 90;
+
+/// Entries expiring within this many days are flagged as a warning
+pub const EXPIRY_WARNING_DAYS: i64 = 14;
+
+/// Password health status for an entry
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PasswordHealth {
+    Excellent,
+    Good,
+    Warning { issues: Vec<String> },
+    Critical { issues: Vec<String> },
+}
+
+/// Password health analysis result
+#[derive(Debug, Clone, Serialize)]
+#[allow(dead_code)]
+pub struct HealthReport {
+    pub entry_id: String,
+    pub health: PasswordHealth,
+    pub last_changed: DateTime<Utc>,
+    pub age_days: i64,
+    pub strength: PasswordStrength,
+    pub recommendations: Vec<String>,
+    /// How to make a weak or fair password stronger; empty for stronger
+    /// ones and for high-security entries, whose password isn't at hand
+    pub suggestions: Vec<StrengthSuggestion>,
+    /// Other analyzed entries sharing this password
+    pub reused_with: usize,
+    /// Known breaches the password appears in
+    pub breach_count: usize,
+}
+
+/// Which entries a health analysis covers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AnalysisScope {
+    /// Everything except the trash, so deleting a weak entry improves the score
+    #[default]
+    ActiveOnly,
+    IncludeTrash,
+}
+
+/// Issue reported for a password on the banned list
+pub const BANNED_ISSUE: &str = "Password matches banned list";
+
+/// Passwords an organization forbids (`health.banned_password_file`): one
+/// per line, blank lines and `#` comments skipped. A line of 40 hex digits,
+/// optionally followed by `:<count>` as in breach corpus dumps, is a SHA-1
+/// hash of a password; any other line is the password itself, matched
+/// ignoring case.
+#[derive(Debug, Default)]
+pub struct BannedList {
+    lowercase: HashSet<String>,
+    sha1: HashSet<[u8; 20]>,
+}
+
+impl BannedList {
+    pub fn parse(text: &str) -> Self {
+        let mut list = Self::default();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match parse_sha1(line) {
+                Some(hash) => {
+                    list.sha1.insert(hash);
+                }
+                None => {
+                    list.lowercase.insert(line.to_lowercase());
+                }
+            }
+        }
+        list
+    }
+
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+        Ok(Self::parse(&String::from_utf8_lossy(&bytes)))
+    }
+
+    /// Number of passwords and hashes on the list
+    pub fn len(&self) -> usize {
+        self.lowercase.len() + self.sha1.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether `password` is on the list, in any case, or hashes to a
+    /// listed SHA-1
+    pub fn contains(&self, password: &str) -> bool {
+        if self.lowercase.contains(&password.to_lowercase()) {
+            return true;
+        }
+        !self.sha1.is_empty() && self.sha1.contains(&<[u8; 20]>::from(Sha1::digest(password.as_bytes())))
+    }
+}
+
+/// `5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8` or `...:3861493`
+fn parse_sha1(line: &str) -> Option<[u8; 20]> {
+    let hex = line.split_once(':').map_or(line, |(hex, count)| {
+        if count.trim().chars().all(|c| c.is_ascii_digit()) { hex } else { line }
+    });
+    if hex.len() != 40 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut hash = [0u8; 20];
+    for (byte, pair) in hash.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(hash)
+}
+
+/// Password health analyzer
+pub struct PasswordHealthAnalyzer {
+    breach_database: HashMap<String, DateTime<Utc>>, // Simulated breach database
+    /// `health.banned_password_file`, read the first time a password is checked
+    banned_file: Option<PathBuf>,
+    banned: OnceLock<Option<BannedList>>,
+}
+
+impl PasswordHealthAnalyzer {
+    pub fn new() -> Self {
+        let banned_file = get_config().health.banned_password_file.trim().to_string();
+        Self {
+            breach_database: Self::create_mock_breach_database(),
+            banned_file: (!banned_file.is_empty()).then(|| PathBuf::from(banned_file)),
+            banned: OnceLock::new(),
+        }
+    }
+
+    /// Check passwords against `list` instead of `health.banned_password_file`
+    pub fn with_banned_list(mut self, list: BannedList) -> Self {
+        self.banned_file = None;
+        self.banned = OnceLock::from(Some(list));
+        self
+    }
+
+    /// The banned list, loaded once. An unreadable file is logged and
+    /// treated as no list, so analysis still runs.
+    pub fn banned_list(&self) -> Option<&BannedList> {
+        self.banned
+            .get_or_init(|| {
+                let path = self.banned_file.as_ref()?;
+                match BannedList::load(path) {
+                    Ok(list) => {
+                        log::info!("Loaded {} banned passwords from {}", list.len(), path.display());
+                        Some(list)
+                    }
+                    Err(e) => {
+                        log::warn!("Could not read banned password file {}: {}", path.display(), e);
+                        None
+                    }
+                }
+            })
+            .as_ref()
+    }
+
+    /// Whether `password` is on the banned list
+    pub fn is_banned(&self, password: &str) -> bool {
+        self.banned_list().is_some_and(|list| list.contains(password))
+    }
+
+    /// Create a mock breach database for demonstration
+    fn create_mock_breach_database() -> HashMap<String, DateTime<Utc>> {
+        let mut db = HashMap::new();
+        
+        // Common breached passwords
+        let common_passwords = vec![
+            "password123",
+            "admin",
+            "123456",
+            "password",
+            "qwerty",
+            "letmein",
+            "welcome",
+            "monkey",
+        ];
+
+        let breach_date = Utc::now() - Duration::days(30);
+        for password in common_passwords {
+            db.insert(password.to_string(), breach_date);
+        }
+
+        db
+    }
+
+    /// Analyze the health of the passwords in a vault that fall in
+    /// `scope`. Archived entries are never analyzed.
+    pub fn analyze_vault(&self, vault: &Vault, scope: AnalysisScope) -> Vec<HealthReport> {
+        let analyzed: Vec<(&str, &Entry)> = vault.iter()
+            .filter(|(_, entry)| !entry.archived && (scope == AnalysisScope::IncludeTrash || !entry.is_trashed()))
+            .collect();
+
+        // High-security passwords are sealed, so they can't be compared
+        let mut uses: HashMap<&str, usize> = HashMap::new();
+        for (_, entry) in analyzed.iter().filter(|(_, entry)| !entry.is_sealed()) {
+            *uses.entry(entry.password_str().reveal()).or_default() += 1;
+        }
+
+        let mut reports: Vec<HealthReport> = analyzed.iter()
+            .map(|(id, entry)| {
+                let reused_with = if entry.is_sealed() { 0 } else { uses[entry.password_str().reveal()] - 1 };
+                self.analyze(id, entry, reused_with, link_issues(vault, entry))
+            })
+            .collect();
+
+        // Sort by health status (worst first)
+        reports.sort_by_key(|a| self.health_priority(&a.health));
+
+        reports
+    }
+
+    /// Analyze the health of a single password entry. Reuse is only known
+    /// when analyzing a whole vault.
+    pub fn analyze_entry(&self, id: &str, entry: &Entry) -> HealthReport {
+        self.analyze(id, entry, 0, Vec::new())
+    }
+
+    /// High-security entries are judged by the strength recorded when they
+    /// were sealed; breach and pattern checks need the password itself.
+    /// Problems with linked entries are never more than a warning here: the
+    /// linked entry's own report carries the severity.
+    fn analyze(&self, id: &str, entry: &Entry, reused_with: usize, links: Vec<(String, &str)>) -> HealthReport {
+        let strength = entry.password_strength();
+        let password = (!entry.is_sealed()).then(|| entry.password_str().reveal());
+        let now = Utc::now();
+        let age_days = (now - entry.created_at).num_days();
+        
+        let mut issues = Vec::new();
+        let mut recommendations = Vec::new();
+
+        // Anyone who can read this entry can open the whole vault
+        if entry.holds_master_password {
+            issues.push("Password is the vault's master password".to_string());
+            recommendations.push("Never store the master password in the vault; change this entry's password".to_string());
+        }
+
+        // Check for breached passwords
+        let breach_count = password.map_or(0, |password| self.breach_count(password));
+        if breach_count > 0 {
+            issues.push("Password found in data breach".to_string());
+            recommendations.push("Change password immediately".to_string());
+        }
+
+        // Check against the organization's banned list
+        if password.is_some_and(|password| self.is_banned(password)) {
+            issues.push(BANNED_ISSUE.to_string());
+            recommendations.push("Choose a password that isn't on the banned list".to_string());
+        }
+
+        // Check for passwords shared with other entries
+        if reused_with > 0 {
+            issues.push(format!("Password reused by {} other entr{}", reused_with, if reused_with == 1 { "y" } else { "ies" }));
+            recommendations.push("Use a unique password for every site".to_string());
+        }
+
+        // Check the credential's own expiry date
+        match entry.days_until_expiry(now) {
+            Some(left) if left < 0 => {
+                issues.push(format!("Credential expired {} day(s) ago", -left));
+                recommendations.push("Renew or remove the expired credential".to_string());
+            }
+            Some(left) if left <= EXPIRY_WARNING_DAYS => {
+                issues.push(format!("Credential expires in {} day(s)", left));
+                recommendations.push("Renew the credential before it expires".to_string());
+            }
+            _ => {}
+        }
+
+        // Check password age
+        if age_days > 365 {
+            issues.push(format!("Password is {} days old", age_days));
+            recommendations.push("Consider changing old passwords".to_string());
+        } else if age_days > 180 {
+            recommendations.push("Password is getting old, consider changing".to_string());
+        }
+
+        // Check password strength
+        let suggestions = match (&strength, password) {
+            (PasswordStrength::VeryWeak | PasswordStrength::Weak | PasswordStrength::Fair, Some(password)) => {
+                analyze_password_strength(password).1
+            }
+            _ => Vec::new(),
+        };
+        match strength {
+            PasswordStrength::VeryWeak | PasswordStrength::Weak => {
+                issues.push("Weak password".to_string());
+                recommendations.push("Use a stronger password".to_string());
+            }
+            PasswordStrength::Fair => {
+                recommendations.push("Password could be stronger".to_string());
+            }
+            _ => {}
+        }
+
+        // Check for common patterns
+        if password.is_some_and(|password| self.has_common_patterns(password)) {
+            issues.push("Password uses common patterns".to_string());
+            recommendations.push("Avoid predictable patterns".to_string());
+        }
+
+        let critical = issues.iter().any(|i| i.contains("breach") || i.contains("Weak") || i.contains("expired") || i.contains("master password") || i == BANNED_ISSUE);
+        for (issue, recommendation) in links {
+            issues.push(issue);
+            recommendations.push(recommendation.to_string());
+        }
+
+        // Determine overall health
+        let health = if critical {
+            PasswordHealth::Critical { issues: issues.clone() }
+        } else if !issues.is_empty() {
+            PasswordHealth::Warning { issues: issues.clone() }
+        } else if !recommendations.is_empty() {
+            PasswordHealth::Good
+        } else {
+            PasswordHealth::Excellent
+        };
+
+        HealthReport {
+            entry_id: id.to_string(),
+            health,
+            last_changed: entry.modified_at,
+            age_days,
+            strength,
+            recommendations,
+            suggestions,
+            reused_with,
+            breach_count,
+        }
+    }
+
+    /// Number of known breaches containing the password
+    fn breach_count(&self, password: &str) -> usize {
+        usize::from(self.breach_database.contains_key(password))
+    }
+
+    /// Check for common password patterns
+    fn has_common_patterns(&self, password: &str) -> bool {
+        let password_lower = password.to_lowercase();
+        
+        // Check for common patterns
+        let patterns = [
+            "123", "abc", "qwe", "asd", "zxc",
+            "password", "admin", "user", "test",
+        ];
+
+        patterns.iter().any(|pattern| password_lower.contains(pattern))
+    }
+
+    /// Get priority for sorting (lower number = higher priority)
+    fn health_priority(&self, health: &PasswordHealth) -> u8 {
+        match health {
+            PasswordHealth::Critical { .. } => 0,
+            PasswordHealth::Warning { .. } => 1,
+            PasswordHealth::Good => 2,
+            PasswordHealth::Excellent => 3,
+        }
+    }
+
+    /// Generate summary statistics
+    pub fn generate_summary(&self, reports: &[HealthReport]) -> HealthSummary {
+        let total = reports.len();
+        let mut critical = 0;
+        let mut warning = 0;
+        let mut good = 0;
+        let mut excellent = 0;
+
+        for report in reports {
+            match report.health {
+                PasswordHealth::Critical { .. } => critical += 1,
+                PasswordHealth::Warning { .. } => warning += 1,
+                PasswordHealth::Good => good += 1,
+                PasswordHealth::Excellent => excellent += 1,
+            }
+        }
+
+        HealthSummary {
+            total,
+            critical,
+            warning,
+            good,
+            excellent,
+            score: self.calculate_health_score(critical, warning, good, excellent, total),
+        }
+    }
+
+    /// Calculate overall health score (0-100)
+    fn calculate_health_score(&self, _critical: usize, warning: usize, good: usize, excellent: usize, total: usize) -> u8 {
+        if total == 0 {
+            return 100;
+        }
+
+        // Critical passwords contribute 0 to score (already penalized by being critical)
+        let score = (excellent * 100 + good * 75 + warning * 40) / total;
+        score.min(100) as u8
+    }
+}
+
+/// Issues an entry inherits from the entries it links to: a link to an
+/// entry that is gone or in the trash, or to a recovery account with a weak
+/// password, which weakens this one too
+fn link_issues(vault: &Vault, entry: &Entry) -> Vec<(String, &'static str)> {
+    entry.linked_ids.iter()
+        .filter_map(|linked_id| match vault.get_entry(linked_id) {
+            None => Some((
+                format!("Linked entry '{}' no longer exists", linked_id),
+                "Remove the link or point it at the right entry",
+            )),
+            Some(linked) if linked.is_trashed() => Some((
+                format!("Linked entry '{}' is in the trash", linked_id),
+                "Restore the linked entry or remove the link",
+            )),
+            Some(linked) if matches!(linked.password_strength(), PasswordStrength::VeryWeak | PasswordStrength::Weak) => Some((
+                format!("Recovery account '{}' has a weak password", linked_id),
+                "Strengthen the linked account's password: it can be used to take over this one",
+            )),
+            Some(_) => None,
+        })
+        .collect()
+}
+
+/// Summary of password health for a vault
+#[derive(Debug, Clone, Serialize)]
+#[allow(dead_code)]
+pub struct HealthSummary {
+    pub total: usize,
+    pub critical: usize,
+    pub warning: usize,
+    pub good: usize,
+    pub excellent: usize,
+    pub score: u8, // 0-100 overall health score
+}
+
+impl HealthSummary {
+    /// Compact snapshot of this summary for the vault's health history
+    pub fn to_record(&self) -> HealthRecord {
+        HealthRecord {
+            timestamp: Utc::now(),
+            score: self.score,
+            total: self.total,
+            critical: self.critical,
+            warning: self.warning,
+            good: self.good,
+            excellent: self.excellent,
+        }
+    }
+}
+
+/// One point in a vault's health trend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthRecord {
+    pub timestamp: DateTime<Utc>,
+    pub score: u8,
+    pub total: usize,
+    pub critical: usize,
+    pub warning: usize,
+    pub good: usize,
+    pub excellent: usize,
+}
+
+/// Append a record to a health history, keeping one record per day
+/// (the latest wins) and at most `HEALTH_HISTORY_LIMIT` records
+pub fn append_health_record(history: &mut Vec<HealthRecord>, record: HealthRecord) {
+    let day = record.timestamp.date_naive();
+    history.retain(|existing| existing.timestamp.date_naive() != day);
+    history.push(record);
+    history.sort_by_key(|existing| existing.timestamp);
+
+    if history.len() > HEALTH_HISTORY_LIMIT {
+        let excess = history.len() - HEALTH_HISTORY_LIMIT;
+        history.drain(..excess);
+    }
+}
+
+/// Several accounts on one site using the same password
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SiteReuse {
+    pub site: String,
+    /// Entry ids sharing the password, sorted
+    pub entry_ids: Vec<String>,
+}
+
+impl SiteReuse {
+    pub fn message(&self) -> String {
+        format!("{} accounts on {} share a password", self.entry_ids.len(), self.site)
+    }
+}
+
+/// Passwords shared by entries of the same site (see
+/// [`url_match::entry_site`](crate::url_match::entry_site)), sorted by site.
+/// Trashed and archived entries are left out.
+pub fn site_reuse(vault: &Vault) -> Vec<SiteReuse> {
+    let active = vault.iter().filter(|(_, entry)| !entry.is_trashed() && !entry.archived);
+    let sites = crate::url_match::group_by_site(active, |(id, entry)| crate::url_match::entry_site(id, entry));
+    let mut reuse = Vec::new();
+    for (site, entries) in sites {
+        let Some(site) = site else { continue };
+        let mut by_password: HashMap<&str, Vec<String>> = HashMap::new();
+        for (id, entry) in entries {
+            if !entry.is_sealed() {
+                by_password.entry(entry.password_str().reveal()).or_default().push(id.to_string());
+            }
+        }
+        let mut shared: Vec<Vec<String>> = by_password.into_values().filter(|ids| ids.len() > 1).collect();
+        for ids in &mut shared {
+            ids.sort();
+        }
+        shared.sort();
+        reuse.extend(shared.into_iter().map(|entry_ids| SiteReuse { site: site.clone(), entry_ids }));
+    }
+    reuse
+}
+
+impl Default for PasswordHealthAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Entry;
+
+    #[test]
+    fn test_password_health_analysis() {
+        let analyzer = PasswordHealthAnalyzer::new();
+        let entry = Entry::new(
+            "test_user".to_string(),
+            "password123".to_string(), // This should be flagged as breached
+            None,
+        );
+
+        let report = analyzer.analyze_entry("test", &entry);
+        
+        match report.health {
+            PasswordHealth::Critical { issues } => {
+                assert!(issues.iter().any(|i| i.contains("breach")));
+            }
+            _ => panic!("Expected critical health status"),
+        }
+    }
+
+    #[test]
+    fn test_banned_list_matches_plaintext_any_case_and_sha1() {
+        // SHA-1 of "password", as breach corpora list it
+        let list = BannedList::parse("# company list\nAcme2024!\n\n5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:3861493\n");
+        assert_eq!(list.len(), 2);
+        assert!(list.contains("Acme2024!") && list.contains("acme2024!") && list.contains("ACME2024!"));
+        assert!(list.contains("password") && !list.contains("Password"));
+        assert!(!list.contains("Acme2025!"));
+
+        let analyzer = PasswordHealthAnalyzer::new().with_banned_list(list);
+        let entry = Entry::new("me".to_string(), "ACME2024!".to_string(), None);
+        match analyzer.analyze_entry("intranet", &entry).health {
+            PasswordHealth::Critical { issues } => assert!(issues.iter().any(|i| i == BANNED_ISSUE)),
+            other => panic!("Expected critical health status, got {:?}", other),
+        }
+        let entry = Entry::new("me".to_string(), "Tr0ub4dor&3-staple-Horse".to_string(), None);
+        assert!(!matches!(analyzer.analyze_entry("intranet", &entry).health, PasswordHealth::Critical { .. }));
+    }
+
+    #[test]
+    fn test_banned_list_file_is_read_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("banned.txt");
+        std::fs::write(&path, "Acme2024!\n").unwrap();
+        let mut analyzer = PasswordHealthAnalyzer::new();
+        analyzer.banned_file = Some(path.clone());
+        assert!(analyzer.is_banned("acme2024!"));
+
+        // Later edits aren't picked up by the same analyzer; a missing file is no list
+        std::fs::write(&path, "Other\n").unwrap();
+        assert!(analyzer.is_banned("acme2024!") && !analyzer.is_banned("other"));
+        let mut analyzer = PasswordHealthAnalyzer::new();
+        analyzer.banned_file = Some(dir.path().join("missing.txt"));
+        assert!(analyzer.banned_list().is_none() && !analyzer.is_banned("acme2024!"));
+    }
+
+    #[test]
+    fn test_master_password_entries_are_critical() {
+        let analyzer = PasswordHealthAnalyzer::new();
+        let mut entry = Entry::new("me".to_string(), "Tr0ub4dor&3-staple-Horse".to_string(), None);
+        assert!(!matches!(analyzer.analyze_entry("vault", &entry).health, PasswordHealth::Critical { .. }));
+
+        entry.holds_master_password = true;
+        match analyzer.analyze_entry("vault", &entry).health {
+            PasswordHealth::Critical { issues } => assert!(issues.iter().any(|i| i.contains("master password"))),
+            other => panic!("Expected critical health status, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_sealed_entries_use_the_strength_recorded_when_sealed() {
+        let analyzer = PasswordHealthAnalyzer::new();
+        let mut vault = Vault::new();
+        for id in ["bank", "broker"] {
+            let mut entry = Entry::new("me".to_string(), "password123".to_string(), None);
+            entry.seal("2468").unwrap();
+            vault.add_entry(id.to_string(), entry);
+        }
+        vault.add_entry("forum".to_string(), Entry::new("me".to_string(), String::new(), None));
+
+        let reports = analyzer.analyze_vault(&vault, AnalysisScope::ActiveOnly);
+        let bank = reports.iter().find(|report| report.entry_id == "bank").unwrap();
+        assert_eq!(bank.strength, analyze_password_strength("password123").0);
+        assert_eq!((bank.reused_with, bank.breach_count), (0, 0));
+        assert!(matches!(&bank.health, PasswordHealth::Critical { issues } if issues == &["Weak password"]));
+        // Sealed passwords aren't at hand to suggest anything about
+        assert!(bank.suggestions.is_empty());
+
+        let open = analyzer.analyze_entry("open", &Entry::new("me".to_string(), "password123".to_string(), None));
+        assert_eq!(open.suggestions, analyze_password_strength("password123").1);
+    }
+
+    #[test]
+    fn test_expiry_dates_raise_warning_then_critical() {
+        let analyzer = PasswordHealthAnalyzer::new();
+        let mut entry = Entry::new("contractor".to_string(), "Zq8#vLp2!mWx9@Tr".to_string(), None);
+        assert_eq!(analyzer.analyze_entry("vpn", &entry).health, PasswordHealth::Excellent);
+
+        entry.expires_at = Some(Utc::now() + Duration::days(EXPIRY_WARNING_DAYS + 5));
+        assert_eq!(analyzer.analyze_entry("vpn", &entry).health, PasswordHealth::Excellent);
+
+        entry.expires_at = Some(Utc::now() + Duration::days(3));
+        assert_eq!(
+            analyzer.analyze_entry("vpn", &entry).health,
+            PasswordHealth::Warning { issues: vec!["Credential expires in 3 day(s)".to_string()] }
+        );
+        assert!(entry.expires_within(EXPIRY_WARNING_DAYS, Utc::now()));
+
+        entry.expires_at = Some(Utc::now() - Duration::days(2));
+        match analyzer.analyze_entry("vpn", &entry).health {
+            PasswordHealth::Critical { issues } => assert_eq!(issues, vec!["Credential expired 2 day(s) ago"]),
+            other => panic!("Expected critical health status, got {:?}", other),
+        }
+        assert_eq!(entry.days_until_expiry(Utc::now()), Some(-2));
+    }
+
+    #[test]
+    fn test_health_summary() {
+        let analyzer = PasswordHealthAnalyzer::new();
+        let reports = vec![
+            HealthReport {
+                entry_id: "1".to_string(),
+                health: PasswordHealth::Excellent,
+                last_changed: Utc::now(),
+                age_days: 30,
+                strength: PasswordStrength::Strong,
+                recommendations: vec![],
+                suggestions: vec![],
+                reused_with: 0,
+                breach_count: 0,
+            },
+            HealthReport {
+                entry_id: "2".to_string(),
+                health: PasswordHealth::Critical { issues: vec!["breach".to_string()] },
+                last_changed: Utc::now(),
+                age_days: 400,
+                strength: PasswordStrength::Weak,
+                recommendations: vec![],
+                suggestions: vec![],
+                reused_with: 0,
+                breach_count: 1,
+            },
+        ];
+
+        let summary = analyzer.generate_summary(&reports);
+        assert_eq!(summary.total, 2);
+        assert_eq!(summary.excellent, 1);
+        assert_eq!(summary.critical, 1);
+        assert!(summary.score < 100);
+    }
+
+    #[test]
+    fn test_score_is_stable_across_trash_and_restore() {
+        let analyzer = PasswordHealthAnalyzer::new();
+        let mut vault = Vault::new();
+        vault.add_entry("strong".to_string(), Entry::new("a".to_string(), "Xk9#mQ2$vL7@pR4!".to_string(), None));
+        vault.add_entry("weak".to_string(), Entry::new("b".to_string(), "password123".to_string(), None));
+        let score = |vault: &Vault, scope| analyzer.generate_summary(&analyzer.analyze_vault(vault, scope));
+
+        let before = score(&vault, AnalysisScope::ActiveOnly);
+        assert_eq!(before.total, 2);
+
+        assert!(vault.trash_entry("weak", Utc::now()));
+        let trashed = score(&vault, AnalysisScope::ActiveOnly);
+        assert_eq!(trashed.total, 1);
+        assert_eq!(trashed.critical, 0);
+        assert!(trashed.score > before.score);
+        assert_eq!(score(&vault, AnalysisScope::IncludeTrash).score, before.score);
+
+        assert!(vault.restore_entry("weak"));
+        let restored = score(&vault, AnalysisScope::ActiveOnly);
+        assert_eq!((restored.total, restored.score), (before.total, before.score));
+    }
+
+    #[test]
+    fn test_reused_passwords_are_counted_within_scope() {
+        let analyzer = PasswordHealthAnalyzer::new();
+        let mut vault = Vault::new();
+        for id in ["a", "b", "c"] {
+            vault.add_entry(id.to_string(), Entry::new(id.to_string(), "Zq8#vLp2!mWx9@Tr".to_string(), None));
+        }
+        vault.add_entry("unique".to_string(), Entry::new("u".to_string(), "Hj4$nB7&kP1^sD6*".to_string(), None));
+
+        let reused = |vault: &Vault, scope| -> Vec<(String, usize)> {
+            let mut counts: Vec<_> = analyzer.analyze_vault(vault, scope)
+                .into_iter()
+                .map(|report| (report.entry_id, report.reused_with))
+                .collect();
+            counts.sort();
+            counts
+        };
+        let counts = reused(&vault, AnalysisScope::ActiveOnly);
+        assert_eq!(counts, vec![("a".into(), 2), ("b".into(), 2), ("c".into(), 2), ("unique".into(), 0)]);
+
+        let report = analyzer.analyze_vault(&vault, AnalysisScope::ActiveOnly).into_iter().find(|r| r.entry_id == "a").unwrap();
+        assert_eq!(report.health, PasswordHealth::Warning { issues: vec!["Password reused by 2 other entries".to_string()] });
+
+        assert!(vault.trash_entry("c", Utc::now()));
+        assert_eq!(reused(&vault, AnalysisScope::ActiveOnly)[0], ("a".to_string(), 1));
+        assert_eq!(reused(&vault, AnalysisScope::IncludeTrash)[0], ("a".to_string(), 2));
+
+        // Archived entries are out of every scope
+        assert!(vault.set_archived("b", true, Utc::now(), None));
+        assert_eq!(reused(&vault, AnalysisScope::IncludeTrash)[0], ("a".to_string(), 1));
+        assert_eq!(reused(&vault, AnalysisScope::IncludeTrash).len(), 3);
+    }
+
+    #[test]
+    fn test_weak_or_missing_linked_entries_warn_their_dependents() {
+        let analyzer = PasswordHealthAnalyzer::new();
+        let mut vault = Vault::new();
+        vault.add_entry("recovery-mail".to_string(), Entry::new("me".to_string(), "abc".to_string(), None));
+        vault.add_entry("old-phone".to_string(), Entry::new("me".to_string(), "Hj4$nB7&kP1^sD6*".to_string(), None));
+        let mut bank = Entry::new("me".to_string(), "Zq8#vLp2!mWx9@Tr".to_string(), None);
+        bank.link("recovery-mail");
+        bank.link("old-phone");
+        bank.link("gone");
+        vault.add_entry("bank".to_string(), bank);
+        assert!(vault.trash_entry("old-phone", Utc::now()));
+
+        let report = analyzer.analyze_vault(&vault, AnalysisScope::ActiveOnly).into_iter().find(|r| r.entry_id == "bank").unwrap();
+        assert_eq!(report.health, PasswordHealth::Warning { issues: vec![
+            "Recovery account 'recovery-mail' has a weak password".to_string(),
+            "Linked entry 'old-phone' is in the trash".to_string(),
+            "Linked entry 'gone' no longer exists".to_string(),
+        ] });
+
+        // Only the vault knows what an entry links to
+        assert_eq!(analyzer.analyze_entry("bank", vault.get_entry("bank").unwrap()).health, PasswordHealth::Excellent);
+    }
+
+    #[test]
+    fn test_report_serializes_for_json_output() {
+        let analyzer = PasswordHealthAnalyzer::new();
+        let entry = Entry::new("user".to_string(), "password123".to_string(), None);
+        let json = serde_json::to_value(analyzer.analyze_entry("mail", &entry)).unwrap();
+        assert_eq!(json["entry_id"], "mail");
+        assert_eq!(json["health"]["status"], "critical");
+        assert_eq!(json["breach_count"], 1);
+        assert_eq!(json["strength"], "VeryWeak");
+    }
+
+    #[test]
+    fn test_site_reuse_groups_by_registrable_domain() {
+        let mut vault = Vault::new();
+        let mut add = |id: &str, url: Option<&str>, password: &str| {
+            let mut entry = Entry::new(id.to_string(), password.to_string(), None);
+            entry.url = url.map(str::to_string);
+            vault.add_entry(id.to_string(), entry);
+        };
+        add("shop", Some("https://shop.example.co.uk"), "Zq8#vLp2!mWx9@Tr");
+        add("shop admin", Some("https://admin.example.co.uk/login"), "Zq8#vLp2!mWx9@Tr");
+        add("example.co.uk (old)", None, "Zq8#vLp2!mWx9@Tr");
+        add("other shop", Some("https://other.co.uk"), "Zq8#vLp2!mWx9@Tr");
+        add("wifi", None, "Zq8#vLp2!mWx9@Tr");
+        add("bank", Some("https://bank.com"), "Hj4$nB7&kP1^sD6*");
+
+        let reuse = site_reuse(&vault);
+        assert_eq!(reuse, vec![SiteReuse {
+            site: "example.co.uk".to_string(),
+            entry_ids: vec!["example.co.uk (old)".to_string(), "shop".to_string(), "shop admin".to_string()],
+        }]);
+        assert_eq!(reuse[0].message(), "3 accounts on example.co.uk share a password");
+
+        assert!(vault.trash_entry("shop", Utc::now()));
+        assert_eq!(site_reuse(&vault)[0].entry_ids.len(), 2);
+    }
+
+    fn record_at(timestamp: DateTime<Utc>, score: u8) -> HealthRecord {
+        HealthRecord { timestamp, score, total: 1, critical: 0, warning: 0, good: 1, excellent: 0 }
+    }
+
+    #[test]
+    fn test_health_history_deduplicates_per_day() {
+        let mut history = Vec::new();
+        let now = Utc::now();
+
+        append_health_record(&mut history, record_at(now - Duration::days(1), 40));
+        append_health_record(&mut history, record_at(now, 50));
+        append_health_record(&mut history, record_at(now, 60));
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.last().unwrap().score, 60);
+    }
+
+    #[test]
+    fn test_health_history_is_capped() {
+        let mut history = Vec::new();
+        let start = Utc::now() - Duration::days(200);
+
+        for day in 0..120 {
+            append_health_record(&mut history, record_at(start + Duration::days(day), 50));
+        }
+
+        assert_eq!(history.len(), HEALTH_HISTORY_LIMIT);
+        assert_eq!(history[0].timestamp, start + Duration::days(30));
+    }
+}
@@ -0,0 +1,1994 @@
+#![allow(dead_code)]
+
+use crate::batch::{apply_ops, BatchResult, EntryOp, OpOutcome};
+use crate::copy_sequence::CopyStep;
+use crate::model::{Attachment, Entry, EntryIcon, GenerationPrefs, Vault};
+use crate::sealed::OpenedEntries;
+use crate::vault::{create_private, sync_service_above, VaultKey, VaultManager};
+use base64ct::{Base64, Encoding};
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+#[derive(Serialize, Deserialize)]
+struct ExportEntry {
+    id: String,
+    username: String,
+    password: String,
+    note: Option<String>,
+    url: Option<String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    last_changed: chrono::DateTime<chrono::Utc>,
+    /// Embedded icons travel as base64; CSV exports leave them out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    icon: Option<EntryIcon>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    modified_by: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    archived: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    copy_sequence: Vec<CopyStep>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    generation: Option<GenerationPrefs>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    linked_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<Attachment>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExportData {
+    version: String,
+    exported_at: chrono::DateTime<chrono::Utc>,
+    entries: Vec<ExportEntry>,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct CsvEntry {
+    #[serde(default)]
+    id: String,
+    username: String,
+    password: String,
+    note: Option<String>,
+    url: Option<String>,
+}
+
+/// Default limit on an imported username or URL, in characters
+pub const DEFAULT_MAX_FIELD_CHARS: usize = 4096;
+
+/// Default limit on an imported note, in characters
+pub const DEFAULT_MAX_NOTE_CHARS: usize = 64 * 1024;
+
+/// Entry fields a CSV column can be mapped to
+pub const IMPORT_FIELDS: [&str; 5] = ["id", "username", "password", "note", "url"];
+
+/// Header names each entry field is recognised by when it isn't mapped
+const CSV_HEADERS: [(&str, &[&str]); 5] = [
+    ("id", &["id", "name", "title", "site"]),
+    ("username", &["username", "login", "email"]),
+    ("password", &["password"]),
+    ("note", &["note", "notes", "comment"]),
+    ("url", &["url", "website"]),
+];
+
+/// What to do with an imported entry whose id the vault already has
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    /// Keep the vault's entry
+    #[default]
+    Skip,
+    /// Replace it with the imported one
+    Overwrite,
+    /// Import under a free id, e.g. "github (copy)"
+    Rename,
+}
+
+impl ConflictPolicy {
+    pub const ALL: [ConflictPolicy; 3] = [Self::Skip, Self::Overwrite, Self::Rename];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Skip => "skip",
+            Self::Overwrite => "overwrite",
+            Self::Rename => "rename",
+        }
+    }
+}
+
+impl std::str::FromStr for ConflictPolicy {
+    type Err = String;
+
+    /// `suffix` is taken for `rename`, which adds a " (copy)" suffix
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "skip" => Ok(Self::Skip),
+            "overwrite" => Ok(Self::Overwrite),
+            "rename" | "suffix" => Ok(Self::Rename),
+            other => Err(format!("Invalid conflict policy '{}': use skip, overwrite or suffix", other)),
+        }
+    }
+}
+
+/// Saved answers for a recurring import: format, CSV layout, how ids are
+/// built, tags for the batch and what to do on id conflicts. Holds no
+/// secrets, so profiles live in the configuration file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ImportProfile {
+    /// `json`, `csv`, `chrome`, `firefox`, `keepass` or `pass`
+    pub format: String,
+    /// CSV field delimiter
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+    /// Source column for each entry field in `IMPORT_FIELDS`; unmapped
+    /// fields are found by their usual header names
+    #[serde(default)]
+    pub columns: BTreeMap<String, String>,
+    /// Entry id built from `{column}` placeholders, e.g. `{System}/{Login}`.
+    /// Empty uses the id column.
+    #[serde(default)]
+    pub id_template: String,
+    /// Tags for every imported entry; `{date}` and `{month}` expand to the
+    /// day of the import, so `import:{month}` tags a batch `import:2024-06`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub conflict: ConflictPolicy,
+    /// Tidy imported ids with [`normalize_id`]
+    #[serde(default = "default_normalize_ids")]
+    pub normalize_ids: bool,
+    /// Longest username or URL imported inline; longer ones go to an
+    /// attachment
+    #[serde(default = "default_max_field_chars")]
+    pub max_field_chars: usize,
+    /// Longest note imported inline
+    #[serde(default = "default_max_note_chars")]
+    pub max_note_chars: usize,
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+fn default_normalize_ids() -> bool {
+    true
+}
+
+fn default_max_field_chars() -> usize {
+    DEFAULT_MAX_FIELD_CHARS
+}
+
+fn default_max_note_chars() -> usize {
+    DEFAULT_MAX_NOTE_CHARS
+}
+
+impl Default for ImportProfile {
+    fn default() -> Self {
+        Self::new("csv")
+    }
+}
+
+impl ImportProfile {
+    /// A profile that imports `format` with no mapping, tags or renames
+    pub fn new(format: &str) -> Self {
+        Self {
+            format: format.to_string(),
+            delimiter: default_delimiter(),
+            columns: BTreeMap::new(),
+            id_template: String::new(),
+            tags: Vec::new(),
+            conflict: ConflictPolicy::Skip,
+            normalize_ids: default_normalize_ids(),
+            max_field_chars: default_max_field_chars(),
+            max_note_chars: default_max_note_chars(),
+        }
+    }
+
+    /// Parse a delimiter as typed: one ASCII character, or `tab` / `\t`
+    pub fn parse_delimiter(text: &str) -> Result<char, String> {
+        match text {
+            "tab" | "\\t" => Ok('\t'),
+            _ => {
+                let mut chars = text.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if c.is_ascii() => Ok(c),
+                    _ => Err(format!("Delimiter must be a single ASCII character, got '{}'", text)),
+                }
+            }
+        }
+    }
+
+    /// The profile's tags with `{date}` and `{month}` filled in for `today`
+    pub fn batch_tags(&self, today: chrono::NaiveDate) -> Vec<String> {
+        let date = today.format("%Y-%m-%d").to_string();
+        let month = today.format("%Y-%m").to_string();
+        self.tags.iter()
+            .map(|tag| tag.replace("{date}", &date).replace("{month}", &month).trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    }
+}
+
+/// A row of an import file that could not be imported
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRowError {
+    /// 1-based position of the record in the file
+    pub row: usize,
+    /// Source line, where the format has them (CSV)
+    pub line: Option<usize>,
+    pub reason: String,
+}
+
+impl std::fmt::Display for ImportRowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.reason),
+            None => write!(f, "entry {}: {}", self.row, self.reason),
+        }
+    }
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    /// Entries added to the vault
+    pub imported: usize,
+    /// IDs left alone because the vault already has them
+    pub skipped: Vec<String>,
+    /// IDs whose vault entry was replaced by the imported one
+    pub replaced: Vec<String>,
+    /// Imported entries given a new id because theirs was taken: (old, new)
+    pub renamed: Vec<(String, String)>,
+    /// Rows that were malformed and not imported
+    pub errors: Vec<ImportRowError>,
+    /// Imported entries with fields over the profile's limits, moved into
+    /// attachments: (id, fields)
+    pub moved_to_attachments: Vec<(String, Vec<&'static str>)>,
+}
+
+impl ImportReport {
+    /// Note the rows [`ImportExportManager::attach_oversize_fields`]
+    /// changed, under the ids they were imported as; skipped rows are left out
+    pub fn record_attached(&mut self, moved: Vec<(String, Vec<&'static str>)>) {
+        for (id, fields) in moved {
+            if self.skipped.contains(&id) {
+                continue;
+            }
+            let id = self.renamed.iter()
+                .find(|(old, _)| *old == id)
+                .map_or(id, |(_, new)| new.clone());
+            self.moved_to_attachments.push((id, fields));
+        }
+    }
+
+    /// The error list as plain text, one row per line
+    pub fn error_list(&self) -> String {
+        self.errors.iter().map(|e| format!("{}\n", e)).collect()
+    }
+}
+
+/// Why a plaintext export shouldn't go where it was pointed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportRisk {
+    /// Sync service whose folder the file would land in, e.g. "Dropbox"
+    pub sync_service: Option<&'static str>,
+    /// Files created there end up readable by other users, e.g. on a
+    /// mount that ignores permissions
+    pub readable_by_others: bool,
+}
+
+impl ExportRisk {
+    /// Warning to confirm before writing the export
+    pub fn message(&self) -> String {
+        let mut reasons = Vec::new();
+        if let Some(service) = self.sync_service {
+            reasons.push(format!("is in a {} folder, so every password will be uploaded to {} unencrypted", service, service));
+        }
+        if self.readable_by_others {
+            reasons.push("is on a drive where other users of this computer can read it".to_string());
+        }
+        format!("This export {}.", reasons.join(" and "))
+    }
+}
+
+/// Check where a plaintext export is going: into a cloud sync folder, or
+/// somewhere the file can't be kept private. `None` if neither.
+pub fn export_risk(output_path: &str) -> Option<ExportRisk> {
+    let parent = Path::new(output_path).parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let dir = std::fs::canonicalize(parent).ok()?;
+    let risk = ExportRisk {
+        sync_service: sync_service_above(&dir),
+        readable_by_others: private_files_readable_by_others(&dir),
+    };
+    (risk.sync_service.is_some() || risk.readable_by_others).then_some(risk)
+}
+
+/// Whether a file created private in `dir` is still readable by group or
+/// others, found by creating one. False if it can't be created.
+#[cfg(unix)]
+fn private_files_readable_by_others(dir: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    let probe = dir.join(format!(".passman-export-probe-{}", std::process::id()));
+    let Some(probe) = probe.to_str() else {
+        return false;
+    };
+    let mode = create_private(probe).and_then(|file| file.metadata()).map(|meta| meta.permissions().mode());
+    let _ = std::fs::remove_file(probe);
+    mode.is_ok_and(|mode| mode & 0o077 != 0)
+}
+
+#[cfg(not(unix))]
+fn private_files_readable_by_others(_dir: &Path) -> bool {
+    false
+}
+
+/// Parsed entries with their ids, and the rows that couldn't be parsed
+pub type ParsedRows = (Vec<(String, Entry)>, Vec<ImportRowError>);
+
+/// [`ParsedRows`] from an entry stream, each with its line number
+pub type StreamRows = (Vec<(usize, String, Entry)>, Vec<ImportRowError>);
+
+/// Words left lowercase inside a title-cased name
+const MINOR_WORDS: &[&str] = &["a", "an", "and", "at", "by", "for", "in", "of", "on", "or", "the", "to"];
+
+/// Tidy an imported id: whitespace is trimmed and runs of it collapsed to
+/// one space, a leading `http(s)://` and `www.` are dropped and host names
+/// lowercased, and multi-word names in lowercase are title-cased. Each
+/// `/`-separated folder is tidied on its own. The result passes
+/// [`Vault::new_id_problem`] whenever the id has any printable character.
+pub fn normalize_id(id: &str) -> String {
+    let spaced: String = id.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+    let collapsed = spaced.split_whitespace().collect::<Vec<_>>().join(" ");
+    let without_scheme = ["https://", "http://"]
+        .iter()
+        .find(|scheme| collapsed.get(..scheme.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme)))
+        .map_or(collapsed.as_str(), |scheme| &collapsed[scheme.len()..]);
+
+    let normalized = without_scheme
+        .split('/')
+        .map(|segment| normalize_segment(segment.trim()))
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    if normalized.is_empty() { collapsed } else { normalized }
+}
+
+/// One folder of an id: a leading host name is lowercased without `www.`;
+/// otherwise a lowercase name of several words is title-cased
+fn normalize_segment(segment: &str) -> String {
+    let host_end = segment
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '-'))
+        .unwrap_or(segment.len());
+    let (host, rest) = segment.split_at(host_end);
+    if is_host_name(host) && !rest.starts_with('@') {
+        let host = host.to_lowercase();
+        let host = host.strip_prefix("www.").filter(|host| is_host_name(host)).unwrap_or(&host);
+        return format!("{}{}", host, rest);
+    }
+    if !segment.contains(' ') {
+        return segment.to_string();
+    }
+    segment
+        .split(' ')
+        .enumerate()
+        .map(|(index, word)| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) if first.is_lowercase() && word.chars().all(|c| !c.is_uppercase())
+                    && (index == 0 || !MINOR_WORDS.contains(&word)) => {
+                    first.to_uppercase().chain(chars).collect()
+                }
+                _ => word.to_string(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// `example.com`, `accounts.google.com`: dotted labels ending in a top
+/// level domain of letters
+fn is_host_name(text: &str) -> bool {
+    let labels: Vec<&str> = text.split('.').collect();
+    labels.len() >= 2
+        && labels.iter().all(|label| !label.is_empty() && !label.starts_with('-') && !label.ends_with('-'))
+        && labels.last().is_some_and(|tld| tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+/// Ids of `rows` that [`normalize_id`] would change, as (original, tidied)
+pub fn id_normalizations(rows: &[(String, Entry)]) -> Vec<(String, String)> {
+    rows.iter()
+        .filter_map(|(id, _)| {
+            let normalized = normalize_id(id);
+            (normalized != *id).then(|| (id.clone(), normalized))
+        })
+        .collect()
+}
+
+/// Parse CSV rows, collecting malformed ones instead of failing
+fn parse_csv(contents: &str) -> ParsedRows {
+    parse_csv_with(contents, &ImportProfile::default())
+}
+
+/// Parse CSV rows with a profile's delimiter, column mapping and id template
+fn parse_csv_with(contents: &str, profile: &ImportProfile) -> ParsedRows {
+    let header_error = |reason: String| (Vec::new(), vec![ImportRowError { row: 0, line: Some(1), reason }]);
+    let Ok(delimiter) = u8::try_from(profile.delimiter) else {
+        return header_error(format!("delimiter '{}' is not an ASCII character", profile.delimiter));
+    };
+    let mut reader = csv::ReaderBuilder::new().delimiter(delimiter).from_reader(contents.as_bytes());
+    let source_headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => {
+            let line = e.position().map(|p| p.line() as usize);
+            return (Vec::new(), vec![ImportRowError { row: 0, line, reason: csv_reason(&e) }]);
+        }
+    };
+    if let Some((field, column)) = profile.columns.iter().find(|(_, column)| !source_headers.iter().any(|h| h == column.as_str())) {
+        return header_error(format!("no column '{}' to map to {}", column, field));
+    }
+    let headers = csv_field_names(&source_headers, &profile.columns);
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for (index, result) in reader.records().enumerate() {
+        let row = index + 1;
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map(|p| p.line() as usize);
+                errors.push(ImportRowError { row, line, reason: csv_reason(&e) });
+                continue;
+            }
+        };
+        let line = record.position().map(|p| p.line() as usize);
+        match record.deserialize::<CsvEntry>(Some(&headers)) {
+            Ok(csv_entry) => {
+                let id = if profile.id_template.is_empty() {
+                    csv_entry.id
+                } else {
+                    fill_id_template(&profile.id_template, &source_headers, &record)
+                };
+                if id.trim().is_empty() {
+                    errors.push(ImportRowError { row, line, reason: "empty id".to_string() });
+                    continue;
+                }
+                let mut entry = Entry::new(csv_entry.username, csv_entry.password, csv_entry.note);
+                entry.url = csv_entry.url;
+                rows.push((id, entry));
+            }
+            Err(e) => errors.push(ImportRowError { row, line, reason: csv_reason(&e) }),
+        }
+    }
+    (rows, errors)
+}
+
+/// Rename CSV headers to the entry fields they hold. Mapped columns win;
+/// a usual header for a field that is mapped elsewhere, and anything
+/// unrecognised, is blanked so it's ignored.
+fn csv_field_names(headers: &csv::StringRecord, columns: &BTreeMap<String, String>) -> csv::StringRecord {
+    headers.iter()
+        .map(|header| {
+            if let Some((field, _)) = columns.iter().find(|(_, column)| column.as_str() == header) {
+                return field.as_str();
+            }
+            CSV_HEADERS.iter()
+                .find(|(field, names)| names.contains(&header) && !columns.contains_key(*field))
+                .map_or("", |(field, _)| field)
+        })
+        .collect()
+}
+
+/// Fill `{column}` placeholders with a record's values. A placeholder
+/// whose column is blank leaves the whole id empty rather than half-built.
+fn fill_id_template(template: &str, headers: &csv::StringRecord, record: &csv::StringRecord) -> String {
+    let mut id = template.to_string();
+    for (header, value) in headers.iter().zip(record.iter()) {
+        let placeholder = format!("{{{}}}", header);
+        if id.contains(&placeholder) {
+            if value.trim().is_empty() {
+                return String::new();
+            }
+            id = id.replace(&placeholder, value.trim());
+        }
+    }
+    id.trim().to_string()
+}
+
+/// Short reason for a CSV error, without the position the row already carries
+fn csv_reason(e: &csv::Error) -> String {
+    match e.kind() {
+        csv::ErrorKind::UnequalLengths { expected_len, len, .. } => {
+            format!("expected {} fields, found {}", expected_len, len)
+        }
+        csv::ErrorKind::Utf8 { .. } => "invalid UTF-8".to_string(),
+        csv::ErrorKind::Deserialize { err, .. } => match err.field() {
+            Some(field) => format!("field {}: {}", field + 1, err.kind()),
+            None => err.kind().to_string(),
+        },
+        _ => e.to_string(),
+    }
+}
+
+/// Parse a Passman JSON export; a malformed entry is reported, not fatal
+fn parse_json(contents: &str) -> Result<ParsedRows, Box<dyn std::error::Error>> {
+    #[derive(Deserialize)]
+    struct ImportData {
+        entries: Vec<serde_json::Value>,
+    }
+    let import_data: ImportData = serde_json::from_str(contents)?;
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for (index, value) in import_data.entries.into_iter().enumerate() {
+        let row = index + 1;
+        match serde_json::from_value::<ExportEntry>(value) {
+            Ok(export_entry) if export_entry.id.trim().is_empty() => {
+                errors.push(ImportRowError { row, line: None, reason: "empty id".to_string() });
+            }
+            Ok(export_entry) => {
+                let mut entry = Entry::new(export_entry.username, export_entry.password, export_entry.note);
+                entry.icon = export_entry.icon.and_then(EntryIcon::validated);
+                entry.expires_at = export_entry.expires_at;
+                entry.created_by = export_entry.created_by;
+                entry.modified_by = export_entry.modified_by;
+                entry.archived = export_entry.archived;
+                entry.copy_sequence = export_entry.copy_sequence;
+                entry.generation = export_entry.generation;
+                entry.linked_ids = export_entry.linked_ids;
+                entry.url = export_entry.url;
+                entry.attachments = export_entry.attachments;
+                rows.push((export_entry.id, entry));
+            }
+            Err(e) => errors.push(ImportRowError { row, line: None, reason: e.to_string() }),
+        }
+    }
+    Ok((rows, errors))
+}
+
+/// One line of an entry stream (`passman add --batch-stdin`)
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StreamEntry {
+    id: String,
+    username: String,
+    password: String,
+    #[serde(default)]
+    note: Option<String>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Parse newline-delimited JSON entries, one object per line, keeping each
+/// entry's line number. Blank lines are ignored; a malformed line is
+/// reported, not fatal.
+pub fn parse_entry_stream(reader: impl std::io::BufRead) -> std::io::Result<StreamRows> {
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line_number = index + 1;
+        let line = Zeroizing::new(line?);
+        if line.trim().is_empty() {
+            continue;
+        }
+        let error = |reason: String| ImportRowError { row: line_number, line: Some(line_number), reason };
+        match serde_json::from_str::<StreamEntry>(&line) {
+            Ok(item) if item.id.trim().is_empty() => errors.push(error("empty id".to_string())),
+            Ok(item) if item.password.is_empty() => errors.push(error("empty password".to_string())),
+            Ok(item) => {
+                let mut entry = Entry::new(item.username, item.password, item.note.filter(|note| !note.is_empty()));
+                entry.url = item.url.filter(|url| !url.trim().is_empty());
+                entry.tags = item.tags.iter().map(|tag| tag.trim().to_string()).filter(|tag| !tag.is_empty()).collect();
+                rows.push((line_number, item.id.trim().to_string(), entry));
+            }
+            Err(e) => errors.push(error(json_reason(&e))),
+        }
+    }
+    Ok((rows, errors))
+}
+
+/// Short reason for a JSON error on a line the row already names
+fn json_reason(e: &serde_json::Error) -> String {
+    let message = e.to_string();
+    match message.rsplit_once(" at line ") {
+        Some((reason, _)) => format!("{} (column {})", reason, e.column()),
+        None => message,
+    }
+}
+
+/// Parse a browser export. Chrome's is a CSV (`name,url,username,password`
+/// with an optional `note` column); its older JSON form is still read.
+fn parse_browser(contents: &str, browser_type: &str) -> Result<ParsedRows, Box<dyn std::error::Error>> {
+    if browser_type == "chrome" && !contents.trim_start().starts_with('{') {
+        return Ok(parse_chrome_csv(contents));
+    }
+
+    let json_data: serde_json::Value = serde_json::from_str(contents)?;
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+
+    // Handle Chrome export format
+    if browser_type == "chrome" {
+        if let Some(passwords) = json_data.get("passwords").and_then(|p| p.as_array()) {
+            for (index, password_entry) in passwords.iter().enumerate() {
+                let field = |name: &str| password_entry.get(name).and_then(|v| v.as_str());
+                let (Some(origin), Some(username), Some(password)) =
+                    (field("origin"), field("username"), field("password"))
+                else {
+                    let missing: Vec<&str> = ["origin", "username", "password"]
+                        .into_iter()
+                        .filter(|name| field(name).is_none())
+                        .collect();
+                    errors.push(ImportRowError {
+                        row: index + 1,
+                        line: None,
+                        reason: format!("missing {}", missing.join(", ")),
+                    });
+                    continue;
+                };
+
+                let entry = Entry::new(
+                    username.to_string(),
+                    password.to_string(),
+                    Some(format!("Imported from Chrome: {}", origin)),
+                );
+                rows.push((format!("{}_{}", origin, username), entry));
+            }
+        }
+    }
+    Ok((rows, errors))
+}
+
+/// Parse Chrome / Google Password Manager CSV. Ids come from `name`, else
+/// the URL host; `android://…@<package>/` URLs become a tag with the
+/// package name instead of a URL. Rows marked `blacklisted` (sites Chrome
+/// was told never to save) and rows with neither username nor password
+/// are skipped. Ids repeated within the file get the username appended.
+fn parse_chrome_csv(contents: &str) -> ParsedRows {
+    let mut reader = csv::ReaderBuilder::new().from_reader(contents.as_bytes());
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => {
+            let line = e.position().map(|p| p.line() as usize);
+            return (Vec::new(), vec![ImportRowError { row: 0, line, reason: csv_reason(&e) }]);
+        }
+    };
+    let column = |names: &[&str]| headers.iter().position(|h| names.contains(&h.trim().to_lowercase().as_str()));
+    let (name, url, note, blocked) = (column(&["name"]), column(&["url", "origin"]), column(&["note", "notes"]), column(&["blacklisted", "blocklisted"]));
+    let (Some(username), Some(password)) = (column(&["username"]), column(&["password"])) else {
+        let reason = "not a Chrome export: needs username and password columns".to_string();
+        return (Vec::new(), vec![ImportRowError { row: 0, line: Some(1), reason }]);
+    };
+
+    let mut rows: Vec<(String, Entry)> = Vec::new();
+    let mut errors = Vec::new();
+    for (index, result) in reader.records().enumerate() {
+        let row = index + 1;
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map(|p| p.line() as usize);
+                errors.push(ImportRowError { row, line, reason: csv_reason(&e) });
+                continue;
+            }
+        };
+        let line = record.position().map(|p| p.line() as usize);
+        let field = |column: Option<usize>| column.and_then(|c| record.get(c)).unwrap_or_default();
+        let (username, password) = (field(Some(username)), field(Some(password)));
+        let blocked = field(blocked).trim();
+        if blocked.eq_ignore_ascii_case("true") || blocked == "1" || (username.is_empty() && password.is_empty()) {
+            continue;
+        }
+
+        let url = field(url).trim();
+        let package = url.strip_prefix("android://").map(|rest| {
+            let authority = rest.split('/').next().unwrap_or_default();
+            authority.rsplit('@').next().unwrap_or(authority).to_string()
+        });
+        let base_id = Some(field(name).trim())
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .or_else(|| package.clone().filter(|package| !package.is_empty()))
+            .or_else(|| crate::url_match::url_host(url));
+        let Some(base_id) = base_id else {
+            errors.push(ImportRowError { row, line, reason: "no name or url to build an id from".to_string() });
+            continue;
+        };
+        let id = if rows.iter().any(|(taken, _)| *taken == base_id) {
+            std::iter::once(format!("{} ({})", base_id, username))
+                .chain((2..).map(|n| format!("{} ({})", base_id, n)))
+                .find(|candidate| !rows.iter().any(|(taken, _)| taken == candidate))
+                .unwrap_or_default()
+        } else {
+            base_id
+        };
+
+        let note = Some(field(note).to_string()).filter(|note| !note.trim().is_empty());
+        let mut entry = Entry::new(username.to_string(), password.to_string(), note);
+        match package {
+            Some(package) if !package.is_empty() => entry.tags.push(package),
+            Some(_) => {}
+            None => entry.url = Some(url.to_string()).filter(|url| !url.is_empty()),
+        }
+        rows.push((id, entry));
+    }
+    (rows, errors)
+}
+
+/// An element of an XML document, as read by [`parse_xml`]
+#[derive(Default)]
+struct XmlNode {
+    name: String,
+    attributes: Vec<(String, String)>,
+    text: String,
+    children: Vec<XmlNode>,
+}
+
+impl XmlNode {
+    fn child(&self, name: &str) -> Option<&XmlNode> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlNode> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|child| child.text.as_str())
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+}
+
+/// Text escaped for XML element content and attribute values. Control
+/// characters XML 1.0 can't carry are dropped.
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // A literal CR would be read back as LF
+            '\r' => escaped.push_str("&#13;"),
+            '\t' | '\n' => escaped.push(c),
+            c if (c as u32) < 0x20 => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Read an XML document into its root element: elements with their
+/// attributes and text, with references resolved. Comments, processing
+/// instructions and the DTD are skipped.
+fn parse_xml(input: &str) -> Result<XmlNode, String> {
+    use quick_xml::events::{BytesStart, Event};
+
+    fn element(start: &BytesStart) -> Result<XmlNode, String> {
+        let attributes = start.attributes()
+            .map(|attribute| {
+                let attribute = attribute.map_err(|e| e.to_string())?;
+                let value = attribute.normalized_value(quick_xml::XmlVersion::Implicit1_0).map_err(|e| e.to_string())?;
+                Ok((String::from_utf8_lossy(attribute.key.as_ref()).into_owned(), value.into_owned()))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(XmlNode { name: String::from_utf8_lossy(start.name().as_ref()).into_owned(), attributes, ..Default::default() })
+    }
+
+    let mut reader = quick_xml::Reader::from_str(input);
+    // The bottom of the stack is the document, which holds the root
+    let mut stack = vec![XmlNode::default()];
+    loop {
+        let event = reader.read_event().map_err(|e| format!("{} (byte {})", e, reader.error_position()))?;
+        match event {
+            Event::Start(start) => stack.push(element(&start)?),
+            Event::Empty(start) => {
+                let node = element(&start)?;
+                stack.last_mut().expect("document node").children.push(node);
+            }
+            // The reader has already checked that the end tag matches
+            Event::End(_) => {
+                let node = stack.pop().filter(|_| !stack.is_empty()).ok_or("unexpected end tag")?;
+                stack.last_mut().expect("document node").children.push(node);
+            }
+            Event::Text(text) => {
+                let text = text.xml10_content().map_err(|e| e.to_string())?;
+                stack.last_mut().expect("document node").text.push_str(&text);
+            }
+            Event::CData(cdata) => {
+                let text = cdata.xml10_content().map_err(|e| e.to_string())?;
+                stack.last_mut().expect("document node").text.push_str(&text);
+            }
+            Event::GeneralRef(reference) => {
+                let text = &mut stack.last_mut().expect("document node").text;
+                match reference.resolve_char_ref().map_err(|e| e.to_string())? {
+                    Some(c) => text.push(c),
+                    None => {
+                        let name = reference.decode().map_err(|e| e.to_string())?;
+                        text.push_str(quick_xml::escape::resolve_predefined_entity(&name).ok_or_else(|| format!("unknown entity &{};", name))?);
+                    }
+                }
+            }
+            Event::Eof => break,
+            Event::Comment(_) | Event::Decl(_) | Event::PI(_) | Event::DocType(_) => {}
+        }
+    }
+    if stack.len() > 1 {
+        return Err(format!("<{}> is never closed", stack[stack.len() - 1].name));
+    }
+    stack.pop()
+        .and_then(|document| document.children.into_iter().next())
+        .ok_or_else(|| "no root element".to_string())
+}
+
+/// Value of one of a KeePass entry's `<String>` fields; empty if missing
+fn keepass_field<'a>(entry: &'a XmlNode, key: &str) -> &'a str {
+    entry.all("String")
+        .find(|string| string.child_text("Key").map(str::trim) == Some(key))
+        .and_then(|string| string.child_text("Value"))
+        .unwrap_or_default()
+}
+
+/// Turn one KeePass `<Entry>` into an entry. The title is the id (the URL
+/// host if there's no title) and `path` is the group it sits in.
+fn keepass_entry(node: &XmlNode, path: &[String]) -> Result<(String, Entry), String> {
+    // The XML inside a .kdbx keeps protected values encrypted with a key
+    // that isn't in the XML; an exported XML file has them in the clear
+    let encrypted = node.all("String")
+        .find(|string| string.child("Value").and_then(|value| value.attribute("Protected")).is_some_and(|protected| protected.eq_ignore_ascii_case("true")));
+    if let Some(string) = encrypted {
+        return Err(format!("{} is encrypted; export the database from KeePass as XML", string.child_text("Key").unwrap_or_default().trim()));
+    }
+    let url = keepass_field(node, "URL").trim();
+    let id = Some(keepass_field(node, "Title").trim())
+        .filter(|title| !title.is_empty())
+        .map(str::to_string)
+        .or_else(|| crate::url_match::url_host(url))
+        .ok_or("no title or URL to build an id from")?;
+
+    let note = Some(keepass_field(node, "Notes").to_string()).filter(|note| !note.trim().is_empty());
+    let mut entry = Entry::new(keepass_field(node, "UserName").to_string(), keepass_field(node, "Password").to_string(), note);
+    entry.url = Some(url.to_string()).filter(|url| !url.is_empty());
+    entry.tags = node.child_text("Tags").unwrap_or_default()
+        .split([';', ','])
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect();
+    // The group is a tag unless the id or tags already say where it belongs
+    let group = path.join("/");
+    if !group.is_empty() && !id.starts_with(&format!("{}/", group)) && !entry.tags.contains(&group) {
+        entry.tags.push(group);
+    }
+
+    if let Some(times) = node.child("Times") {
+        let time = |name: &str| {
+            times.child_text(name)
+                .and_then(|text| chrono::DateTime::parse_from_rfc3339(text.trim()).ok())
+                .map(|time| time.with_timezone(&chrono::Utc))
+        };
+        if let Some(created) = time("CreationTime") {
+            entry.created_at = created;
+        }
+        if let Some(modified) = time("LastModificationTime") {
+            entry.modified_at = modified;
+        }
+        if times.child_text("Expires").is_some_and(|expires| expires.trim().eq_ignore_ascii_case("true")) {
+            entry.expires_at = time("ExpiryTime");
+        }
+    }
+    Ok((id, entry))
+}
+
+/// Collect the entries of a KeePass group and its subgroups, skipping the
+/// recycle bin
+fn read_keepass_group(group: &XmlNode, path: &[String], recycle_bin: Option<&str>, parsed: &mut ParsedRows) {
+    let (rows, errors) = &mut *parsed;
+    for node in group.all("Entry") {
+        let row = rows.len() + errors.len() + 1;
+        match keepass_entry(node, path) {
+            Ok(imported) => rows.push(imported),
+            Err(reason) => errors.push(ImportRowError { row, line: None, reason }),
+        }
+    }
+    for child in group.all("Group") {
+        if recycle_bin.is_some() && child.child_text("UUID").map(str::trim) == recycle_bin {
+            continue;
+        }
+        let mut child_path = path.to_vec();
+        child_path.push(child.child_text("Name").unwrap_or_default().trim().to_string());
+        read_keepass_group(child, &child_path, recycle_bin, parsed);
+    }
+}
+
+/// Parse a KeePass 2.x XML export. Groups below the database's own top
+/// group become a tag like "Internet/Email"; entry history and the
+/// recycle bin are left out.
+fn parse_keepass_xml(contents: &str) -> Result<ParsedRows, Box<dyn std::error::Error>> {
+    let root = parse_xml(contents).map_err(|e| format!("not a KeePass XML file: {}", e))?;
+    if root.name != "KeePassFile" {
+        return Err("not a KeePass XML file: no <KeePassFile> element".into());
+    }
+    let recycle_bin = root.child("Meta")
+        .and_then(|meta| meta.child_text("RecycleBinUUID"))
+        .map(str::trim)
+        .filter(|uuid| !uuid.trim_start_matches('A').trim_end_matches('=').is_empty());
+
+    let mut parsed = (Vec::new(), Vec::new());
+    for top in root.child("Root").into_iter().flat_map(|root| root.all("Group")) {
+        read_keepass_group(top, &[], recycle_bin, &mut parsed);
+    }
+    Ok(parsed)
+}
+
+/// Decrypts one password-store file; the plaintext never touches the disk
+pub type PassDecrypt<'a> = dyn FnMut(&Path) -> Result<Zeroizing<Vec<u8>>, String> + 'a;
+
+/// Decrypt a password-store file with `gpg`, reading its output from a pipe
+pub fn gpg_decrypt(path: &Path) -> Result<Zeroizing<Vec<u8>>, String> {
+    let output = std::process::Command::new("gpg")
+        .args(["--quiet", "--batch", "--decrypt"])
+        .arg(path)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|e| format!("could not run gpg: {}", e))?;
+    let plaintext = Zeroizing::new(output.stdout);
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or("gpg failed");
+        return Err(reason.trim().to_string());
+    }
+    Ok(plaintext)
+}
+
+/// `.gpg` files under a password store, sorted; hidden directories such
+/// as `.git` are skipped and symlinked directories aren't followed
+fn pass_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            pass_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "gpg") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Turn one decrypted pass file into an entry, following pass conventions:
+/// the first line is the password, `key: value` lines after it name the
+/// username and URL, and everything else is kept as the note
+fn parse_pass_entry(plaintext: &str) -> Entry {
+    let mut lines = plaintext.lines();
+    let password = lines.next().unwrap_or_default().to_string();
+    let mut username = None;
+    let mut email = None;
+    let mut url = None;
+    let mut totp = None;
+    let mut note = Vec::new();
+
+    for line in lines {
+        if line.trim_start().starts_with("otpauth://") {
+            totp = line.split(['?', '&'])
+                .find_map(|param| param.strip_prefix("secret="))
+                .map(str::to_string);
+            continue;
+        }
+        let field = line.split_once(':').map(|(key, value)| (key.trim().to_lowercase(), value.trim()));
+        match field {
+            Some((key, value)) if matches!(key.as_str(), "user" | "username" | "login") && username.is_none() => {
+                username = Some(value.to_string());
+            }
+            Some((key, value)) if key == "email" && email.is_none() => email = Some(value.to_string()),
+            Some((key, value)) if matches!(key.as_str(), "url" | "website" | "site") && url.is_none() => {
+                url = Some(value.to_string());
+            }
+            _ => note.push(line.to_string()),
+        }
+    }
+
+    // An email is the login only when nothing else is; otherwise keep it in the note
+    let username = match (username, email) {
+        (Some(username), Some(email)) => {
+            note.insert(0, format!("email: {}", email));
+            username
+        }
+        (username, email) => username.or(email).unwrap_or_default(),
+    };
+    let note = note.join("\n").trim().to_string();
+    let mut entry = Entry::new(username, password, (!note.is_empty()).then_some(note));
+    entry.url = url;
+    if let Some(secret) = totp {
+        entry.totp_secret = crate::secure_types::OptionalSecret::some(secret);
+    }
+    entry
+}
+
+/// Decrypt and parse every entry of a password store. The id is the file's
+/// path inside the store without `.gpg` ("work/github"); its directory
+/// becomes a tag. Files that fail to decrypt are reported, not fatal.
+fn parse_pass_store(
+    store: &Path,
+    decrypt: &mut PassDecrypt,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<ParsedRows, Box<dyn std::error::Error>> {
+    if !store.is_dir() {
+        return Err(format!("{} is not a password store directory", store.display()).into());
+    }
+    let mut files = Vec::new();
+    pass_files(store, &mut files)?;
+    files.sort();
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for (index, path) in files.iter().enumerate() {
+        progress(index, files.len());
+        let relative = path.strip_prefix(store).unwrap_or(path).with_extension("");
+        let id = relative.components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let plaintext = match decrypt(path) {
+            Ok(plaintext) => plaintext,
+            Err(reason) => {
+                errors.push(ImportRowError { row: index + 1, line: None, reason: format!("{}: {}", id, reason) });
+                continue;
+            }
+        };
+        let Ok(text) = std::str::from_utf8(&plaintext) else {
+            errors.push(ImportRowError { row: index + 1, line: None, reason: format!("{}: not UTF-8 text", id) });
+            continue;
+        };
+
+        let mut entry = parse_pass_entry(text);
+        if let Some((group, _)) = id.rsplit_once('/') {
+            entry.tags.push(group.to_string());
+        }
+        rows.push((id, entry));
+    }
+    progress(files.len(), files.len());
+    Ok((rows, errors))
+}
+
+/// Parse a file the way `profile` describes, tag every row with the
+/// profile's batch tags and record it as created by `import:<format>`
+/// unless the file names a creator. `progress` is only called for password stores.
+fn parse_with_profile(
+    input_path: &str,
+    profile: &ImportProfile,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<ParsedRows, Box<dyn std::error::Error>> {
+    let format = profile.format.to_lowercase();
+    let (mut rows, errors) = match format.as_str() {
+        "pass" => parse_pass_store(Path::new(input_path), &mut gpg_decrypt, progress)?,
+        format => {
+            let contents = std::fs::read_to_string(input_path)?;
+            match format {
+                "json" => parse_json(&contents)?,
+                "csv" => parse_csv_with(&contents, profile),
+                browser @ ("chrome" | "firefox") => parse_browser(&contents, browser)?,
+                "keepass" | "keepass-xml" => parse_keepass_xml(&contents)?,
+                other => return Err(format!("Unsupported import format: {}. Use 'json', 'csv', 'chrome', 'firefox', 'keepass' or 'pass'.", other).into()),
+            }
+        }
+    };
+
+    let tags = profile.batch_tags(chrono::Local::now().date_naive());
+    for (id, entry) in &mut rows {
+        if profile.normalize_ids {
+            *id = normalize_id(id);
+        }
+        entry.created_by.get_or_insert_with(|| format!("import:{}", format));
+        for tag in &tags {
+            if !entry.tags.contains(tag) {
+                entry.tags.push(tag.clone());
+            }
+        }
+    }
+    Ok((rows, errors))
+}
+
+/// Password and note to write for an entry; `None` for a high-security
+/// entry that wasn't opened for the export
+fn exported_secrets(id: &str, entry: &Entry, opened: &OpenedEntries) -> Option<(String, Option<String>)> {
+    if !entry.is_sealed() {
+        return Some((entry.password_str().reveal().to_string(), entry.note.clone()));
+    }
+    let secrets = opened.get(id)?;
+    Some((secrets.password.to_string(), secrets.note.as_ref().map(|note| note.to_string())))
+}
+
+fn print_left_out(left_out: usize) {
+    if left_out > 0 {
+        println!("  {} high-security entr{} left out", left_out, if left_out == 1 { "y" } else { "ies" });
+    }
+}
+
+/// An entry on its way into a KeePass export, with its secrets opened
+struct KeePassRow<'a> {
+    id: &'a str,
+    entry: &'a Entry,
+    password: String,
+    note: Option<String>,
+}
+
+/// A group of a KeePass export: its entries and subgroups by name
+#[derive(Default)]
+struct KeePassGroup<'a> {
+    entries: Vec<KeePassRow<'a>>,
+    groups: BTreeMap<String, KeePassGroup<'a>>,
+}
+
+/// Where an entry goes in a KeePass export: the folders of a `/`-path id
+/// ("work/github" goes in work), else its first tag, split the same way
+fn keepass_group_path(id: &str, entry: &Entry) -> Vec<String> {
+    let folders = match id.rsplit_once('/') {
+        Some((folders, _)) => folders,
+        None => entry.tags.first().map(String::as_str).unwrap_or_default(),
+    };
+    folders.split('/')
+        .map(str::trim)
+        .filter(|folder| !folder.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// A fresh KeePass UUID: 16 random bytes, base64
+fn keepass_uuid() -> String {
+    Base64::encode_string(&rand::random::<[u8; 16]>())
+}
+
+fn write_keepass_group(xml: &mut String, name: &str, group: &KeePassGroup, depth: usize) {
+    use std::fmt::Write as _;
+    let pad = "\t".repeat(depth);
+    let _ = writeln!(xml, "{pad}<Group>\n{pad}\t<UUID>{}</UUID>\n{pad}\t<Name>{}</Name>", keepass_uuid(), xml_escape(name));
+    for row in &group.entries {
+        write_keepass_entry(xml, row, depth + 1);
+    }
+    for (name, subgroup) in &group.groups {
+        write_keepass_group(xml, name, subgroup, depth + 1);
+    }
+    let _ = writeln!(xml, "{pad}</Group>");
+}
+
+fn write_keepass_entry(xml: &mut String, row: &KeePassRow, depth: usize) {
+    use std::fmt::Write as _;
+    let pad = "\t".repeat(depth);
+    let entry = row.entry;
+    let time = |at: chrono::DateTime<chrono::Utc>| at.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+    let _ = writeln!(xml, "{pad}<Entry>\n{pad}\t<UUID>{}</UUID>", keepass_uuid());
+    let _ = writeln!(xml, "{pad}\t<Tags>{}</Tags>", xml_escape(&entry.tags.join(";")));
+    let _ = writeln!(xml, "{pad}\t<Times>");
+    let _ = writeln!(xml, "{pad}\t\t<CreationTime>{}</CreationTime>", time(entry.created_at));
+    let _ = writeln!(xml, "{pad}\t\t<LastModificationTime>{}</LastModificationTime>", time(entry.modified_at));
+    let _ = writeln!(xml, "{pad}\t\t<LastAccessTime>{}</LastAccessTime>", time(entry.last_used_at.unwrap_or(entry.modified_at)));
+    let _ = writeln!(xml, "{pad}\t\t<ExpiryTime>{}</ExpiryTime>", time(entry.expires_at.unwrap_or(entry.modified_at)));
+    let _ = writeln!(xml, "{pad}\t\t<Expires>{}</Expires>", if entry.expires_at.is_some() { "True" } else { "False" });
+    let _ = writeln!(xml, "{pad}\t\t<UsageCount>{}</UsageCount>", entry.use_count);
+    let _ = writeln!(xml, "{pad}\t\t<LocationChanged>{}</LocationChanged>", time(entry.modified_at));
+    let _ = writeln!(xml, "{pad}\t</Times>");
+
+    let fields = [
+        ("Notes", row.note.as_deref().unwrap_or_default()),
+        ("Password", row.password.as_str()),
+        ("Title", row.id),
+        ("URL", entry.url.as_deref().unwrap_or_default()),
+        ("UserName", entry.username.as_str()),
+    ];
+    for (key, value) in fields {
+        let protect = if key == "Password" { " ProtectInMemory=\"True\"" } else { "" };
+        let _ = writeln!(
+            xml,
+            "{pad}\t<String>\n{pad}\t\t<Key>{}</Key>\n{pad}\t\t<Value{}>{}</Value>\n{pad}\t</String>",
+            key, protect, xml_escape(value)
+        );
+    }
+    let _ = writeln!(xml, "{pad}</Entry>");
+}
+
+pub struct ImportExportManager;
+
+impl ImportExportManager {
+    /// Export vault to JSON format. High-security entries are left out
+    /// unless `opened` holds their decrypted fields.
+    pub fn export_json(
+        vault: &Vault,
+        output_path: &str,
+        opened: &OpenedEntries,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let entries: Vec<ExportEntry> = vault
+            .list_entries()
+            .iter()
+            .filter_map(|id| {
+                let entry = vault.get_entry(id)?;
+                let (password, note) = exported_secrets(id, entry, opened)?;
+                Some(ExportEntry {
+                    id: id.to_string(),
+                    username: entry.username.clone(),
+                    password,
+                    note,
+                    url: entry.url.clone(),
+                    created_at: entry.created_at,
+                    last_changed: entry.modified_at,
+                    icon: entry.icon.clone(),
+                    expires_at: entry.expires_at,
+                    created_by: entry.created_by.clone(),
+                    modified_by: entry.modified_by.clone(),
+                    archived: entry.archived,
+                    copy_sequence: entry.copy_sequence.clone(),
+                    generation: entry.generation.clone(),
+                    linked_ids: entry.linked_ids.clone(),
+                    attachments: entry.attachments.clone(),
+                })
+            })
+            .collect();
+
+        let export_data = ExportData {
+            version: "1.0".to_string(),
+            exported_at: chrono::Utc::now(),
+            entries,
+        };
+
+        let json = serde_json::to_string_pretty(&export_data)?;
+        let mut file = create_private(output_path)?;
+        file.write_all(json.as_bytes())?;
+
+        println!("✓ Exported {} entries to {}", export_data.entries.len(), output_path);
+        print_left_out(vault.len() - export_data.entries.len());
+        Ok(())
+    }
+
+    /// Export vault to CSV format. High-security entries are left out
+    /// unless `opened` holds their decrypted fields.
+    pub fn export_csv(
+        vault: &Vault,
+        output_path: &str,
+        opened: &OpenedEntries,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = create_private(output_path)?;
+        writeln!(file, "id,username,password,note,url")?;
+
+        let mut count = 0;        for id in vault.list_entries() {
+            if let Some(entry) = vault.get_entry(id) {
+                let Some((password, note)) = exported_secrets(id, entry, opened) else {
+                    continue;
+                };
+                let note = note.as_deref().unwrap_or("");
+                let url = entry.url.as_deref().unwrap_or("");
+                writeln!(
+                    file,
+                    "\"{}\",\"{}\",\"{}\",\"{}\",\"{}\"",
+                    id.replace("\"", "\"\""),
+                    entry.username.replace("\"", "\"\""),
+                    password.replace("\"", "\"\""),
+                    note.replace("\"", "\"\""),
+                    url.replace("\"", "\"\"")
+                )?;
+                count += 1;
+            }
+        }
+
+        println!("✓ Exported {} entries to {}", count, output_path);
+        print_left_out(vault.len() - count);
+        Ok(())
+    }
+
+    /// Export the vault encrypted under `key`, as a vault file that opens
+    /// with the same master password. High-security entries stay sealed.
+    pub fn export_encrypted(vault: &Vault, key: &VaultKey, output_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let data = VaultManager::encrypt_vault(vault, key)?;
+        let mut file = create_private(output_path)?;
+        file.write_all(&data)?;
+        file.sync_all()?;
+        println!("✓ Exported {} entries, encrypted, to {}", vault.len(), output_path);
+        Ok(())
+    }
+
+    /// Export vault to KeePass 2.x XML, which KeePass, KeePassXC and most
+    /// other password managers import. Entries are grouped by the folders
+    /// of a `/`-path id, else by their first tag. High-security entries
+    /// are left out unless `opened` holds their decrypted fields.
+    pub fn export_keepass_xml(
+        vault: &Vault,
+        output_path: &str,
+        opened: &OpenedEntries,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut ids = vault.list_entries();
+        ids.sort();
+
+        let mut top = KeePassGroup::default();
+        let mut count = 0;
+        for id in ids {
+            let Some(entry) = vault.get_entry(id) else {
+                continue;
+            };
+            let Some((password, note)) = exported_secrets(id, entry, opened) else {
+                continue;
+            };
+            let group = keepass_group_path(id, entry)
+                .into_iter()
+                .fold(&mut top, |group, name| group.groups.entry(name).or_default());
+            group.entries.push(KeePassRow { id, entry, password, note });
+            count += 1;
+        }
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\" standalone=\"yes\"?>\n<KeePassFile>\n");
+        xml.push_str("\t<Meta>\n\t\t<Generator>passman</Generator>\n\t\t<DatabaseName>Passman</DatabaseName>\n\t</Meta>\n\t<Root>\n");
+        write_keepass_group(&mut xml, "Passman", &top, 2);
+        xml.push_str("\t</Root>\n</KeePassFile>\n");
+
+        let mut file = create_private(output_path)?;
+        file.write_all(xml.as_bytes())?;
+
+        println!("✓ Exported {} entries to {}", count, output_path);
+        print_left_out(vault.len() - count);
+        Ok(())
+    }
+
+    /// Parse a file the way `profile` describes, without touching a vault.
+    /// `progress` gets (files done, total) for password stores.
+    pub fn parse(
+        input_path: &str,
+        profile: &ImportProfile,
+        progress: &mut dyn FnMut(usize, usize),
+    ) -> Result<ParsedRows, Box<dyn std::error::Error>> {
+        parse_with_profile(input_path, profile, progress)
+    }
+
+    /// In strict mode any row error aborts the import before anything is written
+    pub fn check_strict(errors: &[ImportRowError], strict: bool) -> Result<(), Box<dyn std::error::Error>> {
+        if strict && !errors.is_empty() {
+            return Err(format!(
+                "{} row(s) could not be imported, nothing was imported (first: {})",
+                errors.len(),
+                errors[0]
+            ).into());
+        }
+        Ok(())
+    }
+
+    /// Move usernames and URLs over `profile.max_field_chars` and notes
+    /// over `profile.max_note_chars` into attachments, so a pathological
+    /// row is kept without the GUI having to lay it out. Returns the rows
+    /// changed and their fields.
+    pub fn attach_oversize_fields(rows: &mut [(String, Entry)], profile: &ImportProfile) -> Vec<(String, Vec<&'static str>)> {
+        rows.iter_mut()
+            .filter_map(|(id, entry)| {
+                let moved = entry.move_oversize_to_attachments(profile.max_field_chars, profile.max_note_chars);
+                (!moved.is_empty()).then(|| (id.clone(), moved))
+            })
+            .collect()
+    }
+
+    /// One `Add` per parsed row, settling existing IDs by `conflict`
+    pub fn import_ops(rows: Vec<(String, Entry)>, conflict: ConflictPolicy) -> Vec<EntryOp> {
+        rows.into_iter()
+            .map(|(id, entry)| EntryOp::Add { id, entry, on_conflict: conflict })
+            .collect()
+    }
+
+    /// Summarise the batch an import ran, with the rows that didn't parse
+    pub fn import_report(result: &BatchResult, errors: Vec<ImportRowError>) -> ImportReport {
+        let mut report = ImportReport { errors, ..Default::default() };
+        for (id, outcome) in &result.outcomes {
+            match outcome {
+                OpOutcome::Skipped => {
+                    report.skipped.push(id.clone());
+                    continue;
+                }
+                OpOutcome::Replaced => report.replaced.push(id.clone()),
+                OpOutcome::Renamed { to } => report.renamed.push((id.clone(), to.clone())),
+                OpOutcome::Applied | OpOutcome::Failed { .. } => {}
+            }
+            report.imported += 1;
+        }
+        report
+    }
+
+    /// Merge a file into a vault that is already open, leaving saving to
+    /// the caller
+    pub fn import_into(
+        vault: &mut Vault,
+        input_path: &str,
+        profile: &ImportProfile,
+        strict: bool,
+    ) -> Result<ImportReport, Box<dyn std::error::Error>> {
+        let (mut rows, errors) = parse_with_profile(input_path, profile, &mut |_, _| {})?;
+        let moved = Self::attach_oversize_fields(&mut rows, profile);
+        let mut report = Self::merge_rows(vault, rows, errors, strict, profile.conflict)?;
+        report.record_attached(moved);
+        Ok(report)
+    }
+
+    fn merge_rows(
+        vault: &mut Vault,
+        rows: Vec<(String, Entry)>,
+        errors: Vec<ImportRowError>,
+        strict: bool,
+        conflict: ConflictPolicy,
+    ) -> Result<ImportReport, Box<dyn std::error::Error>> {
+        Self::check_strict(&errors, strict)?;
+        let result = apply_ops(vault, Self::import_ops(rows, conflict));
+        Ok(Self::import_report(&result, errors))
+    }
+
+    /// Create automatic backup before risky operations
+    pub fn create_auto_backup(master_password: &Zeroizing<String>, vault_file: Option<&str>) -> Result<String, Box<dyn std::error::Error>> {
+        VaultManager::create_backup(master_password, vault_file)
+    }    /// List available backup files
+    #[allow(dead_code)]
+    pub fn list_backups() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let mut backups = Vec::new();
+        
+        for entry in std::fs::read_dir(".")? {
+            let entry = entry?;
+            let path = entry.path();
+            if let Some(name) = path.file_name() {
+                if let Some(name_str) = name.to_str() {
+                    if name_str.contains(".backup.") && name_str.ends_with(".dat") {
+                        backups.push(name_str.to_string());
+                    }
+                }
+            }
+        }
+        
+        backups.sort_by(|a, b| b.cmp(a)); // Most recent first
+        Ok(backups)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pass_store_maps_paths_fields_and_failures() {
+        let store = tempfile::tempdir().unwrap();
+        let write = |path: &str, contents: &str| {
+            let path = store.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        };
+        // The fake decrypt below treats these as plaintext
+        write("work/github.gpg", "hunter2\nlogin: octocat\nemail: octo@example.com\nurl: https://github.com\nrecovery codes in the safe\n");
+        write("bank.gpg", "s3cret\nemail: me@example.com\notpauth://totp/bank?secret=JBSWY3DP&issuer=bank\n");
+        write("work/broken.gpg", "");
+        write(".git/objects/ignored.gpg", "nope");
+        write("work/readme.txt", "not an entry");
+
+        let mut decrypt = |path: &Path| {
+            let contents = std::fs::read(path).unwrap();
+            if contents.is_empty() {
+                Err("gpg: decryption failed: No secret key".to_string())
+            } else {
+                Ok(Zeroizing::new(contents))
+            }
+        };
+        let mut calls = Vec::new();
+        let (rows, errors) = parse_pass_store(store.path(), &mut decrypt, &mut |done, total| calls.push((done, total))).unwrap();
+
+        let ids: Vec<&str> = rows.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["bank", "work/github"]);
+        let github = &rows[1].1;
+        assert_eq!((github.username.as_str(), github.password_str().reveal()), ("octocat", "hunter2"));
+        assert_eq!(github.url.as_deref(), Some("https://github.com"));
+        assert_eq!(github.note.as_deref(), Some("email: octo@example.com\nrecovery codes in the safe"));
+        assert_eq!(github.tags, vec!["work"]);
+        let bank = &rows[0].1;
+        assert_eq!(bank.username, "me@example.com");
+        assert_eq!(bank.totp_secret_str(), Some("JBSWY3DP"));
+        assert!(bank.tags.is_empty() && bank.note.is_none());
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].reason.starts_with("work/broken: gpg: decryption failed"));
+        assert_eq!(calls.first(), Some(&(0, 3)));
+        assert_eq!(calls.last(), Some(&(3, 3)));
+    }
+
+    #[test]
+    fn test_csv_bad_rows_are_reported_and_skipped() {
+        let csv = "id,username,password,note,url\n\
+                   github,alice,pw1,,https://github.com\n\
+                   broken,bob\n\
+                   ,carol,pw3,,\n\
+                   gitlab,dave,pw4,note,\n";
+        let (rows, errors) = parse_csv(csv);
+
+        let ids: Vec<&str> = rows.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["github", "gitlab"]);
+        assert_eq!(rows[0].1.url.as_deref(), Some("https://github.com"));
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].line, Some(3));
+        assert_eq!(errors[0].reason, "expected 5 fields, found 2");
+        assert_eq!(errors[1].line, Some(4));
+        assert_eq!(errors[1].reason, "empty id");
+        assert_eq!(errors[1].to_string(), "line 4: empty id");
+    }
+
+    #[test]
+    fn test_csv_missing_column_fails_each_row() {
+        let (rows, errors) = parse_csv("name,login\nsite,alice\n");
+        assert!(rows.is_empty());
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].reason.contains("password"), "{}", errors[0].reason);
+    }
+
+    #[test]
+    fn test_csv_profile_maps_columns_and_builds_ids() {
+        let mut profile = ImportProfile::new("csv");
+        profile.delimiter = ImportProfile::parse_delimiter("tab").unwrap();
+        profile.columns.insert("username".into(), "Login Name".into());
+        profile.columns.insert("password".into(), "Secret".into());
+        profile.id_template = "{System}/{Login Name}".into();
+        let csv = "System\tLogin Name\tlogin\tSecret\n\
+                   crm\tann\tignored\tpw1\n\
+                   \t\t\tpw2\n";
+        let (rows, errors) = parse_csv_with(csv, &profile);
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].0, "crm/ann");
+        assert_eq!((rows[0].1.username.as_str(), rows[0].1.password_str().reveal()), ("ann", "pw1"));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].reason, "empty id");
+
+        profile.columns.insert("url".into(), "Address".into());
+        let (rows, errors) = parse_csv_with(csv, &profile);
+        assert!(rows.is_empty());
+        assert_eq!(errors[0].reason, "no column 'Address' to map to url");
+        assert!(ImportProfile::parse_delimiter(";;").is_err());
+    }
+
+    #[test]
+    fn test_imported_entries_record_their_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("it.csv");
+        std::fs::write(&path, "id,username,password\ncrm,ann,pw\n").unwrap();
+        let (rows, _) = parse_with_profile(path.to_str().unwrap(), &ImportProfile::new("csv"), &mut |_, _| {}).unwrap();
+        assert_eq!(rows[0].1.created_by.as_deref(), Some("import:csv"));
+        assert_eq!(rows[0].1.modified_by, None);
+    }
+
+    #[test]
+    fn test_normalize_id_tidies_messy_exports() {
+        let cases = [
+            ("accounts.google.com", "accounts.google.com"),
+            ("WWW.EXAMPLE.COM", "example.com"),
+            ("  www.Amazon.co.uk  ", "amazon.co.uk"),
+            ("https://Accounts.Google.com_ann", "accounts.google.com_ann"),
+            ("HTTP://www.example.com/", "example.com"),
+            ("Example.com (work)", "example.com (work)"),
+            ("my   bank\taccount", "My Bank Account"),
+            ("bank of the west", "Bank of the West"),
+            ("the guardian", "The Guardian"),
+            ("iCloud mail", "iCloud Mail"),
+            ("IBM portal", "IBM Portal"),
+            ("github", "github"),
+            ("GitHub", "GitHub"),
+            ("Work / my bank", "Work/My Bank"),
+            ("work//github", "work/github"),
+            ("ann.smith@gmail.com", "ann.smith@gmail.com"),
+            ("www.com", "www.com"),
+            ("192.168.1.1", "192.168.1.1"),
+            ("v1.2 notes", "V1.2 Notes"),
+            ("line\nbreak", "Line Break"),
+            ("/", "/"),
+        ];
+        let vault = Vault::new();
+        for (messy, tidy) in cases {
+            assert_eq!(normalize_id(messy), tidy, "{:?}", messy);
+            assert_eq!(normalize_id(tidy), tidy, "not idempotent for {:?}", tidy);
+            assert_eq!(vault.new_id_problem(&normalize_id(messy)), None, "{:?}", messy);
+        }
+
+        let rows: Vec<(String, Entry)> = ["WWW.EXAMPLE.COM", "github", "my bank"]
+            .iter()
+            .map(|id| (id.to_string(), Entry::new("u".into(), "pw".into(), None)))
+            .collect();
+        assert_eq!(id_normalizations(&rows), vec![
+            ("WWW.EXAMPLE.COM".to_string(), "example.com".to_string()),
+            ("my bank".to_string(), "My Bank".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_import_normalizes_ids_unless_turned_off() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chrome.csv");
+        std::fs::write(&path, "name,url,username,password\nWWW.EXAMPLE.COM,https://www.example.com/,ann,pw\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        let mut profile = ImportProfile::new("chrome");
+        let (rows, _) = parse_with_profile(path, &profile, &mut |_, _| {}).unwrap();
+        assert_eq!(rows[0].0, "example.com");
+        profile.normalize_ids = false;
+        let (rows, _) = parse_with_profile(path, &profile, &mut |_, _| {}).unwrap();
+        assert_eq!(rows[0].0, "WWW.EXAMPLE.COM");
+
+        let saved: ImportProfile = toml::from_str("format = \"csv\"").unwrap();
+        assert!(saved.normalize_ids);
+    }
+
+    #[test]
+    fn test_profile_tags_and_conflict_policies() {
+        let mut profile = ImportProfile::new("csv");
+        profile.tags = vec!["import:{month}".into(), "it {date}".into(), " ".into()];
+        let today = chrono::NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        assert_eq!(profile.batch_tags(today), vec!["import:2024-06", "it 2024-06-03"]);
+
+        let rows = || vec![("github".to_string(), Entry::new("new".into(), "pw".into(), None))];
+        let mut vault = Vault::new();
+        vault.add_entry("github".into(), Entry::new("old".into(), "pw".into(), None));
+
+        let report = ImportExportManager::merge_rows(&mut vault, rows(), Vec::new(), false, ConflictPolicy::Skip).unwrap();
+        assert_eq!((report.imported, report.skipped.len()), (0, 1));
+        let report = ImportExportManager::merge_rows(&mut vault, rows(), Vec::new(), false, ConflictPolicy::Rename).unwrap();
+        assert_eq!(report.renamed, vec![("github".to_string(), "github (copy)".to_string())]);
+        assert_eq!(vault.get_entry("github").unwrap().username, "old");
+        let report = ImportExportManager::merge_rows(&mut vault, rows(), Vec::new(), false, ConflictPolicy::Overwrite).unwrap();
+        assert_eq!(report.replaced, vec!["github"]);
+        assert_eq!(vault.get_entry("github").unwrap().username, "new");
+    }
+
+    #[test]
+    fn test_oversize_fields_are_attached_not_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stress.json");
+        let path = path.to_str().unwrap();
+        let stress = crate::sample::stress_vault(chrono::Utc::now());
+        ImportExportManager::export_json(&stress, path, &OpenedEntries::new()).unwrap();
+
+        let mut vault = Vault::new();
+        vault.add_entry("huge-note".into(), Entry::new("old".into(), "pw".into(), None));
+        let mut profile = ImportProfile::new("json");
+        profile.conflict = ConflictPolicy::Rename;
+        let report = ImportExportManager::import_into(&mut vault, path, &profile, true).unwrap();
+        assert_eq!(report.imported, stress.len());
+        let moved: BTreeMap<&str, &[&str]> = report.moved_to_attachments.iter().map(|(id, fields)| (id.as_str(), fields.as_slice())).collect();
+        assert_eq!(moved, BTreeMap::from([
+            ("huge-note (copy)", &["note"][..]),
+            ("long-url-and-tags", &["url"][..]),
+            ("long-username", &["username"][..]),
+        ]));
+
+        let entry = vault.get_entry("huge-note (copy)").unwrap();
+        assert_eq!(entry.attachments[0].content.len(), crate::sample::STRESS_NOTE_BYTES);
+        assert!(entry.note.as_ref().unwrap().starts_with("Moved to the attachment note.txt"));
+        assert_eq!(vault.get_entry("long-username").unwrap().username.chars().count(), DEFAULT_MAX_FIELD_CHARS);
+        // Attachments survive an export and import of their own
+        assert_eq!(vault.get_entry("large-attachment").unwrap().attachments, stress.get_entry("large-attachment").unwrap().attachments);
+
+        // A profile can raise the limits
+        let mut vault = Vault::new();
+        profile.max_field_chars = 100_000;
+        profile.max_note_chars = 4 * 1024 * 1024;
+        let report = ImportExportManager::import_into(&mut vault, path, &profile, true).unwrap();
+        assert!(report.moved_to_attachments.is_empty());
+        let saved: ImportProfile = toml::from_str("format = \"json\"").unwrap();
+        assert_eq!((saved.max_field_chars, saved.max_note_chars), (DEFAULT_MAX_FIELD_CHARS, DEFAULT_MAX_NOTE_CHARS));
+    }
+
+    #[test]
+    fn test_json_and_browser_bad_entries_are_reported() {
+        let now = chrono::Utc::now().to_rfc3339();
+        let json = format!(
+            r#"{{"version":"1.0","exported_at":"{now}","entries":[
+                {{"id":"a","username":"u","password":"p","note":null,"url":null,"created_at":"{now}","last_changed":"{now}"}},
+                {{"id":"b","username":"u"}}
+            ]}}"#
+        );
+        let (rows, errors) = parse_json(&json).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].row, 2);
+        assert!(errors[0].to_string().starts_with("entry 2: missing field"));
+        assert!(parse_json("{not json").is_err());
+
+        let chrome = r#"{"passwords":[
+            {"origin":"https://a.com","username":"u","password":"p"},
+            {"origin":"https://b.com","username":"u"}
+        ]}"#;
+        let (rows, errors) = parse_browser(chrome, "chrome").unwrap();
+        assert_eq!(rows[0].0, "https://a.com_u");
+        assert_eq!(errors, vec![ImportRowError { row: 2, line: None, reason: "missing password".into() }]);
+    }
+
+    #[test]
+    fn test_chrome_csv_old_new_and_android_exports() {
+        let summary = |(rows, errors): ParsedRows| {
+            assert!(errors.is_empty(), "{:?}", errors);
+            rows.into_iter()
+                .map(|(id, entry)| (id, entry.username.clone(), entry.url.clone(), entry.note.clone(), entry.tags.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        let old = summary(parse_chrome_csv(include_str!("../tests/fixtures/chrome_passwords_old.csv")));
+        assert_eq!(old.iter().map(|row| row.0.as_str()).collect::<Vec<_>>(), vec!["github.com", "mail.example.com", "bank.example.org"]);
+        assert_eq!(old[0].2.as_deref(), Some("https://github.com/login"));
+        assert!(old.iter().all(|row| row.3.is_none()));
+
+        let (rows, errors) = parse_chrome_csv(include_str!("../tests/fixtures/chrome_passwords_new.csv"));
+        assert!(errors.is_empty());
+        assert_eq!(rows[2].1.password_str(), "pa\"ss,word");
+        let new = summary((rows, errors));
+        assert_eq!(new.iter().map(|row| row.0.as_str()).collect::<Vec<_>>(), vec!["github.com", "github.com (work-bot)", "shop.example.co.uk"]);
+        assert_eq!(new[0].3.as_deref(), Some("Recovery codes in the safe.\nSecond line of the note, with a comma."));
+        assert_eq!(new[1].3, None);
+        assert_eq!(new[2].3.as_deref(), Some("Loyalty number 1234"));
+
+        let android = summary(parse_chrome_csv(include_str!("../tests/fixtures/chrome_passwords_android.csv")));
+        assert_eq!(android.iter().map(|row| row.0.as_str()).collect::<Vec<_>>(), vec!["Spotify", "com.example.bank", "com.example.bank (ann.work)"]);
+        assert_eq!(android[0].4, vec!["com.spotify.music"]);
+        assert!(android.iter().all(|row| row.2.is_none()));
+        assert_eq!(android[1].3.as_deref(), Some("PIN is separate"));
+
+        // Both forms go through the chrome importer
+        assert_eq!(parse_browser(include_str!("../tests/fixtures/chrome_passwords_old.csv"), "chrome").unwrap().0.len(), 3);
+        let (_, errors) = parse_chrome_csv("site,login\na,b\n");
+        assert_eq!(errors[0].row, 0);
+    }
+
+    #[test]
+    fn test_json_export_carries_icon_and_csv_omits_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut vault = Vault::new();
+        let mut entry = Entry::new("u".to_string(), "p".to_string(), None);
+        entry.icon = Some(EntryIcon::png(b"\x89PNG\r\n\x1a\nabc".to_vec()).unwrap());
+        vault.add_entry("site".to_string(), entry);
+
+        let json_path = dir.path().join("export.json");
+        ImportExportManager::export_json(&vault, json_path.to_str().unwrap(), &OpenedEntries::new()).unwrap();
+        let json = std::fs::read_to_string(&json_path).unwrap();
+        assert!(json.contains("\"png\": \"iVBORw0KGgphYmM=\""), "{}", json);
+        let (rows, _) = parse_json(&json).unwrap();
+        assert_eq!(rows[0].1.icon, vault.get_entry("site").unwrap().icon);
+        assert!(!json.contains("archived"));
+
+        assert!(vault.set_archived("site", true, chrono::Utc::now(), None));
+        ImportExportManager::export_json(&vault, json_path.to_str().unwrap(), &OpenedEntries::new()).unwrap();
+        let (rows, _) = parse_json(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert!(rows[0].1.archived);
+        assert!(rows[0].1.copy_sequence.is_empty());
+
+        let mut entry = vault.get_entry("site").unwrap().clone();
+        entry.copy_sequence = vec![CopyStep::Custom { name: "company".to_string(), value: "ACME".to_string() }, CopyStep::Password];
+        vault.add_entry("site".to_string(), entry);
+        ImportExportManager::export_json(&vault, json_path.to_str().unwrap(), &OpenedEntries::new()).unwrap();
+        let (rows, _) = parse_json(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(rows[0].1.copy_sequence, vault.get_entry("site").unwrap().copy_sequence);
+        assert!(rows[0].1.generation.is_none());
+
+        let mut entry = vault.get_entry("site").unwrap().clone();
+        entry.generation = Some(GenerationPrefs::random(24, crate::utils::PasswordConfig { excluded_chars: "<>".to_string(), ..Default::default() }));
+        vault.add_entry("site".to_string(), entry);
+        ImportExportManager::export_json(&vault, json_path.to_str().unwrap(), &OpenedEntries::new()).unwrap();
+        let (rows, _) = parse_json(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(rows[0].1.generation, vault.get_entry("site").unwrap().generation);
+
+        let csv_path = dir.path().join("export.csv");
+        ImportExportManager::export_csv(&vault, csv_path.to_str().unwrap(), &OpenedEntries::new()).unwrap();
+        let csv = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(!csv.contains("iVBOR"));
+    }
+
+    #[test]
+    fn test_keepass_xml_round_trip_keeps_fields_and_groups() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut vault = Vault::new();
+        let mut github = Entry::new("octo <cat>".to_string(), "p&ss\"w'rd<>".to_string(), Some("line one\r\nline two & more".to_string()));
+        github.url = Some("https://github.com/login?a=1&b=2".to_string());
+        github.created_at = "2023-04-05T06:07:08Z".parse().unwrap();
+        github.modified_at = "2024-01-02T03:04:05Z".parse().unwrap();
+        github.expires_at = Some("2025-06-01T00:00:00Z".parse().unwrap());
+        vault.add_entry("work/dev/github".to_string(), github);
+        let mut forum = Entry::new("me".to_string(), "forum-pw".to_string(), None);
+        forum.tags = vec!["hobby".to_string(), "games".to_string()];
+        vault.add_entry("forum".to_string(), forum);
+        vault.add_entry("loose".to_string(), Entry::new("x".to_string(), "y\u{7}z".to_string(), None));
+
+        let path = dir.path().join("export.xml");
+        let path = path.to_str().unwrap();
+        ImportExportManager::export_keepass_xml(&vault, path, &OpenedEntries::new()).unwrap();
+        let xml = std::fs::read_to_string(path).unwrap();
+        assert!(xml.contains("<Name>work</Name>") && xml.contains("<Name>dev</Name>") && xml.contains("<Name>hobby</Name>"), "{}", xml);
+        assert!(xml.contains("p&amp;ss&quot;w&apos;rd&lt;&gt;") && !xml.contains("<cat>") && !xml.contains('\u{7}'));
+
+        let (rows, errors) = parse_keepass_xml(&xml).unwrap();
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(rows.len(), 3);
+        for (id, imported) in &rows {
+            let original = vault.get_entry(id).unwrap();
+            let password = original.password_str().reveal().replace('\u{7}', "");
+            assert_eq!((imported.username.as_str(), imported.password_str().reveal()), (original.username.as_str(), password.as_str()));
+            assert_eq!((&imported.note, &imported.url, &imported.tags), (&original.note, &original.url, &original.tags), "{}", id);
+            assert_eq!(imported.created_at.timestamp(), original.created_at.timestamp());
+            assert_eq!(imported.modified_at.timestamp(), original.modified_at.timestamp());
+            assert_eq!(imported.expires_at, original.expires_at);
+        }
+    }
+
+    #[test]
+    fn test_keepass_xml_import_skips_history_and_recycle_bin() {
+        let (rows, errors) = parse_keepass_xml(include_str!("../tests/fixtures/keepass_export.xml")).unwrap();
+        let ids: Vec<&str> = rows.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["Bank", "mail.example.com"]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].reason, "no title or URL to build an id from");
+
+        let bank = &rows[0].1;
+        assert_eq!(bank.password_str().reveal(), "cur<rent>pw");
+        assert_eq!(bank.note.as_deref(), Some("PIN is in the safe & the card is blue"));
+        assert_eq!(bank.tags, ["finance", "important"]);
+        assert_eq!(bank.expires_at, Some("2026-01-01T00:00:00Z".parse().unwrap()));
+        let mail = &rows[1].1;
+        assert_eq!((mail.username.as_str(), mail.password_str().reveal()), ("jdoe@example.com", "mail!pw"));
+        assert_eq!(mail.tags, ["Internet/Email"]);
+
+        assert!(parse_keepass_xml("<KeePassFile><Root>").is_err());
+        assert!(parse_keepass_xml("<Database/>").is_err());
+    }
+
+    #[test]
+    fn test_xml_attributes_and_escapes() {
+        let root = parse_xml(concat!(
+            "<?xml version=\"1.0\"?>\n<!-- <Fake> -->\n",
+            "<a note=\"x > y &amp; &quot;z&quot;\" empty=''><b id=\"1\"/>",
+            "t&lt;e&#x78;t &amp; <![CDATA[<raw> &amp;]]></a>",
+        )).unwrap();
+        assert_eq!(root.attribute("note"), Some("x > y & \"z\""));
+        assert_eq!(root.attribute("empty"), Some(""));
+        assert_eq!(root.child("b").and_then(|b| b.attribute("id")), Some("1"));
+        assert_eq!(root.text, "t<ext & <raw> &amp;");
+
+        assert!(parse_xml("<a>&bogus;</a>").is_err());
+        assert!(parse_xml("<a><b></a></b>").is_err());
+        assert!(parse_xml("<a x=\"1>").is_err());
+
+        let kdbx_inner = concat!(
+            "<KeePassFile><Root><Group><Name>db</Name><Entry>",
+            "<String><Key>Title</Key><Value>bank</Value></String>",
+            "<String><Key>Password</Key><Value Protected=\"True\">q83vEjRWeJA=</Value></String>",
+            "</Entry></Group></Root></KeePassFile>",
+        );
+        let (rows, errors) = parse_keepass_xml(kdbx_inner).unwrap();
+        assert!(rows.is_empty());
+        assert_eq!(errors[0].reason, "Password is encrypted; export the database from KeePass as XML");
+    }
+
+    #[test]
+    fn test_export_risk_spots_sync_folders() {
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("export.json");
+        assert_eq!(export_risk(plain.to_str().unwrap()), None);
+
+        let dropbox = dir.path().join("Dropbox").join("keys");
+        std::fs::create_dir_all(&dropbox).unwrap();
+        let risk = export_risk(dropbox.join("export.json").to_str().unwrap()).unwrap();
+        assert_eq!(risk, ExportRisk { sync_service: Some("Dropbox"), readable_by_others: false });
+        assert!(risk.message().contains("uploaded to Dropbox unencrypted"), "{}", risk.message());
+
+        // A renamed Dropbox folder is still found by its cache folder
+        let renamed = dir.path().join("work-sync");
+        std::fs::create_dir_all(renamed.join(".dropbox.cache")).unwrap();
+        std::fs::create_dir_all(renamed.join("exports")).unwrap();
+        let risk = export_risk(renamed.join("exports").join("out.csv").to_str().unwrap()).unwrap();
+        assert_eq!(risk.sync_service, Some("Dropbox"));
+
+        // Nothing to say about a folder that doesn't exist; the export fails there anyway
+        assert_eq!(export_risk(dir.path().join("missing").join("out.csv").to_str().unwrap()), None);
+        assert!(std::fs::read_dir(dir.path()).unwrap().all(|entry| !entry.unwrap().file_name().to_string_lossy().contains("probe")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exports_are_private_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut vault = Vault::new();
+        vault.add_entry("github".to_string(), Entry::new("ann".to_string(), "pw".to_string(), None));
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        let json = dir.path().join("export.json");
+        ImportExportManager::export_json(&vault, json.to_str().unwrap(), &OpenedEntries::new()).unwrap();
+        assert_eq!(mode(&json), 0o600);
+
+        // Overwriting a world-readable file tightens it
+        let csv = dir.path().join("export.csv");
+        std::fs::write(&csv, "old").unwrap();
+        std::fs::set_permissions(&csv, std::fs::Permissions::from_mode(0o644)).unwrap();
+        ImportExportManager::export_csv(&vault, csv.to_str().unwrap(), &OpenedEntries::new()).unwrap();
+        assert_eq!(mode(&csv), 0o600);
+        assert!(!private_files_readable_by_others(dir.path()));
+    }
+
+    #[test]
+    fn test_encrypted_export_opens_with_the_master_password() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.dat");
+        let password = Zeroizing::new("correct horse".to_string());
+        let key = VaultManager::init(&password, Some(vault_path.to_str().unwrap())).unwrap();
+        let mut vault = Vault::new();
+        vault.add_entry("github".to_string(), Entry::new("ann".to_string(), "hunter2".to_string(), None));
+
+        let export = dir.path().join("copy.dat");
+        ImportExportManager::export_encrypted(&vault, &key, export.to_str().unwrap()).unwrap();
+        assert!(!String::from_utf8_lossy(&std::fs::read(&export).unwrap()).contains("hunter2"));
+        let opened = VaultManager::load(&password, Some(export.to_str().unwrap())).unwrap();
+        assert_eq!(opened.get_entry("github").unwrap().username, "ann");
+    }
+
+    #[test]
+    fn test_exports_leave_out_sealed_entries_unless_opened() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut vault = Vault::new();
+        vault.add_entry("forum".to_string(), Entry::new("u".to_string(), "forum-pw".to_string(), None));
+        let mut bank = Entry::new("me".to_string(), "bank-pw".to_string(), Some("seed words".to_string()));
+        bank.seal("1234").unwrap();
+        vault.add_entry("bank".to_string(), bank);
+        let csv_path = dir.path().join("export.csv");
+        let csv_path = csv_path.to_str().unwrap();
+
+        ImportExportManager::export_csv(&vault, csv_path, &OpenedEntries::new()).unwrap();
+        let csv = std::fs::read_to_string(csv_path).unwrap();
+        assert!(csv.contains("forum-pw") && !csv.contains("\"bank\""), "{}", csv);
+
+        let (opened, failed) = crate::sealed::open_all(&vault, "1234");
+        assert!(failed.is_empty());
+        ImportExportManager::export_csv(&vault, csv_path, &opened).unwrap();
+        let csv = std::fs::read_to_string(csv_path).unwrap();
+        assert!(csv.contains("\"bank\",\"me\",\"bank-pw\",\"seed words\""), "{}", csv);
+
+        let (opened, failed) = crate::sealed::open_all(&vault, "9999");
+        assert_eq!((opened.len(), failed), (0, vec!["bank".to_string()]));
+    }
+
+    #[test]
+    fn test_entry_stream_keeps_line_numbers_and_reports_bad_lines() {
+        let stream = concat!(
+            "{\"id\": \"github\", \"username\": \"octo\", \"password\": \"pw1\", \"tags\": [\"work\", \" \"]}\n",
+            "\n",
+            "{\"id\": \"mail\", \"username\": \"me\"\n",
+            "{\"id\": \" \", \"username\": \"x\", \"password\": \"pw\"}\n",
+            "{\"id\": \"bank\", \"username\": \"ann\", \"password\": \"pw3\", \"pin\": \"1234\"}\n",
+            "{\"id\": \"forum\", \"username\": \"ann\", \"password\": \"pw4\", \"note\": \"old account\"}",
+        );
+        let (rows, errors) = parse_entry_stream(stream.as_bytes()).unwrap();
+
+        let parsed: Vec<_> = rows.iter().map(|(line, id, entry)| (*line, id.as_str(), entry.tags.clone(), entry.note.clone())).collect();
+        assert_eq!(parsed, vec![
+            (1, "github", vec!["work".to_string()], None),
+            (6, "forum", vec![], Some("old account".to_string())),
+        ]);
+        assert_eq!(errors.iter().map(|e| e.line).collect::<Vec<_>>(), vec![Some(3), Some(4), Some(5)]);
+        assert_eq!(errors[1].to_string(), "line 4: empty id");
+        assert!(errors[0].reason.starts_with("EOF while parsing an object (column"), "{}", errors[0].reason);
+        assert!(errors[2].reason.contains("pin"), "{}", errors[2].reason);
+
+        assert_eq!("suffix".parse(), Ok(ConflictPolicy::Rename));
+        assert_eq!(" Overwrite".parse(), Ok(ConflictPolicy::Overwrite));
+        assert!("merge".parse::<ConflictPolicy>().is_err());
+    }
+}
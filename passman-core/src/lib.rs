@@ -0,0 +1,82 @@
+//! # Passman Core
+//!
+//! The vault format, cryptography, entry model and analysis behind the
+//! `passman` CLI and GUI. Nothing here touches a terminal, clipboard or
+//! window, so the crate can back other frontends (and builds for wasm32).
+//!
+//! The stable entry points are re-exported at the crate root:
+//!
+//! - [`PassmanCore`] — open, edit and save a vault file
+//! - [`EntryBuilder`] — construct entries with optional fields
+//! - [`VaultManager`] — lower-level file operations (load, save, backups)
+//! - [`PassmanError`] / [`PassmanResult`] — typed errors
+//!
+//! The modules stay public for callers that need the finer-grained pieces
+//! (sync bundles, sharing, vault diffs, search), but their layout may change
+//! between minor versions.
+//!
+//! ## Cargo features
+//!
+//! - `https`: [`net::NetPolicy::fetch_https`] and the update check in
+//!   [`update`], over ureq and rustls. Off by default, so the library makes
+//!   no HTTPS requests unless a frontend turns it on.
+//!
+//! ## Round trip
+//!
+//! ```
+//! use passman_core::{EntryBuilder, PassmanCore, VaultManager, Zeroizing};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let dir = tempfile::tempdir()?;
+//! let path = dir.path().join("vault.dat");
+//! let path = path.to_str().unwrap();
+//! let password = Zeroizing::new("Correct-Horse-Battery-42!".to_string());
+//!
+//! // init → add → save
+//! let mut core = PassmanCore::with_vault_path(path);
+//! core.init_vault(&password)?;
+//! core.add_entry("github", EntryBuilder::new("octocat").password("hunter2").tag("work").build())?;
+//! core.save()?;
+//!
+//! // load it back
+//! let vault = VaultManager::load(&password, Some(path))?;
+//! let entry = vault.get_entry("github").unwrap();
+//! assert_eq!(entry.username, "octocat");
+//! assert_eq!(entry.password_str(), "hunter2");
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod batch;
+pub mod bulk;
+pub mod changes;
+pub mod config;
+pub mod copy_sequence;
+pub mod core;
+pub mod crypto;
+pub mod diff;
+pub mod error;
+pub mod git_credential;
+pub mod health;
+pub mod import_export;
+pub mod lock_summary;
+pub mod model;
+pub mod net;
+pub mod paste;
+pub mod sample;
+pub mod sealed;
+pub mod search;
+pub mod secure_types;
+pub mod share;
+pub mod status;
+pub mod sync;
+pub mod update;
+pub mod url_match;
+pub mod utils;
+pub mod vault;
+
+pub use crate::core::{EntryBuilder, PassmanCore};
+pub use error::{AuthError, ConfigError, CryptoError, NetworkError, PassmanError, PassmanResult, TransferError, VaultError};
+pub use model::{Entry, Vault};
+pub use vault::VaultManager;
+pub use zeroize::Zeroizing;
@@ -0,0 +1,182 @@
+//! Lock Screen Summary
+//!
+//! The login screen can say "last opened 2 days ago · 134 entries · health
+//! 82%" before the vault is unlocked. The figures are taken while it is
+//! open and kept in a sidecar in the config directory, sealed with AES-GCM
+//! under a key derived from the vault id and a secret the frontend keeps in
+//! the OS keyring. Without that secret there is no summary: nothing about
+//! the vault is ever written in the clear here. A summary is only shown
+//! for the exact file it was taken from; once the vault is saved elsewhere
+//! (the CLI, a sync) it is dropped.
+
+use aes_gcm::aead::generic_array::GenericArray;
+use base64ct::{Base64Unpadded, Encoding};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::config::Config;
+use crate::crypto::{decrypt_data, encrypt_data, Key};
+use crate::vault::{VaultId, VaultManager};
+
+/// Sidecar in the config directory, keyed by vault fingerprint
+pub const SUMMARIES_FILE: &str = "lock_summaries.json";
+
+/// What the lock screen shows about a vault
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LockSummary {
+    pub last_opened: DateTime<Utc>,
+    pub entries: usize,
+    /// Overall health score, 0–100
+    pub health: u8,
+}
+
+/// A sealed summary as stored in the sidecar
+#[derive(Serialize, Deserialize)]
+struct SealedSummary {
+    nonce: String,
+    ciphertext: String,
+}
+
+/// The plaintext under the seal: the summary and the SHA-256 of the vault
+/// file it describes
+#[derive(Serialize, Deserialize)]
+struct Contents {
+    summary: LockSummary,
+    file_hash: String,
+}
+
+impl LockSummary {
+    /// One line for the lock screen, e.g. "Last opened 2 days ago · 134
+    /// entries · health 82%"
+    pub fn describe(&self, now: DateTime<Utc>) -> String {
+        format!(
+            "Last opened {} · {} entr{} · health {}%",
+            crate::utils::format_age(self.last_opened, now),
+            self.entries,
+            if self.entries == 1 { "y" } else { "ies" },
+            self.health
+        )
+    }
+
+    /// Seal this summary for the vault at `vault_path` as it is on disk now,
+    /// replacing any earlier one
+    pub fn store(&self, vault_path: &str, machine_secret: &[u8]) -> Result<(), String> {
+        store_in(&sidecar_path(), self, vault_path, machine_secret)
+    }
+
+    /// The summary of the vault at `vault_path`, if one was sealed with
+    /// `machine_secret` and the file hasn't changed since. A summary of an
+    /// older version of the file is removed.
+    pub fn load(vault_path: &str, machine_secret: &[u8]) -> Option<Self> {
+        load_from(&sidecar_path(), vault_path, machine_secret)
+    }
+}
+
+/// A fresh machine secret, for the frontend to keep in the OS keyring
+pub fn new_machine_secret() -> zeroize::Zeroizing<Vec<u8>> {
+    zeroize::Zeroizing::new(rand::random::<[u8; 32]>().to_vec())
+}
+
+fn sidecar_path() -> PathBuf {
+    Config::config_path().with_file_name(SUMMARIES_FILE)
+}
+
+/// Summary key of one vault on this machine
+fn summary_key(vault_id: &VaultId, machine_secret: &[u8]) -> Key {
+    let mut mac = Hmac::<Sha256>::new_from_slice(machine_secret).expect("HMAC can take key of any size");
+    mac.update(b"passman-lock-summary:");
+    mac.update(vault_id.as_bytes());
+    Key::new(GenericArray::clone_from_slice(&mac.finalize().into_bytes()))
+}
+
+fn file_hash(vault_path: &str) -> Result<String, String> {
+    let fingerprint = VaultManager::fingerprint(Some(vault_path)).map_err(|e| e.to_string())?;
+    Ok(fingerprint.hash.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+fn read_sidecar(path: &Path) -> HashMap<String, SealedSummary> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn write_sidecar(path: &Path, summaries: &HashMap<String, SealedSummary>) -> Result<(), String> {
+    let contents = serde_json::to_string_pretty(summaries).map_err(|e| e.to_string())?;
+    fs::write(path, contents).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+fn store_in(sidecar: &Path, summary: &LockSummary, vault_path: &str, machine_secret: &[u8]) -> Result<(), String> {
+    let vault_id = VaultManager::vault_id(Some(vault_path)).map_err(|e| e.to_string())?;
+    let contents = Contents { summary: summary.clone(), file_hash: file_hash(vault_path)? };
+    let plaintext = zeroize::Zeroizing::new(serde_json::to_vec(&contents).map_err(|e| e.to_string())?);
+    let (ciphertext, nonce) = encrypt_data(&summary_key(&vault_id, machine_secret), &plaintext).map_err(|e| e.to_string())?;
+
+    let mut summaries = read_sidecar(sidecar);
+    summaries.insert(vault_id.fingerprint(), SealedSummary {
+        nonce: Base64Unpadded::encode_string(&nonce),
+        ciphertext: Base64Unpadded::encode_string(&ciphertext),
+    });
+    write_sidecar(sidecar, &summaries)
+}
+
+fn load_from(sidecar: &Path, vault_path: &str, machine_secret: &[u8]) -> Option<LockSummary> {
+    let vault_id = VaultManager::vault_id(Some(vault_path)).ok()?;
+    let mut summaries = read_sidecar(sidecar);
+    let sealed = summaries.get(&vault_id.fingerprint())?;
+    let nonce: [u8; 12] = Base64Unpadded::decode_vec(&sealed.nonce).ok()?.try_into().ok()?;
+    let ciphertext = Base64Unpadded::decode_vec(&sealed.ciphertext).ok()?;
+    // Sealed under another machine secret (a reinstall, a copied config):
+    // left alone, the next lock replaces it
+    let plaintext = zeroize::Zeroizing::new(decrypt_data(&summary_key(&vault_id, machine_secret), &ciphertext, &nonce).ok()?);
+    let contents: Contents = serde_json::from_slice(&plaintext).ok()?;
+
+    if file_hash(vault_path).ok()? != contents.file_hash {
+        summaries.remove(&vault_id.fingerprint());
+        if let Err(e) = write_sidecar(sidecar, &summaries) {
+            log::warn!("Could not drop stale lock summary: {}", e);
+        }
+        return None;
+    }
+    Some(contents.summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Entry, Vault};
+    use zeroize::Zeroizing;
+
+    const SECRET: &[u8] = b"machine secret from the keyring";
+
+    #[test]
+    fn test_summary_is_sealed_and_dropped_when_the_vault_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let sidecar = dir.path().join(SUMMARIES_FILE);
+        let vault_path = dir.path().join("vault.dat").to_string_lossy().into_owned();
+        let key = VaultManager::init(&Zeroizing::new("correct horse".to_string()), Some(&vault_path)).unwrap();
+        let mut vault = Vault::new();
+        vault.add_entry("github".to_string(), Entry::new("octo".to_string(), "hunter2".to_string(), None));
+        VaultManager::save_with_key(&vault, &key, Some(&vault_path)).unwrap();
+
+        let summary = LockSummary { last_opened: Utc::now() - chrono::Duration::days(2), entries: 134, health: 82 };
+        assert!(load_from(&sidecar, &vault_path, SECRET).is_none());
+        store_in(&sidecar, &summary, &vault_path, SECRET).unwrap();
+
+        // Nothing readable in the sidecar, and only the same secret opens it
+        let stored = fs::read_to_string(&sidecar).unwrap();
+        assert!(!stored.contains("health") && !stored.contains("file_hash"));
+        assert_eq!(load_from(&sidecar, &vault_path, SECRET), Some(summary.clone()));
+        assert!(load_from(&sidecar, &vault_path, b"another machine").is_none());
+        assert_eq!(summary.describe(Utc::now()), "Last opened 2 days ago · 134 entries · health 82%");
+
+        // Saved since: the summary no longer describes the file
+        VaultManager::save_with_key(&vault, &key, Some(&vault_path)).unwrap();
+        assert!(load_from(&sidecar, &vault_path, SECRET).is_none());
+        assert!(read_sidecar(&sidecar).is_empty());
+    }
+}
@@ -0,0 +1,1285 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use crate::secure_types::{SerializableSecret, OptionalSecret, SecretView};
+use crate::copy_sequence::CopyStep;
+use crate::sealed::{SealError, SealedFields, SealedSecrets};
+use crate::utils::{
+    analyze_password_strength, generate_memorable_password, generate_password_with_config, generate_pronounceable_password,
+    PasswordConfig, PasswordStrength,
+};
+use crate::health::HealthRecord;
+
+const CURRENT_VERSION: u32 = 1;
+
+/// A password entry with secure memory handling for sensitive fields.
+/// 
+/// The `password` and `totp_secret` fields use secure types that:
+/// - Automatically zeroize memory on drop
+/// - Show [REDACTED] in debug output to prevent accidental logging
+/// - Require explicit access via `.expose_secret()` (or `.password_str().reveal()`)
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Entry {
+    pub username: String,
+    /// Password stored securely - auto-zeroizes on drop, Debug shows [REDACTED]
+    pub password: SerializableSecret,
+    pub note: Option<String>,
+    // New fields for enhanced functionality
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub modified_at: chrono::DateTime<chrono::Utc>,
+    pub tags: Vec<String>,
+    pub url: Option<String>,
+    /// TOTP secret stored securely - auto-zeroizes on drop
+    pub totp_secret: OptionalSecret,
+    /// Generation rules for this site, reused when regenerating the password
+    #[serde(default)]
+    pub generation: Option<GenerationPrefs>,
+    /// Icon shown instead of the generic key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<EntryIcon>,
+    /// When the password was last copied or revealed. Not a modification,
+    /// so `modified_at` is left alone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// How many times the password was copied or revealed
+    #[serde(default)]
+    pub use_count: u32,
+    /// Set while the entry sits in the trash. Trashed entries are left out
+    /// of search and health analysis unless a caller asks for them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trashed_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Day the credential itself stops working (certificates, temporary
+    /// accounts); unrelated to password age
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Who added the entry (`general.identity`, or `import:<format>`).
+    /// Advisory only; nothing is enforced.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+    /// Who changed the entry last, from `general.identity`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_by: Option<String>,
+    /// Kept for reference only (old jobs, closed accounts). Archived
+    /// entries are hidden from the default list and search and left out
+    /// of health analysis.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+    /// Set for high-security entries: the password and note, sealed under
+    /// a PIN of their own. `password` and `note` are then left empty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sealed: Option<SealedFields>,
+    /// The password was the vault's master password when last saved from
+    /// the GUI, which checks it against a hash held while the vault is
+    /// open. Only the result is stored; changing the password elsewhere
+    /// clears it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub holds_master_password: bool,
+    /// What "copy next" puts on the clipboard, in order, for logins that
+    /// take more than a username and password
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub copy_sequence: Vec<CopyStep>,
+    /// Entries this one depends on, like the email account that recovers it
+    /// or the registrar behind its DNS. Renames carry links along; a link
+    /// to an entry that is gone stays, flagged, until it is removed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub linked_ids: Vec<String>,
+    /// Text kept with the entry but not shown inline, such as a field too
+    /// long to import as it was
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+}
+
+/// Orders for listing entries (`ui.default_sort`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EntrySort {
+    #[default]
+    Name,
+    Created,
+    Modified,
+    RecentlyUsed,
+    MostUsed,
+}
+
+impl EntrySort {
+    pub const ALL: [EntrySort; 5] = [Self::Name, Self::Created, Self::Modified, Self::RecentlyUsed, Self::MostUsed];
+
+    /// Config value
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Created => "created",
+            Self::Modified => "modified",
+            Self::RecentlyUsed => "recent",
+            Self::MostUsed => "frequent",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Name => "Name",
+            Self::Created => "Newest",
+            Self::Modified => "Recently changed",
+            Self::RecentlyUsed => "Recently used",
+            Self::MostUsed => "Most used",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|sort| sort.as_str().eq_ignore_ascii_case(value.trim()))
+    }
+
+    /// Compare two entries; ties (and never-used entries) fall back to the id
+    pub fn compare(&self, a: (&str, &Entry), b: (&str, &Entry)) -> std::cmp::Ordering {
+        let ((a_id, a), (b_id, b)) = (a, b);
+        let order = match self {
+            Self::Name => std::cmp::Ordering::Equal,
+            Self::Created => b.created_at.cmp(&a.created_at),
+            Self::Modified => b.modified_at.cmp(&a.modified_at),
+            Self::RecentlyUsed => b.last_used_at.cmp(&a.last_used_at),
+            Self::MostUsed => b.use_count.cmp(&a.use_count),
+        };
+        order.then_with(|| a_id.cmp(b_id))
+    }
+}
+
+/// Largest embedded icon, after downscaling
+pub const MAX_ICON_BYTES: usize = 16 * 1024;
+
+/// Built-in glyphs an entry can use as its icon, by name
+pub const ICON_GLYPHS: &[(&str, &str)] = &[
+    ("key", "🔑"),
+    ("lock", "🔒"),
+    ("globe", "🌐"),
+    ("mail", "📧"),
+    ("bank", "🏦"),
+    ("card", "💳"),
+    ("cart", "🛒"),
+    ("work", "💼"),
+    ("code", "💻"),
+    ("cloud", "☁"),
+    ("chat", "💬"),
+    ("game", "🎮"),
+    ("music", "🎵"),
+    ("home", "🏠"),
+    ("wifi", "📶"),
+    ("star", "⭐"),
+];
+
+/// Per-entry icon: a named built-in glyph or a small PNG stored in the
+/// vault, so fetched favicons keep working offline
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryIcon {
+    Glyph(String),
+    /// PNG bytes, base64 in the vault file
+    Png(#[serde(with = "base64_bytes")] Vec<u8>),
+}
+
+impl EntryIcon {
+    /// Wrap PNG bytes, rejecting anything that isn't a PNG or is over the size cap
+    pub fn png(bytes: Vec<u8>) -> Result<Self, String> {
+        if bytes.len() > MAX_ICON_BYTES {
+            return Err(format!("Icon is {} KB, the limit is {} KB", bytes.len() / 1024, MAX_ICON_BYTES / 1024));
+        }
+        if !bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            return Err("Icon is not a PNG image".to_string());
+        }
+        Ok(Self::Png(bytes))
+    }
+
+    /// The glyph to draw, for built-in icons
+    pub fn glyph(&self) -> Option<&'static str> {
+        match self {
+            Self::Glyph(name) => ICON_GLYPHS.iter().find(|(n, _)| n == name).map(|(_, g)| *g),
+            Self::Png(_) => None,
+        }
+    }
+
+    /// Check an icon read from a file: unknown glyph names and oversized
+    /// or non-PNG data are dropped rather than failing the whole vault
+    pub fn validated(self) -> Option<Self> {
+        match self {
+            Self::Glyph(_) => self.glyph().is_some().then_some(self),
+            Self::Png(bytes) => Self::png(bytes).ok(),
+        }
+    }
+}
+
+mod base64_bytes {
+    use base64ct::{Base64, Encoding};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&Base64::encode_string(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        Base64::decode_vec(&encoded).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Named text stored with an entry. Shown on request only, so a large one
+/// doesn't get laid out on every frame.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct Attachment {
+    pub name: String,
+    pub content: String,
+}
+
+// The content can be as sensitive as a note, and as long as anything
+impl std::fmt::Debug for Attachment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Attachment")
+            .field("name", &self.name)
+            .field("content", &format_args!("<{} characters>", self.content.chars().count()))
+            .finish()
+    }
+}
+
+/// Which generator made a password
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GenerationMode {
+    #[default]
+    Random,
+    /// Capitalized dictionary words
+    Memorable,
+    /// Consonant/vowel syllables, easy to read aloud
+    Pronounceable,
+}
+
+/// Password generation settings remembered per entry
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GenerationPrefs {
+    /// Characters for random passwords, words for memorable ones and
+    /// syllables for pronounceable ones
+    pub length: usize,
+    /// Character rules; only random passwords follow them
+    pub rules: PasswordConfig,
+    #[serde(default)]
+    pub mode: GenerationMode,
+}
+
+impl GenerationPrefs {
+    /// Random passwords of `length` characters following `rules`
+    pub fn random(length: usize, rules: PasswordConfig) -> Self {
+        GenerationPrefs { length, rules, mode: GenerationMode::Random }
+    }
+
+    /// A new password made the same way
+    pub fn generate(&self) -> Result<String, String> {
+        match self.mode {
+            GenerationMode::Random => generate_password_with_config(self.length, &self.rules),
+            GenerationMode::Memorable => Ok(generate_memorable_password(self.length)),
+            GenerationMode::Pronounceable => Ok(generate_pronounceable_password(self.length)),
+        }
+    }
+
+    /// Short description for messages, e.g. "20 characters, random"
+    pub fn describe(&self) -> String {
+        match self.mode {
+            GenerationMode::Random => format!("{} characters, random", self.length),
+            GenerationMode::Memorable => format!("{} words, memorable", self.length),
+            GenerationMode::Pronounceable => format!("{} syllables, pronounceable", self.length),
+        }
+    }
+}
+
+/// Defaults for adding similar entries. Text fields may contain an `{id}`
+/// placeholder, substituted with the new entry's id. Never holds a password.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct EntryTemplate {
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub note: String,
+    #[serde(default)]
+    pub generation: Option<GenerationPrefs>,
+}
+
+impl EntryTemplate {
+    /// Replace the `{id}` placeholder in a template field
+    pub fn fill(text: &str, id: &str) -> String {
+        text.replace("{id}", id)
+    }
+
+    /// Apply the template's username (if the given one is empty), tags, URL
+    /// and note boilerplate to a new entry
+    pub fn apply(&self, id: &str, entry: &mut Entry) {
+        if entry.username.trim().is_empty() {
+            entry.username = Self::fill(&self.username, id);
+        } else {
+            entry.username = Self::fill(&entry.username, id);
+        }
+        for tag in &self.tags {
+            if !entry.tags.contains(tag) {
+                entry.tags.push(tag.clone());
+            }
+        }
+        if entry.url.is_none() && !self.url.is_empty() {
+            entry.url = Some(Self::fill(&self.url, id));
+        }
+        match entry.note.as_deref() {
+            Some(note) if !note.is_empty() => entry.note = Some(Self::fill(note, id)),
+            _ if !self.note.is_empty() => entry.note = Some(Self::fill(&self.note, id)),
+            _ => {}
+        }
+        if entry.generation.is_none() {
+            entry.generation = self.generation.clone();
+        }
+    }
+}
+
+// Custom Debug implementation to prevent accidental logging of secrets.
+// Every field must be listed (the password only as a redacted view), so a
+// new field gets a deliberate choice here rather than a derive.
+#[deny(clippy::missing_fields_in_debug)]
+impl std::fmt::Debug for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Entry")
+            .field("username", &self.username)
+            .field("password", &self.password_str())
+            .field("note", &self.note)
+            .field("created_at", &self.created_at)
+            .field("modified_at", &self.modified_at)
+            .field("tags", &self.tags)
+            .field("url", &self.url)
+            .field("totp_secret", &self.totp_secret)
+            .field("generation", &self.generation)
+            .field("icon", &self.icon.as_ref().map(|icon| icon.glyph().unwrap_or("<png>")))
+            .field("last_used_at", &self.last_used_at)
+            .field("use_count", &self.use_count)
+            .field("trashed_at", &self.trashed_at)
+            .field("expires_at", &self.expires_at)
+            .field("created_by", &self.created_by)
+            .field("modified_by", &self.modified_by)
+            .field("archived", &self.archived)
+            .field("sealed", &self.sealed)
+            .field("holds_master_password", &self.holds_master_password)
+            // Custom steps can hold a PIN or an account number: names only
+            .field("copy_sequence", &self.copy_sequence.iter().map(CopyStep::label).collect::<Vec<_>>())
+            .field("linked_ids", &self.linked_ids)
+            .field("attachments", &self.attachments)
+            .finish()
+    }
+}
+
+impl Entry {
+    pub fn new(username: String, password: String, note: Option<String>) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            username,
+            password: SerializableSecret::new(password),
+            note,
+            created_at: now,
+            modified_at: now,
+            tags: Vec::new(),
+            url: None,
+            totp_secret: OptionalSecret::none(),
+            generation: None,
+            icon: None,
+            last_used_at: None,
+            use_count: 0,
+            trashed_at: None,
+            expires_at: None,
+            created_by: None,
+            modified_by: None,
+            archived: false,
+            sealed: None,
+            holds_master_password: false,
+            copy_sequence: Vec::new(),
+            linked_ids: Vec::new(),
+            attachments: Vec::new(),
+        }
+    }
+    
+    /// Create entry from already-secure password (for internal use)
+    pub fn new_secure(username: String, password: SerializableSecret, note: Option<String>) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            username,
+            password,
+            note,
+            created_at: now,
+            modified_at: now,
+            tags: Vec::new(),
+            url: None,
+            totp_secret: OptionalSecret::none(),
+            generation: None,
+            icon: None,
+            last_used_at: None,
+            use_count: 0,
+            trashed_at: None,
+            expires_at: None,
+            created_by: None,
+            modified_by: None,
+            archived: false,
+            sealed: None,
+            holds_master_password: false,
+            copy_sequence: Vec::new(),
+            linked_ids: Vec::new(),
+            attachments: Vec::new(),
+        }
+    }
+    
+    /// Copy for a similar account (another tenant, staging vs prod): every
+    /// field but the password, which is left empty, with fresh timestamps
+    /// and no usage, trash, archive or ownership state
+    pub fn duplicate(&self) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            password: SerializableSecret::new(String::new()),
+            created_at: now,
+            modified_at: now,
+            last_used_at: None,
+            use_count: 0,
+            trashed_at: None,
+            created_by: None,
+            modified_by: None,
+            archived: false,
+            sealed: None,
+            holds_master_password: false,
+            ..self.clone()
+        }
+    }
+    
+    /// A guarded look at the password. Call `.reveal()` on it where the
+    /// text is really needed; it can't be printed or logged as it is.
+    /// Empty for high-security entries, see [`Entry::open_sealed`].
+    pub fn password_str(&self) -> SecretView<'_> {
+        SecretView::new(self.password.expose_secret())
+    }
+    
+    /// Whether the password and note are sealed under a PIN
+    pub fn is_sealed(&self) -> bool {
+        self.sealed.is_some()
+    }
+    
+    /// Make the entry high security: seal its password and note under
+    /// `pin` and clear them from the entry
+    pub fn seal(&mut self, pin: &str) -> Result<(), SealError> {
+        if self.is_sealed() {
+            return Err(SealError::AlreadySealed);
+        }
+        let sealed = SealedFields::seal(pin, self.password_str().reveal(), self.note.as_deref())?;
+        self.password = SerializableSecret::default();
+        self.note = None;
+        self.sealed = Some(sealed);
+        Ok(())
+    }
+    
+    /// Decrypt the sealed password and note for one use; the entry stays sealed
+    pub fn open_sealed(&self, pin: &str) -> Result<SealedSecrets, SealError> {
+        self.sealed.as_ref().ok_or(SealError::NotSealed)?.open(pin)
+    }
+    
+    /// Drop high security, putting the password and note back in the entry
+    pub fn unseal(&mut self, pin: &str) -> Result<(), SealError> {
+        let secrets = self.open_sealed(pin)?;
+        self.password = SerializableSecret::new(secrets.password.to_string());
+        self.note = secrets.note.map(|note| note.to_string());
+        self.sealed = None;
+        Ok(())
+    }
+    
+    /// Strength of the password: measured now, or as recorded when it was
+    /// sealed for high-security entries
+    pub fn password_strength(&self) -> PasswordStrength {
+        match &self.sealed {
+            Some(sealed) => sealed.strength.clone(),
+            None => analyze_password_strength(self.password_str().reveal()).0,
+        }
+    }
+    
+    /// Whether the entry carries `tag` (case-insensitive)
+    pub fn has_tag(&self, tag: &str) -> bool {
+        !tag.is_empty() && self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+    
+    /// Whether `typed` matches `id` for a typed deletion confirmation
+    pub fn confirms_id(typed: &str, id: &str) -> bool {
+        typed.trim().eq_ignore_ascii_case(id)
+    }
+    
+    /// Get TOTP secret as string slice if present
+    pub fn totp_secret_str(&self) -> Option<&str> {
+        self.totp_secret.expose_secret()
+    }
+    
+    /// Count a copy or reveal of the password
+    pub fn record_use(&mut self, at: chrono::DateTime<chrono::Utc>) {
+        self.last_used_at = Some(self.last_used_at.map_or(at, |last| last.max(at)));
+        self.use_count = self.use_count.saturating_add(1);
+    }
+    
+    /// Whole days from `now` until the expiry date, negative once it has
+    /// passed; the expiry day itself counts as 0
+    pub fn days_until_expiry(&self, now: chrono::DateTime<chrono::Utc>) -> Option<i64> {
+        self.expires_at.map(|at| (at.date_naive() - now.date_naive()).num_days())
+    }
+    
+    /// Whether the entry has expired or expires within `days`
+    pub fn expires_within(&self, days: i64, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.days_until_expiry(now).is_some_and(|left| left <= days)
+    }
+    
+    /// Whether `owner` added or last changed the entry (case-insensitive)
+    pub fn is_owned_by(&self, owner: &str) -> bool {
+        [&self.created_by, &self.modified_by].into_iter()
+            .flatten()
+            .any(|name| name.eq_ignore_ascii_case(owner))
+    }
+    
+    /// Link this entry to `id`; false if it already was
+    pub fn link(&mut self, id: &str) -> bool {
+        if self.linked_ids.iter().any(|linked| linked == id) {
+            return false;
+        }
+        self.linked_ids.push(id.to_string());
+        true
+    }
+    
+    /// Drop the link to `id`; false if there was none
+    pub fn unlink(&mut self, id: &str) -> bool {
+        let before = self.linked_ids.len();
+        self.linked_ids.retain(|linked| linked != id);
+        self.linked_ids.len() != before
+    }
+    
+    /// Move fields too long to show into attachments: the username past
+    /// `max_field_chars` (a shortened copy stays), a longer URL, and a
+    /// note past `max_note_chars` (a pointer to the attachment stays).
+    /// The password is left alone. Returns the fields moved.
+    pub fn move_oversize_to_attachments(&mut self, max_field_chars: usize, max_note_chars: usize) -> Vec<&'static str> {
+        let mut moved = Vec::new();
+        if self.username.chars().count() > max_field_chars {
+            let shortened = self.username.chars().take(max_field_chars).collect();
+            let full = std::mem::replace(&mut self.username, shortened);
+            self.attach("username.txt", full);
+            moved.push("username");
+        }
+        if self.url.as_ref().is_some_and(|url| url.chars().count() > max_field_chars) {
+            let full = self.url.take().unwrap_or_default();
+            self.attach("url.txt", full);
+            moved.push("url");
+        }
+        if self.note.as_ref().is_some_and(|note| note.chars().count() > max_note_chars) {
+            let full = self.note.take().unwrap_or_default();
+            let size = full.chars().count();
+            let name = self.attach("note.txt", full);
+            self.note = Some(format!("Moved to the attachment {} ({} characters)", name, size));
+            moved.push("note");
+        }
+        moved
+    }
+    
+    /// Add an attachment, numbering the name if it is taken. Returns the
+    /// name used.
+    pub fn attach(&mut self, name: &str, content: String) -> String {
+        let mut unique = name.to_string();
+        let mut n = 2;
+        while self.attachments.iter().any(|attachment| attachment.name == unique) {
+            unique = format!("{} ({})", name, n);
+            n += 1;
+        }
+        self.attachments.push(Attachment { name: unique.clone(), content });
+        unique
+    }
+    
+    /// Whether the entry is in the trash
+    pub fn is_trashed(&self) -> bool {
+        self.trashed_at.is_some()
+    }
+    
+    #[allow(dead_code)]
+    pub fn update(&mut self) {
+        self.modified_at = chrono::Utc::now();
+    }
+}
+
+/// Limits [`Vault::housekeep`] enforces, from the configuration
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HousekeepingLimits {
+    /// Days an entry stays in the trash before it is deleted for good;
+    /// 0 keeps the trash until it is emptied by hand
+    pub trash_retention_days: u32,
+}
+
+/// What housekeeping removed, or would remove
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct HousekeepingReport {
+    /// Entries deleted from the trash, with when they were trashed,
+    /// oldest first
+    pub purged_trash: Vec<(String, chrono::DateTime<chrono::Utc>)>,
+}
+
+impl HousekeepingReport {
+    pub fn is_empty(&self) -> bool {
+        self.purged_trash.is_empty()
+    }
+
+    /// "deleted 2 entries from the trash", or "nothing to prune"
+    pub fn summary(&self) -> String {
+        match self.purged_trash.len() {
+            0 => "nothing to prune".to_string(),
+            1 => "deleted 1 entry from the trash".to_string(),
+            n => format!("deleted {} entries from the trash", n),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Vault {
+    pub version: u32,
+    entries: HashMap<String, Entry>,
+    pub metadata: VaultMetadata,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VaultMetadata {
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub last_accessed: chrono::DateTime<chrono::Utc>,
+    pub description: Option<String>,
+    /// Last time this vault was exported to or imported from a sync directory
+    #[serde(default)]
+    pub last_synced_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Health scores over time, oldest first
+    #[serde(default)]
+    pub health_history: Vec<HealthRecord>,
+}
+
+impl Default for Vault {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Vault {
+    pub fn new() -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            version: CURRENT_VERSION,
+            entries: HashMap::new(),
+            metadata: VaultMetadata {
+                created_at: now,
+                last_accessed: now,
+                description: None,
+                last_synced_at: None,
+                health_history: Vec::new(),
+            },
+        }
+    }
+      #[allow(dead_code)]
+    pub fn update_access_time(&mut self) {
+        self.metadata.last_accessed = chrono::Utc::now();
+    }
+
+    /// Add an entry, or replace the one with this id; true if one was replaced
+    pub fn add_entry(&mut self, id: String, entry: Entry) -> bool {
+        self.entries.insert(id, entry).is_some()
+    }
+
+    pub fn get_entry(&self, id: &str) -> Option<&Entry> {
+        self.entries.get(id)
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.entries.contains_key(id)
+    }
+
+    /// Number of entries, trashed ones included
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// All entries, trashed ones included, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Entry)> + '_ {
+        self.entries.iter().map(|(id, entry)| (id.as_str(), entry))
+    }
+
+    /// Swap in another vault's entries, keeping this vault's metadata
+    pub fn replace_entries(&mut self, other: Vault) {
+        self.entries = other.entries;
+    }
+
+    /// Count a copy or reveal of an entry's password; false if there is no such entry
+    pub fn record_use(&mut self, id: &str, at: chrono::DateTime<chrono::Utc>) -> bool {
+        self.entries.get_mut(id).map(|entry| entry.record_use(at)).is_some()
+    }
+
+    /// Move an entry to the trash; false if there is no such entry or it is already there
+    pub fn trash_entry(&mut self, id: &str, at: chrono::DateTime<chrono::Utc>) -> bool {
+        match self.entries.get_mut(id) {
+            Some(entry) if !entry.is_trashed() => {
+                entry.trashed_at = Some(at);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Archive or unarchive an entry, counting it as a change made `at` by
+    /// `editor`; false if there is no such entry or it is already in that state
+    pub fn set_archived(&mut self, id: &str, archived: bool, at: chrono::DateTime<chrono::Utc>, editor: Option<String>) -> bool {
+        match self.entries.get_mut(id) {
+            Some(entry) if entry.archived != archived => {
+                entry.archived = archived;
+                entry.modified_at = at;
+                entry.modified_by = editor;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Take an entry back out of the trash; false if it wasn't trashed
+    pub fn restore_entry(&mut self, id: &str) -> bool {
+        self.entries.get_mut(id).and_then(|entry| entry.trashed_at.take()).is_some()
+    }
+
+    pub fn remove_entry(&mut self, id: &str) -> Option<Entry> {
+        self.entries.remove(id)
+    }
+
+    /// Move the entry at `from` to `to`, replacing any entry there, and
+    /// point links to it at the new id; false if there is no such entry
+    pub fn rename_entry(&mut self, from: &str, to: String) -> bool {
+        let Some(entry) = self.entries.remove(from) else {
+            return false;
+        };
+        self.entries.insert(to.clone(), entry);
+        for entry in self.entries.values_mut() {
+            if entry.unlink(from) {
+                entry.link(&to);
+            }
+        }
+        true
+    }
+
+    /// Ids of the entries linking to `id`, sorted
+    pub fn linked_from(&self, id: &str) -> Vec<&str> {
+        let mut ids: Vec<&str> = self.iter()
+            .filter(|(_, entry)| entry.linked_ids.iter().any(|linked| linked == id))
+            .map(|(other, _)| other)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Whether a link to `id` points at nothing: the entry was deleted, or
+    /// is in the trash
+    pub fn is_broken_link(&self, id: &str) -> bool {
+        self.get_entry(id).is_none_or(Entry::is_trashed)
+    }
+
+    /// Free id for a copy of `id`: "github (copy)", then "github (copy 2)"…
+    pub fn copy_id(&self, id: &str) -> String {
+        std::iter::once(format!("{} (copy)", id))
+            .chain((2..).map(|n| format!("{} (copy {})", id, n)))
+            .find(|candidate| !self.contains(candidate))
+            .unwrap_or_default()
+    }
+
+    /// An id already in the vault that matches `id` ignoring case, so
+    /// "GitHub" is caught as a twin of "github"
+    pub fn find_id_ignoring_case(&self, id: &str) -> Option<&str> {
+        self.entries.keys()
+            .find(|existing| existing.to_lowercase() == id.to_lowercase())
+            .map(String::as_str)
+    }
+
+    /// Why `id` can't be used for a new entry: empty, padded with spaces,
+    /// holding line breaks or other control characters, or taken (ignoring case)
+    pub fn new_id_problem(&self, id: &str) -> Option<String> {
+        if id.trim().is_empty() {
+            return Some("Entry ID is required".to_string());
+        }
+        if id.chars().any(char::is_control) {
+            return Some("Entry ID can't contain line breaks, tabs or other control characters".to_string());
+        }
+        if id.trim() != id {
+            return Some("Entry ID can't start or end with a space".to_string());
+        }
+        match self.find_id_ignoring_case(id) {
+            Some(existing) if existing == id => Some("Entry ID already exists".to_string()),
+            Some(existing) => Some(format!("Entry ID already exists as '{}'", existing)),
+            None => None,
+        }
+    }
+
+    /// What [`housekeep`](Self::housekeep) would remove at `now`, without
+    /// removing it
+    pub fn housekeeping_plan(&self, limits: &HousekeepingLimits, now: chrono::DateTime<chrono::Utc>) -> HousekeepingReport {
+        let mut purged_trash: Vec<(String, chrono::DateTime<chrono::Utc>)> = match limits.trash_retention_days {
+            0 => Vec::new(),
+            days => {
+                let cutoff = now - chrono::Duration::days(i64::from(days));
+                self.entries.iter()
+                    .filter_map(|(id, entry)| entry.trashed_at.filter(|at| *at <= cutoff).map(|at| (id.clone(), at)))
+                    .collect()
+            }
+        };
+        // Oldest first, ties by id, so the same vault always prunes the same way
+        purged_trash.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        HousekeepingReport { purged_trash }
+    }
+
+    /// Enforce `limits` at `now`: entries trashed longer than the retention
+    /// period are deleted for good. Run before every save.
+    pub fn housekeep(&mut self, limits: &HousekeepingLimits, now: chrono::DateTime<chrono::Utc>) -> HousekeepingReport {
+        let report = self.housekeeping_plan(limits, now);
+        for (id, _) in &report.purged_trash {
+            self.entries.remove(id);
+        }
+        report
+    }
+
+    pub fn list_entries(&self) -> Vec<&String> {
+        self.entries.keys().collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_entry_creation() {
+        let entry = Entry::new(
+            "user@example.com".to_string(),
+            "secret123".to_string(),
+            Some("My note".to_string()),
+        );
+        
+        assert_eq!(entry.username, "user@example.com");
+        assert_eq!(entry.password.expose_secret(), "secret123");
+        assert_eq!(entry.note, Some("My note".to_string()));
+        assert!(entry.tags.is_empty());
+        assert!(entry.url.is_none());
+        assert!(entry.totp_secret.is_none());
+    }
+    
+    #[test]
+    fn test_entry_timestamps() {
+        let before = chrono::Utc::now();
+        let entry = Entry::new("user".to_string(), "pass".to_string(), None);
+        let after = chrono::Utc::now();
+        
+        assert!(entry.created_at >= before);
+        assert!(entry.created_at <= after);
+        assert_eq!(entry.created_at, entry.modified_at);
+    }
+    
+    #[test]
+    fn test_entry_update() {
+        let mut entry = Entry::new("user".to_string(), "pass".to_string(), None);
+        let original_modified = entry.modified_at;
+        
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        entry.update();
+        
+        assert_eq!(entry.created_at, entry.created_at); // created_at unchanged
+        assert!(entry.modified_at > original_modified);
+    }
+    
+    #[test]
+    fn test_vault_creation() {
+        let vault = Vault::new();
+        
+        assert_eq!(vault.version, CURRENT_VERSION);
+        assert_eq!(vault.len(), 0);
+        assert!(vault.is_empty());
+        assert!(vault.metadata.description.is_none());
+    }
+    
+    #[test]
+    fn test_vault_add_entry() {
+        let mut vault = Vault::new();
+        let entry = Entry::new("user".to_string(), "pass".to_string(), None);
+        
+        vault.add_entry("gmail".to_string(), entry);
+        
+        assert!(!vault.is_empty());
+        assert!(vault.get_entry("gmail").is_some());
+        assert!(vault.get_entry("nonexistent").is_none());
+    }
+    
+    #[test]
+    fn test_vault_remove_entry() {
+        let mut vault = Vault::new();
+        let entry = Entry::new("user".to_string(), "pass".to_string(), None);
+        
+        vault.add_entry("gmail".to_string(), entry);
+        let removed = vault.remove_entry("gmail");
+        
+        assert!(removed.is_some());
+        assert!(vault.is_empty());
+        
+        // Removing non-existent entry returns None
+        let removed2 = vault.remove_entry("gmail");
+        assert!(removed2.is_none());
+    }
+    
+    #[test]
+    fn test_vault_list_entries() {
+        let mut vault = Vault::new();
+        
+        vault.add_entry("gmail".to_string(), Entry::new("user1".to_string(), "pass1".to_string(), None));
+        vault.add_entry("github".to_string(), Entry::new("user2".to_string(), "pass2".to_string(), None));
+        vault.add_entry("work".to_string(), Entry::new("user3".to_string(), "pass3".to_string(), None));
+        
+        let entries = vault.list_entries();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(vault.len(), 3);
+        assert!(vault.contains("github"));
+        assert!(!vault.contains("GitHub"));
+
+        let mut listed: Vec<(&str, &str)> = vault.iter().map(|(id, entry)| (id, entry.username.as_str())).collect();
+        listed.sort();
+        assert_eq!(listed, vec![("github", "user2"), ("gmail", "user1"), ("work", "user3")]);
+    }
+
+    #[test]
+    fn test_vault_replace_entries_keeps_metadata() {
+        let mut vault = Vault::new();
+        vault.metadata.description = Some("team".to_string());
+        vault.add_entry("old".to_string(), Entry::new("a".to_string(), "pw".to_string(), None));
+
+        let mut other = Vault::new();
+        other.add_entry("new".to_string(), Entry::new("b".to_string(), "pw".to_string(), None));
+        vault.replace_entries(other);
+
+        assert!(!vault.contains("old"));
+        assert!(vault.contains("new"));
+        assert_eq!(vault.metadata.description.as_deref(), Some("team"));
+    }
+    
+    #[test]
+    fn test_vault_update_access_time() {
+        let mut vault = Vault::new();
+        let original_access = vault.metadata.last_accessed;
+        
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        vault.update_access_time();
+        
+        assert!(vault.metadata.last_accessed > original_access);
+        assert_eq!(vault.metadata.created_at, vault.metadata.created_at); // created_at unchanged
+    }
+    
+    #[test]
+    fn test_entry_serialization() {
+        let entry = Entry::new(
+            "user@example.com".to_string(),
+            "secret123".to_string(),
+            Some("My note".to_string()),
+        );
+        
+        let json = serde_json::to_string(&entry).expect("Serialization should succeed");
+        let deserialized: Entry = serde_json::from_str(&json).expect("Deserialization should succeed");
+        
+        assert_eq!(entry.username, deserialized.username);
+        assert_eq!(entry.password.expose_secret(), deserialized.password.expose_secret());
+        assert_eq!(entry.note, deserialized.note);
+    }
+
+    #[test]
+    fn test_entry_icon_round_trip_and_limits() {
+        let png = b"\x89PNG\r\n\x1a\n\x00\x01\x02".to_vec();
+        let mut entry = Entry::new("user".to_string(), "pw".to_string(), None);
+        entry.icon = Some(EntryIcon::png(png.clone()).unwrap());
+
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(json.contains(r#""icon":{"png":"iVBORw0KGgoAAQI="}"#), "{}", json);
+        let deserialized: Entry = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.icon, Some(EntryIcon::Png(png)));
+
+        // Entries written before icons existed still load
+        let plain = serde_json::to_string(&Entry::new("u".to_string(), "p".to_string(), None)).unwrap();
+        assert!(!plain.contains("icon"));
+
+        assert!(EntryIcon::png(b"GIF89a".to_vec()).is_err());
+        assert!(EntryIcon::png(vec![0; MAX_ICON_BYTES + 1]).is_err());
+        assert_eq!(EntryIcon::Glyph("bank".to_string()).glyph(), Some("🏦"));
+        assert_eq!(EntryIcon::Glyph("nope".to_string()).validated(), None);
+    }
+
+    #[test]
+    fn test_usage_tracking_and_sorting() {
+        let now = chrono::Utc::now();
+        let mut vault = Vault::new();
+        for id in ["alpha", "beta", "gamma"] {
+            vault.add_entry(id.to_string(), Entry::new("u".to_string(), "p".to_string(), None));
+        }
+        let modified = vault.get_entry("beta").unwrap().modified_at;
+        assert!(vault.record_use("beta", now - chrono::Duration::days(2)));
+        assert!(vault.record_use("beta", now - chrono::Duration::days(3)));
+        assert!(vault.record_use("gamma", now));
+        assert!(!vault.record_use("missing", now));
+
+        let beta = vault.get_entry("beta").unwrap();
+        assert_eq!(beta.use_count, 2);
+        assert_eq!(beta.last_used_at, Some(now - chrono::Duration::days(2)));
+        assert_eq!(beta.modified_at, modified);
+
+        let sorted = |sort: EntrySort| {
+            let mut ids: Vec<&String> = vault.list_entries();
+            ids.sort_by(|a, b| sort.compare((a, vault.get_entry(a).unwrap()), (b, vault.get_entry(b).unwrap())));
+            ids.into_iter().cloned().collect::<Vec<_>>()
+        };
+        assert_eq!(sorted(EntrySort::Name), ["alpha", "beta", "gamma"]);
+        assert_eq!(sorted(EntrySort::RecentlyUsed), ["gamma", "beta", "alpha"]);
+        assert_eq!(sorted(EntrySort::MostUsed), ["beta", "gamma", "alpha"]);
+        assert_eq!(EntrySort::parse("Frequent"), Some(EntrySort::MostUsed));
+        assert_eq!(EntrySort::parse("size"), None);
+
+        // Vaults written before usage tracking load with zero counts
+        let mut json: serde_json::Value = serde_json::to_value(vault.get_entry("alpha").unwrap()).unwrap();
+        json.as_object_mut().unwrap().remove("use_count");
+        let old: Entry = serde_json::from_value(json).unwrap();
+        assert_eq!((old.use_count, old.last_used_at), (0, None));
+    }
+
+    #[test]
+    fn test_duplicate_clears_password_and_usage() {
+        let mut original = Entry::new("ops@corp.example".to_string(), "secret".to_string(), Some("prod".to_string()));
+        original.tags.push("work".to_string());
+        original.url = Some("https://console.example".to_string());
+        original.created_at -= chrono::Duration::days(90);
+        original.record_use(chrono::Utc::now());
+        original.trashed_at = Some(chrono::Utc::now());
+        original.archived = true;
+
+        let copy = original.duplicate();
+        assert_eq!(copy.password_str(), "");
+        assert_eq!((copy.username.as_str(), copy.note.as_deref()), ("ops@corp.example", Some("prod")));
+        assert_eq!((copy.tags.clone(), copy.url.clone()), (original.tags.clone(), original.url.clone()));
+        assert!(copy.created_at > original.created_at);
+        assert_eq!(copy.created_at, copy.modified_at);
+        assert_eq!((copy.use_count, copy.last_used_at, copy.trashed_at), (0, None, None));
+        assert!(!copy.archived);
+
+        let mut vault = Vault::new();
+        vault.add_entry("aws".to_string(), original);
+        assert_eq!(vault.copy_id("aws"), "aws (copy)");
+        vault.add_entry("aws (copy)".to_string(), copy);
+        assert_eq!(vault.copy_id("aws"), "aws (copy 2)");
+    }
+
+    #[test]
+    fn test_vault_serialization() {
+        let mut vault = Vault::new();
+        vault.add_entry("test".to_string(), Entry::new("user".to_string(), "pass".to_string(), None));
+        
+        let json = serde_json::to_string(&vault).expect("Serialization should succeed");
+        let deserialized: Vault = serde_json::from_str(&json).expect("Deserialization should succeed");
+        
+        assert_eq!(vault.version, deserialized.version);
+        assert!(deserialized.get_entry("test").is_some());
+    }
+    
+    #[test]
+    fn test_new_id_problems() {
+        let mut vault = Vault::new();
+        vault.add_entry("GitHub".to_string(), Entry::new("user".to_string(), "pass".to_string(), None));
+
+        assert_eq!(vault.new_id_problem("gitlab"), None);
+        assert_eq!(vault.new_id_problem("  ").as_deref(), Some("Entry ID is required"));
+        assert!(vault.new_id_problem("mail\nwork").is_some());
+        assert!(vault.new_id_problem(" mail").is_some());
+        assert_eq!(vault.new_id_problem("GitHub").as_deref(), Some("Entry ID already exists"));
+        assert_eq!(vault.new_id_problem("github").as_deref(), Some("Entry ID already exists as 'GitHub'"));
+        assert_eq!(vault.find_id_ignoring_case("GITHUB"), Some("GitHub"));
+    }
+
+    #[test]
+    fn test_vault_overwrite_entry() {
+        let mut vault = Vault::new();
+        
+        assert!(!vault.add_entry("key".to_string(), Entry::new("user1".to_string(), "pass1".to_string(), None)));
+        assert!(vault.add_entry("key".to_string(), Entry::new("user2".to_string(), "pass2".to_string(), None)));
+        assert_eq!(vault.len(), 1);
+        
+        let entry = vault.get_entry("key").expect("Entry should exist");
+        assert_eq!(entry.username, "user2");
+        assert_eq!(entry.password.expose_secret(), "pass2");
+    }
+    
+    #[test]
+    fn test_entry_debug_redacted() {
+        let mut entry = Entry::new(
+            "user@example.com".to_string(),
+            "super_secret_password".to_string(),
+            None,
+        );
+        entry.copy_sequence = CopyStep::parse_lines("username\npin=8642").unwrap();
+        
+        let debug_output = format!("{:?}", entry);
+        
+        // Password should NOT appear in debug output
+        assert!(!debug_output.contains("super_secret_password"));
+        // REDACTED should appear
+        assert!(debug_output.contains("REDACTED"));
+        // Copy sequence values are left out, their names kept
+        assert!(debug_output.contains("\"pin\"") && !debug_output.contains("8642"));
+    }
+
+    #[test]
+    fn test_protected_tag_and_confirmation() {
+        let mut entry = Entry::new("user".to_string(), "pass".to_string(), None);
+        entry.tags.push("Critical".to_string());
+        
+        assert!(entry.has_tag("critical"));
+        assert!(!entry.has_tag("work"));
+        assert!(!entry.has_tag(""));
+        assert!(Entry::confirms_id(" GitHub ", "github"));
+        assert!(!Entry::confirms_id("git", "github"));
+    }
+
+    #[test]
+    fn test_template_substitutes_placeholders() {
+        let template = EntryTemplate {
+            username: "{id}@corp.example".to_string(),
+            tags: vec!["work".to_string()],
+            url: "https://{id}.corp.example".to_string(),
+            note: "Provisioned for {id}".to_string(),
+            generation: None,
+        };
+
+        let mut entry = Entry::new(String::new(), "pw".to_string(), None);
+        template.apply("jira", &mut entry);
+
+        assert_eq!(entry.username, "jira@corp.example");
+        assert_eq!(entry.tags, vec!["work".to_string()]);
+        assert_eq!(entry.url.as_deref(), Some("https://jira.corp.example"));
+        assert_eq!(entry.note.as_deref(), Some("Provisioned for jira"));
+        assert_eq!(entry.password_str(), "pw");
+    }
+
+    #[test]
+    fn test_generation_prefs_regenerate_the_same_way() {
+        let rules = PasswordConfig { include_symbols: false, excluded_chars: "aeiou".to_string(), ..Default::default() };
+        let random = GenerationPrefs::random(20, rules);
+        let password = random.generate().unwrap();
+        assert_eq!(password.len(), 20);
+        assert!(password.chars().all(|c| c.is_ascii_alphanumeric() && !"aeiou".contains(c)));
+
+        let memorable = GenerationPrefs { mode: GenerationMode::Memorable, length: 3, ..random.clone() };
+        assert_eq!(memorable.describe(), "3 words, memorable");
+        assert!(!memorable.generate().unwrap().is_empty());
+
+        // Rules saved before modes were remembered are random ones
+        let mut json = serde_json::to_value(&random).unwrap();
+        json.as_object_mut().unwrap().remove("mode");
+        assert_eq!(serde_json::from_value::<GenerationPrefs>(json).unwrap(), random);
+        assert_eq!(serde_json::to_value(&memorable).unwrap()["mode"], "memorable");
+    }
+
+    #[test]
+    fn test_high_security_entry_keeps_secrets_sealed() {
+        let mut entry = Entry::new("me@bank.example".to_string(), "Tr0ub4dor&3-long".to_string(), Some("seed words".to_string()));
+        entry.seal("1357").unwrap();
+        assert!(entry.is_sealed());
+        assert_eq!(entry.password_str(), "");
+        assert_eq!(entry.note, None);
+        assert_eq!(entry.password_strength(), analyze_password_strength("Tr0ub4dor&3-long").0);
+        assert_eq!(entry.seal("1357").unwrap_err(), SealError::AlreadySealed);
+
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(!json.contains("Tr0ub4dor") && !json.contains("seed words"));
+        let mut entry: Entry = serde_json::from_str(&json).unwrap();
+        assert_eq!(entry.open_sealed("1357").unwrap().password.as_str(), "Tr0ub4dor&3-long");
+        assert!(!entry.duplicate().is_sealed());
+
+        assert_eq!(entry.unseal("0000").unwrap_err(), SealError::WrongPin);
+        assert!(entry.is_sealed());
+        entry.unseal("1357").unwrap();
+        assert_eq!((entry.password_str().reveal(), entry.note.as_deref()), ("Tr0ub4dor&3-long", Some("seed words")));
+        assert_eq!(entry.open_sealed("1357").unwrap_err(), SealError::NotSealed);
+    }
+
+    #[test]
+    fn test_links_follow_renames_and_flag_deletions() {
+        let mut vault = Vault::new();
+        vault.add_entry("mail".to_string(), Entry::new("me".to_string(), "pw".to_string(), None));
+        for id in ["shop", "bank"] {
+            let mut entry = Entry::new("me".to_string(), "pw".to_string(), None);
+            assert!(entry.link("mail"));
+            assert!(!entry.link("mail"));
+            vault.add_entry(id.to_string(), entry);
+        }
+        assert_eq!(vault.linked_from("mail"), vec!["bank", "shop"]);
+
+        assert!(vault.rename_entry("mail", "email".to_string()));
+        assert!(!vault.rename_entry("mail", "email".to_string()));
+        assert_eq!(vault.get_entry("shop").unwrap().linked_ids, vec!["email"]);
+        assert_eq!(vault.linked_from("email"), vec!["bank", "shop"]);
+        assert!(vault.linked_from("mail").is_empty());
+
+        // Deleting leaves the links in place, flagged
+        assert!(!vault.is_broken_link("email"));
+        vault.trash_entry("email", chrono::Utc::now());
+        assert!(vault.is_broken_link("email"));
+        vault.remove_entry("email");
+        assert!(vault.is_broken_link("email"));
+        assert_eq!(vault.get_entry("bank").unwrap().linked_ids, vec!["email"]);
+
+        // Old vaults have no links, and empty links aren't written
+        let json = serde_json::to_string(vault.get_entry("bank").unwrap()).unwrap();
+        let mut entry: Entry = serde_json::from_str(&json.replace(r#","linked_ids":["email"]"#, "")).unwrap();
+        assert!(entry.linked_ids.is_empty());
+        assert!(entry.link("x") && entry.unlink("x") && !entry.unlink("x"));
+        assert!(!serde_json::to_string(&entry).unwrap().contains("linked_ids"));
+    }
+
+    #[test]
+    fn test_oversize_fields_move_to_attachments() {
+        let mut entry = Entry::new("u".repeat(10_000), "pw".to_string(), Some("n".repeat(100)));
+        entry.attach("username.txt", "taken".to_string());
+
+        assert_eq!(entry.move_oversize_to_attachments(64, 100), vec!["username"]);
+        assert_eq!(entry.username, "u".repeat(64));
+        assert_eq!(entry.attachments[1].name, "username.txt (2)");
+        assert_eq!(entry.attachments[1].content.len(), 10_000);
+        assert_eq!(entry.password_str().reveal(), "pw");
+
+        entry.url = Some(format!("https://example.com/?{}", "q".repeat(100)));
+        assert_eq!(entry.move_oversize_to_attachments(64, 60), vec!["url", "note"]);
+        assert_eq!(entry.url, None);
+        assert_eq!(entry.note.as_deref(), Some("Moved to the attachment note.txt (100 characters)"));
+        assert!(!format!("{:?}", entry).contains("nnnn"));
+        assert!(entry.move_oversize_to_attachments(64, 60).is_empty());
+    }
+
+    #[test]
+    fn test_housekeeping_purges_old_trash_oldest_first() {
+        let now = chrono::Utc::now();
+        let limits = HousekeepingLimits { trash_retention_days: 30 };
+        let mut vault = Vault::new();
+        for (id, trashed_days_ago) in [("recent", Some(29)), ("old", Some(31)), ("older", Some(400)), ("kept", None)] {
+            vault.add_entry(id.to_string(), Entry::new("me".to_string(), "pw".to_string(), None));
+            if let Some(days) = trashed_days_ago {
+                vault.trash_entry(id, now - chrono::Duration::days(days));
+            }
+        }
+
+        let plan = vault.housekeeping_plan(&limits, now);
+        assert_eq!(plan.purged_trash.iter().map(|(id, _)| id.as_str()).collect::<Vec<_>>(), ["older", "old"]);
+        assert_eq!(vault.len(), 4);
+        assert_eq!(vault.housekeep(&limits, now), plan);
+        assert_eq!(plan.summary(), "deleted 2 entries from the trash");
+        assert!(vault.contains("recent") && vault.contains("kept") && !vault.contains("old"));
+
+        // Nothing more until the next entry ages out; 0 keeps the trash
+        assert!(vault.housekeep(&limits, now).is_empty());
+        assert!(vault.housekeep(&HousekeepingLimits { trash_retention_days: 0 }, now + chrono::Duration::days(90)).is_empty());
+        assert_eq!(vault.housekeep(&limits, now + chrono::Duration::days(2)).purged_trash.len(), 1);
+    }
+}
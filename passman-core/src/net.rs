@@ -0,0 +1,358 @@
+//! Network Policy
+//!
+//! Proxy, offline mode, timeouts and retries for every feature that goes
+//! online (breach checks, remote storage, update checks). Features open
+//! connections through a [`NetPolicy`] instead of `TcpStream` directly, so
+//! offline mode is checked before any socket exists and the proxy, timeout
+//! and retry settings apply the same way everywhere. HTTPS requests (the
+//! `https` feature) go through ureq and rustls, checking certificates
+//! against the Mozilla root store bundled by webpki-roots.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::config::NetworkConfig;
+use crate::error::{NetworkError, PassmanResult};
+
+/// Set by `--offline` for the rest of the process
+static FORCE_OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Pause before retry `n` is `n` times this
+const RETRY_BACKOFF_MS: u64 = 500;
+
+/// Turn on offline mode for this process without touching the config file
+pub fn force_offline() {
+    FORCE_OFFLINE.store(true, Ordering::SeqCst);
+}
+
+/// Proxy settings from the environment (`HTTPS_PROXY`, `HTTP_PROXY`,
+/// `NO_PROXY`, or their lowercase forms)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProxyEnv {
+    pub https: Option<String>,
+    pub http: Option<String>,
+    /// Host suffixes (or `*`) that are reached directly
+    pub no_proxy: Vec<String>,
+}
+
+impl ProxyEnv {
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Read the variables through `lookup`; uppercase names win and empty
+    /// values count as unset
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let var = |name: &str| {
+            lookup(name)
+                .or_else(|| lookup(&name.to_lowercase()))
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let no_proxy = var("NO_PROXY")
+            .map(|list| {
+                list.split(',')
+                    .map(|host| host.trim().trim_start_matches("*.").trim_start_matches('.').to_lowercase())
+                    .filter(|host| !host.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { https: var("HTTPS_PROXY"), http: var("HTTP_PROXY"), no_proxy }
+    }
+
+    /// Whether `NO_PROXY` sends this host straight to the network. Entries
+    /// match the host and its subdomains; `host:port` entries match that
+    /// port only.
+    pub fn bypasses(&self, host: &str, port: u16) -> bool {
+        let host = host.to_lowercase();
+        self.no_proxy.iter().any(|entry| {
+            if entry == "*" {
+                return true;
+            }
+            let (name, entry_port) = match entry.rsplit_once(':') {
+                Some((name, p)) if !name.contains(':') => (name, p.parse::<u16>().ok()),
+                _ => (entry.as_str(), None),
+            };
+            let host_matches = host == name || host.ends_with(&format!(".{}", name));
+            host_matches && entry_port.is_none_or(|p| p == port)
+        })
+    }
+}
+
+/// Scheme, host and port of a URL
+#[derive(Debug, Clone, PartialEq)]
+pub struct Target {
+    pub scheme: String,
+    pub host: String,
+    pub port: u16,
+}
+
+impl Target {
+    pub fn parse(url: &str) -> Result<Self, NetworkError> {
+        let invalid = || NetworkError::ConnectFailed(format!("invalid URL '{}'", url));
+        let (scheme, rest) = url.split_once("://").ok_or_else(invalid)?;
+        let scheme = scheme.to_lowercase();
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        if authority.contains('@') {
+            return Err(NetworkError::ConnectFailed("credentials in URLs are not supported".into()));
+        }
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => (host, port.parse().map_err(|_| invalid())?),
+            _ => (authority, default_port(&scheme).ok_or_else(invalid)?),
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if host.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self { scheme, host: host.to_string(), port })
+    }
+}
+
+fn default_port(scheme: &str) -> Option<u16> {
+    match scheme {
+        "http" => Some(80),
+        "https" => Some(443),
+        _ => None,
+    }
+}
+
+/// How network features may connect
+#[derive(Debug, Clone)]
+pub struct NetPolicy {
+    pub offline: bool,
+    /// `network.proxy` from the config; overrides the environment
+    pub proxy: Option<String>,
+    pub env: ProxyEnv,
+    pub timeout: Duration,
+    pub retries: u32,
+}
+
+impl NetPolicy {
+    /// Policy from the loaded config, the environment and `--offline`
+    pub fn current() -> Self {
+        Self::from_config(&crate::config::get_config().network, ProxyEnv::from_env())
+    }
+
+    pub fn from_config(config: &NetworkConfig, env: ProxyEnv) -> Self {
+        Self {
+            offline: config.offline || FORCE_OFFLINE.load(Ordering::SeqCst),
+            proxy: Some(config.proxy.trim().to_string()).filter(|proxy| !proxy.is_empty()),
+            env,
+            timeout: Duration::from_secs(config.timeout_secs.max(1)),
+            retries: config.retries,
+        }
+    }
+
+    /// Fail fast with a clear error when offline mode is on
+    pub fn check_online(&self, feature: &str) -> PassmanResult<()> {
+        if self.offline {
+            return Err(NetworkError::Offline(feature.to_string()).into());
+        }
+        Ok(())
+    }
+
+    /// The proxy to use for `url`, if any
+    pub fn proxy_for(&self, target: &Target) -> Option<String> {
+        if self.env.bypasses(&target.host, target.port) {
+            return None;
+        }
+        self.proxy.clone().or_else(|| match target.scheme.as_str() {
+            "https" => self.env.https.clone().or_else(|| self.env.http.clone()),
+            _ => self.env.http.clone(),
+        })
+    }
+
+    /// Run `attempt` with the shared retry policy, after the offline check
+    pub fn retry<T>(&self, feature: &str, mut attempt: impl FnMut() -> io::Result<T>) -> PassmanResult<T> {
+        self.check_online(feature)?;
+        let mut tries = 0;
+        loop {
+            match attempt() {
+                Ok(value) => return Ok(value),
+                Err(e) if tries < self.retries => {
+                    tries += 1;
+                    log::debug!("{}: attempt {} failed ({}); retrying", feature, tries, e);
+                    std::thread::sleep(Duration::from_millis(RETRY_BACKOFF_MS * tries as u64));
+                }
+                Err(e) => return Err(NetworkError::ConnectFailed(format!("{}: {}", feature, e)).into()),
+            }
+        }
+    }
+
+    /// Open a TCP connection to the host of `url`, through the proxy
+    /// (as an HTTP CONNECT tunnel) when one applies
+    pub fn connect(&self, feature: &str, url: &str) -> PassmanResult<TcpStream> {
+        self.check_online(feature)?;
+        let target = Target::parse(url)?;
+        let proxy = match self.proxy_for(&target) {
+            Some(proxy) => Some(Target::parse(&proxy).map_err(|e| NetworkError::InvalidProxy(e.to_string()))?),
+            None => None,
+        };
+
+        let hop = proxy.as_ref().unwrap_or(&target);
+        let stream = self.retry(feature, || open(hop, self.timeout))?;
+        if proxy.is_some() {
+            tunnel(&stream, &target)?;
+        }
+        Ok(stream)
+    }
+
+    /// GET an `https://` URL (redirects must stay on https) and return at
+    /// most `max_bytes` of body
+    #[cfg(feature = "https")]
+    pub fn fetch_https(&self, feature: &str, url: &str, max_bytes: usize) -> PassmanResult<Vec<u8>> {
+        self.check_online(feature)?;
+        let target = Target::parse(url)?;
+        if target.scheme != "https" {
+            return Err(NetworkError::ConnectFailed(format!("{}: only https URLs are fetched", feature)).into());
+        }
+        // NO_PROXY was already applied above, so ureq gets an explicit answer
+        // rather than reading the environment itself
+        let proxy = self.proxy_for(&target)
+            .map(|proxy| ureq::Proxy::new(&proxy))
+            .transpose()
+            .map_err(|e| NetworkError::InvalidProxy(e.to_string()))?;
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .https_only(true)
+            .proxy(proxy)
+            .timeout_connect(Some(self.timeout))
+            .timeout_global(Some(self.timeout))
+            .build()
+            .into();
+
+        self.retry(feature, || {
+            let mut response = agent.get(url).call().map_err(ureq::Error::into_io)?;
+            response.body_mut()
+                .with_config()
+                .limit(max_bytes as u64)
+                .read_to_vec()
+                .map_err(|e| match e {
+                    ureq::Error::BodyExceedsLimit(_) => io::Error::other(format!("response larger than {} bytes", max_bytes)),
+                    e => e.into_io(),
+                })
+        })
+    }
+}
+
+fn open(target: &Target, timeout: Duration) -> io::Result<TcpStream> {
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, format!("no address for {}", target.host));
+    for addr in (target.host.as_str(), target.port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                return Ok(stream);
+            }
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+/// Ask an HTTP proxy to tunnel to `target`
+fn tunnel(stream: &TcpStream, target: &Target) -> PassmanResult<()> {
+    let authority = format!("{}:{}", target.host, target.port);
+    let refused = |msg: String| NetworkError::ProxyRefused(msg);
+
+    let mut writer = stream;
+    write!(writer, "CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n\r\n", authority)
+        .map_err(|e| refused(e.to_string()))?;
+
+    let mut reader = BufReader::new(stream);
+    let mut status = String::new();
+    reader.read_line(&mut status).map_err(|e| refused(e.to_string()))?;
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(refused(status.trim().to_string()).into());
+    }
+    // Skip the rest of the response headers
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).map_err(|e| refused(e.to_string()))?;
+        if read == 0 || line == "\r\n" || line == "\n" {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PassmanError;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> ProxyEnv {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        ProxyEnv::from_lookup(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn test_proxy_env_parsing() {
+        let proxies = env(&[
+            ("HTTPS_PROXY", "http://proxy.corp:3128"),
+            ("https_proxy", "http://ignored:1"),
+            ("http_proxy", "http://plain.corp:8080"),
+            ("NO_PROXY", " localhost, .internal.corp,*.svc ,10.0.0.1:8443,"),
+        ]);
+        assert_eq!(proxies.https.as_deref(), Some("http://proxy.corp:3128"));
+        assert_eq!(proxies.http.as_deref(), Some("http://plain.corp:8080"));
+        assert_eq!(proxies.no_proxy, vec!["localhost", "internal.corp", "svc", "10.0.0.1:8443"]);
+
+        assert!(proxies.bypasses("LOCALHOST", 80));
+        assert!(proxies.bypasses("git.internal.corp", 443));
+        assert!(!proxies.bypasses("notinternal.corp", 443));
+        assert!(proxies.bypasses("10.0.0.1", 8443));
+        assert!(!proxies.bypasses("10.0.0.1", 443));
+        assert!(env(&[("no_proxy", "*")]).bypasses("anything.example", 1));
+        assert_eq!(env(&[("HTTPS_PROXY", "  ")]), ProxyEnv::default());
+    }
+
+    #[test]
+    fn test_proxy_selection() {
+        let mut policy = NetPolicy::from_config(
+            &NetworkConfig::default(),
+            env(&[("HTTP_PROXY", "http://plain:8080"), ("NO_PROXY", "local.test")]),
+        );
+        let https = Target::parse("https://api.example.com/v1").unwrap();
+        assert_eq!(https, Target { scheme: "https".into(), host: "api.example.com".into(), port: 443 });
+        assert_eq!(policy.proxy_for(&https).as_deref(), Some("http://plain:8080"));
+        assert_eq!(policy.proxy_for(&Target::parse("http://a.local.test:81").unwrap()), None);
+
+        policy.proxy = Some("http://configured:3128".into());
+        assert_eq!(policy.proxy_for(&https).as_deref(), Some("http://configured:3128"));
+        assert!(Target::parse("https://user:pw@host").is_err());
+    }
+
+    #[test]
+    fn test_offline_mode_never_connects() {
+        let config = NetworkConfig { offline: true, ..Default::default() };
+        let policy = NetPolicy::from_config(&config, ProxyEnv::default());
+
+        let mut attempts = 0;
+        let result = policy.retry("Breach check", || {
+            attempts += 1;
+            Ok(())
+        });
+        assert!(matches!(result, Err(PassmanError::Network(NetworkError::Offline(_)))));
+        assert_eq!(attempts, 0);
+
+        // Would be refused if a socket were opened; offline fails first
+        let err = policy.connect("Update check", "https://127.0.0.1:1").unwrap_err();
+        assert!(err.to_string().starts_with("Update check is unavailable in offline mode"));
+    }
+
+    #[test]
+    fn test_retries_are_bounded() {
+        let config = NetworkConfig { retries: 1, ..Default::default() };
+        let policy = NetPolicy::from_config(&config, ProxyEnv::default());
+        let mut attempts = 0;
+        let result: PassmanResult<()> = policy.retry("Sync", || {
+            attempts += 1;
+            Err(io::Error::new(io::ErrorKind::ConnectionRefused, "refused"))
+        });
+        assert!(matches!(result, Err(PassmanError::Network(NetworkError::ConnectFailed(_)))));
+        assert_eq!(attempts, 2);
+    }
+}
@@ -0,0 +1,409 @@
+//! Credential Paste
+//!
+//! A best guess at the login in pasted text, such as a chat message
+//! ("user: alice pass: hunter2"), a URL with a username and password after
+//! it, or an `otpauth://` link. Labelled values ("Password: …", "login=…")
+//! win; then URLs, email addresses and otpauth links are picked out of the
+//! rest, and a leftover "name secret" pair is taken as username and
+//! password. Whatever isn't used ends up in the note, so nothing pasted is
+//! lost. The result only pre-fills a form; the user reviews it.
+//!
+//! The same readers back the add form's inline hints: an otpauth link
+//! pasted as a password, a URL carrying a query string, an email address
+//! typed as the entry ID.
+
+use regex::Regex;
+use zeroize::Zeroizing;
+
+/// A field [`parse_credentials`] can fill
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PasteField {
+    Username,
+    Password,
+    Url,
+    Totp,
+    Note,
+}
+
+/// What was found in pasted text; `None` where nothing was
+#[derive(Default)]
+pub struct PastedCredentials {
+    pub username: Option<String>,
+    pub password: Option<Zeroizing<String>>,
+    pub url: Option<String>,
+    /// Secret of an `otpauth://` link
+    pub totp_secret: Option<Zeroizing<String>>,
+    /// Lines, or parts of lines, nothing else was taken from
+    pub note: Option<String>,
+}
+
+impl std::fmt::Debug for PastedCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PastedCredentials")
+            .field("fields", &self.fields())
+            .finish_non_exhaustive()
+    }
+}
+
+impl PastedCredentials {
+    /// The fields that were filled, in form order
+    pub fn fields(&self) -> Vec<PasteField> {
+        [
+            (PasteField::Username, self.username.is_some()),
+            (PasteField::Password, self.password.is_some()),
+            (PasteField::Url, self.url.is_some()),
+            (PasteField::Totp, self.totp_secret.is_some()),
+            (PasteField::Note, self.note.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(field, filled)| filled.then_some(field))
+        .collect()
+    }
+
+    /// Whether nothing but a note was found
+    pub fn is_empty(&self) -> bool {
+        self.username.is_none() && self.password.is_none() && self.url.is_none() && self.totp_secret.is_none()
+    }
+
+    fn set(&mut self, field: PasteField, value: &str) {
+        let value = value.trim().trim_end_matches([',', ';', '|']).trim();
+        if value.is_empty() {
+            return;
+        }
+        match field {
+            PasteField::Username if self.username.is_none() => self.username = Some(value.to_string()),
+            PasteField::Password if self.password.is_none() => self.password = Some(Zeroizing::new(value.to_string())),
+            PasteField::Url if self.url.is_none() => self.url = Some(value.to_string()),
+            PasteField::Totp if self.totp_secret.is_none() => self.totp_secret = Some(Zeroizing::new(value.to_string())),
+            _ => {}
+        }
+    }
+}
+
+/// Labels before a value, by the field they name. Longer spellings come
+/// first so "password" isn't read as "pass" plus "word".
+const LABELS: &[(&str, PasteField)] = &[
+    (r"user\s*name|user\s*id|username|login|user|e-?mail(?:\s*address)?|account", PasteField::Username),
+    (r"pass\s*word|passwd|passphrase|passcode|pass|pwd|pw", PasteField::Password),
+    (r"url|website|site|link|address", PasteField::Url),
+    (r"totp|2fa\s*secret|otp\s*secret", PasteField::Totp),
+];
+
+/// Read what looks like credentials out of `text`
+pub fn parse_credentials(text: &str) -> PastedCredentials {
+    let label = Regex::new(&format!(
+        r"(?i)(?:^|[\s,;|(\[])(?:(?P<user>{})|(?P<pass>{})|(?P<url>{})|(?P<totp>{}))\s*[:=]\s*",
+        LABELS[0].0, LABELS[1].0, LABELS[2].0, LABELS[3].0,
+    ))
+    .expect("label pattern is valid");
+
+    let mut found = PastedCredentials::default();
+    let mut unlabelled = Vec::new();
+    let lines: Vec<&str> = text.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+        index += 1;
+        let labels: Vec<(usize, usize, PasteField)> = label.captures_iter(line)
+            .filter_map(|captures| {
+                let whole = captures.get(0)?;
+                let field = LABELS.iter().zip(["user", "pass", "url", "totp"])
+                    .find(|(_, name)| captures.name(name).is_some())
+                    .map(|((_, field), _)| *field)?;
+                Some((whole.start(), whole.end(), field))
+            })
+            .collect();
+        if labels.is_empty() {
+            unlabelled.push(line.to_string());
+            continue;
+        }
+
+        unlabelled.push(line[..labels[0].0].to_string());
+        for (n, &(_, value_start, field)) in labels.iter().enumerate() {
+            let value_end = labels.get(n + 1).map_or(line.len(), |next| next.0);
+            let value = &line[value_start..value_end];
+            // "Password:" alone on its line labels the next one
+            if value.trim().is_empty() && n + 1 == labels.len() && index < lines.len() && !label.is_match(lines[index]) {
+                found.set(field, lines[index]);
+                index += 1;
+            } else {
+                found.set(field, value);
+            }
+        }
+    }
+
+    let mut note = Vec::new();
+    for fragment in &unlabelled {
+        let rest = pick_out_tokens(&mut found, fragment);
+        if !rest.is_empty() && !pair_up(&mut found, &rest) {
+            note.push(rest.join(" "));
+        }
+    }
+    found.note = Some(note.join("\n")).filter(|note| !note.is_empty());
+    found
+}
+
+/// Take otpauth links, URLs and email addresses out of a fragment; returns
+/// the words left over
+fn pick_out_tokens<'a>(found: &mut PastedCredentials, fragment: &'a str) -> Vec<&'a str> {
+    let mut rest = Vec::new();
+    for token in fragment.split_whitespace() {
+        let bare = token.trim_matches(|c: char| matches!(c, '<' | '>' | '(' | ')' | '"' | '\'' | ',' | ';'));
+        let bare = bare.trim_end_matches('.');
+        let lower = bare.to_lowercase();
+        if lower.starts_with("otpauth://") && found.totp_secret.is_none() {
+            read_otpauth(found, bare);
+        } else if (lower.starts_with("http://") || lower.starts_with("https://") || lower.starts_with("www.")) && found.url.is_none() {
+            found.set(PasteField::Url, bare);
+        } else if is_email(bare) && found.username.is_none() {
+            found.set(PasteField::Username, bare);
+        } else if !matches!(token, "/" | "|" | ":" | "-" | "—") {
+            rest.push(token);
+        }
+    }
+    rest
+}
+
+/// Leftover words as a username and password: "alice hunter2-X",
+/// "alice:hunter2-X", or just "hunter2-X" once the username is known.
+/// The password has to look like one.
+fn pair_up(found: &mut PastedCredentials, rest: &[&str]) -> bool {
+    if found.password.is_some() {
+        return false;
+    }
+    match rest {
+        [password] if found.username.is_some() && looks_like_password(password) => {
+            found.set(PasteField::Password, password);
+            true
+        }
+        [pair] if found.username.is_none() => match pair.split_once([':', '/']) {
+            Some((username, password)) if !username.is_empty() && !pair.contains("://") && looks_like_password(password) => {
+                found.set(PasteField::Username, username);
+                found.set(PasteField::Password, password);
+                true
+            }
+            _ => false,
+        },
+        [username, password] if found.username.is_none() && looks_like_password(password) => {
+            found.set(PasteField::Username, username);
+            found.set(PasteField::Password, password);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Six or more characters, with a digit or symbol or mixed case, so that
+/// ordinary words in a message aren't taken for passwords
+fn looks_like_password(word: &str) -> bool {
+    word.chars().count() >= 6
+        && (word.chars().any(|c| c.is_ascii_digit() || !c.is_alphanumeric())
+            || (word.chars().any(char::is_lowercase) && word.chars().any(char::is_uppercase)))
+}
+
+/// Whether `word` is an email address, like `alice@example.com`
+pub fn is_email(word: &str) -> bool {
+    match word.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty() && !word.contains("://") && !domain.contains('@')
+                && domain.split('.').count() >= 2 && domain.split('.').all(|label| !label.is_empty())
+        }
+        None => false,
+    }
+}
+
+/// What an `otpauth://` link carries
+#[derive(Default)]
+pub struct OtpAuthLink {
+    /// Base32 secret, upper-cased with spaces removed
+    pub secret: Option<Zeroizing<String>>,
+    /// The `issuer` parameter, or the label before the colon
+    pub issuer: Option<String>,
+    /// The label after the colon, usually a username or email address
+    pub account: Option<String>,
+}
+
+impl std::fmt::Debug for OtpAuthLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OtpAuthLink")
+            .field("issuer", &self.issuer)
+            .field("account", &self.account)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Read `otpauth://totp/Issuer:account?secret=…&issuer=…`; `None` if
+/// `text` isn't such a link
+pub fn parse_otpauth(text: &str) -> Option<OtpAuthLink> {
+    let link = text.trim();
+    if !link.get(..10).is_some_and(|scheme| scheme.eq_ignore_ascii_case("otpauth://")) {
+        return None;
+    }
+    let (path, query) = link.split_once('?').unwrap_or((link, ""));
+    let param = |name: &str| query.split('&')
+        .find_map(|pair| pair.split_once('=').filter(|(key, _)| key.eq_ignore_ascii_case(name)))
+        .map(|(_, value)| percent_decode(value).trim().to_string())
+        .filter(|value| !value.is_empty());
+
+    let label = percent_decode(path[10..].split_once('/').map_or("", |(_, label)| label));
+    let (label_issuer, account) = match label.rsplit_once(':') {
+        Some((issuer, account)) => (Some(issuer.trim()), account.trim()),
+        None => (None, label.trim()),
+    };
+    Some(OtpAuthLink {
+        secret: param("secret").map(|secret| Zeroizing::new(secret.replace(' ', "").to_uppercase())),
+        issuer: param("issuer").or_else(|| label_issuer.filter(|issuer| !issuer.is_empty()).map(str::to_string)),
+        account: Some(account.to_string()).filter(|account| !account.is_empty()),
+    })
+}
+
+/// `url` without its query string and fragment, which are mostly tracking
+/// parameters and session state; `None` if it has neither
+pub fn strip_url_tracking(url: &str) -> Option<String> {
+    let url = url.trim();
+    let end = url.find(['?', '#'])?;
+    let stripped = &url[..end];
+    (!stripped.is_empty()).then(|| stripped.to_string())
+}
+
+/// The secret of an otpauth link, and its account as username if none was
+/// found
+fn read_otpauth(found: &mut PastedCredentials, link: &str) {
+    let Some(link) = parse_otpauth(link) else {
+        return;
+    };
+    if let Some(secret) = &link.secret {
+        found.set(PasteField::Totp, secret);
+    }
+    if let Some(account) = &link.account {
+        found.set(PasteField::Username, account);
+    }
+}
+
+/// Decode `%XX` escapes; malformed ones are kept as they are
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| std::str::from_utf8(hex).ok());
+        match (bytes[i], hex.and_then(|hex| u8::from_str_radix(hex, 16).ok())) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Username, password, URL, TOTP secret and note, as plain strings
+    type Fields = (Option<String>, Option<String>, Option<String>, Option<String>, Option<String>);
+
+    fn parsed(text: &str) -> Fields {
+        let found = parse_credentials(text);
+        (
+            found.username,
+            found.password.map(|password| password.to_string()),
+            found.url,
+            found.totp_secret.map(|secret| secret.to_string()),
+            found.note,
+        )
+    }
+
+    fn some(value: &str) -> Option<String> {
+        Some(value.to_string())
+    }
+
+    #[test]
+    fn test_common_paste_shapes() {
+        let corpus: &[(&str, Fields)] = &[
+            ("user: alice pass: hunter2", (some("alice"), some("hunter2"), None, None, None)),
+            ("Username: alice\nPassword: Tr0ub4dor&3", (some("alice"), some("Tr0ub4dor&3"), None, None, None)),
+            ("login=alice, password=s3cret!", (some("alice"), some("s3cret!"), None, None, None)),
+            ("Email: alice@example.com\nPassword:\ncorrect horse battery", (some("alice@example.com"), some("correct horse battery"), None, None, None)),
+            ("https://staging.example.com/login alice hunter2", (some("alice"), some("hunter2"), some("https://staging.example.com/login"), None, None)),
+            ("alice@example.com / Hunter22", (some("alice@example.com"), some("Hunter22"), None, None, None)),
+            ("alice:Sup3rSecret", (some("alice"), some("Sup3rSecret"), None, None, None)),
+            (
+                "Here are the staging creds\nURL: https://staging.example.com\nuser: deploy\npass: xK9#mQ2!",
+                (some("deploy"), some("xK9#mQ2!"), some("https://staging.example.com"), None, some("Here are the staging creds")),
+            ),
+            (
+                "otpauth://totp/GitHub:alice%40example.com?secret=jbsw%20y3dp&issuer=GitHub",
+                (some("alice@example.com"), None, None, some("JBSWY3DP"), None),
+            ),
+            ("Thanks, see you tomorrow", (None, None, None, None, some("Thanks, see you tomorrow"))),
+            ("(www.example.org) Account: bob.smith PW: pa55-word", (some("bob.smith"), some("pa55-word"), some("www.example.org"), None, None)),
+        ];
+        for (text, expected) in corpus {
+            assert_eq!(&parsed(text), expected, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_labels_win_and_first_value_is_kept() {
+        let (username, password, ..) = parsed("bob@example.com\nuser: alice\nuser: carol\npassword: first-1 password: second-2");
+        assert_eq!(username.as_deref(), Some("alice"));
+        assert_eq!(password.as_deref(), Some("first-1"));
+        // Words inside other words aren't labels
+        assert_eq!(parsed("mypassword: x").1, None);
+    }
+
+    #[test]
+    fn test_debug_and_fields() {
+        let found = parse_credentials("user: alice pass: hunter2");
+        assert_eq!(found.fields(), vec![PasteField::Username, PasteField::Password]);
+        assert!(!format!("{:?}", found).contains("hunter2"));
+        assert!(parse_credentials("just a note").is_empty());
+    }
+
+    #[test]
+    fn test_otpauth_links() {
+        let link = parse_otpauth(" otpauth://totp/GitHub:alice%40example.com?secret=jbsw%20y3dp&issuer=GitHub%20Inc ").unwrap();
+        assert_eq!(link.secret.as_deref().map(String::as_str), Some("JBSWY3DP"));
+        assert_eq!(link.issuer.as_deref(), Some("GitHub Inc"));
+        assert_eq!(link.account.as_deref(), Some("alice@example.com"));
+        assert!(!format!("{:?}", link).contains("JBSWY3DP"));
+
+        // Issuer from the label when there's no parameter, and no label at all
+        let link = parse_otpauth("OTPAUTH://totp/Example:bob?SECRET=abc").unwrap();
+        assert_eq!((link.issuer.as_deref(), link.account.as_deref()), (Some("Example"), Some("bob")));
+        assert_eq!(parse_otpauth("otpauth://totp/?secret=abc").unwrap().account, None);
+        assert!(parse_otpauth("otpauth://totp/carol").unwrap().secret.is_none());
+
+        assert!(parse_otpauth("hunter2").is_none());
+        assert!(parse_otpauth("https://example.com/?secret=abc").is_none());
+    }
+
+    #[test]
+    fn test_strip_url_tracking() {
+        let cases = [
+            ("https://example.com/login?utm_source=mail&ref=x", Some("https://example.com/login")),
+            ("https://example.com/app#/dashboard", Some("https://example.com/app")),
+            (" https://example.com/a?b#c ", Some("https://example.com/a")),
+            ("https://example.com/login", None),
+            ("?only=query", None),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(strip_url_tracking(url).as_deref(), expected, "{:?}", url);
+        }
+    }
+
+    #[test]
+    fn test_is_email() {
+        assert!(is_email("alice@example.com"));
+        assert!(is_email("a.b+tag@mail.example.co.uk"));
+        for word in ["alice", "alice@localhost", "@example.com", "a@b@example.com", "https://a@example.com", "alice@example..com"] {
+            assert!(!is_email(word), "{:?}", word);
+        }
+    }
+}
@@ -0,0 +1,277 @@
+//! Sample Vaults
+//!
+//! Obviously fake entries for the GUI's demo mode, screenshots, tests and
+//! benchmarks. Everything is made from a seed with a small generator of its
+//! own, so the same seed and `now` give the same vault on every machine and
+//! with every version of `rand`. The sites live under the reserved
+//! `example.*` domains and the usernames are plainly made up.
+//!
+//! The mix is meant to exercise the UI: strong and weak passwords, one
+//! password reused across three sites, tags, ages from days to years, and
+//! some entries with URLs, 2FA secrets and notes. [`stress_vault`] adds
+//! entries with absurdly large fields, for the GUI's size guards.
+
+use chrono::{DateTime, Duration, Utc};
+use crate::model::{Entry, Vault};
+use crate::secure_types::OptionalSecret;
+
+/// Seed of the demo vault
+pub const DEMO_SEED: u64 = 0x5EED;
+
+/// Entries in the demo vault
+pub const DEMO_ENTRIES: usize = 30;
+
+/// Site name and domain of each sample entry, cycled past the end
+const SITES: &[(&str, &str)] = &[
+    ("acme-bank", "bank.example.com"),
+    ("mailbox", "mail.example.org"),
+    ("chirper", "chirper.example"),
+    ("codehub", "codehub.example.net"),
+    ("shopmart", "shop.example.com"),
+    ("streamflix", "stream.example"),
+    ("cloud-drive", "drive.example.org"),
+    ("photo-share", "photos.example.net"),
+    ("travel-booker", "travel.example.com"),
+    ("pizza-place", "pizza.example"),
+    ("gym-portal", "gym.example.org"),
+    ("city-library", "library.example.net"),
+    ("power-company", "power.example.com"),
+    ("mobile-carrier", "carrier.example"),
+    ("work-vpn", "vpn.corp.example.com"),
+    ("work-wiki", "wiki.corp.example.com"),
+    ("work-email", "mail.corp.example.com"),
+    ("forum", "forum.example.org"),
+    ("game-store", "games.example.net"),
+    ("music-box", "music.example"),
+    ("news-daily", "news.example.com"),
+    ("tax-office", "tax.example.org"),
+    ("health-insurer", "insurer.example.net"),
+    ("car-rental", "cars.example.com"),
+    ("home-router", "192.0.2.1"),
+    ("crypto-wallet", "wallet.example"),
+    ("recipe-site", "recipes.example.org"),
+    ("dev-registry", "registry.example.net"),
+    ("pet-vet", "vet.example.com"),
+    ("school-portal", "school.example.org"),
+];
+
+const USERNAMES: &[&str] = &[
+    "demo.user@example.com",
+    "jane.doe",
+    "sample_user",
+    "test.account@example.org",
+    "not-a-real-person",
+];
+
+const TAGS: &[&str] = &["work", "personal", "finance", "social", "shopping", "dev"];
+
+/// The kind of passwords health analysis flags
+const WEAK_PASSWORDS: &[&str] = &["password1", "letmein", "qwerty123", "Summer2024", "iloveyou"];
+
+const NOTES: &[&str] = &[
+    "Demo entry: not a real account.",
+    "Security question: first pet? **Fluffy** (fake)",
+    "Recovery codes:\n- 1111-2222\n- 3333-4444",
+    "Shared with the (fictional) family.",
+];
+
+/// Size of the note in [`stress_vault`]'s "huge-note" entry, in bytes
+pub const STRESS_NOTE_BYTES: usize = 2 * 1024 * 1024;
+
+/// SplitMix64: tiny, and stable across platforms and dependency updates
+struct Seeded(u64);
+
+impl Seeded {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    /// True one time in `n`
+    fn one_in(&mut self, n: u64) -> bool {
+        self.below(n) == 0
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len() as u64) as usize]
+    }
+
+    fn string(&mut self, alphabet: &[u8], len: usize) -> String {
+        (0..len).map(|_| alphabet[self.below(alphabet.len() as u64) as usize] as char).collect()
+    }
+}
+
+/// The demo vault: [`DEMO_ENTRIES`] entries from [`DEMO_SEED`]
+pub fn demo_vault(now: DateTime<Utc>) -> Vault {
+    sample_vault(DEMO_SEED, DEMO_ENTRIES, now)
+}
+
+/// A vault of `count` fake entries made from `seed`, dated before `now`.
+/// Past the built-in sites, IDs get a number: "acme-bank-2".
+pub fn sample_vault(seed: u64, count: usize, now: DateTime<Utc>) -> Vault {
+    let mut rng = Seeded(seed);
+    let shared_password = strong_password(&mut rng);
+    let mut vault = Vault::new();
+    let mut oldest = now;
+
+    for index in 0..count {
+        let (site, domain) = SITES[index % SITES.len()];
+        let id = match index / SITES.len() {
+            0 => site.to_string(),
+            round => format!("{}-{}", site, round + 1),
+        };
+
+        let password = match rng.below(10) {
+            0 | 1 => rng.pick(WEAK_PASSWORDS).to_string(),
+            _ if index % 10 == 6 => rng.pick(WEAK_PASSWORDS).to_string(),
+            _ => strong_password(&mut rng),
+        };
+        // Three sites share one password for the reuse check to find
+        let password = if index % 10 == 3 { shared_password.clone() } else { password };
+        let mut entry = Entry::new(rng.pick(USERNAMES).to_string(), password, None);
+
+        let age_days = rng.below(3 * 365) as i64;
+        entry.created_at = now - Duration::days(age_days) - Duration::minutes(rng.below(24 * 60) as i64);
+        entry.modified_at = entry.created_at + Duration::days(rng.below(age_days as u64 + 1) as i64);
+        oldest = oldest.min(entry.created_at);
+
+        let mut tags: Vec<String> = (0..rng.below(3)).map(|_| rng.pick(TAGS).to_string()).collect();
+        tags.sort();
+        tags.dedup();
+        entry.tags = tags;
+        if !rng.one_in(3) {
+            entry.url = Some(format!("https://{}/login", domain));
+        }
+        if rng.one_in(5) {
+            entry.totp_secret = OptionalSecret::some(rng.string(b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567", 32));
+        }
+        if rng.one_in(4) {
+            entry.note = Some(rng.pick(NOTES).to_string());
+        }
+        if rng.one_in(6) {
+            entry.use_count = rng.below(50) as u32 + 1;
+            entry.last_used_at = Some(now - Duration::hours(rng.below(24 * 30) as i64));
+        }
+        entry.created_by = Some("sample".to_string());
+        vault.add_entry(id, entry);
+    }
+
+    vault.metadata.created_at = oldest;
+    vault.metadata.last_accessed = now;
+    vault.metadata.description = Some("Sample vault: every entry is made up".to_string());
+    vault
+}
+
+/// The demo vault plus entries no real vault should have, but an import
+/// can produce: a 2 MB note (one very long line, then thousands of short
+/// ones), a 10,000-character username, a huge URL and id, hundreds of
+/// tags and a large attachment
+pub fn stress_vault(now: DateTime<Utc>) -> Vault {
+    let mut vault = demo_vault(now);
+    let mut rng = Seeded(DEMO_SEED ^ 0x5757);
+    let add = |vault: &mut Vault, id: String, entry: Entry| {
+        vault.add_entry(id, Entry { created_at: now, modified_at: now, created_by: Some("sample".to_string()), ..entry });
+    };
+
+    let mut note = rng.string(b"abcdefghijklmnopqrstuvwxyz ", STRESS_NOTE_BYTES / 2);
+    while note.len() < STRESS_NOTE_BYTES {
+        note.push('\n');
+        note.push_str(&rng.string(b"abcdefghijklmnopqrstuvwxyz ", 60));
+    }
+    note.truncate(STRESS_NOTE_BYTES);
+    add(&mut vault, "huge-note".to_string(), Entry::new("demo.user@example.com".to_string(), strong_password(&mut rng), Some(note)));
+
+    let username = rng.string(b"abcdefghijklmnopqrstuvwxyz.", 10_000);
+    add(&mut vault, "long-username".to_string(), Entry::new(username, strong_password(&mut rng), None));
+
+    let mut entry = Entry::new("jane.doe".to_string(), strong_password(&mut rng), None);
+    entry.url = Some(format!("https://tracker.example.com/login?ref={}", rng.string(b"abcdef0123456789", 50_000)));
+    entry.tags = (0..500).map(|n| format!("tag-{}", n)).collect();
+    add(&mut vault, "long-url-and-tags".to_string(), entry);
+
+    let id = format!("long-id-{}", rng.string(b"abcdefghijklmnopqrstuvwxyz", 2_000));
+    add(&mut vault, id, Entry::new("sample_user".to_string(), strong_password(&mut rng), None));
+
+    let mut entry = Entry::new("not-a-real-person".to_string(), strong_password(&mut rng), Some("See the attachment".to_string()));
+    entry.attach("dump.txt", rng.string(b"abcdefghijklmnopqrstuvwxyz \n", 1024 * 1024));
+    add(&mut vault, "large-attachment".to_string(), entry);
+    vault
+}
+
+/// 16 to 23 characters from every class
+fn strong_password(rng: &mut Seeded) -> String {
+    const ALPHABET: &[u8] = b"abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ23456789!#%&*+-=?@";
+    let len = 16 + rng.below(8) as usize;
+    loop {
+        let password = rng.string(ALPHABET, len);
+        let classes = [
+            password.chars().any(|c| c.is_ascii_lowercase()),
+            password.chars().any(|c| c.is_ascii_uppercase()),
+            password.chars().any(|c| c.is_ascii_digit()),
+            password.chars().any(|c| !c.is_ascii_alphanumeric()),
+        ];
+        if classes.iter().all(|&has| has) {
+            return password;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::health::{AnalysisScope, PasswordHealthAnalyzer};
+    use crate::utils::PasswordStrength;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-01-01T12:00:00Z").unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_same_seed_same_vault() {
+        let json = |vault: &Vault| serde_json::to_string(&vault.iter().collect::<std::collections::BTreeMap<_, _>>()).unwrap();
+        assert_eq!(json(&demo_vault(now())), json(&demo_vault(now())));
+        assert_ne!(json(&sample_vault(1, DEMO_ENTRIES, now())), json(&sample_vault(2, DEMO_ENTRIES, now())));
+
+        // Pinned so a change to the generator is a deliberate one
+        let vault = demo_vault(now());
+        let first = vault.get_entry("acme-bank").unwrap();
+        assert_eq!(first.created_at.to_rfc3339(), "2024-12-28T09:07:00+00:00");
+    }
+
+    #[test]
+    fn test_sample_vault_has_a_bit_of_everything() {
+        let vault = demo_vault(now());
+        assert_eq!(vault.len(), DEMO_ENTRIES);
+        let entries: Vec<&Entry> = vault.iter().map(|(_, entry)| entry).collect();
+        assert!(entries.iter().all(|entry| entry.created_at <= entry.modified_at && entry.modified_at <= now()));
+        assert!(entries.iter().any(|entry| entry.url.is_some()));
+        assert!(entries.iter().any(|entry| entry.totp_secret.is_some()));
+        assert!(entries.iter().any(|entry| entry.note.is_some()));
+        assert!(entries.iter().any(|entry| !entry.tags.is_empty()));
+        assert!(entries.iter().any(|entry| entry.created_at < now() - Duration::days(365)));
+
+        let reports = PasswordHealthAnalyzer::new().analyze_vault(&vault, AnalysisScope::ActiveOnly);
+        assert!(reports.iter().any(|report| report.reused_with >= 2));
+        assert!(reports.iter().any(|report| matches!(report.strength, PasswordStrength::VeryWeak | PasswordStrength::Weak)));
+
+        // Larger vaults for benchmarks keep unique IDs
+        assert_eq!(sample_vault(7, 75, now()).len(), 75);
+    }
+
+    #[test]
+    fn test_stress_vault_has_the_oversize_fields() {
+        let vault = stress_vault(now());
+        assert_eq!(vault.len(), DEMO_ENTRIES + 5);
+        assert_eq!(vault.get_entry("huge-note").unwrap().note.as_ref().unwrap().len(), STRESS_NOTE_BYTES);
+        assert_eq!(vault.get_entry("long-username").unwrap().username.chars().count(), 10_000);
+        assert!(vault.list_entries().iter().any(|id| id.len() > 2_000));
+    }
+}
@@ -0,0 +1,216 @@
+//! High Security Entries
+//!
+//! An entry marked high security keeps its password and note encrypted a
+//! second time, under a PIN of its own. The vault only holds the sealed
+//! blob: the fields are decrypted when asked for, handed to the caller for
+//! one use and never written back into the entry. The password's strength
+//! is recorded when it is sealed so health checks don't need the password.
+//!
+//! A random 256-bit entry key encrypts the fields (AES-256-GCM), and that
+//! key is wrapped with one derived from the PIN (Argon2id, salt per entry),
+//! so changing the PIN only rewraps the entry key.
+
+use aes_gcm::aead::generic_array::GenericArray;
+use argon2::password_hash::SaltString;
+use base64ct::{Base64, Encoding};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+use crate::crypto::{decrypt_data, derive_key_with_params, encrypt_data, KdfParams, Key};
+use crate::model::Vault;
+use crate::utils::{analyze_password_strength, PasswordStrength};
+
+/// Shortest PIN accepted for sealing
+pub const MIN_PIN_LEN: usize = 4;
+
+/// AES-GCM nonce length at the start of each sealed blob
+const NONCE_LEN: usize = 12;
+
+/// Why a high-security entry couldn't be sealed or opened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SealError {
+    PinTooShort,
+    WrongPin,
+    NotSealed,
+    AlreadySealed,
+    /// The sealed data is damaged, or encryption itself failed
+    Corrupted(String),
+}
+
+impl std::fmt::Display for SealError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SealError::PinTooShort => write!(f, "PIN must be at least {} characters", MIN_PIN_LEN),
+            SealError::WrongPin => write!(f, "Wrong PIN"),
+            SealError::NotSealed => write!(f, "Entry is not high security"),
+            SealError::AlreadySealed => write!(f, "Entry is already high security"),
+            SealError::Corrupted(msg) => write!(f, "High-security data unreadable: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SealError {}
+
+/// The sealed password and note of a high-security entry, as stored in
+/// the vault
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SealedFields {
+    /// Argon2id salt for the PIN
+    salt: String,
+    /// Entry key under the PIN key: nonce then ciphertext, base64
+    wrapped_key: String,
+    /// Password and note under the entry key: nonce then ciphertext, base64
+    fields: String,
+    /// Strength of the password when it was sealed
+    pub strength: PasswordStrength,
+}
+
+impl std::fmt::Debug for SealedFields {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SealedFields").field("strength", &self.strength).finish_non_exhaustive()
+    }
+}
+
+/// A high-security entry's password and note, opened for one use.
+/// Both are wiped when this is dropped.
+pub struct SealedSecrets {
+    pub password: Zeroizing<String>,
+    pub note: Option<Zeroizing<String>>,
+}
+
+impl std::fmt::Debug for SealedSecrets {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[REDACTED]")
+    }
+}
+
+/// What is encrypted under the entry key
+#[derive(Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+struct Payload {
+    password: String,
+    note: Option<String>,
+}
+
+impl SealedFields {
+    /// Seal a password and note under `pin`
+    pub fn seal(pin: &str, password: &str, note: Option<&str>) -> Result<Self, SealError> {
+        if pin.chars().count() < MIN_PIN_LEN {
+            return Err(SealError::PinTooShort);
+        }
+        let mut key_bytes = rand::random::<[u8; 32]>();
+        let entry_key = Key::new(GenericArray::clone_from_slice(&key_bytes));
+        key_bytes.zeroize();
+
+        let salt = SaltString::generate(&mut rand::thread_rng());
+        let wrapped_key = seal_bytes(&pin_key(pin, &salt)?, entry_key.as_ref())?;
+        let payload = Payload { password: password.to_string(), note: note.map(str::to_string) };
+        let plaintext = Zeroizing::new(serde_json::to_vec(&payload).map_err(|e| SealError::Corrupted(e.to_string()))?);
+
+        Ok(Self {
+            salt: salt.as_str().to_string(),
+            wrapped_key,
+            fields: seal_bytes(&entry_key, &plaintext)?,
+            strength: analyze_password_strength(password).0,
+        })
+    }
+
+    /// Decrypt the password and note with `pin`
+    pub fn open(&self, pin: &str) -> Result<SealedSecrets, SealError> {
+        let entry_key = self.entry_key(pin)?;
+        let plaintext = open_bytes(&entry_key, &self.fields)?;
+        let mut payload: Payload = serde_json::from_slice(&plaintext).map_err(|e| SealError::Corrupted(e.to_string()))?;
+        Ok(SealedSecrets {
+            password: Zeroizing::new(std::mem::take(&mut payload.password)),
+            note: payload.note.take().map(Zeroizing::new),
+        })
+    }
+
+    /// Switch to a new PIN; the sealed fields stay as they are
+    pub fn change_pin(&mut self, old_pin: &str, new_pin: &str) -> Result<(), SealError> {
+        if new_pin.chars().count() < MIN_PIN_LEN {
+            return Err(SealError::PinTooShort);
+        }
+        let entry_key = self.entry_key(old_pin)?;
+        let salt = SaltString::generate(&mut rand::thread_rng());
+        self.wrapped_key = seal_bytes(&pin_key(new_pin, &salt)?, entry_key.as_ref())?;
+        self.salt = salt.as_str().to_string();
+        Ok(())
+    }
+
+    fn entry_key(&self, pin: &str) -> Result<Key, SealError> {
+        let salt = SaltString::from_b64(&self.salt).map_err(|e| SealError::Corrupted(e.to_string()))?;
+        let key_bytes = open_bytes(&pin_key(pin, &salt)?, &self.wrapped_key)?;
+        if key_bytes.len() != 32 {
+            return Err(SealError::Corrupted("entry key has the wrong length".to_string()));
+        }
+        Ok(Key::new(GenericArray::clone_from_slice(&key_bytes)))
+    }
+}
+
+/// High-security entries opened for a plaintext export, by id
+pub type OpenedEntries = HashMap<String, SealedSecrets>;
+
+/// Open every high-security entry in `vault` that `pin` unlocks, for an
+/// export the user confirmed. Returns those and the ids it didn't open.
+pub fn open_all(vault: &Vault, pin: &str) -> (OpenedEntries, Vec<String>) {
+    let mut opened = OpenedEntries::new();
+    let mut failed = Vec::new();
+    for (id, entry) in vault.iter().filter(|(_, entry)| entry.is_sealed()) {
+        match entry.open_sealed(pin) {
+            Ok(secrets) => {
+                opened.insert(id.to_string(), secrets);
+            }
+            Err(_) => failed.push(id.to_string()),
+        }
+    }
+    failed.sort();
+    (opened, failed)
+}
+
+fn pin_key(pin: &str, salt: &SaltString) -> Result<Key, SealError> {
+    derive_key_with_params(pin, salt, &KdfParams::default()).map_err(|e| SealError::Corrupted(e.to_string()))
+}
+
+fn seal_bytes(key: &Key, plaintext: &[u8]) -> Result<String, SealError> {
+    let (ciphertext, nonce) = encrypt_data(key, plaintext).map_err(|e| SealError::Corrupted(e.to_string()))?;
+    let mut blob = nonce.to_vec();
+    blob.extend(ciphertext);
+    Ok(Base64::encode_string(&blob))
+}
+
+/// Decrypt a blob from `seal_bytes`. Authentication failure means the
+/// key is wrong, which for the wrapped key means the PIN is.
+fn open_bytes(key: &Key, blob: &str) -> Result<Zeroizing<Vec<u8>>, SealError> {
+    let blob = Base64::decode_vec(blob).map_err(|e| SealError::Corrupted(e.to_string()))?;
+    if blob.len() < NONCE_LEN {
+        return Err(SealError::Corrupted("sealed data is truncated".to_string()));
+    }
+    let (nonce, ciphertext) = blob.split_at(NONCE_LEN);
+    let nonce: [u8; NONCE_LEN] = nonce.try_into().expect("split at the nonce length");
+    decrypt_data(key, ciphertext, &nonce).map(Zeroizing::new).map_err(|_| SealError::WrongPin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_open_and_change_pin() {
+        let mut sealed = SealedFields::seal("4711", "correct horse", Some("seed: abandon ability")).unwrap();
+        assert_eq!(sealed.strength, analyze_password_strength("correct horse").0);
+        let json = serde_json::to_string(&sealed).unwrap();
+        assert!(!json.contains("correct horse") && !json.contains("abandon"));
+        assert!(!format!("{:?}", sealed).contains("correct horse"));
+
+        let secrets = sealed.open("4711").unwrap();
+        assert_eq!(secrets.password.as_str(), "correct horse");
+        assert_eq!(secrets.note.as_deref().map(String::as_str), Some("seed: abandon ability"));
+        assert_eq!(sealed.open("4712").unwrap_err(), SealError::WrongPin);
+
+        sealed.change_pin("4711", "2468").unwrap();
+        assert_eq!(sealed.open("4711").unwrap_err(), SealError::WrongPin);
+        assert_eq!(sealed.open("2468").unwrap().password.as_str(), "correct horse");
+        assert_eq!(sealed.change_pin("2468", "12").unwrap_err(), SealError::PinTooShort);
+        assert_eq!(SealedFields::seal("123", "pw", None).unwrap_err(), SealError::PinTooShort);
+    }
+}
@@ -0,0 +1,436 @@
+//! Entry Search Module
+//!
+//! Shared entry matching for the CLI and GUI. Short fields (id, username,
+//! URL, tags) are matched by substring; notes go through a lowercase token
+//! index so a keystroke costs O(query), not O(total note bytes).
+//!
+//! Archived entries only show up for queries containing `is:archived`,
+//! and then they are the only ones that do. High-security entries match
+//! on their id and tags only.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use crate::model::{Entry, Vault};
+
+/// Indexed form of one entry
+struct IndexedEntry {
+    modified_at: chrono::DateTime<chrono::Utc>,
+    trashed: bool,
+    archived: bool,
+    sealed: bool,
+    /// Lowercased id, username, URL and tags, newline separated (id and
+    /// tags only for high-security entries)
+    fields: String,
+    note_tokens: BTreeSet<String>,
+    /// Lowercased `created_by` and `modified_by`, for `owner:` filters
+    owners: Vec<String>,
+}
+
+/// Incrementally maintained search index over a vault's entries
+#[derive(Default)]
+pub struct SearchIndex {
+    entries: HashMap<String, IndexedEntry>,
+    /// Note token -> ids of entries whose note contains it
+    note_tokens: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Which entries a search looks at
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearchScope {
+    /// Everything except the trash
+    #[default]
+    ActiveOnly,
+    IncludeTrash,
+}
+
+/// Query prefix restricting results to entries a person added or last changed
+const OWNER_FILTER: &str = "owner:";
+
+/// Query word listing archived entries instead of the others
+pub const ARCHIVED_FILTER: &str = "is:archived";
+
+/// A query prepared against an index, reusable across many `matches` calls
+pub struct PreparedQuery {
+    text: String,
+    note_hits: HashSet<String>,
+    scope: SearchScope,
+    owner: Option<String>,
+    archived: bool,
+}
+
+impl PreparedQuery {
+    /// Whether the query has no text or owner filter; `is:archived` alone
+    /// still counts as empty
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty() && self.owner.is_none()
+    }
+}
+
+/// Remove `is:archived` from a lowercased query, saying whether it was there
+fn split_archived_filter(query: &str) -> (String, bool) {
+    if !query.split_whitespace().any(|word| word == ARCHIVED_FILTER) {
+        return (query.to_string(), false);
+    }
+    let rest: Vec<&str> = query.split_whitespace().filter(|word| *word != ARCHIVED_FILTER).collect();
+    (rest.join(" "), true)
+}
+
+/// Lowercase a query and split an `owner:<name>` filter off it; the last
+/// one wins if several are given
+fn split_owner_filter(query: &str) -> (String, Option<String>) {
+    let query = query.trim().to_lowercase();
+    if !query.split_whitespace().any(|word| word.starts_with(OWNER_FILTER)) {
+        return (query, None);
+    }
+    let mut owner = None;
+    let rest: Vec<&str> = query.split_whitespace()
+        .filter(|word| match word.strip_prefix(OWNER_FILTER) {
+            Some(name) => {
+                owner = Some(name.to_string()).filter(|name| !name.is_empty());
+                false
+            }
+            None => true,
+        })
+        .collect();
+    (rest.join(" "), owner)
+}
+
+/// Split text into lowercase alphanumeric tokens
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_lowercase)
+}
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build an index over every entry in a vault
+    pub fn from_vault(vault: &Vault) -> Self {
+        let mut index = Self::new();
+        index.sync(vault.iter());
+        index
+    }
+
+    /// Bring the index in line with `entries`: re-index entries whose
+    /// modification time changed and drop ids that no longer exist
+    pub fn sync<'a>(&mut self, entries: impl IntoIterator<Item = (&'a str, &'a Entry)>) {
+        let mut seen = HashSet::new();
+        for (id, entry) in entries {
+            seen.insert(id.to_string());
+            self.update(id, entry);
+        }
+
+        let stale: Vec<String> = self.entries.keys()
+            .filter(|id| !seen.contains(*id))
+            .cloned()
+            .collect();
+        for id in stale {
+            self.remove(&id);
+        }
+    }
+
+    /// Index or re-index one entry; unchanged entries are skipped. Moving
+    /// to or from the trash doesn't touch `modified_at`, so it is checked
+    /// too, as are the archived and high-security flags.
+    pub fn update(&mut self, id: &str, entry: &Entry) {
+        if self.entries.get(id).is_some_and(|indexed| {
+            indexed.modified_at == entry.modified_at
+                && indexed.trashed == entry.is_trashed()
+                && indexed.archived == entry.archived
+                && indexed.sealed == entry.is_sealed()
+        }) {
+            return;
+        }
+        self.remove(id);
+
+        let mut fields = vec![id.to_lowercase()];
+        if !entry.is_sealed() {
+            fields.push(entry.username.to_lowercase());
+            fields.extend(entry.url.as_deref().map(str::to_lowercase));
+        }
+        fields.extend(entry.tags.iter().map(|tag| tag.to_lowercase()));
+
+        let note_tokens: BTreeSet<String> = entry.note.as_deref().map(|note| tokenize(note).collect()).unwrap_or_default();
+        for token in &note_tokens {
+            self.note_tokens.entry(token.clone()).or_default().insert(id.to_string());
+        }
+
+        self.entries.insert(id.to_string(), IndexedEntry {
+            modified_at: entry.modified_at,
+            trashed: entry.is_trashed(),
+            archived: entry.archived,
+            sealed: entry.is_sealed(),
+            fields: fields.join("\n"),
+            note_tokens,
+            owners: [&entry.created_by, &entry.modified_by].into_iter().flatten().map(|name| name.to_lowercase()).collect(),
+        });
+    }
+
+    /// Drop an entry from the index
+    pub fn remove(&mut self, id: &str) {
+        let Some(indexed) = self.entries.remove(id) else {
+            return;
+        };
+        for token in indexed.note_tokens {
+            if let Some(ids) = self.note_tokens.get_mut(&token) {
+                ids.remove(id);
+                if ids.is_empty() {
+                    self.note_tokens.remove(&token);
+                }
+            }
+        }
+    }
+
+    /// Prepare a query. With notes included, an entry's note matches when
+    /// every query word is a prefix of one of its words. `owner:alice`
+    /// keeps only entries alice added or last changed; `is:archived` lists
+    /// archived entries instead of the others.
+    pub fn prepare(&self, query: &str, include_notes: bool, scope: SearchScope) -> PreparedQuery {
+        let (text, owner) = split_owner_filter(query);
+        let (text, archived) = split_archived_filter(&text);
+        let mut note_hits: Option<HashSet<String>> = None;
+
+        if include_notes {
+            for word in tokenize(&text) {
+                let hits: HashSet<String> = self.note_tokens
+                    .range(word.clone()..)
+                    .take_while(|(token, _)| token.starts_with(&word))
+                    .flat_map(|(_, ids)| ids.iter().cloned())
+                    .collect();
+                note_hits = Some(match note_hits {
+                    Some(previous) => previous.intersection(&hits).cloned().collect(),
+                    None => hits,
+                });
+            }
+        }
+
+        PreparedQuery { text, note_hits: note_hits.unwrap_or_default(), scope, owner, archived }
+    }
+
+    /// Whether the entry with this id matches a prepared query
+    pub fn matches(&self, id: &str, query: &PreparedQuery) -> bool {
+        let indexed = self.entries.get(id);
+        if query.scope == SearchScope::ActiveOnly && indexed.is_some_and(|indexed| indexed.trashed) {
+            return false;
+        }
+        if indexed.is_some_and(|indexed| indexed.archived != query.archived) {
+            return false;
+        }
+        if query.is_empty() {
+            return true;
+        }
+        indexed.is_some_and(|indexed| Self::hit(id, indexed, query))
+    }
+
+    /// Whether an indexed entry matches a non-empty query, ignoring scope
+    fn hit(id: &str, indexed: &IndexedEntry, query: &PreparedQuery) -> bool {
+        query.owner.as_ref().is_none_or(|owner| indexed.owners.contains(owner))
+            && (query.text.is_empty() || indexed.fields.contains(&query.text) || query.note_hits.contains(id))
+    }
+
+    /// How many trashed entries match a non-empty query, whatever its
+    /// scope; used to hint that the trash holds more results
+    pub fn trashed_matches(&self, query: &PreparedQuery) -> usize {
+        if query.is_empty() {
+            return 0;
+        }
+        self.entries.iter()
+            .filter(|(id, indexed)| indexed.trashed && indexed.archived == query.archived && Self::hit(id, indexed, query))
+            .count()
+    }
+}
+
+/// Ids of vault entries matching `query`, sorted
+pub fn search_vault(vault: &Vault, query: &str, include_notes: bool, scope: SearchScope) -> Vec<String> {
+    let index = SearchIndex::from_vault(vault);
+    let prepared = index.prepare(query, include_notes, scope);
+    let mut ids: Vec<String> = vault.iter()
+        .filter(|(id, _)| index.matches(id, &prepared))
+        .map(|(id, _)| id.to_string())
+        .collect();
+    ids.sort();
+    ids
+}
+
+/// Fuzzy match for short names such as commands and entry ids: every
+/// query character must appear in order. Consecutive characters and
+/// matches at word starts score higher; `None` means no match.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<u32> {
+    let query: Vec<char> = query.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    let mut matched = 0;
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut previous_matched = false;
+
+    for c in candidate.to_lowercase().chars() {
+        if matched < query.len() && c == query[matched] {
+            score += 1;
+            if previous_matched {
+                score += 4;
+            }
+            if previous.is_none_or(|p| !p.is_alphanumeric()) {
+                score += 3;
+            }
+            matched += 1;
+            previous_matched = true;
+        } else {
+            previous_matched = false;
+        }
+        previous = Some(c);
+    }
+
+    (matched == query.len()).then_some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_vault() -> Vault {
+        let mut vault = Vault::new();
+        let mut github = Entry::new("octocat".to_string(), "pw".to_string(), Some("Recovery codes in the safe".to_string()));
+        github.url = Some("https://github.com".to_string());
+        github.tags.push("Work".to_string());
+        vault.add_entry("github".to_string(), github);
+        vault.add_entry("bank".to_string(), Entry::new("alice".to_string(), "pw".to_string(), Some("Branch: Downtown, PIN hint: birthday".to_string())));
+        vault.add_entry("email".to_string(), Entry::new("alice@example.com".to_string(), "pw".to_string(), None));
+        vault
+    }
+
+    #[test]
+    fn test_matches_all_fields() {
+        let vault = sample_vault();
+        assert_eq!(search_vault(&vault, "GIT", true, SearchScope::ActiveOnly), vec!["github"]);
+        assert_eq!(search_vault(&vault, "alice", true, SearchScope::ActiveOnly), vec!["bank", "email"]);
+        assert_eq!(search_vault(&vault, "github.com", true, SearchScope::ActiveOnly), vec!["github"]);
+        assert_eq!(search_vault(&vault, "work", true, SearchScope::ActiveOnly), vec!["github"]);
+        assert_eq!(search_vault(&vault, "recov saf", true, SearchScope::ActiveOnly), vec!["github"]);
+        assert_eq!(search_vault(&vault, "downtown", true, SearchScope::ActiveOnly), vec!["bank"]);
+        assert!(search_vault(&vault, "downtown", false, SearchScope::ActiveOnly).is_empty());
+        assert_eq!(search_vault(&vault, "", false, SearchScope::ActiveOnly).len(), 3);
+    }
+
+    #[test]
+    fn test_sealed_entries_match_on_id_and_tags_only() {
+        let mut vault = sample_vault();
+        let mut index = SearchIndex::from_vault(&vault);
+        let mut bank = vault.get_entry("bank").cloned().unwrap();
+        bank.tags.push("finance".to_string());
+        bank.seal("8642").unwrap();
+        index.update("bank", &bank);
+        vault.add_entry("bank".to_string(), bank);
+
+        for (query, expected) in [("bank", vec!["bank"]), ("finance", vec!["bank"]), ("alice", vec!["email"]), ("downtown", vec![])] {
+            assert_eq!(search_vault(&vault, query, true, SearchScope::ActiveOnly), expected, "{}", query);
+            let prepared = index.prepare(query, true, SearchScope::ActiveOnly);
+            assert_eq!(index.matches("bank", &prepared), expected.contains(&"bank"), "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_archived_entries_only_match_is_archived() {
+        let mut vault = sample_vault();
+        let mut bank = vault.get_entry("bank").cloned().unwrap();
+        bank.archived = true;
+        vault.add_entry("bank".to_string(), bank);
+
+        assert_eq!(search_vault(&vault, "", false, SearchScope::ActiveOnly), vec!["email", "github"]);
+        assert_eq!(search_vault(&vault, "alice", false, SearchScope::ActiveOnly), vec!["email"]);
+        assert_eq!(search_vault(&vault, "is:archived", false, SearchScope::ActiveOnly), vec!["bank"]);
+        assert_eq!(search_vault(&vault, "IS:ARCHIVED alice", false, SearchScope::ActiveOnly), vec!["bank"]);
+        assert!(search_vault(&vault, "is:archived git", false, SearchScope::ActiveOnly).is_empty());
+
+        // Unarchiving re-indexes even though nothing else changed
+        let mut index = SearchIndex::from_vault(&vault);
+        let mut bank = vault.get_entry("bank").cloned().unwrap();
+        bank.archived = false;
+        index.update("bank", &bank);
+        assert!(index.matches("bank", &index.prepare("", false, SearchScope::ActiveOnly)));
+    }
+
+    #[test]
+    fn test_owner_filter() {
+        let mut vault = sample_vault();
+        let mut bank = vault.get_entry("bank").cloned().unwrap();
+        bank.created_by = Some("Alice".to_string());
+        vault.add_entry("bank".to_string(), bank);
+        let mut email = vault.get_entry("email").cloned().unwrap();
+        email.created_by = Some("bob".to_string());
+        email.modified_by = Some("alice".to_string());
+        vault.add_entry("email".to_string(), email);
+
+        assert_eq!(search_vault(&vault, "owner:alice", false, SearchScope::ActiveOnly), vec!["bank", "email"]);
+        assert_eq!(search_vault(&vault, "owner:bob", false, SearchScope::ActiveOnly), vec!["email"]);
+        assert_eq!(search_vault(&vault, "birthday owner:ALICE", true, SearchScope::ActiveOnly), vec!["bank"]);
+        assert!(search_vault(&vault, "owner:carol", false, SearchScope::ActiveOnly).is_empty());
+        // A bare prefix filters nothing
+        assert_eq!(search_vault(&vault, "owner:", false, SearchScope::ActiveOnly).len(), 3);
+    }
+
+    #[test]
+    fn test_incremental_index_agrees_with_fresh_index() {
+        // The GUI keeps one index per session and syncs it after each save;
+        // the CLI builds a fresh one per command. Both must agree.
+        let mut vault = sample_vault();
+        let mut incremental = SearchIndex::from_vault(&vault);
+
+        let mut bank = vault.get_entry("bank").cloned().unwrap();
+        bank.note = Some("Moved to uptown branch".to_string());
+        bank.modified_at += chrono::Duration::seconds(1);
+        vault.add_entry("bank".to_string(), bank);
+        vault.remove_entry("email");
+        vault.add_entry("forum".to_string(), Entry::new("bob".to_string(), "pw".to_string(), Some("uptown meetups".to_string())));
+        incremental.sync(vault.iter());
+
+        for query in ["uptown", "downtown", "alice", "bob", "saf", "", "up bra"] {
+            for include_notes in [true, false] {
+                let prepared = incremental.prepare(query, include_notes, SearchScope::ActiveOnly);
+                let mut gui: Vec<String> = vault.iter()
+                    .filter(|(id, _)| incremental.matches(id, &prepared))
+                    .map(|(id, _)| id.to_string())
+                    .collect();
+                gui.sort();
+                assert_eq!(gui, search_vault(&vault, query, include_notes, SearchScope::ActiveOnly), "query {:?}", query);
+            }
+        }
+        assert!(!incremental.note_tokens.contains_key("downtown"));
+    }
+
+    #[test]
+    fn test_trash_is_searched_only_on_request() {
+        let mut vault = sample_vault();
+        let mut index = SearchIndex::from_vault(&vault);
+        assert!(vault.trash_entry("bank", chrono::Utc::now()));
+        index.sync(vault.iter());
+
+        assert_eq!(search_vault(&vault, "alice", true, SearchScope::ActiveOnly), vec!["email"]);
+        assert_eq!(search_vault(&vault, "alice", true, SearchScope::IncludeTrash), vec!["bank", "email"]);
+        assert_eq!(search_vault(&vault, "", true, SearchScope::ActiveOnly).len(), 2);
+
+        let prepared = index.prepare("downtown", true, SearchScope::ActiveOnly);
+        assert!(!index.matches("bank", &prepared));
+        assert_eq!(index.trashed_matches(&prepared), 1);
+        assert_eq!(index.trashed_matches(&index.prepare("", true, SearchScope::ActiveOnly)), 0);
+
+        assert!(vault.restore_entry("bank"));
+        index.sync(vault.iter());
+        let prepared = index.prepare("downtown", true, SearchScope::ActiveOnly);
+        assert!(index.matches("bank", &prepared));
+        assert_eq!(index.trashed_matches(&prepared), 0);
+    }
+
+    #[test]
+    fn test_fuzzy_score_ranks_word_starts_and_runs() {
+        assert!(fuzzy_score("lv", "Lock vault").is_some());
+        assert!(fuzzy_score("lock vault", "Lock vault").is_some());
+        assert_eq!(fuzzy_score("xyz", "Lock vault"), None);
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+
+        let run = fuzzy_score("git", "github").unwrap();
+        let scattered = fuzzy_score("git", "gift tracker").unwrap();
+        assert!(run > scattered);
+        assert!(fuzzy_score("hd", "Open health dashboard") > fuzzy_score("hd", "shared"));
+    }
+}
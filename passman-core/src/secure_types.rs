@@ -10,7 +10,9 @@
 
 #![allow(dead_code)]
 
+use std::marker::PhantomData;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use subtle::ConstantTimeEq;
 
 // Re-export commonly used types from secrecy
 pub use secrecy::{ExposeSecret, SecretString};
@@ -29,11 +31,12 @@ pub use secrecy::zeroize::Zeroize;
 /// 
 /// # Example
 /// ```rust
-/// use crate::secure_types::SecurePassword;
+/// use passman_core::secure_types::{secure_string, SecurePassword};
+/// use secrecy::ExposeSecret;
 /// 
-/// let password = SecurePassword::new("my_secret_password");
+/// let password: SecurePassword = secure_string("my_secret_password".to_string());
 /// // Access requires explicit call
-/// let value = password.expose_secret();
+/// assert_eq!(password.expose_secret(), "my_secret_password");
 /// ```
 pub type SecurePassword = SecretString;
 
@@ -191,6 +194,73 @@ impl<'de> Deserialize<'de> for SerializableSecret {
     }
 }
 
+/// A borrowed look at a secret, as handed out by `Entry::password_str`.
+///
+/// It has no `Display` and its `Debug` prints [REDACTED], so it can't end
+/// up in a `format!` or a log line by accident: the text is only reachable
+/// through `.reveal()`, which makes every place that shows or copies a
+/// password easy to find. It borrows the secret without copying it, and is
+/// `!Send` so it can't be handed to another thread.
+///
+/// ```compile_fail
+/// use passman_core::secure_types::SecretView;
+///
+/// let view = SecretView::new("hunter2");
+/// println!("{}", view);
+/// ```
+#[derive(Clone, Copy)]
+pub struct SecretView<'a> {
+    secret: &'a str,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<'a> SecretView<'a> {
+    pub fn new(secret: &'a str) -> Self {
+        Self { secret, _not_send: PhantomData }
+    }
+
+    /// The secret itself. Call this only where the text is meant to be
+    /// shown, copied or compared.
+    pub fn reveal(self) -> &'a str {
+        self.secret
+    }
+
+    /// Length in bytes
+    pub fn len(&self) -> usize {
+        self.secret.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.secret.is_empty()
+    }
+}
+
+impl std::fmt::Debug for SecretView<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[REDACTED]")
+    }
+}
+
+impl PartialEq for SecretView<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        bool::from(self.secret.as_bytes().ct_eq(other.secret.as_bytes()))
+    }
+}
+
+impl Eq for SecretView<'_> {}
+
+impl PartialEq<str> for SecretView<'_> {
+    fn eq(&self, other: &str) -> bool {
+        *self == SecretView::new(other)
+    }
+}
+
+impl PartialEq<&str> for SecretView<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        *self == SecretView::new(other)
+    }
+}
+
 /// Convert Zeroizing<String> to SecretString
 pub fn zeroizing_to_secret(z: Zeroizing<String>) -> SecretString {
     // Take the value out (will zeroize the Zeroizing wrapper on drop)
@@ -275,4 +345,14 @@ mod tests {
         let back = secret_to_zeroizing(&secret);
         assert_eq!(&*back, "test");
     }
+
+    #[test]
+    fn test_secret_view_redacted_until_revealed() {
+        let secret = String::from("hunter2");
+        let view = SecretView::new(&secret);
+        assert_eq!(format!("{:?}", view), "[REDACTED]");
+        assert_eq!(view.reveal().as_ptr(), secret.as_ptr());
+        assert_eq!(view.len(), 7);
+        assert!(view == "hunter2" && view != "hunter3" && view != "hunter");
+    }
 }
@@ -0,0 +1,267 @@
+//! Entry Sharing Module
+//!
+//! Hands a single credential to someone else without exporting plaintext.
+//! A share bundle contains one entry encrypted with a one-time passphrase:
+//! - Argon2id key derivation (parameters stored in the bundle header)
+//! - AES-256-GCM encryption
+//! - Expiry timestamp inside the authenticated payload, enforced on import
+
+#![allow(dead_code)]
+
+use crate::crypto::{derive_key_with_params, encrypt_data, decrypt_data, KdfParams};
+use crate::model::{Entry, Vault};
+use argon2::password_hash::SaltString;
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use std::fs;
+
+/// Magic bytes to identify share bundles
+const SHARE_MAGIC: &[u8; 4] = b"PSHR";
+
+/// Share bundle format version
+const SHARE_FORMAT_VERSION: u8 = 1;
+
+/// Header: [magic(4)][version(1)][memory_kb(4)][time_cost(4)][parallelism(4)][salt_len(4)]
+const SHARE_HEADER_LEN: usize = 21;
+
+/// Default file extension for share bundles
+pub const SHARE_EXTENSION: &str = "pshare";
+
+/// Default number of days before a bundle expires
+pub const DEFAULT_SHARE_EXPIRY_DAYS: i64 = 7;
+
+/// Characters used for generated passphrases (no ambiguous 0/O/1/l/I)
+const PASSPHRASE_CHARSET: &[u8] = b"abcdefghijkmnpqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// Decrypted contents of a share bundle
+#[derive(Serialize, Deserialize)]
+struct SharePayload {
+    id: String,
+    entry: Entry,
+    created_at: chrono::DateTime<chrono::Utc>,
+    expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// An entry recovered from a share bundle
+pub struct SharedEntry {
+    pub id: String,
+    pub entry: Entry,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub struct ShareManager;
+
+impl ShareManager {
+    /// Generate a random one-time passphrase, grouped for easier reading aloud
+    pub fn generate_passphrase() -> String {
+        let mut rng = rand::thread_rng();
+        (0..5)
+            .map(|_| {
+                (0..5)
+                    .map(|_| PASSPHRASE_CHARSET[rng.gen_range(0..PASSPHRASE_CHARSET.len())] as char)
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    /// Encrypt a single entry into a share bundle
+    pub fn create_bundle(
+        id: &str,
+        entry: &Entry,
+        passphrase: &str,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        if passphrase.is_empty() {
+            return Err("Share passphrase cannot be empty".into());
+        }
+        if entry.is_sealed() {
+            return Err("High-security entries can't be shared; remove high security first".into());
+        }
+
+        let payload = SharePayload {
+            id: id.to_string(),
+            entry: entry.clone(),
+            created_at: chrono::Utc::now(),
+            expires_at,
+        };
+        let serialized = zeroize::Zeroizing::new(serde_json::to_vec(&payload)?);
+
+        let kdf = KdfParams::default();
+        let salt = SaltString::generate(&mut rand::thread_rng());
+        let key = derive_key_with_params(passphrase, &salt, &kdf)?;
+        let (ciphertext, nonce) = encrypt_data(&key, &serialized)?;
+
+        // Assemble bundle: [header(21)][salt][nonce(12)][ciphertext]
+        let salt_bytes = salt.as_str().as_bytes();
+        let mut bundle = Vec::with_capacity(SHARE_HEADER_LEN + salt_bytes.len() + 12 + ciphertext.len());
+        bundle.extend_from_slice(SHARE_MAGIC);
+        bundle.push(SHARE_FORMAT_VERSION);
+        bundle.extend_from_slice(&kdf.memory_kb.to_le_bytes());
+        bundle.extend_from_slice(&kdf.time_cost.to_le_bytes());
+        bundle.extend_from_slice(&kdf.parallelism.to_le_bytes());
+        bundle.extend_from_slice(&(salt_bytes.len() as u32).to_le_bytes());
+        bundle.extend_from_slice(salt_bytes);
+        bundle.extend_from_slice(&nonce);
+        bundle.extend_from_slice(&ciphertext);
+
+        Ok(bundle)
+    }
+
+    /// Decrypt a share bundle, rejecting it if it has expired
+    pub fn open_bundle(data: &[u8], passphrase: &str) -> Result<SharedEntry, Box<dyn std::error::Error>> {
+        if data.len() < SHARE_HEADER_LEN || &data[0..4] != SHARE_MAGIC {
+            return Err("Not a Passman share bundle".into());
+        }
+        if data[4] != SHARE_FORMAT_VERSION {
+            return Err(format!("Unsupported share bundle version: {}", data[4]).into());
+        }
+
+        let read_u32 = |at: usize| u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]);
+        let kdf = KdfParams {
+            memory_kb: read_u32(5),
+            time_cost: read_u32(9),
+            parallelism: read_u32(13),
+        };
+        let salt_len = read_u32(17) as usize;
+
+        let salt_end = SHARE_HEADER_LEN + salt_len;
+        if salt_len > 1000 || data.len() < salt_end + 12 {
+            return Err("Share bundle corrupted: too short".into());
+        }
+
+        let salt_str = std::str::from_utf8(&data[SHARE_HEADER_LEN..salt_end])?;
+        let salt = SaltString::from_b64(salt_str)
+            .map_err(|e| format!("Salt parsing error: {}", e))?;
+        let nonce: [u8; 12] = data[salt_end..salt_end + 12].try_into()?;
+        let ciphertext = &data[salt_end + 12..];
+
+        let key = derive_key_with_params(passphrase, &salt, &kdf)?;
+        let plaintext = zeroize::Zeroizing::new(
+            decrypt_data(&key, ciphertext, &nonce)
+                .map_err(|_| "Wrong passphrase or corrupted share bundle")?
+        );
+        let payload: SharePayload = serde_json::from_slice(&plaintext)?;
+
+        if chrono::Utc::now() > payload.expires_at {
+            return Err(format!(
+                "Share bundle expired on {}",
+                payload.expires_at.format("%Y-%m-%d %H:%M UTC")
+            ).into());
+        }
+
+        Ok(SharedEntry {
+            id: payload.id,
+            entry: payload.entry,
+            expires_at: payload.expires_at,
+        })
+    }
+
+    /// Write a share bundle for an entry to a file
+    pub fn export_entry(
+        vault: &Vault,
+        id: &str,
+        passphrase: &str,
+        expires_in_days: i64,
+        output_path: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let entry = vault.get_entry(id)
+            .ok_or_else(|| format!("Entry '{}' not found", id))?;
+
+        if expires_in_days <= 0 {
+            return Err("Expiry must be at least one day".into());
+        }
+        let expires_at = chrono::Utc::now() + chrono::Duration::days(expires_in_days);
+
+        let bundle = Self::create_bundle(id, entry, passphrase, expires_at)?;
+        fs::write(output_path, bundle)?;
+
+        log::info!("Share bundle written: {}", output_path);
+        Ok(())
+    }
+
+    /// Read and decrypt a share bundle file
+    pub fn read_bundle(input_path: &str, passphrase: &str) -> Result<SharedEntry, Box<dyn std::error::Error>> {
+        let data = fs::read(input_path)
+            .map_err(|e| format!("Cannot read share bundle '{}': {}", input_path, e))?;
+        Self::open_bundle(&data, passphrase)
+    }
+
+    /// Find a free entry id, appending "-shared", "-shared-2", ... on conflict
+    pub fn unique_id(vault: &Vault, id: &str) -> String {
+        if vault.get_entry(id).is_none() {
+            return id.to_string();
+        }
+
+        let base = format!("{}-shared", id);
+        if vault.get_entry(&base).is_none() {
+            return base;
+        }
+
+        (2..)
+            .map(|n| format!("{}-{}", base, n))
+            .find(|candidate| vault.get_entry(candidate).is_none())
+            .unwrap_or(base)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_entry() -> Entry {
+        Entry::new("alice@example.com".to_string(), "s3cret-Pass!".to_string(), Some("prod db".to_string()))
+    }
+
+    #[test]
+    fn test_bundle_roundtrip() {
+        let expires = chrono::Utc::now() + chrono::Duration::days(1);
+        let bundle = ShareManager::create_bundle("db", &sample_entry(), "correct horse", expires)
+            .expect("Bundle creation should succeed");
+
+        assert_eq!(&bundle[0..4], SHARE_MAGIC);
+
+        let shared = ShareManager::open_bundle(&bundle, "correct horse").expect("Bundle should open");
+        assert_eq!(shared.id, "db");
+        assert_eq!(shared.entry.username, "alice@example.com");
+        assert_eq!(shared.entry.password_str(), "s3cret-Pass!");
+    }
+
+    #[test]
+    fn test_wrong_passphrase_rejected() {
+        let expires = chrono::Utc::now() + chrono::Duration::days(1);
+        let bundle = ShareManager::create_bundle("db", &sample_entry(), "correct horse", expires)
+            .expect("Bundle creation should succeed");
+
+        assert!(ShareManager::open_bundle(&bundle, "battery staple").is_err());
+    }
+
+    #[test]
+    fn test_expired_bundle_rejected() {
+        let expired = chrono::Utc::now() - chrono::Duration::hours(1);
+        let bundle = ShareManager::create_bundle("db", &sample_entry(), "pass", expired)
+            .expect("Bundle creation should succeed");
+
+        let err = ShareManager::open_bundle(&bundle, "pass").err().expect("Expired bundle should fail");
+        assert!(err.to_string().contains("expired"));
+    }
+
+    #[test]
+    fn test_generated_passphrase_format() {
+        let passphrase = ShareManager::generate_passphrase();
+        assert_eq!(passphrase.len(), 29);
+        assert_eq!(passphrase.split('-').count(), 5);
+    }
+
+    #[test]
+    fn test_unique_id_on_conflict() {
+        let mut vault = Vault::new();
+        assert_eq!(ShareManager::unique_id(&vault, "db"), "db");
+
+        vault.add_entry("db".to_string(), sample_entry());
+        assert_eq!(ShareManager::unique_id(&vault, "db"), "db-shared");
+
+        vault.add_entry("db-shared".to_string(), sample_entry());
+        assert_eq!(ShareManager::unique_id(&vault, "db"), "db-shared-2");
+    }
+}
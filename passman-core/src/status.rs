@@ -0,0 +1,288 @@
+//! Vault Status
+//!
+//! A single check of a vault file for scripts and monitoring: the file
+//! itself, its HMAC, the newest backup and what housekeeping would do, with
+//! an overall level a cron job can alert on. Nothing is prompted for and
+//! nothing in the vault is changed; given no master password, the checks
+//! that need one are skipped and reported as warnings.
+//!
+//! The pieces come from [`VaultManager`]: `verify_integrity` for the HMAC,
+//! `backup_infos` and `verify_backup` for the backups, and the header
+//! readers behind `passman vault info`.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use zeroize::Zeroizing;
+use crate::model::HousekeepingLimits;
+use crate::vault::VaultManager;
+
+/// Days after which the newest backup is stale, if the vault changed since
+pub const DEFAULT_STALE_BACKUP_DAYS: i64 = 7;
+
+/// Overall result, worst first when sorted
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusLevel {
+    Ok,
+    /// Nothing is broken, but something needs attention (a stale backup)
+    Warnings,
+    /// The vault or its newest backup failed a check
+    Errors,
+}
+
+/// The newest timestamped backup
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BackupStatus {
+    /// File name, beside the vault
+    pub name: String,
+    pub created: Option<DateTime<Utc>>,
+    pub age_days: Option<i64>,
+    /// Decrypted just now with the master password, or as recorded when it
+    /// was last verified if no password was given; `None` if never checked
+    pub verified: Option<bool>,
+}
+
+/// Trash and archive counts, and what the next save's housekeeping deletes
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct HousekeepingStatus {
+    pub trashed: usize,
+    pub archived: usize,
+    /// Trashed entries past `trash.retention_days`
+    pub due_for_purge: usize,
+}
+
+/// Everything `passman vault status` reports
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct VaultStatus {
+    pub status: StatusLevel,
+    pub path: String,
+    pub size: Option<u64>,
+    /// Format version from the header; `None` for the legacy format
+    pub format_version: Option<u8>,
+    pub cipher: Option<String>,
+    /// `None` when there was no master password to check it with
+    pub hmac_verified: Option<bool>,
+    pub entries: Option<usize>,
+    pub last_modified: Option<DateTime<Utc>>,
+    pub newest_backup: Option<BackupStatus>,
+    pub housekeeping: Option<HousekeepingStatus>,
+    pub warnings: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+impl VaultStatus {
+    /// Check the vault at `vault_path`. The HMAC, the entry counts and the
+    /// newest backup's decryption need `master_password`. The newest backup
+    /// is stale once it is more than `stale_after_days` old and the vault
+    /// has been modified after it.
+    pub fn check(
+        vault_path: &str,
+        master_password: Option<&Zeroizing<String>>,
+        limits: &HousekeepingLimits,
+        stale_after_days: i64,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let mut status = VaultStatus {
+            status: StatusLevel::Ok,
+            path: vault_path.to_string(),
+            size: None,
+            format_version: None,
+            cipher: None,
+            hmac_verified: None,
+            entries: None,
+            last_modified: None,
+            newest_backup: None,
+            housekeeping: None,
+            warnings: Vec::new(),
+            errors: Vec::new(),
+        };
+        let vault_file = Some(vault_path);
+
+        if !VaultManager::exists(vault_file) {
+            status.errors.push("Vault file not found".to_string());
+            return status.settled();
+        }
+        if let Ok(metadata) = fs::metadata(vault_path) {
+            status.size = Some(metadata.len());
+            status.last_modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+        }
+        match VaultManager::format_version(vault_file) {
+            Ok(Some(version)) => status.format_version = Some(version),
+            Ok(None) => status.warnings.push("Legacy (v1) format: no HMAC to verify; unlock and save it once to upgrade".to_string()),
+            Err(e) => status.errors.push(format!("Could not read the vault file: {}", e)),
+        }
+        if status.format_version.is_some() {
+            match VaultManager::cipher(vault_file) {
+                Ok(cipher) => status.cipher = Some(cipher.to_string()),
+                Err(e) => status.errors.push(format!("Unreadable header: {}", e)),
+            }
+        }
+        if let Some(loose) = VaultManager::loose_permissions(vault_file) {
+            status.warnings.push(loose.message());
+        }
+
+        match master_password {
+            None => status.warnings.push("No master password given: HMAC, entries and backup decryption not checked".to_string()),
+            Some(password) if status.format_version.is_some() && status.cipher.is_some() => {
+                status.check_contents(password, limits, now);
+            }
+            Some(_) => {}
+        }
+        status.check_newest_backup(master_password, stale_after_days, now);
+        status.settled()
+    }
+
+    /// HMAC first, then decrypt for the counts
+    fn check_contents(&mut self, master_password: &Zeroizing<String>, limits: &HousekeepingLimits, now: DateTime<Utc>) {
+        match VaultManager::verify_integrity(master_password, Some(&self.path)) {
+            Ok(true) => self.hmac_verified = Some(true),
+            Ok(false) => {
+                self.hmac_verified = Some(false);
+                self.errors.push("HMAC check failed: wrong master password, or the file was modified".to_string());
+                return;
+            }
+            Err(e) => {
+                self.errors.push(format!("Integrity check failed: {}", e));
+                return;
+            }
+        }
+
+        let vault = fs::read(&self.path)
+            .map_err(|e| e.to_string())
+            .and_then(|buffer| VaultManager::load_from_bytes(master_password, &buffer).map_err(|e| e.to_string()));
+        let vault = match vault {
+            Ok(vault) => vault,
+            Err(e) => {
+                self.errors.push(format!("Vault does not decrypt: {}", e));
+                return;
+            }
+        };
+        self.entries = Some(vault.len());
+        self.housekeeping = Some(HousekeepingStatus {
+            trashed: vault.iter().filter(|(_, entry)| entry.is_trashed()).count(),
+            archived: vault.iter().filter(|(_, entry)| entry.archived).count(),
+            due_for_purge: vault.housekeeping_plan(limits, now).purged_trash.len(),
+        });
+    }
+
+    fn check_newest_backup(&mut self, master_password: Option<&Zeroizing<String>>, stale_after_days: i64, now: DateTime<Utc>) {
+        let Some(newest) = VaultManager::backup_infos(Some(&self.path)).into_iter().next() else {
+            self.warnings.push("No backups; create one with 'passman backup'".to_string());
+            return;
+        };
+        let name = Path::new(&newest.path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| newest.path.clone());
+        let created = newest.created.map(|created| created.and_utc());
+        let age_days = created.map(|created| (now - created).num_days());
+
+        let verified = match master_password {
+            Some(password) => Some(VaultManager::verify_backup(password, &newest.path, Some(&self.path)).is_ok()),
+            None => newest.verified,
+        };
+        match verified {
+            Some(false) => self.errors.push(format!("Newest backup {} does not decrypt with the master password", name)),
+            None => self.warnings.push(format!("Newest backup {} has never been verified", name)),
+            Some(true) => {}
+        }
+        // Backup names only have whole seconds
+        let changed_since = match (self.last_modified, created) {
+            (Some(modified), Some(created)) => modified.timestamp() > created.timestamp(),
+            _ => true,
+        };
+        if let Some(age) = age_days.filter(|age| *age > stale_after_days && changed_since) {
+            self.warnings.push(format!("Newest backup is {} days old and the vault changed since", age));
+        }
+
+        self.newest_backup = Some(BackupStatus { name, created, age_days, verified });
+    }
+
+    /// Set the overall level from the errors and warnings found
+    fn settled(mut self) -> Self {
+        self.status = if !self.errors.is_empty() {
+            StatusLevel::Errors
+        } else if !self.warnings.is_empty() {
+            StatusLevel::Warnings
+        } else {
+            StatusLevel::Ok
+        };
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Entry, Vault};
+
+    const LIMITS: HousekeepingLimits = HousekeepingLimits { trash_retention_days: 30 };
+
+    fn vault_with_backup(dir: &tempfile::TempDir, password: &Zeroizing<String>) -> String {
+        let path = dir.path().join("vault.dat").to_string_lossy().into_owned();
+        let key = VaultManager::init(password, Some(&path)).unwrap();
+        let mut vault = Vault::new();
+        vault.add_entry("github".to_string(), Entry::new("octo".to_string(), "hunter2".to_string(), None));
+        vault.add_entry("old".to_string(), Entry::new("me".to_string(), "pw".to_string(), None));
+        vault.trash_entry("old", Utc::now() - chrono::Duration::days(40));
+        VaultManager::save_with_key(&vault, &key, Some(&path)).unwrap();
+        VaultManager::create_backup(password, Some(&path)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_status_checks_file_hmac_and_newest_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let password = Zeroizing::new("correct horse".to_string());
+        let path = vault_with_backup(&dir, &password);
+
+        let status = VaultStatus::check(&path, Some(&password), &LIMITS, DEFAULT_STALE_BACKUP_DAYS, Utc::now());
+        assert_eq!(status.status, StatusLevel::Ok, "{:?}", status);
+        assert_eq!(status.hmac_verified, Some(true));
+        assert_eq!(status.entries, Some(2));
+        assert_eq!(status.housekeeping, Some(HousekeepingStatus { trashed: 1, archived: 0, due_for_purge: 1 }));
+        let backup = status.newest_backup.unwrap();
+        assert_eq!((backup.verified, backup.age_days), (Some(true), Some(0)));
+
+        // Without a password only what doesn't need one is checked
+        let status = VaultStatus::check(&path, None, &LIMITS, DEFAULT_STALE_BACKUP_DAYS, Utc::now());
+        assert_eq!(status.status, StatusLevel::Warnings);
+        assert_eq!((status.hmac_verified, status.entries), (None, None));
+        assert_eq!(status.newest_backup.unwrap().verified, Some(true));
+
+        // A month on, after a save, the backup is stale
+        let later = Utc::now() + chrono::Duration::days(30);
+        let status = VaultStatus::check(&path, Some(&password), &LIMITS, DEFAULT_STALE_BACKUP_DAYS, later);
+        assert_eq!(status.status, StatusLevel::Ok, "unchanged since the backup");
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        let vault = VaultManager::load_from_bytes(&password, &fs::read(&path).unwrap()).unwrap();
+        VaultManager::save(&vault, &password, Some(&path)).unwrap();
+        let status = VaultStatus::check(&path, Some(&password), &LIMITS, DEFAULT_STALE_BACKUP_DAYS, later);
+        assert_eq!(status.status, StatusLevel::Warnings);
+        assert!(status.warnings[0].contains("30 days old"), "{:?}", status.warnings);
+    }
+
+    #[test]
+    fn test_tampered_vault_and_missing_file_are_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let password = Zeroizing::new("correct horse".to_string());
+        let path = vault_with_backup(&dir, &password);
+
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xFF;
+        fs::write(&path, bytes).unwrap();
+        let status = VaultStatus::check(&path, Some(&password), &LIMITS, DEFAULT_STALE_BACKUP_DAYS, Utc::now());
+        assert_eq!(status.status, StatusLevel::Errors);
+        assert_eq!(status.hmac_verified, Some(false));
+        // The backup is still good
+        assert_eq!(status.newest_backup.unwrap().verified, Some(true));
+
+        let missing = dir.path().join("missing.dat").to_string_lossy().into_owned();
+        let status = VaultStatus::check(&missing, Some(&password), &LIMITS, DEFAULT_STALE_BACKUP_DAYS, Utc::now());
+        assert_eq!(status.status, StatusLevel::Errors);
+        assert_eq!(status.errors, vec!["Vault file not found"]);
+    }
+}
@@ -0,0 +1,383 @@
+//! Sync Directory Module
+//!
+//! Mirrors a vault into a directory that can live in a git repository:
+//! - One encrypted file per entry, named by a keyed hash of the entry id
+//! - Deterministic encryption, so unchanged entries produce identical bytes
+//! - Encrypted index mapping ids to files and modification times
+//! - Plaintext manifest holding only the salt and KDF parameters
+//!
+//! Deletions are not propagated: an entry removed on one machine is
+//! restored by the next import from a directory that still contains it.
+
+#![allow(dead_code)]
+
+use crate::crypto::{derive_key_with_params, encrypt_data_with_nonce, decrypt_data, Key, KdfParams};
+use crate::model::{Entry, Vault};
+use crate::vault::VaultManager;
+use argon2::password_hash::SaltString;
+use hmac::{Hmac, Mac};
+use serde::{Serialize, Deserialize};
+use sha2::Sha256;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Plaintext manifest file name
+const MANIFEST_FILE: &str = "passman-sync.json";
+
+/// Encrypted index file name
+const INDEX_FILE: &str = "index.enc";
+
+/// Directory holding one encrypted file per entry
+const ENTRIES_DIR: &str = "entries";
+
+/// Extension of encrypted entry files
+const ENTRY_EXTENSION: &str = "enc";
+
+/// Sync directory format version
+const SYNC_FORMAT_VERSION: u32 = 1;
+
+/// Domain separation labels for the keyed hashes
+const ID_LABEL: &[u8] = b"passman-sync-id";
+const NONCE_LABEL: &[u8] = b"passman-sync-nonce";
+
+/// Plaintext description of how the sync key is derived
+#[derive(Serialize, Deserialize)]
+struct SyncManifest {
+    format: u32,
+    salt: String,
+    memory_kb: u32,
+    time_cost: u32,
+    parallelism: u32,
+}
+
+impl SyncManifest {
+    fn kdf(&self) -> KdfParams {
+        KdfParams {
+            memory_kb: self.memory_kb,
+            time_cost: self.time_cost,
+            parallelism: self.parallelism,
+        }
+    }
+}
+
+/// Index record for one entry
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+struct IndexRecord {
+    file: String,
+    modified_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Decrypted contents of an entry file
+#[derive(Serialize, Deserialize)]
+struct EntryPayload {
+    id: String,
+    entry: Entry,
+}
+
+/// Outcome of a sync export or import
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    /// Entries imported that did not exist locally
+    pub added: usize,
+    /// Local entries replaced by newer remote versions
+    pub updated: usize,
+    /// Entries that needed no change
+    pub unchanged: usize,
+    /// Ids under which conflicting remote versions were imported
+    pub conflicts: Vec<String>,
+    /// Entry files written on export
+    pub written: usize,
+    /// Stale entry files removed on export
+    pub removed: usize,
+}
+
+pub struct SyncManager;
+
+impl SyncManager {
+    /// Write the vault into a sync directory, leaving unchanged entry files untouched
+    pub fn export(
+        vault: &mut Vault,
+        master_password: &Zeroizing<String>,
+        vault_file: Option<&str>,
+        dir: &str,
+    ) -> Result<SyncReport, Box<dyn std::error::Error>> {
+        let dir = Path::new(dir);
+        let entries_dir = dir.join(ENTRIES_DIR);
+        fs::create_dir_all(&entries_dir)?;
+
+        let manifest = match Self::read_manifest(dir)? {
+            Some(manifest) => manifest,
+            None => {
+                let (salt, kdf) = VaultManager::key_params(vault_file)?;
+                let manifest = SyncManifest {
+                    format: SYNC_FORMAT_VERSION,
+                    salt: salt.as_str().to_string(),
+                    memory_kb: kdf.memory_kb,
+                    time_cost: kdf.time_cost,
+                    parallelism: kdf.parallelism,
+                };
+                fs::write(dir.join(MANIFEST_FILE), serde_json::to_string_pretty(&manifest)?)?;
+                manifest
+            }
+        };
+        let key = Self::derive_sync_key(master_password, &manifest)?;
+
+        // Make sure an existing directory belongs to this master password
+        if dir.join(INDEX_FILE).exists() {
+            Self::read_index(dir, &key)?;
+        }
+
+        let mut report = SyncReport::default();
+        let mut index = BTreeMap::new();
+        for (id, entry) in vault.iter() {
+            let file = Self::entry_file_name(&key, id)?;
+            let payload = EntryPayload { id: id.to_string(), entry: entry.clone() };
+            let data = Self::seal(&key, &serde_json::to_vec(&payload)?)?;
+
+            let path = entries_dir.join(&file);
+            if fs::read(&path).ok().as_deref() == Some(data.as_slice()) {
+                report.unchanged += 1;
+            } else {
+                fs::write(&path, &data)?;
+                report.written += 1;
+            }
+            index.insert(id.to_string(), IndexRecord { file, modified_at: entry.modified_at });
+        }
+
+        // Remove files for entries that no longer exist in the vault
+        let live: HashSet<&str> = index.values().map(|record| record.file.as_str()).collect();
+        for dir_entry in fs::read_dir(&entries_dir)? {
+            let path = dir_entry?.path();
+            let is_entry_file = path.extension().and_then(|e| e.to_str()) == Some(ENTRY_EXTENSION);
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            if is_entry_file && !live.contains(name) {
+                fs::remove_file(&path)?;
+                report.removed += 1;
+            }
+        }
+
+        let index_data = Self::seal(&key, &serde_json::to_vec(&index)?)?;
+        if fs::read(dir.join(INDEX_FILE)).ok() != Some(index_data.clone()) {
+            fs::write(dir.join(INDEX_FILE), index_data)?;
+        }
+
+        vault.metadata.last_synced_at = Some(chrono::Utc::now());
+        VaultManager::save(vault, master_password, vault_file)?;
+
+        log::info!("Sync export to {}: {} written, {} removed", dir.display(), report.written, report.removed);
+        Ok(report)
+    }
+
+    /// Merge a sync directory into the vault.
+    ///
+    /// The newer side wins by `modified_at`. An entry changed on both sides
+    /// since the last sync keeps the local version and imports the remote
+    /// one under a suffixed id.
+    pub fn import(
+        vault: &mut Vault,
+        master_password: &Zeroizing<String>,
+        vault_file: Option<&str>,
+        dir: &str,
+    ) -> Result<SyncReport, Box<dyn std::error::Error>> {
+        let dir = Path::new(dir);
+        let manifest = Self::read_manifest(dir)?
+            .ok_or_else(|| format!("'{}' is not a Passman sync directory", dir.display()))?;
+        let key = Self::derive_sync_key(master_password, &manifest)?;
+        let index = Self::read_index(dir, &key)?;
+
+        let mut remote = Vec::with_capacity(index.len());
+        for (id, record) in &index {
+            let data = fs::read(dir.join(ENTRIES_DIR).join(&record.file))
+                .map_err(|e| format!("Missing sync file for '{}': {}", id, e))?;
+            let plaintext = Self::open(&key, &data)
+                .map_err(|_| format!("Sync file for '{}' is corrupted", id))?;
+            let payload: EntryPayload = serde_json::from_slice(&plaintext)?;
+            if &payload.id != id {
+                return Err(format!("Sync file for '{}' contains entry '{}'", id, payload.id).into());
+            }
+            remote.push(payload);
+        }
+
+        let report = Self::merge(vault, remote);
+
+        vault.metadata.last_synced_at = Some(chrono::Utc::now());
+        VaultManager::save(vault, master_password, vault_file)?;
+
+        log::info!(
+            "Sync import from {}: {} added, {} updated, {} conflicts",
+            dir.display(), report.added, report.updated, report.conflicts.len()
+        );
+        Ok(report)
+    }
+
+    /// Apply remote entries to the vault
+    fn merge(vault: &mut Vault, remote: Vec<EntryPayload>) -> SyncReport {
+        let last_sync = vault.metadata.last_synced_at;
+        let changed_since_sync = |entry: &Entry| last_sync.is_none_or(|t| entry.modified_at > t);
+
+        let mut report = SyncReport::default();
+        for EntryPayload { id, entry } in remote {
+            let Some(local) = vault.get_entry(&id) else {
+                vault.add_entry(id, entry);
+                report.added += 1;
+                continue;
+            };
+
+            if local.modified_at == entry.modified_at {
+                report.unchanged += 1;
+            } else if changed_since_sync(local) && changed_since_sync(&entry) {
+                let conflict_id = Self::conflict_id(vault, &id);
+                vault.add_entry(conflict_id.clone(), entry);
+                report.conflicts.push(conflict_id);
+            } else if entry.modified_at > local.modified_at {
+                vault.add_entry(id, entry);
+                report.updated += 1;
+            } else {
+                report.unchanged += 1;
+            }
+        }
+        report
+    }
+
+    /// Find a free id for a conflicting copy: "-conflict", "-conflict-2", ...
+    fn conflict_id(vault: &Vault, id: &str) -> String {
+        let base = format!("{}-conflict", id);
+        if vault.get_entry(&base).is_none() {
+            return base;
+        }
+
+        (2..)
+            .map(|n| format!("{}-{}", base, n))
+            .find(|candidate| vault.get_entry(candidate).is_none())
+            .unwrap_or(base)
+    }
+
+    fn read_manifest(dir: &Path) -> Result<Option<SyncManifest>, Box<dyn std::error::Error>> {
+        let path = dir.join(MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let manifest: SyncManifest = serde_json::from_str(&fs::read_to_string(path)?)?;
+        if manifest.format != SYNC_FORMAT_VERSION {
+            return Err(format!("Unsupported sync format version: {}", manifest.format).into());
+        }
+        Ok(Some(manifest))
+    }
+
+    fn read_index(dir: &Path, key: &Key) -> Result<BTreeMap<String, IndexRecord>, Box<dyn std::error::Error>> {
+        let data = fs::read(dir.join(INDEX_FILE))
+            .map_err(|e| format!("Cannot read sync index: {}", e))?;
+        let plaintext = Self::open(key, &data)
+            .map_err(|_| "Wrong master password for this sync directory, or corrupted index")?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    fn derive_sync_key(master_password: &Zeroizing<String>, manifest: &SyncManifest) -> Result<Key, Box<dyn std::error::Error>> {
+        let salt = SaltString::from_b64(&manifest.salt)
+            .map_err(|e| format!("Salt parsing error: {}", e))?;
+        Ok(derive_key_with_params(master_password.as_str(), &salt, &manifest.kdf())?)
+    }
+
+    /// Keyed hash of an entry id, so file names do not reveal entry names
+    fn entry_file_name(key: &Key, id: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let mut mac = HmacSha256::new_from_slice(key.as_ref())
+            .map_err(|e| format!("HMAC error: {}", e))?;
+        mac.update(ID_LABEL);
+        mac.update(id.as_bytes());
+        let digest = mac.finalize().into_bytes();
+        let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        Ok(format!("{}.{}", hex, ENTRY_EXTENSION))
+    }
+
+    /// Encrypt with a nonce derived from the plaintext: [nonce(12)][ciphertext]
+    fn seal(key: &Key, plaintext: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut mac = HmacSha256::new_from_slice(key.as_ref())
+            .map_err(|e| format!("HMAC error: {}", e))?;
+        mac.update(NONCE_LABEL);
+        mac.update(plaintext);
+        let digest = mac.finalize().into_bytes();
+        let nonce: [u8; 12] = digest[..12].try_into()?;
+
+        let ciphertext = encrypt_data_with_nonce(key, plaintext, &nonce)?;
+        let mut data = Vec::with_capacity(12 + ciphertext.len());
+        data.extend_from_slice(&nonce);
+        data.extend_from_slice(&ciphertext);
+        Ok(data)
+    }
+
+    fn open(key: &Key, data: &[u8]) -> Result<Zeroizing<Vec<u8>>, Box<dyn std::error::Error>> {
+        if data.len() < 12 {
+            return Err("Sync file too short".into());
+        }
+        let nonce: [u8; 12] = data[..12].try_into()?;
+        Ok(Zeroizing::new(decrypt_data(key, &data[12..], &nonce)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_key() -> Key {
+        let salt = SaltString::from_b64("c29tZXNhbHRzb21lc2FsdA").unwrap();
+        let kdf = KdfParams { memory_kb: 8192, time_cost: 1, parallelism: 1 };
+        derive_key_with_params("master", &salt, &kdf).unwrap()
+    }
+
+    fn entry_at(username: &str, modified_at: chrono::DateTime<chrono::Utc>) -> Entry {
+        let mut entry = Entry::new(username.to_string(), "pw".to_string(), None);
+        entry.modified_at = modified_at;
+        entry
+    }
+
+    #[test]
+    fn test_seal_is_deterministic() {
+        let key = test_key();
+        let first = SyncManager::seal(&key, b"same entry").unwrap();
+        let second = SyncManager::seal(&key, b"same entry").unwrap();
+        let other = SyncManager::seal(&key, b"other entry").unwrap();
+
+        assert_eq!(first, second);
+        assert_ne!(first[..12], other[..12]);
+        assert_eq!(SyncManager::open(&key, &first).unwrap().as_slice(), b"same entry");
+    }
+
+    #[test]
+    fn test_file_name_hides_id() {
+        let key = test_key();
+        let name = SyncManager::entry_file_name(&key, "github").unwrap();
+
+        assert!(!name.contains("github"));
+        assert_eq!(name, SyncManager::entry_file_name(&key, "github").unwrap());
+    }
+
+    #[test]
+    fn test_merge_newer_wins_and_conflicts_are_suffixed() {
+        let synced = chrono::Utc::now() - chrono::Duration::hours(2);
+        let before = synced - chrono::Duration::hours(1);
+        let after = synced + chrono::Duration::hours(1);
+
+        let mut vault = Vault::new();
+        vault.metadata.last_synced_at = Some(synced);
+        vault.add_entry("stale".to_string(), entry_at("old", before));
+        vault.add_entry("both".to_string(), entry_at("local", after));
+
+        let remote = vec![
+            EntryPayload { id: "stale".to_string(), entry: entry_at("new", after) },
+            EntryPayload { id: "both".to_string(), entry: entry_at("remote", after + chrono::Duration::minutes(5)) },
+            EntryPayload { id: "fresh".to_string(), entry: entry_at("added", after) },
+        ];
+        let report = SyncManager::merge(&mut vault, remote);
+
+        assert_eq!(report.added, 1);
+        assert_eq!(report.updated, 1);
+        assert_eq!(report.conflicts, vec!["both-conflict".to_string()]);
+        assert_eq!(vault.get_entry("stale").unwrap().username, "new");
+        assert_eq!(vault.get_entry("both").unwrap().username, "local");
+        assert_eq!(vault.get_entry("both-conflict").unwrap().username, "remote");
+    }
+}
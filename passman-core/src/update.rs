@@ -0,0 +1,258 @@
+//! Update Check
+//!
+//! Opt-in check for newer releases. The project publishes a small JSON
+//! manifest signed with its Ed25519 release key; it is fetched over HTTPS,
+//! verified against the public key in `release-key.pub`, and only ever
+//! reported to the user. Nothing is downloaded or installed. Fetching needs
+//! the `https` feature; verifying doesn't.
+
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fs;
+
+use crate::config::Config;
+use crate::error::{PassmanError, PassmanResult, TransferError};
+#[cfg(feature = "https")]
+use crate::net::NetPolicy;
+
+/// Where the signed release manifest is published
+pub const MANIFEST_URL: &str = "https://github.com/0M3REXE/passman/releases/latest/download/update.json";
+
+/// The maintainers' key file: comments, then the public key as hex
+const RELEASE_KEY_FILE: &str = include_str!("../release-key.pub");
+
+/// Days between automatic checks
+pub const CHECK_INTERVAL_DAYS: i64 = 7;
+
+/// Sidecar file with the last check time and result
+const UPDATE_STATE_FILE: &str = "update_check.json";
+
+/// The manifest is a few hundred bytes; anything bigger is not ours
+#[cfg(feature = "https")]
+const MAX_MANIFEST_BYTES: usize = 16 * 1024;
+
+#[cfg(feature = "https")]
+const FEATURE: &str = "Update check";
+
+/// What the project says about its latest release
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReleaseManifest {
+    pub version: String,
+    /// Releases older than this have known security issues
+    pub min_secure_version: String,
+    pub notes_url: String,
+}
+
+/// Wire format: the manifest as a string, so the signature covers its exact bytes
+#[derive(Deserialize)]
+struct SignedManifest {
+    manifest: String,
+    /// Hex-encoded Ed25519 signature of `manifest`
+    signature: String,
+}
+
+/// A newer release the user should hear about
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateNotice {
+    pub current: String,
+    pub latest: String,
+    /// The running version is below the minimum secure version
+    pub security: bool,
+    pub notes_url: String,
+}
+
+impl ReleaseManifest {
+    /// Check the signature and parse the manifest it covers
+    pub fn from_signed(bytes: &[u8], key: &[u8; 32]) -> PassmanResult<Self> {
+        let invalid = |msg: &str| PassmanError::Transfer(TransferError::InvalidData(format!("release manifest {}", msg)));
+        let signed: SignedManifest = serde_json::from_slice(bytes).map_err(|_| invalid("is malformed"))?;
+        let signature: [u8; 64] = hex_decode(&signed.signature)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| invalid("has a malformed signature"))?;
+        VerifyingKey::from_bytes(key)
+            .and_then(|key| key.verify_strict(signed.manifest.as_bytes(), &Signature::from_bytes(&signature)))
+            .map_err(|_| invalid("signature does not match the release key"))?;
+        serde_json::from_str(&signed.manifest).map_err(|_| invalid("is malformed"))
+    }
+
+    /// Notice for `current`, if this release is newer
+    pub fn notice_for(&self, current: &str) -> Option<UpdateNotice> {
+        if compare_versions(&self.version, current) != Some(Ordering::Greater) {
+            return None;
+        }
+        Some(UpdateNotice {
+            current: current.to_string(),
+            latest: self.version.clone(),
+            security: compare_versions(current, &self.min_secure_version) == Some(Ordering::Less),
+            notes_url: self.notes_url.clone(),
+        })
+    }
+}
+
+/// Compare dotted numeric versions ("1.10.0" > "1.9.2"); a leading `v` and
+/// pre-release suffixes are ignored
+pub fn compare_versions(a: &str, b: &str) -> Option<Ordering> {
+    let parse = |v: &str| -> Option<Vec<u64>> {
+        let core = v.trim().trim_start_matches('v').split(['-', '+']).next()?;
+        core.split('.').map(|part| part.parse().ok()).collect()
+    };
+    let (mut a, mut b) = (parse(a)?, parse(b)?);
+    let len = a.len().max(b.len());
+    a.resize(len, 0);
+    b.resize(len, 0);
+    Some(a.cmp(&b))
+}
+
+/// Public half of the release signing key; `None` until the maintainers
+/// have put theirs in `release-key.pub`
+pub fn release_signing_key() -> Option<[u8; 32]> {
+    let line = RELEASE_KEY_FILE.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))?;
+    hex_decode(line)?.try_into().ok()
+}
+
+fn hex_decode(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| s.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+        .collect()
+}
+
+/// Last check, kept next to the config so the endpoint is hit at most weekly
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateState {
+    pub checked_at: Option<DateTime<Utc>>,
+    pub latest: Option<ReleaseManifest>,
+    /// Version whose notice the user dismissed
+    pub dismissed: Option<String>,
+}
+
+impl UpdateState {
+    pub fn load() -> Self {
+        fs::read_to_string(Config::config_path().with_file_name(UPDATE_STATE_FILE))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if let Ok(contents) = serde_json::to_string_pretty(self) {
+            let _ = fs::write(Config::config_path().with_file_name(UPDATE_STATE_FILE), contents);
+        }
+    }
+
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.checked_at.is_none_or(|last| now - last >= chrono::Duration::days(CHECK_INTERVAL_DAYS))
+    }
+
+    /// Stop showing the notice for `version`
+    pub fn dismiss(version: &str) {
+        let mut state = Self::load();
+        state.dismissed = Some(version.to_string());
+        state.save();
+    }
+}
+
+/// Fetch and verify the manifest now, recording the result
+#[cfg(feature = "https")]
+pub fn check_now(policy: &NetPolicy) -> PassmanResult<ReleaseManifest> {
+    let key = release_signing_key()
+        .ok_or_else(|| PassmanError::Other(format!("{} is unavailable: this build has no release signing key", FEATURE)))?;
+    let bytes = policy.fetch_https(FEATURE, MANIFEST_URL, MAX_MANIFEST_BYTES)?;
+    let manifest = ReleaseManifest::from_signed(&bytes, &key)?;
+
+    let mut state = UpdateState::load();
+    state.checked_at = Some(Utc::now());
+    state.latest = Some(manifest.clone());
+    state.save();
+    Ok(manifest)
+}
+
+/// The weekly background check: fetches only when due, otherwise answers
+/// from the last result. Dismissed versions stay quiet.
+#[cfg(feature = "https")]
+pub fn check_if_due(policy: &NetPolicy, current: &str) -> PassmanResult<Option<UpdateNotice>> {
+    let state = UpdateState::load();
+    let manifest = if state.is_due(Utc::now()) {
+        // Record the attempt even if it fails, so a dead endpoint isn't retried every launch
+        let mut attempted = state.clone();
+        attempted.checked_at = Some(Utc::now());
+        attempted.save();
+        Some(check_now(policy)?)
+    } else {
+        state.latest.clone()
+    };
+
+    Ok(manifest
+        .and_then(|manifest| manifest.notice_for(current))
+        .filter(|notice| state.dismissed.as_deref() != Some(notice.latest.as_str())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    /// Throwaway key for these tests only. Its private half is right here in
+    /// the source, so it must never be the release key.
+    const TEST_SIGNING_SEED: &str = "b36f1d63fa7235120ddcdc6c7134bde92753a9282ee2826dd7c1fa5ee1e4da25";
+
+    /// A manifest signed with the test key
+    const SIGNED: &str = r#"{"manifest":"{\"version\":\"1.6.0\",\"min_secure_version\":\"1.5.1\",\"notes_url\":\"https://github.com/0M3REXE/passman/releases/tag/v1.6.0\"}","signature":"4e97a166b79301262fa8b40d1f3eeab39945b00c1c36034a19279cb24ee1dcc1a305861dcf24bf10fa370f71946c870e1c48dfde0f4efc9ff89d406499eef90d"}"#;
+
+    fn test_signing_key() -> SigningKey {
+        SigningKey::from_bytes(&hex_decode(TEST_SIGNING_SEED).unwrap().try_into().unwrap())
+    }
+
+    #[test]
+    fn test_signed_manifest_verifies() {
+        let key = test_signing_key().verifying_key().to_bytes();
+        let manifest = ReleaseManifest::from_signed(SIGNED.as_bytes(), &key).unwrap();
+        assert_eq!(manifest.version, "1.6.0");
+
+        let notice = manifest.notice_for("1.5.0").unwrap();
+        assert!(notice.security);
+        assert!(!manifest.notice_for("1.5.1").unwrap().security);
+        assert_eq!(manifest.notice_for("1.6.0"), None);
+
+        let tampered = SIGNED.replace("1.6.0", "9.9.9");
+        assert!(ReleaseManifest::from_signed(tampered.as_bytes(), &key).is_err());
+        assert!(ReleaseManifest::from_signed(SIGNED.as_bytes(), &[7; 32]).is_err());
+        assert!(ReleaseManifest::from_signed(b"{}", &key).is_err());
+
+        // A manifest signed here verifies the same way
+        let manifest = r#"{"version":"2.0.0","min_secure_version":"0","notes_url":""}"#;
+        let signature = test_signing_key().sign(manifest.as_bytes()).to_bytes();
+        let hex: String = signature.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let signed = serde_json::json!({ "manifest": manifest, "signature": hex }).to_string();
+        assert_eq!(ReleaseManifest::from_signed(signed.as_bytes(), &key).unwrap().version, "2.0.0");
+    }
+
+    #[test]
+    fn test_release_key_file() {
+        // A key line that doesn't parse would silently turn update checks off
+        let has_key_line = RELEASE_KEY_FILE.lines().map(str::trim).any(|line| !line.is_empty() && !line.starts_with('#'));
+        assert_eq!(release_signing_key().is_some(), has_key_line);
+        assert_ne!(release_signing_key(), Some(test_signing_key().verifying_key().to_bytes()));
+    }
+
+    #[test]
+    fn test_version_ordering_and_interval() {
+        assert_eq!(compare_versions("1.10.0", "1.9.2"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("v1.5", "1.5.0"), Some(Ordering::Equal));
+        assert_eq!(compare_versions("1.6.0-rc1", "1.5.9"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("one", "1.0"), None);
+
+        let now = Utc::now();
+        assert!(UpdateState::default().is_due(now));
+        let recent = UpdateState { checked_at: Some(now - chrono::Duration::days(2)), ..Default::default() };
+        assert!(!recent.is_due(now));
+        assert!(recent.is_due(now + chrono::Duration::days(6)));
+    }
+}
@@ -0,0 +1,225 @@
+//! URL Matching Module
+//!
+//! Which site an entry belongs to: the registrable domain ("example.co.uk"
+//! for "https://login.shop.example.co.uk/") of its URL, or of a
+//! domain-looking word in its id when it has no URL. Used to group the
+//! entry list by site, to spot passwords shared within one site and to
+//! propose ids for new entries.
+//!
+//! Suffixes come from a small embedded subset of the public suffix list;
+//! hosts under a suffix it doesn't know fall back to the last label, which
+//! is right for every generic and most country-code TLDs.
+
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use crate::model::{Entry, Vault};
+
+/// Label shown for entries without a site
+pub const OTHER_SITE: &str = "Other";
+
+/// Public suffixes of more than one label, so "example.co.uk" isn't
+/// grouped with every other ".co.uk" site. Includes hosting suffixes where
+/// each subdomain belongs to a different owner.
+const MULTI_LABEL_SUFFIXES: &[&str] = &[
+    "ac.uk", "co.uk", "gov.uk", "ltd.uk", "me.uk", "net.uk", "nhs.uk", "org.uk", "plc.uk", "sch.uk",
+    "com.au", "edu.au", "gov.au", "net.au", "org.au",
+    "co.nz", "govt.nz", "net.nz", "org.nz",
+    "ac.jp", "co.jp", "go.jp", "ne.jp", "or.jp",
+    "ac.kr", "co.kr", "go.kr", "or.kr",
+    "ac.in", "co.in", "gov.in", "net.in", "org.in",
+    "ac.za", "co.za", "gov.za", "org.za",
+    "ac.il", "co.il", "org.il",
+    "com.ar", "com.br", "com.cn", "com.co", "com.hk", "com.mx", "com.my", "com.sg", "com.tr", "com.tw", "com.ua",
+    "net.br", "net.cn", "org.br", "org.cn", "gov.br", "gov.cn",
+    "appspot.com", "azurewebsites.net", "blogspot.com", "cloudfront.net", "github.io", "gitlab.io",
+    "herokuapp.com", "netlify.app", "pages.dev", "vercel.app", "workers.dev",
+];
+
+/// Host of a URL without user info or port, lowercased. Bare hosts
+/// ("github.com/login") are accepted.
+pub fn url_host(url: &str) -> Option<String> {
+    let rest = url.trim().split_once("://").map_or(url.trim(), |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host_port = authority.rsplit('@').next().unwrap_or(authority);
+    let host = match host_port.strip_prefix('[') {
+        // IPv6 literal
+        Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+        None => host_port.split(':').next().unwrap_or_default(),
+    };
+    let host = host.trim_end_matches('.');
+    (!host.is_empty()).then(|| host.to_lowercase())
+}
+
+/// The registrable domain of a host: its public suffix plus one label.
+/// IP addresses and single-label hosts ("localhost", "nas") are their own
+/// site; a bare public suffix ("co.uk") has none.
+pub fn registrable_domain(host: &str) -> Option<String> {
+    let host = host.trim().trim_end_matches('.').to_lowercase();
+    if host.is_empty() || host.parse::<IpAddr>().is_ok() || !host.contains('.') {
+        return (!host.is_empty()).then_some(host);
+    }
+    let labels: Vec<&str> = host.split('.').collect();
+    if labels.iter().any(|label| label.is_empty()) {
+        return None;
+    }
+    let suffix_labels = MULTI_LABEL_SUFFIXES.iter()
+        .filter(|suffix| host.ends_with(&format!(".{}", suffix)) || host == **suffix)
+        .map(|suffix| suffix.split('.').count())
+        .max()
+        .unwrap_or(1);
+    (labels.len() > suffix_labels).then(|| labels[labels.len() - suffix_labels - 1..].join("."))
+}
+
+/// Site an entry belongs to: from its URL, else from the first word of its
+/// id that looks like a domain ("github.com (work)")
+pub fn entry_site(id: &str, entry: &Entry) -> Option<String> {
+    if let Some(host) = entry.url.as_deref().and_then(url_host) {
+        return registrable_domain(&host);
+    }
+    id.split(|c: char| c.is_whitespace() || "()[],;/@".contains(c))
+        .find(|word| looks_like_domain(word))
+        .and_then(registrable_domain)
+}
+
+/// Group items by site, keeping their order within each group. Sites are
+/// sorted by name and items without a site come last, under `None`.
+pub fn group_by_site<T>(items: impl IntoIterator<Item = T>, site_of: impl Fn(&T) -> Option<String>) -> Vec<(Option<String>, Vec<T>)> {
+    let mut sites: BTreeMap<String, Vec<T>> = BTreeMap::new();
+    let mut other = Vec::new();
+    for item in items {
+        match site_of(&item) {
+            Some(site) => sites.entry(site).or_default().push(item),
+            None => other.push(item),
+        }
+    }
+    let mut groups: Vec<(Option<String>, Vec<T>)> = sites.into_iter().map(|(site, items)| (Some(site), items)).collect();
+    if !other.is_empty() {
+        groups.push((None, other));
+    }
+    groups
+}
+
+/// Entry id proposed for a new entry: the name part of the site its URL
+/// points at ("https://github.com/login" → "github", "shop.example.co.uk"
+/// → "example"), else of the domain of an email-address username. IP
+/// addresses and single-label hosts are used whole; punycode labels are kept
+/// as they are. Gets " (2)", " (3)"… when the vault already has the id,
+/// ignoring case.
+pub fn suggest_entry_id(vault: &Vault, url: Option<&str>, username: Option<&str>) -> Option<String> {
+    let host = url.and_then(url_host)
+        .or_else(|| username.and_then(|username| username.rsplit_once('@')).map(|(_, domain)| domain.trim().to_lowercase()))
+        .filter(|host| !host.is_empty())?;
+    let base = if host.parse::<IpAddr>().is_ok() {
+        host
+    } else {
+        let domain = registrable_domain(&host)?;
+        domain.split('.').next().unwrap_or_default().to_string()
+    };
+    if base.is_empty() {
+        return None;
+    }
+    Some(unused_id(vault, &base))
+}
+
+/// `base`, or "base (2)", "base (3)"… if the vault already has it
+pub fn unused_id(vault: &Vault, base: &str) -> String {
+    std::iter::once(base.to_string())
+        .chain((2..).map(|n| format!("{} ({})", base, n)))
+        .find(|candidate| vault.find_id_ignoring_case(candidate).is_none())
+        .expect("the candidates never run out")
+}
+
+/// Dotted word whose last label is alphabetic, like a TLD
+fn looks_like_domain(word: &str) -> bool {
+    let word = word.trim_end_matches('.');
+    let Some((_, tld)) = word.rsplit_once('.') else {
+        return false;
+    };
+    tld.len() >= 2
+        && tld.chars().all(|c| c.is_ascii_alphabetic())
+        && word.split('.').all(|label| !label.is_empty())
+        && word.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(url: Option<&str>) -> Entry {
+        let mut entry = Entry::new("ann".to_string(), "pw".to_string(), None);
+        entry.url = url.map(str::to_string);
+        entry
+    }
+
+    #[test]
+    fn test_registrable_domain_with_multi_label_suffixes() {
+        assert_eq!(registrable_domain("login.shop.example.co.uk").as_deref(), Some("example.co.uk"));
+        assert_eq!(registrable_domain("example.co.uk").as_deref(), Some("example.co.uk"));
+        assert_eq!(registrable_domain("co.uk"), None);
+        assert_eq!(registrable_domain("bank.com.au").as_deref(), Some("bank.com.au"));
+        assert_eq!(registrable_domain("www.github.com").as_deref(), Some("github.com"));
+        assert_eq!(registrable_domain("ann.github.io").as_deref(), Some("ann.github.io"));
+        // Suffixes match whole labels only
+        assert_eq!(registrable_domain("mail.notco.uk").as_deref(), Some("notco.uk"));
+        assert_eq!(registrable_domain("192.168.1.1").as_deref(), Some("192.168.1.1"));
+        assert_eq!(registrable_domain("localhost").as_deref(), Some("localhost"));
+        assert_eq!(registrable_domain("a..b"), None);
+    }
+
+    #[test]
+    fn test_url_host_strips_scheme_user_and_port() {
+        assert_eq!(url_host("https://ann@Mail.Example.com:8443/inbox?x=1").as_deref(), Some("mail.example.com"));
+        assert_eq!(url_host("github.com/login").as_deref(), Some("github.com"));
+        assert_eq!(url_host("http://[::1]:8080/").as_deref(), Some("::1"));
+        assert_eq!(url_host("  "), None);
+    }
+
+    #[test]
+    fn test_entry_site_from_url_then_id() {
+        assert_eq!(entry_site("work mail", &entry(Some("https://mail.google.com"))).as_deref(), Some("google.com"));
+        assert_eq!(entry_site("github.com (work)", &entry(None)).as_deref(), Some("github.com"));
+        assert_eq!(entry_site("bbc.co.uk", &entry(None)).as_deref(), Some("bbc.co.uk"));
+        assert_eq!(entry_site("wifi password", &entry(None)), None);
+        assert_eq!(entry_site("v1.2", &entry(None)), None);
+    }
+
+    #[test]
+    fn test_suggest_entry_id_from_unusual_urls() {
+        let mut vault = Vault::new();
+        let suggest = |vault: &Vault, url: &str| suggest_entry_id(vault, Some(url), None);
+        assert_eq!(suggest(&vault, "https://github.com/login").as_deref(), Some("github"));
+        assert_eq!(suggest(&vault, "https://login.shop.example.co.uk/a/b/c?next=/d#top").as_deref(), Some("example"));
+        assert_eq!(suggest(&vault, "http://192.168.1.1:8080/admin").as_deref(), Some("192.168.1.1"));
+        assert_eq!(suggest(&vault, "http://[::1]:8443/").as_deref(), Some("::1"));
+        assert_eq!(suggest(&vault, "https://nas:5001/").as_deref(), Some("nas"));
+        assert_eq!(suggest(&vault, "https://www.xn--mnchen-3ya.de/").as_deref(), Some("xn--mnchen-3ya"));
+        assert_eq!(suggest(&vault, "https://co.uk/"), None);
+        assert_eq!(suggest(&vault, ""), None);
+
+        assert_eq!(suggest_entry_id(&vault, None, Some("ann@Mail.Proton.me")).as_deref(), Some("proton"));
+        assert_eq!(suggest_entry_id(&vault, None, Some("ann")), None);
+
+        vault.add_entry("GitHub".to_string(), entry(None));
+        vault.add_entry("github (2)".to_string(), entry(None));
+        assert_eq!(suggest(&vault, "github.com").as_deref(), Some("github (3)"));
+    }
+
+    #[test]
+    fn test_group_by_site_puts_other_last() {
+        let items = vec![
+            ("z", Some("b.com")),
+            ("y", None),
+            ("x", Some("a.com")),
+            ("w", Some("b.com")),
+        ];
+        let groups = group_by_site(items, |(_, site)| site.map(str::to_string));
+        let summary: Vec<(Option<&str>, Vec<&str>)> = groups.iter()
+            .map(|(site, items)| (site.as_deref(), items.iter().map(|(id, _)| *id).collect()))
+            .collect();
+        assert_eq!(summary, vec![
+            (Some("a.com"), vec!["x"]),
+            (Some("b.com"), vec!["z", "w"]),
+            (None, vec!["y"]),
+        ]);
+    }
+}
@@ -0,0 +1,972 @@
+//! Password Utilities
+//!
+//! Password generation, strength analysis and small file helpers.
+
+use std::fs::File;
+use std::path::Path;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use zeroize::Zeroizing;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PasswordConfig {
+    pub include_uppercase: bool,
+    pub include_lowercase: bool,
+    pub include_numbers: bool,
+    pub include_symbols: bool,
+    pub exclude_ambiguous: bool, // 0, O, l, I, etc.
+    /// Characters a site forbids, removed from every character set
+    #[serde(default)]
+    pub excluded_chars: String,
+    /// First character must be a letter
+    #[serde(default)]
+    pub require_leading_alpha: bool,
+    /// Upper bound on the generated length
+    #[serde(default)]
+    pub max_length: Option<usize>,
+}
+
+impl Default for PasswordConfig {
+    fn default() -> Self {
+        Self {
+            include_uppercase: true,
+            include_lowercase: true,
+            include_numbers: true,
+            include_symbols: true,
+            exclude_ambiguous: false,
+            excluded_chars: String::new(),
+            require_leading_alpha: false,
+            max_length: None,
+        }
+    }
+}
+
+/// Check if file exists
+#[allow(dead_code)]
+pub fn file_exists(path: &str) -> bool {
+    Path::new(path).exists()
+}
+
+/// Create file if it doesn't exist
+#[allow(dead_code)]
+pub fn ensure_file_exists(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if !file_exists(path) {
+        File::create(path)?;
+    }
+    Ok(())
+}
+
+/// Read entire file as bytes
+#[allow(dead_code)]
+pub fn read_file_bytes(path: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    Ok(std::fs::read(path)?)
+}
+
+/// Write bytes to file
+#[allow(dead_code)]
+pub fn write_file_bytes(path: &str, data: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(path, data)?;
+    Ok(())
+}
+
+pub fn generate_password(length: usize) -> String {
+    use rand::Rng;
+    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ\
+                            abcdefghijklmnopqrstuvwxyz\
+                            0123456789\
+                            !@#$%^&*()_+-=[]{}|;:,.<>?";
+    
+    let mut rng = rand::thread_rng();
+    (0..length)
+        .map(|_| {
+            let idx = rng.gen_range(0..CHARSET.len());
+            CHARSET[idx] as char
+        })
+        .collect()
+}
+
+/// Password strength levels
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum PasswordStrength {
+    VeryWeak,
+    Weak,
+    Fair,
+    Good,
+    Strong,
+}
+
+impl std::fmt::Display for PasswordStrength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PasswordStrength::VeryWeak => write!(f, "Very Weak"),
+            PasswordStrength::Weak => write!(f, "Weak"),
+            PasswordStrength::Fair => write!(f, "Fair"),
+            PasswordStrength::Good => write!(f, "Good"),
+            PasswordStrength::Strong => write!(f, "Strong"),
+        }
+    }
+}
+
+/// Something to improve about a password, found by
+/// [`analyze_password_strength`]. Frontends keep these as they are and turn
+/// them into text only with [`message`](Self::message), the one place the
+/// wording lives.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrengthSuggestion {
+    /// Fewer than 8 characters
+    TooShort,
+    /// 8 to 11 characters
+    CouldBeLonger,
+    MissingLowercase,
+    MissingUppercase,
+    MissingNumbers,
+    MissingSymbols,
+    /// The same character three times in a row
+    Repetition,
+    /// A run such as "abc" or "123"
+    SequentialPattern,
+    /// Contains a well-known password
+    CommonPassword { which: &'static str },
+}
+
+impl StrengthSuggestion {
+    /// The suggestion as shown to the user
+    pub fn message(&self) -> String {
+        match self {
+            Self::TooShort => "Use at least 8 characters".to_string(),
+            Self::CouldBeLonger => "Consider using 12+ characters for better security".to_string(),
+            Self::MissingLowercase => "Add lowercase letters".to_string(),
+            Self::MissingUppercase => "Add uppercase letters".to_string(),
+            Self::MissingNumbers => "Add numbers".to_string(),
+            Self::MissingSymbols => "Add special characters".to_string(),
+            Self::Repetition => "Avoid repeating characters".to_string(),
+            Self::SequentialPattern => "Avoid sequential characters".to_string(),
+            Self::CommonPassword { which } => format!("Avoid common passwords such as \"{}\"", which),
+        }
+    }
+
+    /// Lower comes first when only the most important one is shown
+    pub fn rank(&self) -> u8 {
+        match self {
+            Self::CommonPassword { .. } => 0,
+            Self::TooShort => 1,
+            Self::SequentialPattern => 2,
+            Self::Repetition => 3,
+            Self::MissingLowercase | Self::MissingUppercase | Self::MissingNumbers | Self::MissingSymbols => 4,
+            Self::CouldBeLonger => 5,
+        }
+    }
+}
+
+impl std::fmt::Display for StrengthSuggestion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+/// Analyze password strength
+pub fn analyze_password_strength(password: &str) -> (PasswordStrength, Vec<StrengthSuggestion>) {
+    let mut score = 0;
+    let mut suggestions = Vec::new();
+    
+    // Length check
+    if password.len() >= 8 {
+        score += 1;
+    } else {
+        suggestions.push(StrengthSuggestion::TooShort);
+    }
+    
+    if password.len() >= 12 {
+        score += 1;
+    } else if password.len() >= 8 {
+        suggestions.push(StrengthSuggestion::CouldBeLonger);
+    }
+    
+    // Character type checks
+    let has_lowercase = Regex::new(r"[a-z]").unwrap().is_match(password);
+    let has_uppercase = Regex::new(r"[A-Z]").unwrap().is_match(password);
+    let has_numbers = Regex::new(r"\d").unwrap().is_match(password);
+    let has_symbols = Regex::new(r"[!@#$%^&*()_+\-=\[\]{}|;:,.<>?]").unwrap().is_match(password);
+    
+    if has_lowercase { score += 1; } else { suggestions.push(StrengthSuggestion::MissingLowercase); }
+    if has_uppercase { score += 1; } else { suggestions.push(StrengthSuggestion::MissingUppercase); }
+    if has_numbers { score += 1; } else { suggestions.push(StrengthSuggestion::MissingNumbers); }
+    if has_symbols { score += 1; } else { suggestions.push(StrengthSuggestion::MissingSymbols); }
+      // Check for repeated characters (simple approach)
+    let mut has_repeated = false;
+    let chars: Vec<char> = password.chars().collect();
+    for i in 0..chars.len().saturating_sub(2) {
+        if chars[i] == chars[i + 1] && chars[i + 1] == chars[i + 2] {
+            has_repeated = true;
+            break;
+        }
+    }
+    
+    if has_repeated {
+        score -= 1;
+        suggestions.push(StrengthSuggestion::Repetition);
+    }
+    
+    if Regex::new(r"(012|123|234|345|456|567|678|789|890|abc|bcd|cde|def|efg|fgh|ghi|hij|ijk|jkl|klm|lmn|mno|nop|opq|pqr|qrs|rst|stu|tuv|uvw|vwx|wxy|xyz)").unwrap().is_match(&password.to_lowercase()) {
+        score -= 1;
+        suggestions.push(StrengthSuggestion::SequentialPattern);
+    }
+    
+    // Common passwords check
+    let common_passwords = ["password", "123456", "password123", "admin", "qwerty", "letmein"];
+    if let Some(&which) = common_passwords.iter().find(|&&p| password.to_lowercase().contains(p)) {
+        score -= 2;
+        suggestions.push(StrengthSuggestion::CommonPassword { which });
+    }
+    
+    let strength = match score {
+        s if s <= 1 => PasswordStrength::VeryWeak,
+        2 => PasswordStrength::Weak,
+        3 => PasswordStrength::Fair,
+        4 => PasswordStrength::Good,
+        _ => PasswordStrength::Strong,
+    };
+    
+    (strength, suggestions)
+}
+
+/// Generate a password from the enabled character sets, honoring site rules
+/// (excluded characters, leading letter, maximum length)
+pub fn generate_password_with_config(length: usize, config: &PasswordConfig) -> Result<String, String> {
+    use rand::Rng;
+    use rand::seq::SliceRandom;
+    
+    const LOWERCASE: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+    const UPPERCASE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    const NUMBERS: &[u8] = b"0123456789";
+    const SYMBOLS: &[u8] = b"!@#$%^&*()_+-=[]{}|;:,.<>?";
+    
+    let length = match config.max_length {
+        Some(0) => return Err("Maximum length must be at least 1".into()),
+        Some(max) => length.min(max),
+        None => length,
+    };
+    
+    // Apply ambiguous and site-specific exclusions to a character set
+    let allowed = |set: &[u8]| -> Vec<u8> {
+        set.iter()
+            .filter(|&&c| !config.exclude_ambiguous || !b"0O1lI".contains(&c))
+            .filter(|&&c| !config.excluded_chars.as_bytes().contains(&c))
+            .copied()
+            .collect()
+    };
+    
+    let lowercase = if config.include_lowercase { allowed(LOWERCASE) } else { Vec::new() };
+    let uppercase = if config.include_uppercase { allowed(UPPERCASE) } else { Vec::new() };
+    let numbers = if config.include_numbers { allowed(NUMBERS) } else { Vec::new() };
+    let symbols = if config.include_symbols { allowed(SYMBOLS) } else { Vec::new() };
+    
+    let mut charset: Vec<u8> = [lowercase.as_slice(), &uppercase, &numbers, &symbols].concat();
+    
+    let nothing_enabled = !config.include_lowercase && !config.include_uppercase
+        && !config.include_numbers && !config.include_symbols;
+    if nothing_enabled {
+        charset = allowed(LOWERCASE); // fallback
+    }
+    
+    if charset.is_empty() {
+        return Err("No characters left to generate from after exclusions".into());
+    }
+    
+    let letters: Vec<u8> = charset.iter().copied().filter(u8::is_ascii_alphabetic).collect();
+    if config.require_leading_alpha && letters.is_empty() {
+        return Err("A leading letter is required but no letters are allowed".into());
+    }
+    
+    let mut rng = rand::thread_rng();
+    let mut password = Vec::new();
+    
+    // Ensure at least one character from each enabled set
+    for (index, set) in [&lowercase, &uppercase, &numbers, &symbols].into_iter().enumerate() {
+        if length > index && !set.is_empty() {
+            password.push(set[rng.gen_range(0..set.len())]);
+        }
+    }
+    
+    // Fill remaining length
+    while password.len() < length {
+        password.push(charset[rng.gen_range(0..charset.len())]);
+    }
+    password.truncate(length);
+    
+    // Shuffle the password to avoid predictable patterns
+    password.shuffle(&mut rng);
+    
+    if config.require_leading_alpha && !password.is_empty() && !password[0].is_ascii_alphabetic() {
+        let letter_positions: Vec<usize> = (0..password.len())
+            .filter(|&i| password[i].is_ascii_alphabetic())
+            .collect();
+        match letter_positions.choose(&mut rng) {
+            Some(&position) => password.swap(0, position),
+            None => password[0] = letters[rng.gen_range(0..letters.len())],
+        }
+    }
+    
+    String::from_utf8(password).map_err(|e| e.to_string())
+}
+
+// Generate memorable password (diceware-style)
+pub fn generate_memorable_password(word_count: usize) -> String {
+    const WORDS: &[&str] = &[
+        "apple", "brave", "cloud", "dream", "eagle", "flame", "grace", "heart",
+        "ivory", "jewel", "knight", "lemon", "magic", "noble", "ocean", "peace",
+        "quiet", "river", "stone", "tiger", "unity", "voice", "water", "xenon",
+        "youth", "zebra", "anchor", "bridge", "castle", "dragon", "empire", "forest"
+    ];
+    
+    use rand::seq::SliceRandom;
+    let mut rng = rand::thread_rng();
+    
+    (0..word_count)
+        .map(|_| WORDS.choose(&mut rng).unwrap_or(&"word"))
+        .map(|word| {
+            let mut word = word.to_string();
+            // Capitalize first letter
+            if let Some(first_char) = word.chars().next() {
+                word.replace_range(0..first_char.len_utf8(), &first_char.to_uppercase().to_string());
+            }
+            word
+        })
+        .collect::<Vec<_>>()
+        .join("")
+}
+
+const PRONOUNCEABLE_CONSONANTS: &[u8] = b"bdfghjkmnprstvz";
+const PRONOUNCEABLE_VOWELS: &[u8] = b"aeiou";
+const PRONOUNCEABLE_DIGITS: &[u8] = b"0123456789";
+const PRONOUNCEABLE_SYMBOLS: &[u8] = b"!@#$%&*?";
+const SYLLABLES_PER_WORD: usize = 3;
+
+/// Words a pronounceable password must never spell out, matched anywhere in
+/// its letters (hyphens removed, case ignored)
+const PROFANITY_BLOCKLIST: &[&str] = &[
+    "anus", "bitch", "butt", "damn", "dick", "dik", "dildo", "fag", "fuck", "fuk",
+    "homo", "jiz", "kike", "kum", "nazi", "negro", "nigga", "penis", "pedo", "poop",
+    "puta", "rape", "sex", "shit", "slut", "tit", "turd", "twat", "vagina", "whore",
+];
+
+fn contains_blocked_word(password: &str) -> bool {
+    let letters: String = password.chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    PROFANITY_BLOCKLIST.iter().any(|word| letters.contains(word))
+}
+
+/// Generate a password that can be read aloud: consonant/vowel syllables
+/// grouped into capitalized words of three, then two digits and a symbol,
+/// e.g. "Radovi-Tunema-83!"
+pub fn generate_pronounceable_password(syllables: usize) -> String {
+    use rand::Rng;
+    
+    let syllables = syllables.max(1);
+    let mut rng = rand::thread_rng();
+    let mut pick = |set: &[u8]| set[rng.gen_range(0..set.len())] as char;
+    
+    loop {
+        let words: Vec<String> = (0..syllables)
+            .map(|_| [pick(PRONOUNCEABLE_CONSONANTS), pick(PRONOUNCEABLE_VOWELS)])
+            .collect::<Vec<_>>()
+            .chunks(SYLLABLES_PER_WORD)
+            .map(|word| {
+                let mut word: String = word.iter().flatten().collect();
+                word[..1].make_ascii_uppercase();
+                word
+            })
+            .collect();
+        let password = format!(
+            "{}-{}{}{}",
+            words.join("-"),
+            pick(PRONOUNCEABLE_DIGITS),
+            pick(PRONOUNCEABLE_DIGITS),
+            pick(PRONOUNCEABLE_SYMBOLS),
+        );
+        if !contains_blocked_word(&password) {
+            return password;
+        }
+    }
+}
+
+/// Entropy in bits of a pronounceable password with `syllables` syllables.
+/// Rejecting blocklisted words removes a negligible share of the space.
+pub fn pronounceable_entropy_bits(syllables: usize) -> f64 {
+    let per_syllable = (PRONOUNCEABLE_CONSONANTS.len() * PRONOUNCEABLE_VOWELS.len()) as f64;
+    syllables.max(1) as f64 * per_syllable.log2()
+        + 2.0 * (PRONOUNCEABLE_DIGITS.len() as f64).log2()
+        + (PRONOUNCEABLE_SYMBOLS.len() as f64).log2()
+}
+
+/// Default number of glyphs in a password mask (`ui.mask_length`)
+pub const DEFAULT_MASK_LENGTH: usize = 12;
+
+const MAX_MASK_LENGTH: usize = 64;
+
+/// Fixed-width stand-in for a hidden password, so the mask never gives
+/// away how long the password is
+pub fn password_mask(glyph: char, length: usize) -> String {
+    glyph.to_string().repeat(length.clamp(1, MAX_MASK_LENGTH))
+}
+
+/// Characters per group when a password is spelled out
+pub const SPELL_GROUP_LEN: usize = 4;
+
+/// Groups per row of `passman get --spell`
+const SPELL_GROUPS_PER_ROW: usize = 4;
+
+/// What a character that is easily mistaken for another one is, for
+/// typing a password by hand; `None` for the rest
+pub fn glyph_name(c: char) -> Option<&'static str> {
+    Some(match c {
+        '0' => "zero",
+        'O' => "capital o",
+        'o' => "lowercase o",
+        '1' => "one",
+        'l' => "lowercase L",
+        'I' => "capital i",
+        '|' => "vertical bar",
+        '`' => "backtick",
+        '\'' => "apostrophe",
+        ' ' => "space",
+        _ => return None,
+    })
+}
+
+/// A password cut into groups of [`SPELL_GROUP_LEN`] characters
+pub fn spell_groups(password: &str) -> Vec<Zeroizing<String>> {
+    let chars: Zeroizing<Vec<char>> = Zeroizing::new(password.chars().collect());
+    chars.chunks(SPELL_GROUP_LEN).map(|group| Zeroizing::new(group.iter().collect())).collect()
+}
+
+/// The mistakable characters of a password and their names, each once,
+/// in the order they first appear
+pub fn spell_legend(password: &str) -> Vec<(char, &'static str)> {
+    let mut legend: Vec<(char, &'static str)> = Vec::new();
+    for c in password.chars() {
+        if let Some(name) = glyph_name(c).filter(|_| !legend.iter().any(|(seen, _)| *seen == c)) {
+            legend.push((c, name));
+        }
+    }
+    legend
+}
+
+/// A password spelled out for the terminal: rows of groups with `^` under
+/// each mistakable character, then what those characters are. Spaces are
+/// shown as `␣`.
+pub fn spell_out(password: &str) -> Zeroizing<String> {
+    let visible = |c: char| if c == ' ' { '␣' } else { c };
+    let mut out = Zeroizing::new(String::new());
+    for row in spell_groups(password).chunks(SPELL_GROUPS_PER_ROW) {
+        let mut markers = String::new();
+        for (i, group) in row.iter().enumerate() {
+            if i > 0 {
+                out.push_str("  ");
+                markers.push_str("  ");
+            }
+            for c in group.chars() {
+                out.push(visible(c));
+                markers.push(if glyph_name(c).is_some() { '^' } else { ' ' });
+            }
+        }
+        out.push('\n');
+        if !markers.trim().is_empty() {
+            out.push_str(markers.trim_end());
+            out.push('\n');
+        }
+    }
+    for (c, name) in spell_legend(password) {
+        out.push_str(&format!("  {} = {}\n", visible(c), name));
+    }
+    out
+}
+
+/// Parse an expiry date typed as `YYYY-MM-DD`, stored as midnight UTC
+pub fn parse_expiry_date(input: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    let date = chrono::NaiveDate::parse_from_str(input.trim(), "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date '{}': use YYYY-MM-DD", input.trim()))?;
+    Ok(date.and_time(chrono::NaiveTime::MIN).and_utc())
+}
+
+/// Turn an entry URL into one that is safe to hand to a browser. Bare
+/// hosts ("example.com/login") get `https://`; anything with a scheme
+/// other than http(s), such as `javascript:` or `data:`, is refused.
+pub fn site_url(url: &str) -> Result<String, String> {
+    let url = url.trim();
+    if url.is_empty() {
+        return Err("Entry has no URL".to_string());
+    }
+    if url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err("URL contains spaces or control characters".to_string());
+    }
+
+    let lower = url.to_ascii_lowercase();
+    if lower.starts_with("https://") || lower.starts_with("http://") {
+        return Ok(url.to_string());
+    }
+    // A scheme is letters (then letters, digits, + - .) before a colon;
+    // "example.com:8443" is a host and port, not a scheme
+    if let Some((scheme, rest)) = lower.split_once(':') {
+        let is_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+        if is_scheme && !rest.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(format!("Refusing to open a {}: URL", scheme));
+        }
+    }
+    Ok(format!("https://{}", url))
+}
+
+/// Human-readable byte count
+pub fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+/// How long ago `at` was, e.g. "2 days ago"
+pub fn format_age(at: chrono::DateTime<chrono::Utc>, now: chrono::DateTime<chrono::Utc>) -> String {
+    let seconds = (now - at).num_seconds().max(0);
+    let (count, unit) = match seconds {
+        0..=59 => return "just now".to_string(),
+        60..=3_599 => (seconds / 60, "minute"),
+        3_600..=86_399 => (seconds / 3_600, "hour"),
+        86_400..=2_591_999 => (seconds / 86_400, "day"),
+        2_592_000..=31_535_999 => (seconds / 2_592_000, "month"),
+        _ => (seconds / 31_536_000, "year"),
+    };
+    format!("{} {}{} ago", count, unit, if count == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ============ Password Generation Tests ============
+    
+    #[test]
+    fn test_generate_password_length() {
+        for length in [8, 12, 16, 20, 32, 64] {
+            let password = generate_password(length);
+            assert_eq!(password.len(), length, "Password should be {} chars", length);
+        }
+    }
+
+    #[test]
+    fn test_generate_password_uniqueness() {
+        let passwords: Vec<String> = (0..100).map(|_| generate_password(16)).collect();
+        let unique_count = passwords.iter().collect::<std::collections::HashSet<_>>().len();
+        assert_eq!(unique_count, 100, "All generated passwords should be unique");
+    }
+
+    #[test]
+    fn test_generate_password_with_config_lowercase_only() {
+        let config = PasswordConfig {
+            include_lowercase: true,
+            include_uppercase: false,
+            include_numbers: false,
+            include_symbols: false,
+            exclude_ambiguous: false,
+            ..Default::default()
+        };
+        let password = generate_password_with_config(20, &config).expect("valid config");
+        assert!(password.chars().all(|c| c.is_ascii_lowercase()),
+            "Password should contain only lowercase: {}", password);
+    }
+
+    #[test]
+    fn test_generate_password_with_config_uppercase_only() {
+        let config = PasswordConfig {
+            include_lowercase: false,
+            include_uppercase: true,
+            include_numbers: false,
+            include_symbols: false,
+            exclude_ambiguous: false,
+            ..Default::default()
+        };
+        let password = generate_password_with_config(20, &config).expect("valid config");
+        assert!(password.chars().all(|c| c.is_ascii_uppercase()),
+            "Password should contain only uppercase: {}", password);
+    }
+
+    #[test]
+    fn test_generate_password_with_config_numbers_only() {
+        let config = PasswordConfig {
+            include_lowercase: false,
+            include_uppercase: false,
+            include_numbers: true,
+            include_symbols: false,
+            exclude_ambiguous: false,
+            ..Default::default()
+        };
+        let password = generate_password_with_config(20, &config).expect("valid config");
+        assert!(password.chars().all(|c| c.is_ascii_digit()),
+            "Password should contain only numbers: {}", password);
+    }
+
+    #[test]
+    fn test_generate_password_with_config_exclude_ambiguous() {
+        let config = PasswordConfig {
+            include_lowercase: true,
+            include_uppercase: true,
+            include_numbers: true,
+            include_symbols: false,
+            exclude_ambiguous: true,
+            ..Default::default()
+        };
+        let ambiguous_chars = ['0', 'O', '1', 'l', 'I'];
+        
+        // Generate multiple passwords to ensure ambiguous chars are excluded
+        for _ in 0..50 {
+            let password = generate_password_with_config(32, &config).expect("valid config");
+            assert!(!password.chars().any(|c| ambiguous_chars.contains(&c)),
+                "Password should not contain ambiguous chars: {}", password);
+        }
+    }
+
+    #[test]
+    fn test_generate_password_with_config_all_types() {
+        let config = PasswordConfig {
+            include_lowercase: true,
+            include_uppercase: true,
+            include_numbers: true,
+            include_symbols: true,
+            exclude_ambiguous: false,
+            ..Default::default()
+        };
+        
+        // Generate several passwords to check they contain all types
+        let password = generate_password_with_config(32, &config).expect("valid config");
+        assert!(password.chars().any(|c| c.is_ascii_lowercase()), "Should have lowercase");
+        assert!(password.chars().any(|c| c.is_ascii_uppercase()), "Should have uppercase");
+        assert!(password.chars().any(|c| c.is_ascii_digit()), "Should have numbers");
+        assert!(password.chars().any(|c| !c.is_alphanumeric()), "Should have symbols");
+    }
+
+    #[test]
+    fn test_generate_password_empty_config_fallback() {
+        let config = PasswordConfig {
+            include_lowercase: false,
+            include_uppercase: false,
+            include_numbers: false,
+            include_symbols: false,
+            exclude_ambiguous: false,
+            ..Default::default()
+        };
+        let password = generate_password_with_config(16, &config).expect("valid config");
+        assert!(!password.is_empty(), "Should fallback to generating something");
+    }
+
+    #[test]
+    fn test_generate_password_with_config_excluded_chars() {
+        let config = PasswordConfig {
+            excluded_chars: "<>&aeiou".to_string(),
+            ..Default::default()
+        };
+        for _ in 0..50 {
+            let password = generate_password_with_config(32, &config).expect("valid config");
+            assert!(!password.chars().any(|c| "<>&aeiou".contains(c)),
+                "Password should not contain excluded chars: {}", password);
+        }
+    }
+
+    #[test]
+    fn test_generate_password_with_config_leading_alpha() {
+        let config = PasswordConfig {
+            require_leading_alpha: true,
+            ..Default::default()
+        };
+        for _ in 0..50 {
+            let password = generate_password_with_config(12, &config).expect("valid config");
+            assert!(password.chars().next().is_some_and(|c| c.is_ascii_alphabetic()),
+                "Password should start with a letter: {}", password);
+        }
+    }
+
+    #[test]
+    fn test_generate_password_with_config_max_length() {
+        let config = PasswordConfig {
+            max_length: Some(10),
+            ..Default::default()
+        };
+        let password = generate_password_with_config(32, &config).expect("valid config");
+        assert_eq!(password.len(), 10);
+    }
+
+    #[test]
+    fn test_generate_password_with_config_everything_excluded() {
+        let config = PasswordConfig {
+            include_uppercase: false,
+            include_symbols: false,
+            include_numbers: false,
+            excluded_chars: "abcdefghijklmnopqrstuvwxyz".to_string(),
+            ..Default::default()
+        };
+        assert!(generate_password_with_config(16, &config).is_err());
+
+        let digits_only = PasswordConfig {
+            include_uppercase: false,
+            include_lowercase: false,
+            include_symbols: false,
+            require_leading_alpha: true,
+            ..Default::default()
+        };
+        assert!(generate_password_with_config(16, &digits_only).is_err());
+    }
+
+    // ============ Memorable Password Tests ============
+
+    #[test]
+    fn test_generate_memorable_password_word_count() {
+        let password = generate_memorable_password(4);
+        // Each word is capitalized, so count capital letters
+        let capital_count = password.chars().filter(|c| c.is_uppercase()).count();
+        assert_eq!(capital_count, 4, "Should have 4 capitalized words");
+    }
+
+    #[test]
+    fn test_generate_memorable_password_uniqueness() {
+        let passwords: Vec<String> = (0..50).map(|_| generate_memorable_password(4)).collect();
+        let unique_count = passwords.iter().collect::<std::collections::HashSet<_>>().len();
+        assert!(unique_count > 40, "Most passwords should be unique: {}/50", unique_count);
+    }
+
+    #[test]
+    fn test_generate_memorable_password_readable() {
+        let password = generate_memorable_password(3);
+        // Should only contain alphabetic characters (no numbers/symbols)
+        assert!(password.chars().all(|c| c.is_alphabetic()),
+            "Memorable password should be alphabetic: {}", password);
+    }
+
+    // ============ Pronounceable Password Tests ============
+
+    #[test]
+    fn test_generate_pronounceable_password_structure() {
+        let pattern = Regex::new(r"^[A-Z][a-z]{5}-[A-Z][a-z]{3}-[0-9]{2}[!@#$%&*?]$").unwrap();
+        for _ in 0..50 {
+            let password = generate_pronounceable_password(5);
+            assert!(pattern.is_match(&password), "Unexpected shape: {}", password);
+            
+            let letters: Vec<u8> = password.bytes()
+                .filter(u8::is_ascii_alphabetic)
+                .map(|c| c.to_ascii_lowercase())
+                .collect();
+            for pair in letters.chunks(2) {
+                assert!(PRONOUNCEABLE_CONSONANTS.contains(&pair[0]), "{} in {}", pair[0] as char, password);
+                assert!(PRONOUNCEABLE_VOWELS.contains(&pair[1]), "{} in {}", pair[1] as char, password);
+            }
+            assert!(!contains_blocked_word(&password));
+        }
+    }
+
+    #[test]
+    fn test_generate_pronounceable_password_minimum_one_syllable() {
+        let password = generate_pronounceable_password(0);
+        assert!(Regex::new(r"^[A-Z][a-z]-[0-9]{2}[!@#$%&*?]$").unwrap().is_match(&password), "{}", password);
+    }
+
+    #[test]
+    fn test_pronounceable_blocklist_ignores_case_and_hyphens() {
+        assert!(contains_blocked_word("Bana-Zi-42!"));
+        assert!(contains_blocked_word("TITAVO-12#"));
+        assert!(!contains_blocked_word("Radovi-Tunema-83!"));
+    }
+
+    #[test]
+    fn test_pronounceable_entropy_grows_with_syllables() {
+        let six = pronounceable_entropy_bits(6);
+        assert!((six - (6.0 * 75f64.log2() + 100f64.log2() + 3.0)).abs() < 1e-9);
+        assert!(pronounceable_entropy_bits(9) > six);
+    }
+
+    // ============ Password Strength Tests ============
+
+    #[test]
+    fn test_password_strength_very_weak() {
+        let (strength, _) = analyze_password_strength("abc");
+        assert_eq!(strength, PasswordStrength::VeryWeak);
+    }
+
+    #[test]
+    fn test_password_strength_weak() {
+        let (strength, _) = analyze_password_strength("password");
+        assert!(matches!(strength, PasswordStrength::VeryWeak | PasswordStrength::Weak),
+            "Common password should be weak: {:?}", strength);
+    }
+
+    #[test]
+    fn test_password_strength_common_password_penalty() {
+        let (strength, suggestions) = analyze_password_strength("password123");
+        assert!(matches!(strength, PasswordStrength::VeryWeak | PasswordStrength::Weak));
+        assert!(suggestions.contains(&StrengthSuggestion::CommonPassword { which: "password" }));
+    }
+
+    #[test]
+    fn test_password_strength_sequential_penalty() {
+        let (_, suggestions) = analyze_password_strength("abc123XYZ!");
+        assert!(suggestions.contains(&StrengthSuggestion::SequentialPattern));
+    }
+
+    #[test]
+    fn test_password_strength_repeated_chars_penalty() {
+        let (_, suggestions) = analyze_password_strength("Hellooo123!");
+        assert!(suggestions.contains(&StrengthSuggestion::Repetition));
+    }
+
+    #[test]
+    fn test_password_strength_strong() {
+        let (strength, suggestions) = analyze_password_strength("Kj9$mP2!qR5@nL8*");
+        assert_eq!(strength, PasswordStrength::Strong);
+        assert!(suggestions.is_empty() || suggestions.len() <= 1,
+            "Strong password should have few suggestions: {:?}", suggestions);
+    }
+
+    #[test]
+    fn test_password_strength_suggestions_for_missing_types() {
+        let (_, suggestions) = analyze_password_strength("onlylowercase");
+        assert!(suggestions.contains(&StrengthSuggestion::MissingUppercase));
+        assert!(suggestions.contains(&StrengthSuggestion::MissingNumbers));
+        assert!(suggestions.contains(&StrengthSuggestion::MissingSymbols));
+    }
+
+    #[test]
+    fn test_password_strength_length_suggestions() {
+        let (_, suggestions) = analyze_password_strength("Ab1!");
+        assert!(suggestions.contains(&StrengthSuggestion::TooShort));
+    }
+
+    #[test]
+    fn test_password_strength_suggestion_sets() {
+        use StrengthSuggestion::*;
+        let cases: [(&str, Vec<StrengthSuggestion>); 6] = [
+            ("Kj9$mP2!qR5@nL8*", vec![]),
+            ("Ab1!", vec![TooShort]),
+            ("onlylowercase", vec![MissingUppercase, MissingNumbers, MissingSymbols]),
+            ("Tr0ub4dor&3", vec![CouldBeLonger]),
+            ("Hellooo123!", vec![CouldBeLonger, Repetition, SequentialPattern]),
+            ("qwerty", vec![TooShort, MissingUppercase, MissingNumbers, MissingSymbols, CommonPassword { which: "qwerty" }]),
+        ];
+        for (password, expected) in cases {
+            assert_eq!(analyze_password_strength(password).1, expected, "{}", password);
+        }
+    }
+
+    #[test]
+    fn test_strength_suggestions_render_and_rank() {
+        assert_eq!(StrengthSuggestion::TooShort.to_string(), "Use at least 8 characters");
+        assert_eq!(StrengthSuggestion::CommonPassword { which: "admin" }.message(), "Avoid common passwords such as \"admin\"");
+        let (_, suggestions) = analyze_password_strength("qwerty");
+        let first = suggestions.iter().min_by_key(|suggestion| suggestion.rank()).unwrap();
+        assert_eq!(*first, StrengthSuggestion::CommonPassword { which: "qwerty" });
+    }
+
+    // ============ File Utility Tests ============
+
+    #[test]
+    fn test_file_exists_nonexistent() {
+        assert!(!file_exists("/nonexistent/path/to/file.txt"));
+    }
+
+    #[test]
+    fn test_file_exists_current_file() {
+        // This test file should exist
+        assert!(file_exists("Cargo.toml"));
+    }
+
+    #[test]
+    fn test_password_config_default_values() {
+        let config = PasswordConfig {
+            include_uppercase: true,
+            include_lowercase: true,
+            include_numbers: true,
+            include_symbols: true,
+            exclude_ambiguous: false,
+            ..Default::default()
+        };
+        
+        assert!(config.include_uppercase);
+        assert!(config.include_lowercase);
+        assert!(config.include_numbers);
+        assert!(config.include_symbols);
+        assert!(!config.exclude_ambiguous);
+    }
+
+    // ============ Password Strength Display Tests ============
+
+    #[test]
+    fn test_password_strength_display() {
+        assert_eq!(format!("{}", PasswordStrength::VeryWeak), "Very Weak");
+        assert_eq!(format!("{}", PasswordStrength::Weak), "Weak");
+        assert_eq!(format!("{}", PasswordStrength::Fair), "Fair");
+        assert_eq!(format!("{}", PasswordStrength::Good), "Good");
+        assert_eq!(format!("{}", PasswordStrength::Strong), "Strong");
+    }
+
+    #[test]
+    fn test_format_age() {
+        let now = chrono::Utc::now();
+        assert_eq!(format_age(now, now), "just now");
+        assert_eq!(format_age(now - chrono::Duration::minutes(1), now), "1 minute ago");
+        assert_eq!(format_age(now - chrono::Duration::hours(5), now), "5 hours ago");
+        assert_eq!(format_age(now - chrono::Duration::days(2), now), "2 days ago");
+        assert_eq!(format_age(now - chrono::Duration::days(400), now), "1 year ago");
+        assert_eq!(format_age(now + chrono::Duration::days(1), now), "just now");
+    }
+
+    #[test]
+    fn test_password_mask_is_fixed_width() {
+        assert_eq!(password_mask('*', DEFAULT_MASK_LENGTH), "************");
+        assert_eq!(password_mask('•', 4), "••••");
+        assert_eq!(password_mask('*', 0), "*");
+        assert_eq!(password_mask('*', 1000).len(), 64);
+    }
+
+    #[test]
+    fn test_mistakable_glyphs_are_named() {
+        assert_eq!(glyph_name('0'), Some("zero"));
+        assert_eq!(glyph_name('O'), Some("capital o"));
+        assert_eq!(glyph_name('l'), Some("lowercase L"));
+        assert_eq!(glyph_name('I'), Some("capital i"));
+        assert_eq!(glyph_name('1'), Some("one"));
+        assert_eq!(glyph_name('x'), None);
+        assert_eq!(spell_legend("l0l0x"), vec![('l', "lowercase L"), ('0', "zero")]);
+    }
+
+    #[test]
+    fn test_spell_out_groups_and_marks_mistakable_characters() {
+        let groups = spell_groups("aB0lIx9Qz");
+        assert_eq!(groups.iter().map(|group| group.as_str()).collect::<Vec<_>>(), ["aB0l", "Ix9Q", "z"]);
+        assert_eq!(
+            spell_out("aB0lIx9Qz").as_str(),
+            "aB0l  Ix9Q  z\n  ^^  ^\n  0 = zero\n  l = lowercase L\n  I = capital i\n"
+        );
+        // Rows of four groups; a row without mistakable characters has no marker line
+        assert_eq!(spell_out("abcdefghjkmnpqrst u").as_str(), "abcd  efgh  jkmn  pqrs\nt␣u\n ^\n  ␣ = space\n");
+        assert_eq!(spell_out("").as_str(), "");
+    }
+
+    #[test]
+    fn test_parse_expiry_date() {
+        let at = parse_expiry_date(" 2025-09-30 ").unwrap();
+        assert_eq!(at.to_rfc3339(), "2025-09-30T00:00:00+00:00");
+        assert!(parse_expiry_date("30/09/2025").is_err());
+        assert!(parse_expiry_date("2025-02-30").is_err());
+    }
+
+    #[test]
+    fn test_site_url_allows_only_web_schemes() {
+        assert_eq!(site_url(" https://github.com/login ").unwrap(), "https://github.com/login");
+        assert_eq!(site_url("HTTP://intranet").unwrap(), "HTTP://intranet");
+        assert_eq!(site_url("github.com").unwrap(), "https://github.com");
+        assert_eq!(site_url("nas.local:5001/login").unwrap(), "https://nas.local:5001/login");
+        for bad in ["javascript:alert(1)", "JavaScript:alert(1)", "data:text/html,<b>", "file:///etc/passwd", "vbscript:x", "", "https://a b"] {
+            assert!(site_url(bad).is_err(), "{:?} should be refused", bad);
+        }
+    }
+}
@@ -0,0 +1,2755 @@
+//! Vault Management Module
+//! 
+//! Provides secure storage for password entries with:
+//! - AES-256-GCM encryption
+//! - Argon2id key derivation
+//! - HMAC-SHA256 integrity verification
+//! - Atomic file writes to prevent corruption
+
+#![allow(dead_code)]
+
+use crate::crypto::{derive_key_with_params, CipherKind, Key, KdfParams};
+use crate::config::{get_config, Config};
+use crate::error::VaultError;
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::diff::merge_newer;
+use crate::model::Vault;
+use crate::utils::{analyze_password_strength, PasswordStrength};
+use argon2::password_hash::SaltString;
+use base64ct::{Base64Unpadded, Encoding};
+use std::fs::{self, File, read_dir};
+use std::io::{Write, Read};
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+use std::time::{Duration, Instant, SystemTime};
+use std::thread;
+use std::sync::atomic::{AtomicBool, Ordering};
+use sha2::{Sha256, Digest};
+use hmac::{Hmac, Mac};
+use subtle::ConstantTimeEq;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Set by [`VaultManager::force_strict_permissions`] (the CLI's `--strict-permissions`)
+static FORCE_STRICT_PERMISSIONS: AtomicBool = AtomicBool::new(false);
+
+/// Temp files of vault writes in progress, for [`VaultManager::remove_pending_temp_files`]
+static PENDING_TEMP_FILES: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+fn pending_temp_files() -> std::sync::MutexGuard<'static, Vec<String>> {
+    PENDING_TEMP_FILES.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// A temp file registered while it is being written. Dropping it deletes
+/// the file if it wasn't renamed into place, so failed writes leave nothing.
+struct PendingTempFile(String);
+
+impl PendingTempFile {
+    fn new(path: String) -> Self {
+        pending_temp_files().push(path.clone());
+        Self(path)
+    }
+}
+
+impl Drop for PendingTempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+        pending_temp_files().retain(|path| *path != self.0);
+    }
+}
+
+/// Default vault file name
+const DEFAULT_VAULT_FILE: &str = "vault.dat";
+
+/// Vault file format version
+const VAULT_FORMAT_VERSION: u8 = 5;
+
+/// Magic bytes to identify vault files
+const VAULT_MAGIC: &[u8; 4] = b"PMAN";
+
+/// Header length for v2 files: [magic(4)][version(1)][salt_len(4)]
+const V2_HEADER_LEN: usize = 9;
+
+/// Header length for v3 files: v2 header + [memory_kb(4)][time_cost(4)][parallelism(4)]
+const V3_HEADER_LEN: usize = 21;
+
+/// Header length for v4 files: v3 header + [vault_id(16)]
+const V4_HEADER_LEN: usize = 37;
+
+/// Header length for v5 files: v4 header + [cipher_id(1)]
+const V5_HEADER_LEN: usize = 38;
+
+/// Longest salt a vault file may declare (`SaltString`'s own limit)
+const MAX_SALT_LEN: u32 = 64;
+
+/// Most bytes the header and salt can take, so reading the key parameters
+/// never reads the encrypted body of a large vault
+const MAX_KEY_SECTION_LEN: u64 = V5_HEADER_LEN as u64 + MAX_SALT_LEN as u64;
+
+/// Highest Argon2 costs a vault header may ask for: far above anything
+/// calibration picks, but a damaged or crafted file can't make unlocking
+/// allocate more than 4 GiB or run for hours
+const MAX_HEADER_MEMORY_KB: u32 = 4 * 1024 * 1024;
+const MAX_HEADER_TIME_COST: u32 = 256;
+const MAX_HEADER_PARALLELISM: u32 = 64;
+
+/// Sidecar file (next to the config file) remembering unlock timings per vault
+const UNLOCK_TIMINGS_FILE: &str = "unlock_timings.json";
+
+/// Sidecar file pinning the fingerprint of each vault unlocked on this machine
+const FINGERPRINTS_FILE: &str = "vault_fingerprints.json";
+
+/// Sidecar file remembering when each vault last warned about a weak master password
+const MASTER_WARNINGS_FILE: &str = "master_password_warnings.json";
+
+/// Days before a weak master password is mentioned again for the same vault
+const MASTER_WARNING_INTERVAL_DAYS: i64 = 7;
+
+/// Appended to a conflicted copy once it is merged, so it isn't found again
+const RESOLVED_SUFFIX: &str = ".resolved";
+
+/// Sidecar file remembering which sync folder risks each vault was warned about
+const SYNC_WARNINGS_FILE: &str = "sync_folder_warnings.json";
+
+/// Suffix of the sidecar (next to the vault) recording backup verification
+const BACKUP_MANIFEST_SUFFIX: &str = ".backups.json";
+
+/// Suffix of the opt-in plaintext sidecar (next to the vault) holding entry counts
+const STATS_SUFFIX: &str = ".stats.json";
+
+/// Directory entries looked at per directory when listing vaults
+const MAX_VAULT_SCAN_ENTRIES: usize = 10_000;
+
+/// Vault file header structure
+#[derive(Debug)]
+struct VaultHeader {
+    magic: [u8; 4],
+    version: u8,
+    salt_len: u32,
+    kdf: KdfParams,
+    /// Stored since v4; older files are identified by their salt
+    vault_id: Option<VaultId>,
+    /// [`CipherKind::id`], stored since v5; older files are AES-256-GCM
+    cipher_id: u8,
+}
+
+impl VaultHeader {
+    fn new(salt_len: u32, kdf: KdfParams, vault_id: VaultId, cipher: CipherKind) -> Self {
+        Self {
+            magic: *VAULT_MAGIC,
+            version: VAULT_FORMAT_VERSION,
+            salt_len,
+            kdf,
+            vault_id: Some(vault_id),
+            cipher_id: cipher.id(),
+        }
+    }
+
+    /// Size of the header in bytes (v2 files predate stored KDF parameters,
+    /// v3 files the vault id, v4 files the cipher id)
+    fn len(&self) -> usize {
+        match self.version {
+            0..=2 => V2_HEADER_LEN,
+            3 => V3_HEADER_LEN,
+            4 => V4_HEADER_LEN,
+            _ => V5_HEADER_LEN,
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.len());
+        bytes.extend_from_slice(&self.magic);
+        bytes.push(self.version);
+        bytes.extend_from_slice(&self.salt_len.to_le_bytes());
+        if self.version >= 3 {
+            bytes.extend_from_slice(&self.kdf.memory_kb.to_le_bytes());
+            bytes.extend_from_slice(&self.kdf.time_cost.to_le_bytes());
+            bytes.extend_from_slice(&self.kdf.parallelism.to_le_bytes());
+        }
+        if let Some(vault_id) = self.vault_id.filter(|_| self.version >= 4) {
+            bytes.extend_from_slice(&vault_id.0);
+        }
+        if self.version >= 5 {
+            bytes.push(self.cipher_id);
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < V2_HEADER_LEN {
+            return None;
+        }
+
+        let magic: [u8; 4] = bytes[0..4].try_into().ok()?;
+        if &magic != VAULT_MAGIC {
+            return None; // Legacy format
+        }
+
+        let version = bytes[4];
+        let salt_len = u32::from_le_bytes([bytes[5], bytes[6], bytes[7], bytes[8]]);
+
+        let kdf = if version >= 3 {
+            if bytes.len() < V3_HEADER_LEN {
+                return None;
+            }
+            let read_u32 = |at: usize| u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+            KdfParams {
+                memory_kb: read_u32(9),
+                time_cost: read_u32(13),
+                parallelism: read_u32(17),
+            }
+        } else {
+            KdfParams::default()
+        };
+
+        let vault_id = if version >= 4 {
+            Some(VaultId(bytes.get(V3_HEADER_LEN..V4_HEADER_LEN)?.try_into().ok()?))
+        } else {
+            None
+        };
+
+        let cipher_id = if version >= 5 {
+            *bytes.get(V4_HEADER_LEN)?
+        } else {
+            CipherKind::Aes256Gcm.id()
+        };
+
+        Some(Self { magic, version, salt_len, kdf, vault_id, cipher_id })
+    }
+}
+
+/// Bounds-checked reads from the front of a vault file. Every length the
+/// file declares is checked against the bytes actually there, so a
+/// truncated or crafted file is an error rather than a panic.
+struct FileReader<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> FileReader<'a> {
+    fn new(buffer: &'a [u8]) -> Self {
+        Self { buffer, offset: 0 }
+    }
+
+    /// The next `len` bytes; `what` names them in the error
+    fn take(&mut self, len: usize, what: &str) -> Result<&'a [u8], VaultError> {
+        let bytes = self.offset.checked_add(len)
+            .and_then(|end| self.buffer.get(self.offset..end))
+            .ok_or_else(|| VaultError::Corrupted(format!("too short for its {}", what)))?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn take_array<const N: usize>(&mut self, what: &str) -> Result<[u8; N], VaultError> {
+        Ok(self.take(N, what)?.try_into().expect("took N bytes"))
+    }
+
+    /// Everything not read yet
+    fn rest(self) -> &'a [u8] {
+        &self.buffer[self.offset..]
+    }
+}
+
+/// Salt, KDF parameters and id from the start of a vault file
+struct KeySection {
+    /// Format version; `None` for the legacy format, which has no header
+    version: Option<u8>,
+    salt: SaltString,
+    /// How the stored salt had to be read; empty when it was canonical
+    salt_fallbacks: Vec<SaltFallback>,
+    kdf: KdfParams,
+    vault_id: VaultId,
+    cipher: CipherKind,
+}
+
+/// The encrypted part of a vault file, after the salt
+struct Body<'a> {
+    /// As long as the cipher's nonce
+    nonce: &'a [u8],
+    /// Not in the legacy format
+    hmac: Option<&'a [u8]>,
+    ciphertext: &'a [u8],
+}
+
+/// Alphabet of the unpadded base64 salts are stored in
+const B64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// A way of reading a stored salt that `SaltString` rejects as it is.
+/// Each keeps the bytes the salt decodes to, and so the key derived from
+/// it; the canonical form is written back on the next save.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaltFallback {
+    /// Whitespace or NUL padding around it, as from a fixed-width field
+    Trimmed,
+    /// `=` padding at the end
+    Unpadded,
+    /// The URL-safe alphabet, `-` and `_` for `+` and `/`
+    UrlSafe,
+    /// A dangling last character too short to hold a byte, which lenient
+    /// decoders ignored
+    Truncated,
+    /// Unused low bits set in the last character
+    NonCanonicalBits,
+}
+
+impl SaltFallback {
+    /// Every fallback, in the order they are tried; later ones build on
+    /// the earlier ones
+    pub const ALL: [SaltFallback; 5] = [
+        SaltFallback::Trimmed,
+        SaltFallback::Unpadded,
+        SaltFallback::UrlSafe,
+        SaltFallback::Truncated,
+        SaltFallback::NonCanonicalBits,
+    ];
+
+    fn apply(self, salt: &str) -> String {
+        match self {
+            SaltFallback::Trimmed => salt.trim_matches(|c: char| c.is_whitespace() || c == '\0').to_string(),
+            SaltFallback::Unpadded => salt.trim_end_matches('=').to_string(),
+            SaltFallback::UrlSafe => salt.replace('-', "+").replace('_', "/"),
+            SaltFallback::Truncated if salt.is_ascii() && salt.len() % 4 == 1 => salt[..salt.len() - 1].to_string(),
+            SaltFallback::NonCanonicalBits if salt.is_ascii() => {
+                let unused_bits = match salt.len() % 4 {
+                    2 => 4,
+                    3 => 2,
+                    _ => return salt.to_string(),
+                };
+                let (head, last) = salt.split_at(salt.len() - 1);
+                match B64_ALPHABET.iter().position(|&c| c == last.as_bytes()[0]) {
+                    Some(value) => format!("{}{}", head, B64_ALPHABET[value >> unused_bits << unused_bits] as char),
+                    None => salt.to_string(),
+                }
+            }
+            _ => salt.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for SaltFallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SaltFallback::Trimmed => "trimmed whitespace or NUL padding",
+            SaltFallback::Unpadded => "removed '=' padding",
+            SaltFallback::UrlSafe => "read the URL-safe base64 alphabet",
+            SaltFallback::Truncated => "dropped a dangling last character",
+            SaltFallback::NonCanonicalBits => "cleared unused bits in the last character",
+        })
+    }
+}
+
+/// The salt `stored` is, if it is in the canonical form: unpadded base64
+/// that decodes and encodes back to itself, within `SaltString`'s limits
+fn canonical_salt(stored: &str) -> Option<SaltString> {
+    let bytes = Base64Unpadded::decode_vec(stored).ok()?;
+    if Base64Unpadded::encode_string(&bytes) != stored {
+        return None;
+    }
+    SaltString::from_b64(stored).ok()
+}
+
+/// Read a stored salt, trying the [`SaltFallback`]s in turn when it isn't
+/// canonical. Returns the canonical salt and the fallbacks it took.
+fn parse_salt(stored: &str) -> Result<(SaltString, Vec<SaltFallback>), VaultError> {
+    let mut candidate = stored.to_string();
+    let mut applied = Vec::new();
+    if let Some(salt) = canonical_salt(&candidate) {
+        return Ok((salt, applied));
+    }
+    for fallback in SaltFallback::ALL {
+        let repaired = fallback.apply(&candidate);
+        if repaired == candidate {
+            continue;
+        }
+        candidate = repaired;
+        applied.push(fallback);
+        if let Some(salt) = canonical_salt(&candidate) {
+            return Ok((salt, applied));
+        }
+    }
+    let reason = SaltString::from_b64(stored).err().map_or_else(|| "not canonical base64".to_string(), |e| e.to_string());
+    Err(VaultError::InvalidFormat(format!("Salt parsing error: {}", reason)))
+}
+
+/// What `passman vault repair` did to a vault file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaltRepair {
+    /// Fallbacks the stored salt needed; empty if it was already canonical
+    pub fallbacks: Vec<SaltFallback>,
+    /// Backup of the file as it was before the rewrite
+    pub backup: Option<String>,
+}
+
+/// Reject KDF parameters no vault is written with: ones Argon2 refuses,
+/// and costs past the `MAX_HEADER_*` limits
+fn check_kdf_params(kdf: &KdfParams) -> Result<(), VaultError> {
+    let in_range = (1..=MAX_HEADER_PARALLELISM).contains(&kdf.parallelism)
+        && (1..=MAX_HEADER_TIME_COST).contains(&kdf.time_cost)
+        && (8 * kdf.parallelism..=MAX_HEADER_MEMORY_KB).contains(&kdf.memory_kb);
+    if in_range {
+        Ok(())
+    } else {
+        Err(VaultError::InvalidFormat(format!("KDF parameters out of range ({})", kdf)))
+    }
+}
+
+/// Random identity of a vault, written to the header when the vault is
+/// created and kept through re-keying, saves and backups. Files from before
+/// v4 derive theirs from the salt, which stays put until the first password
+/// change, so upgrading a file doesn't change its fingerprint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VaultId([u8; 16]);
+
+impl VaultId {
+    fn generate() -> Self {
+        Self(rand::random())
+    }
+
+    fn from_salt(salt: &SaltString) -> Self {
+        let digest = Sha256::new()
+            .chain_update(b"passman-vault-id:")
+            .chain_update(salt.as_str().as_bytes())
+            .finalize();
+        Self(digest[..16].try_into().expect("SHA-256 is 32 bytes"))
+    }
+
+    pub(crate) fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    /// Short form for people to compare, like "3F9A-12C4-77E0-B1D2-0A5C"
+    pub fn fingerprint(&self) -> String {
+        let digest = Sha256::new()
+            .chain_update(b"passman-fingerprint:")
+            .chain_update(self.0)
+            .finalize();
+        digest[..10].chunks(2)
+            .map(|pair| format!("{:02X}{:02X}", pair[0], pair[1]))
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+}
+
+/// A vault file's fingerprint and the one pinned for its path when it was
+/// last unlocked on this machine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultIdentity {
+    pub fingerprint: String,
+    pub pinned: Option<String>,
+}
+
+impl VaultIdentity {
+    /// Whether the file is not the vault last unlocked at this path. The
+    /// check catches a swapped file, not a determined attacker: the id is
+    /// in the unencrypted header and can be copied into a forged vault.
+    pub fn is_substituted(&self) -> bool {
+        self.pinned.as_ref().is_some_and(|pinned| *pinned != self.fingerprint)
+    }
+
+    /// Warning to show before asking for the master password
+    pub fn warning(&self) -> Option<String> {
+        let pinned = self.pinned.as_ref().filter(|_| self.is_substituted())?;
+        Some(format!(
+            "This vault file is not the one previously opened on this machine (fingerprint {}, expected {}). \
+             If you didn't restore or replace it yourself, don't enter your master password. \
+             Unlocking it trusts the new fingerprint.",
+            self.fingerprint, pinned
+        ))
+    }
+}
+
+/// Snapshot of a vault file on disk, used to detect external modifications
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultFingerprint {
+    pub modified: Option<SystemTime>,
+    pub len: u64,
+    pub hash: [u8; 32],
+}
+
+impl VaultFingerprint {
+    fn of_bytes(modified: Option<SystemTime>, bytes: &[u8]) -> Self {
+        VaultFingerprint { modified, len: bytes.len() as u64, hash: Sha256::digest(bytes).into() }
+    }
+}
+
+/// Verification result stored for one backup file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BackupRecord {
+    verified: bool,
+    size: u64,
+    sha256: String,
+    entries: Option<usize>,
+    checked_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A timestamped backup and what is known about it
+#[derive(Debug, Clone)]
+pub struct BackupInfo {
+    pub path: String,
+    /// Creation time, from the file name
+    pub created: Option<chrono::NaiveDateTime>,
+    pub size: u64,
+    /// Entry count, known once the backup has been verified
+    pub entries: Option<usize>,
+    /// `Some(true)` if it decrypted when checked and hasn't changed since,
+    /// `Some(false)` if it failed or was modified, `None` if never checked
+    pub verified: Option<bool>,
+}
+
+/// A vault file found by `VaultManager::list_vaults`
+#[derive(Debug, Clone, PartialEq)]
+pub struct VaultFileInfo {
+    pub path: String,
+    /// File format version from the header
+    pub version: u8,
+    pub size: u64,
+    /// Counts from the plaintext sidecar, when `security.store_plain_metadata` is on
+    pub stats: Option<VaultStats>,
+}
+
+/// Signs that a vault file sits in a folder a cloud sync client manages,
+/// where partial syncs and conflicted copies can corrupt or fork it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncFolderRisk {
+    /// Sync service the path points to, e.g. "Dropbox"
+    pub service: Option<&'static str>,
+    /// Conflicted copies of the vault next to it
+    pub conflicted_copies: Vec<String>,
+}
+
+impl SyncFolderRisk {
+    /// One-line explanation for a banner or terminal warning
+    pub fn message(&self) -> String {
+        let place = match self.service {
+            Some(service) => format!("a {} folder", service),
+            None => "a synced folder".to_string(),
+        };
+        match self.conflicted_copies.len() {
+            0 => format!("This vault is in {}. Sync clients can upload a half-written vault or fork it into conflicted copies.", place),
+            n => format!("This vault is in {} and has {} conflicted cop{} next to it. Entries saved on another device may only be in those files.", place, n, if n == 1 { "y" } else { "ies" }),
+        }
+    }
+
+    /// Identifies what was warned about, so a new conflicted copy warns again
+    fn signature(&self) -> String {
+        let mut copies = self.conflicted_copies.clone();
+        copies.sort();
+        format!("{}|{}", self.service.unwrap_or_default(), copies.join("|"))
+    }
+}
+
+/// A vault or backup file that users other than its owner can access
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoosePermissions {
+    pub path: String,
+    /// Permission bits, e.g. 0o644
+    pub mode: u32,
+}
+
+impl LoosePermissions {
+    /// One-line explanation for a banner or terminal warning
+    pub fn message(&self) -> String {
+        format!("{} can be read by other users on this machine (mode {:03o})", self.path, self.mode)
+    }
+
+    /// Shell command that fixes it
+    pub fn remedy(&self) -> String {
+        format!("chmod 600 '{}'", self.path.replace('\'', "'\\''"))
+    }
+}
+
+/// What merging a conflicted copy into its vault left behind
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictResolution {
+    /// Backup of the vault file as it was before the merge
+    pub backup: String,
+    /// Where the conflicted copy was moved, with a `.resolved` suffix
+    pub archived: String,
+}
+
+/// Counts and timestamps kept unencrypted next to a vault so the vault list
+/// can show badges without unlocking it. Only written with
+/// `security.store_plain_metadata`; nothing identifying an entry (ids,
+/// usernames, URLs) may ever be added here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultStats {
+    /// Entries outside the trash
+    pub entries: usize,
+    /// Of those, entries with a weak or very weak password
+    pub weak: usize,
+    pub saved_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl VaultStats {
+    pub fn of(vault: &Vault) -> Self {
+        let active: Vec<_> = vault.iter().filter(|(_, entry)| !entry.is_trashed()).collect();
+        let weak = active.iter()
+            .filter(|(_, entry)| !entry.archived && matches!(
+                entry.password_strength(),
+                PasswordStrength::VeryWeak | PasswordStrength::Weak
+            ))
+            .count();
+        Self { entries: active.len(), weak, saved_at: chrono::Utc::now() }
+    }
+}
+
+/// Vault files found in some directories, and what couldn't be read
+#[derive(Debug, Clone, Default)]
+pub struct VaultScan {
+    pub vaults: Vec<VaultFileInfo>,
+    /// Directories and files that were skipped, with the reason
+    pub warnings: Vec<String>,
+}
+
+/// The key protecting a vault file, with the salt and KDF parameters it
+/// was derived from and the cipher it is used with. Held after unlocking
+/// so routine saves can skip the KDF; the key is zeroized on drop.
+#[derive(Clone)]
+pub struct VaultKey {
+    key: Key,
+    salt: SaltString,
+    kdf: KdfParams,
+    vault_id: VaultId,
+    cipher: CipherKind,
+}
+
+impl VaultKey {
+    /// Run the KDF for `password`. The key carries the id of a pre-v4 file
+    /// with this salt, and AES-256-GCM, until it is given the id and cipher
+    /// of the file it belongs to.
+    pub fn derive(password: &str, salt: SaltString, kdf: KdfParams) -> Result<Self, Box<dyn std::error::Error>> {
+        let key = derive_key_with_params(password, &salt, &kdf)?;
+        let vault_id = VaultId::from_salt(&salt);
+        Ok(Self { key, salt, kdf, vault_id, cipher: CipherKind::default() })
+    }
+
+    /// A random key, with no password behind it, for a vault that is only
+    /// ever held in memory (the GUI's demo mode)
+    pub fn ephemeral() -> Self {
+        let key = Key::new(aes_gcm::aead::generic_array::GenericArray::clone_from_slice(&rand::random::<[u8; 32]>()));
+        let salt = SaltString::generate(&mut rand::thread_rng());
+        Self { key, salt, kdf: KdfParams::default(), vault_id: VaultId::generate(), cipher: CipherKind::default() }
+    }
+
+    fn with_vault_id(mut self, vault_id: VaultId) -> Self {
+        self.vault_id = vault_id;
+        self
+    }
+
+    fn with_cipher(mut self, cipher: CipherKind) -> Self {
+        self.cipher = cipher;
+        self
+    }
+
+    pub fn salt(&self) -> &SaltString {
+        &self.salt
+    }
+
+    pub fn kdf(&self) -> KdfParams {
+        self.kdf
+    }
+
+    /// Id of the vault this key protects, written into the header on save
+    pub fn vault_id(&self) -> VaultId {
+        self.vault_id
+    }
+
+    /// Cipher the vault body is encrypted with, written into the header on save
+    pub fn cipher(&self) -> CipherKind {
+        self.cipher
+    }
+
+    /// Whether `password` derives this key. Used to confirm the master
+    /// password of an open vault without keeping it around.
+    pub fn verify_password(&self, password: &str) -> bool {
+        derive_key_with_params(password, &self.salt, &self.kdf)
+            .is_ok_and(|key| bool::from(key.as_ref().ct_eq(self.key.as_ref())))
+    }
+
+    /// Hex HMAC of `data` under this key, for sidecar files that must notice
+    /// a value changing without giving the value away. `context` keeps
+    /// digests taken for different purposes apart.
+    pub fn keyed_digest(&self, context: &str, data: &[u8]) -> String {
+        let mut mac = HmacSha256::new_from_slice(self.key.as_ref())
+            .expect("HMAC can take key of any size");
+        mac.update(b"passman-digest:");
+        mac.update(context.as_bytes());
+        mac.update(&[0]);
+        mac.update(data);
+        mac.finalize().into_bytes().iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// Hand out this key for a file protected by `salt` and `kdf`, or fail
+    /// if the file was re-keyed since the key was derived
+    fn matching(&self, salt: SaltString, kdf: KdfParams) -> Result<Self, Box<dyn std::error::Error>> {
+        if salt.as_str() != self.salt.as_str() || kdf != self.kdf {
+            return Err(VaultError::Rekeyed.into());
+        }
+        Ok(self.clone())
+    }
+}
+
+impl std::fmt::Debug for VaultKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VaultKey")
+            .field("key", &"[REDACTED]")
+            .field("salt", &self.salt.as_str())
+            .field("kdf", &self.kdf)
+            .field("vault_id", &self.vault_id.fingerprint())
+            .field("cipher", &self.cipher)
+            .finish()
+    }
+}
+
+pub struct VaultManager;
+
+impl VaultManager {
+    /// Get the vault file path
+    fn get_vault_path(vault_file: Option<&str>) -> &str {
+        vault_file.unwrap_or(DEFAULT_VAULT_FILE)
+    }
+
+    /// Generate HMAC for vault data
+    fn generate_hmac(key: &Key, data: &[u8]) -> Vec<u8> {
+        let mut mac = HmacSha256::new_from_slice(key.as_ref())
+            .expect("HMAC can take key of any size");
+        mac.update(data);
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    /// Verify HMAC for vault data
+    fn verify_hmac(key: &Key, data: &[u8], expected_hmac: &[u8]) -> bool {
+        let mut mac = HmacSha256::new_from_slice(key.as_ref())
+            .expect("HMAC can take key of any size");
+        mac.update(data);
+        mac.verify_slice(expected_hmac).is_ok()
+    }
+
+    /// Write data atomically (write to temp file, then rename), keeping the
+    /// previous version as `<path>.bak`. The configuration is saved this way too.
+    pub(crate) fn atomic_write(path: &str, data: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let temp = PendingTempFile::new(format!("{}.tmp", path));
+        let temp_path = &temp.0;
+        let backup_path = format!("{}.bak", path);
+
+        // Write to temporary file, readable only by the owner; the rename
+        // keeps its mode
+        {
+            let mut file = create_private(temp_path)?;
+            file.write_all(data)?;
+            file.sync_all()?;
+        }
+
+        // Create backup of existing file if it exists
+        if Path::new(path).exists() {
+            let _ = fs::remove_file(&backup_path);
+            fs::rename(path, &backup_path)?;
+            make_private(Path::new(&backup_path))?;
+        }
+
+        // Rename temp to final
+        fs::rename(temp_path, path)?;
+
+        Ok(())
+    }
+
+    /// Refuse to unlock vault files other users can read, whatever
+    /// `security.strict_permissions` says, for the rest of this process
+    pub fn force_strict_permissions() {
+        FORCE_STRICT_PERMISSIONS.store(true, Ordering::SeqCst);
+    }
+
+    fn strict_permissions() -> bool {
+        FORCE_STRICT_PERMISSIONS.load(Ordering::SeqCst) || get_config().security.strict_permissions
+    }
+
+    /// Warn when other users can read a vault file, or with `strict`
+    /// refuse to go on
+    fn check_permissions(vault_path: &str, strict: bool) -> Result<(), VaultError> {
+        let Some(loose) = Self::loose_permissions(Some(vault_path)) else {
+            return Ok(());
+        };
+        if strict {
+            return Err(VaultError::LoosePermissions(format!("{}. Fix it with: {}", loose.message(), loose.remedy())));
+        }
+        log::warn!("{}. Fix it with: {}", loose.message(), loose.remedy());
+        Ok(())
+    }
+
+    /// The vault file's permissions if users other than its owner can
+    /// access it. Always `None` off Unix, where the file inherits the ACL
+    /// of the folder it is in.
+    pub fn loose_permissions(vault_file: Option<&str>) -> Option<LoosePermissions> {
+        loose_permissions_of(Path::new(Self::get_vault_path(vault_file)))
+    }
+
+    /// [`loose_permissions`](Self::loose_permissions) of every timestamped
+    /// backup and the previous version kept by the last save
+    pub fn loose_backup_permissions(vault_file: Option<&str>) -> Vec<LoosePermissions> {
+        let previous = format!("{}.bak", Self::get_vault_path(vault_file));
+        Self::list_backups(vault_file).into_iter()
+            .chain(std::iter::once(previous))
+            .filter_map(|path| loose_permissions_of(Path::new(&path)))
+            .collect()
+    }
+
+    /// Make a vault (or backup) file readable and writable by its owner only
+    pub fn restrict_permissions(path: &str) -> std::io::Result<()> {
+        make_private(Path::new(path))
+    }
+
+    /// Delete the temp files of vault writes still in progress, for an
+    /// interrupt handler about to exit. The vault files themselves are
+    /// untouched: until the final rename the previous version is in place.
+    /// Returns how many files were removed.
+    pub fn remove_pending_temp_files() -> usize {
+        pending_temp_files().iter().filter(|path| fs::remove_file(path).is_ok()).count()
+    }
+
+    /// Initialize a new encrypted vault with master password, encrypted
+    /// with the cipher in `security.cipher`
+    ///
+    /// Returns the new vault's key so it can be saved without deriving it again.
+    pub fn init(master_password: &Zeroizing<String>, vault_file: Option<&str>) -> Result<VaultKey, Box<dyn std::error::Error>> {
+        Self::init_with_cipher(master_password, get_config().security.cipher, vault_file)
+    }
+
+    /// [`init`](Self::init) with an explicit cipher
+    pub fn init_with_cipher(
+        master_password: &Zeroizing<String>,
+        cipher: CipherKind,
+        vault_file: Option<&str>,
+    ) -> Result<VaultKey, Box<dyn std::error::Error>> {
+        let vault_path = Self::get_vault_path(vault_file);
+        
+        if Path::new(vault_path).exists() {
+            return Err(format!("Vault '{}' already exists! Remove it to reset.", vault_path).into());
+        }
+
+        let salt = SaltString::generate(&mut rand::thread_rng());
+        let key = VaultKey::derive(master_password.as_str(), salt, Self::configured_kdf_params())?
+            .with_vault_id(VaultId::generate())
+            .with_cipher(cipher);
+        Self::atomic_write(vault_path, &Self::encrypt_vault(&Vault::new(), &key)?)?;
+        Self::update_stats(&Vault::new(), vault_path);
+
+        log::info!("Vault initialized ({}): {}", cipher, vault_path);
+        Ok(key)
+    }    /// Load and decrypt vault with master password
+    pub fn load(master_password: &Zeroizing<String>, vault_file: Option<&str>) -> Result<Vault, Box<dyn std::error::Error>> {
+        Self::load_with_key(master_password, vault_file).map(|(vault, _)| vault)
+    }
+
+    /// Load a vault and keep the key it was decrypted with, for
+    /// [`VaultManager::save_with_key`]
+    pub fn load_with_key(master_password: &Zeroizing<String>, vault_file: Option<&str>) -> Result<(Vault, VaultKey), Box<dyn std::error::Error>> {
+        let vault_path = Self::get_vault_path(vault_file);
+        
+        if !Path::new(vault_path).exists() {
+            return Err(format!("Vault '{}' not found! Run 'passman init' first.", vault_path).into());
+        }
+
+        Self::check_permissions(vault_path, Self::strict_permissions())?;
+
+        // One read, sized from the file's metadata
+        let buffer = fs::read(vault_path)?;
+
+        let (vault, key) = Self::decrypt_vault(&buffer, vault_path, |salt, kdf| {
+            // Timed, so later unlocks can show an estimate
+            let derive_start = Instant::now();
+            let key = VaultKey::derive(master_password.as_str(), salt, kdf)?;
+            Self::record_unlock_time(vault_path, derive_start.elapsed().as_millis() as u64);
+            Ok(key)
+        })?;
+        Self::pin_fingerprint(vault_path, &key.vault_id.fingerprint());
+        Ok((vault, key))
+    }
+
+    /// Load a vault with the key it was unlocked with, skipping the KDF.
+    /// Fails if the file has been re-keyed since (e.g. its password was
+    /// changed by another program).
+    pub fn load_with_vault_key(key: &VaultKey, vault_file: Option<&str>) -> Result<Vault, Box<dyn std::error::Error>> {
+        let vault_path = Self::get_vault_path(vault_file);
+        let buffer = fs::read(vault_path)?;
+        Self::decrypt_vault(&buffer, vault_path, |salt, kdf| key.matching(salt, kdf)).map(|(vault, _)| vault)
+    }
+
+    /// [`load_with_vault_key`](Self::load_with_vault_key) for raw file
+    /// contents, such as a backup made since the vault was unlocked
+    pub fn load_bytes_with_vault_key(key: &VaultKey, buffer: &[u8]) -> Result<Vault, Box<dyn std::error::Error>> {
+        Self::decrypt_vault(buffer, "<memory>", |salt, kdf| key.matching(salt, kdf)).map(|(vault, _)| vault)
+    }
+
+    /// Decrypt a vault from its raw file contents without touching the
+    /// filesystem (used by the browser viewer, which has no file paths)
+    pub fn load_from_bytes(master_password: &Zeroizing<String>, buffer: &[u8]) -> Result<Vault, Box<dyn std::error::Error>> {
+        Self::decrypt_vault(buffer, "<memory>", |salt, kdf| VaultKey::derive(master_password.as_str(), salt, kdf))
+            .map(|(vault, _)| vault)
+    }
+
+    /// Parse and decrypt vault bytes. `unlock` supplies the key for the
+    /// salt and KDF parameters found in the file: by deriving it from a
+    /// password, or by checking that a cached key still fits. The returned
+    /// key carries the file's vault id and cipher.
+    fn decrypt_vault(
+        buffer: &[u8],
+        vault_path: &str,
+        unlock: impl FnOnce(SaltString, KdfParams) -> Result<VaultKey, Box<dyn std::error::Error>>,
+    ) -> Result<(Vault, VaultKey), Box<dyn std::error::Error>> {
+        let (section, body) = Self::parse_file(buffer)?;
+        if !section.salt_fallbacks.is_empty() {
+            let fallbacks: Vec<String> = section.salt_fallbacks.iter().map(ToString::to_string).collect();
+            log::warn!("Salt of {} is not canonical, read after: {}. The next save rewrites it.", vault_path, fallbacks.join(", "));
+        }
+        let key = unlock(section.salt, section.kdf)?
+            .with_vault_id(section.vault_id)
+            .with_cipher(section.cipher);
+
+        // Legacy files have no HMAC; AES-GCM still authenticates them
+        if let Some(stored_hmac) = body.hmac {
+            let mut hmac_data = Vec::new();
+            hmac_data.extend_from_slice(body.nonce);
+            hmac_data.extend_from_slice(body.ciphertext);
+            if !Self::verify_hmac(&key.key, &hmac_data, stored_hmac) {
+                return Err(VaultError::IntegrityFailed.into());
+            }
+        }
+
+        let plaintext = key.cipher.cipher().decrypt(&key.key, body.ciphertext, body.nonce)?;
+        let vault: Vault = serde_json::from_slice(&plaintext)?;
+
+        match section.version {
+            Some(version) => log::info!("Vault loaded (v{} format): {}", version, vault_path),
+            None => log::warn!("Loaded legacy vault format (v1): {}. Re-save to upgrade to v2.", vault_path),
+        }
+        Ok((vault, key))
+    }
+
+    /// Split vault bytes into their parts without decrypting anything.
+    ///
+    /// v2+: [header][salt][nonce][hmac(32)][ciphertext], the nonce 12
+    /// bytes for AES-256-GCM and 24 for XChaCha20-Poly1305
+    /// Legacy: [salt_len(4)][salt][nonce(12)][ciphertext]
+    fn parse_file(buffer: &[u8]) -> Result<(KeySection, Body<'_>), VaultError> {
+        let mut reader = FileReader::new(buffer);
+        let section = Self::read_key_section(&mut reader)?;
+        let nonce = reader.take(section.cipher.cipher().nonce_len(), "nonce")?;
+        let hmac = match section.version {
+            Some(_) => Some(reader.take(32, "HMAC")?),
+            None => None,
+        };
+        Ok((section, Body { nonce, hmac, ciphertext: reader.rest() }))
+    }
+
+    /// Read the salt, KDF parameters, id and cipher at the start of a vault
+    /// file, leaving `reader` just past the salt
+    fn read_key_section(reader: &mut FileReader) -> Result<KeySection, VaultError> {
+        let (version, salt_len, kdf, vault_id, cipher) = match VaultHeader::from_bytes(reader.buffer) {
+            Some(header) => {
+                reader.take(header.len(), "header")?;
+                check_kdf_params(&header.kdf)?;
+                let cipher = CipherKind::from_id(header.cipher_id)
+                    .ok_or_else(|| VaultError::InvalidFormat(format!("unknown cipher id {}", header.cipher_id)))?;
+                (Some(header.version), header.salt_len, header.kdf, header.vault_id, cipher)
+            }
+            None if reader.buffer.starts_with(VAULT_MAGIC) => {
+                return Err(VaultError::Corrupted("too short for its header".to_string()));
+            }
+            // Legacy files always used the default parameters
+            None => (None, u32::from_le_bytes(reader.take_array("salt length")?), KdfParams::default(), None, CipherKind::Aes256Gcm),
+        };
+
+        if salt_len > MAX_SALT_LEN {
+            return Err(VaultError::Corrupted(format!("salt length {} is out of range", salt_len)));
+        }
+        let salt_str = std::str::from_utf8(reader.take(salt_len as usize, "salt")?)
+            .map_err(|_| VaultError::InvalidFormat("salt is not text".to_string()))?;
+        let (salt, salt_fallbacks) = parse_salt(salt_str)?;
+        let vault_id = vault_id.unwrap_or_else(|| VaultId::from_salt(&salt));
+        Ok(KeySection { version, salt, salt_fallbacks, kdf, vault_id, cipher })
+    }
+
+    /// Save encrypted vault (v2 format with HMAC and atomic write)
+    ///
+    /// Runs the KDF every time; callers that keep the vault open should
+    /// hold on to a [`VaultKey`] and use [`VaultManager::save_with_key`].
+    pub fn save(vault: &Vault, master_password: &Zeroizing<String>, vault_file: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+        let key = Self::vault_key(master_password, vault_file)?;
+        Self::save_with_key(vault, &key, vault_file)
+    }
+
+    /// Save with an already derived key, skipping the KDF. The file keeps
+    /// the key's salt and KDF parameters.
+    pub fn save_with_key(vault: &Vault, key: &VaultKey, vault_file: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+        Self::save_and_fingerprint(vault, key, vault_file).map(|_| ())
+    }
+
+    /// [`save_with_key`](Self::save_with_key), returning the fingerprint of
+    /// the file just written. It is taken from the bytes in memory, so a
+    /// caller watching for external changes doesn't read the file back.
+    pub fn save_and_fingerprint(vault: &Vault, key: &VaultKey, vault_file: Option<&str>) -> Result<VaultFingerprint, Box<dyn std::error::Error>> {
+        let vault_path = Self::get_vault_path(vault_file);
+        let data = Self::encrypt_vault(vault, key)?;
+        Self::atomic_write(vault_path, &data)?;
+        Self::update_stats(vault, vault_path);
+
+        log::info!("Vault saved: {}", vault_path);
+        Ok(VaultFingerprint::of_bytes(fs::metadata(vault_path)?.modified().ok(), &data))
+    }
+
+    fn stats_path(vault_path: &str) -> PathBuf {
+        PathBuf::from(format!("{}{}", vault_path, STATS_SUFFIX))
+    }
+
+    /// Rewrite the plaintext stats sidecar after a save, or remove it when
+    /// `security.store_plain_metadata` is off. Failures only cost the badges.
+    fn update_stats(vault: &Vault, vault_path: &str) {
+        let path = Self::stats_path(vault_path);
+        if !get_config().security.store_plain_metadata {
+            let _ = fs::remove_file(path);
+            return;
+        }
+        let written = serde_json::to_vec(&VaultStats::of(vault))
+            .map_err(|e| e.to_string())
+            .and_then(|data| fs::write(&path, data).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            log::warn!("Could not write vault stats {}: {}", path.display(), e);
+        }
+    }
+
+    /// Counts recorded at the vault's last save. Always `None` while
+    /// `security.store_plain_metadata` is off, even if an old sidecar remains.
+    pub fn stats(vault_file: Option<&str>) -> Option<VaultStats> {
+        if !get_config().security.store_plain_metadata {
+            return None;
+        }
+        let data = fs::read(Self::stats_path(Self::get_vault_path(vault_file))).ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    /// Remove the stats sidecar of a vault, e.g. after turning
+    /// `security.store_plain_metadata` off
+    pub fn forget_stats(vault_file: Option<&str>) {
+        let _ = fs::remove_file(Self::stats_path(Self::get_vault_path(vault_file)));
+    }
+
+    /// [`forget_stats`](Self::forget_stats) for every vault in
+    /// [`vault_dirs`](Self::vault_dirs)
+    pub fn forget_all_stats(active_vault: &str) {
+        for vault in Self::list_vaults(&Self::vault_dirs(active_vault)).vaults {
+            Self::forget_stats(Some(&vault.path));
+        }
+    }
+
+    /// Derive the key for saving to a vault file: the existing file's salt,
+    /// KDF parameters, id and cipher if there is one, otherwise fresh ones
+    pub fn vault_key(master_password: &Zeroizing<String>, vault_file: Option<&str>) -> Result<VaultKey, Box<dyn std::error::Error>> {
+        let (salt, kdf, vault_id, cipher) = match File::open(Self::get_vault_path(vault_file)) {
+            Ok(file) => {
+                let section = Self::read_key_section(&mut FileReader::new(&Self::read_key_section_bytes(file)?))?;
+                (section.salt, section.kdf, section.vault_id, section.cipher)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (
+                SaltString::generate(&mut rand::thread_rng()),
+                Self::configured_kdf_params(),
+                VaultId::generate(),
+                get_config().security.cipher,
+            ),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(VaultKey::derive(master_password.as_str(), salt, kdf)?.with_vault_id(vault_id).with_cipher(cipher))
+    }
+
+    /// Encrypt a vault into v5 file bytes:
+    /// [header(38)][salt][nonce(12 or 24)][hmac(32)][ciphertext]
+    pub(crate) fn encrypt_vault(vault: &Vault, key: &VaultKey) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let serialized = Zeroizing::new(serde_json::to_vec(vault)?);
+        let (ciphertext, nonce) = key.cipher.cipher().encrypt(&key.key, &serialized)?;
+
+        let salt_bytes = key.salt.as_str().as_bytes();
+        let header = VaultHeader::new(salt_bytes.len() as u32, key.kdf, key.vault_id, key.cipher);
+        
+        // HMAC covers nonce + ciphertext
+        let mut hmac_data = Vec::new();
+        hmac_data.extend_from_slice(&nonce);
+        hmac_data.extend_from_slice(&ciphertext);
+        let hmac = Self::generate_hmac(&key.key, &hmac_data);
+
+        let mut file_data = Vec::new();
+        file_data.extend_from_slice(&header.to_bytes());
+        file_data.extend_from_slice(salt_bytes);
+        file_data.extend_from_slice(&nonce);
+        file_data.extend_from_slice(&hmac);
+        file_data.extend_from_slice(&ciphertext);
+        Ok(file_data)
+    }
+
+    /// Take a fingerprint (mtime, size, SHA-256) of the vault file on disk.
+    ///
+    /// The file is not decrypted; callers use this to cheaply notice that
+    /// another process (CLI, second GUI instance) rewrote the vault.
+    pub fn fingerprint(vault_file: Option<&str>) -> Result<VaultFingerprint, Box<dyn std::error::Error>> {
+        let vault_path = Self::get_vault_path(vault_file);
+        let metadata = fs::metadata(vault_path)?;
+        let buffer = fs::read(vault_path)?;
+        Ok(VaultFingerprint::of_bytes(metadata.modified().ok(), &buffer))
+    }
+
+    /// Check if vault exists
+    pub fn exists(vault_file: Option<&str>) -> bool {
+        let vault_path = Self::get_vault_path(vault_file);
+        Path::new(vault_path).exists()
+    }
+
+    /// Directories worth searching for vaults: the platform data directory,
+    /// then the directories of the configured default vault and of `active_vault`
+    pub fn vault_dirs(active_vault: &str) -> Vec<PathBuf> {
+        let configured = get_config().general.default_vault.clone();
+        let mut found: Vec<PathBuf> = dirs::data_dir().map(|dir| dir.join("passman")).into_iter().collect();
+        for vault in [configured.trim(), active_vault.trim()] {
+            found.push(match Path::new(vault).parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+                _ => PathBuf::from("."),
+            });
+        }
+
+        let mut seen = Vec::new();
+        found.retain(|dir| {
+            let key = dir.canonicalize().unwrap_or_else(|_| dir.clone());
+            let new = !seen.contains(&key);
+            seen.push(key);
+            new
+        });
+        found
+    }
+
+    /// Vault files in `dirs`, recognised by their header rather than the
+    /// extension: only `.dat` files are opened, and only their first bytes
+    /// read. Unreadable directories and files are skipped with a warning.
+    pub fn list_vaults(dirs: &[PathBuf]) -> VaultScan {
+        let mut scan = VaultScan::default();
+        for dir in dirs {
+            let entries = match read_dir(dir) {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => {
+                    scan.warnings.push(format!("{}: {}", dir.display(), e));
+                    continue;
+                }
+            };
+            for (index, entry) in entries.enumerate() {
+                if index == MAX_VAULT_SCAN_ENTRIES {
+                    scan.warnings.push(format!("{}: stopped after {} entries", dir.display(), MAX_VAULT_SCAN_ENTRIES));
+                    break;
+                }
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        scan.warnings.push(format!("{}: {}", dir.display(), e));
+                        continue;
+                    }
+                };
+                let name = entry.file_name();
+                if !name.to_string_lossy().ends_with(".dat") || entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                    continue;
+                }
+                // Keep names in the working directory as short as before
+                let path = if dir == Path::new(".") { PathBuf::from(&name) } else { entry.path() };
+                match Self::read_format_version(&path) {
+                    Ok(Some(version)) => scan.vaults.push(VaultFileInfo {
+                        path: path.to_string_lossy().into_owned(),
+                        version,
+                        size: fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0),
+                        stats: Self::stats(path.to_str()),
+                    }),
+                    Ok(None) => {}
+                    Err(e) => scan.warnings.push(format!("{}: {}", path.display(), e)),
+                }
+            }
+        }
+        scan.vaults.sort_by(|a, b| a.path.cmp(&b.path));
+        scan
+    }
+
+    /// Format version of a vault file; `None` for the legacy format, which
+    /// has no header
+    pub fn format_version(vault_file: Option<&str>) -> std::io::Result<Option<u8>> {
+        Self::read_format_version(Path::new(Self::get_vault_path(vault_file)))
+    }
+
+    /// Format version from the first bytes of a file; `None` if it doesn't
+    /// start with the vault magic
+    fn read_format_version(path: &Path) -> std::io::Result<Option<u8>> {
+        let mut header = [0u8; V2_HEADER_LEN];
+        match File::open(path)?.read_exact(&mut header) {
+            Ok(()) => Ok((&header[..4] == VAULT_MAGIC).then_some(header[4])),
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Verify vault integrity using HMAC (requires password)
+    pub fn verify_integrity(master_password: &Zeroizing<String>, vault_file: Option<&str>) -> Result<bool, Box<dyn std::error::Error>> {
+        let vault_path = Self::get_vault_path(vault_file);
+        
+        if !Path::new(vault_path).exists() {
+            return Err("Vault file not found".into());
+        }
+
+        let buffer = fs::read(vault_path)?;
+
+        let (section, body) = Self::parse_file(&buffer)?;
+        if let Some(stored_hmac) = body.hmac {
+            let key = derive_key_with_params(master_password.as_str(), &section.salt, &section.kdf)?;
+
+            let mut hmac_data = Vec::new();
+            hmac_data.extend_from_slice(body.nonce);
+            hmac_data.extend_from_slice(body.ciphertext);
+            
+            let valid = Self::verify_hmac(&key, &hmac_data, stored_hmac);
+            
+            if valid {
+                log::info!("Vault integrity verified (HMAC): {}", vault_path);
+            } else {
+                log::error!("Vault integrity check FAILED: {}", vault_path);
+            }
+            
+            return Ok(valid);
+        }
+
+        // Legacy format - no HMAC verification available
+        log::warn!("Legacy vault format does not support HMAC integrity verification");
+        
+        // Fall back to SHA-256 checksum logging
+        let mut hasher = Sha256::new();
+        hasher.update(&buffer);
+        let current_hash = hasher.finalize();
+        log::info!("Vault SHA-256: {:x}", current_hash);
+        
+        Ok(true)
+    }
+
+    /// Create a timestamped backup of the vault and verify that it decrypts.
+    ///
+    /// The result is recorded for `backup_infos`. A backup that fails
+    /// verification is kept (it may still be the best copy there is) but
+    /// reported as an error.
+    pub fn create_backup(master_password: &Zeroizing<String>, vault_file: Option<&str>) -> Result<String, Box<dyn std::error::Error>> {
+        Self::create_backup_checked(vault_file, |buffer| Self::load_from_bytes(master_password, buffer))
+    }
+
+    /// [`create_backup`](Self::create_backup) for an open vault, verified
+    /// with the key it was unlocked with
+    pub fn create_backup_with_key(key: &VaultKey, vault_file: Option<&str>) -> Result<String, Box<dyn std::error::Error>> {
+        Self::create_backup_checked(vault_file, |buffer| Self::load_bytes_with_vault_key(key, buffer))
+    }
+
+    fn create_backup_checked(
+        vault_file: Option<&str>,
+        decrypt: impl FnOnce(&[u8]) -> Result<Vault, Box<dyn std::error::Error>>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let vault_path = Self::get_vault_path(vault_file);
+        
+        if !Path::new(vault_path).exists() {
+            return Err("Vault file not found".into());
+        }
+
+        let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+        let mut backup_name = format!("{}.bak.{}", vault_path, timestamp);
+        // Several backups in one second (e.g. restore right after a backup)
+        let mut n = 1;
+        while Path::new(&backup_name).exists() {
+            backup_name = format!("{}.bak.{}_{}", vault_path, timestamp, n);
+            n += 1;
+        }
+        
+        std::io::copy(&mut File::open(vault_path)?, &mut create_private(&backup_name)?)?;
+        log::info!("Vault backup created: {}", backup_name);
+        
+        Self::check_backup(&backup_name, vault_file, decrypt)
+            .map_err(|e| format!("Backup {} failed verification: {}", backup_name, e))?;
+        Ok(backup_name)
+    }
+
+    /// Decrypt a backup (checking its header and HMAC) and record the
+    /// outcome, size and checksum. Returns the number of entries.
+    pub fn verify_backup(
+        master_password: &Zeroizing<String>,
+        backup_path: &str,
+        vault_file: Option<&str>,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        Self::check_backup(backup_path, vault_file, |buffer| Self::load_from_bytes(master_password, buffer))
+    }
+
+    /// Decrypt a backup with `decrypt` and record the outcome in the manifest
+    fn check_backup(
+        backup_path: &str,
+        vault_file: Option<&str>,
+        decrypt: impl FnOnce(&[u8]) -> Result<Vault, Box<dyn std::error::Error>>,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let buffer = fs::read(backup_path)?;
+        let result = decrypt(&buffer).map(|vault| vault.len());
+
+        let record = BackupRecord {
+            verified: result.is_ok(),
+            size: buffer.len() as u64,
+            sha256: format!("{:x}", Sha256::digest(&buffer)),
+            entries: result.as_ref().ok().copied(),
+            checked_at: chrono::Utc::now(),
+        };
+        let mut manifest = Self::read_backup_manifest(vault_file);
+        // Forget backups that were deleted
+        let existing: Vec<String> = Self::list_backups(vault_file).iter().map(|path| Self::backup_key(path)).collect();
+        manifest.retain(|name, _| existing.contains(name));
+        manifest.insert(Self::backup_key(backup_path), record);
+        if let Ok(contents) = serde_json::to_string_pretty(&manifest) {
+            let _ = fs::write(Self::backup_manifest_path(vault_file), contents);
+        }
+
+        match &result {
+            Ok(_) => log::info!("Backup verified: {}", backup_path),
+            Err(e) => log::error!("Backup verification FAILED for {}: {}", backup_path, e),
+        }
+        result
+    }
+
+    /// Backups of a vault with their recorded verification, newest first.
+    /// Nothing is decrypted; a backup whose contents changed since it was
+    /// verified is reported as unverified.
+    pub fn backup_infos(vault_file: Option<&str>) -> Vec<BackupInfo> {
+        let manifest = Self::read_backup_manifest(vault_file);
+
+        Self::list_backups(vault_file).into_iter().map(|path| {
+            let buffer = fs::read(&path).unwrap_or_default();
+            let record = manifest.get(&Self::backup_key(&path));
+            let unchanged = record.is_some_and(|record| {
+                record.size == buffer.len() as u64 && record.sha256 == format!("{:x}", Sha256::digest(&buffer))
+            });
+            let created = path.rsplit(".bak.").next()
+                .and_then(|stamp| stamp.get(..15))
+                .and_then(|stamp| chrono::NaiveDateTime::parse_from_str(stamp, "%Y%m%d_%H%M%S").ok());
+
+            BackupInfo {
+                created,
+                size: buffer.len() as u64,
+                entries: record.filter(|_| unchanged).and_then(|record| record.entries),
+                verified: record.map(|record| record.verified && unchanged),
+                path,
+            }
+        }).collect()
+    }
+
+    /// Replace the vault file with a backup.
+    ///
+    /// The backup is verified again first and refused if that fails, unless
+    /// `force` is set. The current vault is backed up before it is
+    /// overwritten; that safety backup's path is returned.
+    pub fn restore_backup(
+        master_password: &Zeroizing<String>,
+        backup_path: &str,
+        vault_file: Option<&str>,
+        force: bool,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        if let Err(e) = Self::verify_backup(master_password, backup_path, vault_file) {
+            if !force {
+                return Err(format!("{} failed verification: {}", backup_path, e).into());
+            }
+            log::warn!("Restoring unverified backup {}: {}", backup_path, e);
+        }
+
+        let data = fs::read(backup_path)?;
+        let safety = Self::create_backup(master_password, vault_file)?;
+        Self::atomic_write(Self::get_vault_path(vault_file), &data)?;
+        // The counts describe the vault that was replaced
+        Self::forget_stats(vault_file);
+        log::info!("Vault restored from {}", backup_path);
+        Ok(safety)
+    }
+
+    fn backup_manifest_path(vault_file: Option<&str>) -> PathBuf {
+        PathBuf::from(format!("{}{}", Self::get_vault_path(vault_file), BACKUP_MANIFEST_SUFFIX))
+    }
+
+    /// Backups are recorded by file name so the manifest survives moving the folder
+    fn backup_key(backup_path: &str) -> String {
+        Path::new(backup_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| backup_path.to_string())
+    }
+
+    fn read_backup_manifest(vault_file: Option<&str>) -> HashMap<String, BackupRecord> {
+        fs::read_to_string(Self::backup_manifest_path(vault_file))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Timestamped backups made by `create_backup`, newest first
+    pub fn list_backups(vault_file: Option<&str>) -> Vec<String> {
+        let vault_path = Path::new(Self::get_vault_path(vault_file));
+        let Some(file_name) = vault_path.file_name().map(|name| name.to_string_lossy().to_string()) else {
+            return Vec::new();
+        };
+        let prefix = format!("{}.bak.", file_name);
+        let dir = match vault_path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => std::path::PathBuf::from("."),
+        };
+        
+        let mut backups: Vec<String> = fs::read_dir(&dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+                    .map(|entry| entry.path().to_string_lossy().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        // Timestamps are zero-padded, so name order is chronological
+        backups.sort_by(|a, b| b.cmp(a));
+        backups
+    }
+
+    /// Change master password (re-encrypts the vault with new password)
+    pub fn change_password(
+        old_password: &Zeroizing<String>,
+        new_password: &Zeroizing<String>,
+        vault_file: Option<&str>,
+    ) -> Result<VaultKey, Box<dyn std::error::Error>> {
+        let vault_path = Self::get_vault_path(vault_file);
+        
+        // Create backup first
+        let backup = Self::create_backup(old_password, vault_file)?;
+        log::info!("Created backup before password change: {}", backup);
+
+        // Load vault with old password
+        let (vault, old_key) = Self::load_with_key(old_password, vault_file)?;
+        let new_key = Self::rewrite_rekeyed(&vault, new_password, &old_key, old_key.cipher, vault_path)?;
+
+        log::info!("Master password changed successfully: {}", vault_path);
+        Ok(new_key)
+    }
+
+    /// Re-encrypt a vault under a fresh salt, the configured KDF parameters
+    /// and `cipher` (its current one if `None`), keeping the master
+    /// password. Backs the file up first.
+    pub fn rekey(
+        master_password: &Zeroizing<String>,
+        cipher: Option<CipherKind>,
+        vault_file: Option<&str>,
+    ) -> Result<VaultKey, Box<dyn std::error::Error>> {
+        let vault_path = Self::get_vault_path(vault_file);
+        let (vault, old_key) = Self::load_with_key(master_password, vault_file)?;
+        let backup = Self::create_backup_with_key(&old_key, vault_file)?;
+        log::info!("Created backup before re-keying: {}", backup);
+
+        let cipher = cipher.unwrap_or(old_key.cipher);
+        let new_key = Self::rewrite_rekeyed(&vault, master_password, &old_key, cipher, vault_path)?;
+
+        log::info!("Vault re-keyed ({} to {}): {}", old_key.cipher, cipher, vault_path);
+        Ok(new_key)
+    }
+
+    /// Write `vault` under a key derived from `password` with a fresh salt.
+    /// Re-keying also adopts the currently configured (possibly
+    /// recalibrated) KDF parameters. The vault id stays, so its pinned
+    /// fingerprint still matches.
+    fn rewrite_rekeyed(
+        vault: &Vault,
+        password: &Zeroizing<String>,
+        old_key: &VaultKey,
+        cipher: CipherKind,
+        vault_path: &str,
+    ) -> Result<VaultKey, Box<dyn std::error::Error>> {
+        let new_salt = SaltString::generate(&mut rand::thread_rng());
+        let new_key = VaultKey::derive(password.as_str(), new_salt, Self::configured_kdf_params())?
+            .with_vault_id(old_key.vault_id)
+            .with_cipher(cipher);
+        Self::atomic_write(vault_path, &Self::encrypt_vault(vault, &new_key)?)?;
+        Ok(new_key)
+    }
+
+    /// Read the salt and KDF parameters that protect an existing vault file
+    pub fn key_params(vault_file: Option<&str>) -> Result<(SaltString, KdfParams), Box<dyn std::error::Error>> {
+        Self::key_params_from(File::open(Self::get_vault_path(vault_file))?)
+    }
+
+    /// [`key_params`](Self::key_params) from the start of a vault file
+    /// being read, e.g. from a slow share; only the header and salt are read
+    pub fn key_params_from(reader: impl Read) -> Result<(SaltString, KdfParams), Box<dyn std::error::Error>> {
+        let (salt, kdf, _) = Self::read_key_params(&Self::read_key_section_bytes(reader)?)?;
+        Ok((salt, kdf))
+    }
+
+    /// The first bytes of a vault file, enough for its header and salt
+    fn read_key_section_bytes(reader: impl Read) -> std::io::Result<Vec<u8>> {
+        let mut buffer = Vec::with_capacity(MAX_KEY_SECTION_LEN as usize);
+        reader.take(MAX_KEY_SECTION_LEN).read_to_end(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// The [`SaltFallback`]s needed to read a vault file's salt, without
+    /// unlocking it; empty when the salt is canonical
+    pub fn salt_fallbacks(vault_file: Option<&str>) -> Result<Vec<SaltFallback>, Box<dyn std::error::Error>> {
+        let buffer = Self::read_key_section_bytes(File::open(Self::get_vault_path(vault_file))?)?;
+        Ok(Self::read_key_section(&mut FileReader::new(&buffer))?.salt_fallbacks)
+    }
+
+    /// Rewrite a vault whose salt only reads through fallbacks, with the
+    /// canonical salt. The key doesn't change, so neither does the master
+    /// password. Backs the file up first; does nothing if the salt is
+    /// already canonical.
+    pub fn repair_salt(master_password: &Zeroizing<String>, vault_file: Option<&str>) -> Result<SaltRepair, Box<dyn std::error::Error>> {
+        let fallbacks = Self::salt_fallbacks(vault_file)?;
+        if fallbacks.is_empty() {
+            return Ok(SaltRepair { fallbacks, backup: None });
+        }
+        let (vault, key) = Self::load_with_key(master_password, vault_file)?;
+        let backup = Self::create_backup_with_key(&key, vault_file)?;
+        Self::save_with_key(&vault, &key, vault_file)?;
+
+        log::info!("Vault salt rewritten canonically: {}", Self::get_vault_path(vault_file));
+        Ok(SaltRepair { fallbacks, backup: Some(backup) })
+    }
+
+    /// Salt, KDF parameters and vault id from the start of a vault file
+    fn read_key_params(buffer: &[u8]) -> Result<(SaltString, KdfParams, VaultId), Box<dyn std::error::Error>> {
+        let section = Self::read_key_section(&mut FileReader::new(buffer))?;
+        Ok((section.salt, section.kdf, section.vault_id))
+    }
+
+    /// Id of a vault file, read from its header
+    pub(crate) fn vault_id(vault_file: Option<&str>) -> Result<VaultId, Box<dyn std::error::Error>> {
+        let (_, _, vault_id) = Self::read_key_params(&Self::read_key_section_bytes(File::open(Self::get_vault_path(vault_file))?)?)?;
+        Ok(vault_id)
+    }
+
+    /// Fingerprint of a vault file and the one pinned for its path, read
+    /// without unlocking it
+    pub fn identity(vault_file: Option<&str>) -> Result<VaultIdentity, Box<dyn std::error::Error>> {
+        let vault_path = Self::get_vault_path(vault_file);
+        let vault_id = Self::vault_id(Some(vault_path))?;
+        let pinned = Self::read_fingerprints().remove(&Self::timing_key(vault_path));
+        Ok(VaultIdentity { fingerprint: vault_id.fingerprint(), pinned })
+    }
+
+    fn read_fingerprints() -> HashMap<String, String> {
+        fs::read_to_string(Config::config_path().with_file_name(FINGERPRINTS_FILE))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Remember the fingerprint of a vault just unlocked, for
+    /// [`identity`](Self::identity). Failures only cost the warning.
+    fn pin_fingerprint(vault_path: &str, fingerprint: &str) {
+        let mut pins = Self::read_fingerprints();
+        let key = Self::timing_key(vault_path);
+        if pins.get(&key).map(String::as_str) == Some(fingerprint) {
+            return;
+        }
+        if let Some(previous) = pins.get(&key) {
+            log::warn!("Vault fingerprint changed from {} to {}: {}", previous, fingerprint, vault_path);
+        }
+        pins.retain(|path, _| Path::new(path).exists());
+        pins.insert(key, fingerprint.to_string());
+        if let Ok(contents) = serde_json::to_string_pretty(&pins) {
+            let _ = fs::write(Config::config_path().with_file_name(FINGERPRINTS_FILE), contents);
+        }
+    }
+
+    /// KDF parameters from the configuration, used for new vaults and re-keying
+    fn configured_kdf_params() -> KdfParams {
+        KdfParams::from_config(&get_config().security)
+    }
+
+    /// Cipher a vault file is encrypted with, read from its header
+    pub fn cipher(vault_file: Option<&str>) -> Result<CipherKind, Box<dyn std::error::Error>> {
+        let buffer = Self::read_key_section_bytes(File::open(Self::get_vault_path(vault_file))?)?;
+        Ok(Self::read_key_section(&mut FileReader::new(&buffer))?.cipher)
+    }
+
+    /// Read the KDF parameters stored in a vault file header
+    pub fn kdf_params(vault_file: Option<&str>) -> Result<KdfParams, Box<dyn std::error::Error>> {
+        let buffer = Self::read_key_section_bytes(File::open(Self::get_vault_path(vault_file))?)?;
+
+        Ok(VaultHeader::from_bytes(&buffer)
+            .map(|header| header.kdf)
+            .unwrap_or_default())
+    }
+
+    /// Path of the unlock timings sidecar file
+    fn unlock_timings_path() -> PathBuf {
+        Config::config_path().with_file_name(UNLOCK_TIMINGS_FILE)
+    }
+
+    /// Key used to identify a vault in the timings file
+    fn timing_key(vault_path: &str) -> String {
+        fs::canonicalize(vault_path)
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|_| vault_path.to_string())
+    }
+
+    fn read_unlock_timings() -> HashMap<String, u64> {
+        fs::read_to_string(Self::unlock_timings_path())
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Remember how long key derivation took for a vault.
+    ///
+    /// Keeps a running average so the estimate improves over time. Failures
+    /// are ignored; the timing is only used for progress messages.
+    fn record_unlock_time(vault_path: &str, elapsed_ms: u64) {
+        let mut timings = Self::read_unlock_timings();
+        // Drop vaults that no longer exist so the file doesn't grow forever
+        timings.retain(|path, _| Path::new(path).exists());
+
+        let key = Self::timing_key(vault_path);
+        let averaged = match timings.get(&key) {
+            Some(&previous) => (previous * 3 + elapsed_ms) / 4,
+            None => elapsed_ms,
+        };
+        timings.insert(key, averaged);
+
+        if let Ok(contents) = serde_json::to_string_pretty(&timings) {
+            let _ = fs::write(Self::unlock_timings_path(), contents);
+        }
+    }
+
+    /// Estimated key derivation time for a vault, based on previous unlocks
+    pub fn estimated_unlock_ms(vault_file: Option<&str>) -> Option<u64> {
+        let vault_path = Self::get_vault_path(vault_file);
+        Self::read_unlock_timings().get(&Self::timing_key(vault_path)).copied()
+    }
+
+    /// Strength of the master password if it is below Good and the user
+    /// should hear about it now.
+    ///
+    /// Warns at most once every `MASTER_WARNING_INTERVAL_DAYS` per vault and
+    /// never with `security.suppress_master_strength_warning`. A warning
+    /// that is returned counts as shown.
+    pub fn master_password_warning(master_password: &str, vault_file: Option<&str>) -> Option<PasswordStrength> {
+        if get_config().security.suppress_master_strength_warning {
+            return None;
+        }
+        let (strength, _) = analyze_password_strength(master_password);
+        if !matches!(strength, PasswordStrength::VeryWeak | PasswordStrength::Weak | PasswordStrength::Fair) {
+            return None;
+        }
+
+        let path = Config::config_path().with_file_name(MASTER_WARNINGS_FILE);
+        let mut warned: HashMap<String, i64> = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        let key = Self::timing_key(Self::get_vault_path(vault_file));
+        let now = chrono::Utc::now().timestamp();
+        if !master_warning_due(warned.get(&key).copied(), now) {
+            return None;
+        }
+
+        warned.retain(|path, _| Path::new(path).exists());
+        warned.insert(key, now);
+        if let Ok(contents) = serde_json::to_string_pretty(&warned) {
+            let _ = fs::write(path, contents);
+        }
+        Some(strength)
+    }
+
+    /// Best-effort check whether a vault sits in a cloud sync folder
+    /// (Dropbox, OneDrive, Google Drive, iCloud Drive) or has conflicted
+    /// copies beside it. `None` when nothing was recognised.
+    pub fn sync_folder_risk(vault_file: Option<&str>) -> Option<SyncFolderRisk> {
+        let vault_path = Self::get_vault_path(vault_file);
+        let path = fs::canonicalize(vault_path).ok()?;
+        let service = sync_service_above(&path);
+
+        let conflicted_copies: Vec<String> = conflicted_siblings(&path)
+            .iter()
+            .filter_map(|sibling| sibling.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .collect();
+
+        (service.is_some() || !conflicted_copies.is_empty()).then_some(SyncFolderRisk { service, conflicted_copies })
+    }
+
+    /// Conflicted sync copies beside a vault ("vault (conflicted copy
+    /// 2024-06-01).dat"), by path. Unlike the names in
+    /// [`sync_folder_risk`](Self::sync_folder_risk), only files that start
+    /// like a vault file are listed.
+    pub fn conflicted_copies(vault_file: Option<&str>) -> Vec<String> {
+        let Ok(path) = fs::canonicalize(Self::get_vault_path(vault_file)) else {
+            return Vec::new();
+        };
+        conflicted_siblings(&path)
+            .into_iter()
+            .filter(|sibling| matches!(Self::read_format_version(sibling), Ok(Some(_))))
+            .map(|sibling| sibling.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    /// Merge the conflicted copy at `copy_path`, already decrypted as
+    /// `copy`, into `vault`, keeping the newer version of each entry (see
+    /// [`merge_newer`]). The vault file is backed up and saved with `key`,
+    /// then the copy is moved aside with a `.resolved` suffix.
+    pub fn resolve_conflicted_copy(
+        vault: &mut Vault,
+        key: &VaultKey,
+        copy_path: &str,
+        copy: &Vault,
+        vault_file: Option<&str>,
+    ) -> Result<ConflictResolution, Box<dyn std::error::Error>> {
+        let backup = Self::create_backup_with_key(key, vault_file)?;
+        merge_newer(vault, copy);
+        Self::save_with_key(vault, key, vault_file)?;
+
+        let mut archived = format!("{}{}", copy_path, RESOLVED_SUFFIX);
+        let mut n = 1;
+        while Path::new(&archived).exists() {
+            archived = format!("{}.{}{}", copy_path, n, RESOLVED_SUFFIX);
+            n += 1;
+        }
+        fs::rename(copy_path, &archived)?;
+        log::info!("Merged conflicted copy {} into {}", copy_path, Self::get_vault_path(vault_file));
+        Ok(ConflictResolution { backup, archived })
+    }
+
+    /// [`sync_folder_risk`](Self::sync_folder_risk) if the user should hear
+    /// about it now: once per vault until something new turns up (another
+    /// conflicted copy), and never for vaults listed in
+    /// `security.quiet_sync_folder_vaults`. A warning that is returned
+    /// counts as shown.
+    pub fn sync_folder_warning(vault_file: Option<&str>) -> Option<SyncFolderRisk> {
+        let key = Self::timing_key(Self::get_vault_path(vault_file));
+        if get_config().security.quiet_sync_folder_vaults.contains(&key) {
+            return None;
+        }
+        let risk = Self::sync_folder_risk(vault_file)?;
+
+        let path = Config::config_path().with_file_name(SYNC_WARNINGS_FILE);
+        let mut warned: HashMap<String, String> = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        let signature = risk.signature();
+        if warned.get(&key) == Some(&signature) {
+            return None;
+        }
+
+        warned.retain(|path, _| Path::new(path).exists());
+        warned.insert(key, signature);
+        if let Ok(contents) = serde_json::to_string_pretty(&warned) {
+            let _ = fs::write(path, contents);
+        }
+        Some(risk)
+    }
+
+    /// Stop (or resume) warning that a vault is in a sync folder, by
+    /// listing it in `security.quiet_sync_folder_vaults`
+    pub fn set_sync_folder_warning(vault_file: Option<&str>, enabled: bool) -> Result<(), String> {
+        let key = Self::timing_key(Self::get_vault_path(vault_file));
+        {
+            let mut config = crate::config::get_config_mut();
+            let quiet = &mut config.security.quiet_sync_folder_vaults;
+            quiet.retain(|path| *path != key);
+            if !enabled {
+                quiet.push(key);
+            }
+        }
+        crate::config::save_config()
+    }
+
+    /// Delete a vault file along with the `.bak`/`.tmp` files left by saves
+    /// and what the sidecar files remember about it. With `remove_backups`
+    /// the timestamped backups and their manifest go too. Returns the files
+    /// that were removed.
+    pub fn delete(vault_file: Option<&str>, remove_backups: bool) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let vault_path = Self::get_vault_path(vault_file);
+        if !Path::new(vault_path).exists() {
+            return Err(format!("Vault not found: {}", vault_path).into());
+        }
+        // Keyed by canonical path, which needs the file to still exist
+        let key = Self::timing_key(vault_path);
+        let vault_id = Self::vault_id(Some(vault_path)).ok();
+
+        let mut files = vec![
+            vault_path.to_string(),
+            format!("{}.bak", vault_path),
+            format!("{}.tmp", vault_path),
+            Self::stats_path(vault_path).display().to_string(),
+        ];
+        if remove_backups {
+            files.extend(Self::list_backups(vault_file));
+            files.push(Self::backup_manifest_path(vault_file).display().to_string());
+        }
+
+        let mut removed = Vec::new();
+        for file in files {
+            if Path::new(&file).exists() {
+                fs::remove_file(&file)?;
+                removed.push(file);
+            }
+        }
+
+        for sidecar in [UNLOCK_TIMINGS_FILE, MASTER_WARNINGS_FILE, SYNC_WARNINGS_FILE, FINGERPRINTS_FILE] {
+            forget_sidecar_entry(&Config::config_path().with_file_name(sidecar), &key);
+        }
+        if let Some(vault_id) = vault_id {
+            forget_sidecar_entry(&Config::config_path().with_file_name(crate::lock_summary::SUMMARIES_FILE), &vault_id.fingerprint());
+        }
+        log::info!("Vault deleted: {} ({} files)", vault_path, removed.len());
+        Ok(removed)
+    }
+}
+
+/// Drop one vault's entry from a JSON sidecar keyed by vault path or fingerprint
+fn forget_sidecar_entry(path: &Path, key: &str) {
+    let Some(mut entries) = fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str::<HashMap<String, serde_json::Value>>(&contents).ok())
+    else {
+        return;
+    };
+    if entries.remove(key).is_some() {
+        if let Ok(contents) = serde_json::to_string_pretty(&entries) {
+            let _ = fs::write(path, contents);
+        }
+    }
+}
+
+/// Sync service whose folder `path` is in, judged by the folder names the
+/// clients create on Windows, macOS and Linux
+fn sync_service(path: &Path) -> Option<&'static str> {
+    path.components().find_map(|component| {
+        let name = component.as_os_str().to_string_lossy().to_lowercase();
+        if name == "dropbox" || name.starts_with("dropbox (") || name == ".dropbox-dist" {
+            Some("Dropbox")
+        } else if name == "onedrive" || name.starts_with("onedrive -") || name.starts_with("onedrive-") {
+            Some("OneDrive")
+        } else if name == "google drive" || name == "googledrive" || name.starts_with("googledrive-") || name == "my drive" {
+            Some("Google Drive")
+        } else if name == "icloud drive" || name == "com~apple~clouddocs" {
+            Some("iCloud Drive")
+        } else {
+            None
+        }
+    })
+}
+
+/// Create or truncate a file readable only by its owner; on Unix the mode
+/// is 0600, also when the file already existed with a wider one
+pub(crate) fn create_private(path: &str) -> std::io::Result<File> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let file = options.open(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(file)
+}
+
+/// Set an existing file to 0600 on Unix; nothing to do elsewhere
+fn make_private(path: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn loose_permissions_of(path: &Path) -> Option<LoosePermissions> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(path).ok()?.permissions().mode() & 0o777;
+    (mode & 0o077 != 0).then(|| LoosePermissions { path: path.to_string_lossy().into_owned(), mode })
+}
+
+#[cfg(not(unix))]
+fn loose_permissions_of(_path: &Path) -> Option<LoosePermissions> {
+    None
+}
+
+/// [`sync_service`] of a canonical `path`, or Dropbox if a folder above it
+/// holds the cache Dropbox keeps at the top of the synced tree
+pub(crate) fn sync_service_above(path: &Path) -> Option<&'static str> {
+    sync_service(path).or_else(|| {
+        path.ancestors().skip(1).find(|dir| dir.join(".dropbox.cache").is_dir()).map(|_| "Dropbox")
+    })
+}
+
+/// Whether a (lowercased) file name looks like a sync client's conflicted
+/// copy of the vault with the (lowercased) stem `stem`: "vault (Ann's
+/// conflicted copy 2024-01-02).dat", "vault.sync-conflict-…", "vault-conflict-…"
+fn is_conflicted_copy(name: &str, stem: &str) -> bool {
+    name.starts_with(stem) && name.contains("conflict") && !name.ends_with(RESOLVED_SUFFIX)
+}
+
+/// Files beside `vault_path` named like conflicted copies of it, sorted
+fn conflicted_siblings(vault_path: &Path) -> Vec<PathBuf> {
+    let (Some(name), Some(stem), Some(dir)) = (vault_path.file_name(), vault_path.file_stem(), vault_path.parent()) else {
+        return Vec::new();
+    };
+    let stem = stem.to_string_lossy().to_lowercase();
+    let mut siblings: Vec<PathBuf> = read_dir(dir)
+        .into_iter()
+        .flatten()
+        .take(MAX_VAULT_SCAN_ENTRIES)
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name() != name && is_conflicted_copy(&entry.file_name().to_string_lossy().to_lowercase(), &stem))
+        .map(|entry| entry.path())
+        .collect();
+    siblings.sort();
+    siblings
+}
+
+/// Whether a weak master password warning last shown at `last_warned`
+/// (Unix seconds) may be shown again at `now`
+fn master_warning_due(last_warned: Option<i64>, now: i64) -> bool {
+    last_warned.is_none_or(|last| now - last >= MASTER_WARNING_INTERVAL_DAYS * 24 * 60 * 60)
+}
+
+/// Security manager for handling authentication delays and security policies
+#[allow(dead_code)]
+pub struct SecurityManager {
+    failed_attempts: u32,
+    last_attempt: Option<Instant>,
+    lockout_until: Option<Instant>,
+}
+
+impl SecurityManager {
+    pub fn new() -> Self {
+        Self {
+            failed_attempts: 0,
+            last_attempt: None,
+            lockout_until: None,
+        }
+    }
+
+    /// Check if authentication is currently locked out
+    #[allow(dead_code)]
+    pub fn is_locked_out(&self) -> bool {
+        if let Some(lockout_time) = self.lockout_until {
+            Instant::now() < lockout_time
+        } else {
+            false
+        }
+    }
+
+    /// Get remaining lockout time in seconds
+    #[allow(dead_code)]
+    pub fn remaining_lockout_time(&self) -> Option<u64> {
+        if let Some(lockout_time) = self.lockout_until {
+            let now = Instant::now();
+            if now < lockout_time {
+                Some((lockout_time - now).as_secs())
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Get remaining lockout seconds (convenience method)
+    pub fn lockout_remaining_secs(&self) -> u64 {
+        self.remaining_lockout_time().unwrap_or(0)
+    }
+
+    /// Get remaining login attempts before lockout
+    pub fn remaining_attempts(&self) -> u32 {
+        5u32.saturating_sub(self.failed_attempts)
+    }
+
+    /// Record a failed authentication attempt
+    #[allow(dead_code)]
+    pub fn record_failed_attempt(&mut self) {
+        self.failed_attempts += 1;
+        self.last_attempt = Some(Instant::now());
+
+        // Implement exponential backoff
+        let delay = match self.failed_attempts {
+            1..=2 => Duration::from_secs(1),
+            3..=4 => Duration::from_secs(2),
+            5..=6 => Duration::from_secs(5),
+            7..=8 => Duration::from_secs(10),
+            _ => Duration::from_secs(30),
+        };
+
+        // Lock out for longer periods after many attempts
+        if self.failed_attempts >= 5 {
+            self.lockout_until = Some(Instant::now() + delay);
+        }
+
+        // Add immediate delay to slow down brute force
+        thread::sleep(delay);
+    }
+
+    /// Record a successful authentication attempt
+    #[allow(dead_code)]
+    pub fn record_successful_attempt(&mut self) {
+        self.failed_attempts = 0;
+        self.last_attempt = None;
+        self.lockout_until = None;
+    }
+
+    /// Alias for record_successful_attempt (for API consistency)
+    pub fn record_successful_login(&mut self) {
+        self.record_successful_attempt();
+    }
+
+    /// Clear all security state (for testing purposes)
+    #[allow(dead_code)]
+    pub fn reset(&mut self) {
+        self.failed_attempts = 0;
+        self.last_attempt = None;
+        self.lockout_until = None;
+    }
+}
+
+impl Default for SecurityManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Crafted files under tests/fixtures/vault, and whether each should
+    /// fail as corrupted (a length that doesn't fit the file) rather than
+    /// malformed (a value that makes no sense)
+    const MALFORMED_FIXTURES: &[(&str, &[u8], bool)] = &[
+        ("empty", include_bytes!("../tests/fixtures/vault/empty.bin"), true),
+        ("magic_only", include_bytes!("../tests/fixtures/vault/magic_only.bin"), true),
+        ("truncated_v4_header", include_bytes!("../tests/fixtures/vault/truncated_v4_header.bin"), true),
+        ("oversized_salt_len", include_bytes!("../tests/fixtures/vault/oversized_salt_len.bin"), true),
+        ("salt_past_end", include_bytes!("../tests/fixtures/vault/salt_past_end.bin"), true),
+        ("missing_hmac", include_bytes!("../tests/fixtures/vault/missing_hmac.bin"), true),
+        ("legacy_huge_salt_len", include_bytes!("../tests/fixtures/vault/legacy_huge_salt_len.bin"), true),
+        ("legacy_missing_nonce", include_bytes!("../tests/fixtures/vault/legacy_missing_nonce.bin"), true),
+        ("huge_memory_kb", include_bytes!("../tests/fixtures/vault/huge_memory_kb.bin"), false),
+        ("zero_parallelism", include_bytes!("../tests/fixtures/vault/zero_parallelism.bin"), false),
+        ("salt_not_utf8", include_bytes!("../tests/fixtures/vault/salt_not_utf8.bin"), false),
+        ("unknown_cipher", include_bytes!("../tests/fixtures/vault/unknown_cipher.bin"), false),
+    ];
+
+    /// Vault files under tests/fixtures/vault_salt whose salt was written
+    /// in a form `SaltString` rejects, with the fallbacks each one needs
+    /// and the canonical salt it reads as. Each opens with "correct horse".
+    const VAULT_SALT_FIXTURES: &[(&str, &[u8], &[SaltFallback], &str)] = &[
+        ("padded", include_bytes!("../tests/fixtures/vault_salt/padded.bin"), &[SaltFallback::Unpadded], "pNkFSgKip8L/IZx/AFBGLg"),
+        ("url_safe", include_bytes!("../tests/fixtures/vault_salt/url_safe.bin"), &[SaltFallback::UrlSafe], "eOhr+PZI1jCOfVu/7ZUolw"),
+        ("nul_padded", include_bytes!("../tests/fixtures/vault_salt/nul_padded.bin"), &[SaltFallback::Trimmed], "JrgLpvh0MrfdTAAL4E5HnQ"),
+        ("dangling_char", include_bytes!("../tests/fixtures/vault_salt/dangling_char.bin"), &[SaltFallback::Truncated], "+Azj+d5DTAYvHRD08a4P"),
+        ("noncanonical_bits", include_bytes!("../tests/fixtures/vault_salt/noncanonical_bits.bin"), &[SaltFallback::NonCanonicalBits], "MiLbKgpVtvAXqQdM1V8y/g"),
+        (
+            "combined",
+            include_bytes!("../tests/fixtures/vault_salt/combined.bin"),
+            &[SaltFallback::Trimmed, SaltFallback::Unpadded, SaltFallback::UrlSafe],
+            "cnhb71r/z+oV8/Q4oRLi0Q",
+        ),
+    ];
+
+    /// A v2 vault file written before KDF parameters, vault ids and ciphers
+    /// were stored: default Argon2 costs, AES-256-GCM, opens with
+    /// "correct horse" and holds one entry
+    const V2_AES_FIXTURE: &[u8] = include_bytes!("../tests/fixtures/vault_format/v2_aes.bin");
+
+    /// A small v5 vault file under a cheap KDF, and the key that opens it
+    fn light_vault_file() -> (Vec<u8>, VaultKey) {
+        let kdf = KdfParams { memory_kb: 1024, time_cost: 1, parallelism: 1 };
+        let key = VaultKey::derive("correct horse", SaltString::generate(&mut rand::thread_rng()), kdf).unwrap();
+        let mut vault = Vault::new();
+        vault.add_entry("github".to_string(), crate::model::Entry::new("octo".to_string(), "pw".to_string(), None));
+        (VaultManager::encrypt_vault(&vault, &key).unwrap(), key)
+    }
+
+    /// Whether a load error is one of the typed errors loading reports
+    fn is_typed(err: &(dyn std::error::Error + 'static)) -> bool {
+        err.is::<VaultError>() || err.is::<crate::crypto::CryptoError>() || err.is::<serde_json::Error>()
+    }
+
+    #[test]
+    fn test_master_warning_interval() {
+        let day = 24 * 60 * 60;
+        assert!(master_warning_due(None, 0));
+        assert!(!master_warning_due(Some(1_000), 1_000 + day));
+        assert!(master_warning_due(Some(1_000), 1_000 + MASTER_WARNING_INTERVAL_DAYS * day));
+    }
+
+    #[test]
+    fn test_v5_header_roundtrip() {
+        let kdf = KdfParams { memory_kb: 32768, time_cost: 5, parallelism: 2 };
+        let vault_id = VaultId::generate();
+        let header = VaultHeader::new(22, kdf, vault_id, CipherKind::XChaCha20Poly1305);
+        let bytes = header.to_bytes();
+        
+        assert_eq!(bytes.len(), V5_HEADER_LEN);
+        
+        let parsed = VaultHeader::from_bytes(&bytes).expect("Header should parse");
+        assert_eq!(parsed.version, VAULT_FORMAT_VERSION);
+        assert_eq!(parsed.salt_len, 22);
+        assert_eq!(parsed.kdf, kdf);
+        assert_eq!(parsed.vault_id, Some(vault_id));
+        assert_eq!(parsed.cipher_id, CipherKind::XChaCha20Poly1305.id());
+
+        // v4 headers end before the cipher, which can only be AES
+        let mut v4 = bytes[..V4_HEADER_LEN].to_vec();
+        v4[4] = 4;
+        let parsed = VaultHeader::from_bytes(&v4).expect("Header should parse");
+        assert_eq!((parsed.len(), parsed.vault_id, parsed.cipher_id), (V4_HEADER_LEN, Some(vault_id), CipherKind::Aes256Gcm.id()));
+        assert!(VaultHeader::from_bytes(&bytes[..V4_HEADER_LEN]).is_none());
+
+        // v3 headers end before the id
+        let mut v3 = bytes[..V3_HEADER_LEN].to_vec();
+        v3[4] = 3;
+        let parsed = VaultHeader::from_bytes(&v3).expect("Header should parse");
+        assert_eq!((parsed.len(), parsed.kdf, parsed.vault_id), (V3_HEADER_LEN, kdf, None));
+    }
+
+    #[test]
+    fn test_v2_header_uses_default_kdf() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(VAULT_MAGIC);
+        bytes.push(2);
+        bytes.extend_from_slice(&22u32.to_le_bytes());
+        
+        let parsed = VaultHeader::from_bytes(&bytes).expect("Header should parse");
+        assert_eq!(parsed.len(), V2_HEADER_LEN);
+        assert_eq!(parsed.kdf, KdfParams::default());
+    }
+
+    #[test]
+    fn test_malformed_fixtures_fail_before_key_derivation() {
+        let password = Zeroizing::new("correct horse".to_string());
+        for (name, bytes, corrupted) in MALFORMED_FIXTURES {
+            let err = VaultManager::load_from_bytes(&password, bytes).expect_err(name);
+            match err.downcast_ref::<VaultError>() {
+                Some(VaultError::Corrupted(_)) => assert!(*corrupted, "{}: {}", name, err),
+                Some(VaultError::InvalidFormat(_)) => assert!(!*corrupted, "{}: {}", name, err),
+                _ => panic!("{}: unexpected error: {}", name, err),
+            }
+        }
+    }
+
+    #[test]
+    fn test_non_canonical_salts_load_through_fallbacks() {
+        let password = Zeroizing::new("correct horse".to_string());
+        for (name, bytes, fallbacks, canonical) in VAULT_SALT_FIXTURES {
+            let (section, _) = VaultManager::parse_file(bytes).unwrap_or_else(|e| panic!("{}: {}", name, e));
+            assert_eq!(section.salt_fallbacks, *fallbacks, "{}", name);
+            assert_eq!(section.salt.as_str(), *canonical, "{}", name);
+
+            let (vault, key) = VaultManager::decrypt_vault(bytes, name, |salt, kdf| VaultKey::derive(password.as_str(), salt, kdf))
+                .unwrap_or_else(|e| panic!("{}: {}", name, e));
+            assert_eq!(vault.get_entry("github").unwrap().username, "octo", "{}", name);
+
+            // The next save writes the salt canonically, under the same key
+            let resaved = VaultManager::encrypt_vault(&vault, &key).unwrap();
+            let (section, _) = VaultManager::parse_file(&resaved).unwrap();
+            assert!(section.salt_fallbacks.is_empty(), "{}", name);
+            assert_eq!(section.salt.as_str(), *canonical, "{}", name);
+            assert!(VaultManager::load_from_bytes(&password, &resaved).is_ok(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_salt_fallbacks_leave_canonical_and_hopeless_salts_alone() {
+        let salt = SaltString::generate(&mut rand::thread_rng());
+        assert_eq!(parse_salt(salt.as_str()).unwrap(), (salt.clone(), vec![]));
+        for fallback in SaltFallback::ALL {
+            assert_eq!(fallback.apply(salt.as_str()), salt.as_str(), "{}", fallback);
+        }
+        for hopeless in ["", "!!!!!!!!!!!!!!!!!!!!!!", "é-not-base64-at-all"] {
+            assert!(matches!(parse_salt(hopeless), Err(VaultError::InvalidFormat(_))), "{:?}", hopeless);
+        }
+    }
+
+    #[test]
+    fn test_repair_salt_rewrites_the_file_and_keeps_a_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("legacy.dat");
+        let path = path.to_str().unwrap();
+        let password = Zeroizing::new("correct horse".to_string());
+        let (_, bytes, fallbacks, canonical) = VAULT_SALT_FIXTURES.iter().find(|(name, ..)| *name == "combined").unwrap();
+        fs::write(path, bytes).unwrap();
+
+        assert_eq!(VaultManager::salt_fallbacks(Some(path)).unwrap(), *fallbacks);
+        assert!(VaultManager::repair_salt(&Zeroizing::new("wrong".to_string()), Some(path)).is_err());
+        assert_eq!(fs::read(path).unwrap(), *bytes);
+
+        let repair = VaultManager::repair_salt(&password, Some(path)).unwrap();
+        assert_eq!(repair.fallbacks, *fallbacks);
+        assert_eq!(fs::read(repair.backup.unwrap()).unwrap(), *bytes);
+        assert!(VaultManager::salt_fallbacks(Some(path)).unwrap().is_empty());
+        assert_eq!(VaultManager::key_params(Some(path)).unwrap().0.as_str(), *canonical);
+        assert_eq!(VaultManager::load(&password, Some(path)).unwrap().get_entry("github").unwrap().username, "octo");
+
+        // Nothing left to do the second time
+        assert_eq!(VaultManager::repair_salt(&password, Some(path)).unwrap(), SaltRepair { fallbacks: vec![], backup: None });
+    }
+
+    #[test]
+    fn test_truncation_at_every_boundary_is_an_error() {
+        let (bytes, key) = light_vault_file();
+        let ciphertext_start = V5_HEADER_LEN + key.salt().as_str().len() + 12 + 32;
+        for len in 0..bytes.len() {
+            let err = VaultManager::load_bytes_with_vault_key(&key, &bytes[..len]).unwrap_err();
+            let expected = if len < ciphertext_start {
+                matches!(err.downcast_ref::<VaultError>(), Some(VaultError::Corrupted(_)))
+            } else {
+                matches!(err.downcast_ref::<VaultError>(), Some(VaultError::IntegrityFailed))
+            };
+            assert!(expected, "cut at {}: {}", len, err);
+        }
+        assert!(VaultManager::load_bytes_with_vault_key(&key, &bytes).is_ok());
+    }
+
+    #[test]
+    fn test_load_never_panics_on_mangled_files() {
+        let (bytes, key) = light_vault_file();
+        let mut rng = StdRng::seed_from_u64(4198);
+        for round in 0..2000 {
+            let mut input = if rng.gen_bool(0.2) {
+                // Garbage, half of it behind a plausible header start
+                let mut garbage = Vec::new();
+                if rng.gen_bool(0.5) {
+                    garbage.extend_from_slice(VAULT_MAGIC);
+                    garbage.push(rng.gen_range(0..6));
+                }
+                garbage.extend((0..rng.gen_range(0..128)).map(|_| rng.gen::<u8>()));
+                garbage
+            } else {
+                bytes.clone()
+            };
+            for _ in 0..rng.gen_range(1..4) {
+                if input.is_empty() {
+                    break;
+                }
+                let at = rng.gen_range(0..input.len());
+                match rng.gen_range(0..4) {
+                    0 => input[at] ^= rng.gen_range(1..=u8::MAX),
+                    1 => input.truncate(at),
+                    2 => {
+                        // Lengths and costs are u32s: try the edge values
+                        let value = [0, 1, 64, 65, u32::MAX, 0x7FFF_FFFF, rng.gen()][rng.gen_range(0..7)];
+                        let end = (at + 4).min(input.len());
+                        input[at..end].copy_from_slice(&value.to_le_bytes()[..end - at]);
+                    }
+                    _ => input.extend((0..rng.gen_range(1..64)).map(|_| rng.gen::<u8>())),
+                }
+            }
+
+            if let Err(err) = VaultManager::load_bytes_with_vault_key(&key, &input) {
+                assert!(is_typed(&*err), "round {}: untyped error: {}", round, err);
+            }
+        }
+    }
+
+    #[test]
+    fn test_list_vaults_checks_magic_and_skips_unreadable_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let header = VaultHeader::new(22, KdfParams::default(), VaultId::generate(), CipherKind::Aes256Gcm).to_bytes();
+        std::fs::write(dir.path().join("work.dat"), &header).unwrap();
+        std::fs::write(dir.path().join("savegame.dat"), b"GAMEDATA and more bytes").unwrap();
+        std::fs::write(dir.path().join("tiny.dat"), b"PM").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), &header).unwrap();
+        std::fs::create_dir(dir.path().join("folder.dat")).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.path().join("gone"), dir.path().join("broken.dat")).unwrap();
+
+        let missing = dir.path().join("no-such-dir");
+        let scan = VaultManager::list_vaults(&[dir.path().to_path_buf(), missing]);
+
+        assert_eq!(scan.vaults, vec![VaultFileInfo {
+            path: dir.path().join("work.dat").to_string_lossy().into_owned(),
+            version: VAULT_FORMAT_VERSION,
+            size: V5_HEADER_LEN as u64,
+            stats: None,
+        }]);
+        #[cfg(unix)]
+        {
+            assert_eq!(scan.warnings.len(), 1);
+            assert!(scan.warnings[0].contains("broken.dat"), "{:?}", scan.warnings);
+        }
+    }
+
+    #[test]
+    fn test_stats_count_active_and_weak_entries_only() {
+        let mut vault = Vault::new();
+        vault.add_entry("github".to_string(), crate::model::Entry::new("octo".to_string(), "Xk9#mQ2$vL7!pR4@".to_string(), None));
+        vault.add_entry("forum".to_string(), crate::model::Entry::new("ann".to_string(), "abc".to_string(), None));
+        vault.add_entry("old".to_string(), crate::model::Entry::new("ann".to_string(), "123".to_string(), None));
+        assert!(vault.trash_entry("old", chrono::Utc::now()));
+
+        let stats = VaultStats::of(&vault);
+        assert_eq!((stats.entries, stats.weak), (2, 1));
+
+        // Only counts and a timestamp reach the plaintext file
+        let json = serde_json::to_string(&stats).unwrap();
+        for secret in ["github", "forum", "octo", "ann"] {
+            assert!(!json.contains(secret), "{}", json);
+        }
+    }
+
+    #[test]
+    fn test_pending_temp_files_are_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.dat.tmp");
+        let path = path.to_str().unwrap();
+
+        let temp = PendingTempFile::new(path.to_string());
+        std::fs::write(path, b"half a vault").unwrap();
+        assert!(VaultManager::remove_pending_temp_files() >= 1);
+        assert!(!Path::new(path).exists());
+        drop(temp);
+        assert!(!pending_temp_files().iter().any(|pending| pending == path));
+
+        // A completed write leaves neither the temp file nor a registration
+        let vault_path = dir.path().join("vault.dat");
+        let vault_path = vault_path.to_str().unwrap();
+        VaultManager::atomic_write(vault_path, b"data").unwrap();
+        assert!(!Path::new(&format!("{}.tmp", vault_path)).exists());
+        assert!(!pending_temp_files().iter().any(|pending| pending.starts_with(vault_path)));
+    }
+
+    #[test]
+    fn test_sync_service_from_path() {
+        assert_eq!(sync_service(Path::new("/home/ann/Dropbox/keys/vault.dat")), Some("Dropbox"));
+        assert_eq!(sync_service(Path::new("/mnt/c/Users/ann/OneDrive - Contoso/vault.dat")), Some("OneDrive"));
+        assert_eq!(sync_service(Path::new("/Users/ann/Library/CloudStorage/GoogleDrive-ann@example.com/My Drive/vault.dat")), Some("Google Drive"));
+        assert_eq!(sync_service(Path::new("/Users/ann/Library/Mobile Documents/com~apple~CloudDocs/vault.dat")), Some("iCloud Drive"));
+        assert_eq!(sync_service(Path::new("/home/ann/dropbox-notes/vault.dat")), None);
+        assert_eq!(sync_service(Path::new("/home/ann/.local/share/passman/vault.dat")), None);
+    }
+
+    #[test]
+    fn test_sync_folder_risk_finds_conflicted_copies() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.dat");
+        std::fs::write(&path, b"PMAN").unwrap();
+        std::fs::write(dir.path().join("other.dat"), b"PMAN").unwrap();
+        std::fs::write(dir.path().join("vault.dat.bak"), b"PMAN").unwrap();
+        assert_eq!(VaultManager::sync_folder_risk(path.to_str()), None);
+
+        std::fs::write(dir.path().join("vault (Ann's conflicted copy 2024-05-01).dat"), b"PMAN").unwrap();
+        std::fs::write(dir.path().join("vault.sync-conflict-20240501-101010-ABCDEFG.dat"), b"PMAN").unwrap();
+        let risk = VaultManager::sync_folder_risk(path.to_str()).unwrap();
+        assert_eq!(risk.service, None);
+        assert_eq!(risk.conflicted_copies, vec![
+            "vault (Ann's conflicted copy 2024-05-01).dat".to_string(),
+            "vault.sync-conflict-20240501-101010-ABCDEFG.dat".to_string(),
+        ]);
+        assert!(risk.message().contains("2 conflicted copies"));
+    }
+
+    #[test]
+    fn test_conflicted_copy_is_merged_and_moved_aside() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.dat");
+        let path_str = path.to_str().unwrap();
+        let (bytes, key) = light_vault_file();
+        fs::write(&path, &bytes).unwrap();
+
+        // Another device added an entry and rotated a password
+        let mut other = VaultManager::load_bytes_with_vault_key(&key, &bytes).unwrap();
+        let mut github = other.get_entry("github").unwrap().clone();
+        github.password = "rotated".to_string().into();
+        github.modified_at += chrono::Duration::seconds(5);
+        other.add_entry("github".to_string(), github);
+        other.add_entry("mail".to_string(), crate::model::Entry::new("ann".to_string(), "pw".to_string(), None));
+        let copy = dir.path().join("vault (Ann's conflicted copy 2024-06-01).dat");
+        fs::write(&copy, VaultManager::encrypt_vault(&other, &key).unwrap()).unwrap();
+        // Named like a copy, but not a vault file
+        fs::write(dir.path().join("vault (conflicted copy 2).dat"), b"not a vault").unwrap();
+
+        let copies = VaultManager::conflicted_copies(Some(path_str));
+        assert_eq!(copies.len(), 1);
+        assert!(copies[0].ends_with("vault (Ann's conflicted copy 2024-06-01).dat"));
+
+        let mut vault = VaultManager::load_with_vault_key(&key, Some(path_str)).unwrap();
+        let copy_vault = VaultManager::load_with_vault_key(&key, Some(copies[0].as_str())).unwrap();
+        let resolution = VaultManager::resolve_conflicted_copy(&mut vault, &key, &copies[0], &copy_vault, Some(path_str)).unwrap();
+        assert_eq!(fs::read(&resolution.backup).unwrap(), bytes);
+        assert_eq!(resolution.archived, format!("{}.resolved", copies[0]));
+        assert!(!Path::new(&copies[0]).exists() && Path::new(&resolution.archived).exists());
+
+        let merged = VaultManager::load_with_vault_key(&key, Some(path_str)).unwrap();
+        assert_eq!(merged.get_entry("github").unwrap().password_str().reveal(), "rotated");
+        assert!(merged.get_entry("mail").is_some());
+        assert!(VaultManager::conflicted_copies(Some(path_str)).is_empty());
+        // The copy that isn't a vault is still named in the sync warning
+        assert_eq!(VaultManager::sync_folder_risk(Some(path_str)).unwrap().conflicted_copies, vec!["vault (conflicted copy 2).dat".to_string()]);
+    }
+
+    #[test]
+    fn test_load_from_bytes_matches_file_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bytes.dat");
+        let path = path.to_str().unwrap();
+        let password = Zeroizing::new("correct horse".to_string());
+        
+        let mut vault = Vault::new();
+        vault.add_entry("github".to_string(), crate::model::Entry::new("octo".to_string(), "pw".to_string(), None));
+        VaultManager::save(&vault, &password, Some(path)).unwrap();
+        
+        let bytes = fs::read(path).unwrap();
+        let loaded = VaultManager::load_from_bytes(&password, &bytes).unwrap();
+        assert_eq!(loaded.get_entry("github").unwrap().username, "octo");
+        
+        let wrong = Zeroizing::new("wrong".to_string());
+        assert!(VaultManager::load_from_bytes(&wrong, &bytes).is_err());
+    }
+
+    #[test]
+    fn test_save_with_key_reuses_salt_and_decrypts_with_password() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keyed.dat");
+        let path = path.to_str().unwrap();
+        let password = Zeroizing::new("correct horse".to_string());
+        
+        VaultManager::init(&password, Some(path)).unwrap();
+        let (mut vault, key) = VaultManager::load_with_key(&password, Some(path)).unwrap();
+        let (salt, kdf) = VaultManager::key_params(Some(path)).unwrap();
+        assert_eq!((key.salt().as_str(), key.kdf()), (salt.as_str(), kdf));
+        
+        vault.add_entry("github".to_string(), crate::model::Entry::new("octo".to_string(), "pw".to_string(), None));
+        VaultManager::save_with_key(&vault, &key, Some(path)).unwrap();
+        
+        assert_eq!(VaultManager::key_params(Some(path)).unwrap().0.as_str(), salt.as_str());
+        let reloaded = VaultManager::load(&password, Some(path)).unwrap();
+        assert_eq!(reloaded.get_entry("github").unwrap().username, "octo");
+        assert!(VaultManager::load(&Zeroizing::new("wrong".to_string()), Some(path)).is_err());
+        assert!(format!("{:?}", key).contains("[REDACTED]"));
+    }
+
+    #[test]
+    fn test_saves_and_key_lookups_skip_reading_the_body() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("large.dat");
+        let path = path.to_str().unwrap();
+        let password = Zeroizing::new("correct horse".to_string());
+
+        let key = VaultManager::init(&password, Some(path)).unwrap();
+        let mut vault = Vault::new();
+        let mut entry = crate::model::Entry::new("octo".to_string(), "pw".to_string(), None);
+        entry.note = Some("x".repeat(256 * 1024));
+        vault.add_entry("github".to_string(), entry);
+        let saved = VaultManager::save_and_fingerprint(&vault, &key, Some(path)).unwrap();
+        let read_back = VaultManager::fingerprint(Some(path)).unwrap();
+        assert_eq!((saved.len, saved.hash), (read_back.len, read_back.hash));
+
+        // Reading past the salt would hit the guard
+        struct BodyGuard;
+        impl Read for BodyGuard {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                panic!("read past the key section");
+            }
+        }
+        let bytes = fs::read(path).unwrap();
+        let prefix = &bytes[..MAX_KEY_SECTION_LEN as usize];
+        let (salt, kdf) = VaultManager::key_params_from(prefix.chain(BodyGuard)).unwrap();
+        assert_eq!((salt.as_str(), kdf), (key.salt().as_str(), key.kdf()));
+
+        // Older formats fit in the same prefix
+        for (name, bytes, _, canonical) in VAULT_SALT_FIXTURES {
+            let (salt, _) = VaultManager::key_params_from(*bytes).unwrap_or_else(|e| panic!("{}: {}", name, e));
+            assert_eq!(salt.as_str(), *canonical, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_vault_key_reloads_until_rekeyed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rekeyed.dat");
+        let path = path.to_str().unwrap();
+        let password = Zeroizing::new("correct horse".to_string());
+        let new_password = Zeroizing::new("battery staple".to_string());
+
+        let key = VaultManager::init(&password, Some(path)).unwrap();
+        assert!(key.verify_password(&password));
+        assert!(!key.verify_password("wrong"));
+        assert!(VaultManager::load_with_vault_key(&key, Some(path)).unwrap().is_empty());
+        let backup = VaultManager::create_backup_with_key(&key, Some(path)).unwrap();
+        assert_eq!(VaultManager::backup_infos(Some(path))[0].verified, Some(true));
+
+        let new_key = VaultManager::change_password(&password, &new_password, Some(path)).unwrap();
+        assert!(VaultManager::load_with_vault_key(&key, Some(path)).is_err());
+        assert!(VaultManager::load_with_vault_key(&new_key, Some(path)).is_ok());
+        assert!(new_key.verify_password(&new_password) && !new_key.verify_password(&password));
+        // Older backups still open with the key that wrote them
+        assert!(VaultManager::load_bytes_with_vault_key(&key, &fs::read(backup).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_fingerprint_survives_rekeying_and_pins_on_unlock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pinned.dat");
+        let path = path.to_str().unwrap();
+        let password = Zeroizing::new("correct horse".to_string());
+        let new_password = Zeroizing::new("battery staple".to_string());
+
+        let key = VaultManager::init(&password, Some(path)).unwrap();
+        let fingerprint = VaultManager::identity(Some(path)).unwrap().fingerprint;
+        assert_eq!(fingerprint, key.vault_id().fingerprint());
+        assert_eq!(fingerprint.len(), 24);
+
+        VaultManager::load_with_key(&password, Some(path)).unwrap();
+        let identity = VaultManager::identity(Some(path)).unwrap();
+        assert_eq!(identity.pinned.as_deref(), Some(fingerprint.as_str()));
+        assert!(!identity.is_substituted() && identity.warning().is_none());
+
+        VaultManager::change_password(&password, &new_password, Some(path)).unwrap();
+        assert_eq!(VaultManager::identity(Some(path)).unwrap().fingerprint, fingerprint);
+
+        // Another vault moved into its place
+        let other = dir.path().join("other.dat");
+        VaultManager::init(&password, other.to_str()).unwrap();
+        fs::rename(&other, path).unwrap();
+        let identity = VaultManager::identity(Some(path)).unwrap();
+        assert!(identity.is_substituted());
+        assert!(identity.warning().unwrap().contains("not the one previously opened"));
+
+        VaultManager::delete(Some(path), false).unwrap();
+        assert!(!VaultManager::read_fingerprints().contains_key(&VaultManager::timing_key(path)));
+    }
+
+    #[test]
+    fn test_v3_file_keeps_fingerprint_when_upgraded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("old.dat");
+        let path = path.to_str().unwrap();
+        let password = Zeroizing::new("correct horse".to_string());
+
+        VaultManager::init_with_cipher(&password, CipherKind::Aes256Gcm, Some(path)).unwrap();
+        let bytes = fs::read(path).unwrap();
+        let mut v3 = bytes[..V3_HEADER_LEN].to_vec();
+        v3[4] = 3;
+        v3.extend_from_slice(&bytes[V5_HEADER_LEN..]);
+        fs::write(path, &v3).unwrap();
+
+        let (salt, _) = VaultManager::key_params(Some(path)).unwrap();
+        let fingerprint = VaultManager::identity(Some(path)).unwrap().fingerprint;
+        assert_eq!(fingerprint, VaultId::from_salt(&salt).fingerprint());
+
+        let (vault, key) = VaultManager::load_with_key(&password, Some(path)).unwrap();
+        VaultManager::save_with_key(&vault, &key, Some(path)).unwrap();
+        assert_eq!(VaultManager::read_format_version(Path::new(path)).unwrap(), Some(VAULT_FORMAT_VERSION));
+        assert_eq!(VaultManager::identity(Some(path)).unwrap().fingerprint, fingerprint);
+    }
+
+    #[test]
+    fn test_vault_roundtrips_with_every_cipher() {
+        let (_, key) = light_vault_file();
+        let mut vault = Vault::new();
+        vault.add_entry("github".to_string(), crate::model::Entry::new("octo".to_string(), "pw".to_string(), None));
+
+        for cipher in CipherKind::ALL {
+            let key = key.clone().with_cipher(cipher);
+            let bytes = VaultManager::encrypt_vault(&vault, &key).unwrap();
+            assert_eq!(bytes[V4_HEADER_LEN], cipher.id());
+
+            let (section, body) = VaultManager::parse_file(&bytes).unwrap();
+            assert_eq!(section.cipher, cipher);
+            assert_eq!(body.nonce.len(), cipher.cipher().nonce_len());
+
+            let (loaded, loaded_key) = VaultManager::decrypt_vault(&bytes, "<memory>", |salt, kdf| key.matching(salt, kdf)).unwrap();
+            assert_eq!(loaded.get_entry("github").unwrap().password_str().reveal(), "pw", "{}", cipher);
+            assert_eq!(loaded_key.cipher(), cipher);
+
+            // The header decides the cipher, not the key it is opened with
+            let opened = VaultManager::decrypt_vault(&bytes, "<memory>", |salt, kdf| {
+                key.clone().with_cipher(CipherKind::Aes256Gcm).matching(salt, kdf)
+            });
+            assert!(opened.is_ok(), "{}", cipher);
+        }
+    }
+
+    #[test]
+    fn test_v2_aes_vault_still_loads_byte_identically() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("v2.dat");
+        let path = path.to_str().unwrap();
+        fs::write(path, V2_AES_FIXTURE).unwrap();
+        let password = Zeroizing::new("correct horse".to_string());
+
+        assert_eq!(VaultManager::format_version(Some(path)).unwrap(), Some(2));
+        assert_eq!(VaultManager::cipher(Some(path)).unwrap(), CipherKind::Aes256Gcm);
+        assert!(VaultManager::verify_integrity(&password, Some(path)).unwrap());
+        let (vault, key) = VaultManager::load_with_key(&password, Some(path)).unwrap();
+        assert_eq!(vault.get_entry("github").unwrap().username, "octo");
+        assert_eq!((key.cipher(), key.kdf()), (CipherKind::Aes256Gcm, KdfParams::default()));
+
+        // Opening a v2 file leaves it as it was, and a cached key reads it too
+        assert_eq!(fs::read(path).unwrap(), V2_AES_FIXTURE);
+        assert!(VaultManager::load_bytes_with_vault_key(&key, V2_AES_FIXTURE).is_ok());
+
+        // The next save upgrades it to the current format, still AES
+        VaultManager::save_with_key(&vault, &key, Some(path)).unwrap();
+        assert_eq!(VaultManager::format_version(Some(path)).unwrap(), Some(VAULT_FORMAT_VERSION));
+        assert_eq!(VaultManager::cipher(Some(path)).unwrap(), CipherKind::Aes256Gcm);
+        assert_eq!(VaultManager::load(&password, Some(path)).unwrap().get_entry("github").unwrap().username, "octo");
+    }
+
+    #[test]
+    fn test_rekey_switches_cipher_and_keeps_password_and_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rekey.dat");
+        let path = path.to_str().unwrap();
+        let password = Zeroizing::new("correct horse".to_string());
+        let key = VaultManager::init_with_cipher(&password, CipherKind::Aes256Gcm, Some(path)).unwrap();
+        let mut vault = Vault::new();
+        vault.add_entry("github".to_string(), crate::model::Entry::new("octo".to_string(), "pw".to_string(), None));
+        VaultManager::save_with_key(&vault, &key, Some(path)).unwrap();
+
+        let rekeyed = VaultManager::rekey(&password, Some(CipherKind::XChaCha20Poly1305), Some(path)).unwrap();
+        assert_eq!(VaultManager::cipher(Some(path)).unwrap(), CipherKind::XChaCha20Poly1305);
+        assert_ne!(rekeyed.salt().as_str(), key.salt().as_str());
+        assert_eq!(rekeyed.vault_id(), key.vault_id());
+        assert!(VaultManager::load_with_vault_key(&key, Some(path)).is_err());
+        let (loaded, loaded_key) = VaultManager::load_with_key(&password, Some(path)).unwrap();
+        assert_eq!(loaded.get_entry("github").unwrap().password_str().reveal(), "pw");
+        assert_eq!(loaded_key.cipher(), CipherKind::XChaCha20Poly1305);
+        assert_eq!(VaultManager::list_backups(Some(path)).len(), 1);
+
+        // Without a cipher, and on a password change, the cipher stays
+        VaultManager::rekey(&password, None, Some(path)).unwrap();
+        assert_eq!(VaultManager::cipher(Some(path)).unwrap(), CipherKind::XChaCha20Poly1305);
+        let new_password = Zeroizing::new("battery staple".to_string());
+        let changed = VaultManager::change_password(&password, &new_password, Some(path)).unwrap();
+        assert_eq!(changed.cipher(), CipherKind::XChaCha20Poly1305);
+        assert_eq!(VaultManager::load(&new_password, Some(path)).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_delete_removes_vault_and_optionally_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("doomed.dat");
+        let path = path.to_str().unwrap();
+        let password = Zeroizing::new("correct horse".to_string());
+        
+        VaultManager::save(&Vault::new(), &password, Some(path)).unwrap();
+        VaultManager::save(&Vault::new(), &password, Some(path)).unwrap();
+        let backup = VaultManager::create_backup(&password, Some(path)).unwrap();
+        
+        let removed = VaultManager::delete(Some(path), false).unwrap();
+        assert_eq!(removed, vec![path.to_string(), format!("{}.bak", path)]);
+        assert!(Path::new(&backup).exists());
+        assert!(VaultManager::delete(Some(path), false).is_err());
+        
+        VaultManager::save(&Vault::new(), &password, Some(path)).unwrap();
+        let removed = VaultManager::delete(Some(path), true).unwrap();
+        assert!(removed.contains(&backup));
+        assert!(!Path::new(&backup).exists());
+        assert!(!Path::new(&format!("{}{}", path, BACKUP_MANIFEST_SUFFIX)).exists());
+    }
+    
+    #[test]
+    fn test_backups_are_verified_and_restore_checks_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backed.dat");
+        let path = path.to_str().unwrap();
+        let password = Zeroizing::new("correct horse".to_string());
+        
+        let mut vault = Vault::new();
+        vault.add_entry("github".to_string(), crate::model::Entry::new("octo".to_string(), "pw".to_string(), None));
+        VaultManager::save(&vault, &password, Some(path)).unwrap();
+        
+        let good = VaultManager::create_backup(&password, Some(path)).unwrap();
+        let infos = VaultManager::backup_infos(Some(path));
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].verified, Some(true));
+        assert_eq!(infos[0].entries, Some(1));
+        assert!(infos[0].created.is_some());
+        
+        // A truncated copy fails and is refused unless forced
+        let truncated = format!("{}.bak.20000101_000000", path);
+        let bytes = fs::read(&good).unwrap();
+        fs::write(&truncated, &bytes[..bytes.len() / 2]).unwrap();
+        assert!(VaultManager::verify_backup(&password, &truncated, Some(path)).is_err());
+        assert!(VaultManager::restore_backup(&password, &truncated, Some(path), false).is_err());
+        assert!(VaultManager::load(&password, Some(path)).is_ok());
+        
+        // Editing a verified backup afterwards makes it unverified again
+        fs::write(&good, b"tampered").unwrap();
+        let infos = VaultManager::backup_infos(Some(path));
+        assert!(infos.iter().all(|info| info.verified == Some(false)));
+        
+        // A good backup restores, and the replaced vault is kept as a new backup
+        VaultManager::save(&Vault::new(), &password, Some(path)).unwrap();
+        let fresh = VaultManager::create_backup(&password, Some(path)).unwrap();
+        VaultManager::save(&vault, &password, Some(path)).unwrap();
+        let safety = VaultManager::restore_backup(&password, &fresh, Some(path), false).unwrap();
+        assert_ne!(safety, fresh);
+        assert!(VaultManager::load(&password, Some(path)).unwrap().get_entry("github").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_vault_backup_and_temp_files_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("private.dat");
+        let path = path.to_str().unwrap();
+        let mode = |path: &str| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        let password = Zeroizing::new("correct horse".to_string());
+
+        let key = VaultManager::init(&password, Some(path)).unwrap();
+        assert_eq!(mode(path), 0o600);
+        assert_eq!(VaultManager::loose_permissions(Some(path)), None);
+
+        // A file left world-readable, e.g. by an older version under umask 022,
+        // is tightened by the next save and its backups are private too
+        fs::set_permissions(path, fs::Permissions::from_mode(0o644)).unwrap();
+        let backup = VaultManager::create_backup_with_key(&key, Some(path)).unwrap();
+        assert_eq!(mode(&backup), 0o600);
+        VaultManager::save_with_key(&Vault::new(), &key, Some(path)).unwrap();
+        assert_eq!(mode(path), 0o600);
+        assert_eq!(mode(&format!("{}.bak", path)), 0o600);
+        assert!(VaultManager::loose_backup_permissions(Some(path)).is_empty());
+        assert!(!Path::new(&format!("{}.tmp", path)).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_readable_vault_warns_or_is_refused() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("it's shared.dat");
+        let path = path.to_str().unwrap();
+        let password = Zeroizing::new("correct horse".to_string());
+        VaultManager::init(&password, Some(path)).unwrap();
+        fs::set_permissions(path, fs::Permissions::from_mode(0o640)).unwrap();
+
+        let loose = VaultManager::loose_permissions(Some(path)).unwrap();
+        assert_eq!(loose.mode, 0o640);
+        assert!(loose.message().contains("mode 640"), "{}", loose.message());
+        assert_eq!(loose.remedy(), format!("chmod 600 '{}'", path.replace('\'', "'\\''")));
+
+        assert!(VaultManager::check_permissions(path, false).is_ok());
+        let err = VaultManager::check_permissions(path, true).unwrap_err();
+        assert!(matches!(err, VaultError::LoosePermissions(_)));
+        assert!(err.to_string().contains("chmod 600"), "{}", err);
+
+        VaultManager::restrict_permissions(path).unwrap();
+        assert_eq!(VaultManager::loose_permissions(Some(path)), None);
+        assert!(VaultManager::check_permissions(path, true).is_ok());
+    }
+}
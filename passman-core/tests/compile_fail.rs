@@ -0,0 +1,11 @@
+//! Passwords only leave an entry through an explicit `.reveal()`: the view
+//! `Entry::password_str` returns can't be formatted or sent to a thread, and
+//! `{:?}` on an entry keeps the password out.
+
+#[test]
+fn secret_views_stay_guarded() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/entry_debug_redacted.rs");
+    t.compile_fail("tests/ui/secret_view_display.rs");
+    t.compile_fail("tests/ui/secret_view_send.rs");
+}
@@ -0,0 +1,8 @@
+use passman_core::model::Entry;
+
+fn main() {
+    let entry = Entry::new("octocat".to_string(), "hunter2".to_string(), None);
+    let debug = format!("{:?} {:?}", entry, entry.password_str());
+    assert!(!debug.contains("hunter2"), "{}", debug);
+    assert_eq!(entry.password_str().reveal(), "hunter2");
+}
@@ -0,0 +1,6 @@
+use passman_core::model::Entry;
+
+fn main() {
+    let entry = Entry::new("octocat".to_string(), "hunter2".to_string(), None);
+    println!("{}", entry.password_str());
+}
@@ -0,0 +1,8 @@
+use passman_core::model::Entry;
+
+fn send_to_thread<T: Send>(_: T) {}
+
+fn main() {
+    let entry = Entry::new("octocat".to_string(), "hunter2".to_string(), None);
+    send_to_thread(entry.password_str());
+}
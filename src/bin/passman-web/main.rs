@@ -0,0 +1,49 @@
+//! Passman Web Viewer
+//!
+//! Read-only vault viewer that compiles to wasm32-unknown-unknown and runs
+//! as a static page (see web/index.html). The vault is decrypted entirely
+//! in the browser; nothing is stored or sent anywhere. Built natively, the
+//! same viewer opens in a desktop window, which is handy for development.
+
+mod viewer;
+
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    use wasm_bindgen::JsCast;
+
+    wasm_bindgen_futures::spawn_local(async {
+        let canvas = web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.get_element_by_id("passman_canvas"))
+            .and_then(|element| element.dyn_into::<web_sys::HtmlCanvasElement>().ok())
+            .expect("index.html must contain <canvas id=\"passman_canvas\">");
+
+        let result = eframe::WebRunner::new()
+            .start(
+                canvas,
+                eframe::WebOptions::default(),
+                Box::new(|_cc| Ok(Box::new(viewer::Viewer::default()))),
+            )
+            .await;
+
+        if let Err(e) = result {
+            web_sys::console::error_1(&e);
+        }
+    });
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> Result<(), eframe::Error> {
+    let options = eframe::NativeOptions {
+        viewport: eframe::egui::ViewportBuilder::default()
+            .with_inner_size([720.0, 560.0])
+            .with_title("Passman Viewer"),
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "Passman Viewer",
+        options,
+        Box::new(|_cc| Ok(Box::new(viewer::Viewer::default()))),
+    )
+}
@@ -0,0 +1,234 @@
+//! Viewer UI
+//!
+//! Pick a vault file, unlock it with the master password, then search and
+//! copy entries. Read-only: there is no editing and no persistence.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use eframe::egui;
+use zeroize::Zeroizing;
+
+use passman_core::search::{SearchIndex, SearchScope};
+use passman_core::utils::{password_mask, DEFAULT_MASK_LENGTH};
+use passman_core::{Entry, Vault, VaultManager};
+
+/// File name and contents of the picked vault
+type PickedFile = (String, Vec<u8>);
+
+#[derive(Default)]
+pub struct Viewer {
+    /// Filled by the file picker, which completes asynchronously on the web
+    picked: Arc<Mutex<Option<PickedFile>>>,
+    file: Option<PickedFile>,
+    password: Zeroizing<String>,
+    /// Pass on which Unlock was pressed; the blocking key derivation runs
+    /// on a later pass so "Decrypting…" is painted first
+    unlock_requested: Option<u64>,
+    vault: Option<Vault>,
+    index: SearchIndex,
+    query: String,
+    revealed: HashSet<String>,
+    error: Option<String>,
+}
+
+impl Viewer {
+    fn unlock(&mut self) {
+        let Some((_, bytes)) = &self.file else {
+            return;
+        };
+        match VaultManager::load_from_bytes(&self.password, bytes) {
+            Ok(vault) => {
+                self.index = SearchIndex::from_vault(&vault);
+                self.vault = Some(vault);
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+        *self.password = String::new();
+    }
+
+    /// Forget the decrypted vault and the picked file
+    fn lock(&mut self) {
+        self.vault = None;
+        self.index = SearchIndex::new();
+        self.file = None;
+        self.query.clear();
+        self.revealed.clear();
+    }
+
+    fn show_unlock(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Passman Viewer");
+        ui.label("Open a vault file. It is decrypted in this page and never uploaded.");
+        ui.add_space(12.0);
+
+        ui.horizontal(|ui| {
+            if ui.button("📂 Choose vault…").clicked() {
+                pick_file(self.picked.clone(), ui.ctx().clone());
+            }
+            if let Some((name, bytes)) = &self.file {
+                ui.label(format!("{} ({} bytes)", name, bytes.len()));
+            }
+        });
+
+        if self.file.is_none() {
+            return;
+        }
+
+        ui.add_space(8.0);
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut *self.password)
+                .password(true)
+                .hint_text("Master password"),
+        );
+        let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+        if self.unlock_requested.is_some() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Decrypting…");
+            });
+        } else if ui.button("Unlock").clicked() || submitted {
+            self.unlock_requested = Some(ui.ctx().cumulative_pass_nr());
+            ui.ctx().request_repaint();
+        }
+    }
+
+    fn show_entries(&mut self, ui: &mut egui::Ui) {
+        let Some(vault) = &self.vault else {
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.query)
+                    .hint_text("🔍 Search")
+                    .desired_width(260.0),
+            );
+            ui.label(format!("{} entries", vault.len()));
+        });
+        ui.add_space(8.0);
+
+        let query = self.index.prepare(&self.query, true, SearchScope::ActiveOnly);
+        let mut entries: Vec<(&str, &Entry)> = vault.iter()
+            .filter(|(id, _)| self.index.matches(id, &query))
+            .collect();
+        entries.sort_by_key(|(id, _)| *id);
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (id, entry) in entries {
+                let revealed = self.revealed.contains(id);
+
+                ui.group(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.strong(id);
+                        if let Some(url) = &entry.url {
+                            ui.hyperlink_to(url.as_str(), url.as_str());
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(format!("👤 {}", entry.username));
+                        if ui.small_button("Copy").clicked() {
+                            ui.ctx().copy_text(entry.username.clone());
+                        }
+                    });
+                    if entry.is_sealed() {
+                        ui.label(egui::RichText::new("🛡 High security: the password needs its PIN, open it in passman").weak());
+                    } else {
+                        ui.horizontal(|ui| {
+                            let shown = if revealed {
+                                entry.password_str().reveal().to_string()
+                            } else {
+                                password_mask('•', DEFAULT_MASK_LENGTH)
+                            };
+                            ui.monospace(format!("🔑 {}", shown));
+                            if ui.small_button(if revealed { "Hide" } else { "Show" }).clicked() {
+                                if revealed {
+                                    self.revealed.remove(id);
+                                } else {
+                                    self.revealed.insert(id.to_string());
+                                }
+                            }
+                            if ui.small_button("Copy").clicked() {
+                                ui.ctx().copy_text(entry.password_str().reveal().to_string());
+                            }
+                        });
+                    }
+                    if let Some(note) = entry.note.as_deref().filter(|note| !note.is_empty()) {
+                        ui.label(egui::RichText::new(note).weak());
+                    }
+                });
+            }
+        });
+    }
+}
+
+impl eframe::App for Viewer {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let picked = self.picked.lock().ok().and_then(|mut slot| slot.take());
+        if let Some(file) = picked {
+            self.lock();
+            self.file = Some(file);
+            self.error = None;
+        }
+
+        if self.unlock_requested.is_some_and(|pass| ctx.cumulative_pass_nr() > pass + 1) {
+            self.unlock_requested = None;
+            self.unlock();
+        } else if self.unlock_requested.is_some() {
+            ctx.request_repaint();
+        }
+
+        egui::TopBottomPanel::top("viewer_header").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("🔒 Passman").strong());
+                ui.label(egui::RichText::new("read-only").weak());
+                if self.vault.is_some() {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Lock").clicked() {
+                            self.lock();
+                        }
+                    });
+                }
+            });
+        });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if let Some(error) = &self.error {
+                ui.colored_label(egui::Color32::from_rgb(239, 68, 68), error);
+                ui.add_space(8.0);
+            }
+            if self.vault.is_some() {
+                self.show_entries(ui);
+            } else {
+                self.show_unlock(ui);
+            }
+        });
+    }
+}
+
+/// Let the user choose a vault file through the browser's file input
+#[cfg(target_arch = "wasm32")]
+fn pick_file(slot: Arc<Mutex<Option<PickedFile>>>, ctx: egui::Context) {
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Some(handle) = rfd::AsyncFileDialog::new().pick_file().await {
+            let bytes = handle.read().await;
+            if let Ok(mut slot) = slot.lock() {
+                *slot = Some((handle.file_name(), bytes));
+            }
+            ctx.request_repaint();
+        }
+    });
+}
+
+/// Let the user choose a vault file with the native file dialog
+#[cfg(not(target_arch = "wasm32"))]
+fn pick_file(slot: Arc<Mutex<Option<PickedFile>>>, _ctx: egui::Context) {
+    let Some(path) = rfd::FileDialog::new().pick_file() else {
+        return;
+    };
+    let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    if let (Ok(bytes), Ok(mut slot)) = (std::fs::read(&path), slot.lock()) {
+        *slot = Some((name, bytes));
+    }
+}
@@ -1,4 +1,4 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 
 const BANNER: &str = r#"
   _____                                    
@@ -17,11 +17,16 @@ Features AES-256-GCM encryption and Argon2 key derivation.
 #[derive(Parser)]
 #[command(name = "passman", version = "1.0", author = "0m3rexe")]
 #[command(about = BANNER, long_about = BANNER)]
+#[command(after_help = "Run without arguments to open the GUI, or with just --demo to try it on a vault of made-up entries.")]
 pub struct Cli {
     #[command(subcommand)]
-    pub command: Commands,
+    pub command: Option<Commands>,
     
-    /// Specify vault file (default: vault.dat)
+    /// Open the GUI on a vault of made-up entries; nothing is saved
+    #[arg(long, exclusive = true)]
+    pub demo: bool,
+    
+    /// Specify vault file (overrides PASSMAN_VAULT and general.default_vault)
     #[arg(long, global = true)]
     pub vault: Option<String>,
     
@@ -32,6 +37,18 @@ pub struct Cli {
     /// Quiet mode (minimal output)
     #[arg(short, long, global = true)]
     pub quiet: bool,
+    
+    /// Never touch the network (overrides network.offline)
+    #[arg(long, global = true)]
+    pub offline: bool,
+    
+    /// Refuse to unlock a vault file other users can read (overrides security.strict_permissions)
+    #[arg(long, global = true)]
+    pub strict_permissions: bool,
+    
+    /// Read the master password from the first line of this file instead of prompting (PASSMAN_PASSWORD also works)
+    #[arg(long, global = true, value_name = "FILE")]
+    pub password_file: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -41,31 +58,15 @@ pub enum Commands {
         /// Description for the vault
         #[arg(short, long)]
         description: Option<String>,
-    },
-    
-    /// Add new entry (interactive)
-    Add { 
-        id: String,
-        /// Username/email
-        #[arg(short, long)]
-        username: Option<String>,
-        /// Password (if not provided, will be generated or prompted)
-        #[arg(short, long)]
-        password: Option<String>,
-        /// Note/description
-        #[arg(short, long)]
-        note: Option<String>,
-        /// URL associated with this entry
+        /// Cipher to encrypt the vault with (aes-256-gcm, xchacha20);
+        /// defaults to security.cipher
         #[arg(long)]
-        url: Option<String>,
-        /// Generate password automatically
-        #[arg(short, long)]
-        generate: bool,
-        /// Password length for generation
-        #[arg(short, long, default_value = "16")]
-        length: usize,
+        cipher: Option<String>,
     },
     
+    /// Add new entry (interactive unless --username is given)
+    Add(AddArgs),
+    
     /// Print or copy credentials
     Get { 
         id: String,
@@ -75,6 +76,21 @@ pub enum Commands {
         /// Show password in plaintext
         #[arg(short, long)]
         show: bool,
+        /// Exit right after copying instead of waiting to clear the clipboard
+        #[arg(long)]
+        no_wait: bool,
+        /// Print only these fields, one per line (username, password, url, note, tags); repeatable
+        #[arg(short, long, conflicts_with_all = ["copy", "show"])]
+        field: Vec<String>,
+        /// Copy the next item of the entry's copy sequence; run again within a minute for the one after
+        #[arg(long, conflicts_with_all = ["copy", "show", "field"])]
+        copy_next: bool,
+        /// Show the password in groups of four, naming look-alike characters (0 = zero, l = lowercase L)
+        #[arg(long, conflicts_with_all = ["copy", "show", "field", "copy_next"])]
+        spell: bool,
+        /// Show only the entries this one links to and the ones linking to it
+        #[arg(long, conflicts_with_all = ["copy", "show", "field", "copy_next", "spell"])]
+        links: bool,
     },
     
     /// List all saved entries
@@ -82,16 +98,85 @@ pub enum Commands {
         /// Filter entries by tag
         #[arg(short, long)]
         tag: Option<String>,
-        /// Search entries by pattern
-        #[arg(short, long)]
+        /// Search entries by pattern (id, username, URL, tags and notes)
+        #[arg(short, long, conflicts_with = "saved")]
         search: Option<String>,
+        /// Use a saved search from the GUI (ui.saved_searches) by name
+        #[arg(long)]
+        saved: Option<String>,
         /// Show detailed information
         #[arg(short, long)]
         verbose: bool,
+        /// Leave notes out of the search
+        #[arg(long)]
+        no_notes: bool,
+        /// Also list entries in the trash
+        #[arg(long)]
+        include_trash: bool,
+        /// List archived entries instead (same as searching "is:archived")
+        #[arg(long)]
+        archived: bool,
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        output: String,
     },
     
+    /// Open an entry's site, copying its username and then its password
+    Open { id: String },
+    
     /// Edit an existing entry
-    Edit { id: String },
+    Edit {
+        id: String,
+        /// Set the expiry date (YYYY-MM-DD, or 'none' to clear) instead of prompting for it
+        #[arg(long)]
+        expires: Option<String>,
+        /// Replace the copy sequence (username, password, url or <name>=<value>; repeatable, 'none' to clear)
+        #[arg(long)]
+        copy_sequence: Vec<String>,
+    },
+    
+    /// Copy an entry for a similar account; the copy starts without a password
+    Duplicate {
+        id: String,
+        /// Id for the copy (default: "<id> (copy)")
+        new_id: Option<String>,
+        /// Give the copy a freshly generated password
+        #[arg(short, long)]
+        generate: bool,
+    },
+    
+    /// Give an entry a newly generated password
+    Rotate {
+        id: String,
+        /// Generate it the way the current one was (mode, length and character rules)
+        #[arg(long)]
+        same_rules: bool,
+        /// Password length (default: password.default_length)
+        #[arg(short, long, conflicts_with = "same_rules")]
+        length: Option<usize>,
+    },
+    
+    /// Find and replace text in one field (username, url or note) of every entry
+    Replace {
+        /// Field to edit: username, url or note (passwords are refused)
+        #[arg(long)]
+        field: String,
+        /// Text to look for
+        #[arg(long)]
+        find: String,
+        /// Text to put in its place
+        #[arg(long)]
+        replace: String,
+        /// Treat --find as a regular expression ($1 in --replace inserts a group)
+        #[arg(long)]
+        regex: bool,
+        /// Only show what would change
+        #[arg(long)]
+        dry_run: bool,
+        /// Apply without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
     
     /// Remove an entry
     #[command(name = "rm")]
@@ -102,16 +187,69 @@ pub enum Commands {
         force: bool,
     },
     
+    /// Archive an entry: keep it, but hide it from lists, search and health checks
+    Archive {
+        id: String,
+        /// Unarchive it instead
+        #[arg(long)]
+        undo: bool,
+    },
+    
+    /// Link an entry to a related one, such as the email account that recovers it
+    Link {
+        id: String,
+        other_id: String,
+        /// Remove the link instead
+        #[arg(long)]
+        remove: bool,
+    },
+    
+    /// Make an entry high security: its password and note are sealed under a PIN of their own
+    Seal {
+        id: String,
+    },
+    
+    /// Drop high security from an entry, putting its password and note back
+    Unseal {
+        id: String,
+    },
+    
     /// Analyze password strength
     Check { 
         password: Option<String>,
         /// Check all passwords in vault
         #[arg(short, long)]
         all: bool,
+        /// Output format for --all (text, json)
+        #[arg(short, long, default_value = "text", requires = "all")]
+        output: String,
+        /// Also check entries in the trash
+        #[arg(long, requires = "all")]
+        include_trash: bool,
+        /// Add a column with the number of known breaches per password
+        #[arg(long, requires = "all")]
+        breaches: bool,
+        /// Exit non-zero when the overall health score (0-100) is below this
+        #[arg(long, value_name = "SCORE", requires = "all", value_parser = clap::value_parser!(u8).range(0..=100))]
+        fail_below: Option<u8>,
+        /// List entries that expired or expire within this many days (default 14); prints nothing when none do
+        #[arg(long, value_name = "DAYS", num_args = 0..=1, default_missing_value = "14", conflicts_with_all = ["password", "all"])]
+        expiring: Option<i64>,
+    },
+    
+    /// List vault files in the data directory and next to the configured vault
+    Vaults {
+        /// Page of results to show
+        #[arg(long, default_value = "1")]
+        page: usize,
     },
     
-    /// List available vaults
-    Vaults,
+    /// Vault maintenance operations
+    #[command(subcommand)]
+    Vault(VaultCommands),
+    
+    /// Check the vault file, its backups and their permissions without unlocking it
+    Doctor,
     
     /// Generate password
     Generate {
@@ -127,8 +265,37 @@ pub enum Commands {
         /// Generate memorable password
         #[arg(short, long)]
         memorable: bool,
+        /// Generate a password that is easy to read aloud (e.g. "Radovi-Tunema-83!")
+        #[arg(short, long, conflicts_with = "memorable")]
+        pronounceable: bool,
+        /// Number of syllables for --pronounceable (default: password.pronounceable_syllables)
+        #[arg(long, requires = "pronounceable")]
+        syllables: Option<usize>,
+        /// Characters the site forbids (e.g. "<>&")
+        #[arg(long, conflicts_with_all = ["memorable", "pronounceable"])]
+        exclude: Option<String>,
+        /// Start the password with a letter
+        #[arg(long, conflicts_with_all = ["memorable", "pronounceable"])]
+        leading_alpha: bool,
+        /// Maximum length allowed by the site
+        #[arg(long, conflicts_with_all = ["memorable", "pronounceable"])]
+        max: Option<usize>,
+        /// Exit right after copying instead of waiting to clear the clipboard
+        #[arg(long)]
+        no_wait: bool,
     },
     
+    /// Manage entry templates
+    #[command(subcommand)]
+    Template(TemplateCommands),
+    
+    /// Share one entry via an encrypted one-time bundle
+    Share(ShareArgs),
+    
+    /// Mirror the vault into a git-friendly encrypted directory
+    #[command(subcommand)]
+    Sync(SyncCommands),
+    
     /// Import/Export operations
     #[command(subcommand)]
     Transfer(TransferCommands),
@@ -137,13 +304,24 @@ pub enum Commands {
     #[command(subcommand)]
     Config(ConfigCommands),
     
-    /// Create vault backup
-    Backup {
-        /// Output file path (default: vault_backup_<timestamp>.dat)
-        #[arg(short, long)]
-        output: Option<String>,
+    /// Compare the vault with another vault or backup file
+    Diff {
+        /// Vault or backup file to compare against
+        other: String,
+        /// Show changed passwords and TOTP secrets instead of "changed"
+        #[arg(long)]
+        show_secrets: bool,
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        output: String,
     },
     
+    /// List entries changed since a marker, for secret rotation tooling
+    Changes(ChangesArgs),
+    
+    /// Create, list or restore vault backups
+    Backup(BackupArgs),
+    
     /// Analyze password health across vault
     Health {
         /// Show detailed report
@@ -156,6 +334,277 @@ pub enum Commands {
     
     /// Change master password
     ChangePassword,
+    
+    /// Browse and edit the vault in a full-screen terminal UI
+    Tui,
+    
+    /// Serve the vault to local tools over JSON-RPC on a unix socket (a named pipe on Windows)
+    Serve {
+        /// Socket path (default: passman.sock in the runtime directory; \\.\pipe\passman-<user> on Windows)
+        #[arg(long)]
+        socket: Option<String>,
+        /// Require a per-session token, printed at startup, on every request
+        #[arg(long)]
+        token: bool,
+    },
+    
+    /// Talk to a running 'passman serve'
+    Client(ClientArgs),
+    
+    /// Git credential helper (git config credential.helper '!passman git-credential')
+    #[command(name = "git-credential")]
+    GitCredential {
+        #[command(subcommand)]
+        action: GitCredentialCommands,
+        /// Socket of a 'passman serve' to ask before unlocking the vault
+        #[arg(long)]
+        socket: Option<String>,
+    },
+    
+    /// Show the version, optionally checking for a newer release
+    Version {
+        /// Fetch the signed release manifest and compare
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+#[derive(Args)]
+pub struct AddArgs {
+    /// Entry ID; without one, an ID derived from --url (or an email
+    /// --username) is proposed for confirmation
+    pub id: Option<String>,
+    /// Username/email; with this flag nothing else is prompted for
+    /// except a password that is neither given nor generated
+    #[arg(short, long)]
+    pub username: Option<String>,
+    /// Password (if not provided, will be generated or prompted)
+    #[arg(short, long)]
+    pub password: Option<String>,
+    /// Note/description
+    #[arg(short, long)]
+    pub note: Option<String>,
+    /// URL associated with this entry
+    #[arg(long)]
+    pub url: Option<String>,
+    /// Comma-separated tags
+    #[arg(short, long)]
+    pub tags: Option<String>,
+    /// Generate password automatically
+    #[arg(short, long, conflicts_with = "password")]
+    pub generate: bool,
+    /// Password length for generation
+    #[arg(short, long, default_value = "16")]
+    pub length: usize,
+    /// Pre-fill username, tags, URL and note from a template
+    #[arg(long)]
+    pub template: Option<String>,
+    /// Date the credential stops working (YYYY-MM-DD)
+    #[arg(long)]
+    pub expires: Option<String>,
+    /// Item for `get --copy-next` to copy, in order: username, password,
+    /// url or <name>=<value>; repeatable
+    #[arg(long)]
+    pub copy_sequence: Vec<String>,
+    /// Add many entries from newline-delimited JSON on stdin, one
+    /// {"id", "username", "password", "note", "tags"} object per line,
+    /// saving once. The master password must come from --password-file or
+    /// PASSMAN_PASSWORD.
+    #[arg(long, conflicts_with_all = ["id", "username", "password", "note", "url", "tags", "generate", "template", "expires", "copy_sequence"])]
+    pub batch_stdin: bool,
+    /// With --batch-stdin, what to do when an ID is taken: skip, overwrite
+    /// or suffix (add as e.g. "github (copy)") [default: skip]
+    #[arg(long, value_name = "POLICY", requires = "batch_stdin")]
+    pub on_conflict: Option<String>,
+    /// With --batch-stdin, add nothing if any line is malformed
+    #[arg(long, requires = "batch_stdin")]
+    pub strict: bool,
+}
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct ShareArgs {
+    #[command(subcommand)]
+    pub command: Option<ShareCommands>,
+    
+    /// Entry to share
+    pub id: Option<String>,
+    
+    /// Output bundle path (default: <id>.pshare)
+    #[arg(short, long)]
+    pub out: Option<String>,
+    
+    /// Prompt for your own passphrase instead of generating one
+    #[arg(long)]
+    pub passphrase: bool,
+    
+    /// Days until the bundle can no longer be imported
+    #[arg(long, default_value = "7")]
+    pub expires_days: i64,
+}
+
+#[derive(Subcommand)]
+pub enum ShareCommands {
+    /// Import an entry from a share bundle
+    Import {
+        /// Bundle file path
+        file: String,
+    },
+}
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct BackupArgs {
+    #[command(subcommand)]
+    pub command: Option<BackupCommands>,
+    
+    /// Output file path (default: <vault>.bak.<timestamp>)
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum BackupCommands {
+    /// List backups with their size, entry count and verification status
+    List,
+    /// Replace the vault with a backup (the current vault is backed up first)
+    Restore {
+        /// Backup file path
+        file: String,
+        /// Restore even if the backup does not decrypt with the master password
+        #[arg(long)]
+        force: bool,
+        /// Merge the backup in instead, keeping the newer copy of each entry
+        #[arg(long, conflicts_with = "force")]
+        merge: bool,
+    },
+}
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct ChangesArgs {
+    #[command(subcommand)]
+    pub command: Option<ChangesCommands>,
+    
+    /// Where to start: last-export (the last 'changes mark'), YYYY-MM-DD or an RFC 3339 timestamp
+    #[arg(long, default_value = "last-export")]
+    pub since: String,
+    
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    pub output: String,
+    
+    /// Also print the current values of the changed fields, passwords included (asks first)
+    #[arg(long)]
+    pub include_secrets: bool,
+    
+    /// Don't ask before printing secrets
+    #[arg(short, long, requires = "include_secrets")]
+    pub yes: bool,
+}
+
+#[derive(Subcommand)]
+pub enum ChangesCommands {
+    /// Record the vault as it is now; later 'changes --since last-export' lists what changed after
+    Mark,
+}
+
+#[derive(Subcommand)]
+pub enum TemplateCommands {
+    /// Create or replace a template ({id} is replaced by the entry id)
+    Add {
+        /// Template name
+        name: String,
+        /// Username pattern, e.g. "{id}@corp.example"
+        #[arg(short, long)]
+        username: Option<String>,
+        /// Comma-separated tags
+        #[arg(short, long)]
+        tags: Option<String>,
+        /// URL pattern, e.g. "https://{id}.corp.example"
+        #[arg(long)]
+        url: Option<String>,
+        /// Note boilerplate
+        #[arg(short, long)]
+        note: Option<String>,
+        /// Generated password length
+        #[arg(short, long)]
+        length: Option<usize>,
+        /// Characters to exclude from generated passwords
+        #[arg(long)]
+        exclude: Option<String>,
+        /// Generated passwords start with a letter
+        #[arg(long)]
+        leading_alpha: bool,
+        /// Maximum generated password length
+        #[arg(long)]
+        max: Option<usize>,
+    },
+    /// List templates
+    List,
+    /// Remove a template
+    #[command(name = "rm")]
+    Remove {
+        /// Template name
+        name: String,
+    },
+}
+
+#[derive(Args)]
+pub struct ClientArgs {
+    #[command(subcommand)]
+    pub command: ClientCommands,
+    
+    /// Socket path (default: passman.sock in the runtime directory; \\.\pipe\passman-<user> on Windows)
+    #[arg(long, global = true)]
+    pub socket: Option<String>,
+    
+    /// Session token from 'passman serve --token' (or PASSMAN_RPC_TOKEN)
+    #[arg(long, global = true)]
+    pub token: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum ClientCommands {
+    /// Show whether the server's vault is unlocked
+    Status,
+    /// Unlock the server's vault (prompts for the master password)
+    Unlock,
+    /// Lock the server's vault
+    Lock,
+    /// Print fields of an entry, one per line
+    Get {
+        id: String,
+        /// Field to print (username, password, url, note, tags); repeatable
+        #[arg(short, long, default_value = "password")]
+        field: Vec<String>,
+    },
+}
+
+/// Operations git invokes a credential helper with; the credential is
+/// read from stdin in git's key=value format
+#[derive(Subcommand)]
+pub enum GitCredentialCommands {
+    /// Print the username and password for the host
+    Get,
+    /// Save the credential git used successfully
+    Store,
+    /// Delete a credential git was told is invalid
+    Erase,
+}
+
+#[derive(Subcommand)]
+pub enum SyncCommands {
+    /// Write one encrypted file per entry into a directory
+    Export {
+        /// Sync directory (e.g. a git working tree)
+        dir: String,
+    },
+    /// Merge entries from a sync directory into the vault
+    Import {
+        /// Sync directory (e.g. a git working tree)
+        dir: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -165,21 +614,104 @@ pub enum TransferCommands {
         /// Output file path
         #[arg(short, long)]
         output: String,
-        /// Export format (json, csv)
+        /// Export format (json, csv, keepass-xml, or encrypted for a copy
+        /// of the vault that opens with the master password)
         #[arg(short, long, default_value = "json")]
         format: String,
+        /// Also export high-security entries, opened with their PIN after confirming
+        #[arg(long)]
+        include_high_security: bool,
+        /// Write a plaintext export into a synced or shared folder without asking
+        #[arg(short, long)]
+        yes: bool,
     },
       /// Import from various formats
     Import {
         /// Input file path
         #[arg(short, long)]
         input: String,
-        /// Import format (json, csv, chrome, firefox)
-        #[arg(short, long)]
-        format: String,
+        /// Import format (json, csv, chrome, firefox, keepass, pass); overrides the profile's
+        #[arg(short, long, required_unless_present = "profile")]
+        format: Option<String>,
         /// Merge with existing vault instead of overwriting
         #[arg(short, long)]
         merge: bool,
+        /// Import nothing if any row is malformed
+        #[arg(long)]
+        strict: bool,
+        /// Use a saved import profile (column mapping, tags, conflict policy)
+        #[arg(long)]
+        profile: Option<String>,
+        /// Import ids as they are, without trimming, dropping `www.` or
+        /// fixing their case
+        #[arg(long)]
+        keep_ids: bool,
+        /// Longest username or URL to import as is; longer ones are moved
+        /// into an attachment (default 4096, or the profile's)
+        #[arg(long)]
+        max_field_chars: Option<usize>,
+        /// Longest note to import as is (default 65536, or the profile's)
+        #[arg(long)]
+        max_note_chars: Option<usize>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum VaultCommands {
+    /// Show the vault file's format, cipher, KDF parameters and fingerprint
+    Info,
+    /// Check the vault, its HMAC and its newest backup without prompting, for
+    /// cron jobs and monitoring. The master password comes from
+    /// PASSMAN_PASSWORD or --password-file; without it those checks are
+    /// skipped. Exits 0 when all is well, 1 on warnings (such as a stale
+    /// backup) and 2 on errors (such as a failed integrity check).
+    Status {
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        output: String,
+        /// Warn when the newest backup is older than this many days and the vault changed since
+        #[arg(long, default_value_t = passman_core::status::DEFAULT_STALE_BACKUP_DAYS)]
+        stale_after: i64,
+    },
+    /// Benchmark key derivation and suggest Argon2 parameters
+    Calibrate {
+        /// Target unlock time in milliseconds
+        #[arg(long, default_value = "500")]
+        target_ms: u64,
+        /// Write the suggested parameters to the configuration
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Rewrite a vault whose salt only reads through compatibility
+    /// fallbacks (padding, URL-safe alphabet, truncation) in canonical form
+    Repair,
+    /// Re-encrypt the vault under a fresh salt and the configured KDF
+    /// parameters, keeping the master password
+    Rekey {
+        /// Switch to this cipher (aes-256-gcm, xchacha20)
+        #[arg(long)]
+        cipher: Option<String>,
+    },
+    /// Find conflicted sync copies of the vault and merge them into it
+    FindConflicts {
+        /// Merge every copy without asking
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Delete entries trashed longer than trash.retention_days (every save does this too)
+    Housekeeping {
+        /// Only print what would be deleted
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Permanently delete the vault file (select it with --vault)
+    Delete {
+        /// Also delete its timestamped backups
+        #[arg(long)]
+        backups: bool,
+        /// Skip the confirmation prompt
+        #[arg(long)]
+        force: bool,
     },
 }
 
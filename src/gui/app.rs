@@ -5,53 +5,490 @@
 #![allow(dead_code)]
 
 use eframe::egui;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc;
 use std::time::Instant;
-use zeroize::Zeroizing;
+use zeroize::{Zeroize, Zeroizing};
 
-use crate::model::{Entry, Vault};
-use crate::vault::{VaultManager, SecurityManager};
-use crate::utils::generate_password;
-use crate::health::PasswordHealthAnalyzer;
+use crate::model::{Entry, EntryIcon, EntrySort, GenerationMode, GenerationPrefs, Vault};
+use crate::vault::{BackupInfo, LoosePermissions, VaultKey, VaultManager, VaultFingerprint, VaultIdentity, VaultScan, SecurityManager, SyncFolderRisk};
+use crate::utils::{site_url, PasswordConfig, PasswordStrength};
+use crate::health::{append_health_record, AnalysisScope, HealthRecord, HealthReport, PasswordHealthAnalyzer};
 use crate::secure_clipboard::SecureClipboard;
-use crate::config::get_config;
+use crate::crypto::MasterPasswordCheck;
+use crate::paste::{is_email, parse_credentials, parse_otpauth, strip_url_tracking, PasteField};
+use crate::copy_sequence::{CopyStep, SequenceCursor};
+use crate::sealed::SealedSecrets;
+use crate::secure_types::{OptionalSecret, SecretView};
+use crate::config::{forget_vault, get_config, identity, resolve_vault_path, take_config_recovery, LockTimeoutBehavior};
+use crate::search::{fuzzy_score, SearchIndex, SearchScope};
+use crate::diff::VaultDiff;
+use crate::bulk::{BulkChange, BulkField};
+use crate::import_export::{ConflictPolicy, ExportRisk, ImportReport};
+use crate::lock_summary::LockSummary;
+use crate::net::NetPolicy;
+use crate::update::{self, UpdateNotice};
+use crate::url_match::{suggest_entry_id, unused_id};
 
 use super::types::*;
-use super::theme;
+use super::theme::{self, Motion};
 use super::toasts;
-use super::overlays;
+use super::overlays::{self, CommandPalette, PaletteCommand, PaletteEvent, PaletteItem};
+use super::markdown;
+use super::screens;
 use super::widgets;
+use super::icons::{self, IconCache};
+use super::window::{self, WindowState};
+
+/// Vault unlock running on a background thread so the UI stays responsive
+/// while Argon2 derives the key
+pub struct PendingUnlock {
+    receiver: mpsc::Receiver<Result<(Vault, VaultKey), String>>,
+    vault_file: String,
+    started: Instant,
+    estimate_ms: Option<u64>,
+}
+
+impl PendingUnlock {
+    /// Progress text based on the last measured unlock time for this vault
+    fn progress_message(&self) -> String {
+        match self.estimate_ms {
+            Some(estimate) if estimate > 0 => {
+                let elapsed = self.started.elapsed().as_millis() as u64;
+                let percent = (elapsed * 100 / estimate).min(99);
+                format!(
+                    "Deriving key… ~{:.1}s on this machine ({}%)",
+                    estimate as f64 / 1000.0,
+                    percent
+                )
+            }
+            _ => "Deriving key…".to_string(),
+        }
+    }
+}
+
+/// Name of the demo vault's session; there is no file behind it
+pub const DEMO_VAULT: &str = "Demo";
+
+/// An unlocked vault and everything needed to read and save it
+pub struct VaultSession {
+    pub vault: Vault,
+    pub vault_file: String,
+    /// Key derived at unlock; every save and reload goes through it, so
+    /// the master password isn't kept once the vault is open
+    pub key: VaultKey,
+    pub entries: Vec<(String, Entry)>,
+    /// Revealed passwords and when they were revealed
+    pub show_password: HashMap<String, Instant>,
+    /// Revealed passwords shown spelled out in groups
+    pub spelled_out: HashSet<String>,
+    pub show_note: HashMap<String, bool>,
+    pub search_index: SearchIndex,
+    
+    // Throttles master password re-entry (e.g. password change) for this vault
+    pub security_manager: SecurityManager,
+    
+    // External change detection
+    pub fingerprint: Option<VaultFingerprint>,
+    pub external_change_pending: bool,
+    
+    // Changes not yet written to disk (autosave off, or its write failed)
+    pub dirty: bool,
+    
+    // Set after unlocking with a weak master password until dismissed
+    pub master_password_warning: Option<PasswordStrength>,
+    
+    // Salted hash of the master password, to warn when it is stored as
+    // an entry password. None for sessions opened without the password.
+    pub master_check: Option<MasterPasswordCheck>,
+    
+    // Set after unlocking a vault in a cloud sync folder until dismissed
+    pub sync_folder_warning: Option<SyncFolderRisk>,
+    
+    // Set after unlocking a vault file other users can read until fixed or dismissed
+    pub permissions_warning: Option<LoosePermissions>,
+    
+    // Conflicted sync copies found beside the vault at unlock, by path,
+    // until merged or dismissed
+    pub conflicted_copies: Vec<String>,
+    
+    // Latest health analysis, added to the vault's history on the next save
+    pub pending_health_record: Option<HealthRecord>,
+    
+    // Health of the entries outside the trash; computed when the dashboard
+    // needs it and dropped whenever the entry list is rebuilt
+    pub health_reports: Option<Vec<HealthReport>>,
+    
+    // Password copies and reveals since the last save. Already applied to
+    // `vault`; written with the next save instead of re-encrypting per copy.
+    pub pending_uses: Vec<(String, chrono::DateTime<chrono::Utc>)>,
+    
+    // When the vault was unlocked, for the lock screen summary
+    pub opened_at: chrono::DateTime<chrono::Utc>,
+    
+    // Held only in memory (demo mode): saves succeed without writing and
+    // there is no file to watch, back up or re-key
+    pub in_memory: bool,
+}
+
+impl VaultSession {
+    pub fn new(vault: Vault, vault_file: String, key: VaultKey) -> Self {
+        Self::with_storage(vault, vault_file, key, false)
+    }
+    
+    /// The demo vault: sample entries under a random key, never written
+    pub fn demo() -> Self {
+        let vault = crate::sample::demo_vault(chrono::Utc::now());
+        Self::with_storage(vault, DEMO_VAULT.to_string(), VaultKey::ephemeral(), true)
+    }
+    
+    fn with_storage(vault: Vault, vault_file: String, key: VaultKey, in_memory: bool) -> Self {
+        let mut session = Self {
+            vault,
+            vault_file,
+            key,
+            entries: Vec::new(),
+            show_password: HashMap::new(),
+            spelled_out: HashSet::new(),
+            show_note: HashMap::new(),
+            search_index: SearchIndex::new(),
+            security_manager: SecurityManager::new(),
+            fingerprint: None,
+            external_change_pending: false,
+            dirty: false,
+            master_password_warning: None,
+            master_check: None,
+            sync_folder_warning: None,
+            permissions_warning: None,
+            conflicted_copies: Vec::new(),
+            pending_health_record: None,
+            health_reports: None,
+            pending_uses: Vec::new(),
+            opened_at: chrono::Utc::now(),
+            in_memory,
+        };
+        session.load_entries();
+        session
+    }
+    
+    /// Whether an entry's password is currently shown
+    pub fn is_revealed(&self, id: &str) -> bool {
+        self.show_password.contains_key(id)
+    }
+    
+    /// Show or hide an entry's password
+    pub fn set_revealed(&mut self, id: &str, revealed: bool) {
+        if revealed {
+            self.show_password.insert(id.to_string(), Instant::now());
+        } else {
+            self.show_password.remove(id);
+            self.spelled_out.remove(id);
+        }
+    }
+    
+    /// Whether a revealed password is shown spelled out
+    pub fn is_spelled_out(&self, id: &str) -> bool {
+        self.spelled_out.contains(id)
+    }
+    
+    /// Spell a revealed password out, or show it plainly again
+    pub fn toggle_spelled_out(&mut self, id: &str) {
+        if !self.spelled_out.remove(id) && self.is_revealed(id) {
+            self.spelled_out.insert(id.to_string());
+        }
+    }
+    
+    /// Hide passwords revealed more than `timeout` ago. Returns how long
+    /// until the next one is due to be hidden.
+    pub fn expire_reveals(&mut self, timeout: std::time::Duration) -> Option<std::time::Duration> {
+        self.show_password.retain(|_, since| since.elapsed() < timeout);
+        self.spelled_out.retain(|id| self.show_password.contains_key(id));
+        self.show_password.values().map(|since| timeout.saturating_sub(since.elapsed())).min()
+    }
+    
+    /// Short display name (file name without extension)
+    pub fn name(&self) -> String {
+        std::path::Path::new(&self.vault_file)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.vault_file.clone())
+    }
+    
+    pub fn load_entries(&mut self) {
+        self.refresh_entries();
+        
+        // Entries are reloaded after every load/save, so the file on disk
+        // now matches what we hold in memory
+        self.refresh_fingerprint();
+    }
+    
+    /// Rebuild the sorted entry list and search index from the vault
+    fn refresh_entries(&mut self) {
+        self.entries = self.vault.list_entries()
+            .into_iter()
+            .filter_map(|id| {
+                self.vault.get_entry(id).map(|entry| (id.clone(), entry.clone()))
+            })
+            .collect();
+        self.entries.sort_by(|a, b| a.0.cmp(&b.0));
+        self.search_index.sync(self.entries.iter().map(|(id, entry)| (id.as_str(), entry)));
+        self.health_reports = None;
+    }
+    
+    /// Analyze the vault's health unless the last analysis still applies
+    pub fn ensure_health_reports(&mut self, analyzer: &PasswordHealthAnalyzer) {
+        if self.health_reports.is_none() {
+            self.health_reports = Some(analyzer.analyze_vault(&self.vault, AnalysisScope::ActiveOnly));
+        }
+    }
+    
+    /// Seal what the lock screen may show about this vault. Needs the
+    /// machine secret from the OS keyring, and a vault with nothing left
+    /// to save, as the summary describes the file on disk.
+    pub fn store_lock_summary(&mut self, analyzer: &PasswordHealthAnalyzer) {
+        if self.in_memory {
+            return;
+        }
+        let Some(secret) = crate::machine_secret::get() else {
+            return;
+        };
+        if self.dirty {
+            return;
+        }
+        self.ensure_health_reports(analyzer);
+        let health = analyzer.generate_summary(self.health_reports.as_deref().unwrap_or_default());
+        let summary = LockSummary { last_opened: self.opened_at, entries: health.total, health: health.score };
+        if let Err(e) = summary.store(&self.vault_file, secret) {
+            log::warn!("Could not store the lock screen summary: {}", e);
+        }
+    }
+    
+    /// Remember the current on-disk state so our own saves are not
+    /// reported as external modifications
+    pub fn refresh_fingerprint(&mut self) {
+        self.fingerprint = if self.in_memory { None } else { VaultManager::fingerprint(Some(&self.vault_file)).ok() };
+        self.external_change_pending = false;
+    }
+    
+    /// Count a copy or reveal of an entry's password
+    pub fn record_use(&mut self, id: &str) {
+        let now = chrono::Utc::now();
+        if !self.vault.record_use(id, now) {
+            return;
+        }
+        if let Some((_, entry)) = self.entries.iter_mut().find(|(entry_id, _)| entry_id == id) {
+            entry.record_use(now);
+        }
+        self.pending_uses.push((id.to_string(), now));
+    }
+    
+    /// Whether anything, including usage counts, is waiting to be written
+    pub fn needs_save(&self) -> bool {
+        self.dirty || !self.pending_uses.is_empty()
+    }
+    
+    /// Record a change to the vault: written straight away with autosave,
+    /// otherwise kept in memory until the next explicit save
+    pub fn commit(&mut self, autosave: bool) -> Result<(), String> {
+        if autosave || self.in_memory {
+            let result = self.save();
+            if result.is_err() {
+                // Keep the change marked unsaved so lock and quit retry it
+                self.dirty = true;
+                self.refresh_entries();
+            }
+            return result;
+        }
+        self.dirty = true;
+        self.refresh_entries();
+        Ok(())
+    }
+    
+    /// Write the vault to this session's file with this session's password.
+    /// Refuses if another process changed the file since we read it.
+    pub fn save(&mut self) -> Result<(), String> {
+        if self.external_change_pending || self.changed_on_disk() {
+            self.external_change_pending = true;
+            self.dirty = true;
+            return Err("The vault file was changed by another program; reload or overwrite it".into());
+        }
+        self.overwrite()
+    }
+    
+    /// Write the vault even if the file changed on disk
+    pub fn overwrite(&mut self) -> Result<(), String> {
+        if let Some(record) = self.pending_health_record.take() {
+            append_health_record(&mut self.vault.metadata.health_history, record);
+        }
+        let housekeeping = self.vault.housekeep(&get_config().housekeeping_limits(), chrono::Utc::now());
+        if self.in_memory {
+            self.dirty = false;
+            self.pending_uses.clear();
+            self.refresh_entries();
+            return Ok(());
+        }
+        let fingerprint = VaultManager::save_and_fingerprint(&self.vault, &self.key, Some(&self.vault_file))
+            .map_err(|e| e.to_string())?;
+        if !housekeeping.is_empty() {
+            log::info!("Vault saved; housekeeping {}", housekeeping.summary());
+        }
+        self.dirty = false;
+        self.pending_uses.clear();
+        self.refresh_entries();
+        // What we just wrote, without reading the file back
+        self.fingerprint = Some(fingerprint);
+        self.external_change_pending = false;
+        Ok(())
+    }
+    
+    /// Merge a conflicted sync copy into the vault and write it, with any
+    /// unsaved changes, after backing the file up; the copy is moved
+    /// aside. Refuses if another program changed the file.
+    pub fn resolve_conflicted_copy(&mut self, copy_path: &str, copy: &Vault) -> Result<String, String> {
+        if self.external_change_pending || self.changed_on_disk() {
+            self.external_change_pending = true;
+            return Err("The vault file was changed by another program; reload or overwrite it first".into());
+        }
+        let resolution = VaultManager::resolve_conflicted_copy(&mut self.vault, &self.key, copy_path, copy, Some(&self.vault_file))
+            .map_err(|e| e.to_string())?;
+        self.dirty = false;
+        self.pending_uses.clear();
+        self.conflicted_copies.retain(|path| path != copy_path);
+        // Merged entries keep their timestamps, so index from scratch
+        self.search_index = SearchIndex::new();
+        self.load_entries();
+        Ok(resolution.archived)
+    }
+    
+    /// Save pending changes before an operation that works on the file itself.
+    /// Usage counts alone are saved if possible but never block.
+    pub fn flush(&mut self) -> Result<(), String> {
+        if self.dirty {
+            self.save()?;
+        } else if !self.pending_uses.is_empty() {
+            if let Err(e) = self.save() {
+                log::warn!("Usage counts for '{}' not saved: {}", self.name(), e);
+            }
+        }
+        Ok(())
+    }
+    
+    /// Reload the vault from disk. Loading re-verifies the file's HMAC; a
+    /// file re-keyed by another program can't be reloaded without unlocking again.
+    pub fn reload(&mut self) -> Result<(), String> {
+        if self.in_memory {
+            return Ok(());
+        }
+        match VaultManager::load_with_vault_key(&self.key, Some(&self.vault_file)) {
+            Ok(vault) => {
+                self.vault = vault;
+                self.dirty = false;
+                for (id, at) in &self.pending_uses {
+                    self.vault.record_use(id, *at);
+                }
+                // Another process may have changed entries without touching
+                // their timestamps, so index from scratch
+                self.search_index = SearchIndex::new();
+                self.load_entries();
+                Ok(())
+            }
+            Err(e) => {
+                // Accept the new file state so we don't retry on every poll
+                self.refresh_fingerprint();
+                Err(e.to_string())
+            }
+        }
+    }
+    
+    /// Whether another process changed the vault contents since we last read them
+    fn changed_on_disk(&mut self) -> bool {
+        if self.in_memory {
+            return false;
+        }
+        let Ok(current) = VaultManager::fingerprint(Some(&self.vault_file)) else {
+            return false;
+        };
+        
+        let changed = self.fingerprint
+            .as_ref()
+            .is_none_or(|known| known.hash != current.hash);
+        
+        if !changed {
+            // Metadata-only change (e.g. touch); keep the newer snapshot
+            self.fingerprint = Some(current);
+        }
+        changed
+    }
+}
 
 /// Main application state
 pub struct PassmanApp {
     // App state
     pub current_screen: Screen,
-    pub vault: Option<Vault>,
+    /// Screen drawn last frame, to notice the user crossing the lock
+    rendered_screen: Screen,
+    /// When the current screen was first drawn, for its transition
+    screen_shown_at: Instant,
+    /// Screens to return to on Back or Escape, most recent last
+    screen_history: Vec<Screen>,
+    /// Title and icon last given to the window
+    window_state: WindowState,
+    pub sessions: Vec<VaultSession>,
+    pub active_session: usize,
+    /// Vault file targeted by the welcome, login and create screens
     pub vault_file: String,
-    pub master_password: Zeroizing<String>,
     
     // Security state
     pub security_manager: SecurityManager,
     pub secure_clipboard: SecureClipboard,
     pub last_activity: Option<Instant>,
     pub lock_timeout_secs: u64,
+    pub lock_timeout_behavior: LockTimeoutBehavior,
+    pub autosave: bool,
     pub clipboard_clear_secs: u64,
     
-    // UI state
-    pub show_password: HashMap<String, bool>,
-    pub entries: Vec<(String, Entry)>,
-    
     // Form fields
     pub init_password: Zeroizing<String>,
     pub init_confirm: Zeroizing<String>,
+    pub init_show_password: bool,
     pub login_password: Zeroizing<String>,
+    pub login_show_password: bool,
+    pub health_filter: String,
+    pub health_sort: HealthSort,
+    /// Fingerprint shown on the login screen, read once per vault path
+    pub login_identity: Option<(String, Option<VaultIdentity>)>,
+    /// "Last opened … · N entries · health …" under it, read once per vault path
+    pub login_summary: Option<(String, Option<String>)>,
     pub add_id: String,
     pub add_username: String,
-    pub add_password: String,
+    pub add_url: String,
+    /// ID proposed from the URL or username, shown greyed out in the empty
+    /// ID field until Tab accepts it
+    pub add_id_suggestion: Option<String>,
+    pub add_password: Zeroizing<String>,
     pub add_note: String,
+    /// TOTP secret from a pasted otpauth link; the form has no field for it
+    pub add_totp: Zeroizing<String>,
+    /// Fields "New from clipboard" filled, highlighted until edited
+    pub add_inferred: Vec<PasteField>,
+    /// Offers to fix what was pasted into a field, until accepted, dismissed
+    /// or the field is edited
+    pub add_hints: Vec<AddFormHint>,
     pub generate_password: bool,
     pub add_show_password: bool,
     pub password_length: usize,
+    pub generation_rules: PasswordConfig,
+    pub show_generation_options: bool,
+    pub generator_mode: GeneratorMode,
+    pub pronounceable_syllables: usize,
+    pub add_template: Option<String>,
+    
+    // Template editor (settings)
+    pub template_name: String,
+    pub template_username: String,
+    pub template_tags: String,
+    pub template_url: String,
+    pub template_note: String,
     
     // Form validation errors
     pub form_errors: HashMap<String, String>,
@@ -59,16 +496,102 @@ pub struct PassmanApp {
     // Edit entry fields
     pub edit_id: String,
     pub edit_username: String,
-    pub edit_password: String,
+    pub edit_password: Zeroizing<String>,
     pub edit_note: String,
     pub edit_generate_password: bool,
     pub edit_show_password: bool,
+    pub edit_icon: Option<EntryIcon>,
+    pub edit_expires: Option<chrono::NaiveDate>,
+    /// Copy sequence, one step per line as `CopyStep::parse` reads them
+    pub edit_copy_sequence: String,
+    pub edit_linked_ids: Vec<String>,
+    /// Search text of the "Add link…" picker
+    pub edit_link_filter: String,
+    // The entry's modified_at when editing started, to catch saves from elsewhere
+    pub edit_snapshot: Option<chrono::DateTime<chrono::Utc>>,
+    pub edit_conflict: Option<EditConflict>,
+    // High security: whether the entry was sealed when editing started,
+    // whether it should be once saved, and the PIN for the change
+    pub edit_was_sealed: bool,
+    pub edit_seal: bool,
+    pub edit_pin: Zeroizing<String>,
+    pub edit_pin_confirm: Zeroizing<String>,
+    // How the entry's password was last generated, for "Regenerate (same rules)"
+    pub edit_generation: Option<GenerationPrefs>,
+    // Whether the password of the edit being saved is the master password
+    edit_holds_master: bool,
+    // Set when a form's password is the master password, until the user
+    // changes it or confirms; the save is retried with the confirmation
+    pub master_reuse_warning: Option<EntryForm>,
+    master_reuse_confirmed: bool,
+    pub show_icon_picker: bool,
+    pub favicon_fetch: Option<mpsc::Receiver<Result<EntryIcon, String>>>,
+    
+    // Decoded entry icons
+    pub icon_cache: IconCache,
     
     // Confirmation dialog
     pub pending_delete: Option<String>,
+    pub delete_confirm_text: String,
+    
+    // Master password prompt before revealing (security.reveal_requires_password)
+    pub pending_reveal: Option<(usize, String)>,
+    pub reveal_password: Zeroizing<String>,
+    pub reveal_show_password: bool,
+    
+    // PIN prompt for a high-security entry (session, entry, what for), and
+    // an entry opened with it for showing
+    pub pending_pin: Option<(usize, String, PinPurpose)>,
+    pub pin_input: Zeroizing<String>,
+    pub opened_sealed: Option<(String, SealedSecrets)>,
+    /// A password shown as a QR code until closed or the reveal timeout
+    pub password_qr: Option<PasswordQr>,
+    /// Context menu of an entry card
+    pub entry_menu: Option<EntryMenu>,
+    /// When the "Getting started" checklist last looked for backups
+    pub getting_started_backup_check: Option<Instant>,
+    
+    // Password mask held down for hold-to-reveal: session, entry, press start
+    pub password_peek: Option<(usize, String, Instant)>,
+    
+    // "Open & copy" in progress: username first, then the password
+    pub open_copy: Option<OpenCopy>,
+    
+    // Conflicted sync copy shown in the "Resolve…" summary
+    pub conflict_preview: Option<SyncConflictPreview>,
+    
+    // Position of the entry cards' "Next ▸" copy sequence
+    pub copy_sequence: Option<SequenceCursor>,
+    
+    // Vault deletion (Settings danger zone)
+    pub delete_vault_text: String,
+    pub delete_vault_has_backup: bool,
+    pub delete_vault_backups: bool,
+    
+    // Backup restore (settings)
+    pub restore_path: String,
+    pub restore_password: Zeroizing<String>,
+    pub restore_show_password: bool,
+    pub restore_preview: Option<(String, VaultDiff)>,
+    // Backups of the vault shown in Settings, read once per visit
+    pub backup_list: Option<(String, Vec<BackupInfo>)>,
+    
+    // Bulk find-and-replace (settings)
+    pub bulk_field: BulkField,
+    pub bulk_find: String,
+    pub bulk_replace: String,
+    pub bulk_regex: bool,
+    pub bulk_preview: Option<Vec<BulkChange>>,
     
     // Search and filtering
     pub search_query: String,
+    pub search_all_vaults: bool,
+    pub search_include_notes: bool,
+    pub search_scope: SearchScope,
+    /// Entry the list scrolls to on its next frame, after a link chip is clicked
+    pub scroll_to_entry: Option<(usize, String)>,
+    /// The long note or attachment opened with "Show full", one at a time
+    pub full_text: Option<FullText>,
     
     // Password strength
     pub password_strength: String,
@@ -77,28 +600,61 @@ pub struct PassmanApp {
     // Health dashboard
     pub health_analyzer: PasswordHealthAnalyzer,
     
+    // Vault files listed in settings; rescanned when cleared
+    pub vault_scan: Option<VaultScan>,
+    
     // Import/Export fields
     pub export_file_path: String,
     pub import_file_path: String,
     pub export_format: ExportFormat,
+    // Export high-security entries too, opened with this PIN
+    pub export_include_sealed: bool,
+    pub export_pin: Zeroizing<String>,
+    // Set while a plaintext export to a risky location awaits confirmation
+    pub export_risk: Option<ExportRisk>,
     pub import_format: ImportFormat,
     pub merge_on_import: bool,
+    pub import_report: Option<ImportReport>,
+    
+    // Import profile being used or edited
+    pub import_profile: Option<String>,
+    pub import_profile_name: String,
+    pub import_delimiter: String,
+    pub import_columns: std::collections::BTreeMap<String, String>,
+    pub import_id_template: String,
+    pub import_tags: String,
+    pub import_conflict: ConflictPolicy,
+    pub import_normalize_ids: bool,
+    // Ids the import would tidy, (original, normalized), for the file path held
+    pub import_id_preview: Option<(String, Vec<(String, String)>)>,
+    
+    // Entry sharing
+    pub share_import_path: String,
+    pub share_import_passphrase: Zeroizing<String>,
+    pub share_show_passphrase: bool,
+    pub shared_passphrase: Option<(String, Zeroizing<String>)>,
     
     // Password change fields
     pub change_current_password: Zeroizing<String>,
     pub change_new_password: Zeroizing<String>,
     pub change_confirm_password: Zeroizing<String>,
     pub show_password_change: bool,
+    pub open_password_change: bool,
     
     // Theme
     pub current_theme: Theme,
+    pub motion: Motion,
     
     // Keyboard shortcut state
     pub request_search_focus: bool,
+    pub search_history: SearchHistory,
+    /// Name being typed for pinning the current query as a saved search
+    pub pinning_search: Option<String>,
     
     // Loading state
     pub is_loading: bool,
     pub loading_message: String,
+    pub pending_unlock: Option<PendingUnlock>,
     
     // Onboarding
     pub show_onboarding: bool,
@@ -106,60 +662,207 @@ pub struct PassmanApp {
     
     // Toast notifications
     pub toasts: Vec<Toast>,
+    /// Every toast shown since the app started, for the bell panel
+    pub toast_history: Vec<ToastRecord>,
+    /// How many history records the bell panel has shown
+    pub toast_history_seen: usize,
+    pub show_toast_history: bool,
+    /// The entry deleted last, for the Undo button on its toast: vault file, id, entry
+    pub last_deleted: Option<(String, String, Entry)>,
+    
+    // External change detection
+    pub last_external_check: Option<Instant>,
+    pub show_external_change_prompt: bool,
+    
+    // Unsaved changes when the window is closed
+    pub show_close_prompt: bool,
+    pub allow_close: bool,
+    
+    // Non-https link from a note, waiting for confirmation
+    pub pending_link: Option<String>,
+    
+    // Screen that panicked while rendering
+    pub render_error: Option<RenderError>,
+    
+    // Entry list order (`ui.default_sort`)
+    pub entry_sort: EntrySort,
+    
+    // Entry list grouped under site headers (`ui.group_by_site`)
+    pub group_by_site: bool,
+    
+    // Ctrl+K command palette, while open
+    pub command_palette: Option<CommandPalette>,
+    
+    // Weekly release check (opt-in)
+    pub update_check: Option<mpsc::Receiver<Option<UpdateNotice>>>,
+    pub update_notice: Option<UpdateNotice>,
 }
 
 impl Default for PassmanApp {
     fn default() -> Self {
         Self {
             current_screen: Screen::default(),
-            vault: None,
+            rendered_screen: Screen::default(),
+            screen_shown_at: Instant::now(),
+            screen_history: Vec::new(),
+            window_state: WindowState::default(),
+            sessions: Vec::new(),
+            active_session: 0,
             vault_file: String::new(),
-            master_password: Zeroizing::new(String::new()),
             security_manager: SecurityManager::new(),
             secure_clipboard: SecureClipboard::new(),
             last_activity: None,
             lock_timeout_secs: 0,
+            lock_timeout_behavior: LockTimeoutBehavior::default(),
+            autosave: true,
             clipboard_clear_secs: 30,
-            show_password: HashMap::new(),
-            entries: Vec::new(),
             init_password: Zeroizing::new(String::new()),
             init_confirm: Zeroizing::new(String::new()),
+            init_show_password: false,
             login_password: Zeroizing::new(String::new()),
+            login_show_password: false,
+            health_filter: String::new(),
+            health_sort: HealthSort::default(),
+            login_identity: None,
+            login_summary: None,
             add_id: String::new(),
             add_username: String::new(),
-            add_password: String::new(),
+            add_url: String::new(),
+            add_id_suggestion: None,
+            add_password: Zeroizing::new(String::new()),
             add_note: String::new(),
+            add_totp: Zeroizing::new(String::new()),
+            add_inferred: Vec::new(),
+            add_hints: Vec::new(),
             generate_password: false,
             add_show_password: false,
             password_length: 16,
+            generation_rules: PasswordConfig::default(),
+            show_generation_options: false,
+            generator_mode: GeneratorMode::Random,
+            pronounceable_syllables: 6,
+            add_template: None,
+            template_name: String::new(),
+            template_username: String::new(),
+            template_tags: String::new(),
+            template_url: String::new(),
+            template_note: String::new(),
             form_errors: HashMap::new(),
             edit_id: String::new(),
             edit_username: String::new(),
-            edit_password: String::new(),
+            edit_password: Zeroizing::new(String::new()),
             edit_note: String::new(),
             edit_generate_password: false,
             edit_show_password: false,
+            edit_icon: None,
+            edit_expires: None,
+            edit_copy_sequence: String::new(),
+            edit_linked_ids: Vec::new(),
+            edit_link_filter: String::new(),
+            edit_snapshot: None,
+            edit_conflict: None,
+            edit_was_sealed: false,
+            edit_seal: false,
+            edit_pin: Zeroizing::new(String::new()),
+            edit_pin_confirm: Zeroizing::new(String::new()),
+            edit_generation: None,
+            edit_holds_master: false,
+            master_reuse_warning: None,
+            master_reuse_confirmed: false,
+            show_icon_picker: false,
+            favicon_fetch: None,
+            icon_cache: IconCache::default(),
             pending_delete: None,
+            delete_confirm_text: String::new(),
+            pending_reveal: None,
+            reveal_password: Zeroizing::new(String::new()),
+            reveal_show_password: false,
+            pending_pin: None,
+            pin_input: Zeroizing::new(String::new()),
+            opened_sealed: None,
+            password_qr: None,
+            entry_menu: None,
+            getting_started_backup_check: None,
+            password_peek: None,
+            open_copy: None,
+            conflict_preview: None,
+            copy_sequence: None,
+            delete_vault_text: String::new(),
+            delete_vault_has_backup: false,
+            delete_vault_backups: false,
+            restore_path: String::new(),
+            restore_password: Zeroizing::new(String::new()),
+            restore_show_password: false,
+            restore_preview: None,
+            backup_list: None,
+            bulk_field: BulkField::Username,
+            bulk_find: String::new(),
+            bulk_replace: String::new(),
+            bulk_regex: false,
+            bulk_preview: None,
             search_query: String::new(),
+            search_all_vaults: false,
+            search_include_notes: true,
+            search_scope: SearchScope::ActiveOnly,
+            scroll_to_entry: None,
+            full_text: None,
             password_strength: String::new(),
             password_suggestions: Vec::new(),
             health_analyzer: PasswordHealthAnalyzer::new(),
             export_file_path: String::new(),
+            vault_scan: None,
             import_file_path: String::new(),
             export_format: ExportFormat::default(),
+            export_include_sealed: false,
+            export_pin: Zeroizing::new(String::new()),
+            export_risk: None,
             import_format: ImportFormat::default(),
             merge_on_import: false,
+            import_report: None,
+            import_profile: None,
+            import_profile_name: String::new(),
+            import_delimiter: ",".to_string(),
+            import_columns: std::collections::BTreeMap::new(),
+            import_id_template: String::new(),
+            import_tags: String::new(),
+            import_conflict: ConflictPolicy::Skip,
+            import_normalize_ids: true,
+            import_id_preview: None,
+            share_import_path: String::new(),
+            share_import_passphrase: Zeroizing::new(String::new()),
+            share_show_passphrase: false,
+            shared_passphrase: None,
             change_current_password: Zeroizing::new(String::new()),
             change_new_password: Zeroizing::new(String::new()),
             change_confirm_password: Zeroizing::new(String::new()),
             show_password_change: false,
+            open_password_change: false,
             current_theme: Theme::default(),
+            motion: Motion::default(),
             request_search_focus: false,
+            search_history: SearchHistory::default(),
+            pinning_search: None,
             is_loading: false,
             loading_message: String::new(),
+            pending_unlock: None,
             show_onboarding: false,
             onboarding_step: 0,
             toasts: Vec::new(),
+            toast_history: Vec::new(),
+            toast_history_seen: 0,
+            show_toast_history: false,
+            last_deleted: None,
+            last_external_check: None,
+            show_external_change_prompt: false,
+            show_close_prompt: false,
+            allow_close: false,
+            pending_link: None,
+            render_error: None,
+            entry_sort: EntrySort::default(),
+            group_by_site: false,
+            command_palette: None,
+            update_check: None,
+            update_notice: None,
         }
     }
 }
@@ -167,33 +870,232 @@ impl Default for PassmanApp {
 impl PassmanApp {
     /// Create new application with configuration
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        // Resolve before taking the config lock; the resolver reads it too
+        let (vault_file, _) = resolve_vault_path(None);
         let config = get_config();
         
         // Dark theme only
         let initial_theme = Theme::Dark;
         
-        let vault_exists = std::path::Path::new(&config.general.default_vault).exists();
+        let vault_exists = std::path::Path::new(&vault_file).exists();
 
-        let app = Self {
-            vault_file: config.general.default_vault.clone(),
+        let update_check = (cfg!(feature = "update-check") && config.general.check_updates)
+            .then(Self::spawn_update_check);
+        
+        let mut app = Self {
+            vault_file,
             password_length: config.password.default_length,
+            generation_rules: Self::default_generation_rules(),
+            pronounceable_syllables: config.password.pronounceable_syllables,
             lock_timeout_secs: config.security.lock_timeout_secs,
+            lock_timeout_behavior: config.security.lock_timeout_behavior,
+            autosave: config.general.autosave,
             clipboard_clear_secs: config.security.clipboard_timeout_secs,
+            search_include_notes: config.ui.search_notes,
+            entry_sort: EntrySort::parse(&config.ui.default_sort).unwrap_or_default(),
+            group_by_site: config.ui.group_by_site,
             secure_clipboard: SecureClipboard::with_timeout(config.security.clipboard_timeout_secs),
             current_theme: initial_theme,
+            motion: Motion::from_config(&config.ui.animations),
             show_onboarding: !vault_exists,
+            update_check,
             ..Default::default()
         };
         
         theme::apply_theme(&app.current_theme, &cc.egui_ctx);
+        theme::apply_motion(app.motion, &cc.egui_ctx);
+        if let Some(recovery) = take_config_recovery() {
+            app.toast_warning(recovery.message());
+        }
         
         app
     }
+    
+    /// `passman --demo`: straight into the demo vault
+    pub fn new_demo(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut app = Self::new(cc);
+        app.open_demo();
+        app
+    }
+
+    /// Generator rules from the configured password settings
+    pub fn default_generation_rules() -> PasswordConfig {
+        get_config().password.generation_defaults().rules
+    }
+    
+    /// Generate a password in the selected mode, along with the settings
+    /// the entry remembers to generate the next one the same way
+    fn generate_for_entry(mode: GeneratorMode, length: usize, rules: &PasswordConfig, syllables: usize) -> Result<(String, GenerationPrefs), String> {
+        let length = match mode {
+            GeneratorMode::Random => length,
+            GeneratorMode::Memorable => get_config().password.memorable_word_count,
+            GeneratorMode::Pronounceable => syllables,
+        };
+        let prefs = GenerationPrefs { length, rules: rules.clone(), mode: mode.into() };
+        Ok((prefs.generate()?, prefs))
+    }
 
+    // === Getting Started ===
+    
+    /// Whether the "Getting started" checklist shows for the active vault
+    pub fn shows_getting_started(&self) -> bool {
+        if self.active_session().is_some_and(|session| session.in_memory) {
+            return false;
+        }
+        let config = get_config();
+        self.active_vault().is_some_and(|vault| {
+            GettingStartedStep::checklist_shows(vault.len(), config.ui.getting_started_dismissed, &config.ui.getting_started_done)
+        })
+    }
+    
+    /// Each checklist item and whether it is done. Items are checked off
+    /// when what they ask for is true of the vault or the settings, and are
+    /// recorded so they stay checked.
+    pub fn getting_started_progress(&mut self) -> Vec<(GettingStartedStep, bool)> {
+        // Listing backups reads a directory; once a second is plenty
+        let check_backups = self.getting_started_backup_check.is_none_or(|at| at.elapsed().as_secs() >= 1);
+        if check_backups {
+            self.getting_started_backup_check = Some(Instant::now());
+        }
+        let mut seen = Vec::new();
+        if let Some(session) = self.active_session() {
+            if !session.vault.is_empty() {
+                seen.push(GettingStartedStep::FirstEntry);
+            }
+            if session.vault.iter().any(|(_, entry)| entry.created_by.as_deref().is_some_and(|by| by.starts_with("import:"))) {
+                seen.push(GettingStartedStep::Import);
+            }
+            if check_backups && !VaultManager::list_backups(Some(&session.vault_file)).is_empty() {
+                seen.push(GettingStartedStep::Backup);
+            }
+        }
+        if self.lock_timeout_secs > 0 {
+            seen.push(GettingStartedStep::AutoLock);
+        }
+        for step in seen {
+            self.mark_getting_started(step);
+        }
+        
+        let config = get_config();
+        GettingStartedStep::ALL
+            .iter()
+            .map(|step| (*step, step.is_recorded(&config.ui.getting_started_done)))
+            .collect()
+    }
+    
+    /// Record a checklist item as done
+    pub fn mark_getting_started(&mut self, step: GettingStartedStep) {
+        let mut config = crate::config::get_config_mut();
+        if step.is_recorded(&config.ui.getting_started_done) {
+            return;
+        }
+        config.ui.getting_started_done.push(step.key().to_string());
+        drop(config);
+        if let Err(e) = crate::config::save_config() {
+            log::warn!("Could not record the getting started checklist: {}", e);
+        }
+    }
+    
+    /// Hide the checklist for good
+    pub fn dismiss_getting_started(&mut self) {
+        crate::config::get_config_mut().ui.getting_started_dismissed = true;
+        if let Err(e) = crate::config::save_config() {
+            self.toast_error(e);
+        }
+    }
+    
+    /// Take a checklist item's button: go to where it is done, or for a
+    /// backup, make one
+    pub fn run_getting_started_step(&mut self, step: GettingStartedStep) {
+        match step {
+            GettingStartedStep::FirstEntry => self.navigate(Screen::AddEntry),
+            GettingStartedStep::Import => self.navigate(Screen::ImportExport),
+            GettingStartedStep::AutoLock => self.navigate(Screen::Settings),
+            GettingStartedStep::Generator => {
+                self.generate_password = true;
+                self.navigate(Screen::AddEntry);
+            }
+            GettingStartedStep::Backup => {
+                let Some(session) = self.active_session_mut() else {
+                    return;
+                };
+                let backup = session.flush()
+                    .and_then(|()| VaultManager::create_backup_with_key(&session.key, Some(&session.vault_file)).map_err(|e| e.to_string()));
+                match backup {
+                    Ok(path) => {
+                        self.mark_getting_started(step);
+                        self.toast_success(format!("Backup created: {}", path));
+                    }
+                    Err(e) => self.toast_error(format!("Backup failed: {}", e)),
+                }
+            }
+        }
+    }
+    
     // === Toast Methods ===
     
     pub fn add_toast(&mut self, message: impl Into<String>, toast_type: ToastType) {
-        self.toasts.push(Toast::new(message, toast_type));
+        self.push_toast(Toast::new(message, toast_type));
+    }
+    
+    /// Show a toast with a button that runs `action`
+    pub fn toast_with_action(&mut self, message: impl Into<String>, toast_type: ToastType, action: ToastAction) {
+        self.push_toast(Toast::new(message, toast_type).with_action(action));
+    }
+    
+    fn push_toast(&mut self, toast: Toast) {
+        self.toast_history.push(ToastRecord::from(&toast));
+        if self.toast_history.len() > TOAST_HISTORY_LEN {
+            let excess = self.toast_history.len() - TOAST_HISTORY_LEN;
+            self.toast_history.drain(..excess);
+            self.toast_history_seen = self.toast_history_seen.saturating_sub(excess);
+        }
+        self.toasts.push(toast);
+    }
+    
+    /// Notifications the bell panel hasn't shown yet
+    pub fn unseen_toasts(&self) -> usize {
+        self.toast_history.len().saturating_sub(self.toast_history_seen)
+    }
+    
+    /// React to a click on a toast
+    fn handle_toast_event(&mut self, event: toasts::ToastEvent) {
+        match event {
+            toasts::ToastEvent::Action(index, action) => {
+                if index < self.toasts.len() {
+                    self.toasts.remove(index);
+                }
+                self.run_toast_action(action);
+            }
+            toasts::ToastEvent::Dismiss(index) => {
+                if index < self.toasts.len() {
+                    self.toasts.remove(index);
+                }
+            }
+            toasts::ToastEvent::ShowHistory => self.show_toast_history = true,
+        }
+    }
+    
+    /// Run the button of a toast
+    pub fn run_toast_action(&mut self, action: ToastAction) {
+        match action {
+            ToastAction::UndoDelete => match self.undo_delete() {
+                Ok(id) => self.toast_success(format!("Entry '{}' restored", id)),
+                Err(e) => self.toast_error(e),
+            },
+            ToastAction::ClearClipboard => {
+                self.open_copy = None;
+                match self.secure_clipboard.clear_now() {
+                    Ok(()) => self.toast_info("Clipboard cleared"),
+                    Err(e) => self.toast_error(format!("Could not clear the clipboard: {}", e)),
+                }
+            }
+            ToastAction::RetrySave => {
+                if self.save_dirty_sessions() {
+                    self.toast_success("Vault saved");
+                }
+            }
+        }
     }
     
     pub fn toast_success(&mut self, message: impl Into<String>) {
@@ -208,7 +1110,17 @@ impl PassmanApp {
         self.add_toast(message, ToastType::Info);
     }
     
-    #[allow(dead_code)]
+    /// Report a failed save with a Retry button. A conflict with changes on
+    /// disk gets a plain toast: the external change prompt offers the way out.
+    pub fn toast_save_error(&mut self, error: impl std::fmt::Display) {
+        let message = format!("Failed to save vault: {}", error);
+        if self.active_session().is_some_and(|session| session.external_change_pending) {
+            self.toast_error(message);
+        } else {
+            self.toast_with_action(message, ToastType::Error, ToastAction::RetrySave);
+        }
+    }
+    
     pub fn toast_warning(&mut self, message: impl Into<String>) {
         self.add_toast(message, ToastType::Warning);
     }
@@ -251,15 +1163,71 @@ impl PassmanApp {
     
     // === Validation Methods ===
     
+    /// What's wrong with the typed entry ID, if anything (see [`Vault::new_id_problem`])
+    pub fn add_id_problem(&self) -> Option<String> {
+        match self.active_vault() {
+            Some(vault) => vault.new_id_problem(&self.add_id),
+            None => Vault::new().new_id_problem(&self.add_id),
+        }
+    }
+    
+    /// Re-check the entry ID as it is typed, showing or clearing its error
+    pub fn validate_add_id(&mut self) {
+        match self.add_id_problem() {
+            Some(problem) => self.set_form_error("add_id", problem),
+            None => self.clear_form_error("add_id"),
+        }
+    }
+    
+    /// Whether anything would stop the add form from being saved; the Add
+    /// button is disabled while it is
+    pub fn add_form_blocked(&self) -> bool {
+        self.add_id_problem().is_some()
+            || self.add_username.trim().is_empty()
+            || (!self.generate_password && self.add_password.trim().is_empty())
+    }
+    
+    /// Warning for a typed password weak enough that health checks will
+    /// flag it. Doesn't block adding.
+    pub fn add_password_warning(&self) -> Option<String> {
+        if self.generate_password || self.add_password.is_empty() {
+            return None;
+        }
+        let (strength, suggestions) = crate::utils::analyze_password_strength(&self.add_password);
+        let shown = get_config().ui.suggestion_detail.select(&suggestions).first().copied().cloned();
+        matches!(strength, PasswordStrength::VeryWeak | PasswordStrength::Weak).then(|| match shown {
+            Some(suggestion) => format!("{} password: the health dashboard will flag it. {}.", strength, suggestion),
+            None => format!("{} password: the health dashboard will flag it.", strength),
+        })
+    }
+    
+    /// Propose an ID from the URL or username while the ID field is empty
+    pub fn suggest_add_id(&mut self) {
+        self.add_id_suggestion = if self.add_id.is_empty() {
+            let url = Some(self.add_url.as_str()).filter(|url| !url.trim().is_empty());
+            let vault = self.active_vault();
+            vault.and_then(|vault| suggest_entry_id(vault, url, Some(&self.add_username)))
+        } else {
+            None
+        };
+    }
+    
+    /// Fill the empty ID field with the proposed ID
+    pub fn accept_add_id_suggestion(&mut self) {
+        if self.add_id.is_empty() {
+            if let Some(id) = self.add_id_suggestion.take() {
+                self.add_id = id;
+                self.validate_add_id();
+            }
+        }
+    }
+    
     pub fn validate_add_entry(&mut self) -> bool {
         self.clear_form_errors();
         let mut is_valid = true;
         
-        if self.add_id.trim().is_empty() {
-            self.set_form_error("add_id", "Entry ID is required");
-            is_valid = false;
-        } else if self.vault.as_ref().is_some_and(|v| v.entries.contains_key(&self.add_id)) {
-            self.set_form_error("add_id", "Entry ID already exists");
+        if let Some(problem) = self.add_id_problem() {
+            self.set_form_error("add_id", problem);
             is_valid = false;
         }
         
@@ -285,49 +1253,505 @@ impl PassmanApp {
             is_valid = false;
         }
         
-        if !self.edit_generate_password && self.edit_password.trim().is_empty() {
+        if !self.edit_was_sealed && !self.edit_generate_password && self.edit_password.trim().is_empty() {
             self.set_form_error("edit_password", "Password is required");
             is_valid = false;
         }
         
+        if self.edit_seal != self.edit_was_sealed && self.edit_pin.is_empty() {
+            self.set_form_error("edit_pin", "PIN is required");
+            is_valid = false;
+        } else if self.edit_seal && !self.edit_was_sealed && *self.edit_pin != *self.edit_pin_confirm {
+            self.set_form_error("edit_pin", "PINs don't match");
+            is_valid = false;
+        }
+        
+        if let Err(e) = CopyStep::parse_lines(&self.edit_copy_sequence) {
+            self.set_form_error("edit_copy_sequence", e);
+            is_valid = false;
+        }
+        
         is_valid
     }
     
-    // === Vault Operations ===
+    // === Sessions ===
     
-    pub fn lock_vault(&mut self) {
-        self.vault = None;
-        *self.master_password = String::new();
-        self.entries.clear();
-        self.show_password.clear();
-        self.last_activity = None;
-        self.current_screen = Screen::Welcome;
-        let _ = self.secure_clipboard.clear_now();
+    pub fn active_session(&self) -> Option<&VaultSession> {
+        self.sessions.get(self.active_session)
     }
     
-    pub fn load_entries(&mut self) {
-        if let Some(vault) = &self.vault {
-            self.entries = vault.list_entries()
-                .into_iter()
-                .filter_map(|id| {
-                    vault.get_entry(id).map(|entry| (id.clone(), entry.clone()))
-                })
-                .collect();
-            self.entries.sort_by(|a, b| a.0.cmp(&b.0));
+    pub fn active_session_mut(&mut self) -> Option<&mut VaultSession> {
+        self.sessions.get_mut(self.active_session)
+    }
+    
+    pub fn active_vault(&self) -> Option<&Vault> {
+        self.active_session().map(|session| &session.vault)
+    }
+    
+    pub fn is_unlocked(&self) -> bool {
+        !self.sessions.is_empty()
+    }
+    
+    /// Entries of the active vault
+    pub fn entries(&self) -> &[(String, Entry)] {
+        self.active_session().map(|session| session.entries.as_slice()).unwrap_or_default()
+    }
+    
+    /// Make an unlocked vault the active one
+    pub fn switch_session(&mut self, index: usize) {
+        if index < self.sessions.len() && index != self.active_session {
+            self.active_session = index;
+            self.pending_delete = None;
+            self.delete_confirm_text.clear();
+            // A conflict on a vault with unsaved changes needs a decision
+            self.show_external_change_prompt = self.sessions[index].external_change_pending && self.sessions[index].dirty;
         }
     }
-
-    pub fn filter_entries(&self) -> Vec<&(String, Entry)> {
-        if self.search_query.is_empty() {
-            self.entries.iter().collect()
-        } else {
-            self.entries
-                .iter()
-                .filter(|(id, entry)| {
-                    id.to_lowercase().contains(&self.search_query.to_lowercase())
-                        || entry.username.to_lowercase().contains(&self.search_query.to_lowercase())
-                })
-                .collect()
+    
+    /// Add a freshly unlocked vault, or switch to it if it is already open
+    fn open_session(&mut self, vault: Vault, vault_file: String, key: VaultKey) {
+        self.add_session(VaultSession::new(vault, vault_file, key));
+    }
+    
+    /// Open the demo vault of made-up entries, or switch to it
+    pub fn open_demo(&mut self) {
+        self.show_onboarding = false;
+        match self.sessions.iter().position(|session| session.in_memory) {
+            Some(index) => {
+                self.switch_session(index);
+                self.navigate_root(Screen::Main);
+            }
+            None => self.add_session(VaultSession::demo()),
+        }
+    }
+    
+    fn add_session(&mut self, session: VaultSession) {
+        if let Some(index) = self.sessions.iter().position(|s| s.vault_file == session.vault_file) {
+            self.sessions[index] = session;
+            self.switch_session(index);
+        } else {
+            self.sessions.push(session);
+            self.switch_session(self.sessions.len() - 1);
+        }
+        self.navigate_root(Screen::Main);
+    }
+    
+    // === Vault Operations ===
+    
+    /// Whether any unlocked vault has changes not yet written to disk
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.sessions.iter().any(|session| session.dirty)
+    }
+    
+    /// Record a change to the active vault, saving it unless autosave is off
+    pub fn commit_changes(&mut self) -> Result<(), String> {
+        let autosave = self.autosave;
+        let session = self.active_session_mut().ok_or("No vault loaded")?;
+        let result = session.commit(autosave);
+        let conflict = result.is_err() && session.external_change_pending;
+        self.show_external_change_prompt |= conflict;
+        result
+    }
+    
+    /// Write the active vault now (Save button / Ctrl+Shift+S)
+    pub fn save_active_vault(&mut self) {
+        let Some(session) = self.active_session_mut() else {
+            return;
+        };
+        let result = session.save();
+        let conflict = session.external_change_pending;
+        match result {
+            Ok(()) => self.toast_success("Vault saved"),
+            Err(e) => {
+                self.show_external_change_prompt |= conflict;
+                self.toast_save_error(e);
+            }
+        }
+    }
+    
+    /// Write every vault with unsaved changes. Returns false, after
+    /// reporting the failures, if any of them could not be saved.
+    pub fn save_dirty_sessions(&mut self) -> bool {
+        let mut failures = Vec::new();
+        for session in self.sessions.iter_mut().filter(|session| session.needs_save()) {
+            if let Err(e) = session.flush() {
+                failures.push(format!("Could not save vault '{}': {}", session.name(), e));
+            }
+        }
+        if self.active_session().is_some_and(|s| s.dirty && s.external_change_pending) {
+            self.show_external_change_prompt = true;
+        }
+        let saved = failures.is_empty();
+        for failure in failures {
+            self.toast_error(failure);
+        }
+        saved
+    }
+    
+    /// Lock the active vault; other unlocked vaults stay open. Unsaved
+    /// changes are written first; if that fails the vault stays unlocked
+    /// and false is returned.
+    pub fn lock_vault(&mut self) -> bool {
+        if let Some(session) = self.active_session_mut() {
+            let result = session.flush();
+            let conflict = session.external_change_pending;
+            if let Err(e) = result {
+                self.show_external_change_prompt |= conflict;
+                self.toast_error(format!("Vault not locked — unsaved changes could not be saved: {}", e));
+                return false;
+            }
+        }
+        
+        if self.active_session < self.sessions.len() {
+            let mut session = self.sessions.remove(self.active_session);
+            session.store_lock_summary(&self.health_analyzer);
+            self.login_summary = None;
+        }
+        
+        if self.sessions.is_empty() {
+            self.lock_all_vaults();
+            return true;
+        }
+        
+        self.active_session = self.active_session.min(self.sessions.len() - 1);
+        self.last_deleted = None;
+        self.clear_sensitive_forms();
+        self.bulk_preview = None;
+        self.show_external_change_prompt = false;
+        self.navigate_root(Screen::Main);
+        let _ = self.secure_clipboard.clear_now();
+        true
+    }
+    
+    /// Close the vault without saving if it is open, delete its file, and
+    /// go back to Welcome. Returns the number of files removed.
+    pub fn delete_vault(&mut self, vault_file: &str, remove_backups: bool) -> Result<usize, String> {
+        let removed = VaultManager::delete(Some(vault_file), remove_backups).map_err(|e| e.to_string())?;
+        
+        if let Some(index) = self.sessions.iter().position(|session| session.vault_file == vault_file) {
+            self.sessions.remove(index);
+            self.active_session = self.active_session.min(self.sessions.len().saturating_sub(1));
+        }
+        if self.sessions.is_empty() {
+            self.lock_all_vaults();
+        }
+        
+        if let Err(e) = forget_vault(vault_file) {
+            self.toast_error(e);
+        }
+        if self.vault_file == vault_file {
+            self.vault_file = resolve_vault_path(None).0;
+        }
+        self.delete_vault_text.clear();
+        self.delete_vault_has_backup = false;
+        self.delete_vault_backups = false;
+        self.vault_scan = None;
+        self.navigate_root(Screen::Welcome);
+        Ok(removed.len())
+    }
+    
+    /// Wipe every form that can hold a secret or a half-typed entry: add,
+    /// edit, password change, create, login, reveal, PIN, restore, share
+    /// and export.
+    /// Done on lock and whenever the user crosses between the locked
+    /// screens and a vault, so the next person to unlock finds them empty.
+    pub fn clear_sensitive_forms(&mut self) {
+        self.clear_add_form();
+        self.clear_edit_form();
+        self.clear_password_change_form();
+        self.show_password_change = false;
+        self.open_password_change = false;
+        self.init_password.zeroize();
+        self.init_confirm.zeroize();
+        self.init_show_password = false;
+        self.login_password.zeroize();
+        self.login_show_password = false;
+        self.clear_reveal_state();
+        self.clear_restore_form();
+        self.shared_passphrase = None;
+        self.share_import_passphrase.zeroize();
+        self.share_show_passphrase = false;
+        self.export_include_sealed = false;
+        self.export_pin.zeroize();
+        self.pending_delete = None;
+        self.delete_confirm_text.clear();
+        self.delete_vault_text.clear();
+        self.clear_form_errors();
+    }
+    
+    /// Wipe the three master password change fields
+    pub fn clear_password_change_form(&mut self) {
+        self.change_current_password.zeroize();
+        self.change_new_password.zeroize();
+        self.change_confirm_password.zeroize();
+    }
+    
+    /// Clear the sensitive forms when the screen moved into or out of the
+    /// locked screens (Welcome, Create, Login) since the last frame
+    fn note_screen_change(&mut self) {
+        if self.current_screen == self.rendered_screen {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.rendered_screen, self.current_screen.clone());
+        self.screen_shown_at = Instant::now();
+        if previous.is_locked() || self.current_screen.is_locked() {
+            self.clear_sensitive_forms();
+        }
+    }
+    
+    /// Forget the backup restore path, password and preview
+    pub fn clear_restore_form(&mut self) {
+        self.restore_path.clear();
+        self.restore_password.zeroize();
+        self.restore_show_password = false;
+        self.restore_preview = None;
+        self.backup_list = None;
+    }
+    
+    /// Lock every unlocked vault and return to the welcome screen.
+    /// Call `save_dirty_sessions` first to keep unsaved changes.
+    pub fn lock_all_vaults(&mut self) {
+        for session in &mut self.sessions {
+            session.store_lock_summary(&self.health_analyzer);
+        }
+        self.sessions.clear();
+        self.login_summary = None;
+        self.active_session = 0;
+        self.last_deleted = None;
+        self.clear_sensitive_forms();
+        self.bulk_preview = None;
+        self.last_activity = None;
+        self.show_external_change_prompt = false;
+        self.icon_cache.clear();
+        self.search_history.clear();
+        self.pinning_search = None;
+        // Saves since the last scan may have changed the vault list badges
+        self.vault_scan = None;
+        self.navigate_root(Screen::Welcome);
+        let _ = self.secure_clipboard.clear_now();
+    }
+    
+    pub fn load_entries(&mut self) {
+        if let Some(session) = self.active_session_mut() {
+            session.load_entries();
+        }
+        self.show_external_change_prompt = false;
+    }
+    
+    // === External Change Detection ===
+    
+    /// Whether the current screen holds form input that a reload could invalidate
+    fn has_unsaved_form_input(&self) -> bool {
+        match self.current_screen {
+            Screen::AddEntry => {
+                !self.add_id.is_empty()
+                    || !self.add_username.is_empty()
+                    || !self.add_url.is_empty()
+                    || !self.add_password.is_empty()
+                    || !self.add_note.is_empty()
+            }
+            Screen::EditEntry(_) => true,
+            _ => false,
+        }
+    }
+    
+    /// Poll the unlocked vault files and reload any that another process modified
+    fn check_external_changes(&mut self) {
+        if self.sessions.is_empty() {
+            return;
+        }
+        
+        // A change was seen while a form was open; reload once the user leaves it.
+        // Vaults with unsaved changes wait for the user to pick a side.
+        if self.active_session().is_some_and(|s| s.external_change_pending && !s.dirty) && !self.has_unsaved_form_input() {
+            self.reload_external_changes();
+        }
+        
+        if self.last_external_check.is_some_and(|t| t.elapsed().as_secs() < EXTERNAL_CHANGE_POLL_SECS) {
+            return;
+        }
+        self.last_external_check = Some(Instant::now());
+        
+        let form_open = self.has_unsaved_form_input();
+        let mut background_results = Vec::new();
+        let mut conflicts = Vec::new();
+        
+        for (index, session) in self.sessions.iter_mut().enumerate() {
+            if session.external_change_pending || !session.changed_on_disk() {
+                continue;
+            }
+            
+            if session.dirty {
+                session.external_change_pending = true;
+                if index == self.active_session {
+                    self.show_external_change_prompt = true;
+                } else {
+                    conflicts.push(session.name());
+                }
+            } else if index != self.active_session {
+                background_results.push((session.name(), session.reload()));
+            } else if form_open {
+                session.external_change_pending = true;
+                self.show_external_change_prompt = true;
+            } else {
+                session.external_change_pending = true;
+            }
+        }
+        
+        if self.active_session().is_some_and(|s| s.external_change_pending && !s.dirty) && !form_open {
+            self.reload_external_changes();
+        }
+        
+        for (name, result) in background_results {
+            match result {
+                Ok(()) => self.toast_info(format!("Vault '{}' updated externally — reloaded", name)),
+                Err(e) => self.toast_error(format!("Vault '{}' changed on disk but could not be reloaded: {}", name, e)),
+            }
+        }
+        for name in conflicts {
+            self.toast_warning(format!("Vault '{}' was changed by another program and has unsaved changes — switch to it to resolve", name));
+        }
+    }
+    
+    /// Reload the active vault from disk after an external modification
+    pub fn reload_external_changes(&mut self) {
+        self.show_external_change_prompt = false;
+        let Some(session) = self.active_session_mut() else {
+            return;
+        };
+        
+        match session.reload() {
+            Ok(()) => self.toast_info("Vault updated externally — reloaded"),
+            Err(e) => self.toast_error(format!("Vault changed on disk but could not be reloaded: {}", e)),
+        }
+    }
+    
+    /// Resolve a conflict by writing the in-memory vault over the file on disk
+    pub fn overwrite_external_changes(&mut self) {
+        self.show_external_change_prompt = false;
+        let Some(session) = self.active_session_mut() else {
+            return;
+        };
+        
+        match session.overwrite() {
+            Ok(()) => self.toast_success("Vault saved over the external changes"),
+            Err(e) => self.toast_save_error(e),
+        }
+    }
+
+    /// Entries matching the search, paired with the index of their session.
+    /// Searches every unlocked vault when `search_all_vaults` is set.
+    pub fn filter_entries(&self) -> Vec<(usize, &(String, Entry))> {
+        let searching_all = self.search_all_vaults && !self.search_query.trim().is_empty();
+        
+        let mut entries: Vec<(usize, &(String, Entry))> = self.sessions
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| searching_all || *index == self.active_session)
+            .flat_map(|(index, session)| {
+                let query = session.search_index.prepare(&self.search_query, self.search_include_notes, self.search_scope);
+                session.entries
+                    .iter()
+                    .filter(move |(id, _)| session.search_index.matches(id, &query))
+                    .map(move |item| (index, item))
+            })
+            .collect();
+        // Each session's list is already sorted by name
+        if self.entry_sort != EntrySort::Name {
+            let sort = self.entry_sort;
+            entries.sort_by(|(_, (a_id, a)), (_, (b_id, b))| sort.compare((a_id, a), (b_id, b)));
+        }
+        entries
+    }
+    
+    /// Bring a linked entry into view: the search is cleared so that it is
+    /// listed, and the list scrolls to its card
+    pub fn focus_entry(&mut self, session: usize, id: &str) {
+        self.search_query.clear();
+        self.scroll_to_entry = Some((session, id.to_string()));
+    }
+    
+    /// Open the whole of a long note (`attachment` `None`) or an attachment
+    /// on the entry's card, or close it if it is open
+    pub fn toggle_full_text(&mut self, session: usize, id: &str, entry: &Entry, attachment: Option<&str>) {
+        let text = match attachment {
+            None => entry.note.as_deref(),
+            Some(name) => entry.attachments.iter().find(|a| a.name == name).map(|a| a.content.as_str()),
+        };
+        // Reopened rather than closed if the text changed since
+        let open = self.full_text.as_ref()
+            .is_some_and(|full| full.shows(session, id, attachment) && Some(full.len) == text.map(str::len));
+        self.full_text = text.filter(|_| !open).map(|text| FullText::new(session, id, attachment, text));
+    }
+    
+    /// Trashed entries the current search would match if the trash were
+    /// included; zero when it already is
+    pub fn trashed_matches(&self) -> usize {
+        if self.search_scope == SearchScope::IncludeTrash || self.search_query.trim().is_empty() {
+            return 0;
+        }
+        self.sessions
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| self.search_all_vaults || *index == self.active_session)
+            .map(|(_, session)| {
+                let query = session.search_index.prepare(&self.search_query, self.search_include_notes, self.search_scope);
+                session.search_index.trashed_matches(&query)
+            })
+            .sum()
+    }
+    
+    /// Show an entry's password, asking for the master password first
+    /// when `security.reveal_requires_password` is set
+    pub fn request_reveal(&mut self, session: usize, id: &str) {
+        // The demo vault has no master password to ask for
+        let in_memory = self.sessions.get(session).is_some_and(|vault_session| vault_session.in_memory);
+        if get_config().security.reveal_requires_password && !in_memory {
+            self.pending_reveal = Some((session, id.to_string()));
+            return;
+        }
+        if let Some(vault_session) = self.sessions.get_mut(session) {
+            vault_session.set_revealed(id, true);
+        }
+        self.record_use(session, id);
+    }
+    
+    /// Forget reveal and PIN prompts, opened high-security entries, peeks,
+    /// a pending "Open & copy", the copy sequence position and a conflicted
+    /// copy summary; session indices are about to change
+    fn clear_reveal_state(&mut self) {
+        self.pending_reveal = None;
+        self.reveal_password.zeroize();
+        self.reveal_show_password = false;
+        self.clear_pin_prompt();
+        self.opened_sealed = None;
+        self.password_qr = None;
+        self.entry_menu = None;
+        self.password_peek = None;
+        self.open_copy = None;
+        self.copy_sequence = None;
+        self.conflict_preview = None;
+        self.scroll_to_entry = None;
+        self.full_text = None;
+    }
+    
+    /// Hide passwords that have been on screen longer than `ui.reveal_timeout_secs`
+    fn expire_reveals(&mut self, ctx: &egui::Context) {
+        let timeout_secs = get_config().ui.reveal_timeout_secs;
+        if timeout_secs == 0 {
+            return;
+        }
+        let timeout = std::time::Duration::from_secs(timeout_secs);
+        let next = self.sessions.iter_mut().filter_map(|session| session.expire_reveals(timeout)).min();
+        if let Some(next) = next {
+            ctx.request_repaint_after(next);
+        }
+    }
+    
+    /// Count a copy or reveal; saved lazily with the session's next write
+    pub fn record_use(&mut self, session: usize, id: &str) {
+        if let Some(session) = self.sessions.get_mut(session) {
+            session.record_use(id);
         }
     }
 
@@ -340,20 +1764,34 @@ impl PassmanApp {
             return Err("Password must be at least 8 characters long!".into());
         }
 
-        VaultManager::init(&self.init_password, Some(&self.vault_file))
+        let key = VaultManager::init(&self.init_password, Some(&self.vault_file))
             .map_err(|e| e.to_string())?;
 
-        *self.master_password = self.init_password.to_string();
-        self.vault = Some(Vault::new());
-        self.load_entries();
-        self.current_screen = Screen::Main;
-        *self.init_password = String::new();
-        *self.init_confirm = String::new();
+        let sync_warning = VaultManager::sync_folder_warning(Some(&self.vault_file));
+        let master_check = MasterPasswordCheck::new(&self.init_password).ok();
+        self.open_session(Vault::new(), self.vault_file.clone(), key);
+        if let Some(session) = self.active_session_mut() {
+            session.sync_folder_warning = sync_warning;
+            session.master_check = master_check;
+        }
+        self.vault_scan = None;
+        self.last_activity = Some(Instant::now());
+        self.init_password.zeroize();
+        self.init_confirm.zeroize();
+        self.init_show_password = false;
 
         Ok(())
     }
 
+    /// Start unlocking the vault in the background.
+    ///
+    /// Returns an error only for problems detected before key derivation;
+    /// the outcome of the unlock itself is reported by `poll_pending_unlock`.
     pub fn login(&mut self) -> Result<(), String> {
+        if self.pending_unlock.is_some() {
+            return Ok(());
+        }
+        
         if self.login_password.trim().is_empty() {
             return Err("Please enter your master password".into());
         }
@@ -363,36 +1801,157 @@ impl PassmanApp {
             return Err(format!("Account locked. Please wait {} seconds.", remaining));
         }
 
-        match VaultManager::load(&self.login_password, Some(&self.vault_file)) {
-            Ok(vault) => {
+        let password = self.login_password.clone();
+        let vault_file = self.vault_file.clone();
+        let (sender, receiver) = mpsc::channel();
+        
+        std::thread::spawn(move || {
+            let result = VaultManager::load_with_key(&password, Some(&vault_file))
+                .map_err(|e| e.to_string());
+            let _ = sender.send(result);
+        });
+        
+        let pending = PendingUnlock {
+            receiver,
+            vault_file: self.vault_file.clone(),
+            started: Instant::now(),
+            estimate_ms: VaultManager::estimated_unlock_ms(Some(&self.vault_file)),
+        };
+        self.start_loading(pending.progress_message());
+        self.pending_unlock = Some(pending);
+        
+        Ok(())
+    }
+    
+    /// Check for a newer release in the background; only hits the network
+    /// once a week
+    fn spawn_update_check() -> mpsc::Receiver<Option<UpdateNotice>> {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let notice = update::check_if_due(&NetPolicy::current(), env!("CARGO_PKG_VERSION"))
+                .unwrap_or_else(|e| {
+                    log::warn!("Update check failed: {}", e);
+                    None
+                });
+            let _ = sender.send(notice);
+        });
+        receiver
+    }
+    
+    /// Fetch the favicon for the entry being edited, from its URL
+    pub fn start_favicon_fetch(&mut self) {
+        let url = self.active_vault()
+            .and_then(|vault| vault.get_entry(&self.edit_id))
+            .and_then(|entry| entry.url.clone());
+        match url {
+            Some(url) if !url.trim().is_empty() => self.favicon_fetch = Some(icons::spawn_favicon_fetch(&url)),
+            _ => self.toast_error("This entry has no URL to fetch a favicon from"),
+        }
+    }
+    
+    fn poll_favicon_fetch(&mut self, ctx: &egui::Context) {
+        let Some(receiver) = &self.favicon_fetch else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(result) => {
+                self.favicon_fetch = None;
+                match result {
+                    Ok(icon) => {
+                        self.edit_icon = Some(icon);
+                        self.toast_success("Favicon downloaded — update the entry to keep it");
+                    }
+                    Err(e) => self.toast_error(format!("Could not fetch favicon: {}", e)),
+                }
+            }
+            Err(mpsc::TryRecvError::Empty) => ctx.request_repaint_after(std::time::Duration::from_millis(250)),
+            Err(mpsc::TryRecvError::Disconnected) => self.favicon_fetch = None,
+        }
+    }
+    
+    fn poll_update_check(&mut self, ctx: &egui::Context) {
+        let Some(receiver) = &self.update_check else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(notice) => {
+                self.update_notice = notice;
+                self.update_check = None;
+            }
+            Err(mpsc::TryRecvError::Empty) => ctx.request_repaint_after(std::time::Duration::from_secs(1)),
+            Err(mpsc::TryRecvError::Disconnected) => self.update_check = None,
+        }
+    }
+    
+    /// Check whether a background unlock has finished
+    fn poll_pending_unlock(&mut self) {
+        let Some(pending) = &self.pending_unlock else {
+            return;
+        };
+        
+        match pending.receiver.try_recv() {
+            Ok(result) => {
+                let vault_file = pending.vault_file.clone();
+                self.pending_unlock = None;
+                self.stop_loading();
+                self.finish_login(result, vault_file);
+            }
+            Err(mpsc::TryRecvError::Empty) => {
+                self.loading_message = pending.progress_message();
+            }
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.pending_unlock = None;
+                self.stop_loading();
+                self.login_password.zeroize();
+                self.toast_error("Unlock failed unexpectedly");
+            }
+        }
+    }
+    
+    fn finish_login(&mut self, result: Result<(Vault, VaultKey), String>, vault_file: String) {
+        match result {
+            Ok((vault, key)) => {
                 self.security_manager.record_successful_login();
-                *self.master_password = self.login_password.to_string();
-                self.vault = Some(vault);
-                self.load_entries();
-                self.current_screen = Screen::Main;
-                *self.login_password = String::new();
+                let warning = VaultManager::master_password_warning(&self.login_password, Some(&vault_file));
+                let sync_warning = VaultManager::sync_folder_warning(Some(&vault_file));
+                let permissions_warning = VaultManager::loose_permissions(Some(&vault_file));
+                let conflicted_copies = VaultManager::conflicted_copies(Some(&vault_file));
+                let master_check = MasterPasswordCheck::new(&self.login_password).ok();
+                self.open_session(vault, vault_file, key);
+                if let Some(session) = self.active_session_mut() {
+                    session.master_password_warning = warning;
+                    session.master_check = master_check;
+                    session.sync_folder_warning = sync_warning;
+                    session.permissions_warning = permissions_warning;
+                    session.conflicted_copies = conflicted_copies;
+                }
+                self.login_password.zeroize();
+                self.login_show_password = false;
+                // Unlocking pinned the fingerprint; read it again next time
+                self.login_identity = None;
                 self.last_activity = Some(Instant::now());
-                Ok(())
+                self.toast_success("Vault opened successfully!");
             }
             Err(e) => {
                 self.security_manager.record_failed_attempt();
-                *self.login_password = String::new();
+                self.login_password.zeroize();
                 
                 if self.security_manager.is_locked_out() {
                     let remaining = self.security_manager.lockout_remaining_secs();
-                    Err(format!("Too many failed attempts. Locked for {} seconds.", remaining))
+                    self.toast_error(format!("Too many failed attempts. Locked for {} seconds.", remaining));
                 } else {
                     let remaining_attempts = self.security_manager.remaining_attempts();
-                    Err(format!("{} ({} attempts remaining)", e, remaining_attempts))
+                    self.toast_error(format!("{} ({} attempts remaining)", e, remaining_attempts));
                 }
             }
         }
     }
 
     pub fn add_entry(&mut self) -> Result<(), String> {
-        if let Some(vault) = &mut self.vault {
-            if self.add_id.trim().is_empty() {
-                return Err("Entry ID cannot be empty!".into());
+        if let Some(session) = self.sessions.get_mut(self.active_session) {
+            let vault = &mut session.vault;
+            if let Some(problem) = vault.new_id_problem(&self.add_id) {
+                return Err(problem);
             }
             if self.add_username.trim().is_empty() {
                 return Err("Username cannot be empty!".into());
@@ -400,15 +1959,18 @@ impl PassmanApp {
             if !self.generate_password && self.add_password.trim().is_empty() {
                 return Err("Password cannot be empty!".into());
             }
-
-            if vault.get_entry(&self.add_id).is_some() {
-                return Err(format!("Entry '{}' already exists!", self.add_id));
+            let holds_master = !self.generate_password
+                && session.master_check.as_ref().is_some_and(|check| check.matches(&self.add_password));
+            if holds_master && !self.master_reuse_confirmed {
+                self.master_reuse_warning = Some(EntryForm::Add);
+                return Ok(());
             }
 
-            let password = if self.generate_password {
-                generate_password(self.password_length)
+            let (password, generation) = if self.generate_password {
+                let (password, prefs) = Self::generate_for_entry(self.generator_mode, self.password_length, &self.generation_rules, self.pronounceable_syllables)?;
+                (password, Some(prefs))
             } else {
-                self.add_password.clone()
+                (self.add_password.to_string(), None)
             };
 
             let note = if self.add_note.is_empty() {
@@ -417,14 +1979,25 @@ impl PassmanApp {
                 Some(self.add_note.clone())
             };
 
-            let entry = Entry::new(self.add_username.clone(), password, note);
+            let mut entry = Entry::new(self.add_username.clone(), password, note);
+            entry.url = Some(self.add_url.trim().to_string()).filter(|url| !url.is_empty());
+            if !self.add_totp.is_empty() {
+                entry.totp_secret = OptionalSecret::some(self.add_totp.to_string());
+            }
+            entry.generation = generation;
+            entry.holds_master_password = holds_master;
+            entry.created_by = identity();
+            if let Some(template) = self.add_template.as_ref().and_then(|name| get_config().templates.get(name).cloned()) {
+                template.apply(&self.add_id, &mut entry);
+            }
             vault.add_entry(self.add_id.clone(), entry);
 
-            VaultManager::save(vault, &self.master_password, Some(&self.vault_file))
-                .map_err(|e| e.to_string())?;
+            self.commit_changes()?;
+            if self.generate_password {
+                self.mark_getting_started(GettingStartedStep::Generator);
+            }
 
-            self.load_entries();
-            self.current_screen = Screen::Main;
+            self.navigate_back();
             self.clear_add_form();
 
             Ok(())
@@ -433,148 +2006,1475 @@ impl PassmanApp {
         }
     }
 
-    pub fn remove_entry(&mut self, id: &str) -> Result<(), String> {
-        if let Some(vault) = &mut self.vault {
-            vault.remove_entry(id).ok_or("Entry not found")?;
-
-            VaultManager::save(vault, &self.master_password, Some(&self.vault_file))
-                .map_err(|e| e.to_string())?;
+    /// Copy an entry under "<id> (copy)" and open the copy for editing,
+    /// set to generate a fresh password when saved
+    pub fn duplicate_entry(&mut self, id: &str) -> Result<(), String> {
+        let session = self.active_session_mut().ok_or("No vault loaded")?;
+        let mut copy = session.vault.get_entry(id).map(Entry::duplicate).ok_or("Entry not found")?;
+        copy.created_by = identity();
+        let new_id = session.vault.copy_id(id);
+        session.vault.add_entry(new_id.clone(), copy);
+        self.commit_changes()?;
+        
+        self.start_edit_entry(&new_id);
+        self.edit_generate_password = true;
+        Ok(())
+    }
 
-            self.load_entries();
-            Ok(())
+    pub fn remove_entry(&mut self, id: &str) -> Result<(), String> {
+        if let Some(session) = self.active_session_mut() {
+            let entry = session.vault.remove_entry(id).ok_or("Entry not found")?;
+            self.last_deleted = Some((session.vault_file.clone(), id.to_string(), entry));
+            self.commit_changes()
         } else {
             Err("No vault loaded".into())
         }
     }
+    
+    /// Put back the entry deleted last, if its vault is the active one and
+    /// the id is still free. Returns the id.
+    pub fn undo_delete(&mut self) -> Result<String, String> {
+        let (vault_file, id, entry) = self.last_deleted.take().ok_or("Nothing to undo")?;
+        let session = self.active_session_mut().ok_or("No vault loaded")?;
+        if session.vault_file != vault_file {
+            return Err("The deleted entry belongs to another vault; switch to it first".into());
+        }
+        if session.vault.contains(&id) {
+            return Err(format!("An entry named '{}' was added since; rename it first", id));
+        }
+        session.vault.add_entry(id.clone(), entry);
+        self.commit_changes()?;
+        Ok(id)
+    }
+
+    /// Archive or unarchive an entry in the active vault
+    pub fn set_entry_archived(&mut self, id: &str, archived: bool) -> Result<(), String> {
+        let session = self.active_session_mut().ok_or("No vault loaded")?;
+        if !session.vault.set_archived(id, archived, chrono::Utc::now(), identity()) {
+            return Err("Entry not found".into());
+        }
+        self.commit_changes()
+    }
 
     pub fn clear_add_form(&mut self) {
         self.add_id.clear();
         self.add_username.clear();
-        self.add_password.clear();
+        self.add_url.clear();
+        self.add_id_suggestion = None;
+        self.add_password.zeroize();
         self.add_note.clear();
+        self.add_totp.zeroize();
+        self.add_inferred.clear();
+        self.add_hints.clear();
         self.generate_password = false;
         self.add_show_password = false;
+        self.generation_rules = Self::default_generation_rules();
+        self.show_generation_options = false;
+        self.generator_mode = GeneratorMode::Random;
+        self.add_template = None;
         self.password_strength.clear();
+        if self.master_reuse_warning == Some(EntryForm::Add) {
+            self.master_reuse_warning = None;
+        }
         self.password_suggestions.clear();
     }
 
-    pub fn start_edit_entry(&mut self, id: &str) {
-        if let Some(vault) = &self.vault {
-            if let Some(entry) = vault.get_entry(id) {
-                self.edit_id = id.to_string();
-                self.edit_username = entry.username.clone();
-                self.edit_password = entry.password_str().to_string();
-                self.edit_note = entry.note.clone().unwrap_or_default();
-                self.current_screen = Screen::EditEntry(id.to_string());
+    /// "New from clipboard": take the clipboard's text, clearing it, and
+    /// open the add form filled with what looks like credentials in it.
+    /// Nothing is saved until the form is reviewed and added.
+    pub fn new_from_clipboard(&mut self) {
+        let text = match self.secure_clipboard.take_text() {
+            Ok(text) => text,
+            Err(e) => {
+                self.toast_error(e.to_string());
+                return;
             }
+        };
+        let found = parse_credentials(&text);
+        if found.is_empty() {
+            self.toast_info("No credentials found on the clipboard");
+            return;
+        }
+        
+        self.clear_add_form();
+        self.add_inferred = found.fields();
+        self.add_username = found.username.unwrap_or_default();
+        self.add_url = found.url.unwrap_or_default();
+        self.add_note = found.note.unwrap_or_default();
+        if let Some(password) = found.password {
+            self.add_password = password;
         }
+        if let Some(secret) = found.totp_secret {
+            self.add_totp = secret;
+        }
+        self.check_add_url();
+        self.suggest_add_id();
+        self.navigate(Screen::AddEntry);
+        self.toast_info("Filled in from the clipboard, which is now cleared. Check the highlighted fields.");
+    }
+    
+    /// Stop highlighting a pasted field once it has been edited
+    pub fn mark_add_field_reviewed(&mut self, field: PasteField) {
+        self.add_inferred.retain(|inferred| *inferred != field);
+    }
+    
+    /// Offer to move an otpauth link out of the password field
+    pub fn check_add_password(&mut self) {
+        let is_link = parse_otpauth(&self.add_password).is_some();
+        self.set_add_hint(AddFormHint::OtpAuthPassword, is_link);
     }
 
-    pub fn update_entry(&mut self) -> Result<(), String> {
-        if let Some(vault) = &mut self.vault {
-            if self.edit_username.trim().is_empty() {
-                return Err("Username cannot be empty!".into());
-            }
-            if !self.edit_generate_password && self.edit_password.trim().is_empty() {
-                return Err("Password cannot be empty!".into());
-            }
-
-            let password = if self.edit_generate_password {
-                generate_password(self.password_length)
-            } else {
-                self.edit_password.clone()
-            };
-
-            let note = if self.edit_note.trim().is_empty() {
-                None
-            } else {
-                Some(self.edit_note.clone())
-            };
-
-            if let Some(existing_entry) = vault.get_entry(&self.edit_id) {
-                let updated_entry = Entry {
-                    username: self.edit_username.clone(),
-                    password: password.into(),
-                    note,
-                    created_at: existing_entry.created_at,
-                    modified_at: chrono::Utc::now(),
-                    tags: existing_entry.tags.clone(),
-                    url: existing_entry.url.clone(),
-                    totp_secret: existing_entry.totp_secret.clone(),
-                };
-                
-                vault.add_entry(self.edit_id.clone(), updated_entry);
-            } else {
-                return Err("Entry not found".into());
-            }
+    /// Offer to drop the URL's query string and fragment
+    pub fn check_add_url(&mut self) {
+        self.add_hints.retain(|hint| hint.field() != "add_url");
+        if let Some(stripped) = strip_url_tracking(&self.add_url) {
+            self.add_hints.push(AddFormHint::TrackedUrl { stripped });
+        }
+    }
 
-            VaultManager::save(vault, &self.master_password, Some(&self.vault_file))
-                .map_err(|e| e.to_string())?;
+    /// Offer to move an email address typed as the ID to the empty username
+    pub fn check_add_id(&mut self) {
+        let is_email = is_email(self.add_id.trim()) && self.add_username.trim().is_empty();
+        self.set_add_hint(AddFormHint::EmailId, is_email);
+    }
 
-            self.load_entries();
-            self.current_screen = Screen::Main;
-            self.clear_edit_form();
-            Ok(())
-        } else {
-            Err("No vault loaded".into())
+    fn set_add_hint(&mut self, hint: AddFormHint, shown: bool) {
+        self.add_hints.retain(|other| *other != hint);
+        if shown {
+            self.add_hints.push(hint);
         }
     }
 
-    pub fn clear_edit_form(&mut self) {
-        self.edit_id.clear();
-        self.edit_username.clear();
-        self.edit_password.clear();
-        self.edit_note.clear();
-        self.edit_generate_password = false;
-        self.edit_show_password = false;
-        self.password_strength.clear();
-        self.password_suggestions.clear();
+    pub fn dismiss_add_hint(&mut self, hint: &AddFormHint) {
+        self.add_hints.retain(|other| other != hint);
     }
-    
-    // === Keyboard Shortcuts ===
-    
-    fn handle_keyboard_shortcuts(&mut self, ctx: &egui::Context) {
-        ctx.input(|i| {
-            if self.vault.is_some() {
-                // Ctrl+N - New entry
-                if i.modifiers.ctrl && i.key_pressed(egui::Key::N) && self.current_screen == Screen::Main {
-                    self.current_screen = Screen::AddEntry;
-                    self.clear_add_form();
-                }
-                
-                // Ctrl+F - Focus search
-                if i.modifiers.ctrl && i.key_pressed(egui::Key::F) && self.current_screen == Screen::Main {
-                    self.request_search_focus = true;
-                }
-                
-                // Ctrl+L - Lock vault
-                if i.modifiers.ctrl && i.key_pressed(egui::Key::L) {
-                    self.lock_vault();
-                    self.toast_info("Vault locked".to_string());
+
+    /// Make the change a hint offers
+    pub fn accept_add_hint(&mut self, hint: &AddFormHint) {
+        self.dismiss_add_hint(hint);
+        match hint {
+            AddFormHint::OtpAuthPassword => {
+                let Some(link) = parse_otpauth(&self.add_password) else {
+                    return;
+                };
+                if let Some(secret) = link.secret {
+                    self.add_totp = secret;
                 }
-                
-                // Ctrl+H - Health dashboard
-                if i.modifiers.ctrl && i.key_pressed(egui::Key::H) && self.current_screen == Screen::Main {
-                    self.current_screen = Screen::HealthDashboard;
+                self.add_password.zeroize();
+                self.password_strength.clear();
+                self.password_suggestions.clear();
+                if let Some(account) = link.account.filter(|_| self.add_username.trim().is_empty()) {
+                    self.add_username = account;
+                    self.clear_form_error("add_username");
                 }
-                
-                // Ctrl+S - Settings
-                if i.modifiers.ctrl && i.key_pressed(egui::Key::S) && self.current_screen == Screen::Main {
-                    self.current_screen = Screen::Settings;
+                let issuer = link.issuer.map(|issuer| issuer.trim().to_lowercase()).filter(|issuer| !issuer.is_empty() && self.add_id.is_empty());
+                match issuer.and_then(|issuer| Some(unused_id(self.active_vault()?, &issuer))) {
+                    Some(id) => self.add_id_suggestion = Some(id),
+                    None => self.suggest_add_id(),
                 }
             }
-            
-            // Escape - Go back
-            if i.key_pressed(egui::Key::Escape) {
-                match &self.current_screen {
-                    Screen::AddEntry | Screen::EditEntry(_) | Screen::Settings | 
-                    Screen::HealthDashboard | Screen::ImportExport => {
-                        self.current_screen = Screen::Main;
-                    }
-                    _ => {}
-                }
+            AddFormHint::TrackedUrl { stripped } => {
+                let original = std::mem::replace(&mut self.add_url, stripped.clone());
+                self.add_hints.push(AddFormHint::TrimmedUrl { original });
+                self.suggest_add_id();
             }
-        });
+            AddFormHint::TrimmedUrl { original } => {
+                self.add_url = original.clone();
+                self.suggest_add_id();
+            }
+            AddFormHint::EmailId => {
+                self.add_username = std::mem::take(&mut self.add_id).trim().to_string();
+                self.clear_form_error("add_id");
+                self.clear_form_error("add_username");
+                self.suggest_add_id();
+            }
+        }
+    }
+    
+    /// Pre-fill the add form from a saved template; `{id}` stays unexpanded
+    /// until the entry is added so it picks up the final ID.
+    pub fn apply_add_template(&mut self, name: &str) {
+        let Some(template) = get_config().templates.get(name).cloned() else {
+            return;
+        };
+        
+        if !template.username.is_empty() {
+            self.add_username = template.username.clone();
+        }
+        if !template.note.is_empty() {
+            self.add_note = template.note.clone();
+        }
+        match template.generation {
+            Some(prefs) => {
+                self.generate_password = true;
+                self.password_length = prefs.length;
+                self.generation_rules = prefs.rules;
+            }
+            None => {
+                self.generation_rules = Self::default_generation_rules();
+            }
+        }
+        self.add_template = Some(name.to_string());
+    }
+
+    pub fn start_edit_entry(&mut self, id: &str) {
+        if let Some(vault) = self.active_vault() {
+            if let Some(entry) = vault.get_entry(id).cloned() {
+                self.edit_id = id.to_string();
+                self.edit_username = entry.username.clone();
+                self.edit_password = Zeroizing::new(entry.password_str().reveal().to_string());
+                self.edit_note = entry.note.clone().unwrap_or_default();
+                self.edit_icon = entry.icon.clone();
+                self.edit_expires = entry.expires_at.map(|at| at.date_naive());
+                self.edit_copy_sequence = entry.copy_sequence.iter().map(CopyStep::spec).collect::<Vec<_>>().join("\n");
+                self.edit_linked_ids = entry.linked_ids.clone();
+                self.edit_link_filter.clear();
+                self.edit_snapshot = Some(entry.modified_at);
+                self.edit_conflict = None;
+                self.edit_was_sealed = entry.is_sealed();
+                self.edit_seal = entry.is_sealed();
+                
+                // Regenerating reuses the rules this site was generated with
+                if let Some(prefs) = &entry.generation {
+                    self.generator_mode = prefs.mode.into();
+                    match prefs.mode {
+                        GenerationMode::Random => self.password_length = prefs.length,
+                        GenerationMode::Pronounceable => self.pronounceable_syllables = prefs.length,
+                        GenerationMode::Memorable => {}
+                    }
+                    self.generation_rules = prefs.rules.clone();
+                    self.show_generation_options = true;
+                }
+                self.edit_generation = entry.generation.clone();
+                self.navigate(Screen::EditEntry(id.to_string()));
+            }
+        }
+    }
+
+    /// Put a new password in the edit form, generated the way the entry's
+    /// password last was
+    pub fn regenerate_with_saved_rules(&mut self) {
+        let Some(prefs) = self.edit_generation.clone() else {
+            return;
+        };
+        match prefs.generate() {
+            Ok(password) => {
+                self.edit_password = Zeroizing::new(password);
+                self.edit_generate_password = false;
+                self.clear_form_error("edit_password");
+                self.mark_getting_started(GettingStartedStep::Generator);
+                self.toast_success(format!("New password generated ({})", prefs.describe()));
+            }
+            Err(e) => self.toast_error(e),
+        }
+    }
+
+    /// Whether `password` is the active vault's master password, going by
+    /// the session's salted hash. The answer is never logged.
+    fn is_master_password(&self, password: &str) -> bool {
+        self.active_session()
+            .and_then(|session| session.master_check.as_ref())
+            .is_some_and(|check| check.matches(password))
+    }
+
+    /// Save the edit form, unless the entry changed since editing started;
+    /// then `edit_conflict` is set and nothing is written until resolved.
+    /// A password that is the master password waits for confirmation in
+    /// `master_reuse_warning` first.
+    pub fn update_entry(&mut self) -> Result<(), String> {
+        if self.edit_username.trim().is_empty() {
+            return Err("Username cannot be empty!".into());
+        }
+        if !self.edit_was_sealed && !self.edit_generate_password && self.edit_password.trim().is_empty() {
+            return Err("Password cannot be empty!".into());
+        }
+        // A sealed entry's password isn't in the form, so its flag is kept
+        let holds_master = !self.edit_was_sealed && !self.edit_generate_password && self.is_master_password(&self.edit_password);
+        if holds_master && !self.master_reuse_confirmed {
+            self.master_reuse_warning = Some(EntryForm::Edit);
+            return Ok(());
+        }
+        self.edit_holds_master = holds_master;
+
+        let vault = self.active_vault().ok_or("No vault loaded")?;
+
+        let current = vault.get_entry(&self.edit_id).cloned();
+        let conflict = match &current {
+            None => Some(EditConflict::Removed),
+            Some(entry) if self.edit_snapshot.is_some_and(|at| at != entry.modified_at) => Some(EditConflict::Modified),
+            Some(_) => None,
+        };
+        self.edit_conflict = conflict;
+        if conflict.is_some() {
+            return Ok(());
+        }
+        self.save_edit_as(self.edit_id.clone(), current)
+    }
+
+    /// Settle a conflict raised by `update_entry`
+    pub fn resolve_edit_conflict(&mut self, resolution: ConflictResolution) -> Result<(), String> {
+        self.edit_conflict = None;
+        let vault = self.active_vault().ok_or("No vault loaded")?;
+        match resolution {
+            ConflictResolution::Overwrite => {
+                let base = vault.get_entry(&self.edit_id).cloned();
+                self.save_edit_as(self.edit_id.clone(), base)
+            }
+            ConflictResolution::SaveAsNew => {
+                let base = vault.get_entry(&self.edit_id).map(|entry| Entry {
+                    created_by: identity(),
+                    ..entry.duplicate()
+                });
+                let new_id = if base.is_some() { vault.copy_id(&self.edit_id) } else { self.edit_id.clone() };
+                self.save_edit_as(new_id, base)
+            }
+            ConflictResolution::Discard => {
+                self.navigate_back();
+                self.clear_edit_form();
+                Ok(())
+            }
+        }
+    }
+
+    /// Write the edit form over `base` (fields the form doesn't show are
+    /// kept) as entry `id`, then leave the edit screen. A high-security
+    /// entry's password and note aren't in the form and stay sealed.
+    fn save_edit_as(&mut self, id: String, base: Option<Entry>) -> Result<(), String> {
+        let mut entry = base.unwrap_or_else(|| Entry {
+            created_by: identity(),
+            ..Entry::new(String::new(), String::new(), None)
+        });
+        if !self.edit_was_sealed {
+            let (password, generation) = if self.edit_generate_password {
+                self.mark_getting_started(GettingStartedStep::Generator);
+                let (password, prefs) = Self::generate_for_entry(self.generator_mode, self.password_length, &self.generation_rules, self.pronounceable_syllables)?;
+                (password, Some(prefs))
+            } else {
+                (self.edit_password.to_string(), None)
+            };
+            entry.password = password.into();
+            entry.note = Some(self.edit_note.clone()).filter(|note| !note.trim().is_empty());
+            entry.generation = generation.or(entry.generation);
+            entry.holds_master_password = self.edit_holds_master;
+        }
+        match (self.edit_was_sealed, self.edit_seal) {
+            (false, true) => entry.seal(&self.edit_pin).map_err(|e| e.to_string())?,
+            (true, false) => entry.unseal(&self.edit_pin).map_err(|e| e.to_string())?,
+            _ => {}
+        }
+        entry.username = self.edit_username.clone();
+        entry.modified_at = chrono::Utc::now();
+        entry.modified_by = identity();
+        entry.icon = self.edit_icon.clone();
+        entry.expires_at = self.edit_expires.map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc());
+        entry.copy_sequence = CopyStep::parse_lines(&self.edit_copy_sequence)?;
+        entry.linked_ids = self.edit_linked_ids.clone();
+
+        let session = self.active_session_mut().ok_or("No vault loaded")?;
+        // Saving as a new entry, or over one removed meanwhile, adds rather than updates
+        let replaced = session.vault.add_entry(id.clone(), entry);
+        crate::log_security!("Entry {} in GUI: {}", if replaced { "updated" } else { "added" }, id);
+        self.commit_changes()?;
+
+        self.navigate_back();
+        self.clear_edit_form();
+        Ok(())
+    }
+
+
+    pub fn clear_edit_form(&mut self) {
+        self.edit_id.clear();
+        self.edit_username.clear();
+        self.edit_password.zeroize();
+        self.edit_note.clear();
+        self.edit_generate_password = false;
+        self.edit_show_password = false;
+        self.edit_icon = None;
+        self.edit_expires = None;
+        self.edit_copy_sequence.clear();
+        self.edit_linked_ids.clear();
+        self.edit_link_filter.clear();
+        self.edit_snapshot = None;
+        self.edit_conflict = None;
+        self.edit_was_sealed = false;
+        self.edit_seal = false;
+        self.edit_pin.zeroize();
+        self.edit_pin_confirm.zeroize();
+        self.edit_generation = None;
+        self.edit_holds_master = false;
+        if self.master_reuse_warning == Some(EntryForm::Edit) {
+            self.master_reuse_warning = None;
+        }
+        self.show_icon_picker = false;
+        self.favicon_fetch = None;
+        self.password_length = get_config().password.default_length;
+        self.generation_rules = Self::default_generation_rules();
+        self.show_generation_options = false;
+        self.generator_mode = GeneratorMode::Random;
+        self.password_strength.clear();
+        self.password_suggestions.clear();
+    }
+    
+    // === Idle Detection ===
+    
+    /// Whether this frame contains deliberate user input. Held keys and
+    /// key-repeat are ignored so a stuck key cannot keep the vault open.
+    fn has_user_activity(ctx: &egui::Context) -> bool {
+        ctx.input(|i| {
+            i.pointer.is_moving()
+                || i.pointer.any_click()
+                || i.raw_scroll_delta != egui::Vec2::ZERO
+                || i.events.iter().any(|event| matches!(
+                    event,
+                    egui::Event::Key { pressed: true, repeat: false, .. }
+                ))
+        })
+    }
+    
+    /// In lenient mode, typing into an entry form pauses the timer
+    fn is_idle_timer_paused(&self, ctx: &egui::Context) -> bool {
+        self.lock_timeout_behavior == LockTimeoutBehavior::Lenient
+            && matches!(self.current_screen, Screen::AddEntry | Screen::EditEntry(_))
+            && ctx.memory(|m| m.focused().is_some())
+    }
+    
+    /// Seconds until the idle timeout locks all vaults
+    pub fn lock_remaining_secs(&self) -> Option<u64> {
+        if self.lock_timeout_secs == 0 {
+            return None;
+        }
+        let elapsed = self.last_activity?.elapsed().as_secs();
+        Some(self.lock_timeout_secs.saturating_sub(elapsed))
+    }
+    
+    // === Navigation ===
+    
+    /// Where Back ends up when there is nothing left to go back to
+    fn home_screen(&self) -> Screen {
+        if self.is_unlocked() { Screen::Main } else { Screen::Welcome }
+    }
+    
+    /// Whether Back may land on `screen`: the screens inside a vault need
+    /// one unlocked, and an entry being edited must still exist
+    fn can_return_to(&self, screen: &Screen) -> bool {
+        match screen {
+            Screen::Welcome | Screen::Init | Screen::Login | Screen::Settings => true,
+            Screen::EditEntry(id) => self.is_unlocked() && self.active_vault().is_some_and(|vault| vault.get_entry(id).is_some()),
+            Screen::Main | Screen::AddEntry | Screen::HealthDashboard | Screen::ImportExport => self.is_unlocked(),
+        }
+    }
+    
+    /// Go to `screen`, remembering the current one for Back. Going to a
+    /// screen that is already in the history goes back to it instead.
+    pub fn navigate(&mut self, screen: Screen) {
+        if screen == self.current_screen {
+            return;
+        }
+        match self.screen_history.iter().position(|previous| *previous == screen) {
+            Some(index) => self.screen_history.truncate(index),
+            None => self.screen_history.push(self.current_screen.clone()),
+        }
+        self.current_screen = screen;
+    }
+    
+    /// Back button and Escape: discard the form on this screen and return
+    /// to the latest screen in the history that can still be shown, else
+    /// to the vault list or the welcome screen
+    pub fn navigate_back(&mut self) {
+        match self.current_screen {
+            Screen::AddEntry => self.clear_add_form(),
+            Screen::EditEntry(_) => {
+                self.clear_edit_form();
+                self.clear_form_errors();
+            }
+            Screen::Init => {
+                self.init_password.zeroize();
+                self.init_confirm.zeroize();
+                self.init_show_password = false;
+            }
+            Screen::Login => {
+                self.login_password.zeroize();
+                self.login_show_password = false;
+            }
+            _ => {}
+        }
+        while let Some(screen) = self.screen_history.pop() {
+            if self.can_return_to(&screen) {
+                self.current_screen = screen;
+                return;
+            }
+        }
+        self.current_screen = self.home_screen();
+    }
+    
+    /// Start over at `screen` with an empty history, as on unlock and lock
+    pub fn navigate_root(&mut self, screen: Screen) {
+        self.screen_history.clear();
+        self.current_screen = screen;
+    }
+    
+    // === Keyboard Shortcuts ===
+    
+    fn handle_keyboard_shortcuts(&mut self, ctx: &egui::Context) {
+        // Ctrl+K - Command palette; while it is open it owns the keyboard
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::K)) {
+            self.command_palette = match self.command_palette {
+                Some(_) => None,
+                None => Some(CommandPalette::default()),
+            };
+        }
+        if self.command_palette.is_some() || self.entry_menu.is_some() {
+            return;
+        }
+        
+        ctx.input(|i| {
+            if self.is_unlocked() {
+                // Ctrl+N - New entry, Ctrl+Shift+N - New entry from the clipboard
+                if i.modifiers.ctrl && i.modifiers.shift && i.key_pressed(egui::Key::N) && self.current_screen == Screen::Main {
+                    self.new_from_clipboard();
+                } else if i.modifiers.ctrl && i.key_pressed(egui::Key::N) && self.current_screen == Screen::Main {
+                    self.navigate(Screen::AddEntry);
+                    self.clear_add_form();
+                }
+                
+                // Ctrl+F - Focus search
+                if i.modifiers.ctrl && i.key_pressed(egui::Key::F) && self.current_screen == Screen::Main {
+                    self.request_search_focus = true;
+                }
+                
+                // Ctrl+L - Lock vault, Ctrl+Shift+L - Lock all vaults
+                if i.modifiers.ctrl && i.modifiers.shift && i.key_pressed(egui::Key::L) {
+                    if self.save_dirty_sessions() {
+                        self.lock_all_vaults();
+                        self.toast_info("All vaults locked".to_string());
+                    }
+                } else if i.modifiers.ctrl && i.key_pressed(egui::Key::L) && self.lock_vault() {
+                    self.toast_info("Vault locked".to_string());
+                }
+                
+                // Ctrl+H - Health dashboard
+                if i.modifiers.ctrl && i.key_pressed(egui::Key::H) && self.current_screen == Screen::Main {
+                    self.navigate(Screen::HealthDashboard);
+                }
+                
+                // Ctrl+Shift+S - Save now, Ctrl+S - Settings
+                if i.modifiers.ctrl && i.modifiers.shift && i.key_pressed(egui::Key::S) {
+                    if self.active_session().is_some_and(|s| s.dirty) {
+                        self.save_active_vault();
+                    }
+                } else if i.modifiers.ctrl && i.key_pressed(egui::Key::S) && self.current_screen == Screen::Main {
+                    self.navigate(Screen::Settings);
+                }
+            }
+            
+            // Escape - Go back, except from the vault list and while a
+            // vault is being unlocked
+            if i.key_pressed(egui::Key::Escape) {
+                let can_go_back = match &self.current_screen {
+                    Screen::Main => false,
+                    Screen::Welcome | Screen::Init | Screen::Login => self.pending_unlock.is_none(),
+                    _ => true,
+                };
+                if can_go_back {
+                    self.navigate_back();
+                }
+            }
+        });
+    }
+    
+    // === Reveal Prompt ===
+    
+    /// Ask for the master password before revealing, if a reveal is pending
+    fn show_reveal_prompt(&mut self, ctx: &egui::Context) {
+        let Some((_, entry_id)) = self.pending_reveal.clone() else {
+            return;
+        };
+        let mut confirm = false;
+        let mut cancel = ctx.input(|i| i.key_pressed(egui::Key::Escape));
+        
+        egui::Window::new("🔒 Show Password")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                ui.add_space(SPACING);
+                ui.label(format!("Enter the master password to show the password for '{}'.", entry_id));
+                ui.add_space(SPACING);
+                let field = widgets::password_field(ui, &mut self.reveal_password, &mut self.reveal_show_password, "Master password", 240.0, false);
+                field.response.request_focus();
+                confirm = field.submitted;
+                ui.add_space(SPACING * 2.0);
+                
+                ui.horizontal(|ui| {
+                    if self.primary_button(ui, "Show", [100.0, BUTTON_HEIGHT]).clicked() {
+                        confirm = true;
+                    }
+                    ui.add_space(SPACING);
+                    if self.secondary_button(ui, "Cancel", [100.0, BUTTON_HEIGHT]).clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        
+        if confirm {
+            if let Err(e) = self.confirm_reveal() {
+                self.toast_error(e);
+            }
+        } else if cancel {
+            self.clear_reveal_state();
+        }
+    }
+    
+    /// Check the typed master password and reveal the pending entry
+    fn confirm_reveal(&mut self) -> Result<(), String> {
+        let Some((index, entry_id)) = self.pending_reveal.clone() else {
+            return Ok(());
+        };
+        let typed = std::mem::replace(&mut self.reveal_password, Zeroizing::new(String::new()));
+        let session = self.sessions.get_mut(index).ok_or("Vault is locked")?;
+        
+        if session.security_manager.is_locked_out() {
+            return Err(format!(
+                "Too many failed attempts. Try again in {} seconds.",
+                session.security_manager.lockout_remaining_secs()
+            ));
+        }
+        if !session.key.verify_password(&typed) {
+            session.security_manager.record_failed_attempt();
+            return Err("Incorrect master password".into());
+        }
+        
+        session.security_manager.record_successful_login();
+        session.set_revealed(&entry_id, true);
+        self.record_use(index, &entry_id);
+        self.clear_reveal_state();
+        Ok(())
+    }
+    
+    // === High-Security Entries ===
+    
+    /// Ask for a high-security entry's PIN to copy or show its password
+    pub fn request_pin(&mut self, session: usize, id: &str, purpose: PinPurpose) {
+        self.clear_pin_prompt();
+        self.pending_pin = Some((session, id.to_string(), purpose));
+    }
+    
+    fn clear_pin_prompt(&mut self) {
+        self.pending_pin = None;
+        self.pin_input.zeroize();
+    }
+    
+    /// Ask for the PIN of the high-security entry waiting to be opened
+    fn show_pin_prompt(&mut self, ctx: &egui::Context) {
+        let Some((_, entry_id, purpose)) = self.pending_pin.clone() else {
+            return;
+        };
+        let mut confirm = false;
+        let mut cancel = ctx.input(|i| i.key_pressed(egui::Key::Escape));
+        let action = match purpose {
+            PinPurpose::Copy => "Copy",
+            PinPurpose::Show => "Show",
+            PinPurpose::ShowQr => "Show QR",
+        };
+        
+        egui::Window::new("🛡 High Security")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                ui.add_space(SPACING);
+                ui.label(format!("Enter the PIN for '{}'.", entry_id));
+                ui.add_space(SPACING);
+                let field = ui.add(egui::TextEdit::singleline(&mut *self.pin_input)
+                    .password(true)
+                    .hint_text("PIN")
+                    .desired_width(240.0));
+                field.request_focus();
+                confirm = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                ui.add_space(SPACING * 2.0);
+                
+                ui.horizontal(|ui| {
+                    if self.primary_button(ui, action, [100.0, BUTTON_HEIGHT]).clicked() {
+                        confirm = true;
+                    }
+                    ui.add_space(SPACING);
+                    if self.secondary_button(ui, "Cancel", [100.0, BUTTON_HEIGHT]).clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        
+        if confirm {
+            if let Err(e) = self.confirm_pin(ctx) {
+                self.toast_error(e);
+            }
+        } else if cancel {
+            self.clear_pin_prompt();
+        }
+    }
+    
+    /// Open the pending entry with the typed PIN, then copy its password
+    /// or show it. Nothing opened is written back to the vault.
+    fn confirm_pin(&mut self, ctx: &egui::Context) -> Result<(), String> {
+        let Some((index, entry_id, purpose)) = self.pending_pin.clone() else {
+            return Ok(());
+        };
+        let pin = std::mem::replace(&mut self.pin_input, Zeroizing::new(String::new()));
+        let secrets = self.sessions.get(index)
+            .and_then(|session| session.vault.get_entry(&entry_id))
+            .ok_or("Entry not found")?
+            .open_sealed(&pin)
+            .map_err(|e| e.to_string())?;
+        
+        self.clear_pin_prompt();
+        match purpose {
+            PinPurpose::Copy => self.copy_password(ctx, SecretView::new(&secrets.password)),
+            PinPurpose::Show => self.opened_sealed = Some((entry_id.clone(), secrets)),
+            PinPurpose::ShowQr => self.password_qr = Some(PasswordQr::new(&entry_id, &secrets.password)?),
+        }
+        self.record_use(index, &entry_id);
+        Ok(())
+    }
+    
+    /// Show a high-security entry opened with its PIN until closed
+    fn show_opened_sealed(&mut self, ctx: &egui::Context) {
+        // Taken out while shown, and put back unless closed
+        let Some((entry_id, secrets)) = self.opened_sealed.take() else {
+            return;
+        };
+        let mut copy = false;
+        let mut close = ctx.input(|i| i.key_pressed(egui::Key::Escape));
+        
+        egui::Window::new(format!("🛡 {}", entry_id))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                ui.add_space(SPACING);
+                ui.horizontal(|ui| {
+                    ui.label("🔒");
+                    ui.label(egui::RichText::new(secrets.password.as_str())
+                        .monospace()
+                        .color(egui::Color32::from_rgb(251, 191, 36)));
+                });
+                if let Some(note) = &secrets.note {
+                    ui.add_space(SPACING);
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label("📝");
+                        ui.label(note.as_str());
+                    });
+                }
+                ui.add_space(SPACING * 2.0);
+                
+                ui.horizontal(|ui| {
+                    copy = self.primary_button(ui, "📋 Copy", [100.0, BUTTON_HEIGHT]).clicked();
+                    ui.add_space(SPACING);
+                    close |= self.secondary_button(ui, "Close", [100.0, BUTTON_HEIGHT]).clicked();
+                });
+            });
+        
+        if copy {
+            self.copy_password(ctx, SecretView::new(&secrets.password));
+        }
+        if !close {
+            self.opened_sealed = Some((entry_id, secrets));
+        }
+    }
+    
+    // === Edit Conflict ===
+    
+    /// Ask what to do with an edit whose entry changed underneath it
+    fn show_edit_conflict(&mut self, ctx: &egui::Context) {
+        let Some(conflict) = self.edit_conflict else {
+            return;
+        };
+        let mut resolution = None;
+        
+        egui::Window::new("⚠ Edit Conflict")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                ui.add_space(SPACING);
+                ui.label(match conflict {
+                    EditConflict::Modified => format!("'{}' was changed elsewhere while you were editing it.", self.edit_id),
+                    EditConflict::Removed => format!("'{}' was deleted or renamed while you were editing it.", self.edit_id),
+                });
+                ui.add_space(SPACING * 2.0);
+                
+                ui.horizontal(|ui| {
+                    let overwrite = match conflict {
+                        EditConflict::Modified => "Overwrite",
+                        EditConflict::Removed => "Recreate",
+                    };
+                    if self.danger_button(ui, overwrite, [120.0, BUTTON_HEIGHT]).clicked() {
+                        resolution = Some(ConflictResolution::Overwrite);
+                    }
+                    ui.add_space(SPACING);
+                    if conflict == EditConflict::Modified
+                        && self.primary_button(ui, "Save as new id", [120.0, BUTTON_HEIGHT]).clicked() {
+                        resolution = Some(ConflictResolution::SaveAsNew);
+                    }
+                    ui.add_space(SPACING);
+                    if self.secondary_button(ui, "Discard my changes", [140.0, BUTTON_HEIGHT]).clicked() {
+                        resolution = Some(ConflictResolution::Discard);
+                    }
+                });
+            });
+        
+        if let Some(resolution) = resolution {
+            match self.resolve_edit_conflict(resolution) {
+                Ok(()) if resolution == ConflictResolution::Discard => {}
+                Ok(()) => self.toast_success("Entry updated successfully!"),
+                Err(e) => self.toast_error(e),
+            }
+        }
+    }
+    
+    // === Master Password Reuse ===
+    
+    /// Explain why storing the master password in the vault defeats it,
+    /// and save the form anyway only when told to
+    fn show_master_reuse_warning(&mut self, ctx: &egui::Context) {
+        let Some(form) = self.master_reuse_warning else {
+            return;
+        };
+        let mut store = None;
+        
+        egui::Window::new("⚠ This Is Your Master Password")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                ui.set_max_width(420.0);
+                ui.add_space(SPACING);
+                ui.label(egui::RichText::new("The password you entered is the master password of this vault.").strong());
+                ui.add_space(SPACING);
+                ui.label("Anyone who gets to see this one entry, over your shoulder, in an export, a backup or a shared copy, can open every other entry with it. It doesn't help you remember it either: if you forget the master password, the copy is locked inside the vault it opens.");
+                ui.add_space(SPACING);
+                ui.label("Keep the master password only in your head, or on paper somewhere safe.");
+                ui.add_space(SPACING * 2.0);
+                
+                ui.horizontal(|ui| {
+                    if self.primary_button(ui, "Use another password", [170.0, BUTTON_HEIGHT]).clicked() {
+                        store = Some(false);
+                    }
+                    ui.add_space(SPACING);
+                    if self.danger_button(ui, "Store it anyway", [140.0, BUTTON_HEIGHT]).clicked() {
+                        store = Some(true);
+                    }
+                });
+            });
+        
+        match store {
+            Some(true) => {
+                self.master_reuse_warning = None;
+                self.master_reuse_confirmed = true;
+                let result = match form {
+                    EntryForm::Add => self.add_entry(),
+                    EntryForm::Edit => self.update_entry(),
+                };
+                self.master_reuse_confirmed = false;
+                match result {
+                    Ok(()) if self.edit_conflict.is_some() => {}
+                    Ok(()) => {
+                        self.toast_success(match form {
+                            EntryForm::Add => "Entry added successfully!",
+                            EntryForm::Edit => "Entry updated successfully!",
+                        });
+                        self.clear_form_errors();
+                    }
+                    Err(e) => self.toast_error(e),
+                }
+            }
+            Some(false) => self.master_reuse_warning = None,
+            None => {}
+        }
+    }
+    
+    // === Command Palette ===
+    
+    /// Commands and matching entries, or the actions for an entry picked
+    /// in the first step
+    fn show_command_palette(&mut self, ctx: &egui::Context) {
+        enum Target {
+            Command(&'static PaletteCommand),
+            Entry(usize, String),
+            Action(EntryAction),
+        }
+        
+        let Some(mut palette) = self.command_palette.take() else {
+            return;
+        };
+        
+        let mut scored: Vec<(u32, PaletteItem, Target)> = Vec::new();
+        let title = if let Some((session, id)) = palette.entry.clone() {
+            let url = self.sessions.get(session)
+                .and_then(|s| s.vault.get_entry(&id))
+                .and_then(|entry| entry.url.clone())
+                .filter(|url| !url.trim().is_empty());
+            for action in self.entry_actions(session, &id).into_iter().filter(|(_, enabled)| *enabled).map(|(action, _)| action) {
+                let detail = match action {
+                    EntryAction::OpenUrl => url.clone().unwrap_or_default(),
+                    _ => String::new(),
+                };
+                if let Some(score) = fuzzy_score(&palette.query, action.label()) {
+                    scored.push((score, PaletteItem { label: action.label().to_string(), detail }, Target::Action(action)));
+                }
+            }
+            format!("{} (Esc to go back)", id)
+        } else {
+            for command in screens::palette_commands().filter(|command| (command.available)(self)) {
+                if let Some(score) = fuzzy_score(&palette.query, &format!("{} {}", command.label, command.keywords)) {
+                    let item = PaletteItem {
+                        label: command.label.to_string(),
+                        detail: command.shortcut.unwrap_or_default().to_string(),
+                    };
+                    scored.push((score, item, Target::Command(command)));
+                }
+            }
+            if !palette.query.trim().is_empty() {
+                let label_vaults = self.sessions.len() > 1;
+                for (index, session) in self.sessions.iter().enumerate() {
+                    for (id, entry) in session.entries.iter().filter(|(_, entry)| !entry.is_trashed() && !entry.archived) {
+                        if let Some(score) = fuzzy_score(&palette.query, id) {
+                            let detail = if label_vaults {
+                                format!("{} · {}", entry.username, session.name())
+                            } else {
+                                entry.username.clone()
+                            };
+                            scored.push((score, PaletteItem { label: format!("🔑 {}", id), detail }, Target::Entry(index, id.clone())));
+                        }
+                    }
+                }
+            }
+            "Commands and entries".to_string()
+        };
+        
+        // Stable sort: commands stay ahead of entries with the same score
+        scored.sort_by_key(|(score, _, _)| std::cmp::Reverse(*score));
+        scored.truncate(MAX_PALETTE_ITEMS);
+        let (items, targets): (Vec<PaletteItem>, Vec<Target>) = scored.into_iter()
+            .map(|(_, item, target)| (item, target))
+            .unzip();
+        
+        let event = overlays::render_command_palette(ctx, &mut palette, &title, &items);
+        let Some(event) = event else {
+            self.command_palette = Some(palette);
+            return;
+        };
+        
+        let picked = match event {
+            PaletteEvent::Close => return,
+            PaletteEvent::Back => {
+                self.command_palette = Some(CommandPalette::default());
+                return;
+            }
+            PaletteEvent::Picked(index) => targets.into_iter().nth(index),
+        };
+        let entry = palette.entry.clone();
+        match picked {
+            Some(Target::Command(command)) => (command.run)(self),
+            Some(Target::Entry(session, id)) => {
+                self.command_palette = Some(CommandPalette { entry: Some((session, id)), ..Default::default() });
+            }
+            Some(Target::Action(action)) => {
+                if let Some((session, id)) = entry {
+                    self.run_entry_action(ctx, session, &id, action);
+                }
+            }
+            None => self.command_palette = Some(palette),
+        }
+    }
+    
+    // === Entry Actions ===
+    
+    /// The context menu's actions for an entry, and whether each applies
+    /// to it now
+    pub fn entry_actions(&self, session: usize, id: &str) -> Vec<(EntryAction, bool)> {
+        let Some(entry) = self.sessions.get(session).and_then(|s| s.vault.get_entry(id)) else {
+            return Vec::new();
+        };
+        let has_url = entry.url.as_deref().is_some_and(|url| !url.trim().is_empty());
+        EntryAction::MENU
+            .into_iter()
+            .map(|action| match action {
+                EntryAction::Archive if entry.archived => EntryAction::Unarchive,
+                action => action,
+            })
+            .map(|action| (action, action.applies(entry.archived, has_url)))
+            .collect()
+    }
+    
+    /// Do `action` to an entry; every way of picking one ends up here.
+    /// Actions that change the vault switch to the entry's vault first.
+    pub fn run_entry_action(&mut self, ctx: &egui::Context, session: usize, id: &str, action: EntryAction) {
+        let Some(entry) = self.sessions.get(session).and_then(|s| s.vault.get_entry(id)) else {
+            return;
+        };
+        if !action.applies(entry.archived, entry.url.as_deref().is_some_and(|url| !url.trim().is_empty())) {
+            return;
+        }
+        let sealed = entry.is_sealed();
+        match action {
+            EntryAction::CopyPassword if sealed => self.request_pin(session, id, PinPurpose::Copy),
+            EntryAction::CopyPassword => {
+                let password = Zeroizing::new(entry.password_str().reveal().to_string());
+                self.copy_password(ctx, SecretView::new(&password));
+                self.record_use(session, id);
+            }
+            EntryAction::CopyUsername => {
+                let username = entry.username.clone();
+                self.open_copy = None;
+                if self.secure_clipboard.copy_username(&username).is_err() {
+                    ctx.output_mut(|o| o.copied_text = username);
+                }
+                self.toast_info("Username copied");
+            }
+            EntryAction::OpenUrl => match site_url(entry.url.as_deref().unwrap_or_default()) {
+                Ok(url) => self.open_link(ctx, url),
+                Err(e) => self.toast_error(e),
+            },
+            EntryAction::Edit => {
+                self.switch_session(session);
+                self.start_edit_entry(id);
+            }
+            EntryAction::Duplicate => {
+                self.switch_session(session);
+                if let Err(e) = self.duplicate_entry(id) {
+                    self.toast_error(e);
+                }
+            }
+            EntryAction::Archive | EntryAction::Unarchive => {
+                let archive = action == EntryAction::Archive;
+                self.switch_session(session);
+                match self.set_entry_archived(id, archive) {
+                    Ok(()) if archive => self.toast_info(format!("Archived '{}'; search is:archived to find it", id)),
+                    Ok(()) => self.toast_info(format!("Unarchived '{}'", id)),
+                    Err(e) => self.toast_error(e),
+                }
+            }
+            EntryAction::Share => {
+                self.switch_session(session);
+                self.share_entry(id);
+            }
+            EntryAction::Delete => {
+                self.switch_session(session);
+                self.pending_delete = Some(id.to_string());
+            }
+            // A QR code shows the password as much as revealing it does
+            EntryAction::ShowQr if sealed => self.request_pin(session, id, PinPurpose::ShowQr),
+            EntryAction::ShowQr if get_config().security.reveal_requires_password && !self.sessions[session].is_revealed(id) => {
+                self.toast_info("Reveal the password first; that asks for the master password");
+            }
+            EntryAction::ShowQr => {
+                let qr = PasswordQr::new(id, entry.password_str().reveal());
+                match qr {
+                    Ok(qr) => self.password_qr = Some(qr),
+                    Err(e) => self.toast_error(e),
+                }
+                self.record_use(session, id);
+            }
+        }
+    }
+    
+    /// Open an entry's context menu at `pos`
+    pub fn open_entry_menu(&mut self, session: usize, id: &str, pos: egui::Pos2) {
+        self.entry_menu = Some(EntryMenu { session, id: id.to_string(), pos, selected: 0 });
+    }
+    
+    /// The open context menu. Arrows move between the items that apply,
+    /// Enter runs one, Escape or a click elsewhere closes it.
+    fn show_entry_menu(&mut self, ctx: &egui::Context) {
+        let Some(mut menu) = self.entry_menu.take() else {
+            return;
+        };
+        let items = self.entry_actions(menu.session, &menu.id);
+        let enabled: Vec<usize> = items.iter().enumerate().filter(|(_, (_, enabled))| *enabled).map(|(i, _)| i).collect();
+        if enabled.is_empty() {
+            return;
+        }
+        if !enabled.contains(&menu.selected) {
+            menu.selected = enabled[0];
+        }
+        
+        let (up, down, enter, escape) = ctx.input_mut(|i| (
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+        ));
+        if escape {
+            return;
+        }
+        let position = enabled.iter().position(|i| *i == menu.selected).unwrap_or(0);
+        if down {
+            menu.selected = enabled[(position + 1).min(enabled.len() - 1)];
+        }
+        if up {
+            menu.selected = enabled[position.saturating_sub(1)];
+        }
+        
+        let mut picked = enter.then_some(items[menu.selected].0);
+        let area = egui::Area::new(egui::Id::new("entry_menu"))
+            .fixed_pos(menu.pos)
+            .constrain(true)
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                egui::Frame::menu(ui.style()).show(ui, |ui| {
+                    ui.set_min_width(180.0);
+                    for (i, (action, enabled)) in items.iter().enumerate() {
+                        let button = egui::Button::new(action.label())
+                            .frame(false)
+                            .selected(i == menu.selected)
+                            .min_size(egui::vec2(ui.available_width(), 0.0));
+                        if ui.add_enabled(*enabled, button).clicked() {
+                            picked = Some(*action);
+                        }
+                    }
+                });
+            });
+        
+        if let Some(action) = picked {
+            let (session, id) = (menu.session, menu.id.clone());
+            self.run_entry_action(ctx, session, &id, action);
+            return;
+        }
+        let clicked_elsewhere = ctx.input(|i| {
+            i.pointer.any_pressed() && i.pointer.interact_pos().is_some_and(|pos| !area.response.rect.contains(pos))
+        });
+        if !clicked_elsewhere {
+            self.entry_menu = Some(menu);
+        }
+    }
+    
+    /// The QR code of a password, hidden again with the reveal timeout
+    fn show_password_qr(&mut self, ctx: &egui::Context) {
+        let Some(qr) = self.password_qr.take() else {
+            return;
+        };
+        let timeout_secs = get_config().ui.reveal_timeout_secs;
+        if timeout_secs > 0 && qr.shown_at.elapsed().as_secs() >= timeout_secs {
+            return;
+        }
+        if timeout_secs > 0 {
+            ctx.request_repaint_after(std::time::Duration::from_secs(timeout_secs).saturating_sub(qr.shown_at.elapsed()));
+        }
+        let mut close = ctx.input(|i| i.key_pressed(egui::Key::Escape));
+        
+        egui::Window::new(format!("▦ {}", qr.id))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                // Quiet zone of four modules around the code, as scanners expect
+                let module = 6.0;
+                let side = (qr.width + 8) as f32 * module;
+                let (rect, _) = ui.allocate_exact_size(egui::vec2(side, side), egui::Sense::hover());
+                let painter = ui.painter_at(rect);
+                painter.rect_filled(rect, 0.0, egui::Color32::WHITE);
+                for (i, dark) in qr.modules.iter().enumerate() {
+                    if *dark {
+                        let min = rect.min + egui::vec2((i % qr.width + 4) as f32, (i / qr.width + 4) as f32) * module;
+                        painter.rect_filled(egui::Rect::from_min_size(min, egui::vec2(module, module)), 0.0, egui::Color32::BLACK);
+                    }
+                }
+                ui.add_space(SPACING);
+                ui.label(egui::RichText::new("Scan to type the password on another device").size(12.0));
+                ui.add_space(SPACING);
+                close |= self.secondary_button(ui, "Close", [100.0, BUTTON_HEIGHT]).clicked();
+            });
+        
+        if !close {
+            self.password_qr = Some(qr);
+        }
+    }
+    
+    /// Copy a password, falling back to the standard clipboard
+    pub fn copy_password(&mut self, ctx: &egui::Context, password: SecretView<'_>) {
+        // Anything copied by hand ends a pending "Open & copy"
+        self.open_copy = None;
+        match self.secure_clipboard.copy_password(password.reveal()) {
+            Ok(()) if self.clipboard_clear_secs > 0 => {
+                let timeout = self.clipboard_clear_secs;
+                self.toast_with_action(format!("Password copied! Auto-clear in {}s", timeout), ToastType::Success, ToastAction::ClearClipboard);
+            }
+            Ok(()) => self.toast_with_action("Password copied!", ToastType::Success, ToastAction::ClearClipboard),
+            Err(_) => {
+                ctx.output_mut(|o| o.copied_text = password.reveal().to_string());
+                self.toast_info("Password copied (standard clipboard)");
+            }
+        }
+    }
+    
+    // === Conflicted Copies ===
+    
+    /// Open the active vault's first conflicted copy with the session key
+    /// and show what merging it would do. A copy the key doesn't open (its
+    /// master password was changed) is reported and dropped from the list.
+    pub fn preview_conflicted_copy(&mut self) {
+        let session_index = self.active_session;
+        let Some(session) = self.active_session() else {
+            return;
+        };
+        let Some(copy_path) = session.conflicted_copies.first().cloned() else {
+            return;
+        };
+        match VaultManager::load_with_vault_key(&session.key, Some(&copy_path)) {
+            Ok(copy) => {
+                let mut diff = VaultDiff::compare(&session.vault, &copy);
+                diff.redact_secrets();
+                self.conflict_preview = Some(SyncConflictPreview { session: session_index, copy_path, copy, diff });
+            }
+            Err(e) => {
+                self.toast_error(format!("Could not open {}: {}", copy_path, e));
+                if let Some(session) = self.active_session_mut() {
+                    session.conflicted_copies.retain(|path| *path != copy_path);
+                }
+            }
+        }
+    }
+    
+    /// Merge the conflicted copy shown in the summary into its vault
+    pub fn resolve_conflicted_copy(&mut self) {
+        let Some(preview) = self.conflict_preview.take() else {
+            return;
+        };
+        let Some(session) = self.sessions.get_mut(preview.session) else {
+            return;
+        };
+        let result = session.resolve_conflicted_copy(&preview.copy_path, &preview.copy);
+        let conflict = session.external_change_pending;
+        match result {
+            Ok(archived) => {
+                crate::log_security!("Conflicted copy merged in GUI: {}", preview.copy_path);
+                self.toast_success(format!("Conflicted copy merged and moved to {}", archived));
+            }
+            Err(e) => {
+                self.show_external_change_prompt |= conflict && preview.session == self.active_session;
+                self.toast_error(format!("Could not merge the conflicted copy: {}", e));
+            }
+        }
+    }
+    
+    /// Summary of a conflicted copy's merge, with Merge and Cancel
+    fn show_conflict_preview(&mut self, ctx: &egui::Context) {
+        let Some(preview) = &self.conflict_preview else {
+            return;
+        };
+        let file_name = std::path::Path::new(&preview.copy_path)
+            .file_name()
+            .map_or_else(|| preview.copy_path.clone(), |name| name.to_string_lossy().into_owned());
+        let summary = preview.diff.merge_summary();
+        let added = preview.diff.only_in_b.clone();
+        let updated: Vec<String> = preview.diff.merge_updates().map(|entry| entry.id.clone()).collect();
+        let mut merge = false;
+        let mut cancel = ctx.input(|i| i.key_pressed(egui::Key::Escape));
+        
+        egui::Window::new("☁ Conflicted Copy")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                ui.set_max_width(460.0);
+                ui.add_space(SPACING);
+                ui.label(egui::RichText::new(&file_name).strong());
+                ui.add_space(SPACING);
+                match &summary {
+                    Some(summary) => ui.label(format!("{}. The newer version of each entry is kept.", summary)),
+                    None => ui.label("Everything in it is already in this vault."),
+                };
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    for id in &added {
+                        ui.label(format!("+ {}", id));
+                    }
+                    for id in &updated {
+                        ui.label(format!("~ {} (newer in the copy)", id));
+                    }
+                });
+                ui.add_space(SPACING);
+                ui.label(egui::RichText::new("The vault is backed up first, and the copy is renamed with a .resolved suffix.")
+                    .size(11.0)
+                    .color(theme::muted_text_color(&self.current_theme)));
+                ui.add_space(SPACING * 2.0);
+                
+                ui.horizontal(|ui| {
+                    merge = self.primary_button(ui, "Merge", [110.0, BUTTON_HEIGHT]).clicked();
+                    ui.add_space(SPACING);
+                    cancel |= self.secondary_button(ui, "Cancel", [110.0, BUTTON_HEIGHT]).clicked();
+                });
+            });
+        
+        if merge {
+            self.resolve_conflicted_copy();
+        } else if cancel {
+            self.conflict_preview = None;
+        }
+    }
+    
+    // === Copy Sequences ===
+    
+    /// Index of the step an entry's "Next ▸" button copies next
+    pub fn copy_sequence_next(&self, session: usize, id: &str, len: usize) -> usize {
+        let Some(vault_session) = self.sessions.get(session) else {
+            return 0;
+        };
+        let key = SequenceCursor::key(&vault_session.vault_file, id);
+        SequenceCursor::step(self.copy_sequence.as_ref(), &key, len, chrono::Utc::now().timestamp())
+            .map_or(0, |(index, _)| index)
+    }
+    
+    /// Copy the next item of an entry's copy sequence. The password goes
+    /// through the secure clipboard's auto-clear like any copied password;
+    /// the other items stay until replaced, and replacing a password on
+    /// the clipboard doesn't let its pending clear wipe them.
+    pub fn copy_next_in_sequence(&mut self, ctx: &egui::Context, session: usize, id: &str) {
+        let Some((vault_file, entry)) = self.sessions.get(session)
+            .and_then(|s| s.vault.get_entry(id).map(|entry| (s.vault_file.clone(), entry.clone())))
+        else {
+            return;
+        };
+        let key = SequenceCursor::key(&vault_file, id);
+        let now = chrono::Utc::now().timestamp();
+        let Some((index, next)) = SequenceCursor::step(self.copy_sequence.as_ref(), &key, entry.copy_sequence.len(), now) else {
+            return;
+        };
+        self.copy_sequence = next;
+        self.open_copy = None;
+        let step = &entry.copy_sequence[index];
+        let position = format!("{}/{}: {}", index + 1, entry.copy_sequence.len(), step.label());
+        
+        let Some(value) = step.value(&entry) else {
+            // A high-security password waits for the PIN prompt
+            self.request_pin(session, id, PinPurpose::Copy);
+            return;
+        };
+        let copied = if step.is_secret() {
+            self.secure_clipboard.copy_password(&value)
+        } else {
+            self.secure_clipboard.copy_username(&value)
+        };
+        match copied {
+            Ok(()) if step.is_secret() && self.clipboard_clear_secs > 0 => {
+                let message = format!("Copied {} · auto-clear in {}s", position, self.clipboard_clear_secs);
+                self.toast_with_action(message, ToastType::Success, ToastAction::ClearClipboard);
+            }
+            Ok(()) => self.toast_info(format!("Copied {}", position)),
+            Err(_) => {
+                ctx.output_mut(|o| o.copied_text = value.to_string());
+                self.toast_info(format!("Copied {} (standard clipboard)", position));
+            }
+        }
+        if step.is_secret() {
+            self.record_use(session, id);
+        }
+    }
+    
+    // === Open & Copy ===
+    
+    /// Open an entry's site with its username on the clipboard; the
+    /// password follows after `ui.open_copy_delay_secs` or when this is
+    /// called again for the same entry
+    pub fn open_and_copy(&mut self, ctx: &egui::Context, session: usize, id: &str) {
+        let same_entry = self.open_copy.as_ref().is_some_and(|open_copy| {
+            open_copy.session == session && open_copy.id == id && matches!(open_copy.stage, ClipboardStage::Username { .. })
+        });
+        if same_entry {
+            self.copy_open_copy_password(ctx);
+            return;
+        }
+        
+        let Some(entry) = self.sessions.get(session).and_then(|s| s.vault.get_entry(id)) else {
+            return;
+        };
+        let url = match site_url(entry.url.as_deref().unwrap_or_default()) {
+            Ok(url) => url,
+            Err(e) => {
+                self.toast_error(e);
+                return;
+            }
+        };
+        let username = entry.username.clone();
+        if let Err(e) = open::that(&url) {
+            self.toast_error(format!("Could not open {}: {}", url, e));
+            return;
+        }
+        if self.secure_clipboard.copy_username(&username).is_err() {
+            ctx.output_mut(|o| o.copied_text = username);
+        }
+        
+        let delay = get_config().ui.open_copy_delay_secs;
+        let swap_at = (delay > 0).then(|| Instant::now() + std::time::Duration::from_secs(delay));
+        self.open_copy = Some(OpenCopy { session, id: id.to_string(), stage: ClipboardStage::Username { swap_at } });
+        self.toast_info(if delay > 0 {
+            format!("Username copied, password in {}s", delay)
+        } else {
+            "Username copied, click Open again for the password".to_string()
+        });
+    }
+    
+    /// Second stage of "Open & copy": replace the username with the password
+    pub fn copy_open_copy_password(&mut self, ctx: &egui::Context) {
+        let Some(OpenCopy { session, id, .. }) = self.open_copy.take() else {
+            return;
+        };
+        if self.sessions.get(session).and_then(|s| s.vault.get_entry(&id)).is_some_and(Entry::is_sealed) {
+            self.request_pin(session, &id, PinPurpose::Copy);
+            return;
+        }
+        let Some(password) = self.sessions.get(session)
+            .and_then(|s| s.vault.get_entry(&id))
+            .map(|entry| Zeroizing::new(entry.password_str().reveal().to_string()))
+        else {
+            return;
+        };
+        self.copy_password(ctx, SecretView::new(&password));
+        self.record_use(session, &id);
+        let clears_at = (self.clipboard_clear_secs > 0)
+            .then(|| Instant::now() + std::time::Duration::from_secs(self.clipboard_clear_secs));
+        self.open_copy = Some(OpenCopy { session, id, stage: ClipboardStage::Password { clears_at } });
+    }
+    
+    /// Advance "Open & copy" on schedule
+    fn poll_open_copy(&mut self, ctx: &egui::Context) {
+        let Some(open_copy) = &self.open_copy else {
+            return;
+        };
+        let now = Instant::now();
+        match open_copy.stage {
+            ClipboardStage::Username { swap_at: Some(at) } if now >= at => self.copy_open_copy_password(ctx),
+            ClipboardStage::Password { clears_at: Some(at) } if now >= at => self.open_copy = None,
+            ClipboardStage::Password { clears_at: None } => {}
+            _ => ctx.request_repaint_after(std::time::Duration::from_secs(1)),
+        }
+    }
+    
+    /// What "Open & copy" has on the clipboard, for the header
+    pub fn open_copy_status(&self) -> Option<String> {
+        let open_copy = self.open_copy.as_ref()?;
+        let seconds_left = |at: Instant| at.saturating_duration_since(Instant::now()).as_secs() + 1;
+        Some(match open_copy.stage {
+            ClipboardStage::Username { swap_at: Some(at) } => format!("📋 Username · password in {}s", seconds_left(at)),
+            ClipboardStage::Username { swap_at: None } => "📋 Username · click for the password".to_string(),
+            ClipboardStage::Password { clears_at: Some(at) } => format!("📋 Password · clears in {}s", seconds_left(at)),
+            ClipboardStage::Password { clears_at: None } => "📋 Password".to_string(),
+        })
+    }
+    
+    /// Open a link in the browser; anything but https asks first
+    pub fn open_link(&mut self, ctx: &egui::Context, url: String) {
+        if markdown::is_https(&url) {
+            ctx.open_url(egui::OpenUrl::new_tab(url));
+        } else {
+            self.pending_link = Some(url);
+        }
     }
     
     // === Button Helpers ===
@@ -594,58 +3494,168 @@ impl PassmanApp {
     pub fn danger_button(&self, ui: &mut egui::Ui, text: &str, size: [f32; 2]) -> egui::Response {
         widgets::ButtonWidgets::danger(ui, text, size)
     }
+}
 
-    pub fn show_password_strength_indicator(&self, ui: &mut egui::Ui, password: &str) {
-        widgets::show_password_strength_indicator(ui, password);
+impl PassmanApp {
+    /// Handle a screen that panicked mid-frame. An edit may have changed the
+    /// vault in memory without reaching disk; with nothing unsaved the file
+    /// is the last good state, so reload it rather than keep the half-edit.
+    fn recover_from_render_panic(&mut self, screen: Screen) {
+        self.is_loading = false;
+        if let Some(session) = self.active_session_mut() {
+            if !session.dirty {
+                if let Err(e) = session.reload() {
+                    log::error!("Could not reload vault after a rendering error: {}", e);
+                }
+            }
+        }
+        self.render_error = Some(RenderError {
+            screen,
+            report: super::crash::take_last_report(),
+        });
+    }
+    
+    /// Error panel shown in place of a screen that panicked
+    fn show_render_error(&mut self, ui: &mut egui::Ui, error: &RenderError) {
+        let muted_color = theme::muted_text_color(&self.current_theme);
+        let mut go_back = false;
+        
+        ui.vertical_centered(|ui| {
+            ui.add_space(ui.available_height() * 0.25);
+            ui.label(egui::RichText::new("⚠").size(40.0).color(egui::Color32::from_rgb(251, 191, 36)));
+            ui.add_space(SPACING);
+            ui.label(egui::RichText::new("Something went wrong rendering this screen").size(18.0).strong());
+            ui.add_space(SPACING);
+            match &error.report {
+                Some(path) => {
+                    ui.label(egui::RichText::new("A report was saved to:").color(muted_color));
+                    ui.label(egui::RichText::new(path.display().to_string()).monospace().size(12.0));
+                    if ui.small_button("📋 Copy path").clicked() {
+                        ui.ctx().copy_text(path.display().to_string());
+                    }
+                }
+                None => {
+                    ui.label(egui::RichText::new("The crash report could not be saved.").color(muted_color));
+                }
+            }
+            ui.add_space(SPACING * 2.0);
+            
+            let label = if self.is_unlocked() { "Back to vault" } else { "Back to start" };
+            if self.primary_button(ui, label, [160.0, BUTTON_HEIGHT]).clicked() {
+                go_back = true;
+            }
+        });
+        
+        if go_back {
+            log::info!("Leaving screen that failed to render");
+            self.render_error = None;
+            let home = self.home_screen();
+            self.navigate_root(home);
+        }
     }
 }
 
 /// eframe App implementation
 impl eframe::App for PassmanApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Check for session timeout
-        if self.vault.is_some() && self.lock_timeout_secs > 0 {
+        // Check for session timeout (locks every unlocked vault)
+        if self.is_unlocked() && self.lock_timeout_secs > 0 {
             if let Some(last) = self.last_activity {
                 if last.elapsed().as_secs() >= self.lock_timeout_secs {
-                    self.lock_vault();
+                    // Locks regardless; failures to save are reported
+                    self.save_dirty_sessions();
+                    self.lock_all_vaults();
                     self.toast_info(format!("Session timed out after {} seconds of inactivity", self.lock_timeout_secs));
                 }
             }
         }
         
-        // Update last activity on any input
-        if ctx.input(|i| i.pointer.any_click() || i.key_pressed(egui::Key::Enter) || !i.keys_down.is_empty()) {
+        // Update last activity on real usage only
+        if self.is_unlocked() && (Self::has_user_activity(ctx) || self.is_idle_timer_paused(ctx)) {
             self.last_activity = Some(Instant::now());
         }
         
-        // Handle keyboard shortcuts
-        self.handle_keyboard_shortcuts(ctx);
+        // Finish a background unlock if key derivation completed
+        self.poll_pending_unlock();
+        self.poll_update_check(ctx);
+        self.poll_favicon_fetch(ctx);
+        self.expire_reveals(ctx);
+        self.poll_open_copy(ctx);
+        
+        // Handle keyboard shortcuts
+        self.handle_keyboard_shortcuts(ctx);
+        
+        // Pick up modifications made by the CLI or another instance
+        self.check_external_changes();
+        if self.is_unlocked() {
+            ctx.request_repaint_after(std::time::Duration::from_secs(EXTERNAL_CHANGE_POLL_SECS));
+        }
+        
+        // Unsaved changes: ask before the window goes away. Usage counts
+        // are written without asking.
+        let close_requested = ctx.input(|i| i.viewport().close_requested());
+        if close_requested {
+            for session in self.sessions.iter_mut().filter(|session| !session.dirty) {
+                let _ = session.flush();
+            }
+        }
+        if close_requested && self.has_unsaved_changes() && !self.allow_close {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.show_close_prompt = true;
+        }
+        
+        // Crossing the lock wipes what was typed on the other side
+        self.note_screen_change();
+        // Title and icon show the lock state and unsaved changes
+        let title = window::title(self.active_session().map(|session| (session.vault_file.as_str(), session.dirty)));
+        let locked = !self.is_unlocked();
+        self.window_state.update(ctx, title, locked);
         
         // Clean up expired toasts
         self.cleanup_toasts();
         
         let panel_fill = theme::panel_fill(&self.current_theme);
+        let (opacity, slide) = theme::screen_transition(self.motion, self.screen_shown_at.elapsed().as_secs_f32());
+        if opacity < 1.0 {
+            ctx.request_repaint();
+        }
         
         egui::CentralPanel::default()
             .frame(egui::Frame::none()
                 .inner_margin(PADDING)
                 .fill(panel_fill))
             .show(ctx, |ui| {
-                match self.current_screen.clone() {
-                    Screen::Welcome => self.show_welcome_screen(ui),
-                    Screen::Init => self.show_init_screen(ui),
-                    Screen::Login => self.show_login_screen(ui),
-                    Screen::Main => self.show_main_screen(ui, ctx),
-                    Screen::AddEntry => self.show_add_entry_screen(ui),
-                    Screen::EditEntry(id) => self.show_edit_entry_screen(ui, &id),
-                    Screen::Settings => self.show_settings_screen(ui, ctx),
-                    Screen::HealthDashboard => self.show_health_dashboard(ui),
-                    Screen::ImportExport => self.show_import_export_screen(ui),
+                if let Some(error) = self.render_error.clone() {
+                    self.show_render_error(ui, &error);
+                    return;
+                }
+                ui.multiply_opacity(opacity);
+                ui.add_space(slide);
+                
+                // A panicking screen shows an error panel instead of closing
+                // the window. Vault files are only ever replaced atomically,
+                // so a panic can't leave one half-written.
+                let screen = self.current_screen.clone();
+                let rendered = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    match screen.clone() {
+                        Screen::Welcome => self.show_welcome_screen(ui),
+                        Screen::Init => self.show_init_screen(ui),
+                        Screen::Login => self.show_login_screen(ui),
+                        Screen::Main => self.show_main_screen(ui, ctx),
+                        Screen::AddEntry => self.show_add_entry_screen(ui),
+                        Screen::EditEntry(id) => self.show_edit_entry_screen(ui, &id),
+                        Screen::Settings => self.show_settings_screen(ui, ctx),
+                        Screen::HealthDashboard => self.show_health_dashboard(ui),
+                        Screen::ImportExport => self.show_import_export_screen(ui),
+                    }
+                }));
+                if rendered.is_err() {
+                    self.recover_from_render_panic(screen);
                 }
             });
         
         // Render overlays
-        overlays::render_loading_overlay(ctx, self.is_loading, &self.loading_message);
+        overlays::render_loading_overlay(ctx, self.is_loading, &self.loading_message, self.motion);
         overlays::render_onboarding(ctx, &mut self.show_onboarding, &mut self.onboarding_step);
         
         // Handle confirmation dialog
@@ -653,6 +3663,10 @@ impl eframe::App for PassmanApp {
             let entry_id = self.pending_delete.clone().unwrap();
             let mut should_delete = false;
             let mut should_cancel = false;
+            let protected_tag = get_config().security.protected_tag.clone();
+            let protected = self.active_vault()
+                .and_then(|vault| vault.get_entry(&entry_id))
+                .is_some_and(|entry| entry.has_tag(&protected_tag));
             
             // Modal background overlay
             egui::Area::new(egui::Id::new("confirm_overlay"))
@@ -677,11 +3691,23 @@ impl eframe::App for PassmanApp {
                     ui.add_space(SPACING);
                     ui.label(format!("Are you sure you want to delete '{}'?", entry_id));
                     ui.add_space(SPACING);
-                    ui.label("This action cannot be undone.");
+                    ui.label("You can undo this from the notification that follows.");
+                    
+                    if protected {
+                        ui.add_space(SPACING);
+                        ui.label(format!("This entry is tagged '{}'. Type its ID to confirm:", protected_tag));
+                        ui.add(egui::TextEdit::singleline(&mut self.delete_confirm_text)
+                            .hint_text(entry_id.as_str())
+                            .desired_width(240.0));
+                    }
                     ui.add_space(SPACING * 2.0);
                     
+                    let confirmed = !protected || Entry::confirms_id(&self.delete_confirm_text, &entry_id);
                     ui.horizontal(|ui| {
-                        if self.danger_button(ui, "Delete", [100.0, BUTTON_HEIGHT]).clicked() {
+                        let delete = ui.add_enabled_ui(confirmed, |ui| {
+                            self.danger_button(ui, "Delete", [100.0, BUTTON_HEIGHT])
+                        }).inner;
+                        if delete.clicked() {
                             should_delete = true;
                         }
                         
@@ -696,18 +3722,998 @@ impl eframe::App for PassmanApp {
             if should_delete {
                 match self.remove_entry(&entry_id) {
                     Ok(()) => {
-                        self.toast_success(format!("Entry '{}' deleted", entry_id));
+                        self.toast_with_action(format!("Entry '{}' deleted", entry_id), ToastType::Success, ToastAction::UndoDelete);
                     }
                     Err(e) => {
                         self.toast_error(e);
                     }
                 }
                 self.pending_delete = None;
+                self.delete_confirm_text.clear();
             } else if should_cancel {
                 self.pending_delete = None;
+                self.delete_confirm_text.clear();
+            }
+        }
+        
+        self.show_reveal_prompt(ctx);
+        self.show_pin_prompt(ctx);
+        self.show_opened_sealed(ctx);
+        self.show_password_qr(ctx);
+        self.show_edit_conflict(ctx);
+        self.show_master_reuse_warning(ctx);
+        self.show_conflict_preview(ctx);
+        
+        // Show a freshly generated share passphrase exactly once
+        if let Some((entry_id, passphrase)) = self.shared_passphrase.clone() {
+            let mut should_close = false;
+            
+            egui::Window::new("🔗 Entry Shared")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+                .order(egui::Order::Foreground)
+                .show(ctx, |ui| {
+                    ui.add_space(SPACING);
+                    ui.label(format!("Share bundle for '{}' created.", entry_id));
+                    ui.add_space(SPACING);
+                    ui.label("Passphrase (shown only once):");
+                    ui.label(egui::RichText::new(passphrase.as_str()).monospace().size(16.0));
+                    ui.add_space(SPACING);
+                    ui.label(
+                        egui::RichText::new("⚠ Send the passphrase over a different channel than the bundle")
+                            .size(11.0)
+                            .color(egui::Color32::from_rgb(251, 191, 36))
+                    );
+                    ui.add_space(SPACING * 2.0);
+                    
+                    ui.horizontal(|ui| {
+                        if self.primary_button(ui, "📋 Copy", [100.0, BUTTON_HEIGHT]).clicked() {
+                            match self.secure_clipboard.copy_password(passphrase.as_str()) {
+                                Ok(()) => self.toast_success("Passphrase copied"),
+                                Err(e) => self.toast_error(format!("Copy failed: {}", e)),
+                            }
+                        }
+                        
+                        ui.add_space(SPACING);
+                        
+                        if self.secondary_button(ui, "Done", [100.0, BUTTON_HEIGHT]).clicked() {
+                            should_close = true;
+                        }
+                    });
+                });
+            
+            if should_close {
+                self.shared_passphrase = None;
+            }
+        }
+        
+        // Prompt to reload when the vault changed under an open form or
+        // under unsaved changes
+        if self.show_external_change_prompt {
+            let mut should_reload = false;
+            let mut should_overwrite = false;
+            let mut should_postpone = false;
+            let conflict = self.active_session().is_some_and(|s| s.dirty);
+            
+            egui::Window::new("🔄 Vault Changed")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+                .order(egui::Order::Foreground)
+                .show(ctx, |ui| {
+                    ui.add_space(SPACING);
+                    ui.label("The vault file was modified by another program.");
+                    ui.add_space(SPACING);
+                    if conflict {
+                        ui.label("You have unsaved changes. Reloading discards them; overwriting discards the other program's changes.");
+                    } else {
+                        ui.label("Reload now? Your input on this screen will be kept.");
+                    }
+                    ui.add_space(SPACING * 2.0);
+                    
+                    ui.horizontal(|ui| {
+                        if self.primary_button(ui, "Reload", [100.0, BUTTON_HEIGHT]).clicked() {
+                            should_reload = true;
+                        }
+                        
+                        if conflict {
+                            ui.add_space(SPACING);
+                            if self.danger_button(ui, "Overwrite", [100.0, BUTTON_HEIGHT]).clicked() {
+                                should_overwrite = true;
+                            }
+                        }
+                        
+                        ui.add_space(SPACING);
+                        
+                        if self.secondary_button(ui, "Later", [100.0, BUTTON_HEIGHT]).clicked() {
+                            should_postpone = true;
+                        }
+                    });
+                });
+            
+            if should_reload {
+                self.reload_external_changes();
+            } else if should_overwrite {
+                self.overwrite_external_changes();
+            } else if should_postpone {
+                // Reload happens automatically once the form is closed;
+                // unsaved changes ask again on the next save
+                self.show_external_change_prompt = false;
+            }
+        }
+        
+        // Links with other schemes than https open only after confirmation
+        if let Some(url) = self.pending_link.clone() {
+            let mut should_open = false;
+            let mut should_cancel = false;
+            
+            egui::Window::new("🔗 Open Link?")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+                .order(egui::Order::Foreground)
+                .show(ctx, |ui| {
+                    ui.add_space(SPACING);
+                    ui.label("This link does not use https:");
+                    ui.add_space(SPACING);
+                    ui.label(egui::RichText::new(url.as_str()).monospace());
+                    ui.add_space(SPACING * 2.0);
+                    
+                    ui.horizontal(|ui| {
+                        if self.primary_button(ui, "Open", [100.0, BUTTON_HEIGHT]).clicked() {
+                            should_open = true;
+                        }
+                        
+                        ui.add_space(SPACING);
+                        
+                        if self.secondary_button(ui, "Cancel", [100.0, BUTTON_HEIGHT]).clicked() {
+                            should_cancel = true;
+                        }
+                    });
+                });
+            
+            if should_open {
+                ctx.open_url(egui::OpenUrl::new_tab(url));
+                self.pending_link = None;
+            } else if should_cancel {
+                self.pending_link = None;
+            }
+        }
+        
+        // Closing with unsaved changes
+        if self.show_close_prompt {
+            let mut should_save = false;
+            let mut should_discard = false;
+            let mut should_cancel = false;
+            
+            egui::Window::new("💾 Unsaved Changes")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+                .order(egui::Order::Foreground)
+                .show(ctx, |ui| {
+                    ui.add_space(SPACING);
+                    let names: Vec<String> = self.sessions.iter()
+                        .filter(|session| session.dirty)
+                        .map(|session| session.name())
+                        .collect();
+                    ui.label(format!("Unsaved changes in: {}", names.join(", ")));
+                    ui.add_space(SPACING * 2.0);
+                    
+                    ui.horizontal(|ui| {
+                        if self.primary_button(ui, "Save & Quit", [110.0, BUTTON_HEIGHT]).clicked() {
+                            should_save = true;
+                        }
+                        
+                        ui.add_space(SPACING);
+                        
+                        if self.danger_button(ui, "Discard", [100.0, BUTTON_HEIGHT]).clicked() {
+                            should_discard = true;
+                        }
+                        
+                        ui.add_space(SPACING);
+                        
+                        if self.secondary_button(ui, "Cancel", [100.0, BUTTON_HEIGHT]).clicked() {
+                            should_cancel = true;
+                        }
+                    });
+                });
+            
+            if should_save {
+                self.show_close_prompt = false;
+                if self.save_dirty_sessions() {
+                    self.allow_close = true;
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+            } else if should_discard {
+                self.show_close_prompt = false;
+                self.allow_close = true;
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            } else if should_cancel {
+                self.show_close_prompt = false;
             }
         }
         
-        toasts::render_toasts(ctx, &self.toasts);
+        self.show_command_palette(ctx);
+        self.show_entry_menu(ctx);
+        
+        if let Some(event) = toasts::render_toasts(ctx, &mut self.toasts, self.motion) {
+            self.handle_toast_event(event);
+        }
+        if self.show_toast_history {
+            self.toast_history_seen = self.toast_history.len();
+            self.show_toast_history = toasts::render_toast_history(ctx, &mut self.toast_history);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An unlocked app holding "github" and "mail", with autosave off so
+    /// nothing is written
+    fn app_with_entries(dir: &tempfile::TempDir) -> PassmanApp {
+        let path = dir.path().join("vault.dat").to_string_lossy().into_owned();
+        let key = VaultManager::init(&Zeroizing::new("correct horse".to_string()), Some(&path)).unwrap();
+        let mut vault = Vault::new();
+        for id in ["github", "mail"] {
+            vault.add_entry(id.to_string(), Entry::new(format!("{}-user", id), "pw".to_string(), None));
+        }
+        let mut app = PassmanApp { autosave: false, ..PassmanApp::default() };
+        app.sessions.push(VaultSession::new(vault, path, key));
+        app.navigate_root(Screen::Main);
+        app
+    }
+
+    /// Save `id` from "another screen", bumping its modified_at
+    fn edit_elsewhere(app: &mut PassmanApp, id: &str, username: &str) {
+        let vault = &mut app.active_session_mut().unwrap().vault;
+        let mut entry = vault.get_entry(id).unwrap().clone();
+        entry.username = username.to_string();
+        entry.modified_at += chrono::Duration::seconds(1);
+        vault.add_entry(id.to_string(), entry);
+    }
+
+    fn username(app: &PassmanApp, id: &str) -> Option<String> {
+        app.active_vault().unwrap().get_entry(id).map(|entry| entry.username.clone())
+    }
+
+    #[test]
+    fn test_edit_without_interleaving_saves() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        app.start_edit_entry("github");
+        app.edit_username = "octocat".into();
+        app.update_entry().unwrap();
+
+        assert_eq!(app.edit_conflict, None);
+        assert_eq!(username(&app, "github").as_deref(), Some("octocat"));
+        assert!(app.current_screen == Screen::Main);
+    }
+
+    #[test]
+    fn test_edit_form_links_entries_and_chips_find_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        app.start_edit_entry("github");
+        app.edit_linked_ids.push("mail".to_string());
+        app.update_entry().unwrap();
+        assert_eq!(app.active_vault().unwrap().get_entry("github").unwrap().linked_ids, vec!["mail"]);
+
+        // Following a link clears a search that would hide the linked entry
+        app.search_query = "git".to_string();
+        app.focus_entry(app.active_session, "mail");
+        assert!(app.filter_entries().iter().any(|(_, (id, _))| id == "mail"));
+        assert_eq!(app.scroll_to_entry, Some((app.active_session, "mail".to_string())));
+
+        app.start_edit_entry("github");
+        assert_eq!(app.edit_linked_ids, vec!["mail"]);
+        app.edit_linked_ids.clear();
+        app.update_entry().unwrap();
+        assert!(app.active_vault().unwrap().get_entry("github").unwrap().linked_ids.is_empty());
+    }
+
+    #[test]
+    fn test_edit_form_seals_keeps_sealed_and_unseals() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        app.start_edit_entry("github");
+        app.edit_note = "recovery codes".into();
+        app.edit_seal = true;
+        app.edit_pin = Zeroizing::new("2468".into());
+        app.edit_pin_confirm = Zeroizing::new("2469".into());
+        assert!(!app.validate_edit_entry());
+        app.edit_pin_confirm = Zeroizing::new("2468".into());
+        assert!(app.validate_edit_entry());
+        app.update_entry().unwrap();
+
+        let sealed = |app: &PassmanApp| app.active_vault().unwrap().get_entry("github").unwrap().clone();
+        assert!(sealed(&app).is_sealed() && sealed(&app).note.is_none());
+        assert!(app.edit_pin.is_empty());
+
+        // Editing other fields leaves the sealed password and note alone
+        app.start_edit_entry("github");
+        assert!(app.edit_was_sealed && app.edit_password.is_empty());
+        app.edit_username = "octocat".into();
+        assert!(app.validate_edit_entry());
+        app.update_entry().unwrap();
+        assert_eq!(sealed(&app).username, "octocat");
+        assert_eq!(sealed(&app).open_sealed("2468").unwrap().password.as_str(), "pw");
+
+        app.start_edit_entry("github");
+        app.edit_seal = false;
+        app.edit_pin = Zeroizing::new("1111".into());
+        assert!(app.update_entry().is_err());
+        app.edit_pin = Zeroizing::new("2468".into());
+        app.update_entry().unwrap();
+        let entry = sealed(&app);
+        assert!(!entry.is_sealed());
+        assert_eq!((entry.password_str().reveal(), entry.note.as_deref()), ("pw", Some("recovery codes")));
+    }
+
+    #[test]
+    fn test_failed_autosave_stays_unsaved_and_offers_retry() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        app.autosave = true;
+        let path = app.active_session().unwrap().vault_file.clone();
+        app.active_session_mut().unwrap().vault_file = dir.path().join("gone/vault.dat").to_string_lossy().into_owned();
+
+        edit_elsewhere(&mut app, "github", "octocat");
+        assert!(app.commit_changes().is_err());
+        assert!(app.active_session().unwrap().needs_save());
+        assert_eq!(username(&app, "github").as_deref(), Some("octocat"));
+
+        app.save_active_vault();
+        let toast = app.toasts.last().unwrap();
+        assert!(toast.toast_type == ToastType::Error && toast.action == Some(ToastAction::RetrySave));
+
+        app.active_session_mut().unwrap().vault_file = path;
+        app.run_toast_action(ToastAction::RetrySave);
+        assert!(!app.active_session().unwrap().needs_save());
+        assert_eq!(app.toasts.last().unwrap().message, "Vault saved");
+    }
+
+    #[test]
+    fn test_spelled_out_password_hides_with_its_reveal() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        let session = app.active_session_mut().unwrap();
+        session.toggle_spelled_out("github");
+        assert!(!session.is_spelled_out("github"), "only a revealed password is spelled out");
+
+        session.set_revealed("github", true);
+        session.set_revealed("mail", true);
+        session.toggle_spelled_out("github");
+        session.toggle_spelled_out("mail");
+        assert!(session.is_spelled_out("github"));
+        session.set_revealed("mail", false);
+        session.set_revealed("mail", true);
+        assert!(!session.is_spelled_out("mail"));
+
+        session.expire_reveals(std::time::Duration::ZERO);
+        assert!(!session.is_revealed("github") && !session.is_spelled_out("github"));
+    }
+
+    #[test]
+    fn test_getting_started_checklist_is_for_new_vaults() {
+        let keys = |steps: &[GettingStartedStep]| steps.iter().map(|step| step.key().to_string()).collect::<Vec<_>>();
+        let some_done = keys(&[GettingStartedStep::FirstEntry, GettingStartedStep::AutoLock]);
+        assert!(GettingStartedStep::checklist_shows(0, false, &[]));
+        assert!(GettingStartedStep::checklist_shows(GETTING_STARTED_MAX_ENTRIES, false, &some_done));
+        assert!(!GettingStartedStep::checklist_shows(GETTING_STARTED_MAX_ENTRIES + 1, false, &[]));
+        assert!(!GettingStartedStep::checklist_shows(0, true, &[]));
+        assert!(!GettingStartedStep::checklist_shows(0, false, &keys(&GettingStartedStep::ALL)));
+
+        // Items take you to where they are done
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        app.run_getting_started_step(GettingStartedStep::Import);
+        assert!(app.current_screen == Screen::ImportExport);
+        app.run_getting_started_step(GettingStartedStep::Generator);
+        assert!(app.current_screen == Screen::AddEntry && app.generate_password);
+    }
+
+    #[test]
+    fn test_context_menu_runs_the_card_actions() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        let ctx = egui::Context::default();
+        let enabled = |app: &PassmanApp, id: &str| -> Vec<EntryAction> {
+            app.entry_actions(0, id).into_iter().filter(|(_, enabled)| *enabled).map(|(action, _)| action).collect()
+        };
+        assert_eq!(enabled(&app, "github").len(), EntryAction::MENU.len() - 1, "no URL to open");
+
+        app.run_entry_action(&ctx, 0, "github", EntryAction::Archive);
+        assert!(app.active_vault().unwrap().get_entry("github").unwrap().archived);
+        // Archived entries only keep their username at hand, and can be unarchived
+        assert_eq!(enabled(&app, "github"), [EntryAction::CopyUsername, EntryAction::Unarchive]);
+        app.run_entry_action(&ctx, 0, "github", EntryAction::Delete);
+        assert_eq!(app.pending_delete, None);
+
+        app.run_entry_action(&ctx, 0, "github", EntryAction::Unarchive);
+        app.run_entry_action(&ctx, 0, "github", EntryAction::Delete);
+        assert_eq!(app.pending_delete.as_deref(), Some("github"));
+        app.run_entry_action(&ctx, 0, "mail", EntryAction::ShowQr);
+        assert!(app.password_qr.as_ref().is_some_and(|qr| qr.id == "mail" && qr.modules.len() == qr.width * qr.width));
+
+        // The menu closes when the vault locks
+        app.open_entry_menu(0, "mail", egui::pos2(10.0, 10.0));
+        app.lock_all_vaults();
+        assert!(app.entry_menu.is_none() && app.password_qr.is_none());
+    }
+
+    #[test]
+    fn test_edit_after_change_elsewhere_asks_before_overwriting() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        app.start_edit_entry("github");
+        edit_elsewhere(&mut app, "github", "renamed-elsewhere");
+        app.edit_username = "octocat".into();
+        app.update_entry().unwrap();
+
+        assert_eq!(app.edit_conflict, Some(EditConflict::Modified));
+        assert_eq!(username(&app, "github").as_deref(), Some("renamed-elsewhere"));
+        assert!(app.current_screen == Screen::EditEntry("github".into()));
+
+        app.resolve_edit_conflict(ConflictResolution::Overwrite).unwrap();
+        assert_eq!(username(&app, "github").as_deref(), Some("octocat"));
+        assert_eq!(app.edit_conflict, None);
+    }
+
+    #[test]
+    fn test_conflict_saved_as_new_id_keeps_both() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        app.start_edit_entry("mail");
+        edit_elsewhere(&mut app, "mail", "changed-elsewhere");
+        app.edit_username = "mine".into();
+        app.update_entry().unwrap();
+        app.resolve_edit_conflict(ConflictResolution::SaveAsNew).unwrap();
+
+        assert_eq!(username(&app, "mail").as_deref(), Some("changed-elsewhere"));
+        assert_eq!(username(&app, "mail (copy)").as_deref(), Some("mine"));
+        let copy = app.active_vault().unwrap().get_entry("mail (copy)").unwrap();
+        assert_eq!(copy.password_str(), "pw");
+    }
+
+    #[test]
+    fn test_deleted_entry_can_be_discarded_or_recreated() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        app.start_edit_entry("github");
+        app.remove_entry("github").unwrap();
+        app.update_entry().unwrap();
+        assert_eq!(app.edit_conflict, Some(EditConflict::Removed));
+
+        app.resolve_edit_conflict(ConflictResolution::Discard).unwrap();
+        assert_eq!(username(&app, "github"), None);
+        assert!(app.current_screen == Screen::Main && app.edit_id.is_empty());
+
+        app.start_edit_entry("mail");
+        app.remove_entry("mail").unwrap();
+        app.update_entry().unwrap();
+        app.resolve_edit_conflict(ConflictResolution::Overwrite).unwrap();
+        assert_eq!(username(&app, "mail").as_deref(), Some("mail-user"));
+    }
+
+    #[test]
+    fn test_health_reports_are_cached_until_entries_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        let analyzer = PasswordHealthAnalyzer::new();
+        let session = app.active_session_mut().unwrap();
+        session.ensure_health_reports(&analyzer);
+        assert!(session.health_reports.as_ref().unwrap().iter().all(|report| report.reused_with == 1));
+
+        app.start_edit_entry("mail");
+        app.edit_password = Zeroizing::new("Vq7#mZ2!pL9$xR4&".into());
+        app.update_entry().unwrap();
+        let session = app.active_session_mut().unwrap();
+        assert!(session.health_reports.is_none());
+        session.ensure_health_reports(&analyzer);
+
+        let reports = session.health_reports.as_deref().unwrap();
+        let mut rows: Vec<&HealthReport> = reports.iter().collect();
+        let mut sort = HealthSort::default();
+        sort.sort(&mut rows);
+        assert_eq!(rows.iter().map(|report| report.entry_id.as_str()).collect::<Vec<_>>(), ["github", "mail"]);
+        sort.click(HealthColumn::Status);
+        sort.sort(&mut rows);
+        assert_eq!(rows[0].entry_id, "mail");
+        sort.click(HealthColumn::Entry);
+        assert_eq!(sort, HealthSort { column: HealthColumn::Entry, descending: false });
+    }
+
+    #[test]
+    fn test_add_form_checks_id_while_typing() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        assert!(app.add_form_blocked());
+
+        app.add_id = "GitHub".into();
+        app.validate_add_id();
+        assert_eq!(app.form_errors.get("add_id").map(String::as_str), Some("Entry ID already exists as 'github'"));
+
+        app.add_id = "gitlab".into();
+        app.validate_add_id();
+        assert!(!app.form_errors.contains_key("add_id"));
+        app.add_username = "ann".into();
+        app.add_password = Zeroizing::new("abc".into());
+        assert!(!app.add_form_blocked());
+        assert!(app.add_password_warning().is_some());
+
+        app.generate_password = true;
+        app.add_password.clear();
+        assert!(!app.add_form_blocked());
+        assert!(app.add_password_warning().is_none());
+    }
+
+    /// The slowest of a few frames of the main screen, drawn headless at a
+    /// desktop window size after a first frame that loads the fonts
+    fn main_screen_frame_time(app: &mut PassmanApp, ctx: &egui::Context) -> std::time::Duration {
+        let input = || egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(1280.0, 800.0))),
+            ..Default::default()
+        };
+        let mut frame = || {
+            let start = std::time::Instant::now();
+            let _ = ctx.run(input(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| app.show_main_screen(ui, ctx));
+            });
+            start.elapsed()
+        };
+        frame();
+        (0..3).map(|_| frame()).max().unwrap()
+    }
+
+    #[test]
+    fn test_pathological_vault_renders_within_a_frame_budget() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        let ctx = egui::Context::default();
+        app.active_session_mut().unwrap().vault.replace_entries(crate::sample::stress_vault(chrono::Utc::now()));
+        app.commit_changes().unwrap();
+        let session = app.active_session_mut().unwrap();
+        let ids: Vec<String> = session.vault.list_entries().into_iter().cloned().collect();
+        for id in ids {
+            session.show_note.insert(id, true);
+        }
+
+        // Every note expanded, the 2 MB one and the attachment opened in full
+        let entry = app.active_vault().unwrap().get_entry("huge-note").unwrap().clone();
+        app.toggle_full_text(0, "huge-note", &entry, None);
+        let full = app.full_text.clone().unwrap();
+        assert!(full.rows.len() > crate::sample::STRESS_NOTE_BYTES / FULL_TEXT_ROW_CHARS);
+        assert!(full.rows.iter().all(|row| row.len() <= FULL_TEXT_ROW_CHARS));
+        // Laid out unguarded, the note alone takes seconds; debug builds get some slack
+        let budget = std::time::Duration::from_millis(if cfg!(debug_assertions) { 1500 } else { 250 });
+        let took = main_screen_frame_time(&mut app, &ctx);
+        assert!(took < budget, "note frame took {:?}", took);
+
+        let entry = app.active_vault().unwrap().get_entry("large-attachment").unwrap().clone();
+        app.toggle_full_text(0, "large-attachment", &entry, Some("dump.txt"));
+        assert!(app.full_text.as_ref().unwrap().shows(0, "large-attachment", Some("dump.txt")));
+        let took = main_screen_frame_time(&mut app, &ctx);
+        assert!(took < budget, "attachment frame took {:?}", took);
+        app.toggle_full_text(0, "large-attachment", &entry, Some("dump.txt"));
+        assert_eq!(app.full_text, None);
+    }
+
+    #[test]
+    fn test_demo_vault_is_never_written() {
+        let mut app = PassmanApp { autosave: false, ..PassmanApp::default() };
+        app.open_demo();
+        assert!(app.current_screen == Screen::Main);
+        let session = app.active_session().unwrap();
+        assert!(session.in_memory && session.name() == DEMO_VAULT);
+        assert_eq!(session.vault.len(), crate::sample::DEMO_ENTRIES);
+        assert!(!app.shows_getting_started());
+
+        // Changes stay in memory and never count as unsaved
+        app.active_session_mut().unwrap().vault.add_entry("scratch".into(), Entry::new("me".into(), "pw".into(), None));
+        app.commit_changes().unwrap();
+        assert!(!app.has_unsaved_changes());
+        assert_eq!(app.active_session().unwrap().entries.len(), crate::sample::DEMO_ENTRIES + 1);
+        assert!(!std::path::Path::new(DEMO_VAULT).exists());
+
+        // Opening it again switches to it as it is
+        app.open_demo();
+        assert_eq!(app.sessions.len(), 1);
+        assert!(app.active_vault().unwrap().get_entry("scratch").is_some());
+        assert!(app.lock_vault());
+        assert!(app.sessions.is_empty());
+    }
+
+    #[test]
+    fn test_add_form_hints_are_offered_not_applied() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+
+        // An otpauth link pasted as the password stays there until accepted
+        app.add_password = Zeroizing::new("otpauth://totp/GitHub:octo?secret=jbswy3dp&issuer=GitHub".into());
+        app.check_add_password();
+        assert_eq!(app.add_hints, vec![AddFormHint::OtpAuthPassword]);
+        assert!(app.add_totp.is_empty());
+        app.accept_add_hint(&AddFormHint::OtpAuthPassword);
+        assert!(app.add_password.is_empty());
+        assert_eq!((app.add_totp.as_str(), app.add_username.as_str()), ("JBSWY3DP", "octo"));
+        assert_eq!(app.add_id_suggestion.as_deref(), Some("github (2)"));
+
+        // The trimmed URL can be put back
+        app.add_url = "https://example.com/login?utm_source=mail#top".into();
+        app.check_add_url();
+        let offer = AddFormHint::TrackedUrl { stripped: "https://example.com/login".into() };
+        assert_eq!(app.add_hints, vec![offer.clone()]);
+        app.accept_add_hint(&offer);
+        assert_eq!(app.add_url, "https://example.com/login");
+        let undo = AddFormHint::TrimmedUrl { original: "https://example.com/login?utm_source=mail#top".into() };
+        assert_eq!(app.add_hints, vec![undo.clone()]);
+        app.accept_add_hint(&undo);
+        assert_eq!(app.add_url, "https://example.com/login?utm_source=mail#top");
+        assert!(app.add_hints.is_empty());
+
+        // An email address as the ID is only offered while the username is empty
+        app.add_id = "ann@example.com".into();
+        app.check_add_id();
+        assert!(app.add_hints.is_empty());
+        app.add_username.clear();
+        app.check_add_id();
+        app.dismiss_add_hint(&AddFormHint::EmailId);
+        assert_eq!(app.add_id, "ann@example.com");
+        app.check_add_id();
+        app.accept_add_hint(&AddFormHint::EmailId);
+        assert_eq!((app.add_id.as_str(), app.add_username.as_str()), ("", "ann@example.com"));
+        assert_eq!(app.add_id_suggestion.as_deref(), Some("example"));
+    }
+
+    #[test]
+    fn test_copy_sequence_steps_through_and_starts_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        let ctx = egui::Context::default();
+        // Copies succeed without touching the system clipboard
+        app.secure_clipboard.set_enabled(false);
+        app.clipboard_clear_secs = 30;
+        let vault = &mut app.active_session_mut().unwrap().vault;
+        let mut entry = vault.get_entry("github").unwrap().clone();
+        entry.copy_sequence = CopyStep::parse_lines("company=ACME\nusername\npassword").unwrap();
+        vault.add_entry("github".to_string(), entry);
+
+        let copy_next = |app: &mut PassmanApp| {
+            app.copy_next_in_sequence(&ctx, 0, "github");
+            let toast = app.toasts.last().unwrap();
+            (toast.message.clone(), toast.action)
+        };
+        assert_eq!(copy_next(&mut app), ("Copied 1/3: company".to_string(), None));
+        assert_eq!(app.copy_sequence_next(0, "github", 3), 1);
+        assert_eq!(copy_next(&mut app).0, "Copied 2/3: username");
+        // Only the password starts the auto-clear, and counts as a use
+        assert_eq!(copy_next(&mut app), ("Copied 3/3: password · auto-clear in 30s".to_string(), Some(ToastAction::ClearClipboard)));
+        assert_eq!(app.active_vault().unwrap().get_entry("github").unwrap().use_count, 1);
+        assert!(app.copy_sequence.is_none());
+        assert_eq!(copy_next(&mut app).0, "Copied 1/3: company");
+
+        // Idle for a minute, or another entry's sequence, starts over
+        app.copy_sequence.as_mut().unwrap().updated_at -= crate::copy_sequence::SEQUENCE_TIMEOUT_SECS;
+        assert_eq!(app.copy_sequence_next(0, "github", 3), 0);
+        assert_eq!(copy_next(&mut app).0, "Copied 1/3: company");
+        assert_eq!(app.copy_sequence_next(0, "mail", 3), 0);
+
+        // Entries without a sequence have nothing to step through
+        let toasts = app.toasts.len();
+        app.copy_next_in_sequence(&ctx, 0, "mail");
+        assert_eq!(app.toasts.len(), toasts);
+    }
+
+    #[test]
+    fn test_conflicted_copy_is_previewed_then_merged() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        let session = app.active_session_mut().unwrap();
+        session.save().unwrap();
+        // A second copy of the vault, as another device saved it
+        let (mut other, _) = VaultManager::load_with_key(&Zeroizing::new("correct horse".to_string()), Some(&session.vault_file)).unwrap();
+        other.add_entry("bank".to_string(), Entry::new("ann".to_string(), "pw".to_string(), None));
+        let copy_path = dir.path().join("vault (conflicted copy).dat").to_string_lossy().into_owned();
+        VaultManager::save_with_key(&other, &session.key, Some(&copy_path)).unwrap();
+        session.conflicted_copies = vec![copy_path.clone()];
+
+        app.preview_conflicted_copy();
+        let preview = app.conflict_preview.as_ref().unwrap();
+        assert_eq!(preview.diff.only_in_b, ["bank"]);
+        assert_eq!(preview.diff.merge_summary().unwrap(), "Merging will add 1 entry");
+
+        app.resolve_conflicted_copy();
+        assert!(app.conflict_preview.is_none());
+        assert!(app.active_session().unwrap().conflicted_copies.is_empty());
+        assert_eq!(username(&app, "bank").as_deref(), Some("ann"));
+        assert!(std::path::Path::new(&format!("{}.resolved", copy_path)).exists());
+    }
+
+    #[test]
+    fn test_master_password_is_only_stored_once_confirmed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        app.active_session_mut().unwrap().master_check = MasterPasswordCheck::new("correct horse").ok();
+        let flagged = |app: &PassmanApp, id: &str| app.active_vault().unwrap().get_entry(id).map(|entry| entry.holds_master_password);
+
+        app.navigate(Screen::AddEntry);
+        app.add_id = "vault".into();
+        app.add_username = "me".into();
+        app.add_password = Zeroizing::new("correct horse".into());
+        app.add_entry().unwrap();
+        assert_eq!(app.master_reuse_warning, Some(EntryForm::Add));
+        assert_eq!(flagged(&app, "vault"), None);
+        assert!(app.current_screen == Screen::AddEntry);
+
+        app.master_reuse_warning = None;
+        app.master_reuse_confirmed = true;
+        app.add_entry().unwrap();
+        app.master_reuse_confirmed = false;
+        assert_eq!(flagged(&app, "vault"), Some(true));
+        assert!(app.current_screen == Screen::Main);
+
+        // Editing another entry to the master password asks too
+        app.start_edit_entry("github");
+        app.edit_password = Zeroizing::new("correct horse".into());
+        app.update_entry().unwrap();
+        assert_eq!(app.master_reuse_warning, Some(EntryForm::Edit));
+        assert_eq!(flagged(&app, "github"), Some(false));
+        app.edit_password = Zeroizing::new("something else".into());
+        app.update_entry().unwrap();
+        assert_eq!(flagged(&app, "github"), Some(false));
+
+        // Changing the password clears the flag
+        app.start_edit_entry("vault");
+        app.edit_password = Zeroizing::new("a new password".into());
+        app.update_entry().unwrap();
+        assert_eq!(flagged(&app, "vault"), Some(false));
+        assert_eq!(app.master_reuse_warning, None);
+    }
+
+    #[test]
+    fn test_undo_delete_through_toast_action() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        app.remove_entry("github").unwrap();
+        app.toast_with_action("Entry 'github' deleted", ToastType::Success, ToastAction::UndoDelete);
+        assert_eq!(app.toasts.last().and_then(|toast| toast.action), Some(ToastAction::UndoDelete));
+
+        app.run_toast_action(ToastAction::UndoDelete);
+        assert_eq!(username(&app, "github").as_deref(), Some("github-user"));
+        // Only one undo per delete
+        assert!(app.undo_delete().is_err());
+        assert_eq!(app.toast_history.last().map(|record| record.message.as_str()), Some("Entry 'github' restored"));
+    }
+
+    #[test]
+    fn test_toast_history_is_capped_and_counts_unseen() {
+        let mut app = PassmanApp::default();
+        for n in 0..TOAST_HISTORY_LEN + 5 {
+            app.toast_info(format!("note {}", n));
+        }
+        assert_eq!(app.toast_history.len(), TOAST_HISTORY_LEN);
+        assert_eq!(app.toast_history[0].message, "note 5");
+        assert_eq!(app.unseen_toasts(), TOAST_HISTORY_LEN);
+        app.toast_history_seen = app.toast_history.len();
+        app.toast_error("failed");
+        assert_eq!(app.unseen_toasts(), 1);
+    }
+
+    /// Type something into every form that can hold a secret
+    fn fill_sensitive_forms(app: &mut PassmanApp) {
+        app.add_id = "draft".into();
+        app.add_username = "me@example.com".into();
+        app.add_url = "https://example.com".into();
+        app.add_password = Zeroizing::new("add-secret".into());
+        app.add_note = "half-typed".into();
+        app.edit_id = "github".into();
+        app.edit_username = "octocat".into();
+        app.edit_password = Zeroizing::new("edit-secret".into());
+        app.edit_note = "recovery codes".into();
+        for field in [
+            &mut app.change_current_password, &mut app.change_new_password, &mut app.change_confirm_password,
+            &mut app.init_password, &mut app.init_confirm, &mut app.login_password,
+            &mut app.reveal_password, &mut app.restore_password, &mut app.share_import_passphrase,
+            &mut app.pin_input, &mut app.edit_pin, &mut app.edit_pin_confirm, &mut app.export_pin,
+        ] {
+            *field = Zeroizing::new("secret".into());
+        }
+        app.show_password_change = true;
+        app.pending_reveal = Some((0, "github".into()));
+        app.pending_pin = Some((0, "github".into(), PinPurpose::Show));
+        app.shared_passphrase = Some(("github".into(), Zeroizing::new("passphrase".into())));
+        app.delete_confirm_text = "github".into();
+        app.set_form_error("add_id", "Entry ID already exists");
+    }
+
+    fn assert_no_form_survives(app: &PassmanApp) {
+        let fields: &[(&str, &str)] = &[
+            ("add_id", &app.add_id), ("add_username", &app.add_username), ("add_url", &app.add_url),
+            ("add_password", &app.add_password), ("add_note", &app.add_note),
+            ("edit_id", &app.edit_id), ("edit_username", &app.edit_username),
+            ("edit_password", &app.edit_password), ("edit_note", &app.edit_note),
+            ("change_current_password", &app.change_current_password),
+            ("change_new_password", &app.change_new_password),
+            ("change_confirm_password", &app.change_confirm_password),
+            ("init_password", &app.init_password), ("init_confirm", &app.init_confirm),
+            ("login_password", &app.login_password), ("reveal_password", &app.reveal_password),
+            ("restore_password", &app.restore_password), ("share_import_passphrase", &app.share_import_passphrase),
+            ("pin_input", &app.pin_input), ("edit_pin", &app.edit_pin),
+            ("edit_pin_confirm", &app.edit_pin_confirm), ("export_pin", &app.export_pin),
+            ("delete_confirm_text", &app.delete_confirm_text),
+        ];
+        for &(name, value) in fields {
+            assert!(value.is_empty(), "{} survived", name);
+        }
+        assert!(!app.show_password_change);
+        assert!(app.pending_reveal.is_none() && app.shared_passphrase.is_none());
+        assert!(app.pending_pin.is_none() && app.opened_sealed.is_none());
+        assert!(app.form_errors.is_empty());
+    }
+
+    #[test]
+    fn test_locking_the_last_vault_clears_every_form() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        app.current_screen = Screen::AddEntry;
+        fill_sensitive_forms(&mut app);
+
+        assert!(app.lock_vault());
+        assert!(app.current_screen == Screen::Welcome);
+        assert_no_form_survives(&app);
+    }
+
+    #[test]
+    fn test_locking_one_of_two_vaults_clears_every_form() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        let other = dir.path().join("other.dat").to_string_lossy().into_owned();
+        let key = VaultManager::init(&Zeroizing::new("battery staple".to_string()), Some(&other)).unwrap();
+        app.sessions.push(VaultSession::new(Vault::new(), other, key));
+        app.current_screen = Screen::EditEntry("github".into());
+        fill_sensitive_forms(&mut app);
+
+        assert!(app.lock_vault());
+        assert_eq!(app.sessions.len(), 1);
+        assert!(app.current_screen == Screen::Main);
+        assert_no_form_survives(&app);
+    }
+
+    #[test]
+    fn test_crossing_the_lock_screens_clears_every_form() {
+        let mut app = PassmanApp::default();
+        for (from, to) in [
+            (Screen::Main, Screen::Welcome),
+            (Screen::Welcome, Screen::Login),
+            (Screen::Login, Screen::Welcome),
+            (Screen::Login, Screen::Main),
+            (Screen::Init, Screen::Main),
+            (Screen::AddEntry, Screen::Login),
+        ] {
+            app.rendered_screen = from;
+            app.current_screen = to;
+            fill_sensitive_forms(&mut app);
+            app.note_screen_change();
+            assert_no_form_survives(&app);
+        }
+
+        // Moving around an unlocked vault keeps the forms
+        app.rendered_screen = Screen::Main;
+        app.current_screen = Screen::AddEntry;
+        fill_sensitive_forms(&mut app);
+        app.note_screen_change();
+        assert_eq!(app.add_password.as_str(), "add-secret");
+        assert!(app.rendered_screen == Screen::AddEntry);
+    }
+
+    #[test]
+    fn test_back_from_settings_while_locked_returns_to_welcome() {
+        let mut app = PassmanApp::default();
+        app.navigate(Screen::Settings);
+        app.navigate_back();
+        assert!(app.current_screen == Screen::Welcome);
+
+        // With nothing left in the history Back stays on Welcome
+        app.navigate_back();
+        assert!(app.current_screen == Screen::Welcome);
+
+        // Back from the login screen wipes the typed password
+        app.navigate(Screen::Login);
+        app.login_password = Zeroizing::new("half typed".to_string());
+        app.navigate_back();
+        assert!(app.current_screen == Screen::Welcome);
+        assert!(app.login_password.is_empty());
+    }
+
+    #[test]
+    fn test_escape_twice_unwinds_edit_then_health() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        app.navigate(Screen::HealthDashboard);
+        app.start_edit_entry("github");
+        assert!(app.current_screen == Screen::EditEntry("github".into()));
+
+        app.navigate_back();
+        assert!(app.current_screen == Screen::HealthDashboard);
+        assert!(app.edit_id.is_empty());
+        app.navigate_back();
+        assert!(app.current_screen == Screen::Main);
+        app.navigate_back();
+        assert!(app.current_screen == Screen::Main);
+
+        // Saving an edit also returns to where it was started
+        app.navigate(Screen::HealthDashboard);
+        app.start_edit_entry("mail");
+        app.edit_username = "postmaster".into();
+        app.update_entry().unwrap();
+        assert!(app.current_screen == Screen::HealthDashboard);
+    }
+
+    #[test]
+    fn test_revisiting_a_screen_unwinds_the_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        app.navigate(Screen::Settings);
+        app.navigate(Screen::HealthDashboard);
+        app.navigate(Screen::Settings);
+        app.navigate_back();
+        assert!(app.current_screen == Screen::Main);
+    }
+
+    #[test]
+    fn test_back_skips_entries_deleted_since() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        app.start_edit_entry("github");
+        app.navigate(Screen::Settings);
+        app.active_session_mut().unwrap().vault.remove_entry("github");
+        app.navigate_back();
+        assert!(app.current_screen == Screen::Main);
+    }
+
+    #[test]
+    fn test_lock_mid_stack_clears_the_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        app.navigate(Screen::HealthDashboard);
+        app.start_edit_entry("github");
+
+        assert!(app.lock_vault());
+        assert!(app.current_screen == Screen::Welcome);
+        assert!(app.screen_history.is_empty());
+        app.navigate_back();
+        assert!(app.current_screen == Screen::Welcome);
+
+        // A stale history can't lead back into a locked vault either
+        app.screen_history = vec![Screen::Main, Screen::HealthDashboard, Screen::EditEntry("github".into())];
+        app.navigate(Screen::Settings);
+        app.navigate_back();
+        assert!(app.current_screen == Screen::Welcome);
+    }
+
+    #[test]
+    fn test_plaintext_export_to_a_sync_folder_asks_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(&dir);
+        let dropbox = dir.path().join("Dropbox");
+        std::fs::create_dir(&dropbox).unwrap();
+        app.export_format = ExportFormat::Csv;
+        app.export_file_path = dropbox.join("passwords.csv").to_string_lossy().into_owned();
+
+        app.request_export();
+        assert_eq!(app.export_risk.as_ref().and_then(|risk| risk.sync_service), Some("Dropbox"));
+        assert!(!dropbox.join("passwords.csv").exists());
+
+        app.export_encrypted_instead();
+        assert!(app.export_risk.is_none());
+        assert!(app.export_format == ExportFormat::Encrypted);
+        let copy = dropbox.join("passwords.dat");
+        let vault = VaultManager::load(&Zeroizing::new("correct horse".to_string()), Some(copy.to_str().unwrap())).unwrap();
+        assert_eq!(vault.len(), 2);
+        assert!(!dropbox.join("passwords.csv").exists());
     }
 }
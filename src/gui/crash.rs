@@ -0,0 +1,139 @@
+//! Crash Reports
+//!
+//! A panic hook that writes a report next to the log files, so a screen
+//! that fails to render can be reported without taking the window down.
+//! Reports carry the version, location and backtrace; anything quoted in
+//! the panic message is dropped since slice and parse panics echo the
+//! data they were working on.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Most recent report written by the hook, picked up by the GUI
+static LAST_REPORT: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+const MAX_MESSAGE_CHARS: usize = 300;
+
+/// Write a crash report for every panic, then run the default hook
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info.payload().downcast_ref::<&str>().map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "<non-string panic payload>".to_string());
+        let location = info.location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+            .unwrap_or_else(|| "unknown".to_string());
+        let thread = std::thread::current().name().unwrap_or("<unnamed>").to_string();
+        let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+
+        let report = build_report(&message, &location, &thread, &backtrace);
+        match write_report(&report) {
+            Ok(path) => {
+                log::error!("Panic at {}; crash report saved to {}", location, path.display());
+                if let Ok(mut last) = LAST_REPORT.lock() {
+                    *last = Some(path);
+                }
+            }
+            Err(e) => log::error!("Panic at {}; could not save crash report: {}", location, e),
+        }
+
+        default_hook(info);
+    }));
+}
+
+/// Path of the report written for the latest panic, if any
+pub fn take_last_report() -> Option<PathBuf> {
+    LAST_REPORT.lock().ok().and_then(|mut last| last.take())
+}
+
+/// Directory crash reports are written to
+pub fn report_dir() -> PathBuf {
+    crate::logging::get_log_file_path()
+        .parent()
+        .map(|dir| dir.to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+fn write_report(report: &str) -> std::io::Result<PathBuf> {
+    let dir = report_dir();
+    std::fs::create_dir_all(&dir)?;
+    let stamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    let mut path = dir.join(format!("crash_{}.txt", stamp));
+    let mut n = 1;
+    while path.exists() {
+        path = dir.join(format!("crash_{}_{}.txt", stamp, n));
+        n += 1;
+    }
+    std::fs::write(&path, report)?;
+    Ok(path)
+}
+
+fn build_report(message: &str, location: &str, thread: &str, backtrace: &str) -> String {
+    format!(
+        "Passman crash report\n\
+         Version: {}\n\
+         Time: {}\n\
+         Platform: {} {}\n\
+         Thread: {}\n\
+         Location: {}\n\
+         Message: {}\n\
+         \n\
+         Backtrace:\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        thread,
+        location,
+        redact(message),
+        backtrace,
+    )
+}
+
+/// Replace quoted and backticked spans with `…`; an unclosed quote drops
+/// the rest of the message
+fn redact(message: &str) -> String {
+    let mut out = String::new();
+    let mut quote = None;
+    for c in message.chars().take(MAX_MESSAGE_CHARS) {
+        match quote {
+            Some(open) if c == open => {
+                out.push('…');
+                out.push(c);
+                quote = None;
+            }
+            Some(_) => {}
+            None if c == '`' || c == '\'' || c == '"' => {
+                out.push(c);
+                quote = Some(c);
+            }
+            None => out.push(c),
+        }
+    }
+    if quote.is_some() {
+        out.push('…');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_omits_quoted_data() {
+        let message = "byte index 40 is not a char boundary; it is inside 'é' (bytes 39..41) of `hunter2 is the wifi password`";
+        let redacted = redact(message);
+        assert!(!redacted.contains("hunter2"));
+        assert!(!redacted.contains('é'));
+        assert!(redacted.starts_with("byte index 40 is not a char boundary"));
+
+        assert_eq!(redact("unterminated `secret"), "unterminated `…");
+
+        let report = build_report(message, "src/gui/screens/main.rs:10:5", "main", "<backtrace>");
+        assert!(report.contains(env!("CARGO_PKG_VERSION")));
+        assert!(report.contains("src/gui/screens/main.rs:10:5"));
+        assert!(!report.contains("hunter2"));
+    }
+}
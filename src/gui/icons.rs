@@ -0,0 +1,174 @@
+//! Entry Icons
+//!
+//! Built-in glyphs and embedded PNG icons for entry cards. Favicons are
+//! fetched only on request, downscaled and stored in the vault, so the
+//! vault never reaches out to a site just because it was opened.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io::Cursor;
+use std::sync::mpsc;
+
+use eframe::egui;
+use image::imageops::FilterType;
+use crate::model::{EntryIcon, ICON_GLYPHS};
+use crate::net::{NetPolicy, Target};
+
+/// Stored icons are downscaled to this many pixels square
+pub const ICON_PIXELS: u32 = 32;
+
+/// Shown when an entry has no icon, or its PNG can't be decoded
+pub const DEFAULT_GLYPH: &str = "🔑";
+
+/// Favicons are small; anything bigger is not worth downloading
+const MAX_FAVICON_BYTES: usize = 256 * 1024;
+
+const FEATURE: &str = "Favicon download";
+
+/// What to draw for an entry
+#[derive(Clone)]
+pub enum IconImage {
+    Glyph(&'static str),
+    Texture(egui::TextureHandle),
+}
+
+impl IconImage {
+    pub fn show(&self, ui: &mut egui::Ui, size: f32) {
+        match self {
+            Self::Glyph(glyph) => {
+                ui.label(egui::RichText::new(*glyph).size(size));
+            }
+            Self::Texture(texture) => {
+                ui.add(egui::Image::new((texture.id(), egui::vec2(size, size))));
+            }
+        }
+    }
+}
+
+/// Decoded PNG icons, keyed by a hash of their bytes so each image is
+/// decoded and uploaded once rather than every frame
+#[derive(Default)]
+pub struct IconCache {
+    textures: HashMap<u64, Option<egui::TextureHandle>>,
+}
+
+impl IconCache {
+    pub fn get(&mut self, ctx: &egui::Context, icon: Option<&EntryIcon>) -> IconImage {
+        let bytes = match icon {
+            None => return IconImage::Glyph(DEFAULT_GLYPH),
+            Some(icon) => match icon {
+                EntryIcon::Glyph(_) => return IconImage::Glyph(icon.glyph().unwrap_or(DEFAULT_GLYPH)),
+                EntryIcon::Png(bytes) => bytes,
+            },
+        };
+
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        let key = hasher.finish();
+
+        // Failed decodes are cached too, so a bad icon isn't retried every frame
+        let texture = self.textures.entry(key).or_insert_with(|| {
+            let image = image::load_from_memory(bytes).ok()?.to_rgba8();
+            let size = [image.width() as usize, image.height() as usize];
+            let pixels = egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+            Some(ctx.load_texture(format!("entry-icon-{:016x}", key), pixels, egui::TextureOptions::LINEAR))
+        });
+        match texture {
+            Some(texture) => IconImage::Texture(texture.clone()),
+            None => IconImage::Glyph(DEFAULT_GLYPH),
+        }
+    }
+
+    /// Drop all textures, e.g. when vaults are locked
+    pub fn clear(&mut self) {
+        self.textures.clear();
+    }
+}
+
+/// Pick one of the built-in glyphs; returns the chosen name
+pub fn glyph_picker(ui: &mut egui::Ui, current: Option<&EntryIcon>) -> Option<String> {
+    let mut picked = None;
+    ui.horizontal_wrapped(|ui| {
+        for (name, glyph) in ICON_GLYPHS {
+            let selected = matches!(current, Some(EntryIcon::Glyph(n)) if n.as_str() == *name);
+            if ui.selectable_label(selected, egui::RichText::new(*glyph).size(18.0))
+                .on_hover_text(*name)
+                .clicked()
+            {
+                picked = Some(name.to_string());
+            }
+        }
+    });
+    picked
+}
+
+/// `https://host/favicon.ico` for an entry URL; plain-http sites are asked over https
+pub fn favicon_url(entry_url: &str) -> Result<String, String> {
+    let url = entry_url.trim();
+    let url = if url.contains("://") { url.to_string() } else { format!("https://{}", url) };
+    let target = Target::parse(&url).map_err(|e| e.to_string())?;
+    if target.scheme != "https" && target.scheme != "http" {
+        return Err(format!("Can't fetch a favicon for a {} URL", target.scheme));
+    }
+    let host = if target.host.contains(':') { format!("[{}]", target.host) } else { target.host };
+    Ok(match target.port {
+        443 | 80 => format!("https://{}/favicon.ico", host),
+        port => format!("https://{}:{}/favicon.ico", host, port),
+    })
+}
+
+/// Decode an ICO or PNG, downscale it and re-encode as a PNG small enough
+/// to store in the vault
+pub fn to_entry_icon(bytes: &[u8]) -> Result<EntryIcon, String> {
+    let image = image::load_from_memory(bytes).map_err(|e| format!("Not a usable image: {}", e))?;
+    let image = if image.width() > ICON_PIXELS || image.height() > ICON_PIXELS {
+        image.resize(ICON_PIXELS, ICON_PIXELS, FilterType::Lanczos3)
+    } else {
+        image
+    };
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| format!("Could not encode icon: {}", e))?;
+    EntryIcon::png(png)
+}
+
+/// Fetch and convert a site's favicon in the background
+pub fn spawn_favicon_fetch(entry_url: &str) -> mpsc::Receiver<Result<EntryIcon, String>> {
+    let (sender, receiver) = mpsc::channel();
+    let url = favicon_url(entry_url);
+    std::thread::spawn(move || {
+        let result = url.and_then(|url| {
+            let bytes = NetPolicy::current()
+                .fetch_https(FEATURE, &url, MAX_FAVICON_BYTES)
+                .map_err(|e| e.to_string())?;
+            to_entry_icon(&bytes)
+        });
+        let _ = sender.send(result);
+    });
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_favicon_url_and_downscale() {
+        assert_eq!(favicon_url("https://github.com/login").unwrap(), "https://github.com/favicon.ico");
+        assert_eq!(favicon_url("example.com").unwrap(), "https://example.com/favicon.ico");
+        assert_eq!(favicon_url("http://intranet:8443/x").unwrap(), "https://intranet:8443/favicon.ico");
+        assert!(favicon_url("ftp://files.example").is_err());
+
+        let big = image::DynamicImage::new_rgba8(128, 64);
+        let mut png = Vec::new();
+        big.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png).unwrap();
+        let EntryIcon::Png(stored) = to_entry_icon(&png).unwrap() else {
+            panic!("expected a PNG icon");
+        };
+        let decoded = image::load_from_memory(&stored).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (32, 16));
+        assert!(to_entry_icon(b"not an image").is_err());
+    }
+}
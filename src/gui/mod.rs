@@ -9,6 +9,10 @@
 //! - `widgets` - Reusable UI widgets (buttons, password strength)
 //! - `toasts` - Toast notification system
 //! - `overlays` - Modal dialogs, loading overlay, onboarding
+//! - `markdown` - Markdown subset for entry notes
+//! - `crash` - Panic hook and crash reports
+//! - `icons` - Entry icons, favicon download and texture cache
+//! - `window` - Application icon, window title and lock state
 //! - `app` - Main PassmanApp struct and state management
 //! - `screens` - Individual screen implementations
 //!   - `welcome` - Welcome, Init, Login screens
@@ -23,8 +27,74 @@ pub mod theme;
 pub mod widgets;
 pub mod toasts;
 pub mod overlays;
+pub mod markdown;
+pub mod crash;
+pub mod icons;
+pub mod window;
 pub mod app;
 pub mod screens;
 
 // Re-export main types for convenience
 pub use app::PassmanApp;
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    /// Every `.rs` file under `dir`
+    fn rust_sources(dir: &Path, files: &mut Vec<std::path::PathBuf>) {
+        for entry in std::fs::read_dir(dir).unwrap().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                rust_sources(&path, files);
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                files.push(path);
+            }
+        }
+    }
+
+    #[test]
+    fn test_single_gui_implementation() {
+        // The old monolithic src/gui.rs drifted from this module (deletes
+        // without confirmation, a different strength scorer); keep one GUI
+        let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        assert!(!src.join("gui.rs").exists(), "src/gui.rs is back; the GUI lives in src/gui/");
+
+        let mut files = Vec::new();
+        rust_sources(&src, &mut files);
+        for (item, home) in [
+            ("struct PassmanApp ", "gui/app.rs"),
+            ("enum Screen ", "gui/types.rs"),
+            ("enum ExportFormat ", "gui/types.rs"),
+            ("enum ImportFormat ", "gui/types.rs"),
+        ] {
+            let defined_in: Vec<String> = files.iter()
+                .filter(|path| {
+                    std::fs::read_to_string(path).unwrap().lines()
+                        .any(|line| line.trim_start().trim_start_matches("pub ").starts_with(item))
+                })
+                .map(|path| path.strip_prefix(&src).unwrap().to_string_lossy().replace('\\', "/"))
+                .collect();
+            assert!(
+                defined_in.is_empty() || defined_in == [home],
+                "`{}` should only be defined in {}, found in {:?}", item.trim(), home, defined_in
+            );
+        }
+    }
+
+    #[test]
+    fn test_feedback_goes_through_toasts() {
+        // The old message bar is no longer rendered, so anything written to
+        // it would never be seen; errors and notices are toasts
+        let gui = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/gui");
+        let mut files = Vec::new();
+        rust_sources(&gui, &mut files);
+        // This file names them below, so it is left out
+        for path in files.iter().filter(|path| **path != gui.join("mod.rs")) {
+            let source = std::fs::read_to_string(path).unwrap();
+            for pattern in ["show_message(", "clear_message(", "message_type", "MessageType", "self.message "] {
+                assert!(!source.contains(pattern), "{} uses the removed message bar (`{}`)", path.display(), pattern);
+            }
+        }
+    }
+}
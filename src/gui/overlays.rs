@@ -1,12 +1,14 @@
 //! Overlays Module
 //!
-//! Modal dialogs, loading overlays, and onboarding wizard.
+//! Modal dialogs, loading overlays, onboarding wizard and command palette.
 
 #![allow(dead_code)]
 
 use eframe::egui;
+use super::theme::{self, Motion};
 use super::types::{SPACING, BUTTON_HEIGHT};
 use super::widgets::ButtonWidgets;
+use super::app::PassmanApp;
 
 /// Render confirmation dialog for delete
 pub fn render_confirmation_dialog(
@@ -65,8 +67,8 @@ pub fn render_confirmation_dialog(
     }
 }
 
-/// Show loading overlay with animated spinner
-pub fn render_loading_overlay(ctx: &egui::Context, is_loading: bool, loading_message: &str) {
+/// Show loading overlay with a spinner; it only turns with full motion
+pub fn render_loading_overlay(ctx: &egui::Context, is_loading: bool, loading_message: &str, motion: Motion) {
     if !is_loading {
         return;
     }
@@ -87,15 +89,14 @@ pub fn render_loading_overlay(ctx: &egui::Context, is_loading: bool, loading_mes
             let center = screen_rect.center();
             
             // Draw spinner animation
-            let time = ctx.input(|i| i.time);
-            let angle = (time * 2.0) % (2.0 * std::f64::consts::PI);
+            let (angle, opacity) = theme::spinner_phase(motion, ctx.input(|i| i.time));
             let spinner_radius = 20.0;
             let spinner_center = egui::pos2(center.x, center.y - 20.0);
             
             // Draw spinning arc
             for i in 0..8 {
                 let segment_angle = angle + (i as f64 * std::f64::consts::PI / 4.0);
-                let alpha = ((i as f32 + 1.0) / 8.0 * 255.0) as u8;
+                let alpha = ((i as f32 + 1.0) / 8.0 * 255.0 * opacity) as u8;
                 let start = egui::pos2(
                     spinner_center.x + (spinner_radius * segment_angle.cos() as f32),
                     spinner_center.y + (spinner_radius * segment_angle.sin() as f32),
@@ -123,7 +124,8 @@ pub fn render_loading_overlay(ctx: &egui::Context, is_loading: bool, loading_mes
             }
         });
     
-    // Request repaint for animation
+    // Keep drawing: the spinner moves and the background work is polled
+    // once a frame
     ctx.request_repaint();
 }
 
@@ -185,7 +187,9 @@ pub fn render_onboarding(
                     ui.add_space(SPACING * 2.0);
                     ui.label("Keyboard shortcuts (when vault is open):");
                     ui.add_space(SPACING / 2.0);
+                    ui.label("• Ctrl+K - Command palette");
                     ui.label("• Ctrl+N - Create new entry");
+                    ui.label("• Ctrl+Shift+N - New entry from the clipboard");
                     ui.label("• Ctrl+F - Search entries");
                     ui.label("• Ctrl+L - Lock vault");
                     ui.label("• Ctrl+H - Health dashboard");
@@ -247,3 +251,118 @@ pub fn render_onboarding(
             });
         });
 }
+
+/// An action in the command palette. Each screen module lists its own in a
+/// `PALETTE_COMMANDS` slice; `screens::palette_commands` collects them.
+pub struct PaletteCommand {
+    pub label: &'static str,
+    /// Extra words to match on, e.g. "preferences" for Settings
+    pub keywords: &'static str,
+    pub shortcut: Option<&'static str>,
+    /// Whether the command applies in the current state
+    pub available: fn(&PassmanApp) -> bool,
+    pub run: fn(&mut PassmanApp),
+}
+
+/// Command palette state while it is open
+#[derive(Default)]
+pub struct CommandPalette {
+    pub query: String,
+    pub selected: usize,
+    /// Entry picked in the first step (session, id); the palette then
+    /// lists actions for it
+    pub entry: Option<(usize, String)>,
+}
+
+/// One row of the palette
+pub struct PaletteItem {
+    pub label: String,
+    pub detail: String,
+}
+
+/// What the user did in the palette this frame
+pub enum PaletteEvent {
+    Picked(usize),
+    Back,
+    Close,
+}
+
+/// Render the command palette. Keyboard-only: arrows move, Enter picks,
+/// Escape goes back a step (`Back`) or closes.
+pub fn render_command_palette(
+    ctx: &egui::Context,
+    palette: &mut CommandPalette,
+    title: &str,
+    items: &[PaletteItem],
+) -> Option<PaletteEvent> {
+    let (up, down, enter, escape) = ctx.input_mut(|i| (
+        i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+        i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+        i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+        i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+    ));
+    if escape {
+        return Some(if palette.entry.is_some() { PaletteEvent::Back } else { PaletteEvent::Close });
+    }
+    if down && palette.selected + 1 < items.len() {
+        palette.selected += 1;
+    }
+    if up {
+        palette.selected = palette.selected.saturating_sub(1);
+    }
+    palette.selected = palette.selected.min(items.len().saturating_sub(1));
+
+    let mut event = (enter && !items.is_empty()).then_some(PaletteEvent::Picked(palette.selected));
+
+    egui::Area::new(egui::Id::new("command_palette_overlay"))
+        .anchor(egui::Align2::LEFT_TOP, egui::vec2(0.0, 0.0))
+        .order(egui::Order::Middle)
+        .show(ctx, |ui| {
+            ui.painter().rect_filled(ctx.screen_rect(), 0.0, egui::Color32::from_black_alpha(150));
+        });
+
+    egui::Window::new("command_palette")
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .fixed_size([460.0, 0.0])
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 80.0))
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            ui.label(egui::RichText::new(title).size(12.0).weak());
+            ui.add_space(SPACING / 2.0);
+            let response = ui.add(egui::TextEdit::singleline(&mut palette.query)
+                .desired_width(f32::INFINITY)
+                .hint_text("Type a command or entry name"));
+            response.request_focus();
+            if response.changed() {
+                palette.selected = 0;
+            }
+            ui.add_space(SPACING / 2.0);
+
+            if items.is_empty() {
+                ui.label(egui::RichText::new("No matches").weak());
+            }
+            for (index, item) in items.iter().enumerate() {
+                let selected = index == palette.selected;
+                let row = ui.horizontal(|ui| {
+                    ui.set_min_width(ui.available_width());
+                    let label = ui.selectable_label(selected, &item.label);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.label(egui::RichText::new(&item.detail).size(11.0).weak());
+                    });
+                    label
+                });
+                if selected {
+                    row.inner.scroll_to_me(None);
+                }
+                if row.inner.clicked() {
+                    event = Some(PaletteEvent::Picked(index));
+                }
+            }
+            ui.add_space(SPACING / 2.0);
+            ui.label(egui::RichText::new("↑↓ to move · Enter to run · Esc to close").size(11.0).weak());
+        });
+
+    event
+}
@@ -3,15 +3,42 @@
 //! Add and Edit entry screens.
 
 use eframe::egui;
-use super::super::types::{Screen, SPACING, INPUT_WIDTH, BUTTON_HEIGHT};
+use zeroize::Zeroize;
+use super::super::types::{AddFormHint, GeneratorMode, Screen, SPACING, INPUT_WIDTH, BUTTON_HEIGHT, FIELD_CHAR_LIMIT, NOTE_CHAR_LIMIT};
 use super::super::theme;
 use super::super::widgets;
+use super::super::icons;
 use super::super::app::PassmanApp;
+use super::super::overlays::PaletteCommand;
+use crate::model::EntryIcon;
+use crate::paste::PasteField;
+use crate::utils::pronounceable_entropy_bits;
+
+/// Palette actions for the entry screens
+pub(super) const PALETTE_COMMANDS: &[PaletteCommand] = &[
+    PaletteCommand {
+        label: "Add entry",
+        keywords: "new create password",
+        shortcut: Some("Ctrl+N"),
+        available: PassmanApp::is_unlocked,
+        run: |app| {
+            app.clear_add_form();
+            app.navigate(Screen::AddEntry);
+        },
+    },
+    PaletteCommand {
+        label: "New entry from clipboard",
+        keywords: "paste credentials add create",
+        shortcut: Some("Ctrl+Shift+N"),
+        available: PassmanApp::is_unlocked,
+        run: PassmanApp::new_from_clipboard,
+    },
+];
 
 impl PassmanApp {
     /// Show add entry screen
     pub fn show_add_entry_screen(&mut self, ui: &mut egui::Ui) {
-        let current_theme = self.current_theme.clone();
+        let current_theme = self.current_theme;
         let border_color = theme::border_color(&current_theme);
         
         // ════════════════════════════════════════════════════════════════════
@@ -35,8 +62,7 @@ impl PassmanApp {
                             .min_size(egui::vec2(70.0, 28.0));
                         
                         if ui.add(back_btn).clicked() {
-                            self.current_screen = Screen::Main;
-                            self.clear_add_form();
+                            self.navigate_back();
                         }
                     });
                 });
@@ -48,6 +74,31 @@ impl PassmanApp {
             ui.vertical_centered(|ui| {
                 ui.add_space(SPACING);
 
+                let template_names: Vec<String> = crate::config::get_config().templates.keys().cloned().collect();
+                if !template_names.is_empty() {
+                    ui.horizontal(|ui| {
+                        ui.label("Template:");
+                        let mut selected = self.add_template.clone();
+                        egui::ComboBox::from_id_salt("add_template")
+                            .selected_text(selected.as_deref().unwrap_or("None"))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut selected, None, "None");
+                                for name in &template_names {
+                                    ui.selectable_value(&mut selected, Some(name.clone()), name);
+                                }
+                            });
+                        if selected != self.add_template {
+                            match selected {
+                                Some(name) => self.apply_add_template(&name),
+                                None => self.add_template = None,
+                            }
+                        }
+                    });
+                    ui.add_space(SPACING);
+                }
+
+                // Enter in a single-line field saves, like the button
+                let mut submit = false;
                 egui::Grid::new("add_entry_grid")
                     .num_columns(2)
                     .spacing([SPACING * 2.0, SPACING])
@@ -55,25 +106,66 @@ impl PassmanApp {
                 .show(ui, |ui| {
                     ui.label("Entry ID:");
                     ui.vertical(|ui| {
-                        let id_response = ui.add(egui::TextEdit::singleline(&mut self.add_id)
+                        let hint = self.add_id_suggestion.clone().unwrap_or_else(|| "e.g., gmail, work".to_string());
+                        let id_response = widgets::limited_text_edit(ui, &mut self.add_id, FIELD_CHAR_LIMIT, |text| egui::TextEdit::singleline(text)
                             .desired_width(INPUT_WIDTH)
-                            .hint_text("e.g., gmail, work"));
+                            .hint_text(hint));
                         if id_response.changed() {
-                            self.clear_form_error("add_id");
+                            self.validate_add_id();
+                            self.suggest_add_id();
+                            self.check_add_id();
+                        }
+                        if id_response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Tab)) {
+                            self.accept_add_id_suggestion();
+                        }
+                        submit |= id_response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        if let Some(suggestion) = self.add_id_suggestion.as_ref().filter(|_| self.add_id.is_empty()) {
+                            ui.label(egui::RichText::new(format!("Press Tab here to use \"{}\"", suggestion)).weak().small());
                         }
                         self.show_field_error(ui, "add_id");
+                        self.show_add_hints(ui, "add_id");
                     });
                     ui.end_row();
 
                     ui.label("Username:");
                     ui.vertical(|ui| {
-                        let username_response = ui.add(egui::TextEdit::singleline(&mut self.add_username)
+                        let username_response = widgets::limited_text_edit(ui, &mut self.add_username, FIELD_CHAR_LIMIT, |text| egui::TextEdit::singleline(text)
                             .desired_width(INPUT_WIDTH)
                             .hint_text("Username or email"));
                         if username_response.changed() {
                             self.clear_form_error("add_username");
+                            self.mark_add_field_reviewed(PasteField::Username);
+                            if self.add_hints.contains(&AddFormHint::EmailId) {
+                                self.check_add_id();
+                            }
+                        }
+                        if username_response.lost_focus() && self.add_username.trim().is_empty() {
+                            self.set_form_error("add_username", "Username is required");
                         }
+                        if username_response.lost_focus() {
+                            self.suggest_add_id();
+                        }
+                        submit |= username_response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
                         self.show_field_error(ui, "add_username");
+                        self.show_pasted_hint(ui, PasteField::Username);
+                    });
+                    ui.end_row();
+
+                    ui.label("URL:");
+                    ui.vertical(|ui| {
+                        let url_response = widgets::limited_text_edit(ui, &mut self.add_url, FIELD_CHAR_LIMIT, |text| egui::TextEdit::singleline(text)
+                            .desired_width(INPUT_WIDTH)
+                            .hint_text("Optional, e.g. https://github.com/login"));
+                        if url_response.changed() {
+                            self.mark_add_field_reviewed(PasteField::Url);
+                            self.check_add_url();
+                        }
+                        if url_response.lost_focus() {
+                            self.suggest_add_id();
+                        }
+                        submit |= url_response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        self.show_pasted_hint(ui, PasteField::Url);
+                        self.show_add_hints(ui, "add_url");
                     });
                     ui.end_row();
 
@@ -82,63 +174,69 @@ impl PassmanApp {
                     ui.end_row();
 
                     if self.generate_password {
-                        ui.label("Length:");
-                        ui.add(egui::Slider::new(&mut self.password_length, 8..=64)
-                            .text("characters"));
-                        ui.end_row();
+                        self.show_generator_rows(ui);
                     } else {
                         ui.label("Password:");
+                        ui.vertical(|ui| {
+                            let field = widgets::password_field(
+                                ui, &mut self.add_password, &mut self.add_show_password,
+                                "", INPUT_WIDTH, true,
+                            );
+                            if field.response.changed() {
+                                self.clear_form_error("add_password");
+                                self.mark_add_field_reviewed(PasteField::Password);
+                                self.check_add_password();
+                            }
+                            submit |= field.submitted;
+                            self.show_field_error(ui, "add_password");
+                            self.show_pasted_hint(ui, PasteField::Password);
+                            self.show_add_hints(ui, "add_password");
+                            if let Some(warning) = self.add_password_warning() {
+                                ui.colored_label(egui::Color32::from_rgb(251, 191, 36), format!("⚠ {}", warning));
+                            }
+                        });
+                        ui.end_row();
+                    }
+
+                    if !self.add_totp.is_empty() {
+                        ui.label("2FA secret:");
                         ui.vertical(|ui| {
                             ui.horizontal(|ui| {
-                                let field_height = 24.0;
-                                let btn_width = 40.0;
-                                let gap = 8.0;
-                                let field_width = INPUT_WIDTH - btn_width - gap;
-                                
-                                let pw_response = ui.add_sized(
-                                    egui::vec2(field_width, field_height),
-                                    egui::TextEdit::singleline(&mut self.add_password)
-                                        .password(!self.add_show_password)
-                                );
-                                if pw_response.changed() {
-                                    self.clear_form_error("add_password");
-                                }
-                                
-                                let eye_text = if self.add_show_password { "🙈" } else { "👁" };
-                                if ui.add_sized(
-                                    egui::vec2(btn_width, field_height),
-                                    egui::Button::new(eye_text)
-                                ).clicked() {
-                                    self.add_show_password = !self.add_show_password;
+                                ui.label(egui::RichText::new("From a pasted otpauth link").weak());
+                                if ui.small_button("Remove").clicked() {
+                                    self.add_totp.zeroize();
+                                    self.mark_add_field_reviewed(PasteField::Totp);
                                 }
                             });
-                            self.show_field_error(ui, "add_password");
+                            self.show_pasted_hint(ui, PasteField::Totp);
                         });
                         ui.end_row();
-                        
-                        // Visual password strength indicator
-                        if !self.add_password.is_empty() {
-                            ui.label("");
-                            ui.scope(|ui| {
-                                let password = self.add_password.clone();
-                                widgets::show_password_strength_indicator(ui, &password);
-                            });
-                            ui.end_row();
-                        }
                     }
 
                     ui.label("Note:");
-                    ui.add(egui::TextEdit::multiline(&mut self.add_note)
-                        .desired_width(INPUT_WIDTH)
-                        .desired_rows(3)
-                        .hint_text("Optional notes"));
+                    ui.vertical(|ui| {
+                        let note_response = widgets::limited_text_edit(ui, &mut self.add_note, NOTE_CHAR_LIMIT, |text| egui::TextEdit::multiline(text)
+                            .desired_width(INPUT_WIDTH)
+                            .desired_rows(3)
+                            .hint_text("Optional notes"));
+                        if note_response.changed() {
+                            self.mark_add_field_reviewed(PasteField::Note);
+                        }
+                        self.show_pasted_hint(ui, PasteField::Note);
+                    });
                     ui.end_row();
                 });
 
                 ui.add_space(SPACING * 2.0);
                 
-                if self.success_button(ui, "Add Entry", [150.0, BUTTON_HEIGHT]).clicked() && self.validate_add_entry() {
+                let blocked = self.add_form_blocked();
+                let clicked = ui.add_enabled_ui(!blocked, |ui| self.success_button(ui, "Add Entry", [150.0, BUTTON_HEIGHT]))
+                    .inner
+                    .on_disabled_hover_text("Fill in an unused entry ID, a username and a password")
+                    .clicked();
+                if (clicked || (submit && !blocked)) && self.validate_add_entry() {
                     match self.add_entry() {
+                        Ok(()) if self.master_reuse_warning.is_some() => {}
                         Ok(()) => {
                             self.toast_success("Entry added successfully!");
                             self.clear_form_errors();
@@ -156,7 +254,7 @@ impl PassmanApp {
 
     /// Show edit entry screen
     pub fn show_edit_entry_screen(&mut self, ui: &mut egui::Ui, id: &str) {
-        let current_theme = self.current_theme.clone();
+        let current_theme = self.current_theme;
         let border_color = theme::border_color(&current_theme);
         
         // ════════════════════════════════════════════════════════════════════
@@ -180,9 +278,7 @@ impl PassmanApp {
                             .min_size(egui::vec2(70.0, 28.0));
                         
                         if ui.add(back_btn).clicked() {
-                            self.current_screen = Screen::Main;
-                            self.clear_edit_form();
-                            self.clear_form_errors();
+                            self.navigate_back();
                         }
                     });
                 });
@@ -194,6 +290,8 @@ impl PassmanApp {
             ui.vertical_centered(|ui| {
                 ui.add_space(SPACING);
             
+                let mut submit = false;
+                let mut regenerate = false;
                 egui::Grid::new("edit_entry_grid")
                 .num_columns(2)
                 .spacing([SPACING * 2.0, SPACING])
@@ -201,7 +299,7 @@ impl PassmanApp {
                 .show(ui, |ui| {
                     ui.label("Username:");
                     ui.vertical(|ui| {
-                        let username_response = ui.add(egui::TextEdit::singleline(&mut self.edit_username)
+                        let username_response = widgets::limited_text_edit(ui, &mut self.edit_username, FIELD_CHAR_LIMIT, |text| egui::TextEdit::singleline(text)
                             .desired_width(INPUT_WIDTH)
                             .hint_text("Username or email"));
                         if username_response.changed() {
@@ -211,68 +309,156 @@ impl PassmanApp {
                     });
                     ui.end_row();
 
-                    ui.label("");
-                    ui.checkbox(&mut self.edit_generate_password, "Generate new password");
-                    ui.end_row();
-
-                    if self.edit_generate_password {
-                        ui.label("Length:");
-                        ui.add(egui::Slider::new(&mut self.password_length, 8..=64)
-                            .text("characters"));
+                    if self.edit_was_sealed {
+                        ui.label("Password:");
+                        ui.label(egui::RichText::new("🛡 Sealed under the entry's PIN, with the note").weak());
                         ui.end_row();
                     } else {
-                        ui.label("Password:");
-                        ui.vertical(|ui| {
-                            ui.horizontal(|ui| {
-                                let field_height = 24.0;
-                                let btn_width = 40.0;
-                                let gap = 8.0;
-                                let field_width = INPUT_WIDTH - btn_width - gap;
-                                
-                                let pw_response = ui.add_sized(
-                                    egui::vec2(field_width, field_height),
-                                    egui::TextEdit::singleline(&mut self.edit_password)
-                                        .password(!self.edit_show_password)
-                                );
-                                if pw_response.changed() {
-                                    self.clear_form_error("edit_password");
-                                }
-                                
-                                let eye_text = if self.edit_show_password { "🙈" } else { "👁" };
-                                if ui.add_sized(
-                                    egui::vec2(btn_width, field_height),
-                                    egui::Button::new(eye_text)
-                                ).clicked() {
-                                    self.edit_show_password = !self.edit_show_password;
-                                }
-                            });
-                            self.show_field_error(ui, "edit_password");
+                        ui.label("");
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut self.edit_generate_password, "Generate new password");
+                            if let Some(prefs) = &self.edit_generation {
+                                regenerate = ui.button("🔄 Regenerate (same rules)")
+                                    .on_hover_text(format!("A new password made like the last one: {}", prefs.describe()))
+                                    .clicked();
+                            }
                         });
                         ui.end_row();
 
-                        // Visual password strength indicator
-                        if !self.edit_password.is_empty() {
-                            ui.label("");
-                            ui.scope(|ui| {
-                                let password = self.edit_password.clone();
-                                widgets::show_password_strength_indicator(ui, &password);
+                        if self.edit_generate_password {
+                            self.show_generator_rows(ui);
+                        } else {
+                            ui.label("Password:");
+                            ui.vertical(|ui| {
+                                let field = widgets::password_field(
+                                    ui, &mut self.edit_password, &mut self.edit_show_password,
+                                    "", INPUT_WIDTH, true,
+                                );
+                                if field.response.changed() {
+                                    self.clear_form_error("edit_password");
+                                }
+                                submit |= field.submitted;
+                                self.show_field_error(ui, "edit_password");
                             });
                             ui.end_row();
                         }
                     }
 
-                    ui.label("Note:");
-                    ui.add(egui::TextEdit::multiline(&mut self.edit_note)
-                        .desired_width(INPUT_WIDTH)
-                        .desired_rows(3)
-                        .hint_text("Optional notes"));
+                    ui.label("High security:");
+                    ui.vertical(|ui| {
+                        ui.checkbox(&mut self.edit_seal, "Seal the password and note under a PIN")
+                            .on_hover_text("Copying, showing or exporting them then asks for this PIN as well");
+                        if self.edit_seal != self.edit_was_sealed {
+                            let hint = if self.edit_seal { "New PIN" } else { "PIN" };
+                            let pin = ui.add(egui::TextEdit::singleline(&mut *self.edit_pin)
+                                .password(true)
+                                .hint_text(hint)
+                                .desired_width(INPUT_WIDTH));
+                            let confirm = self.edit_seal.then(|| ui.add(egui::TextEdit::singleline(&mut *self.edit_pin_confirm)
+                                .password(true)
+                                .hint_text("Confirm PIN")
+                                .desired_width(INPUT_WIDTH)));
+                            if pin.changed() || confirm.is_some_and(|confirm| confirm.changed()) {
+                                self.clear_form_error("edit_pin");
+                            }
+                            self.show_field_error(ui, "edit_pin");
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Icon:");
+                    ui.vertical(|ui| {
+                        ui.horizontal(|ui| {
+                            let ctx = ui.ctx().clone();
+                            self.icon_cache.get(&ctx, self.edit_icon.as_ref()).show(ui, 24.0);
+                            ui.add_space(8.0);
+                            let picker_text = if self.show_icon_picker { "Hide icons" } else { "Choose…" };
+                            if ui.button(picker_text).clicked() {
+                                self.show_icon_picker = !self.show_icon_picker;
+                            }
+                            let fetching = self.favicon_fetch.is_some();
+                            let has_url = self.active_vault()
+                                .and_then(|vault| vault.get_entry(id))
+                                .is_some_and(|entry| entry.url.as_deref().is_some_and(|url| !url.trim().is_empty()));
+                            let fetch_text = if fetching { "Fetching…" } else { "🌐 Fetch favicon" };
+                            let fetch_hint = if has_url { "Download the site's icon from the entry URL and store it in the vault" } else { "Needs an entry URL" };
+                            if ui.add_enabled(has_url && !fetching, egui::Button::new(fetch_text))
+                                .on_hover_text(fetch_hint)
+                                .on_disabled_hover_text(fetch_hint)
+                                .clicked()
+                            {
+                                self.start_favicon_fetch();
+                            }
+                            if self.edit_icon.is_some() && ui.button("Reset").clicked() {
+                                self.edit_icon = None;
+                            }
+                        });
+                        if self.show_icon_picker {
+                            if let Some(name) = icons::glyph_picker(ui, self.edit_icon.as_ref()) {
+                                self.edit_icon = Some(EntryIcon::Glyph(name));
+                            }
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Expires:");
+                    ui.horizontal(|ui| {
+                        let mut expires = self.edit_expires.is_some();
+                        if ui.checkbox(&mut expires, "").on_hover_text("For credentials that stop working on a known date").changed() {
+                            self.edit_expires = expires.then(|| chrono::Utc::now().date_naive() + chrono::Days::new(30));
+                        }
+                        if let Some(date) = self.edit_expires.as_mut() {
+                            widgets::date_picker(ui, date);
+                            let left = (*date - chrono::Utc::now().date_naive()).num_days();
+                            let text = match left {
+                                l if l < 0 => format!("expired {} day(s) ago", -l),
+                                0 => "expires today".to_string(),
+                                l => format!("in {} day(s)", l),
+                            };
+                            ui.label(egui::RichText::new(text).weak());
+                        } else {
+                            ui.label(egui::RichText::new("Never").weak());
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Links:").on_hover_text("Related entries, such as the email account that recovers this one");
+                    self.show_link_picker(ui, id);
+                    ui.end_row();
+
+                    ui.label("Copy sequence:").on_hover_text("What the card's \"Next ▸\" button copies, one item per click");
+                    ui.vertical(|ui| {
+                        let sequence = ui.add(egui::TextEdit::multiline(&mut self.edit_copy_sequence)
+                            .desired_width(INPUT_WIDTH)
+                            .desired_rows(2)
+                            .hint_text("One per line: username, password, url or name=value"));
+                        if sequence.changed() {
+                            self.clear_form_error("edit_copy_sequence");
+                        }
+                        self.show_field_error(ui, "edit_copy_sequence");
+                    });
                     ui.end_row();
+
+                    if !self.edit_was_sealed {
+                        ui.label("Note:");
+                        ui.vertical(|ui| {
+                            widgets::limited_text_edit(ui, &mut self.edit_note, NOTE_CHAR_LIMIT, |text| egui::TextEdit::multiline(text)
+                                .desired_width(INPUT_WIDTH)
+                                .desired_rows(3)
+                                .hint_text("Optional notes"));
+                        });
+                        ui.end_row();
+                    }
                 });
+                if regenerate {
+                    self.regenerate_with_saved_rules();
+                }
 
                 ui.add_space(SPACING * 2.0);
                 
-                if self.success_button(ui, "Update Entry", [150.0, BUTTON_HEIGHT]).clicked() && self.validate_edit_entry() {
+                if (self.success_button(ui, "Update Entry", [150.0, BUTTON_HEIGHT]).clicked() || submit) && self.validate_edit_entry() {
                     match self.update_entry() {
+                        Ok(()) if self.edit_conflict.is_some() || self.master_reuse_warning.is_some() => {}
                         Ok(()) => {
                             self.toast_success("Entry updated successfully!");
                             self.clear_form_errors();
@@ -287,4 +473,167 @@ impl PassmanApp {
             });
         });
     }
+
+    /// Under a field "New from clipboard" filled and nobody has edited yet
+    fn show_pasted_hint(&self, ui: &mut egui::Ui, field: PasteField) {
+        if self.add_inferred.contains(&field) {
+            ui.label(egui::RichText::new("📋 Guessed from the clipboard, check it")
+                .small()
+                .color(egui::Color32::from_rgb(251, 191, 36)));
+        }
+    }
+
+    /// Offers to fix what was pasted into an add form field, each with a
+    /// button to accept it and one to dismiss it
+    fn show_add_hints(&mut self, ui: &mut egui::Ui, field: &str) {
+        let hints: Vec<AddFormHint> = self.add_hints.iter().filter(|hint| hint.field() == field).cloned().collect();
+        for hint in hints {
+            ui.horizontal_wrapped(|ui| {
+                ui.label(egui::RichText::new(format!("💡 {}", hint.message()))
+                    .small()
+                    .color(egui::Color32::from_rgb(96, 165, 250)));
+                if ui.small_button(hint.action()).clicked() {
+                    self.accept_add_hint(&hint);
+                }
+                if ui.small_button("✕").on_hover_text("Dismiss").clicked() {
+                    self.dismiss_add_hint(&hint);
+                }
+            });
+        }
+    }
+
+    /// Grid rows for the generator: mode, then its length or syllable count
+    /// The edit form's links, each removable, and an "Add link…" dropdown
+    /// searching the vault's other entries
+    fn show_link_picker(&mut self, ui: &mut egui::Ui, id: &str) {
+        const PICKER_ROWS: usize = 50;
+        let filter = self.edit_link_filter.to_lowercase();
+        let mut candidates: Vec<String> = self.active_vault()
+            .map(|vault| vault.iter()
+                .filter(|(other, entry)| *other != id && !entry.is_trashed() && !self.edit_linked_ids.iter().any(|linked| linked.as_str() == *other))
+                .map(|(other, _)| other.to_string())
+                .filter(|other| other.to_lowercase().contains(&filter))
+                .collect())
+            .unwrap_or_default();
+        candidates.sort();
+        let broken: Vec<bool> = self.edit_linked_ids.iter()
+            .map(|linked| self.active_vault().is_some_and(|vault| vault.is_broken_link(linked)))
+            .collect();
+
+        ui.vertical(|ui| {
+            let mut unlink = None;
+            if !self.edit_linked_ids.is_empty() {
+                ui.horizontal_wrapped(|ui| {
+                    for (index, linked_id) in self.edit_linked_ids.iter().enumerate() {
+                        let (badge, hint) = match broken[index] {
+                            true => ("⚠", "Deleted or in the trash; remove this link"),
+                            false => ("🔗", "Remove this link"),
+                        };
+                        if ui.small_button(format!("{} {}  ✕", badge, linked_id)).on_hover_text(hint).clicked() {
+                            unlink = Some(index);
+                        }
+                    }
+                });
+            }
+            if let Some(index) = unlink {
+                self.edit_linked_ids.remove(index);
+            }
+
+            let mut picked = None;
+            egui::ComboBox::from_id_salt("edit_link_picker")
+                .selected_text("Add link…")
+                .width(INPUT_WIDTH)
+                .show_ui(ui, |ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.edit_link_filter)
+                        .hint_text("Search entries")
+                        .desired_width(INPUT_WIDTH - 16.0))
+                        .request_focus();
+                    if candidates.is_empty() {
+                        ui.label(egui::RichText::new("No other entries match").weak());
+                    }
+                    for other in candidates.iter().take(PICKER_ROWS) {
+                        if ui.selectable_label(false, other).clicked() {
+                            picked = Some(other.clone());
+                        }
+                    }
+                    if candidates.len() > PICKER_ROWS {
+                        ui.label(egui::RichText::new(format!("{} more; type to narrow down", candidates.len() - PICKER_ROWS)).weak());
+                    }
+                });
+            if let Some(other) = picked {
+                self.edit_linked_ids.push(other);
+                self.edit_link_filter.clear();
+            }
+        });
+    }
+
+    fn show_generator_rows(&mut self, ui: &mut egui::Ui) {
+        ui.label("Mode:");
+        ui.horizontal(|ui| {
+            for mode in GeneratorMode::ALL {
+                ui.selectable_value(&mut self.generator_mode, mode, mode.label());
+            }
+        });
+        ui.end_row();
+
+        match self.generator_mode {
+            GeneratorMode::Random => {
+                ui.label("Length:");
+                ui.add(egui::Slider::new(&mut self.password_length, 8..=64)
+                    .text("characters"));
+                ui.end_row();
+
+                ui.label("");
+                self.show_generation_rules(ui);
+                ui.end_row();
+            }
+            GeneratorMode::Memorable => {
+                ui.label("");
+                ui.label(egui::RichText::new(format!("{} capitalized words", crate::config::get_config().password.memorable_word_count)).weak());
+                ui.end_row();
+            }
+            GeneratorMode::Pronounceable => {
+                ui.label("Syllables:");
+                ui.horizontal(|ui| {
+                    ui.add(egui::Slider::new(&mut self.pronounceable_syllables, 2..=12));
+                    let bits = pronounceable_entropy_bits(self.pronounceable_syllables);
+                    ui.label(egui::RichText::new(format!("~{:.0} bits", bits)).weak())
+                        .on_hover_text("Easy to read aloud, e.g. Radovi-Tunema-83!");
+                });
+                ui.end_row();
+            }
+        }
+    }
+
+    /// Advanced generator options for sites with password rules
+    fn show_generation_rules(&mut self, ui: &mut egui::Ui) {
+        ui.vertical(|ui| {
+            ui.checkbox(&mut self.show_generation_options, "Advanced options");
+            if !self.show_generation_options {
+                return;
+            }
+            
+            let rules = &mut self.generation_rules;
+            ui.checkbox(&mut rules.include_symbols, "Include symbols");
+            ui.checkbox(&mut rules.exclude_ambiguous, "Avoid ambiguous characters (0, O, 1, l, I)");
+            ui.checkbox(&mut rules.require_leading_alpha, "Start with a letter");
+            
+            ui.horizontal(|ui| {
+                ui.label("Exclude:");
+                ui.add(egui::TextEdit::singleline(&mut rules.excluded_chars)
+                    .desired_width(120.0)
+                    .hint_text("e.g. <>&\"'"));
+            });
+            
+            ui.horizontal(|ui| {
+                let mut limit = rules.max_length.is_some();
+                if ui.checkbox(&mut limit, "Maximum length").changed() {
+                    rules.max_length = limit.then_some(self.password_length);
+                }
+                if let Some(max) = rules.max_length.as_mut() {
+                    ui.add(egui::DragValue::new(max).range(1..=128));
+                }
+            });
+        });
+    }
 }
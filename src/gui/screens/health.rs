@@ -1,16 +1,33 @@
 //! Health Dashboard Screen Module
 //!
-//! Password health analysis and recommendations.
+//! Password health analysis and recommendations, with a sortable table of
+//! every entry's health.
 
 use eframe::egui;
-use super::super::types::{Screen, SPACING};
+use egui_extras::{Column, TableBuilder};
+use super::super::types::{HealthColumn, Screen, SPACING};
 use super::super::theme;
+use super::super::widgets;
+use crate::health::{append_health_record, site_reuse, HealthReport, PasswordHealth};
 use super::super::app::PassmanApp;
+use super::super::overlays::PaletteCommand;
+
+/// Palette actions for the health dashboard
+pub(super) const PALETTE_COMMANDS: &[PaletteCommand] = &[
+    PaletteCommand {
+        label: "Open health dashboard",
+        keywords: "weak reused passwords audit",
+        shortcut: Some("Ctrl+H"),
+        available: PassmanApp::is_unlocked,
+        run: |app| app.navigate(Screen::HealthDashboard),
+    },
+];
 
 impl PassmanApp {
     /// Show password health dashboard
     pub fn show_health_dashboard(&mut self, ui: &mut egui::Ui) {
-        let current_theme = self.current_theme.clone();
+        let current_theme = self.current_theme;
+        let suggestion_detail = crate::config::get_config().ui.suggestion_detail;
         let border_color = theme::border_color(&current_theme);
         
         // ════════════════════════════════════════════════════════════════════
@@ -34,7 +51,7 @@ impl PassmanApp {
                             .min_size(egui::vec2(70.0, 28.0));
                         
                         if ui.add(back_btn).clicked() {
-                            self.current_screen = Screen::Main;
+                            self.navigate_back();
                         }
                     });
                 });
@@ -43,12 +60,26 @@ impl PassmanApp {
         ui.add_space(SPACING);
 
         egui::ScrollArea::vertical().show(ui, |ui| {
-            ui.vertical_centered(|ui| {
+            let Some(session) = self.sessions.get_mut(self.active_session) else {
+                ui.vertical_centered(|ui| ui.label("No health data available. Please add entries to analyze."));
+                return;
+            };
+            session.ensure_health_reports(&self.health_analyzer);
+            let reports = session.health_reports.as_deref().unwrap_or_default();
+            let summary = self.health_analyzer.generate_summary(reports);
             
-            // Generate health summary if we have a vault
-            if let Some(vault) = &self.vault {
-                let reports = self.health_analyzer.analyze_vault(vault);
-                let summary = self.health_analyzer.generate_summary(&reports);
+            // Recorded in the vault only when it is next saved
+            let record = summary.to_record();
+            let mut trend = session.vault.metadata.health_history.clone();
+            append_health_record(&mut trend, record.clone());
+            session.pending_health_record = Some(record);
+            
+            ui.vertical_centered(|ui| {
+                ui.label("Score Trend");
+                let points: Vec<_> = trend.iter().map(|r| (r.timestamp, r.score)).collect();
+                widgets::paint_score_trend(ui, &points);
+                
+                ui.add_space(SPACING);
                 
                 ui.label(format!("Overall Health: {:.1}%", summary.score));
                 ui.add(egui::ProgressBar::new(summary.score as f32 / 100.0)
@@ -68,37 +99,130 @@ impl PassmanApp {
                     ui.colored_label(egui::Color32::GREEN, format!("{}", summary.excellent));
                 });
                 
-                ui.add_space(SPACING * 2.0);
-                
-                // Show individual entry health
+                // Passwords shared within one site
+                let shared = site_reuse(&session.vault);
+                if !shared.is_empty() {
+                    ui.add_space(SPACING);
+                    for reuse in &shared {
+                        ui.colored_label(egui::Color32::YELLOW, format!("⚠ {}", reuse.message()))
+                            .on_hover_text(reuse.entry_ids.join(", "));
+                    }
+                }
+            });
+            
+            ui.add_space(SPACING * 2.0);
+            
+            // Individual entry health
+            ui.horizontal(|ui| {
                 ui.label("Entry Details:");
-                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
-                    for report in &reports {
-                        ui.group(|ui| {
-                            ui.horizontal(|ui| {
-                                ui.label(&report.entry_id);
-                                let health_text = match &report.health {
-                                    crate::health::PasswordHealth::Excellent => "Excellent",
-                                    crate::health::PasswordHealth::Good => "Good", 
-                                    crate::health::PasswordHealth::Warning { .. } => "Warning",
-                                    crate::health::PasswordHealth::Critical { .. } => "Critical",
-                                };
-                                let color = match &report.health {
-                                    crate::health::PasswordHealth::Excellent => egui::Color32::GREEN,
-                                    crate::health::PasswordHealth::Good => egui::Color32::LIGHT_GREEN,
-                                    crate::health::PasswordHealth::Warning { .. } => egui::Color32::YELLOW,
-                                    crate::health::PasswordHealth::Critical { .. } => egui::Color32::RED,
-                                };
-                                ui.colored_label(color, health_text);
-                                ui.label(format!("Age: {} days", report.age_days));
-                            });
+                ui.add(egui::TextEdit::singleline(&mut self.health_filter)
+                    .hint_text("Filter by name or issue")
+                    .desired_width(200.0));
+            });
+            ui.add_space(4.0);
+            
+            let filter = self.health_filter.trim().to_lowercase();
+            let mut rows: Vec<&HealthReport> = reports.iter()
+                .filter(|report| filter.is_empty() || report_matches(report, &filter))
+                .collect();
+            self.health_sort.sort(&mut rows);
+            
+            let sort = self.health_sort;
+            let mut clicked_column = None;
+            let mut edit = None;
+            let mut table = TableBuilder::new(ui)
+                .striped(true)
+                .resizable(true)
+                .max_scroll_height(320.0)
+                .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                .column(Column::initial(160.0).at_least(80.0).clip(true));
+            for _ in 1..HealthColumn::ALL.len() {
+                table = table.column(Column::auto().at_least(60.0));
+            }
+            table.column(Column::remainder().at_least(50.0))
+                .header(24.0, |mut header| {
+                    for column in HealthColumn::ALL {
+                        header.col(|ui| {
+                            let arrow = match (sort.column == column, sort.descending) {
+                                (true, false) => " ⏶",
+                                (true, true) => " ⏷",
+                                (false, _) => "",
+                            };
+                            let label = egui::RichText::new(format!("{}{}", column.label(), arrow)).strong();
+                            if ui.add(egui::Button::new(label).frame(false)).clicked() {
+                                clicked_column = Some(column);
+                            }
                         });
                     }
+                    header.col(|_| {});
+                })
+                .body(|body| {
+                    body.rows(26.0, rows.len(), |mut row| {
+                        let report = rows[row.index()];
+                        let (health_text, color, issues) = match &report.health {
+                            PasswordHealth::Excellent => ("Excellent", egui::Color32::GREEN, None),
+                            PasswordHealth::Good => ("Good", egui::Color32::LIGHT_GREEN, None),
+                            PasswordHealth::Warning { issues } => ("Warning", egui::Color32::YELLOW, Some(issues)),
+                            PasswordHealth::Critical { issues } => ("Critical", egui::Color32::RED, Some(issues)),
+                        };
+                        row.col(|ui| {
+                            ui.label(&report.entry_id);
+                        });
+                        row.col(|ui| {
+                            let status = ui.colored_label(color, health_text);
+                            if let Some(issues) = issues {
+                                status.on_hover_text(issues.join("\n"));
+                            }
+                        });
+                        row.col(|ui| {
+                            ui.label(report.strength.to_string());
+                        });
+                        row.col(|ui| {
+                            ui.label(format!("{} d", report.age_days));
+                        });
+                        row.col(|ui| {
+                            if report.reused_with > 0 {
+                                ui.colored_label(egui::Color32::YELLOW, report.reused_with.to_string())
+                                    .on_hover_text(format!("Shared with {} other entr{}", report.reused_with, if report.reused_with == 1 { "y" } else { "ies" }));
+                            } else {
+                                ui.label("—");
+                            }
+                        });
+                        row.col(|ui| {
+                            ui.label(report.last_changed.with_timezone(&chrono::Local).format("%Y-%m-%d").to_string());
+                        });
+                        row.col(|ui| {
+                            let action = if issues.is_some() { "Fix" } else { "Edit" };
+                            let advice: Vec<String> = report.recommendations.iter().cloned()
+                                .chain(suggestion_detail.select(&report.suggestions).into_iter().map(|suggestion| format!("• {}", suggestion)))
+                                .collect();
+                            if ui.small_button(action).on_hover_text(advice.join("\n")).clicked() {
+                                edit = Some(report.entry_id.clone());
+                            }
+                        });
+                    });
                 });
-            } else {
-                ui.label("No health data available. Please add entries to analyze.");
+            
+            if rows.is_empty() {
+                ui.label(egui::RichText::new("No entries match the filter.").color(theme::muted_text_color(&current_theme)));
+            }
+            if let Some(column) = clicked_column {
+                self.health_sort.click(column);
+            }
+            if let Some(id) = edit {
+                self.start_edit_entry(&id);
             }
-            });
         });
     }
 }
+
+/// Whether a report's entry id or one of its issues contains `filter`
+/// (already lowercased)
+fn report_matches(report: &HealthReport, filter: &str) -> bool {
+    let issues = match &report.health {
+        PasswordHealth::Warning { issues } | PasswordHealth::Critical { issues } => issues.as_slice(),
+        PasswordHealth::Good | PasswordHealth::Excellent => &[],
+    };
+    report.entry_id.to_lowercase().contains(filter)
+        || issues.iter().any(|issue| issue.to_lowercase().contains(filter))
+}
@@ -3,21 +3,52 @@
 //! Data import and export functionality with native file dialogs.
 
 use eframe::egui;
-use crate::vault::VaultManager;
-use crate::import_export::ImportExportManager;
-use super::super::types::{Screen, ExportFormat, ImportFormat, SPACING};
+use crate::config::{get_config, get_config_mut, save_config};
+use crate::import_export::{export_risk, id_normalizations, ConflictPolicy, ImportExportManager, ImportProfile, IMPORT_FIELDS};
+use crate::sealed::{open_all, OpenedEntries};
+use crate::model::Vault;
+use crate::share::{ShareManager, SHARE_EXTENSION, DEFAULT_SHARE_EXPIRY_DAYS};
+use zeroize::{Zeroize, Zeroizing};
+use super::super::types::{Screen, ExportFormat, ImportFormat, SPACING, BUTTON_HEIGHT, CARD_FIELD_CHARS};
 use super::super::theme;
+use super::super::widgets;
 use super::super::app::PassmanApp;
+use super::super::overlays::PaletteCommand;
+
+/// Palette actions for import and export
+pub(super) const PALETTE_COMMANDS: &[PaletteCommand] = &[
+    PaletteCommand {
+        label: "Import…",
+        keywords: "csv json browser chrome",
+        shortcut: None,
+        available: PassmanApp::is_unlocked,
+        run: |app| app.navigate(Screen::ImportExport),
+    },
+    PaletteCommand {
+        label: "Export…",
+        keywords: "csv json backup share",
+        shortcut: None,
+        available: PassmanApp::is_unlocked,
+        run: |app| app.navigate(Screen::ImportExport),
+    },
+];
 
 impl PassmanApp {
     /// Show import/export screen
     pub fn show_import_export_screen(&mut self, ui: &mut egui::Ui) {
-        let current_theme = self.current_theme.clone();
+        let current_theme = self.current_theme;
         let header_bg = theme::header_bg_color(&current_theme);
         let frame_fill = theme::frame_fill(&current_theme);
         let border_color = theme::border_color(&current_theme);
         let muted_color = theme::muted_text_color(&current_theme);
         
+        if self.import_report.is_some() {
+            self.show_import_report(ui.ctx(), muted_color);
+        }
+        if self.export_risk.is_some() {
+            self.show_export_risk(ui.ctx());
+        }
+        
         // ════════════════════════════════════════════════════════════════════
         // HEADER
         // ════════════════════════════════════════════════════════════════════
@@ -39,7 +70,7 @@ impl PassmanApp {
                             .min_size(egui::vec2(70.0, 28.0));
                         
                         if ui.add(back_btn).clicked() {
-                            self.current_screen = Screen::Main;
+                            self.navigate_back();
                         }
                     });
                 });
@@ -81,6 +112,12 @@ impl PassmanApp {
                             ui.selectable_value(&mut self.export_format, ExportFormat::Json, "📄 JSON");
                             ui.add_space(8.0);
                             ui.selectable_value(&mut self.export_format, ExportFormat::Csv, "📊 CSV");
+                            ui.add_space(8.0);
+                            ui.selectable_value(&mut self.export_format, ExportFormat::KeePassXml, "🔑 KeePass XML")
+                                .on_hover_text("For KeePass 2.x, KeePassXC and other managers that import it; grouped by folder ids or first tag");
+                            ui.add_space(8.0);
+                            ui.selectable_value(&mut self.export_format, ExportFormat::Encrypted, "🔒 Encrypted")
+                                .on_hover_text("A copy of the vault that opens with your master password");
                         });
                         
                         ui.add_space(SPACING);
@@ -97,21 +134,13 @@ impl PassmanApp {
                             );
                             
                             if self.secondary_button(ui, "📁 Browse", [80.0, 28.0]).clicked() {
-                                let extension = match self.export_format {
-                                    ExportFormat::Json => "json",
-                                    ExportFormat::Csv => "csv",
-                                };
-                                
-                                let filter_name = match self.export_format {
-                                    ExportFormat::Json => "JSON files",
-                                    ExportFormat::Csv => "CSV files",
-                                };
+                                let extension = self.export_format.extension();
                                 
                                 if let Some(path) = rfd::FileDialog::new()
                                     .set_title("Export Passwords")
-                                    .add_filter(filter_name, &[extension])
+                                    .add_filter(self.export_format.filter_name(), &[extension])
                                     .add_filter("All files", &["*"])
-                                    .set_file_name(&format!("passwords_backup.{}", extension))
+                                    .set_file_name(format!("passwords_backup.{}", extension))
                                     .save_file()
                                 {
                                     self.export_file_path = path.display().to_string();
@@ -119,6 +148,21 @@ impl PassmanApp {
                             }
                         });
                         
+                        // High-security entries stay out unless their PIN is given
+                        let has_sealed = self.export_format.is_plaintext()
+                            && self.active_vault().is_some_and(|vault| vault.iter().any(|(_, entry)| entry.is_sealed()));
+                        if has_sealed {
+                            ui.add_space(SPACING);
+                            ui.checkbox(&mut self.export_include_sealed, "Include high-security entries")
+                                .on_hover_text("Their passwords and notes are written in plaintext too");
+                            if self.export_include_sealed {
+                                ui.add(egui::TextEdit::singleline(&mut *self.export_pin)
+                                    .password(true)
+                                    .hint_text("PIN")
+                                    .desired_width(ui.available_width() - 90.0));
+                            }
+                        }
+                        
                         ui.add_space(SPACING * 1.5);
                         
                         // Export button
@@ -131,7 +175,7 @@ impl PassmanApp {
                             .min_size(egui::vec2(140.0, 40.0));
                             
                             if ui.add(button).clicked() {
-                                self.do_export();
+                                self.request_export();
                             }
                         });
                         
@@ -139,11 +183,19 @@ impl PassmanApp {
                         
                         // Info text
                         ui.vertical_centered(|ui| {
-                            ui.label(
-                                egui::RichText::new("⚠ Exported files are NOT encrypted")
-                                    .size(11.0)
-                                    .color(egui::Color32::from_rgb(251, 191, 36))
-                            );
+                            if self.export_format.is_plaintext() {
+                                ui.label(
+                                    egui::RichText::new("⚠ Exported files are NOT encrypted")
+                                        .size(11.0)
+                                        .color(egui::Color32::from_rgb(251, 191, 36))
+                                );
+                            } else {
+                                ui.label(
+                                    egui::RichText::new("Opens with your master password")
+                                        .size(11.0)
+                                        .color(muted_color)
+                                );
+                            }
                         });
                     });
             });
@@ -172,6 +224,30 @@ impl PassmanApp {
                         ui.separator();
                         ui.add_space(SPACING);
                         
+                        // Saved import profiles
+                        let profile_names: Vec<String> = get_config().import_profiles.keys().cloned().collect();
+                        if !profile_names.is_empty() {
+                            ui.horizontal(|ui| {
+                                ui.label(egui::RichText::new("Profile").size(13.0).strong());
+                                let mut selected = self.import_profile.clone();
+                                egui::ComboBox::from_id_salt("import_profile")
+                                    .selected_text(selected.as_deref().unwrap_or("None"))
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(&mut selected, None, "None");
+                                        for name in &profile_names {
+                                            ui.selectable_value(&mut selected, Some(name.clone()), name);
+                                        }
+                                    });
+                                if selected != self.import_profile {
+                                    match selected {
+                                        Some(name) => self.load_import_profile(&name),
+                                        None => self.import_profile = None,
+                                    }
+                                }
+                            });
+                            ui.add_space(SPACING);
+                        }
+                        
                         // Format selection
                         ui.label(egui::RichText::new("Format").size(13.0).strong());
                         ui.add_space(4.0);
@@ -231,6 +307,17 @@ impl PassmanApp {
                             .size(11.0)
                             .color(if self.merge_on_import { muted_color } else { egui::Color32::from_rgb(251, 191, 36) })
                         );
+                        if self.merge_on_import {
+                            ui.horizontal(|ui| {
+                                ui.label("Existing ids:");
+                                for policy in ConflictPolicy::ALL {
+                                    ui.selectable_value(&mut self.import_conflict, policy, policy.as_str());
+                                }
+                            });
+                        }
+                        
+                        ui.add_space(SPACING);
+                        self.show_import_options(ui, muted_color);
                         
                         ui.add_space(SPACING * 1.5);
                         
@@ -261,6 +348,449 @@ impl PassmanApp {
                     });
             });
         });
+        
+        ui.add_space(SPACING * 2.0);
+        
+        // ════════════════════════════════════════════════════════════════════
+        // SHARED ENTRY SECTION
+        // ════════════════════════════════════════════════════════════════════
+        egui::Frame::none()
+            .fill(frame_fill)
+            .stroke(egui::Stroke::new(1.0, border_color))
+            .rounding(egui::Rounding::same(12.0))
+            .inner_margin(egui::Margin::same(20.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("🔗").size(20.0));
+                    ui.add_space(8.0);
+                    ui.label(egui::RichText::new("Import Shared Entry").size(16.0).strong());
+                });
+                
+                ui.add_space(4.0);
+                ui.label(
+                    egui::RichText::new("Add a single entry from an encrypted .pshare bundle")
+                        .size(12.0)
+                        .color(muted_color)
+                );
+                
+                ui.add_space(SPACING);
+                
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.share_import_path)
+                            .hint_text("Select share bundle...")
+                            .desired_width(ui.available_width() * 0.45)
+                    );
+                    
+                    if self.secondary_button(ui, "📁 Browse", [80.0, 28.0]).clicked() {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .set_title("Import Shared Entry")
+                            .add_filter("Share bundles", &[SHARE_EXTENSION])
+                            .add_filter("All files", &["*"])
+                            .pick_file()
+                        {
+                            self.share_import_path = path.display().to_string();
+                        }
+                    }
+                    
+                    let submit = widgets::password_field(
+                        ui, &mut self.share_import_passphrase, &mut self.share_show_passphrase,
+                        "Passphrase", 220.0, false,
+                    ).submitted;
+                    
+                    if self.primary_button(ui, "⬆ Import", [90.0, 28.0]).clicked() || submit {
+                        self.do_share_import();
+                    }
+                });
+            });
+    }
+    
+    /// Column mapping, id template, batch tags and saving as a profile
+    fn show_import_options(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        egui::CollapsingHeader::new("Options")
+            .id_salt("import_options")
+            .show(ui, |ui| {
+                egui::Grid::new("import_options_grid")
+                    .num_columns(2)
+                    .spacing([SPACING, 6.0])
+                    .show(ui, |ui| {
+                        if self.import_format == ImportFormat::Csv {
+                            ui.label("Delimiter:");
+                            ui.add(egui::TextEdit::singleline(&mut self.import_delimiter)
+                                .hint_text(", or tab")
+                                .desired_width(60.0));
+                            ui.end_row();
+                            
+                            for field in IMPORT_FIELDS {
+                                ui.label(format!("{} column:", field));
+                                ui.add(egui::TextEdit::singleline(self.import_columns.entry(field.to_string()).or_default())
+                                    .hint_text(field)
+                                    .desired_width(160.0));
+                                ui.end_row();
+                            }
+                            
+                            ui.label("Id template:");
+                            ui.add(egui::TextEdit::singleline(&mut self.import_id_template)
+                                .hint_text("{System}/{Login}")
+                                .desired_width(160.0));
+                            ui.end_row();
+                        }
+                        
+                        ui.label("Tags:");
+                        ui.add(egui::TextEdit::singleline(&mut self.import_tags)
+                            .hint_text("import:{month}")
+                            .desired_width(160.0));
+                        ui.end_row();
+                    });
+                ui.label(
+                    egui::RichText::new("Tags are added to every imported entry; {date} and {month} become today's date")
+                        .size(11.0)
+                        .color(muted_color)
+                );
+                
+                ui.add_space(SPACING);
+                self.show_id_cleanup(ui, muted_color);
+                
+                ui.add_space(SPACING);
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.import_profile_name)
+                        .hint_text("Profile name")
+                        .desired_width(140.0));
+                    if self.secondary_button(ui, "Save Profile", [100.0, 24.0]).clicked() {
+                        match self.save_import_profile() {
+                            Ok(name) => self.toast_success(format!("Import profile '{}' saved", name)),
+                            Err(e) => self.toast_error(e),
+                        }
+                    }
+                });
+            });
+    }
+    
+    /// Toggle for tidying imported ids, with a preview of what changes
+    fn show_id_cleanup(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.import_normalize_ids, "Clean up entry IDs")
+                .on_hover_text("Trim spaces, drop \"www.\", lowercase site names and capitalize multi-word names");
+            if self.secondary_button(ui, "Preview", [80.0, 24.0]).clicked() {
+                self.preview_import_ids();
+            }
+        });
+        
+        let Some((path, changes)) = &self.import_id_preview else {
+            return;
+        };
+        if *path != self.import_file_path {
+            return;
+        }
+        if changes.is_empty() {
+            ui.label(egui::RichText::new("No IDs need cleaning up").size(11.0).color(muted_color));
+            return;
+        }
+        let (color, note) = if self.import_normalize_ids {
+            (ui.visuals().text_color(), format!("{} ID(s) will be cleaned up:", changes.len()))
+        } else {
+            (muted_color, format!("{} ID(s) would be cleaned up; turned off, they are imported as they are:", changes.len()))
+        };
+        ui.label(egui::RichText::new(note).size(11.0).color(muted_color));
+        egui::ScrollArea::vertical().id_salt("import_id_preview").max_height(140.0).show(ui, |ui| {
+            for (original, normalized) in changes {
+                ui.label(egui::RichText::new(format!("{} → {}", original, normalized)).monospace().size(12.0).color(color));
+            }
+        });
+    }
+    
+    /// Parse the chosen file and list the ids that cleanup would change
+    fn preview_import_ids(&mut self) {
+        if self.import_file_path.trim().is_empty() {
+            self.toast_error("Please select a file to import");
+            return;
+        }
+        let mut profile = match self.current_import_profile() {
+            Ok(profile) => profile,
+            Err(e) => {
+                self.toast_error(e);
+                return;
+            }
+        };
+        profile.normalize_ids = false;
+        match ImportExportManager::parse(&self.import_file_path, &profile, &mut |_, _| {}) {
+            Ok((rows, _)) => {
+                self.import_id_preview = Some((self.import_file_path.clone(), id_normalizations(&rows)));
+            }
+            Err(e) => self.toast_error(format!("Could not read the file: {}", e)),
+        }
+    }
+    
+    /// The import settings on screen as a profile
+    fn current_import_profile(&self) -> Result<ImportProfile, String> {
+        let mut profile = ImportProfile::new(self.import_format.as_str());
+        profile.delimiter = ImportProfile::parse_delimiter(self.import_delimiter.trim())?;
+        profile.columns = self.import_columns.iter()
+            .filter(|(_, column)| !column.trim().is_empty())
+            .map(|(field, column)| (field.clone(), column.trim().to_string()))
+            .collect();
+        profile.id_template = self.import_id_template.trim().to_string();
+        profile.tags = self.import_tags
+            .split(',')
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        profile.conflict = self.import_conflict;
+        profile.normalize_ids = self.import_normalize_ids;
+        Ok(profile)
+    }
+    
+    /// Save the settings on screen under the typed profile name
+    fn save_import_profile(&mut self) -> Result<String, String> {
+        let name = self.import_profile_name.trim().to_string();
+        if name.is_empty() {
+            return Err("Profile name is required".into());
+        }
+        let profile = self.current_import_profile()?;
+        get_config_mut().import_profiles.insert(name.clone(), profile);
+        save_config()?;
+        self.import_profile = Some(name.clone());
+        Ok(name)
+    }
+    
+    /// Fill the import settings from a saved profile
+    fn load_import_profile(&mut self, name: &str) {
+        let Some(profile) = get_config().import_profiles.get(name).cloned() else {
+            return;
+        };
+        let Some(format) = ImportFormat::parse(&profile.format) else {
+            self.toast_error(format!("Profile '{}' imports {}, which only the CLI supports", name, profile.format));
+            return;
+        };
+        self.import_format = format;
+        self.import_delimiter = match profile.delimiter {
+            '\t' => "tab".to_string(),
+            c => c.to_string(),
+        };
+        self.import_columns = profile.columns;
+        self.import_id_template = profile.id_template;
+        self.import_tags = profile.tags.join(", ");
+        self.import_conflict = profile.conflict;
+        self.import_normalize_ids = profile.normalize_ids;
+        self.import_profile_name = name.to_string();
+        self.import_profile = Some(name.to_string());
+    }
+    
+    /// Results of an import that left some rows out or moved fields into
+    /// attachments
+    fn show_import_report(&mut self, ctx: &egui::Context, muted_color: egui::Color32) {
+        let Some(report) = self.import_report.as_ref() else {
+            return;
+        };
+        let mut should_save = false;
+        let mut should_close = false;
+        
+        egui::Window::new("📥 Import Results")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                ui.set_max_width(460.0);
+                ui.add_space(SPACING);
+                ui.label(format!("Imported: {} entries", report.imported));
+                if !report.skipped.is_empty() {
+                    ui.label(format!("Skipped: {} already in the vault", report.skipped.len()));
+                }
+                if !report.moved_to_attachments.is_empty() {
+                    ui.label(format!("Moved to attachments: {} entries with fields too long to show", report.moved_to_attachments.len()))
+                        .on_hover_text("The full text is kept as an attachment on the entry's card");
+                }
+                if !report.errors.is_empty() {
+                    ui.label(egui::RichText::new(format!("Not imported: {} malformed rows", report.errors.len()))
+                        .color(egui::Color32::from_rgb(251, 191, 36)));
+                }
+                ui.add_space(SPACING);
+                
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    for error in &report.errors {
+                        ui.label(egui::RichText::new(error.to_string()).monospace().size(12.0).color(muted_color));
+                    }
+                    for (id, fields) in &report.moved_to_attachments {
+                        let line = format!("{}: {}", widgets::clipped(id, CARD_FIELD_CHARS), fields.join(", "));
+                        ui.label(egui::RichText::new(line).monospace().size(12.0).color(muted_color));
+                    }
+                });
+                ui.add_space(SPACING * 2.0);
+                
+                ui.horizontal(|ui| {
+                    if !report.errors.is_empty() && self.secondary_button(ui, "Save List", [100.0, BUTTON_HEIGHT]).clicked() {
+                        should_save = true;
+                    }
+                    ui.add_space(SPACING);
+                    if self.primary_button(ui, "Close", [100.0, BUTTON_HEIGHT]).clicked() {
+                        should_close = true;
+                    }
+                });
+            });
+        
+        if should_save {
+            let errors = report.error_list();
+            if let Some(path) = rfd::FileDialog::new()
+                .set_title("Save Import Errors")
+                .add_filter("Text files", &["txt"])
+                .set_file_name("import_errors.txt")
+                .save_file()
+            {
+                match std::fs::write(&path, errors) {
+                    Ok(()) => self.toast_success(format!("Error list saved to {}", path.display())),
+                    Err(e) => self.toast_error(format!("Could not save error list: {}", e)),
+                }
+            }
+        } else if should_close {
+            self.import_report = None;
+        }
+    }
+    
+    /// Write a share bundle for an entry and show its one-time passphrase
+    pub fn share_entry(&mut self, id: &str) {
+        let Some(vault) = self.active_vault() else {
+            self.toast_error("No vault loaded");
+            return;
+        };
+        
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Share Entry")
+            .add_filter("Share bundles", &[SHARE_EXTENSION])
+            .set_file_name(format!("{}.{}", id, SHARE_EXTENSION))
+            .save_file()
+        else {
+            return;
+        };
+        
+        let passphrase = Zeroizing::new(ShareManager::generate_passphrase());
+        let output_path = path.display().to_string();
+        
+        match ShareManager::export_entry(vault, id, &passphrase, DEFAULT_SHARE_EXPIRY_DAYS, &output_path) {
+            Ok(()) => {
+                self.shared_passphrase = Some((id.to_string(), passphrase));
+                self.toast_success(format!(
+                    "Share bundle saved (expires in {} days)",
+                    DEFAULT_SHARE_EXPIRY_DAYS
+                ));
+            }
+            Err(e) => {
+                self.toast_error(format!("Share failed: {}", e));
+            }
+        }
+    }
+    
+    /// Import an entry from a share bundle, renaming it on id conflict
+    fn do_share_import(&mut self) {
+        if self.share_import_path.trim().is_empty() {
+            self.toast_error("Please select a share bundle");
+            return;
+        }
+        
+        if self.share_import_passphrase.is_empty() {
+            self.toast_error("Please enter the bundle passphrase");
+            return;
+        }
+        
+        let shared = match ShareManager::read_bundle(&self.share_import_path, &self.share_import_passphrase) {
+            Ok(shared) => shared,
+            Err(e) => {
+                self.toast_error(format!("Import failed: {}", e));
+                return;
+            }
+        };
+        
+        let Some(session) = self.active_session_mut() else {
+            self.toast_error("No vault loaded");
+            return;
+        };
+        
+        let target_id = ShareManager::unique_id(&session.vault, &shared.id);
+        session.vault.add_entry(target_id.clone(), shared.entry);
+        
+        if let Err(e) = self.commit_changes() {
+            self.toast_save_error(e);
+            return;
+        }
+        
+        self.share_import_path.clear();
+        self.share_import_passphrase.zeroize();
+        
+        if target_id == shared.id {
+            self.toast_success(format!("Imported shared entry '{}'", target_id));
+        } else {
+            self.toast_warning(format!("'{}' already existed; imported as '{}'", shared.id, target_id));
+        }
+    }
+    
+    /// Export, first asking about a plaintext export into a sync folder or
+    /// somewhere other users can read it
+    pub fn request_export(&mut self) {
+        let risk = if self.export_format.is_plaintext() && !self.export_file_path.trim().is_empty() {
+            export_risk(&self.export_file_path)
+        } else {
+            None
+        };
+        match risk {
+            Some(risk) => self.export_risk = Some(risk),
+            None => self.do_export(),
+        }
+    }
+    
+    /// Switch a risky plaintext export to the encrypted format, at the same
+    /// path with a `.dat` extension, and run it
+    pub fn export_encrypted_instead(&mut self) {
+        self.export_risk = None;
+        self.export_format = ExportFormat::Encrypted;
+        let path = std::path::Path::new(self.export_file_path.trim());
+        self.export_file_path = path.with_extension(ExportFormat::Encrypted.extension()).display().to_string();
+        self.do_export();
+    }
+    
+    /// Confirmation for a plaintext export to a risky location
+    fn show_export_risk(&mut self, ctx: &egui::Context) {
+        let Some(risk) = self.export_risk.as_ref() else {
+            return;
+        };
+        let message = risk.message();
+        let mut export_anyway = false;
+        let mut encrypt_instead = false;
+        let mut cancel = false;
+        
+        egui::Window::new("⚠ Plaintext Export")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                ui.set_max_width(420.0);
+                ui.add_space(SPACING);
+                ui.label(message);
+                ui.add_space(SPACING);
+                ui.label("An encrypted export opens only with your master password and is safe to keep there.");
+                ui.add_space(SPACING * 2.0);
+                
+                ui.horizontal(|ui| {
+                    if self.primary_button(ui, "🔒 Export Encrypted Instead", [200.0, BUTTON_HEIGHT]).clicked() {
+                        encrypt_instead = true;
+                    }
+                    if self.secondary_button(ui, "Export Anyway", [110.0, BUTTON_HEIGHT]).clicked() {
+                        export_anyway = true;
+                    }
+                    if self.secondary_button(ui, "Cancel", [70.0, BUTTON_HEIGHT]).clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        
+        if encrypt_instead {
+            self.export_encrypted_instead();
+        } else if export_anyway {
+            self.export_risk = None;
+            self.do_export();
+        } else if cancel {
+            self.export_risk = None;
+        }
     }
     
     /// Execute export operation
@@ -270,20 +800,45 @@ impl PassmanApp {
             return;
         }
         
-        let Some(vault) = &self.vault else {
+        let Some(session) = self.active_session() else {
             self.toast_error("No vault loaded");
             return;
         };
+        if session.in_memory && self.export_format == ExportFormat::Encrypted {
+            // Its key is random; nobody could open the file
+            self.toast_error("The demo vault has no master password to encrypt with; export it as JSON or CSV");
+            return;
+        }
+        let vault = &session.vault;
         
+        let (opened, not_opened) = if self.export_include_sealed && self.export_format.is_plaintext() {
+            open_all(vault, &self.export_pin)
+        } else {
+            (OpenedEntries::new(), Vec::new())
+        };
         let result = match self.export_format {
-            ExportFormat::Json => ImportExportManager::export_json(vault, &self.export_file_path),
-            ExportFormat::Csv => ImportExportManager::export_csv(vault, &self.export_file_path),
+            ExportFormat::Json => ImportExportManager::export_json(vault, &self.export_file_path, &opened),
+            ExportFormat::Csv => ImportExportManager::export_csv(vault, &self.export_file_path, &opened),
+            ExportFormat::KeePassXml => ImportExportManager::export_keepass_xml(vault, &self.export_file_path, &opened),
+            ExportFormat::Encrypted => ImportExportManager::export_encrypted(vault, &session.key, &self.export_file_path),
+        };
+        let left_out = if self.export_format.is_plaintext() {
+            vault.iter().filter(|(_, entry)| entry.is_sealed()).count() - opened.len()
+        } else {
+            0
         };
+        self.export_include_sealed = false;
+        self.export_pin.zeroize();
         
         match result {
             Ok(()) => {
                 self.toast_success(format!("Exported to {}", self.export_file_path));
                 self.export_file_path.clear();
+                if !not_opened.is_empty() {
+                    self.toast_warning(format!("The PIN didn't open {}; left out", not_opened.join(", ")));
+                } else if left_out > 0 {
+                    self.toast_info(format!("{} high-security entr{} left out", left_out, if left_out == 1 { "y" } else { "ies" }));
+                }
             }
             Err(e) => {
                 self.toast_error(format!("Export failed: {}", e));
@@ -298,36 +853,50 @@ impl PassmanApp {
             return;
         }
         
-        if self.vault.is_none() {
+        let profile = match self.current_import_profile() {
+            Ok(profile) => profile,
+            Err(e) => {
+                self.toast_error(e);
+                return;
+            }
+        };
+        
+        let Some(session) = self.sessions.get_mut(self.active_session) else {
             self.toast_error("No vault loaded");
             return;
+        };
+        
+        // The import reads and rewrites the file, so write pending changes first
+        if let Err(e) = session.flush() {
+            self.toast_save_error(e);
+            return;
         }
         
-        let result = match self.import_format {
-            ImportFormat::Json => {
-                ImportExportManager::import_json(&self.import_file_path, &self.master_password, Some(&self.vault_file), self.merge_on_import)
-            }
-            ImportFormat::Csv => {
-                ImportExportManager::import_csv(&self.import_file_path, &self.master_password, Some(&self.vault_file), self.merge_on_import)
-            }
-            ImportFormat::Chrome => {
-                ImportExportManager::import_browser(&self.import_file_path, &self.master_password, Some(&self.vault_file), "chrome", self.merge_on_import)
-            }
-        };
+        // A failed import changes nothing, so merging can go straight into
+        // the open vault; replacing fills a new one first
+        let mut replacement = Vault::new();
+        let target = if self.merge_on_import { &mut session.vault } else { &mut replacement };
+        let result = ImportExportManager::import_into(target, &self.import_file_path, &profile, false);
         
         match result {
-            Ok(()) => {
-                // Reload the vault
-                match VaultManager::load(&self.master_password, Some(&self.vault_file)) {
-                    Ok(vault) => {
-                        let count = vault.entries.len();
-                        self.vault = Some(vault);
-                        self.load_entries();
+            Ok(report) => {
+                if !self.merge_on_import {
+                    session.vault.replace_entries(replacement);
+                }
+                session.search_index = crate::search::SearchIndex::new();
+                match session.save() {
+                    Ok(()) if report.errors.is_empty() && report.moved_to_attachments.is_empty() => {
+                        let count = session.vault.len();
                         self.toast_success(format!("Imported successfully! {} entries total", count));
                         self.import_file_path.clear();
                     }
+                    Ok(()) => {
+                        // Some rows were bad or too big: show what was left out or moved
+                        self.import_report = Some(report);
+                        self.import_file_path.clear();
+                    }
                     Err(e) => {
-                        self.toast_error(format!("Import succeeded but reload failed: {}", e));
+                        self.toast_error(format!("Import succeeded but saving failed: {}", e));
                     }
                 }
             }
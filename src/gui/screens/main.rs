@@ -3,16 +3,66 @@
 //! Main vault screen with entry list and search.
 
 use eframe::egui;
-use super::super::types::{Screen, SPACING};
+use super::super::types::{ClipboardStage, EntryAction, PinPurpose, Screen, CARD_ACTIONS_WIDTH, CARD_FIELD_CHARS, EXPANDED_NOTE_CHARS, NOTE_PREVIEW_CHARS, PEEK_HOLD, SPACING};
 use super::super::theme;
 use super::super::widgets;
+use super::super::markdown;
 use super::super::app::PassmanApp;
+use crate::model::EntrySort;
+use crate::search::SearchScope;
+use crate::url_match;
+use crate::utils::{format_age, format_size, password_mask};
+use super::super::overlays::PaletteCommand;
+
+/// Icon, reason and colour of the chip for a link that can't be followed
+type LinkProblem = (&'static str, &'static str, egui::Color32);
+
+/// Palette actions for the vault screen
+pub(super) const PALETTE_COMMANDS: &[PaletteCommand] = &[
+    PaletteCommand {
+        label: "Show entries",
+        keywords: "main vault list home back",
+        shortcut: Some("Esc"),
+        available: |app| app.is_unlocked() && app.current_screen != Screen::Main,
+        run: |app| app.navigate(Screen::Main),
+    },
+    PaletteCommand {
+        label: "Lock vault",
+        keywords: "close",
+        shortcut: Some("Ctrl+L"),
+        available: PassmanApp::is_unlocked,
+        run: |app| {
+            if app.lock_vault() {
+                app.toast_info("Vault locked");
+            }
+        },
+    },
+    PaletteCommand {
+        label: "Lock all vaults",
+        keywords: "close everything",
+        shortcut: Some("Ctrl+Shift+L"),
+        available: PassmanApp::is_unlocked,
+        run: |app| {
+            if app.save_dirty_sessions() {
+                app.lock_all_vaults();
+                app.toast_info("All vaults locked");
+            }
+        },
+    },
+    PaletteCommand {
+        label: "Save changes",
+        keywords: "write",
+        shortcut: Some("Ctrl+Shift+S"),
+        available: |app| app.active_session().is_some_and(|session| session.dirty),
+        run: PassmanApp::save_active_vault,
+    },
+];
 
 impl PassmanApp {
     /// Show main vault screen
     pub fn show_main_screen(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
         // Clone theme to avoid borrow issues
-        let current_theme = self.current_theme.clone();
+        let current_theme = self.current_theme;
         
         // ════════════════════════════════════════════════════════════════════
         // HEADER BAR
@@ -26,7 +76,12 @@ impl PassmanApp {
                     // Title section
                     ui.label(egui::RichText::new("🔐").size(24.0));
                     ui.add_space(8.0);
-                    ui.label(egui::RichText::new("Password Vault").size(20.0).strong());
+                    let dirty = self.active_session().is_some_and(|session| session.dirty);
+                    let title = if dirty { "Password Vault *" } else { "Password Vault" };
+                    let title = ui.label(egui::RichText::new(title).size(20.0).strong());
+                    if dirty {
+                        title.on_hover_text("Unsaved changes");
+                    }
                     
                     // Keyboard shortcuts hint
                     ui.add_space(8.0);
@@ -37,53 +92,124 @@ impl PassmanApp {
                     ).on_hover_text(
                         "Keyboard Shortcuts:\n\
                         • Ctrl+N - New entry\n\
+                        • Ctrl+Shift+N - New entry from the clipboard\n\
+                        • Ctrl+K - Command palette\n\
                         • Ctrl+F - Focus search\n\
                         • Ctrl+L - Lock vault\n\
+                        • Ctrl+Shift+L - Lock all vaults\n\
                         • Ctrl+H - Health dashboard\n\
                         • Ctrl+S - Settings\n\
+                        • Ctrl+Shift+S - Save vault\n\
                         • Escape - Go back"
                     );
                     
+                    // Auto-lock countdown
+                    if let Some(remaining) = self.lock_remaining_secs() {
+                        ui.add_space(4.0);
+                        ui.label(
+                            egui::RichText::new("🔒")
+                                .size(12.0)
+                                .color(theme::muted_text_color(&current_theme))
+                        ).on_hover_text(format!(
+                            "Auto-lock in {}:{:02} without activity ({} mode)",
+                            remaining / 60,
+                            remaining % 60,
+                            self.lock_timeout_behavior
+                        ));
+                    }
+                    
+                    // Clipboard stage of "Open & copy"; clicking swaps in the password early
+                    if let Some(status) = self.open_copy_status() {
+                        ui.add_space(4.0);
+                        let waiting = self.open_copy.as_ref()
+                            .is_some_and(|open_copy| matches!(open_copy.stage, ClipboardStage::Username { .. }));
+                        let response = ui.add(
+                            egui::Label::new(
+                                egui::RichText::new(status)
+                                    .size(12.0)
+                                    .color(theme::muted_text_color(&current_theme))
+                            ).sense(egui::Sense::click())
+                        );
+                        if waiting && response.on_hover_text("Copy the password now").clicked() {
+                            self.copy_open_copy_password(ctx);
+                        }
+                    }
+                    
                     // Right-aligned buttons
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         ui.spacing_mut().item_spacing.x = 8.0;
                         
                         // Lock button
-                        if self.secondary_button(ui, "Lock", [65.0, 32.0]).clicked() {
-                            self.lock_vault();
+                        if self.secondary_button(ui, "Lock", [65.0, 32.0]).clicked() && self.lock_vault() {
                             self.toast_info("Vault locked".to_string());
                         }
                         
+                        // Explicit save when autosave is off
+                        if !self.autosave {
+                            let save = ui.add_enabled_ui(dirty, |ui| {
+                                self.primary_button(ui, "Save", [60.0, 32.0])
+                            }).inner;
+                            if save.on_hover_text("Save changes (Ctrl+Shift+S)").clicked() {
+                                self.save_active_vault();
+                            }
+                        }
+                        
                         // Settings
                         if self.secondary_button(ui, "⚙", [36.0, 32.0]).clicked() {
-                            self.current_screen = Screen::Settings;
+                            self.navigate(Screen::Settings);
+                        }
+                        
+                        // Notification history
+                        let unseen = self.unseen_toasts();
+                        let bell = if unseen > 0 { format!("🔔{}", unseen) } else { "🔔".to_string() };
+                        if self.secondary_button(ui, &bell, [44.0, 32.0]).on_hover_text("Notifications this session").clicked() {
+                            self.show_toast_history = !self.show_toast_history;
                         }
                         
                         ui.add_space(4.0);
                         
                         // Health dashboard
                         if self.primary_button(ui, "Health", [70.0, 32.0]).clicked() {
-                            self.current_screen = Screen::HealthDashboard;
+                            self.navigate(Screen::HealthDashboard);
                         }
                         
                         // Export
                         if self.secondary_button(ui, "Export", [70.0, 32.0]).clicked() {
-                            self.current_screen = Screen::ImportExport;
+                            self.navigate(Screen::ImportExport);
                         }
                         
                         ui.add_space(4.0);
                         
                         // Add button (prominent)
                         if self.success_button(ui, "+ Add", [65.0, 32.0]).clicked() {
-                            self.current_screen = Screen::AddEntry;
+                            self.navigate(Screen::AddEntry);
                             self.clear_add_form();
                         }
+                        if self.secondary_button(ui, "📋", [32.0, 32.0])
+                            .on_hover_text("New from clipboard (Ctrl+Shift+N): fill in an entry from pasted credentials")
+                            .clicked()
+                        {
+                            self.new_from_clipboard();
+                        }
                     });
                 });
             });
         
         ui.add_space(SPACING);
         
+        self.render_demo_banner(ui);
+        self.render_master_password_warning(ui);
+        self.render_sync_folder_warning(ui);
+        self.render_permissions_warning(ui);
+        self.render_conflicted_copies_banner(ui);
+        
+        // ════════════════════════════════════════════════════════════════════
+        // VAULT TABS
+        // ════════════════════════════════════════════════════════════════════
+        self.render_vault_tabs(ui);
+        
+        ui.add_space(SPACING);
+        
         // Get colors upfront
         let search_bg = theme::search_bg_color(&current_theme);
         let border_col = theme::border_color(&current_theme);
@@ -94,6 +220,26 @@ impl PassmanApp {
         // SEARCH BAR
         // ════════════════════════════════════════════════════════════════════
         ui.horizontal(|ui| {
+            // Up/Down step through recent searches; taken before the text
+            // box sees them
+            let search_id = egui::Id::new("entry_search");
+            if ui.memory(|memory| memory.has_focus(search_id)) {
+                let (up, down) = ui.input_mut(|input| (
+                    input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                    input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                ));
+                let step = if up {
+                    self.search_history.older(&self.search_query)
+                } else if down {
+                    self.search_history.newer()
+                } else {
+                    None
+                };
+                if let Some(query) = step {
+                    self.search_query = query;
+                }
+            }
+            
             // Styled search bar
             let search_response = egui::Frame::none()
                 .fill(search_bg)
@@ -106,6 +252,7 @@ impl PassmanApp {
                         ui.add_space(6.0);
                         let response = ui.add(
                             egui::TextEdit::singleline(&mut self.search_query)
+                                .id(search_id)
                                 .hint_text("Search entries... (Ctrl+F)")
                                 .frame(false)
                                 .desired_width(220.0)
@@ -119,25 +266,118 @@ impl PassmanApp {
                 search_response.request_focus();
                 self.request_search_focus = false;
             }
+            if search_response.changed() {
+                self.search_history.stop_browsing();
+            }
+            if search_response.lost_focus() {
+                self.search_history.record(&self.search_query);
+            }
             
             // Clear search button
-            if !self.search_query.is_empty() {
-                if ui.add(
-                    egui::Button::new("✕")
-                        .fill(egui::Color32::TRANSPARENT)
-                        .stroke(egui::Stroke::NONE)
-                ).clicked() {
-                    self.search_query.clear();
+            if !self.search_query.is_empty() && ui.add(
+                egui::Button::new("✕")
+                    .fill(egui::Color32::TRANSPARENT)
+                    .stroke(egui::Stroke::NONE)
+            ).clicked() {
+                self.search_query.clear();
+            }
+            
+            // Pin the query as a saved search
+            let query = self.search_query.trim();
+            let already_saved = crate::config::get_config().ui.saved_searches.values().any(|saved| saved == query);
+            if !query.is_empty() && !already_saved && self.pinning_search.is_none() && ui.add(
+                egui::Button::new("☆")
+                    .fill(egui::Color32::TRANSPARENT)
+                    .stroke(egui::Stroke::NONE)
+            ).on_hover_text("Save this search").clicked() {
+                self.pinning_search = Some(String::new());
+            }
+            
+            // Notes filter chip (remembered in the config)
+            ui.add_space(SPACING);
+            if ui.selectable_label(self.search_include_notes, "📝 Notes")
+                .on_hover_text("Include entry notes in the search")
+                .clicked()
+            {
+                self.search_include_notes = !self.search_include_notes;
+                crate::config::get_config_mut().ui.search_notes = self.search_include_notes;
+                if let Err(e) = crate::config::save_config() {
+                    self.toast_error(e);
                 }
             }
             
+            // Trash filter chip (per session, off by default)
+            let include_trash = self.search_scope == SearchScope::IncludeTrash;
+            if ui.selectable_label(include_trash, "🗑 Trash")
+                .on_hover_text("Include entries in the trash")
+                .clicked()
+            {
+                self.search_scope = if include_trash { SearchScope::ActiveOnly } else { SearchScope::IncludeTrash };
+            }
+            
+            // Archived filter chip: shows only archived entries
+            let archived_only = self.search_query.split_whitespace().any(|word| word.eq_ignore_ascii_case(crate::search::ARCHIVED_FILTER));
+            if ui.selectable_label(archived_only, "📦 Archived")
+                .on_hover_text("Show archived entries instead (is:archived)")
+                .clicked()
+            {
+                self.search_query = if archived_only {
+                    self.search_query.split_whitespace()
+                        .filter(|word| !word.eq_ignore_ascii_case(crate::search::ARCHIVED_FILTER))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                } else {
+                    format!("{} {}", crate::search::ARCHIVED_FILTER, self.search_query.trim()).trim_end().to_string()
+                };
+            }
+            
+            // Sort order (remembered in the config)
+            ui.add_space(SPACING);
+            let previous_sort = self.entry_sort;
+            egui::ComboBox::from_id_salt("entry_sort")
+                .selected_text(self.entry_sort.label())
+                .show_ui(ui, |ui| {
+                    for sort in EntrySort::ALL {
+                        ui.selectable_value(&mut self.entry_sort, sort, sort.label());
+                    }
+                });
+            if self.entry_sort != previous_sort {
+                crate::config::get_config_mut().ui.default_sort = self.entry_sort.as_str().to_string();
+                if let Err(e) = crate::config::save_config() {
+                    self.toast_error(e);
+                }
+            }
+            
+            // Group by site (remembered in the config)
+            if ui.selectable_label(self.group_by_site, "🌐 Group by site")
+                .on_hover_text("Group entries under the domain of their URL")
+                .clicked()
+            {
+                self.group_by_site = !self.group_by_site;
+                crate::config::get_config_mut().ui.group_by_site = self.group_by_site;
+                if let Err(e) = crate::config::save_config() {
+                    self.toast_error(e);
+                }
+            }
+            
+            // Cross-vault search toggle
+            if self.sessions.len() > 1 {
+                ui.add_space(SPACING);
+                ui.checkbox(&mut self.search_all_vaults, "All vaults")
+                    .on_hover_text("Search every unlocked vault");
+            }
+            
             ui.add_space(SPACING * 2.0);
             
             // Entry count badge
             let filtered_count = self.filter_entries().len();
-            let total_count = self.entries.len();
+            let total_count = if self.search_all_vaults && !self.search_query.is_empty() {
+                self.sessions.iter().map(|session| session.entries.len()).sum()
+            } else {
+                self.entries().len()
+            };
             let count_text = if self.search_query.is_empty() {
-                format!("{} entries", total_count)
+                format!("{} entries", filtered_count)
             } else {
                 format!("{} of {}", filtered_count, total_count)
             };
@@ -149,8 +389,17 @@ impl PassmanApp {
                 .show(ui, |ui| {
                     ui.label(egui::RichText::new(count_text).size(12.0).color(muted_col));
                 });
+            
+            let in_trash = self.trashed_matches();
+            if in_trash > 0 {
+                let noun = if in_trash == 1 { "entry" } else { "entries" };
+                ui.label(egui::RichText::new(format!("{} matching {} in trash", in_trash, noun)).size(12.0).color(muted_col))
+                    .on_hover_text("Turn on the Trash filter to show them");
+            }
         });
         
+        self.render_saved_searches(ui, muted_col);
+        
         ui.add_space(SPACING);
         
         // ════════════════════════════════════════════════════════════════════
@@ -159,25 +408,338 @@ impl PassmanApp {
         self.render_entry_list(ui, ctx);
     }
 
+    /// Naming row for a search being pinned, then one chip per saved
+    /// search: click applies it, right-click removes it
+    fn render_saved_searches(&mut self, ui: &mut egui::Ui, muted_col: egui::Color32) {
+        if let Some(mut name) = self.pinning_search.take() {
+            let mut done = false;
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Save search as").size(12.0).color(muted_col));
+                let response = ui.add(egui::TextEdit::singleline(&mut name).desired_width(140.0).hint_text("e.g. Work"));
+                response.request_focus();
+                let enter = response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+                if (ui.small_button("Save").clicked() || enter) && !name.trim().is_empty() {
+                    crate::config::get_config_mut().ui.saved_searches
+                        .insert(name.trim().to_string(), self.search_query.trim().to_string());
+                    if let Err(e) = crate::config::save_config() {
+                        self.toast_error(e);
+                    }
+                    done = true;
+                }
+                if ui.small_button("Cancel").clicked() || ui.input(|input| input.key_pressed(egui::Key::Escape)) {
+                    done = true;
+                }
+            });
+            if !done {
+                self.pinning_search = Some(name);
+            }
+        }
+        
+        let saved = crate::config::get_config().ui.saved_searches.clone();
+        if saved.is_empty() {
+            return;
+        }
+        let mut remove = None;
+        ui.add_space(6.0);
+        ui.horizontal_wrapped(|ui| {
+            ui.label(egui::RichText::new("Saved:").size(12.0).color(muted_col));
+            for (name, query) in &saved {
+                let chip = ui.selectable_label(self.search_query.trim() == query, name)
+                    .on_hover_text(format!("{}\nRight-click to remove", query));
+                if chip.clicked() {
+                    self.search_query = query.clone();
+                    self.search_history.record(query);
+                }
+                if chip.secondary_clicked() {
+                    remove = Some(name.clone());
+                }
+            }
+        });
+        if let Some(name) = remove {
+            crate::config::get_config_mut().ui.saved_searches.remove(&name);
+            if let Err(e) = crate::config::save_config() {
+                self.toast_error(e);
+            }
+        }
+    }
+
+    /// Render one tab per unlocked vault, plus a button to open another
+    /// Dismissible nudge shown after unlocking with a weak master password
+    fn render_master_password_warning(&mut self, ui: &mut egui::Ui) {
+        let Some(strength) = self.active_session().and_then(|session| session.master_password_warning.clone()) else {
+            return;
+        };
+        let warning_col = egui::Color32::from_rgb(251, 191, 36);
+        let mut change = false;
+        let mut dismiss = false;
+        
+        egui::Frame::none()
+            .fill(egui::Color32::from_rgba_unmultiplied(251, 191, 36, 20))
+            .stroke(egui::Stroke::new(1.0, warning_col))
+            .rounding(egui::Rounding::same(8.0))
+            .inner_margin(egui::Margin::symmetric(12.0, 8.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(format!("⚠ Your master password is {} — change it in Settings", strength.to_string().to_lowercase()))
+                            .color(warning_col)
+                    );
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        dismiss = ui.small_button("✕").on_hover_text("Dismiss").clicked();
+                        change = ui.small_button("Change password").clicked();
+                    });
+                });
+            });
+        ui.add_space(SPACING);
+        
+        if change {
+            self.open_password_change = true;
+            self.navigate(Screen::Settings);
+        }
+        if change || dismiss {
+            if let Some(session) = self.active_session_mut() {
+                session.master_password_warning = None;
+            }
+        }
+    }
+    
+    /// Marks the demo vault for as long as it is open; not dismissible, so
+    /// no screenshot or tester mistakes it for a real vault
+    fn render_demo_banner(&mut self, ui: &mut egui::Ui) {
+        if !self.active_session().is_some_and(|session| session.in_memory) {
+            return;
+        }
+        let demo_col = egui::Color32::from_rgb(192, 132, 252);
+        let mut leave = false;
+        
+        egui::Frame::none()
+            .fill(egui::Color32::from_rgba_unmultiplied(192, 132, 252, 24))
+            .stroke(egui::Stroke::new(1.0, demo_col))
+            .rounding(egui::Rounding::same(8.0))
+            .inner_margin(egui::Margin::symmetric(12.0, 8.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("DEMO — nothing is saved").strong().color(demo_col));
+                    ui.label(egui::RichText::new("Every entry is made up and changes are lost when it is closed").small().color(demo_col));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        leave = ui.small_button("Close demo").clicked();
+                    });
+                });
+            });
+        ui.add_space(SPACING);
+        
+        if leave {
+            self.lock_vault();
+        }
+    }
+    
+    /// Dismissible notice shown after unlocking a vault file that other
+    /// users on this machine can read, with a button to make it private
+    fn render_permissions_warning(&mut self, ui: &mut egui::Ui) {
+        let Some(loose) = self.active_session().and_then(|session| session.permissions_warning.clone()) else {
+            return;
+        };
+        let warning_col = egui::Color32::from_rgb(251, 191, 36);
+        let mut fix = false;
+        let mut dismiss = false;
+        
+        egui::Frame::none()
+            .fill(egui::Color32::from_rgba_unmultiplied(251, 191, 36, 20))
+            .stroke(egui::Stroke::new(1.0, warning_col))
+            .rounding(egui::Rounding::same(8.0))
+            .inner_margin(egui::Margin::symmetric(12.0, 8.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(format!("⚠ {}", loose.message())).color(warning_col))
+                        .on_hover_text(loose.remedy());
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        dismiss = ui.small_button("✕").on_hover_text("Dismiss").clicked();
+                        fix = ui.small_button("Make private").clicked();
+                    });
+                });
+            });
+        ui.add_space(SPACING);
+        
+        if fix {
+            match crate::vault::VaultManager::restrict_permissions(&loose.path) {
+                Ok(()) => self.toast_success("Only you can read the vault file now"),
+                Err(e) => self.toast_error(format!("Could not change permissions: {}", e)),
+            }
+        }
+        if fix || dismiss {
+            if let Some(session) = self.active_session_mut() {
+                session.permissions_warning = None;
+            }
+        }
+    }
+    
+    /// Dismissible notice shown once after unlocking a vault that sits in a
+    /// cloud sync folder or has conflicted copies
+    fn render_sync_folder_warning(&mut self, ui: &mut egui::Ui) {
+        let Some((risk, vault_file)) = self.active_session()
+            .and_then(|session| session.sync_folder_warning.clone().map(|risk| (risk, session.vault_file.clone())))
+        else {
+            return;
+        };
+        let info_col = egui::Color32::from_rgb(96, 165, 250);
+        let backups_off = !crate::config::get_config().backup.auto_backup;
+        let mut enable_backups = false;
+        let mut silence = false;
+        let mut dismiss = false;
+        
+        egui::Frame::none()
+            .fill(egui::Color32::from_rgba_unmultiplied(96, 165, 250, 20))
+            .stroke(egui::Stroke::new(1.0, info_col))
+            .rounding(egui::Rounding::same(8.0))
+            .inner_margin(egui::Margin::symmetric(12.0, 8.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(format!("☁ {}", risk.message())).color(info_col))
+                        .on_hover_text(risk.conflicted_copies.join("\n"));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        dismiss = ui.small_button("✕").on_hover_text("Dismiss").clicked();
+                        silence = ui.small_button("Don't warn for this vault").clicked();
+                        if backups_off {
+                            enable_backups = ui.small_button("Turn on backups").clicked();
+                        }
+                    });
+                });
+                ui.label(
+                    egui::RichText::new("Safer: keep the vault outside the synced folder and share it with 'passman sync export' through git.")
+                        .size(11.0)
+                        .color(theme::muted_text_color(&self.current_theme))
+                );
+            });
+        ui.add_space(SPACING);
+        
+        if enable_backups {
+            crate::config::get_config_mut().backup.auto_backup = true;
+            match crate::config::save_config() {
+                Ok(()) => self.toast_success("Automatic backups turned on"),
+                Err(e) => self.toast_error(e),
+            }
+        }
+        if silence {
+            if let Err(e) = crate::vault::VaultManager::set_sync_folder_warning(Some(&vault_file), false) {
+                self.toast_error(e);
+            }
+        }
+        if silence || dismiss {
+            if let Some(session) = self.active_session_mut() {
+                session.sync_folder_warning = None;
+            }
+        }
+    }
+    
+    /// Banner while conflicted sync copies of the vault are waiting to be
+    /// merged; "Resolve…" shows what merging the first one would do
+    fn render_conflicted_copies_banner(&mut self, ui: &mut egui::Ui) {
+        let count = self.active_session().map_or(0, |session| session.conflicted_copies.len());
+        if count == 0 {
+            return;
+        }
+        let warning_col = egui::Color32::from_rgb(251, 191, 36);
+        let mut resolve = false;
+        let mut dismiss = false;
+        
+        egui::Frame::none()
+            .fill(egui::Color32::from_rgba_unmultiplied(251, 191, 36, 20))
+            .stroke(egui::Stroke::new(1.0, warning_col))
+            .rounding(egui::Rounding::same(8.0))
+            .inner_margin(egui::Margin::symmetric(12.0, 8.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let message = if count == 1 {
+                        "☁ A conflicted sync copy of this vault has changes of its own".to_string()
+                    } else {
+                        format!("☁ {} conflicted sync copies of this vault have changes of their own", count)
+                    };
+                    ui.label(egui::RichText::new(message).color(warning_col));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        dismiss = ui.small_button("✕").on_hover_text("Dismiss").clicked();
+                        resolve = ui.small_button("Resolve…").clicked();
+                    });
+                });
+            });
+        ui.add_space(SPACING);
+        
+        if resolve {
+            self.preview_conflicted_copy();
+        } else if dismiss {
+            if let Some(session) = self.active_session_mut() {
+                session.conflicted_copies.clear();
+            }
+        }
+    }
+    
+    fn render_vault_tabs(&mut self, ui: &mut egui::Ui) {
+        let muted_col = theme::muted_text_color(&self.current_theme);
+        let mut switch_to = None;
+        let mut lock_index = None;
+        
+        ui.horizontal_wrapped(|ui| {
+            for (index, session) in self.sessions.iter().enumerate() {
+                let is_active = index == self.active_session;
+                let marker = if session.dirty { " *" } else { "" };
+                let label = egui::RichText::new(format!("🗄 {}{}", session.name(), marker)).size(13.0);
+                let label = if is_active { label.strong() } else { label.color(muted_col) };
+                
+                if ui.selectable_label(is_active, label)
+                    .on_hover_text(&session.vault_file)
+                    .clicked()
+                {
+                    switch_to = Some(index);
+                }
+                if ui.small_button("✕").on_hover_text("Lock this vault").clicked() {
+                    lock_index = Some(index);
+                }
+                ui.add_space(6.0);
+            }
+            
+            if ui.small_button("+ Open Vault").on_hover_text("Unlock another vault").clicked() {
+                self.navigate(Screen::Welcome);
+            }
+        });
+        
+        if let Some(index) = switch_to {
+            self.switch_session(index);
+        }
+        if let Some(index) = lock_index {
+            let name = self.sessions[index].name();
+            self.switch_session(index);
+            if self.lock_vault() {
+                self.toast_info(format!("Vault '{}' locked", name));
+            }
+        }
+    }
+
     /// Render the entry list
     fn render_entry_list(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
         egui::ScrollArea::vertical()
             .auto_shrink([false, false])
             .show(ui, |ui| {
-            let filtered_entries: Vec<(String, crate::model::Entry)> = self.filter_entries()
+            let filtered_entries: Vec<(usize, String, crate::model::Entry)> = self.filter_entries()
                 .into_iter()
-                .map(|(id, entry)| (id.clone(), entry.clone()))
+                .map(|(session, (id, entry))| (session, id.clone(), entry.clone()))
                 .collect();
+            let label_vaults = filtered_entries.iter().any(|(session, _, _)| *session != self.active_session);
+            let getting_started = self.search_query.is_empty() && self.shows_getting_started();
+            if getting_started {
+                self.render_getting_started(ui);
+            }
             
             if filtered_entries.is_empty() {
-                // Empty state
+                // Empty state; on a new vault the checklist stands in for it
                 if self.search_query.is_empty() {
-                    widgets::empty_state(
-                        ui,
-                        "📭",
-                        "No entries yet",
-                        "Click '+ Add' to create your first password entry"
-                    );
+                    if !getting_started {
+                        widgets::empty_state(
+                            ui,
+                            "📭",
+                            "No entries yet",
+                            "Click '+ Add' to create your first password entry"
+                        );
+                    }
                 } else {
                     widgets::empty_state(
                         ui,
@@ -186,35 +748,171 @@ impl PassmanApp {
                         &format!("No entries match \"{}\"", self.search_query)
                     );
                 }
+            } else if self.group_by_site {
+                let groups = url_match::group_by_site(filtered_entries, |(_, id, entry)| url_match::entry_site(id, entry));
+                for (site, entries) in groups {
+                    let site = site.unwrap_or_else(|| url_match::OTHER_SITE.to_string());
+                    egui::CollapsingHeader::new(egui::RichText::new(format!("{}  ({})", site, entries.len())).strong())
+                        .id_salt(("site_group", &site))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for (session, id, entry) in &entries {
+                                let vault_label = label_vaults.then(|| self.sessions[*session].name());
+                                self.render_entry_card(ui, ctx, *session, vault_label, id, entry);
+                                ui.add_space(8.0);
+                            }
+                        });
+                }
             } else {
-                for (id, entry) in filtered_entries.iter() {
-                    self.render_entry_card(ui, ctx, id, entry);
+                for (session, id, entry) in filtered_entries.iter() {
+                    let vault_label = label_vaults.then(|| self.sessions[*session].name());
+                    self.render_entry_card(ui, ctx, *session, vault_label, id, entry);
                     ui.add_space(8.0);
                 }
             }
         });
     }
 
-    /// Render a single entry card
-    fn render_entry_card(&mut self, ui: &mut egui::Ui, ctx: &egui::Context, id: &str, entry: &crate::model::Entry) {
+    /// "Getting started" checklist on a new vault. Items tick themselves
+    /// off; the card goes away once all are done or it is dismissed.
+    fn render_getting_started(&mut self, ui: &mut egui::Ui) {
+        let progress = self.getting_started_progress();
+        let done_count = progress.iter().filter(|(_, done)| *done).count();
+        let muted_col = theme::muted_text_color(&self.current_theme);
+        let done_col = theme::StrengthColors::good();
+        let mut run = None;
+        let mut dismiss = false;
+        
+        egui::Frame::none()
+            .fill(theme::frame_fill(&self.current_theme))
+            .stroke(egui::Stroke::new(1.0, theme::accent_border_color(&self.current_theme)))
+            .rounding(egui::Rounding::same(8.0))
+            .inner_margin(egui::Margin::same(14.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("🚀 Getting started").size(16.0).strong());
+                    ui.label(egui::RichText::new(format!("{}/{}", done_count, progress.len())).size(12.0).color(muted_col));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        dismiss = ui.small_button("✕").on_hover_text("Don't show this again").clicked();
+                    });
+                });
+                ui.add_space(6.0);
+                for (step, done) in &progress {
+                    ui.horizontal(|ui| {
+                        if *done {
+                            ui.label(egui::RichText::new("✔").color(done_col));
+                            ui.label(egui::RichText::new(step.label()).strikethrough().color(muted_col));
+                        } else {
+                            ui.label(egui::RichText::new("○").color(muted_col));
+                            ui.label(step.label());
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                if ui.small_button(step.action()).clicked() {
+                                    run = Some(*step);
+                                }
+                            });
+                        }
+                    });
+                }
+            });
+        ui.add_space(SPACING);
+        
+        if let Some(step) = run {
+            self.run_getting_started_step(step);
+        }
+        if dismiss {
+            self.dismiss_getting_started();
+        }
+    }
+
+    /// Render a single entry card. Actions switch to the entry's vault first.
+    fn render_entry_card(
+        &mut self,
+        ui: &mut egui::Ui,
+        ctx: &egui::Context,
+        session: usize,
+        vault_label: Option<String>,
+        id: &str,
+        entry: &crate::model::Entry,
+    ) {
         // Get all theme colors upfront to avoid borrow issues
-        let current_theme = self.current_theme.clone();
+        let current_theme = self.current_theme;
         let frame_fill = theme::frame_fill(&current_theme);
         let border_color = theme::border_color(&current_theme);
         let muted_col = theme::muted_text_color(&current_theme);
         
-        let password_str = entry.password_str();
-        let strength_score = widgets::calculate_password_score(password_str);
+        // Borrowed, never copied: revealed only where it is shown or copied.
+        // High-security entries only have the strength recorded when sealed.
+        let password = entry.password_str();
+        let sealed = entry.is_sealed();
+        let strength_score = if sealed {
+            widgets::strength_score(&entry.password_strength())
+        } else {
+            widgets::calculate_password_score(password.reveal())
+        };
         let strength_color = widgets::strength_color(strength_score);
         
         // Clone data we need for the closure
-        let username = entry.username.clone();
-        let note = entry.note.clone();
-        let show_pwd = *self.show_password.get(id).unwrap_or(&false);
-        let password_display = password_str.to_string();
+        let username = widgets::clipped(&entry.username, CARD_FIELD_CHARS).into_owned();
+        let last_used = entry.last_used_at.map(|at| format!("Last used {}", format_age(at, chrono::Utc::now())));
+        let trashed = entry.is_trashed();
+        let archived = entry.archived;
+        let sequence_next = (!entry.copy_sequence.is_empty()).then(|| {
+            let index = self.copy_sequence_next(session, id, entry.copy_sequence.len());
+            format!("Copy {}/{}: {}", index + 1, entry.copy_sequence.len(), entry.copy_sequence[index].label())
+        });
+        let attribution = match (&entry.created_by, &entry.modified_by) {
+            (Some(creator), Some(editor)) if creator != editor => Some(format!("added by {}, changed by {}", creator, editor)),
+            (Some(creator), _) => Some(format!("added by {}", creator)),
+            (None, Some(editor)) => Some(format!("changed by {}", editor)),
+            (None, None) => None,
+        };
+        let expiry = entry.days_until_expiry(chrono::Utc::now())
+            .filter(|left| *left <= crate::health::EXPIRY_WARNING_DAYS)
+            .map(|left| match left {
+                l if l < 0 => (theme::StrengthColors::very_weak(), format!("Expired {} day(s) ago", -l)),
+                0 => (theme::StrengthColors::weak(), "Expires today".to_string()),
+                l => (theme::StrengthColors::weak(), format!("Expires in {} day(s)", l)),
+            });
+        let has_url = entry.url.as_deref().is_some_and(|url| !url.trim().is_empty());
+        let note = entry.note.as_deref();
+        // Linked entries that can't be shown in the list get no chip to click
+        let links: Vec<(String, Option<LinkProblem>)> = entry.linked_ids.iter()
+            .map(|linked_id| {
+                let broken = theme::StrengthColors::weak();
+                let problem = match self.sessions[session].vault.get_entry(linked_id) {
+                    None => Some(("⚠", "no longer exists", broken)),
+                    Some(linked) if linked.is_trashed() => Some(("⚠", "is in the trash", broken)),
+                    Some(linked) if linked.archived => Some(("📦", "is archived", muted_col)),
+                    Some(_) => None,
+                };
+                (linked_id.clone(), problem)
+            })
+            .collect();
+        let mut followed_link = None;
+        let show_pwd = self.sessions[session].is_revealed(id);
+        let spelled = show_pwd && self.sessions[session].is_spelled_out(id);
+        let (mask, can_peek) = {
+            let config = crate::config::get_config();
+            // Peeking can't ask for the master password, so it is off when reveals need one
+            (password_mask('•', config.ui.mask_length), config.ui.hold_to_reveal && !config.security.reveal_requires_password && !sealed)
+        };
+        let peeking = !show_pwd && self.password_peek.as_ref().is_some_and(|(peek_session, peek_id, since)| {
+            *peek_session == session && peek_id == id && since.elapsed() >= PEEK_HOLD
+        });
+        let show_note = self.sessions[session].show_note.get(id).copied().unwrap_or(false);
+        let mut toggle_note = false;
+        // Set when "Show full" or "Hide full" is clicked: None for the note, or the attachment
+        let mut toggle_full: Option<Option<String>> = None;
+        // Borrowed by the card as it draws and put back after; only this card's
+        let full_text = match &self.full_text {
+            Some(full) if full.session == session && full.id == id => self.full_text.take(),
+            _ => None,
+        };
+        let mut clicked_link = None;
         let id_owned = id.to_string();
+        let icon = self.icon_cache.get(ctx, entry.icon.as_ref());
         
-        egui::Frame::none()
+        let card = egui::Frame::none()
             .fill(frame_fill)
             .rounding(egui::Rounding::same(12.0))
             .stroke(egui::Stroke::new(1.0, border_color))
@@ -249,9 +947,18 @@ impl PassmanApp {
                         
                         // Entry title with strength dots
                         ui.horizontal(|ui| {
-                            ui.label(egui::RichText::new(format!("🔑 {}", id_owned)).size(15.0).strong());
+                            icon.show(ui, 16.0);
+                            ui.label(egui::RichText::new(widgets::clipped(&id_owned, CARD_FIELD_CHARS)).size(15.0).strong());
                             ui.add_space(8.0);
                             widgets::paint_strength_dots(ui, strength_score);
+                            if let Some((color, text)) = &expiry {
+                                ui.add_space(6.0);
+                                ui.label(egui::RichText::new("⌛").size(12.0).color(*color)).on_hover_text(text);
+                            }
+                            if let Some(ref vault_name) = vault_label {
+                                ui.add_space(8.0);
+                                ui.label(egui::RichText::new(format!("🗄 {}", vault_name)).size(11.0).color(muted_col));
+                            }
                         });
                         
                         ui.add_space(6.0);
@@ -261,35 +968,151 @@ impl PassmanApp {
                             ui.label(egui::RichText::new("👤").size(12.0));
                             ui.add_space(4.0);
                             ui.label(egui::RichText::new(&username).color(muted_col));
+                            if let Some(last_used) = &last_used {
+                                ui.add_space(8.0);
+                                ui.label(egui::RichText::new(format!("· {}", last_used)).size(11.0).color(muted_col));
+                            }
+                            if let Some(attribution) = &attribution {
+                                ui.add_space(8.0);
+                                ui.label(egui::RichText::new(format!("· {}", attribution)).size(11.0).color(muted_col));
+                            }
+                            if trashed {
+                                ui.add_space(8.0);
+                                ui.label(egui::RichText::new("· 🗑 In trash").size(11.0).color(muted_col));
+                            }
+                            if archived {
+                                ui.add_space(8.0);
+                                ui.label(egui::RichText::new("· 📦 Archived").size(11.0).color(muted_col));
+                            }
                         });
                         
                         // Password row
                         ui.horizontal(|ui| {
                             ui.label(egui::RichText::new("🔒").size(12.0));
                             ui.add_space(4.0);
-                            if show_pwd {
-                                ui.add(egui::Label::new(
-                                    egui::RichText::new(&password_display)
-                                        .monospace()
-                                        .color(egui::Color32::from_rgb(251, 191, 36))
-                                ).selectable(false));
+                            let revealed_color = egui::Color32::from_rgb(251, 191, 36);
+                            let response = if spelled {
+                                widgets::spelled_password(ui, password.reveal(), revealed_color, theme::accent_border_color(&self.current_theme))
                             } else {
-                                ui.label(egui::RichText::new("••••••••••••").color(muted_col));
+                                let text = if show_pwd || peeking {
+                                    egui::RichText::new(password.reveal())
+                                        .monospace()
+                                        .color(revealed_color)
+                                } else {
+                                    egui::RichText::new(&mask).color(muted_col)
+                                };
+                                let sense = if can_peek && !show_pwd { egui::Sense::click() } else { egui::Sense::hover() };
+                                ui.add(egui::Label::new(text).selectable(false).sense(sense))
+                            };
+                            if sealed {
+                                ui.add_space(4.0);
+                                ui.label(egui::RichText::new("🛡").size(12.0).color(muted_col))
+                                    .on_hover_text("High security: copying or showing asks for the entry's PIN");
+                            }
+                            
+                            // Hold-to-reveal: shown only while the mask stays pressed
+                            if can_peek && !show_pwd {
+                                let response = response.on_hover_text("Hold to show the password");
+                                if response.is_pointer_button_down_on() {
+                                    let held = self.password_peek.as_ref()
+                                        .is_some_and(|(peek_session, peek_id, _)| *peek_session == session && peek_id == id);
+                                    if !held {
+                                        self.password_peek = Some((session, id.to_string(), std::time::Instant::now()));
+                                        ctx.request_repaint_after(PEEK_HOLD);
+                                    } else if !peeking {
+                                        ctx.request_repaint();
+                                    }
+                                } else if self.password_peek.as_ref().is_some_and(|(peek_session, peek_id, _)| *peek_session == session && peek_id == id) {
+                                    if peeking {
+                                        self.record_use(session, id);
+                                    }
+                                    self.password_peek = None;
+                                }
                             }
                         });
                         
-                        // Note (if exists)
-                        if let Some(ref note_text) = note {
-                            if !note_text.is_empty() {
-                                ui.horizontal(|ui| {
-                                    ui.label(egui::RichText::new("📝").size(12.0));
-                                    ui.add_space(4.0);
-                                    let display_note = if note_text.len() > 40 {
-                                        format!("{}...", &note_text[..40])
-                                    } else {
-                                        note_text.clone()
-                                    };
-                                    ui.label(egui::RichText::new(display_note).size(12.0).color(muted_col));
+                        // Links: chips that scroll to the linked entry
+                        if !links.is_empty() {
+                            ui.horizontal_wrapped(|ui| {
+                                ui.label(egui::RichText::new("🔗").size(12.0));
+                                ui.add_space(4.0);
+                                for (linked_id, problem) in &links {
+                                    match problem {
+                                        None => {
+                                            if ui.small_button(linked_id).on_hover_text("Go to this entry").clicked() {
+                                                followed_link = Some(linked_id.clone());
+                                            }
+                                        }
+                                        Some((badge, reason, color)) => {
+                                            ui.label(egui::RichText::new(format!("{} {}", badge, linked_id)).size(12.0).color(*color))
+                                                .on_hover_text(format!("The linked entry {}", reason));
+                                        }
+                                    }
+                                }
+                            });
+                        }
+                        
+                        // Note (if exists): first line, or the whole note as markdown
+                        if let Some(note_text) = note.filter(|note| !note.trim().is_empty()) {
+                            let first_line = note_text.trim().lines().next().unwrap_or_default();
+                            let preview: String = first_line.chars().take(NOTE_PREVIEW_CHARS).collect();
+                            let more = preview.len() < note_text.trim().len();
+                            ui.horizontal(|ui| {
+                                ui.label(egui::RichText::new("📝").size(12.0));
+                                ui.add_space(4.0);
+                                if !show_note {
+                                    let ellipsis = if more { "…" } else { "" };
+                                    ui.label(egui::RichText::new(format!("{}{}", preview, ellipsis)).size(12.0).color(muted_col));
+                                }
+                                if more || show_note {
+                                    let toggle = if show_note { "less" } else { "more" };
+                                    toggle_note = ui.small_button(toggle).clicked();
+                                }
+                            });
+                            if show_note {
+                                ui.indent(("note", id), |ui| {
+                                    ui.set_max_width((ui.available_width() - CARD_ACTIONS_WIDTH).max(200.0));
+                                    // A huge note shows its start as markdown, then all of it on request
+                                    let shown = widgets::clipped(note_text, EXPANDED_NOTE_CHARS);
+                                    let full = full_text.as_ref().filter(|full| full.attachment.is_none() && full.len == note_text.len());
+                                    match (&shown, full) {
+                                        (std::borrow::Cow::Borrowed(_), _) => clicked_link = markdown::show(ui, note_text, muted_col),
+                                        (std::borrow::Cow::Owned(_), Some(full)) => {
+                                            widgets::full_text(ui, ("full_note", id), note_text, &full.rows, muted_col);
+                                            if ui.small_button("Hide full").clicked() {
+                                                toggle_full = Some(None);
+                                            }
+                                        }
+                                        (std::borrow::Cow::Owned(start), None) => {
+                                            clicked_link = markdown::show(ui, start, muted_col);
+                                            let size = format_size(note_text.len() as u64);
+                                            if ui.small_button(format!("Show full ({})", size)).clicked() {
+                                                toggle_full = Some(None);
+                                            }
+                                        }
+                                    }
+                                });
+                            }
+                        }
+                        
+                        // Attachments: the text is laid out only once opened
+                        for attachment in &entry.attachments {
+                            let open = full_text.as_ref()
+                                .filter(|full| full.attachment.as_deref() == Some(attachment.name.as_str()) && full.len == attachment.content.len());
+                            ui.horizontal(|ui| {
+                                ui.label(egui::RichText::new("📎").size(12.0));
+                                ui.add_space(4.0);
+                                let size = format_size(attachment.content.len() as u64);
+                                ui.label(egui::RichText::new(format!("{} ({})", widgets::clipped(&attachment.name, CARD_FIELD_CHARS), size)).size(12.0).color(muted_col));
+                                let toggle = if open.is_some() { "Hide full" } else { "Show full" };
+                                if ui.small_button(toggle).clicked() {
+                                    toggle_full = Some(Some(attachment.name.clone()));
+                                }
+                            });
+                            if let Some(full) = open {
+                                ui.indent(("attachment", id, &attachment.name), |ui| {
+                                    ui.set_max_width((ui.available_width() - CARD_ACTIONS_WIDTH).max(200.0));
+                                    widgets::full_text(ui, ("full_attachment", id, &attachment.name), &attachment.content, &full.rows, muted_col);
                                 });
                             }
                         }
@@ -304,38 +1127,122 @@ impl PassmanApp {
                         ui.add_space(12.0);
                         ui.spacing_mut().item_spacing.x = 6.0;
                         
+                        // Archived entries are for reference: no copying or editing until unarchived
+                        if archived {
+                            if self.secondary_button(ui, "Unarchive", [90.0, 36.0]).clicked() {
+                                self.run_entry_action(ctx, session, id, EntryAction::Unarchive);
+                            }
+                            return;
+                        }
+                        
+                        // More actions menu
+                        ui.menu_button(egui::RichText::new("⋮").size(16.0), |ui| {
+                            for action in [EntryAction::Share, EntryAction::Duplicate, EntryAction::Archive, EntryAction::ShowQr] {
+                                let mut button = ui.button(action.label());
+                                if action == EntryAction::Archive {
+                                    button = button.on_hover_text("Keep the entry, but hide it from the list, search and health checks");
+                                }
+                                if button.clicked() {
+                                    self.run_entry_action(ctx, session, id, action);
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                        
                         // Delete button
                         if self.danger_button(ui, "🗑", [36.0, 36.0]).clicked() {
-                            self.pending_delete = Some(id.to_string());
+                            self.run_entry_action(ctx, session, id, EntryAction::Delete);
                         }
                         
                         // Copy button
                         if self.primary_button(ui, "📋 Copy", [75.0, 36.0]).clicked() {
-                            match self.secure_clipboard.copy_password(&password_display) {
-                                Ok(()) => {
-                                    let timeout = self.clipboard_clear_secs;
-                                    self.toast_success(format!("Password copied! Auto-clear in {}s", timeout));
-                                }
-                                Err(_) => {
-                                    ctx.output_mut(|o| o.copied_text = password_display.clone());
-                                    self.toast_info("Password copied (standard clipboard)");
-                                }
+                            self.run_entry_action(ctx, session, id, EntryAction::CopyPassword);
+                        }
+                        
+                        // Copy sequence: one item per click, for logins with more than two fields
+                        if let Some(hint) = &sequence_next {
+                            if self.secondary_button(ui, "Next ▸", [64.0, 36.0]).on_hover_text(hint).clicked() {
+                                self.copy_next_in_sequence(ctx, session, id);
+                            }
+                        }
+                        
+                        // Open & copy: site in the browser, username then password on the clipboard
+                        if has_url {
+                            let waiting = self.open_copy.as_ref().is_some_and(|open_copy| {
+                                open_copy.session == session && open_copy.id == id
+                                    && matches!(open_copy.stage, ClipboardStage::Username { .. })
+                            });
+                            let (label, hint) = if waiting {
+                                ("🔑", "Copy the password now")
+                            } else {
+                                ("🌐", "Open the site and copy the username, then the password")
+                            };
+                            if self.secondary_button(ui, label, [36.0, 36.0]).on_hover_text(hint).clicked() {
+                                self.open_and_copy(ctx, session, id);
                             }
                         }
                         
                         // Edit button
                         if self.success_button(ui, "✏", [36.0, 36.0]).clicked() {
-                            self.start_edit_entry(id);
+                            self.run_entry_action(ctx, session, id, EntryAction::Edit);
+                        }
+                        
+                        // Spell out: groups of four with look-alike characters named
+                        if show_pwd {
+                            let hint = if spelled { "Show the password plainly" } else { "Spell out the password to type it by hand" };
+                            if self.secondary_button(ui, "🔤", [36.0, 36.0]).on_hover_text(hint).clicked() {
+                                self.sessions[session].toggle_spelled_out(id);
+                            }
                         }
                         
                         // Show/hide password button
                         let eye_icon = if show_pwd { "🙈" } else { "👁" };
                         if self.secondary_button(ui, eye_icon, [36.0, 36.0]).clicked() {
-                            let current = self.show_password.entry(id.to_string()).or_insert(false);
-                            *current = !*current;
+                            if show_pwd {
+                                self.sessions[session].set_revealed(id, false);
+                            } else if sealed {
+                                self.request_pin(session, id, PinPurpose::Show);
+                            } else {
+                                self.request_reveal(session, id);
+                            }
                         }
                     });
                 });
             });
+        
+        if full_text.is_some() {
+            self.full_text = full_text;
+        }
+        
+        // Context menu: right-click or long-press the card, or Shift+F10
+        // while it has keyboard focus (Tab moves between cards)
+        let card = card.response.interact(egui::Sense::click());
+        let from_keyboard = card.has_focus() && ui.input_mut(|i| i.consume_key(egui::Modifiers::SHIFT, egui::Key::F10));
+        if card.secondary_clicked() || card.long_touched() || from_keyboard {
+            let pos = match card.interact_pointer_pos() {
+                Some(pos) if !from_keyboard => pos,
+                _ => card.rect.left_top() + egui::vec2(16.0, 16.0),
+            };
+            self.open_entry_menu(session, id, pos);
+        }
+        
+        if toggle_note {
+            let current = self.sessions[session].show_note.entry(id.to_string()).or_insert(false);
+            *current = !*current;
+        }
+        if let Some(attachment) = toggle_full {
+            self.toggle_full_text(session, id, entry, attachment.as_deref());
+        }
+        if let Some(url) = clicked_link {
+            self.open_link(ctx, url);
+        }
+        if let Some(linked_id) = followed_link {
+            self.focus_entry(session, &linked_id);
+        }
+        if self.scroll_to_entry.as_ref().is_some_and(|(target_session, target)| *target_session == session && target == id) {
+            card.scroll_to_me(Some(egui::Align::Center));
+            card.request_focus();
+            self.scroll_to_entry = None;
+        }
     }
 }
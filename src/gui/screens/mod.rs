@@ -8,3 +8,35 @@ mod entry;
 mod settings;
 mod health;
 mod import_export;
+
+use super::overlays::PaletteCommand;
+
+/// Command-palette actions registered by each screen
+pub fn palette_commands() -> impl Iterator<Item = &'static PaletteCommand> {
+    [
+        main::PALETTE_COMMANDS,
+        entry::PALETTE_COMMANDS,
+        health::PALETTE_COMMANDS,
+        import_export::PALETTE_COMMANDS,
+        settings::PALETTE_COMMANDS,
+        welcome::PALETTE_COMMANDS,
+    ]
+    .into_iter()
+    .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_palette_commands_are_unique_and_reachable_by_name() {
+        let mut labels = HashSet::new();
+        for command in palette_commands() {
+            assert!(labels.insert(command.label), "duplicate palette command {:?}", command.label);
+            assert!(crate::search::fuzzy_score(command.label, command.label).is_some());
+        }
+        assert!(labels.contains("Lock vault"));
+    }
+}
@@ -3,16 +3,44 @@
 //! Application settings and configuration.
 
 use eframe::egui;
-use crate::vault::VaultManager;
-use super::super::types::{Screen, SPACING};
-use super::super::theme;
-use super::super::widgets;
+use crate::config::{get_config, get_config_mut, save_config, SuggestionDetail};
+use crate::crypto::MasterPasswordCheck;
+use crate::model::{EntryTemplate, Vault};
+use crate::vault::{VaultKey, VaultManager};
+use zeroize::Zeroizing;
+use crate::diff::VaultDiff;
+use crate::bulk::{BulkField, BulkReplace};
+use super::super::types::{Screen, SPACING, BUTTON_HEIGHT};
+use super::super::theme::{self, Motion};
+use super::super::widgets::{self, ButtonWidgets};
 use super::super::app::PassmanApp;
+use super::super::overlays::PaletteCommand;
+
+/// Palette actions for the settings screen
+pub(super) const PALETTE_COMMANDS: &[PaletteCommand] = &[
+    PaletteCommand {
+        label: "Open settings",
+        keywords: "preferences options",
+        shortcut: Some("Ctrl+S"),
+        available: PassmanApp::is_unlocked,
+        run: |app| app.navigate(Screen::Settings),
+    },
+    PaletteCommand {
+        label: "Change master password…",
+        keywords: "settings security",
+        shortcut: None,
+        available: PassmanApp::is_unlocked,
+        run: |app| {
+            app.open_password_change = true;
+            app.navigate(Screen::Settings);
+        },
+    },
+];
 
 impl PassmanApp {
     /// Show settings screen
     pub fn show_settings_screen(&mut self, ui: &mut egui::Ui, _ctx: &egui::Context) {
-        let current_theme = self.current_theme.clone();
+        let current_theme = self.current_theme;
         let muted_color = theme::muted_text_color(&current_theme);
         let frame_fill = theme::frame_fill(&current_theme);
         let border_color = theme::border_color(&current_theme);
@@ -38,11 +66,7 @@ impl PassmanApp {
                             .min_size(egui::vec2(70.0, 28.0));
                         
                         if ui.add(back_btn).clicked() {
-                            if self.vault.is_some() {
-                                self.current_screen = Screen::Main;
-                            } else {
-                                self.current_screen = Screen::Welcome;
-                            }
+                            self.navigate_back();
                         }
                     });
                 });
@@ -85,13 +109,21 @@ impl PassmanApp {
                         
                         ui.add_space(12.0);
                         
-                        // Available vaults
-                        ui.label(
-                            egui::RichText::new("Available vault files:")
-                                .size(12.0)
-                                .color(muted_color)
-                        );
+                        // Available vaults, scanned once and on refresh
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                egui::RichText::new("Available vault files:")
+                                    .size(12.0)
+                                    .color(muted_color)
+                            );
+                            if ui.small_button("↻").on_hover_text("Scan again").clicked() {
+                                self.vault_scan = None;
+                            }
+                        });
                         ui.add_space(6.0);
+                        let scan = self.vault_scan
+                            .get_or_insert_with(|| VaultManager::list_vaults(&VaultManager::vault_dirs(&self.vault_file)))
+                            .clone();
                         
                         egui::Frame::none()
                             .fill(egui::Color32::from_rgba_unmultiplied(0, 0, 0, 30))
@@ -99,49 +131,168 @@ impl PassmanApp {
                             .inner_margin(egui::Margin::same(8.0))
                             .show(ui, |ui| {
                                 egui::ScrollArea::vertical().max_height(80.0).show(ui, |ui| {
-                                    if let Ok(vaults) = VaultManager::list_vaults() {
-                                        if vaults.is_empty() {
-                                            ui.label(
-                                                egui::RichText::new("No vault files found")
-                                                    .size(12.0)
-                                                    .color(muted_color)
-                                            );
-                                        } else {
-                                            for vault_filename in vaults {
-                                                ui.horizontal(|ui| {
-                                                    let is_current = self.vault_file == vault_filename;
-                                                    
-                                                    ui.label(&vault_filename);
-                                                    
-                                                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                                        if is_current {
-                                                            ui.label(
-                                                                egui::RichText::new("current")
-                                                                    .size(11.0)
-                                                                    .color(egui::Color32::from_rgb(34, 197, 94))
-                                                            );
-                                                        } else {
-                                                            let select_btn = egui::Button::new(
-                                                                egui::RichText::new("Select").size(11.0)
-                                                            )
-                                                            .rounding(egui::Rounding::same(4.0))
-                                                            .min_size(egui::vec2(50.0, 22.0));
-                                                            
-                                                            if ui.add(select_btn).clicked() {
-                                                                self.vault_file = vault_filename.clone();
-                                                                self.toast_info(format!("Vault file set to '{}'. Please reopen.", self.vault_file));
-                                                                self.current_screen = Screen::Welcome;
-                                                            }
+                                    if scan.vaults.is_empty() {
+                                        ui.label(
+                                            egui::RichText::new("No vault files found")
+                                                .size(12.0)
+                                                .color(muted_color)
+                                        );
+                                    } else {
+                                        for vault in &scan.vaults {
+                                            let vault_filename = vault.path.clone();
+                                            ui.horizontal(|ui| {
+                                                let is_current = self.vault_file == vault_filename;
+                                                let is_open = self.sessions.iter().any(|session| session.vault_file == vault_filename);
+                                                
+                                                ui.label(&vault_filename);
+                                                
+                                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                                    if is_open {
+                                                        ui.label(
+                                                            egui::RichText::new("unlocked")
+                                                                .size(11.0)
+                                                                .color(egui::Color32::from_rgb(34, 197, 94))
+                                                        );
+                                                    } else if is_current {
+                                                        ui.label(
+                                                            egui::RichText::new("current")
+                                                                .size(11.0)
+                                                                .color(egui::Color32::from_rgb(34, 197, 94))
+                                                        );
+                                                    } else {
+                                                        let select_btn = egui::Button::new(
+                                                            egui::RichText::new("Select").size(11.0)
+                                                        )
+                                                        .rounding(egui::Rounding::same(4.0))
+                                                        .min_size(egui::vec2(50.0, 22.0));
+                                                        
+                                                        if ui.add(select_btn).clicked() {
+                                                            self.vault_file = vault_filename.clone();
+                                                            self.toast_info(format!("Vault file set to '{}'. Please reopen.", self.vault_file));
+                                                            self.navigate(Screen::Welcome);
                                                         }
-                                                    });
+                                                    }
+                                                    if let Some(stats) = &vault.stats {
+                                                        widgets::vault_stats_badges(ui, stats);
+                                                    }
                                                 });
-                                            }
+                                            });
                                         }
-                                    } else {
-                                        ui.label("Could not read vault files.");
+                                    }
+                                    for warning in &scan.warnings {
+                                        ui.label(
+                                            egui::RichText::new(format!("⚠ Skipped {}", warning))
+                                                .size(11.0)
+                                                .color(muted_color)
+                                        );
                                     }
                                 });
                             });
+                        
+                        // Badges above come from an unencrypted sidecar, opt-in
+                        let mut store_stats = get_config().security.store_plain_metadata;
+                        if ui.checkbox(&mut store_stats, "Show entry counts on the vault list")
+                            .on_hover_text("Keeps entry and weak password counts unencrypted next to each vault, updated on save. No entry names or usernames are stored.")
+                            .changed()
+                        {
+                            get_config_mut().security.store_plain_metadata = store_stats;
+                            if let Err(e) = save_config() {
+                                self.toast_error(e);
+                            }
+                            if !store_stats {
+                                VaultManager::forget_all_stats(&self.vault_file);
+                            }
+                            self.vault_scan = None;
+                        }
+                        
+                        ui.add_space(12.0);
+                        
+                        // Save mode
+                        let mut autosave = self.autosave;
+                        if ui.checkbox(&mut autosave, "Save changes automatically")
+                            .on_hover_text("When off, changes are saved with the Save button (Ctrl+Shift+S), on lock and on exit")
+                            .changed()
+                        {
+                            self.set_autosave(autosave);
+                        }
+                        
+                        // Hold-to-reveal on entry cards
+                        let mut hold_to_reveal = get_config().ui.hold_to_reveal;
+                        if ui.checkbox(&mut hold_to_reveal, "Hold a password's mask to show it")
+                            .on_hover_text("Off when revealing requires the master password")
+                            .changed()
+                        {
+                            get_config_mut().ui.hold_to_reveal = hold_to_reveal;
+                            if let Err(e) = save_config() {
+                                self.toast_error(e);
+                            }
+                        }
+
+                        // Animations: "system" follows the desktop's reduce-motion setting
+                        let mut animations = get_config().ui.animations.clone();
+                        let previous = animations.clone();
+                        ui.horizontal(|ui| {
+                            ui.label("Animations:");
+                            egui::ComboBox::from_id_salt("animations")
+                                .selected_text(match animations.as_str() {
+                                    "full" => "Full",
+                                    "reduced" => "Reduced",
+                                    "off" => "Off",
+                                    _ => "Follow system",
+                                })
+                                .show_ui(ui, |ui| {
+                                    for (value, label) in [("system", "Follow system"), ("full", "Full"), ("reduced", "Reduced"), ("off", "Off")] {
+                                        ui.selectable_value(&mut animations, value.to_string(), label);
+                                    }
+                                });
+                        });
+                        if animations != previous {
+                            self.motion = Motion::from_config(&animations);
+                            theme::apply_motion(self.motion, ui.ctx());
+                            get_config_mut().ui.animations = animations;
+                            if let Err(e) = save_config() {
+                                self.toast_error(e);
+                            }
+                        }
+
+                        // Password strength suggestions: all, the most important one, or none
+                        let mut suggestion_detail = get_config().ui.suggestion_detail;
+                        let previous = suggestion_detail;
+                        ui.horizontal(|ui| {
+                            ui.label("Strength suggestions:");
+                            egui::ComboBox::from_id_salt("suggestion_detail")
+                                .selected_text(match suggestion_detail {
+                                    SuggestionDetail::Full => "All",
+                                    SuggestionDetail::Brief => "Most important",
+                                    SuggestionDetail::Off => "None",
+                                })
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut suggestion_detail, SuggestionDetail::Full, "All");
+                                    ui.selectable_value(&mut suggestion_detail, SuggestionDetail::Brief, "Most important");
+                                    ui.selectable_value(&mut suggestion_detail, SuggestionDetail::Off, "None");
+                                });
+                        });
+                        if suggestion_detail != previous {
+                            get_config_mut().ui.suggestion_detail = suggestion_detail;
+                            if let Err(e) = save_config() {
+                                self.toast_error(e);
+                            }
+                        }
+
+                        // Open & copy: how long the username stays before the password
+                        let mut open_copy_delay = get_config().ui.open_copy_delay_secs;
+                        let changed = ui.horizontal(|ui| {
+                            ui.label("Open & copy: password follows after");
+                            let changed = ui.add(egui::DragValue::new(&mut open_copy_delay).range(0..=120).suffix(" s")).changed();
+                            ui.label(egui::RichText::new("(0 = on a second click)").size(11.0).color(muted_color));
+                            changed
+                        }).inner;
+                        if changed {
+                            get_config_mut().ui.open_copy_delay_secs = open_copy_delay;
+                            if let Err(e) = save_config() {
+                                self.toast_error(e);
+                            }
+                        }
                     });
                 
                 ui.add_space(16.0);
@@ -149,7 +300,7 @@ impl PassmanApp {
                 // ════════════════════════════════════════════════════════════════
                 // PASSWORD CHANGE SECTION
                 // ════════════════════════════════════════════════════════════════
-                if self.vault.is_some() {
+                if self.is_unlocked() {
                     egui::Frame::none()
                         .fill(frame_fill)
                         .stroke(egui::Stroke::new(1.0, border_color))
@@ -162,10 +313,14 @@ impl PassmanApp {
                                 egui::RichText::new("Change Master Password").size(14.0).strong()
                             )
                             .default_open(false)
+                            .open(std::mem::take(&mut self.open_password_change).then_some(true))
                             .show(ui, |ui| {
                                 ui.add_space(12.0);
                                 
                                 let label_width = 120.0;
+                                let field_width = 240.0;
+                                // Enter in any of the fields changes the password, like the button
+                                let mut submit = false;
                                 
                                 // Current password
                                 ui.horizontal(|ui| {
@@ -174,38 +329,27 @@ impl PassmanApp {
                                         egui::Layout::right_to_left(egui::Align::Center),
                                         |ui| { ui.label("Current:"); }
                                     );
-                                    ui.add_sized(
-                                        egui::vec2(200.0, 24.0),
-                                        egui::TextEdit::singleline(&mut *self.change_current_password)
-                                            .password(true)
-                                    );
+                                    submit |= widgets::password_field(
+                                        ui, &mut self.change_current_password, &mut self.show_password_change,
+                                        "", field_width, false,
+                                    ).submitted;
                                 });
                                 
                                 ui.add_space(8.0);
                                 
-                                // New password
+                                // New password, with its strength underneath
                                 ui.horizontal(|ui| {
                                     ui.allocate_ui_with_layout(
                                         egui::vec2(label_width, 24.0),
                                         egui::Layout::right_to_left(egui::Align::Center),
                                         |ui| { ui.label("New:"); }
                                     );
-                                    ui.add_sized(
-                                        egui::vec2(200.0, 24.0),
-                                        egui::TextEdit::singleline(&mut *self.change_new_password)
-                                            .password(!self.show_password_change)
-                                    );
+                                    submit |= widgets::password_field(
+                                        ui, &mut self.change_new_password, &mut self.show_password_change,
+                                        "", field_width, true,
+                                    ).submitted;
                                 });
                                 
-                                // Strength indicator
-                                if !self.change_new_password.is_empty() {
-                                    ui.horizontal(|ui| {
-                                        ui.add_space(label_width + 8.0);
-                                        let password_clone = self.change_new_password.to_string();
-                                        widgets::show_password_strength_indicator(ui, &password_clone);
-                                    });
-                                }
-                                
                                 ui.add_space(8.0);
                                 
                                 // Confirm password
@@ -215,18 +359,10 @@ impl PassmanApp {
                                         egui::Layout::right_to_left(egui::Align::Center),
                                         |ui| { ui.label("Confirm:"); }
                                     );
-                                    ui.add_sized(
-                                        egui::vec2(200.0, 24.0),
-                                        egui::TextEdit::singleline(&mut *self.change_confirm_password)
-                                            .password(!self.show_password_change)
-                                    );
-                                });
-                                
-                                ui.add_space(8.0);
-                                
-                                ui.horizontal(|ui| {
-                                    ui.add_space(label_width + 8.0);
-                                    ui.checkbox(&mut self.show_password_change, "Show passwords");
+                                    submit |= widgets::password_field(
+                                        ui, &mut self.change_confirm_password, &mut self.show_password_change,
+                                        "", field_width, false,
+                                    ).submitted;
                                 });
                                 
                                 ui.add_space(16.0);
@@ -241,7 +377,7 @@ impl PassmanApp {
                                     .rounding(egui::Rounding::same(8.0))
                                     .min_size(egui::vec2(140.0, 32.0));
                                     
-                                    if ui.add(change_btn).clicked() {
+                                    if ui.add(change_btn).clicked() || submit {
                                         if self.change_current_password.is_empty() {
                                             self.toast_error("Current password is required");
                                         } else if self.change_new_password.is_empty() {
@@ -250,25 +386,10 @@ impl PassmanApp {
                                             self.toast_error("New password must be at least 8 characters");
                                         } else if self.change_new_password.as_str() != self.change_confirm_password.as_str() {
                                             self.toast_error("New passwords do not match");
-                                        } else if self.change_current_password.as_str() != self.master_password.as_str() {
-                                            self.toast_error("Current password is incorrect");
+                                        } else if let Err(e) = self.change_master_password() {
+                                            self.toast_error(e);
                                         } else {
-                                            match VaultManager::change_password(
-                                                &self.change_current_password,
-                                                &self.change_new_password,
-                                                Some(&self.vault_file)
-                                            ) {
-                                                Ok(()) => {
-                                                    *self.master_password = self.change_new_password.to_string();
-                                                    *self.change_current_password = String::new();
-                                                    *self.change_new_password = String::new();
-                                                    *self.change_confirm_password = String::new();
-                                                    self.toast_success("Master password changed successfully!");
-                                                }
-                                                Err(e) => {
-                                                    self.toast_error(format!("Failed to change password: {}", e));
-                                                }
-                                            }
+                                            self.toast_success("Master password changed successfully!");
                                         }
                                     }
                                 });
@@ -278,6 +399,79 @@ impl PassmanApp {
                     ui.add_space(16.0);
                 }
                 
+                // ════════════════════════════════════════════════════════════════
+                // RESTORE BACKUP SECTION
+                // ════════════════════════════════════════════════════════════════
+                if self.is_unlocked() {
+                    egui::Frame::none()
+                        .fill(frame_fill)
+                        .stroke(egui::Stroke::new(1.0, border_color))
+                        .rounding(egui::Rounding::same(12.0))
+                        .inner_margin(egui::Margin::same(20.0))
+                        .show(ui, |ui| {
+                            ui.set_width(card_width);
+                            
+                            egui::CollapsingHeader::new(
+                                egui::RichText::new("Restore Backup").size(14.0).strong()
+                            )
+                            .default_open(false)
+                            .show(ui, |ui| {
+                                ui.add_space(8.0);
+                                self.show_restore_backup(ui, muted_color);
+                            });
+                        });
+                    
+                    ui.add_space(16.0);
+                }
+                
+                // ════════════════════════════════════════════════════════════════
+                // BULK EDIT SECTION
+                // ════════════════════════════════════════════════════════════════
+                if self.is_unlocked() {
+                    egui::Frame::none()
+                        .fill(frame_fill)
+                        .stroke(egui::Stroke::new(1.0, border_color))
+                        .rounding(egui::Rounding::same(12.0))
+                        .inner_margin(egui::Margin::same(20.0))
+                        .show(ui, |ui| {
+                            ui.set_width(card_width);
+                            
+                            egui::CollapsingHeader::new(
+                                egui::RichText::new("Bulk Edit…").size(14.0).strong()
+                            )
+                            .default_open(false)
+                            .show(ui, |ui| {
+                                ui.add_space(8.0);
+                                self.show_bulk_edit(ui, muted_color);
+                            });
+                        });
+                    
+                    ui.add_space(16.0);
+                }
+                
+                // ════════════════════════════════════════════════════════════════
+                // TEMPLATES SECTION
+                // ════════════════════════════════════════════════════════════════
+                egui::Frame::none()
+                    .fill(frame_fill)
+                    .stroke(egui::Stroke::new(1.0, border_color))
+                    .rounding(egui::Rounding::same(12.0))
+                    .inner_margin(egui::Margin::same(20.0))
+                    .show(ui, |ui| {
+                        ui.set_width(card_width);
+                        
+                        egui::CollapsingHeader::new(
+                            egui::RichText::new("Entry Templates").size(14.0).strong()
+                        )
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            ui.add_space(8.0);
+                            self.show_template_editor(ui, muted_color);
+                        });
+                    });
+                
+                ui.add_space(16.0);
+                
                 // ════════════════════════════════════════════════════════════════
                 // ABOUT SECTION
                 // ════════════════════════════════════════════════════════════════
@@ -314,11 +508,520 @@ impl PassmanApp {
                             if ui.link("github.com/0M3REXE/passman").clicked() {
                                 let _ = open::that("https://github.com/0M3REXE/passman");
                             }
+                            ui.add_space(8.0);
+                            
+                            if cfg!(feature = "update-check") {
+                                let mut check_updates = get_config().general.check_updates;
+                                if ui.checkbox(&mut check_updates, "Check for updates weekly")
+                                    .on_hover_text("Fetches a small signed release manifest; nothing is downloaded or installed")
+                                    .changed()
+                                {
+                                    get_config_mut().general.check_updates = check_updates;
+                                    if let Err(e) = save_config() {
+                                        self.toast_error(e);
+                                    }
+                                }
+                            }
                         });
+                        
+                        // Exercises the crash report and error panel
+                        #[cfg(debug_assertions)]
+                        {
+                            ui.add_space(12.0);
+                            if ui.small_button("Trigger test panic").clicked() {
+                                panic!("Test panic triggered from Settings");
+                            }
+                        }
                     });
                 
+                // ════════════════════════════════════════════════════════════════
+                // DANGER ZONE
+                // ════════════════════════════════════════════════════════════════
+                let target = self.active_session()
+                    .map(|session| session.vault_file.clone())
+                    .unwrap_or_else(|| self.vault_file.clone());
+                if VaultManager::exists(Some(&target)) {
+                    ui.add_space(16.0);
+                    egui::Frame::none()
+                        .fill(frame_fill)
+                        .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(239, 68, 68)))
+                        .rounding(egui::Rounding::same(12.0))
+                        .inner_margin(egui::Margin::same(20.0))
+                        .show(ui, |ui| {
+                            ui.set_width(card_width);
+                            self.show_danger_zone(ui, &target, muted_color);
+                        });
+                }
+                
                 ui.add_space(SPACING * 2.0);
             });
         });
     }
+
+    /// Delete the selected vault after the user types its file name
+    fn show_danger_zone(&mut self, ui: &mut egui::Ui, vault_file: &str, muted_color: egui::Color32) {
+        let file_name = std::path::Path::new(vault_file)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| vault_file.to_string());
+        let is_open = self.sessions.iter().any(|session| session.vault_file == vault_file);
+        let backup_count = VaultManager::list_backups(Some(vault_file)).len();
+        
+        ui.label(egui::RichText::new("Danger Zone").size(14.0).strong().color(egui::Color32::from_rgb(239, 68, 68)));
+        ui.add_space(12.0);
+        ui.label(egui::RichText::new(format!("Permanently delete '{}'. This cannot be undone.", vault_file))
+            .size(12.0)
+            .color(muted_color));
+        ui.add_space(8.0);
+        
+        ui.add(egui::TextEdit::singleline(&mut self.delete_vault_text)
+            .hint_text(format!("Type {} to confirm", file_name))
+            .desired_width(280.0));
+        if is_open {
+            ui.checkbox(&mut self.delete_vault_has_backup, "I have a backup of this vault");
+        }
+        if backup_count > 0 {
+            ui.checkbox(&mut self.delete_vault_backups, format!("Also delete its {} backup(s)", backup_count));
+        }
+        ui.add_space(8.0);
+        
+        let confirmed = self.delete_vault_text.trim() == file_name && (!is_open || self.delete_vault_has_backup);
+        let delete = ui.add_enabled_ui(confirmed, |ui| {
+            self.danger_button(ui, "Delete Vault", [120.0, BUTTON_HEIGHT])
+        }).inner;
+        if delete.clicked() {
+            let remove_backups = self.delete_vault_backups;
+            match self.delete_vault(vault_file, remove_backups) {
+                Ok(count) => self.toast_success(format!("Deleted '{}' ({} file(s) removed)", file_name, count)),
+                Err(e) => self.toast_error(format!("Could not delete vault: {}", e)),
+            }
+        }
+    }
+
+    /// Pick a backup, preview what restoring it would change, then restore
+    fn show_restore_backup(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        let Some(vault_file) = self.active_session().map(|session| session.vault_file.clone()) else {
+            return;
+        };
+        
+        ui.horizontal(|ui| {
+            ui.label("Backup:");
+            if ui.add(egui::TextEdit::singleline(&mut self.restore_path)
+                .desired_width(260.0)
+                .hint_text("vault.dat.bak.…"))
+                .changed()
+            {
+                self.restore_preview = None;
+            }
+        });
+        
+        // Recorded verification; nothing is decrypted until Preview
+        if self.backup_list.as_ref().is_none_or(|(file, _)| *file != vault_file) {
+            self.backup_list = Some((vault_file.clone(), VaultManager::backup_infos(Some(&vault_file))));
+        }
+        let backups = self.backup_list.as_ref().map(|(_, backups)| backups.clone()).unwrap_or_default();
+        if backups.is_empty() {
+            ui.label(egui::RichText::new("No backups found for this vault").size(12.0).color(muted_color));
+        }
+        egui::ScrollArea::vertical().id_salt("backup_list").max_height(110.0).show(ui, |ui| {
+            for backup in &backups {
+                let (marker, color, status) = match backup.verified {
+                    Some(true) => ("✓", egui::Color32::from_rgb(34, 197, 94), "Verified"),
+                    Some(false) => ("✗", egui::Color32::from_rgb(239, 68, 68), "Failed verification or changed since"),
+                    None => ("?", muted_color, "Never verified"),
+                };
+                let created = backup.created
+                    .map(|created| created.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_else(|| backup.path.clone());
+                let mut details = vec![created, crate::utils::format_size(backup.size)];
+                if let Some(entries) = backup.entries {
+                    details.push(format!("{} entries", entries));
+                }
+                
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(marker).color(color)).on_hover_text(status);
+                    let selected = self.restore_path == backup.path;
+                    if ui.selectable_label(selected, egui::RichText::new(details.join(" · ")).size(12.0))
+                        .on_hover_text(&backup.path)
+                        .clicked()
+                    {
+                        self.restore_path = backup.path.clone();
+                        self.restore_preview = None;
+                    }
+                });
+            }
+        });
+        let submit = ui.horizontal(|ui| {
+            ui.label("Password:");
+            widgets::password_field(
+                ui, &mut self.restore_password, &mut self.restore_show_password,
+                "Leave empty to use the current one", 300.0, false,
+            ).submitted
+        }).inner;
+        
+        ui.add_space(8.0);
+        if ui.button("Preview Restore").clicked() || submit {
+            if let Err(e) = self.preview_restore() {
+                self.toast_error(e);
+            }
+        }
+        
+        let Some((path, diff)) = self.restore_preview.clone() else {
+            return;
+        };
+        
+        ui.add_space(12.0);
+        if let Some(warning) = diff.restore_warning() {
+            ui.label(egui::RichText::new(format!("⚠ {}. Merge keeps the newer copy of each entry instead.", warning))
+                .color(egui::Color32::from_rgb(245, 158, 11)));
+        }
+        ui.label(egui::RichText::new(format!("Restoring {} would:", path)).strong());
+        if diff.is_empty() {
+            ui.label(egui::RichText::new("change nothing — the backup matches the vault").color(muted_color));
+        }
+        let sections = [
+            ("remove", &diff.only_in_a, egui::Color32::from_rgb(239, 68, 68)),
+            ("bring back", &diff.only_in_b, egui::Color32::from_rgb(34, 197, 94)),
+        ];
+        for (verb, ids, color) in sections {
+            if !ids.is_empty() {
+                ui.label(egui::RichText::new(format!("{} {}: {}", verb, ids.len(), ids.join(", "))).color(color));
+            }
+        }
+        for entry in diff.newer_in_a() {
+            let fields: Vec<&str> = entry.changes.iter().map(|change| change.field).collect();
+            ui.label(
+                egui::RichText::new(format!("revert {} to an older copy ({})", entry.id, fields.join(", ")))
+                    .color(egui::Color32::from_rgb(245, 158, 11))
+            );
+        }
+        for entry in diff.newer_in_b() {
+            ui.label(egui::RichText::new(format!("update {} to the backup's newer copy", entry.id)).color(muted_color));
+        }
+        
+        ui.add_space(8.0);
+        ui.horizontal(|ui| {
+            if ButtonWidgets::danger(ui, "Restore", [100.0, 28.0]).clicked() {
+                match self.restore_backup(false) {
+                    Ok(()) => self.toast_success(format!("Restored {}", path)),
+                    Err(e) => self.toast_error(e),
+                }
+            }
+            if ui.button("Merge (keep newer)").clicked() {
+                match self.restore_backup(true) {
+                    Ok(()) => self.toast_success(format!("Merged {}", path)),
+                    Err(e) => self.toast_error(e),
+                }
+            }
+            if ui.button("Cancel").clicked() {
+                self.clear_restore_form();
+            }
+        });
+    }
+    
+    /// Decrypt the chosen backup and diff it against the active vault
+    fn preview_restore(&mut self) -> Result<(), String> {
+        let path = self.restore_path.trim().to_string();
+        if path.is_empty() {
+            return Err("Choose a backup file first".into());
+        }
+        let session = self.active_session().ok_or("No vault loaded")?;
+        
+        let backup = Self::load_backup(&session.key, &self.restore_password, &path)?;
+        let mut diff = VaultDiff::compare(&session.vault, &backup);
+        diff.redact_secrets();
+        
+        self.restore_preview = Some((path, diff));
+        Ok(())
+    }
+    
+    /// Decrypt a backup with its own password if one was typed, otherwise
+    /// with the open vault's key (which fits backups made since the last
+    /// password change)
+    fn load_backup(key: &VaultKey, password: &Zeroizing<String>, path: &str) -> Result<Vault, String> {
+        if password.is_empty() {
+            let buffer = std::fs::read(path).map_err(|e| e.to_string())?;
+            VaultManager::load_bytes_with_vault_key(key, &buffer)
+        } else {
+            VaultManager::load(password, Some(path))
+        }
+        .map_err(|e| e.to_string())
+    }
+    
+    /// Replace the active vault's contents with the previewed backup, or
+    /// with `merge` keep the newer copy of each entry from either. The
+    /// current file is backed up first and the result is saved with the
+    /// current master password.
+    fn restore_backup(&mut self, merge: bool) -> Result<(), String> {
+        let (path, _) = self.restore_preview.clone().ok_or("Preview the restore first")?;
+        let password = self.restore_password.clone();
+        let session = self.active_session_mut().ok_or("No vault loaded")?;
+        
+        let backup = Self::load_backup(&session.key, &password, &path)?;
+        // Unsaved changes belong in the safety backup
+        session.flush()?;
+        VaultManager::create_backup_with_key(&session.key, Some(&session.vault_file)).map_err(|e| e.to_string())?;
+        
+        if merge {
+            crate::diff::merge_newer(&mut session.vault, &backup);
+        } else {
+            session.vault = backup;
+        }
+        session.search_index = crate::search::SearchIndex::new();
+        session.save()?;
+        self.clear_restore_form();
+        Ok(())
+    }
+
+    /// Find and replace in one field of every entry, previewed before it is applied
+    fn show_bulk_edit(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        ui.label(egui::RichText::new("Passwords are never changed here. Entries in the trash are skipped.").size(12.0).color(muted_color));
+        ui.add_space(4.0);
+        
+        let mut changed = false;
+        egui::Grid::new("bulk_edit_form").num_columns(2).spacing([8.0, 6.0]).show(ui, |ui| {
+            ui.label("Field:");
+            egui::ComboBox::from_id_salt("bulk_field")
+                .selected_text(self.bulk_field.as_str())
+                .show_ui(ui, |ui| {
+                    for field in BulkField::ALL {
+                        changed |= ui.selectable_value(&mut self.bulk_field, field, field.as_str()).changed();
+                    }
+                });
+            ui.end_row();
+            
+            ui.label("Find:");
+            changed |= ui.add(egui::TextEdit::singleline(&mut self.bulk_find)
+                .desired_width(260.0)
+                .hint_text("@oldcorp.com"))
+                .changed();
+            ui.end_row();
+            
+            ui.label("Replace:");
+            changed |= ui.add(egui::TextEdit::singleline(&mut self.bulk_replace)
+                .desired_width(260.0)
+                .hint_text("@newcorp.com"))
+                .changed();
+            ui.end_row();
+            
+            ui.label("");
+            changed |= ui.checkbox(&mut self.bulk_regex, "Regular expression ($1 inserts a group)").changed();
+            ui.end_row();
+        });
+        if changed {
+            self.bulk_preview = None;
+        }
+        
+        ui.add_space(8.0);
+        if ui.button("Preview").clicked() {
+            match self.bulk_replace_rule().and_then(|rule| {
+                let vault = &self.active_session().ok_or("No vault loaded")?.vault;
+                Ok(rule.preview(vault))
+            }) {
+                Ok(preview) => self.bulk_preview = Some(preview),
+                Err(e) => self.toast_error(e),
+            }
+        }
+        
+        let Some(preview) = self.bulk_preview.clone() else {
+            return;
+        };
+        ui.add_space(8.0);
+        if preview.is_empty() {
+            ui.label(egui::RichText::new("No entries match").color(muted_color));
+            return;
+        }
+        egui::ScrollArea::vertical().id_salt("bulk_preview").max_height(200.0).show(ui, |ui| {
+            egui::Grid::new("bulk_preview_table").num_columns(3).striped(true).spacing([12.0, 4.0]).show(ui, |ui| {
+                ui.label(egui::RichText::new("Entry").strong());
+                ui.label(egui::RichText::new("Before").strong());
+                ui.label(egui::RichText::new("After").strong());
+                ui.end_row();
+                for change in &preview {
+                    ui.label(&change.id);
+                    ui.label(egui::RichText::new(&change.before).color(muted_color));
+                    ui.label(&change.after);
+                    ui.end_row();
+                }
+            });
+        });
+        
+        ui.add_space(8.0);
+        ui.horizontal(|ui| {
+            let label = format!("Update {} entr{}", preview.len(), if preview.len() == 1 { "y" } else { "ies" });
+            if ButtonWidgets::danger(ui, &label, [140.0, 28.0]).clicked() {
+                match self.apply_bulk_replace() {
+                    Ok(count) => self.toast_success(format!("Updated {} entr{}", count, if count == 1 { "y" } else { "ies" })),
+                    Err(e) => self.toast_error(e),
+                }
+            }
+            if ui.button("Cancel").clicked() {
+                self.bulk_preview = None;
+            }
+        });
+    }
+    
+    fn bulk_replace_rule(&self) -> Result<BulkReplace, String> {
+        BulkReplace::new(self.bulk_field, &self.bulk_find, &self.bulk_replace, self.bulk_regex)
+    }
+    
+    /// Apply the previewed replacement to the active vault and save once
+    fn apply_bulk_replace(&mut self) -> Result<usize, String> {
+        let rule = self.bulk_replace_rule()?;
+        let session = self.active_session_mut().ok_or("No vault loaded")?;
+        let applied = rule.apply(&mut session.vault);
+        self.commit_changes()?;
+        self.bulk_preview = None;
+        Ok(applied.len())
+    }
+
+    /// List saved entry templates and a small form for adding one
+    fn show_template_editor(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        let templates = get_config().templates.clone();
+        let mut to_remove = None;
+        
+        if templates.is_empty() {
+            ui.label(
+                egui::RichText::new("No templates yet. Use {id} to insert the entry ID.")
+                    .size(12.0)
+                    .color(muted_color)
+            );
+        }
+        for (name, template) in &templates {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(name).strong());
+                if !template.username.is_empty() {
+                    ui.label(egui::RichText::new(&template.username).size(12.0).color(muted_color));
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.small_button("Remove").clicked() {
+                        to_remove = Some(name.clone());
+                    }
+                });
+            });
+        }
+        
+        if let Some(name) = to_remove {
+            get_config_mut().templates.remove(&name);
+            match save_config() {
+                Ok(()) => self.toast_success(format!("Template '{}' removed", name)),
+                Err(e) => self.toast_error(e),
+            }
+        }
+        
+        ui.add_space(12.0);
+        ui.separator();
+        ui.add_space(8.0);
+        
+        egui::Grid::new("template_editor_grid")
+            .num_columns(2)
+            .spacing([8.0, 6.0])
+            .show(ui, |ui| {
+                ui.label("Name:");
+                ui.add(egui::TextEdit::singleline(&mut self.template_name).desired_width(220.0));
+                ui.end_row();
+                
+                ui.label("Username:");
+                ui.add(egui::TextEdit::singleline(&mut self.template_username)
+                    .desired_width(220.0)
+                    .hint_text("{id}@corp.example"));
+                ui.end_row();
+                
+                ui.label("Tags:");
+                ui.add(egui::TextEdit::singleline(&mut self.template_tags)
+                    .desired_width(220.0)
+                    .hint_text("work, sso"));
+                ui.end_row();
+                
+                ui.label("URL:");
+                ui.add(egui::TextEdit::singleline(&mut self.template_url).desired_width(220.0));
+                ui.end_row();
+                
+                ui.label("Note:");
+                ui.add(egui::TextEdit::singleline(&mut self.template_note).desired_width(220.0));
+                ui.end_row();
+            });
+        
+        ui.add_space(8.0);
+        
+        if ui.button("Save Template").clicked() {
+            let name = self.template_name.trim().to_string();
+            if name.is_empty() {
+                self.toast_error("Template name is required");
+                return;
+            }
+            
+            let template = EntryTemplate {
+                username: self.template_username.trim().to_string(),
+                tags: self.template_tags
+                    .split(',')
+                    .map(|tag| tag.trim().to_string())
+                    .filter(|tag| !tag.is_empty())
+                    .collect(),
+                url: self.template_url.trim().to_string(),
+                note: self.template_note.clone(),
+                generation: None,
+            };
+            get_config_mut().templates.insert(name.clone(), template);
+            match save_config() {
+                Ok(()) => {
+                    self.toast_success(format!("Template '{}' saved", name));
+                    self.template_name.clear();
+                    self.template_username.clear();
+                    self.template_tags.clear();
+                    self.template_url.clear();
+                    self.template_note.clear();
+                }
+                Err(e) => self.toast_error(e),
+            }
+        }
+    }
+
+    /// Switch between saving after every change and saving on demand.
+    /// Turning autosave on writes anything still pending.
+    fn set_autosave(&mut self, autosave: bool) {
+        self.autosave = autosave;
+        get_config_mut().general.autosave = autosave;
+        if let Err(e) = save_config() {
+            self.toast_error(e);
+        }
+        if autosave {
+            self.save_dirty_sessions();
+        }
+    }
+
+    /// Re-key the active vault after checking its current master password
+    /// by decrypting the file with it
+    fn change_master_password(&mut self) -> Result<(), String> {
+        let current = self.change_current_password.clone();
+        let new_password = self.change_new_password.clone();
+        let session = self.active_session_mut().ok_or("No vault loaded")?;
+        if session.in_memory {
+            return Err("The demo vault has no master password".into());
+        }
+        
+        if session.security_manager.is_locked_out() {
+            return Err(format!(
+                "Too many failed attempts. Try again in {} seconds.",
+                session.security_manager.lockout_remaining_secs()
+            ));
+        }
+        if VaultManager::load(&current, Some(&session.vault_file)).is_err() {
+            session.security_manager.record_failed_attempt();
+            return Err("Current password is incorrect".into());
+        }
+        
+        // Re-keying rewrites the file from disk, so write pending changes first
+        session.flush()?;
+        session.key = VaultManager::change_password(&current, &new_password, Some(&session.vault_file))
+            .map_err(|e| format!("Failed to change password: {}", e))?;
+        
+        session.security_manager.record_successful_login();
+        session.refresh_fingerprint();
+        session.master_password_warning = None;
+        session.master_check = MasterPasswordCheck::new(&new_password).ok();
+        
+        self.clear_password_change_form();
+        Ok(())
+    }
 }
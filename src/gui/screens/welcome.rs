@@ -3,15 +3,98 @@
 //! Welcome, Init (create vault), and Login screens.
 
 use eframe::egui;
+use crate::lock_summary::LockSummary;
+use crate::update::UpdateState;
+use crate::utils::format_age;
 use crate::vault::VaultManager;
 use super::super::types::Screen;
 use super::super::theme;
+use super::super::markdown;
+use super::super::widgets;
 use super::super::app::PassmanApp;
+use super::super::overlays::PaletteCommand;
+
+/// Palette actions for opening vaults
+pub(super) const PALETTE_COMMANDS: &[PaletteCommand] = &[
+    PaletteCommand {
+        label: "Open another vault…",
+        keywords: "unlock login switch",
+        shortcut: None,
+        available: |app| !matches!(app.current_screen, Screen::Welcome | Screen::Login),
+        run: |app| app.navigate(Screen::Welcome),
+    },
+    PaletteCommand {
+        label: "Create new vault…",
+        keywords: "init new",
+        shortcut: None,
+        available: |app| app.current_screen != Screen::Init,
+        run: |app| app.navigate(Screen::Init),
+    },
+];
 
 impl PassmanApp {
+    /// One-line notice about a newer release; dismissing it silences that version
+    fn show_update_notice(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        let Some(notice) = self.update_notice.clone() else {
+            return;
+        };
+        let (text, color) = if notice.security {
+            (format!("⚠ Passman {} fixes security issues in {}", notice.latest, notice.current), egui::Color32::from_rgb(251, 191, 36))
+        } else {
+            (format!("Passman {} is available", notice.latest), muted_color)
+        };
+        
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(text).size(11.0).color(color));
+            if markdown::is_https(&notice.notes_url) && ui.small_button("Release notes").clicked() {
+                ui.ctx().open_url(egui::OpenUrl::new_tab(&notice.notes_url));
+            }
+            if ui.small_button("✕").on_hover_text("Don't mention this version again").clicked() {
+                UpdateState::dismiss(&notice.latest);
+                self.update_notice = None;
+            }
+        });
+    }
+    
+    /// Vault files found nearby, with their badges; clicking one selects it
+    fn show_known_vaults(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        let scan = self.vault_scan
+            .get_or_insert_with(|| VaultManager::list_vaults(&VaultManager::vault_dirs(&self.vault_file)))
+            .clone();
+        if scan.vaults.is_empty() {
+            return;
+        }
+        
+        ui.add_space(16.0);
+        ui.label(egui::RichText::new("Known vaults").size(11.0).color(muted_color));
+        ui.add_space(4.0);
+        egui::ScrollArea::vertical().max_height(110.0).show(ui, |ui| {
+            for vault in &scan.vaults {
+                ui.horizontal(|ui| {
+                    let name = std::path::Path::new(&vault.path)
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| vault.path.clone());
+                    let selected = self.vault_file == vault.path;
+                    if ui.selectable_label(selected, egui::RichText::new(name).size(12.0))
+                        .on_hover_text(&vault.path)
+                        .clicked()
+                    {
+                        self.vault_file = vault.path.clone();
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if let Some(stats) = &vault.stats {
+                            widgets::vault_stats_badges(ui, stats);
+                        }
+                    });
+                });
+            }
+        });
+    }
+    
     /// Show welcome/home screen
     pub fn show_welcome_screen(&mut self, ui: &mut egui::Ui) {
-        let current_theme = self.current_theme.clone();
+        let current_theme = self.current_theme;
         let muted_color = theme::muted_text_color(&current_theme);
         let frame_fill = theme::frame_fill(&current_theme);
         let border_color = theme::border_color(&current_theme);
@@ -107,7 +190,7 @@ impl PassmanApp {
                             .min_size(egui::vec2(btn_width, btn_height));
                             
                             if ui.add(open_btn).clicked() {
-                                self.current_screen = Screen::Login;
+                                self.navigate(Screen::Login);
                             }
                             
                             ui.add_space(8.0);
@@ -122,7 +205,7 @@ impl PassmanApp {
                             .min_size(egui::vec2(btn_width, btn_height));
                             
                             if ui.add(create_btn).clicked() {
-                                self.current_screen = Screen::Init;
+                                self.navigate(Screen::Init);
                             }
                         } else {
                             // Create new vault (primary)
@@ -134,7 +217,7 @@ impl PassmanApp {
                             .min_size(egui::vec2(btn_width, btn_height));
                             
                             if ui.add(create_btn).clicked() {
-                                self.current_screen = Screen::Init;
+                                self.navigate(Screen::Init);
                             }
                             
                             ui.add_space(8.0);
@@ -145,21 +228,41 @@ impl PassmanApp {
                             );
                         }
                     });
+                    
+                    self.show_known_vaults(ui, muted_color);
                 });
             
             ui.add_space(12.0);
             
+            if self.update_notice.is_some() {
+                self.show_update_notice(ui, muted_color);
+                ui.add_space(12.0);
+            }
+            
             // ════════════════════════════════════════════════════════════════
             // BOTTOM ACTIONS
             // ════════════════════════════════════════════════════════════════
             ui.horizontal(|ui| {
+                // Return to already unlocked vaults
+                if self.is_unlocked() {
+                    if ui.add(
+                        egui::Button::new(egui::RichText::new("← Open Vaults").size(11.0).color(muted_color))
+                            .fill(egui::Color32::TRANSPARENT)
+                            .stroke(egui::Stroke::NONE)
+                    ).clicked() {
+                        self.navigate(Screen::Main);
+                    }
+                    
+                    ui.add_space(8.0);
+                }
+                
                 // Settings button
                 if ui.add(
                     egui::Button::new(egui::RichText::new("⚙ Settings").size(11.0).color(muted_color))
                         .fill(egui::Color32::TRANSPARENT)
                         .stroke(egui::Stroke::NONE)
                 ).clicked() {
-                    self.current_screen = Screen::Settings;
+                    self.navigate(Screen::Settings);
                 }
                 
                 ui.add_space(8.0);
@@ -176,6 +279,16 @@ impl PassmanApp {
                 
                 ui.add_space(8.0);
                 
+                if ui.add(
+                    egui::Button::new(egui::RichText::new("▶ Try demo").size(11.0).color(muted_color))
+                        .fill(egui::Color32::TRANSPARENT)
+                        .stroke(egui::Stroke::NONE)
+                ).on_hover_text("Open a vault of made-up entries; nothing is saved").clicked() {
+                    self.open_demo();
+                }
+                
+                ui.add_space(8.0);
+                
                 // Troubleshooting menu
                 ui.menu_button(
                     egui::RichText::new("🔧 Troubleshoot").size(11.0).color(muted_color),
@@ -231,7 +344,7 @@ impl PassmanApp {
 
     /// Show vault initialization screen
     pub fn show_init_screen(&mut self, ui: &mut egui::Ui) {
-        let current_theme = self.current_theme.clone();
+        let current_theme = self.current_theme;
         let muted_color = theme::muted_text_color(&current_theme);
         let frame_fill = theme::frame_fill(&current_theme);
         let border_color = theme::border_color(&current_theme);
@@ -263,33 +376,23 @@ impl PassmanApp {
                     
                     let field_width = 240.0;
                     
-                    // Master password
+                    // Master password, with its strength underneath
                     ui.label(egui::RichText::new("Master Password").size(11.0).strong());
                     ui.add_space(4.0);
-                    ui.add(
-                        egui::TextEdit::singleline(&mut *self.init_password)
-                            .password(true)
-                            .hint_text("Enter a strong password")
-                            .desired_width(field_width)
-                    );
+                    let mut submit = widgets::password_field(
+                        ui, &mut self.init_password, &mut self.init_show_password,
+                        "Enter a strong password", field_width, true,
+                    ).submitted;
                     
                     ui.add_space(12.0);
                     
                     // Confirm password
                     ui.label(egui::RichText::new("Confirm Password").size(11.0).strong());
                     ui.add_space(4.0);
-                    ui.add(
-                        egui::TextEdit::singleline(&mut *self.init_confirm)
-                            .password(true)
-                            .hint_text("Re-enter your password")
-                            .desired_width(field_width)
-                    );
-                    
-                    // Password strength indicator
-                    if !self.init_password.is_empty() {
-                        ui.add_space(8.0);
-                        self.show_password_strength_indicator(ui, &self.init_password.clone());
-                    }
+                    submit |= widgets::password_field(
+                        ui, &mut self.init_confirm, &mut self.init_show_password,
+                        "Re-enter your password", field_width, false,
+                    ).submitted;
                     
                     ui.add_space(16.0);
                     
@@ -304,7 +407,7 @@ impl PassmanApp {
                         .rounding(egui::Rounding::same(8.0))
                         .min_size(egui::vec2(btn_width, 34.0));
                         
-                        if ui.add(create_btn).clicked() {
+                        if ui.add(create_btn).clicked() || submit {
                             match self.init_vault() {
                                 Ok(()) => {
                                     self.toast_success("Vault created successfully!");
@@ -326,9 +429,7 @@ impl PassmanApp {
                         .min_size(egui::vec2(btn_width, 34.0));
                         
                         if ui.add(back_btn).clicked() {
-                            self.current_screen = Screen::Welcome;
-                            *self.init_password = String::new();
-                            *self.init_confirm = String::new();
+                            self.navigate_back();
                         }
                     });
                 });
@@ -346,7 +447,7 @@ impl PassmanApp {
 
     /// Show login screen
     pub fn show_login_screen(&mut self, ui: &mut egui::Ui) {
-        let current_theme = self.current_theme.clone();
+        let current_theme = self.current_theme;
         let muted_color = theme::muted_text_color(&current_theme);
         let frame_fill = theme::frame_fill(&current_theme);
         let border_color = theme::border_color(&current_theme);
@@ -384,14 +485,13 @@ impl PassmanApp {
                     });
                     ui.add_space(6.0);
                     
-                    ui.vertical_centered(|ui| {
-                        ui.add_sized(
-                            egui::vec2(btn_width, 30.0),
-                            egui::TextEdit::singleline(&mut *self.login_password)
-                                .password(true)
-                                .hint_text("Enter your password")
-                        );
-                    });
+                    let submit = ui.horizontal(|ui| {
+                        ui.add_space((ui.available_width() - btn_width).max(0.0) / 2.0);
+                        widgets::password_field(
+                            ui, &mut self.login_password, &mut self.login_show_password,
+                            "Enter your password", btn_width, false,
+                        ).submitted
+                    }).inner;
                     
                     ui.add_space(18.0);
                     
@@ -404,14 +504,9 @@ impl PassmanApp {
                         .rounding(egui::Rounding::same(8.0))
                         .min_size(egui::vec2(btn_width, 36.0));
                         
-                        if ui.add(open_btn).clicked() {
-                            match self.login() {
-                                Ok(()) => {
-                                    self.toast_success("Vault opened successfully!");
-                                }
-                                Err(e) => {
-                                    self.toast_error(e);
-                                }
+                        if ui.add(open_btn).clicked() || submit {
+                            if let Err(e) = self.login() {
+                                self.toast_error(e);
                             }
                         }
                         
@@ -426,8 +521,7 @@ impl PassmanApp {
                         .min_size(egui::vec2(btn_width, 36.0));
                         
                         if ui.add(back_btn).clicked() {
-                            self.current_screen = Screen::Welcome;
-                            *self.login_password = String::new();
+                            self.navigate_back();
                         }
                     });
                 });
@@ -439,6 +533,65 @@ impl PassmanApp {
                     .size(10.0)
                     .color(egui::Color32::from_rgb(80, 80, 85))
             );
+            self.render_login_fingerprint(ui);
+            self.render_login_summary(ui);
         });
     }
+
+    /// What is known about the vault without unlocking it: the summary
+    /// sealed when it was last locked, or else the plaintext stats the user
+    /// chose to keep. Neither, and the line is left out.
+    fn render_login_summary(&mut self, ui: &mut egui::Ui) {
+        if self.login_summary.as_ref().map(|(path, _)| path) != Some(&self.vault_file) {
+            let now = chrono::Utc::now();
+            let sealed = crate::machine_secret::get()
+                .and_then(|secret| LockSummary::load(&self.vault_file, secret))
+                .map(|summary| summary.describe(now));
+            let line = sealed.or_else(|| {
+                let stats = VaultManager::stats(Some(&self.vault_file))?;
+                Some(format!("Saved {} · {} entries", format_age(stats.saved_at, now), stats.entries))
+            });
+            self.login_summary = Some((self.vault_file.clone(), line));
+        }
+        if let Some((_, Some(line))) = &self.login_summary {
+            ui.label(
+                egui::RichText::new(line)
+                    .size(10.0)
+                    .color(egui::Color32::from_rgb(80, 80, 85))
+            );
+        }
+    }
+
+    /// The vault's fingerprint under the path, and a loud warning if it
+    /// isn't the vault last unlocked there
+    fn render_login_fingerprint(&mut self, ui: &mut egui::Ui) {
+        if self.login_identity.as_ref().map(|(path, _)| path) != Some(&self.vault_file) {
+            let identity = VaultManager::identity(Some(&self.vault_file)).ok();
+            self.login_identity = Some((self.vault_file.clone(), identity));
+        }
+        let Some((_, Some(identity))) = &self.login_identity else {
+            return;
+        };
+
+        ui.label(
+            egui::RichText::new(format!("🔑 {}", identity.fingerprint))
+                .size(10.0)
+                .monospace()
+                .color(egui::Color32::from_rgb(80, 80, 85))
+        ).on_hover_text("Vault fingerprint. It stays the same across password changes; compare it with the one 'passman vault info' shows on your other devices.");
+
+        if let Some(warning) = identity.warning() {
+            ui.add_space(10.0);
+            egui::Frame::none()
+                .fill(egui::Color32::from_rgba_unmultiplied(239, 68, 68, 30))
+                .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(239, 68, 68)))
+                .rounding(egui::Rounding::same(8.0))
+                .inner_margin(egui::Margin::same(10.0))
+                .show(ui, |ui| {
+                    ui.set_max_width(320.0);
+                    ui.label(egui::RichText::new("⚠ Vault file changed").strong().color(egui::Color32::from_rgb(239, 68, 68)));
+                    ui.label(egui::RichText::new(warning).size(11.0));
+                });
+        }
+    }
 }
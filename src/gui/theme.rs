@@ -1,12 +1,126 @@
 //! Theme Module
 //!
-//! Handles application theming and visual styling.
+//! Handles application theming and visual styling, and the timing of
+//! every animation so screens don't each pick their own.
 
 #![allow(dead_code)]
 
 use eframe::egui;
 use super::types::{Theme, SPACING};
 
+/// Screen switch: fade in while sliding up this far
+pub const SCREEN_TRANSITION_SECS: f32 = 0.18;
+pub const SCREEN_SLIDE_PX: f32 = 12.0;
+/// Toasts rise into place and fade out at the end of their time
+pub const TOAST_ENTER_SECS: f32 = 0.2;
+pub const TOAST_EXIT_SECS: f32 = 0.4;
+pub const TOAST_RISE_PX: f32 = 24.0;
+/// egui's own animations (collapsing headers, scrolling)
+const WIDGET_ANIMATION_SECS: f32 = 1.0 / 12.0;
+
+/// How much the interface moves (`ui.animations`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Motion {
+    /// Transitions between screens and for toasts
+    #[default]
+    Full,
+    /// No transitions; the loading spinner pulses instead of turning
+    Reduced,
+    /// Nothing moves
+    Off,
+}
+
+impl Motion {
+    /// Parse `full`, `reduced` or `off`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "full" => Some(Motion::Full),
+            "reduced" => Some(Motion::Reduced),
+            "off" => Some(Motion::Off),
+            _ => None,
+        }
+    }
+
+    /// The configured setting; `system` (or anything unknown) follows the
+    /// desktop's reduce-motion preference
+    pub fn from_config(value: &str) -> Self {
+        Self::parse(value).unwrap_or_else(|| {
+            if os_prefers_reduced_motion() { Motion::Reduced } else { Motion::Full }
+        })
+    }
+}
+
+/// Ease out (cubic) over `t` in 0..=1
+pub fn ease_out(t: f32) -> f32 {
+    1.0 - (1.0 - t.clamp(0.0, 1.0)).powi(3)
+}
+
+/// Opacity and downward offset of a screen shown `elapsed` seconds ago
+pub fn screen_transition(motion: Motion, elapsed: f32) -> (f32, f32) {
+    match motion {
+        Motion::Full => {
+            let t = ease_out(elapsed / SCREEN_TRANSITION_SECS);
+            (t, (1.0 - t) * SCREEN_SLIDE_PX)
+        }
+        Motion::Reduced | Motion::Off => (1.0, 0.0),
+    }
+}
+
+/// Opacity and downward offset of a toast shown for `shown` of its
+/// `duration` seconds
+pub fn toast_transition(motion: Motion, shown: f32, duration: f32) -> (f32, f32) {
+    match motion {
+        Motion::Full => {
+            let enter = ease_out(shown / TOAST_ENTER_SECS);
+            let exit = ((duration - shown) / TOAST_EXIT_SECS).clamp(0.0, 1.0);
+            (enter.min(exit), (1.0 - enter) * TOAST_RISE_PX)
+        }
+        Motion::Reduced | Motion::Off => (1.0, 0.0),
+    }
+}
+
+/// Rotation (radians) and opacity of the loading spinner at `time` seconds
+pub fn spinner_phase(motion: Motion, time: f64) -> (f64, f32) {
+    match motion {
+        Motion::Full => ((time * 2.0) % std::f64::consts::TAU, 1.0),
+        Motion::Reduced => (0.0, 0.6 + 0.4 * (time * 2.0).sin().abs() as f32),
+        Motion::Off => (0.0, 1.0),
+    }
+}
+
+/// Turn egui's own widget animations on or off to match
+pub fn apply_motion(motion: Motion, ctx: &egui::Context) {
+    ctx.style_mut(|style| {
+        style.animation_time = if motion == Motion::Full { WIDGET_ANIMATION_SECS } else { 0.0 };
+    });
+}
+
+/// Whether the desktop asks for less motion: macOS "Reduce motion",
+/// Windows "Animate controls and elements", GNOME's animation switch
+fn os_prefers_reduced_motion() -> bool {
+    let query = |program: &str, args: &[&str]| {
+        let mut command = std::process::Command::new(program);
+        command.args(args);
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+            command.creation_flags(CREATE_NO_WINDOW);
+        }
+        command.output().ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    if cfg!(target_os = "macos") {
+        query("defaults", &["read", "com.apple.universalaccess", "reduceMotion"]).is_some_and(|out| out == "1")
+    } else if cfg!(windows) {
+        query("reg", &["query", r"HKCU\Control Panel\Desktop\WindowMetrics", "/v", "MinAnimate"])
+            .is_some_and(|out| out.split_whitespace().last() == Some("0"))
+    } else {
+        query("gsettings", &["get", "org.gnome.desktop.interface", "enable-animations"]).is_some_and(|out| out == "false")
+    }
+}
+
 /// Apply theme to egui context
 pub fn apply_theme(_theme: &Theme, ctx: &egui::Context) {
     let mut style = (*ctx.style()).clone();
@@ -104,3 +218,25 @@ impl StrengthColors {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transitions_only_move_with_full_motion() {
+        assert_eq!(screen_transition(Motion::Full, 0.0), (0.0, SCREEN_SLIDE_PX));
+        assert_eq!(screen_transition(Motion::Full, SCREEN_TRANSITION_SECS), (1.0, 0.0));
+        let (opacity, offset) = toast_transition(Motion::Full, 3.0 - TOAST_EXIT_SECS / 2.0, 3.0);
+        assert!((opacity - 0.5).abs() < 1e-4 && offset == 0.0);
+
+        for motion in [Motion::Reduced, Motion::Off] {
+            assert_eq!(screen_transition(motion, 0.0), (1.0, 0.0));
+            assert_eq!(toast_transition(motion, 0.0, 3.0), (1.0, 0.0));
+            assert_eq!(spinner_phase(motion, 1.0).0, 0.0);
+        }
+        assert_eq!(spinner_phase(Motion::Off, 1.0), spinner_phase(Motion::Off, 2.0));
+        assert_eq!(Motion::from_config("off"), Motion::Off);
+        assert_eq!(Motion::parse("system"), None);
+    }
+}
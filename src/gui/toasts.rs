@@ -1,85 +1,157 @@
 //! Toast Notifications Module
 //!
-//! Handles toast notifications with auto-dismiss and animations.
+//! Handles toast notifications with auto-dismiss and animations, their
+//! action buttons and the notification history panel.
 
+use std::time::Instant;
 use eframe::egui;
-use super::types::{Toast, ToastType};
+use super::theme::{self, Motion};
+use super::types::{Toast, ToastAction, ToastRecord, ToastType, MAX_VISIBLE_TOASTS};
 
-/// Render toast notifications
-pub fn render_toasts(ctx: &egui::Context, toasts: &[Toast]) {
+/// What was clicked on the toasts this frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToastEvent {
+    /// The action button of the toast at this index
+    Action(usize, ToastAction),
+    /// The close button of the toast at this index
+    Dismiss(usize),
+    /// The "N more" line
+    ShowHistory,
+}
+
+/// Background, icon and text colour for a kind of toast
+pub fn toast_style(toast_type: ToastType) -> (egui::Color32, &'static str, egui::Color32) {
+    match toast_type {
+        ToastType::Success => (egui::Color32::from_rgb(40, 167, 69), "✓", egui::Color32::WHITE),
+        ToastType::Error => (egui::Color32::from_rgb(220, 53, 69), "✕", egui::Color32::WHITE),
+        ToastType::Info => (egui::Color32::from_rgb(23, 162, 184), "ℹ", egui::Color32::WHITE),
+        ToastType::Warning => (egui::Color32::from_rgb(255, 193, 7), "⚠", egui::Color32::BLACK),
+    }
+}
+
+/// Render toast notifications, newest at the bottom. Only the newest
+/// `MAX_VISIBLE_TOASTS` are shown; hovering one pauses its countdown.
+/// With full motion they slide in and fade out.
+pub fn render_toasts(ctx: &egui::Context, toasts: &mut [Toast], motion: Motion) -> Option<ToastEvent> {
     if toasts.is_empty() {
-        return;
+        return None;
     }
-    
+
     // Request repaint for animation
     ctx.request_repaint();
-    
+
+    let hidden = toasts.len().saturating_sub(MAX_VISIBLE_TOASTS);
+    let now = Instant::now();
+    let mut event = None;
+
     // Render toasts in top-right corner
     egui::Area::new(egui::Id::new("toast_area"))
         .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-20.0, 50.0))
         .order(egui::Order::Foreground)
         .show(ctx, |ui| {
             ui.vertical(|ui| {
-                for (i, toast) in toasts.iter().enumerate() {
-                    let (bg_color, icon, text_color) = match toast.toast_type {
-                        ToastType::Success => (
-                            egui::Color32::from_rgb(40, 167, 69),
-                            "✓",
-                            egui::Color32::WHITE,
-                        ),
-                        ToastType::Error => (
-                            egui::Color32::from_rgb(220, 53, 69),
-                            "✕",
-                            egui::Color32::WHITE,
-                        ),
-                        ToastType::Info => (
-                            egui::Color32::from_rgb(23, 162, 184),
-                            "ℹ",
-                            egui::Color32::WHITE,
-                        ),
-                        ToastType::Warning => (
-                            egui::Color32::from_rgb(255, 193, 7),
-                            "⚠",
-                            egui::Color32::BLACK,
-                        ),
-                    };
-                    
-                    // Fade out effect
-                    let alpha = (toast.progress() * 255.0) as u8;
-                    let bg_with_alpha = egui::Color32::from_rgba_unmultiplied(
-                        bg_color.r(), bg_color.g(), bg_color.b(), alpha
-                    );
-                    
-                    egui::Frame::none()
-                        .fill(bg_with_alpha)
-                        .rounding(egui::Rounding::same(8.0))
-                        .inner_margin(egui::Margin::symmetric(12.0, 8.0))
-                        .shadow(egui::epaint::Shadow {
-                            offset: egui::vec2(2.0, 2.0),
-                            blur: 8.0,
-                            spread: 0.0,
-                            color: egui::Color32::from_black_alpha(50),
-                        })
-                        .show(ui, |ui| {
-                            ui.horizontal(|ui| {
-                                ui.colored_label(text_color, icon);
-                                ui.colored_label(text_color, &toast.message);
-                            });
-                            
-                            // Progress bar showing remaining time
-                            let progress_color = egui::Color32::from_white_alpha(100);
-                            let rect = ui.available_rect_before_wrap();
-                            let progress_rect = egui::Rect::from_min_size(
-                                egui::pos2(rect.min.x, rect.max.y - 2.0),
-                                egui::vec2(rect.width() * toast.progress(), 2.0),
-                            );
-                            ui.painter().rect_filled(progress_rect, 0.0, progress_color);
-                        });
-                    
-                    if i < toasts.len() - 1 {
+                if hidden > 0 {
+                    let more = ui.add(egui::Label::new(
+                        egui::RichText::new(format!("+{} more", hidden)).small().weak()
+                    ).sense(egui::Sense::click()));
+                    if more.on_hover_text("Show all notifications").clicked() {
+                        event = Some(ToastEvent::ShowHistory);
+                    }
+                    ui.add_space(4.0);
+                }
+
+                let count = toasts.len();
+                for (i, toast) in toasts.iter_mut().enumerate().skip(hidden) {
+                    let (bg_color, icon, text_color) = toast_style(toast.toast_type);
+
+                    let (opacity, rise) = theme::toast_transition(motion, toast.shown_secs(), toast.duration_secs);
+                    let frame = ui.scope(|ui| {
+                        ui.multiply_opacity(opacity);
+                        egui::Frame::none()
+                            .fill(bg_color)
+                            .outer_margin(egui::Margin { top: rise, ..Default::default() })
+                            .rounding(egui::Rounding::same(8.0))
+                            .inner_margin(egui::Margin::symmetric(12.0, 8.0))
+                            .shadow(egui::epaint::Shadow {
+                                offset: egui::vec2(2.0, 2.0),
+                                blur: 8.0,
+                                spread: 0.0,
+                                color: egui::Color32::from_black_alpha(50),
+                            })
+                            .show(ui, |ui| {
+                                ui.horizontal(|ui| {
+                                    ui.colored_label(text_color, icon);
+                                    ui.colored_label(text_color, &toast.message);
+                                    if let Some(action) = toast.action {
+                                        let button = egui::Button::new(egui::RichText::new(action.label()).color(text_color).strong())
+                                            .fill(egui::Color32::from_white_alpha(30))
+                                            .stroke(egui::Stroke::new(1.0, text_color));
+                                        if ui.add(button).clicked() {
+                                            event = Some(ToastEvent::Action(i, action));
+                                        }
+                                    }
+                                    if ui.add(egui::Button::new(egui::RichText::new("×").color(text_color)).frame(false))
+                                        .on_hover_text("Dismiss")
+                                        .clicked()
+                                    {
+                                        event = Some(ToastEvent::Dismiss(i));
+                                    }
+                                });
+
+                                // Progress bar showing remaining time
+                                let progress_color = egui::Color32::from_white_alpha(100);
+                                let rect = ui.available_rect_before_wrap();
+                                let progress_rect = egui::Rect::from_min_size(
+                                    egui::pos2(rect.min.x, rect.max.y - 2.0),
+                                    egui::vec2(rect.width() * toast.progress(), 2.0),
+                                );
+                                ui.painter().rect_filled(progress_rect, 0.0, progress_color);
+                            })
+                    }).inner;
+                    toast.set_paused(frame.response.contains_pointer(), now);
+
+                    if i + 1 < count {
                         ui.add_space(8.0);
                     }
                 }
             });
         });
+    event
+}
+
+/// Notification history for this session, newest first. Returns false
+/// when the panel was closed.
+pub fn render_toast_history(ctx: &egui::Context, history: &mut Vec<ToastRecord>) -> bool {
+    let mut open = true;
+    let mut clear = false;
+    egui::Window::new("🔔 Notifications")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_width(380.0)
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-20.0, 50.0))
+        .show(ctx, |ui| {
+            if history.is_empty() {
+                ui.label(egui::RichText::new("Nothing yet this session").weak());
+                return;
+            }
+            egui::ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+                for record in history.iter().rev() {
+                    let (color, icon, _) = toast_style(record.toast_type);
+                    ui.horizontal_wrapped(|ui| {
+                        ui.colored_label(color, icon);
+                        ui.label(egui::RichText::new(record.at.format("%H:%M:%S").to_string()).weak().small());
+                        ui.label(&record.message);
+                    });
+                }
+            });
+            ui.separator();
+            if ui.button("Clear").clicked() {
+                clear = true;
+            }
+        });
+    if clear {
+        history.clear();
+    }
+    open
 }
@@ -5,6 +5,11 @@
 #![allow(dead_code)]
 
 use std::time::Instant;
+use eframe::egui;
+use crate::diff::VaultDiff;
+use crate::health::{HealthReport, PasswordHealth};
+use crate::model::{GenerationMode, Vault};
+use crate::utils::PasswordStrength;
 
 // UI Constants
 pub const BUTTON_HEIGHT: f32 = 36.0;
@@ -14,6 +19,41 @@ pub const PADDING: f32 = 20.0;
 pub const MIN_WINDOW_WIDTH: f32 = 500.0;
 pub const MIN_WINDOW_HEIGHT: f32 = 400.0;
 
+/// How often the open vault file is checked for external modifications
+pub const EXTERNAL_CHANGE_POLL_SECS: u64 = 3;
+
+/// Characters of a note shown on a collapsed entry card
+pub const NOTE_PREVIEW_CHARS: usize = 60;
+
+/// Width kept free for the card's action buttons when a note is expanded
+pub const CARD_ACTIONS_WIDTH: f32 = 366.0;
+
+/// Characters of an id or username shown on an entry card; the rest is
+/// cut off so that an imported monster doesn't get laid out every frame
+pub const CARD_FIELD_CHARS: usize = 120;
+
+/// Characters of a note shown when its card is expanded; past this,
+/// "Show full" opens the rest as a [`FullText`]
+pub const EXPANDED_NOTE_CHARS: usize = 16 * 1024;
+
+/// Characters per row of a [`FullText`]; longer lines take several rows
+pub const FULL_TEXT_ROW_CHARS: usize = 200;
+
+/// Height of the scroll area a [`FullText`] is shown in
+pub const FULL_TEXT_HEIGHT: f32 = 300.0;
+
+/// Longest id, username or URL the entry forms take
+pub const FIELD_CHAR_LIMIT: usize = crate::import_export::DEFAULT_MAX_FIELD_CHARS;
+
+/// Longest note the entry forms take
+pub const NOTE_CHAR_LIMIT: usize = crate::import_export::DEFAULT_MAX_NOTE_CHARS;
+
+/// Rows shown in the command palette
+pub const MAX_PALETTE_ITEMS: usize = 10;
+
+/// How long a password mask must be held down before it shows the password
+pub const PEEK_HOLD: std::time::Duration = std::time::Duration::from_millis(1500);
+
 /// Get responsive input width based on available space
 pub fn responsive_input_width(available_width: f32) -> f32 {
     let base = INPUT_WIDTH;
@@ -51,6 +91,152 @@ pub enum Screen {
     ImportExport,
 }
 
+impl Screen {
+    /// Screens shown before a vault is unlocked
+    pub fn is_locked(&self) -> bool {
+        matches!(self, Self::Welcome | Self::Init | Self::Login)
+    }
+}
+
+/// A long note or attachment opened with "Show full". Split into rows
+/// once, so each frame lays out only the rows in view.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FullText {
+    pub session: usize,
+    pub id: String,
+    /// `None` for the note, or the attachment's name
+    pub attachment: Option<String>,
+    /// Byte ranges of the rows, each at most [`FULL_TEXT_ROW_CHARS`] long
+    pub rows: Vec<std::ops::Range<usize>>,
+    /// Length of the text the rows were made from, to notice an edit
+    pub len: usize,
+}
+
+impl FullText {
+    pub fn new(session: usize, id: &str, attachment: Option<&str>, text: &str) -> Self {
+        let mut rows = Vec::new();
+        let (mut start, mut chars) = (0, 0);
+        for (index, c) in text.char_indices() {
+            if c == '\n' {
+                rows.push(start..index);
+                (start, chars) = (index + 1, 0);
+                continue;
+            }
+            if chars == FULL_TEXT_ROW_CHARS {
+                rows.push(start..index);
+                (start, chars) = (index, 0);
+            }
+            chars += 1;
+        }
+        rows.push(start..text.len());
+        Self { session, id: id.to_string(), attachment: attachment.map(str::to_string), rows, len: text.len() }
+    }
+
+    /// Whether this is the text of `attachment` (or the note) of entry `id`
+    pub fn shows(&self, session: usize, id: &str, attachment: Option<&str>) -> bool {
+        self.session == session && self.id == id && self.attachment.as_deref() == attachment
+    }
+}
+
+/// A screen that panicked while rendering
+#[derive(Clone)]
+pub struct RenderError {
+    pub screen: Screen,
+    pub report: Option<std::path::PathBuf>,
+}
+
+/// What the "Open & copy" flow has put on the clipboard
+#[derive(Clone, Copy, PartialEq)]
+pub enum ClipboardStage {
+    /// The username; the password replaces it at `swap_at`, or on a
+    /// second click when there is no delay
+    Username { swap_at: Option<Instant> },
+    /// The password; cleared at `clears_at` when auto-clear is on
+    Password { clears_at: Option<Instant> },
+}
+
+/// A two-stage login in progress for one entry
+#[derive(Clone)]
+pub struct OpenCopy {
+    pub session: usize,
+    pub id: String,
+    pub stage: ClipboardStage,
+}
+
+/// A conflicted sync copy opened to show what merging it would do
+pub struct SyncConflictPreview {
+    pub session: usize,
+    pub copy_path: String,
+    pub copy: Vault,
+    /// The vault (A) against the copy (B), secrets redacted
+    pub diff: VaultDiff,
+}
+
+/// Searches remembered for Up/Down in the search box
+pub const SEARCH_HISTORY_LEN: usize = 20;
+
+/// Recent search queries, kept in memory until every vault is locked
+#[derive(Default)]
+pub struct SearchHistory {
+    /// Oldest first, without duplicates
+    queries: Vec<String>,
+    /// Query being shown while stepping through the history
+    position: Option<usize>,
+    /// What was typed before stepping into the history
+    draft: String,
+}
+
+impl SearchHistory {
+    /// Remember a query that was searched for
+    pub fn record(&mut self, query: &str) {
+        let query = query.trim();
+        self.position = None;
+        if query.is_empty() {
+            return;
+        }
+        self.queries.retain(|previous| previous != query);
+        self.queries.push(query.to_string());
+        if self.queries.len() > SEARCH_HISTORY_LEN {
+            self.queries.remove(0);
+        }
+    }
+
+    /// Step to an older query (Up); `current` is kept to come back to
+    pub fn older(&mut self, current: &str) -> Option<String> {
+        let position = match self.position {
+            Some(position) => position.saturating_sub(1),
+            None => {
+                let newest = self.queries.len().checked_sub(1)?;
+                self.draft = current.to_string();
+                newest
+            }
+        };
+        self.position = Some(position);
+        Some(self.queries[position].clone())
+    }
+
+    /// Step to a newer query (Down), ending at what was typed before
+    pub fn newer(&mut self) -> Option<String> {
+        let position = self.position?;
+        if position + 1 < self.queries.len() {
+            self.position = Some(position + 1);
+            Some(self.queries[position + 1].clone())
+        } else {
+            self.position = None;
+            Some(std::mem::take(&mut self.draft))
+        }
+    }
+
+    /// Typing leaves the history
+    pub fn stop_browsing(&mut self) {
+        self.position = None;
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
 /// Toast notification types
 #[derive(Clone, Copy, PartialEq)]
 pub enum ToastType {
@@ -60,13 +246,45 @@ pub enum ToastType {
     Warning,
 }
 
-/// Toast notification with auto-dismiss
+/// Toasts shown at once; the rest collapse into an "N more" line
+pub const MAX_VISIBLE_TOASTS: usize = 4;
+
+/// Notifications kept in the bell panel for this session, oldest dropped first
+pub const TOAST_HISTORY_LEN: usize = 100;
+
+/// Button on a toast, dispatched by `PassmanApp::run_toast_action`.
+/// Variants carry no data: what they act on (the deleted entry, the
+/// clipboard) stays in the app, so a toast never holds a secret.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToastAction {
+    /// Put back the entry deleted last
+    UndoDelete,
+    /// Clear the clipboard now instead of at the end of its countdown
+    ClearClipboard,
+    /// Write the vaults whose last save failed
+    RetrySave,
+}
+
+impl ToastAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::UndoDelete => "Undo",
+            Self::ClearClipboard => "Clear now",
+            Self::RetrySave => "Retry",
+        }
+    }
+}
+
+/// Toast notification with auto-dismiss. Hovering pauses the countdown.
 #[derive(Clone)]
 pub struct Toast {
     pub message: String,
     pub toast_type: ToastType,
     pub created_at: Instant,
     pub duration_secs: f32,
+    pub action: Option<ToastAction>,
+    /// When hovering started; the countdown stands still until it ends
+    paused_at: Option<Instant>,
 }
 
 impl Toast {
@@ -76,6 +294,8 @@ impl Toast {
             toast_type,
             created_at: Instant::now(),
             duration_secs: 3.0,
+            action: None,
+            paused_at: None,
         }
     }
 
@@ -85,12 +305,52 @@ impl Toast {
         self
     }
 
+    /// Add a button; toasts with one stay up longer so it can be reached
+    pub fn with_action(mut self, action: ToastAction) -> Self {
+        self.action = Some(action);
+        self.duration_secs = self.duration_secs.max(8.0);
+        self
+    }
+
+    /// Stop or restart the countdown as the pointer enters or leaves
+    pub fn set_paused(&mut self, paused: bool, now: Instant) {
+        match (paused, self.paused_at) {
+            (true, None) => self.paused_at = Some(now),
+            (false, Some(at)) => {
+                self.created_at += now.saturating_duration_since(at);
+                self.paused_at = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Seconds shown so far, not counting time spent hovered
+    pub fn shown_secs(&self) -> f32 {
+        let now = self.paused_at.unwrap_or_else(Instant::now);
+        now.saturating_duration_since(self.created_at).as_secs_f32()
+    }
+
     pub fn is_expired(&self) -> bool {
-        self.created_at.elapsed().as_secs_f32() >= self.duration_secs
+        self.shown_secs() >= self.duration_secs
     }
 
     pub fn progress(&self) -> f32 {
-        1.0 - (self.created_at.elapsed().as_secs_f32() / self.duration_secs).min(1.0)
+        1.0 - (self.shown_secs() / self.duration_secs).min(1.0)
+    }
+}
+
+/// A notification as kept in the history panel: its text, kind and time,
+/// without the action
+#[derive(Clone)]
+pub struct ToastRecord {
+    pub message: String,
+    pub toast_type: ToastType,
+    pub at: chrono::DateTime<chrono::Local>,
+}
+
+impl From<&Toast> for ToastRecord {
+    fn from(toast: &Toast) -> Self {
+        Self { message: toast.message.clone(), toast_type: toast.toast_type, at: chrono::Local::now() }
     }
 }
 
@@ -107,12 +367,81 @@ impl Theme {
     }
 }
 
+/// Password generator modes on the add and edit screens
+#[derive(Default, PartialEq, Clone, Copy)]
+pub enum GeneratorMode {
+    #[default]
+    Random,
+    Memorable,
+    Pronounceable,
+}
+
+impl GeneratorMode {
+    pub const ALL: [GeneratorMode; 3] = [Self::Random, Self::Memorable, Self::Pronounceable];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Random => "Random",
+            Self::Memorable => "Memorable",
+            Self::Pronounceable => "Pronounceable",
+        }
+    }
+}
+
+impl From<GeneratorMode> for GenerationMode {
+    fn from(mode: GeneratorMode) -> Self {
+        match mode {
+            GeneratorMode::Random => GenerationMode::Random,
+            GeneratorMode::Memorable => GenerationMode::Memorable,
+            GeneratorMode::Pronounceable => GenerationMode::Pronounceable,
+        }
+    }
+}
+
+impl From<GenerationMode> for GeneratorMode {
+    fn from(mode: GenerationMode) -> Self {
+        match mode {
+            GenerationMode::Random => GeneratorMode::Random,
+            GenerationMode::Memorable => GeneratorMode::Memorable,
+            GenerationMode::Pronounceable => GeneratorMode::Pronounceable,
+        }
+    }
+}
+
 /// Export file formats
 #[derive(Default, PartialEq, Clone, Copy)]
 pub enum ExportFormat {
     #[default]
     Json,
     Csv,
+    KeePassXml,
+    /// A copy of the vault that opens with the master password
+    Encrypted,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::KeePassXml => "xml",
+            Self::Encrypted => "dat",
+        }
+    }
+
+    /// Name of the file dialog filter
+    pub fn filter_name(&self) -> &'static str {
+        match self {
+            Self::Json => "JSON files",
+            Self::Csv => "CSV files",
+            Self::KeePassXml => "KeePass XML files",
+            Self::Encrypted => "Vault files",
+        }
+    }
+
+    pub fn is_plaintext(&self) -> bool {
+        *self != Self::Encrypted
+    }
 }
 
 /// Import file formats
@@ -123,3 +452,340 @@ pub enum ImportFormat {
     Csv,
     Chrome,
 }
+
+impl ImportFormat {
+    /// Format name as used by import profiles and the CLI
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Chrome => "chrome",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            "chrome" => Some(Self::Chrome),
+            _ => None,
+        }
+    }
+}
+
+/// What a high-security entry's PIN is asked for
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PinPurpose {
+    Copy,
+    Show,
+    ShowQr,
+}
+
+/// Something done to one entry. Card buttons, the card's context menu and
+/// the command palette all run these through `PassmanApp::run_entry_action`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EntryAction {
+    CopyPassword,
+    CopyUsername,
+    OpenUrl,
+    Edit,
+    Duplicate,
+    Archive,
+    Unarchive,
+    Share,
+    Delete,
+    ShowQr,
+}
+
+impl EntryAction {
+    /// The context menu, top to bottom; `Archive` becomes `Unarchive` on an
+    /// archived entry
+    pub const MENU: [EntryAction; 9] = [
+        Self::CopyPassword,
+        Self::CopyUsername,
+        Self::OpenUrl,
+        Self::Edit,
+        Self::Duplicate,
+        Self::Archive,
+        Self::Share,
+        Self::Delete,
+        Self::ShowQr,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::CopyPassword => "📋 Copy password",
+            Self::CopyUsername => "👤 Copy username",
+            Self::OpenUrl => "🌐 Open URL",
+            Self::Edit => "✏ Edit",
+            Self::Duplicate => "⧉ Duplicate",
+            Self::Archive => "📦 Archive",
+            Self::Unarchive => "📦 Unarchive",
+            Self::Share => "🔗 Share…",
+            Self::Delete => "🗑 Delete",
+            Self::ShowQr => "▦ Show QR",
+        }
+    }
+
+    /// Whether it applies to an entry in this state. Archived entries are
+    /// for reference: only their username and site stay at hand until
+    /// they are unarchived.
+    pub fn applies(&self, archived: bool, has_url: bool) -> bool {
+        match self {
+            Self::OpenUrl => has_url,
+            Self::Archive => !archived,
+            Self::Unarchive => archived,
+            Self::CopyUsername => true,
+            _ => !archived,
+        }
+    }
+}
+
+/// Entries past which a vault is no longer new enough for the "Getting
+/// started" checklist
+pub const GETTING_STARTED_MAX_ENTRIES: usize = 5;
+
+/// One item of the "Getting started" checklist on a new vault's main screen
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GettingStartedStep {
+    FirstEntry,
+    Import,
+    AutoLock,
+    Generator,
+    Backup,
+}
+
+impl GettingStartedStep {
+    pub const ALL: [GettingStartedStep; 5] = [
+        Self::FirstEntry,
+        Self::Import,
+        Self::AutoLock,
+        Self::Generator,
+        Self::Backup,
+    ];
+
+    /// Name recorded in `ui.getting_started_done` once it is done
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::FirstEntry => "first_entry",
+            Self::Import => "import",
+            Self::AutoLock => "auto_lock",
+            Self::Generator => "generator",
+            Self::Backup => "backup",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::FirstEntry => "Add your first entry",
+            Self::Import => "Import from a browser",
+            Self::AutoLock => "Set up auto-lock",
+            Self::Generator => "Try the password generator",
+            Self::Backup => "Create a backup",
+        }
+    }
+
+    pub fn is_recorded(&self, done: &[String]) -> bool {
+        done.iter().any(|key| key == self.key())
+    }
+
+    /// Whether the checklist shows for a vault of `entries` entries: it is
+    /// new, the checklist wasn't dismissed and something is left to do
+    pub fn checklist_shows(entries: usize, dismissed: bool, done: &[String]) -> bool {
+        !dismissed && entries <= GETTING_STARTED_MAX_ENTRIES && !Self::ALL.iter().all(|step| step.is_recorded(done))
+    }
+
+    /// The button that takes you there
+    pub fn action(&self) -> &'static str {
+        match self {
+            Self::FirstEntry => "Add entry",
+            Self::Import => "Import…",
+            Self::AutoLock => "Settings",
+            Self::Generator => "Generate",
+            Self::Backup => "Back up now",
+        }
+    }
+}
+
+/// An entry's context menu while it is open
+#[derive(Debug, PartialEq, Clone)]
+pub struct EntryMenu {
+    pub session: usize,
+    pub id: String,
+    /// Top-left corner: where it was right-clicked
+    pub pos: egui::Pos2,
+    /// Item highlighted for the keyboard
+    pub selected: usize,
+}
+
+/// A password shown as a QR code, to scan it into a phone. Only the
+/// modules are kept, not the password.
+pub struct PasswordQr {
+    pub id: String,
+    /// Modules per side
+    pub width: usize,
+    /// Row by row, true for dark
+    pub modules: Vec<bool>,
+    pub shown_at: Instant,
+}
+
+impl PasswordQr {
+    pub fn new(id: &str, password: &str) -> Result<Self, String> {
+        let code = qr_code::QrCode::new(password.as_bytes()).map_err(|e| format!("Could not make a QR code: {:?}", e))?;
+        Ok(PasswordQr { id: id.to_string(), width: code.width(), modules: code.to_vec(), shown_at: Instant::now() })
+    }
+}
+
+/// The add or the edit entry form
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum EntryForm {
+    Add,
+    Edit,
+}
+
+/// An offer under an add form field to fix what was typed or pasted
+/// there. Nothing changes until it is accepted.
+#[derive(Debug, PartialEq, Clone)]
+pub enum AddFormHint {
+    /// The password field holds an otpauth link, not a password
+    OtpAuthPassword,
+    /// The URL has a query string or fragment that can be dropped
+    TrackedUrl { stripped: String },
+    /// The URL was trimmed; undo puts the original back
+    TrimmedUrl { original: String },
+    /// The ID field holds an email address and the username is empty
+    EmailId,
+}
+
+impl AddFormHint {
+    /// Form field the hint is shown under, as in form errors
+    pub fn field(&self) -> &'static str {
+        match self {
+            Self::OtpAuthPassword => "add_password",
+            Self::TrackedUrl { .. } | Self::TrimmedUrl { .. } => "add_url",
+            Self::EmailId => "add_id",
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::OtpAuthPassword => "This is a 2FA setup link, not a password",
+            Self::TrackedUrl { .. } => "The query string and fragment are usually tracking or session state",
+            Self::TrimmedUrl { .. } => "Removed the query string and fragment",
+            Self::EmailId => "This looks like a username",
+        }
+    }
+
+    /// Label of the button that accepts it
+    pub fn action(&self) -> &'static str {
+        match self {
+            Self::OtpAuthPassword => "Use as 2FA secret",
+            Self::TrackedUrl { .. } => "Remove them",
+            Self::TrimmedUrl { .. } => "Undo",
+            Self::EmailId => "Move to username",
+        }
+    }
+}
+
+/// Why an edit wasn't saved: the entry changed after editing started
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum EditConflict {
+    /// Saved from another screen since the edit form was opened
+    Modified,
+    /// Deleted or renamed since the edit form was opened
+    Removed,
+}
+
+/// How to settle an edit conflict
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ConflictResolution {
+    Overwrite,
+    Discard,
+    SaveAsNew,
+}
+
+/// Column the health dashboard table is sorted by
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum HealthColumn {
+    Entry,
+    /// Most urgent first
+    #[default]
+    Status,
+    Strength,
+    Age,
+    Reuse,
+    Modified,
+}
+
+impl HealthColumn {
+    pub const ALL: [HealthColumn; 6] = [Self::Entry, Self::Status, Self::Strength, Self::Age, Self::Reuse, Self::Modified];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Entry => "Entry",
+            Self::Status => "Status",
+            Self::Strength => "Strength",
+            Self::Age => "Age",
+            Self::Reuse => "Reused",
+            Self::Modified => "Modified",
+        }
+    }
+
+    /// Compare two reports in ascending order: critical before excellent,
+    /// weak before strong, new before old. Ties fall back to the id.
+    pub fn compare(&self, a: &HealthReport, b: &HealthReport) -> std::cmp::Ordering {
+        let order = match self {
+            Self::Entry => std::cmp::Ordering::Equal,
+            Self::Status => severity(&a.health).cmp(&severity(&b.health)),
+            Self::Strength => strength_rank(&a.strength).cmp(&strength_rank(&b.strength)),
+            Self::Age => a.age_days.cmp(&b.age_days),
+            Self::Reuse => a.reused_with.cmp(&b.reused_with),
+            Self::Modified => a.last_changed.cmp(&b.last_changed),
+        };
+        order.then_with(|| a.entry_id.to_lowercase().cmp(&b.entry_id.to_lowercase()))
+    }
+}
+
+/// Sort order of the health dashboard table
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct HealthSort {
+    pub column: HealthColumn,
+    pub descending: bool,
+}
+
+impl HealthSort {
+    pub fn sort(&self, reports: &mut [&HealthReport]) {
+        reports.sort_by(|a, b| {
+            let order = self.column.compare(a, b);
+            if self.descending { order.reverse() } else { order }
+        });
+    }
+
+    /// A header click: sort by `column`, or flip the order if it already is
+    pub fn click(&mut self, column: HealthColumn) {
+        if self.column == column {
+            self.descending = !self.descending;
+        } else {
+            *self = Self { column, descending: false };
+        }
+    }
+}
+
+fn severity(health: &PasswordHealth) -> u8 {
+    match health {
+        PasswordHealth::Critical { .. } => 0,
+        PasswordHealth::Warning { .. } => 1,
+        PasswordHealth::Good => 2,
+        PasswordHealth::Excellent => 3,
+    }
+}
+
+fn strength_rank(strength: &PasswordStrength) -> u8 {
+    match strength {
+        PasswordStrength::VeryWeak => 0,
+        PasswordStrength::Weak => 1,
+        PasswordStrength::Fair => 2,
+        PasswordStrength::Good => 3,
+        PasswordStrength::Strong => 4,
+    }
+}
@@ -6,6 +6,8 @@
 
 use eframe::egui;
 use std::collections::HashMap;
+use crate::config::SuggestionDetail;
+use crate::utils::{analyze_password_strength, glyph_name, spell_groups, spell_legend, PasswordStrength};
 
 // ============================================================================
 // BUTTON WIDGETS
@@ -85,6 +87,87 @@ pub fn show_field_error(ui: &mut egui::Ui, form_errors: &HashMap<String, String>
     }
 }
 
+// ============================================================================
+// PASSWORD FIELD
+// ============================================================================
+
+/// What a [`password_field`] did this frame
+pub struct PasswordFieldResponse {
+    /// The text edit's response, for `changed()` and focus
+    pub response: egui::Response,
+    /// Enter was pressed in the field; run the form's primary action
+    pub submitted: bool,
+}
+
+/// Masked password input with a reveal toggle, a Caps Lock warning and,
+/// with `strength`, a strength bar underneath. `width` covers the input and
+/// the toggle; `revealed` is the toggle state, kept by the caller.
+pub fn password_field(
+    ui: &mut egui::Ui,
+    text: &mut String,
+    revealed: &mut bool,
+    hint: &str,
+    width: f32,
+    strength: bool,
+) -> PasswordFieldResponse {
+    let height = 24.0;
+    let toggle_width = 32.0;
+    let response = ui.vertical(|ui| {
+        let response = ui.horizontal(|ui| {
+            let field_width = width - toggle_width - ui.spacing().item_spacing.x;
+            let response = ui.add_sized(
+                [field_width, height],
+                egui::TextEdit::singleline(text).password(!*revealed).hint_text(hint),
+            );
+            let (eye, tip) = if *revealed { ("🙈", "Hide password") } else { ("👁", "Show password") };
+            if ui.add_sized([toggle_width, height], egui::Button::new(eye)).on_hover_text(tip).clicked() {
+                *revealed = !*revealed;
+            }
+            if caps_lock_on(ui, &response) {
+                ui.label(egui::RichText::new("⇪").color(egui::Color32::from_rgb(251, 191, 36)))
+                    .on_hover_text("Caps Lock is on");
+            }
+            response
+        }).inner;
+        if strength {
+            show_password_strength_indicator(ui, text);
+        }
+        response
+    }).inner;
+    let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+    PasswordFieldResponse { response, submitted }
+}
+
+/// Caps Lock as last inferred from letters typed into a focused password
+/// field. egui doesn't report the lock state, so this guesses from typing.
+fn caps_lock_on(ui: &egui::Ui, field: &egui::Response) -> bool {
+    if !field.has_focus() {
+        return false;
+    }
+    let id = egui::Id::new("password_field_caps_lock");
+    let (typed, shift) = ui.input(|i| {
+        let typed: String = i.events.iter()
+            .filter_map(|event| match event {
+                egui::Event::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        (typed, i.modifiers.shift)
+    });
+    if let Some(on) = caps_lock_from_typing(&typed, shift) {
+        ui.ctx().data_mut(|data| data.insert_temp(id, on));
+    }
+    ui.ctx().data(|data| data.get_temp(id)).unwrap_or(false)
+}
+
+/// Whether the last cased letter typed implies Caps Lock: uppercase
+/// without Shift, or lowercase with it. `None` if no cased letter was typed.
+fn caps_lock_from_typing(typed: &str, shift: bool) -> Option<bool> {
+    typed.chars().rev()
+        .find(|c| c.is_uppercase() || c.is_lowercase())
+        .map(|c| c.is_uppercase() != shift)
+}
+
 /// Visual password strength indicator with progress bar and color
 pub fn show_password_strength_indicator(ui: &mut egui::Ui, password: &str) {
     if password.is_empty() {
@@ -93,7 +176,6 @@ pub fn show_password_strength_indicator(ui: &mut egui::Ui, password: &str) {
     
     // Calculate strength score (0-100)
     let mut score = 0;
-    let mut suggestions = Vec::new();
     
     // Length scoring
     if password.len() >= 16 {
@@ -102,8 +184,6 @@ pub fn show_password_strength_indicator(ui: &mut egui::Ui, password: &str) {
         score += 25;
     } else if password.len() >= 8 {
         score += 15;
-    } else {
-        suggestions.push("Use at least 8 characters");
     }
     
     // Character variety
@@ -112,10 +192,10 @@ pub fn show_password_strength_indicator(ui: &mut egui::Ui, password: &str) {
     let has_numbers = password.chars().any(|c| c.is_numeric());
     let has_symbols = password.chars().any(|c| !c.is_alphanumeric());
     
-    if has_lowercase { score += 15; } else { suggestions.push("Add lowercase letters"); }
-    if has_uppercase { score += 15; } else { suggestions.push("Add uppercase letters"); }
-    if has_numbers { score += 15; } else { suggestions.push("Add numbers"); }
-    if has_symbols { score += 15; } else { suggestions.push("Add symbols (!@#$%^&*)"); }
+    if has_lowercase { score += 15; }
+    if has_uppercase { score += 15; }
+    if has_numbers { score += 15; }
+    if has_symbols { score += 15; }
     
     // Uniqueness bonus
     let unique_chars: std::collections::HashSet<char> = password.chars().collect();
@@ -173,65 +253,56 @@ pub fn show_password_strength_indicator(ui: &mut egui::Ui, password: &str) {
         ui.colored_label(color, format!("{} ({}%)", label, score));
     });
     
-    // Show suggestions in a collapsible section
-    if !suggestions.is_empty() {
-        ui.collapsing("💡 Suggestions", |ui| {
-            for suggestion in suggestions {
-                ui.horizontal(|ui| {
-                    ui.label("•");
-                    ui.label(suggestion);
-                });
-            }
-        });
+    // Suggestions from the strength analysis, as many as
+    // `ui.suggestion_detail` asks for: one inline, several collapsed
+    let (_, suggestions) = analyze_password_strength(password);
+    let detail = crate::config::get_config().ui.suggestion_detail;
+    match detail.select(&suggestions).as_slice() {
+        [] => {}
+        [suggestion] if detail == SuggestionDetail::Brief => {
+            ui.label(format!("💡 {}", suggestion));
+        }
+        shown => {
+            ui.collapsing("💡 Suggestions", |ui| {
+                for suggestion in shown {
+                    ui.horizontal(|ui| {
+                        ui.label("•");
+                        ui.label(suggestion.message());
+                    });
+                }
+            });
+        }
     }
 }
 
-/// Analyze password and return strength description and suggestions
-pub fn analyze_password_strength(password: &str) -> (String, Vec<String>) {
-    if password.is_empty() {
-        return (String::new(), Vec::new());
-    }
-
-    let mut score = 0;
-    let mut suggestions = Vec::new();
-
-    // Length check
-    if password.len() >= 12 {
-        score += 25;
-    } else if password.len() >= 8 {
-        score += 15;
-    } else {
-        suggestions.push("Use at least 8 characters".to_string());
-    }
-
-    // Character variety checks
-    let has_lowercase = password.chars().any(|c| c.is_lowercase());
-    let has_uppercase = password.chars().any(|c| c.is_uppercase());
-    let has_numbers = password.chars().any(|c| c.is_numeric());
-    let has_symbols = password.chars().any(|c| !c.is_alphanumeric());
-
-    if has_lowercase { score += 15; } else { suggestions.push("Add lowercase letters".to_string()); }
-    if has_uppercase { score += 15; } else { suggestions.push("Add uppercase letters".to_string()); }
-    if has_numbers { score += 15; } else { suggestions.push("Add numbers".to_string()); }
-    if has_symbols { score += 15; } else { suggestions.push("Add symbols (!@#$%^&*)".to_string()); }
-
-    // Repetition check
-    let unique_chars: std::collections::HashSet<char> = password.chars().collect();
-    if unique_chars.len() as f32 / password.len() as f32 > 0.7 {
-        score += 15;
-    } else {
-        suggestions.push("Avoid repeating characters".to_string());
-    }
-
-    // Set strength description
-    let strength = match score {
-        0..=30 => format!("Weak ({}%)", score),
-        31..=60 => format!("Fair ({}%)", score),
-        61..=80 => format!("Good ({}%)", score),
-        _ => format!("Strong ({}%)", score),
-    };
-
-    (strength, suggestions)
+/// A revealed password spelled out for typing it by hand: groups of four
+/// in a large monospace font, look-alike characters in `accent` and named
+/// underneath
+pub fn spelled_password(ui: &mut egui::Ui, password: &str, color: egui::Color32, accent: egui::Color32) -> egui::Response {
+    ui.vertical(|ui| {
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing.x = 14.0;
+            for group in spell_groups(password) {
+                let mut job = egui::text::LayoutJob::default();
+                for c in group.chars() {
+                    let format = egui::TextFormat {
+                        font_id: egui::FontId::monospace(20.0),
+                        color: if glyph_name(c).is_some() { accent } else { color },
+                        ..Default::default()
+                    };
+                    job.append(&if c == ' ' { '␣' } else { c }.to_string(), 0.0, format);
+                }
+                ui.label(job);
+            }
+        });
+        let legend: Vec<String> = spell_legend(password)
+            .into_iter()
+            .map(|(c, name)| format!("{} = {}", if c == ' ' { '␣' } else { c }, name))
+            .collect();
+        if !legend.is_empty() {
+            ui.label(egui::RichText::new(legend.join("   ")).size(11.0).color(accent));
+        }
+    }).response
 }
 
 // ============================================================================
@@ -270,6 +341,18 @@ pub fn calculate_password_score(password: &str) -> u32 {
     score.min(100)
 }
 
+/// Score for a strength recorded without the password (high-security
+/// entries), in the middle of the band `strength_color` gives it
+pub fn strength_score(strength: &PasswordStrength) -> u32 {
+    match strength {
+        PasswordStrength::VeryWeak => 15,
+        PasswordStrength::Weak => 40,
+        PasswordStrength::Fair => 60,
+        PasswordStrength::Good => 80,
+        PasswordStrength::Strong => 95,
+    }
+}
+
 /// Get strength color based on score
 pub fn strength_color(score: u32) -> egui::Color32 {
     match score {
@@ -303,6 +386,92 @@ pub fn paint_strength_dots(ui: &mut egui::Ui, score: u32) {
     });
 }
 
+/// Line chart of health scores (0-100) over time
+pub fn paint_score_trend(ui: &mut egui::Ui, points: &[(chrono::DateTime<chrono::Utc>, u8)]) {
+    let desired = egui::vec2(ui.available_width().min(480.0), 90.0);
+    let (rect, _) = ui.allocate_exact_size(desired, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let grid_color = egui::Color32::from_rgb(60, 60, 65);
+    
+    painter.rect_stroke(rect, egui::Rounding::same(6.0), egui::Stroke::new(1.0, grid_color));
+    for score in [25.0, 50.0, 75.0] {
+        let y = rect.bottom() - rect.height() * score / 100.0;
+        painter.line_segment(
+            [egui::pos2(rect.left(), y), egui::pos2(rect.right(), y)],
+            egui::Stroke::new(0.5, grid_color),
+        );
+    }
+    
+    if points.len() < 2 {
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "Trend appears after analyses on two different days",
+            egui::FontId::proportional(11.0),
+            egui::Color32::from_rgb(156, 163, 175),
+        );
+        return;
+    }
+    
+    let first = points[0].0.timestamp() as f32;
+    let span = (points[points.len() - 1].0.timestamp() as f32 - first).max(1.0);
+    let inner = rect.shrink(6.0);
+    let positions: Vec<egui::Pos2> = points
+        .iter()
+        .map(|(time, score)| {
+            let x = inner.left() + inner.width() * (time.timestamp() as f32 - first) / span;
+            let y = inner.bottom() - inner.height() * *score as f32 / 100.0;
+            egui::pos2(x, y)
+        })
+        .collect();
+    
+    let line_color = strength_color(points[points.len() - 1].1 as u32);
+    painter.add(egui::Shape::line(positions.clone(), egui::Stroke::new(2.0, line_color)));
+    for position in positions {
+        painter.circle_filled(position, 2.5, line_color);
+    }
+}
+
+/// Entry count and weak-password badges for a vault list row, from the
+/// vault's plaintext stats sidecar
+pub fn vault_stats_badges(ui: &mut egui::Ui, stats: &crate::vault::VaultStats) {
+    let saved = stats.saved_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M");
+    if stats.weak > 0 {
+        ui.label(
+            egui::RichText::new(format!("⚠ {}", stats.weak))
+                .size(11.0)
+                .color(egui::Color32::from_rgb(251, 191, 36))
+        ).on_hover_text(format!("{} weak password(s)", stats.weak));
+    }
+    ui.label(
+        egui::RichText::new(format!("🔑 {}", stats.entries))
+            .size(11.0)
+            .color(egui::Color32::from_rgb(156, 163, 175))
+    ).on_hover_text(format!("{} entries, last saved {}", stats.entries, saved));
+}
+
+/// Year / month / day pickers; the day is clamped to the chosen month
+pub fn date_picker(ui: &mut egui::Ui, date: &mut chrono::NaiveDate) -> bool {
+    use chrono::Datelike;
+
+    let (mut year, mut month, mut day) = (date.year(), date.month(), date.day());
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        changed |= ui.add(egui::DragValue::new(&mut year).range(1970..=9999)).changed();
+        ui.label("-");
+        changed |= ui.add(egui::DragValue::new(&mut month).range(1..=12).custom_formatter(|n, _| format!("{:02}", n))).changed();
+        ui.label("-");
+        changed |= ui.add(egui::DragValue::new(&mut day).range(1..=31).custom_formatter(|n, _| format!("{:02}", n))).changed();
+    });
+    if changed {
+        let picked = (1..=day).rev().find_map(|day| chrono::NaiveDate::from_ymd_opt(year, month, day));
+        if let Some(picked) = picked {
+            *date = picked;
+        }
+    }
+    changed
+}
+
 /// Section header with optional action
 pub fn section_header(ui: &mut egui::Ui, title: &str) {
     ui.add_space(4.0);
@@ -339,6 +508,56 @@ pub fn styled_search_bar(
         }).inner
 }
 
+// ============================================================================
+// SIZE GUARDS
+// ============================================================================
+
+/// `text` cut to `max_chars` with an ellipsis, borrowed when it fits
+pub fn clipped(text: &str, max_chars: usize) -> std::borrow::Cow<'_, str> {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]).into(),
+        None => text.into(),
+    }
+}
+
+/// A text edit built by `edit`, capped at `limit` characters with a
+/// warning once the cap is reached. Text already past the cap (from an
+/// import or the command line) isn't laid out at all: a notice stands in
+/// for the field and the text is saved as it is.
+pub fn limited_text_edit(
+    ui: &mut egui::Ui,
+    text: &mut String,
+    limit: usize,
+    edit: impl FnOnce(&mut String) -> egui::TextEdit<'_>,
+) -> egui::Response {
+    let warning = egui::Color32::from_rgb(251, 191, 36);
+    let chars = text.chars().count();
+    if chars > limit {
+        return ui.colored_label(warning, format!("⚠ {} characters, too long to edit here; kept as it is", chars));
+    }
+    let response = ui.add(edit(text).char_limit(limit));
+    if text.chars().count() >= limit {
+        ui.colored_label(warning, format!("⚠ Limit of {} characters reached", limit));
+    }
+    response
+}
+
+/// A [`FullText`](super::types::FullText) of `text` in a scroll area,
+/// laying out only the rows in view
+pub fn full_text(ui: &mut egui::Ui, id_salt: impl std::hash::Hash, text: &str, rows: &[std::ops::Range<usize>], color: egui::Color32) {
+    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+    egui::ScrollArea::both()
+        .id_salt(id_salt)
+        .max_height(super::types::FULL_TEXT_HEIGHT)
+        .auto_shrink([false, true])
+        .show_rows(ui, row_height, rows.len(), |ui, visible| {
+            for row in &rows[visible] {
+                let line = text.get(row.clone()).unwrap_or_default();
+                ui.add(egui::Label::new(egui::RichText::new(line).monospace().color(color)).extend());
+            }
+        });
+}
+
 /// Empty state widget with icon and message
 pub fn empty_state(ui: &mut egui::Ui, icon: &str, title: &str, subtitle: &str) {
     ui.vertical_centered(|ui| {
@@ -350,3 +569,26 @@ pub fn empty_state(ui: &mut egui::Ui, icon: &str, title: &str, subtitle: &str) {
         ui.label(egui::RichText::new(subtitle).size(14.0).color(egui::Color32::from_rgb(156, 163, 175)));
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_caps_lock_from_typing() {
+        assert_eq!(caps_lock_from_typing("A", false), Some(true));
+        assert_eq!(caps_lock_from_typing("a", true), Some(true));
+        assert_eq!(caps_lock_from_typing("a", false), Some(false));
+        assert_eq!(caps_lock_from_typing("A", true), Some(false));
+        // Digits and symbols say nothing; the last letter decides
+        assert_eq!(caps_lock_from_typing("7!", false), None);
+        assert_eq!(caps_lock_from_typing("aB1", false), Some(true));
+    }
+
+    #[test]
+    fn test_clipped_cuts_on_characters() {
+        assert_eq!(clipped("short", 5), "short");
+        assert_eq!(clipped("émigré", 3), "émi…");
+        assert!(matches!(clipped("short", 10), std::borrow::Cow::Borrowed(_)));
+    }
+}
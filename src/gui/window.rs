@@ -0,0 +1,97 @@
+//! Window Module
+//!
+//! The bundled application icon, and the window title and icon that
+//! follow the lock state.
+
+use std::sync::Arc;
+use eframe::egui;
+
+/// Closed padlock, the icon while every vault is locked and the one
+/// bundled with the executable (`assets/icon.ico` holds it in several sizes)
+const ICON_LOCKED_PNG: &[u8] = include_bytes!("../../assets/icon-256.png");
+
+/// Open padlock, shown while a vault is unlocked
+const ICON_UNLOCKED_PNG: &[u8] = include_bytes!("../../assets/icon-unlocked-256.png");
+
+/// Application id, matching `assets/passman.desktop` so Linux desktops
+/// find the icon for the window
+pub const APP_ID: &str = "passman";
+
+/// The window icon for the lock state
+pub fn icon(locked: bool) -> egui::IconData {
+    let png = if locked { ICON_LOCKED_PNG } else { ICON_UNLOCKED_PNG };
+    eframe::icon_data::from_png_bytes(png).expect("bundled icon is a valid PNG")
+}
+
+/// "Passman — locked", or the active vault's file name with an asterisk
+/// while it has unsaved changes
+pub fn title(active_vault: Option<(&str, bool)>) -> String {
+    match active_vault {
+        Some((vault_file, dirty)) => {
+            let name = std::path::Path::new(vault_file)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| vault_file.to_string());
+            format!("Passman — {}{}", name, if dirty { "*" } else { "" })
+        }
+        None => "Passman — locked".to_string(),
+    }
+}
+
+/// The title and icon last sent to the window, so they are only sent
+/// again when they change
+#[derive(Default)]
+pub struct WindowState {
+    title: String,
+    locked: Option<bool>,
+}
+
+impl WindowState {
+    /// Bring the window title and icon in line with the app state. On
+    /// Windows the icon is also the taskbar button's, so it shows whether
+    /// a vault is open without switching to the window.
+    pub fn update(&mut self, ctx: &egui::Context, title: String, locked: bool) {
+        if self.title != title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.title = title;
+        }
+        if self.locked != Some(locked) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Icon(Some(Arc::new(icon(locked)))));
+            self.locked = Some(locked);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_title_follows_lock_and_dirty_state() {
+        assert_eq!(title(None), "Passman — locked");
+        assert_eq!(title(Some(("/home/ann/vaults/work.dat", false))), "Passman — work.dat");
+        assert_eq!(title(Some(("work.dat", true))), "Passman — work.dat*");
+    }
+
+    #[test]
+    fn test_bundled_icons_decode() {
+        for locked in [true, false] {
+            let icon = icon(locked);
+            assert_eq!((icon.width, icon.height), (256, 256));
+        }
+
+        // The executable's icon carries the sizes the OS shell asks for
+        let ico = include_bytes!("../../assets/icon.ico");
+        assert_eq!(&ico[..4], &[0, 0, 1, 0]);
+        let count = u16::from_le_bytes([ico[4], ico[5]]) as usize;
+        let sizes: Vec<u32> = (0..count)
+            .map(|i| match ico[6 + 16 * i] {
+                0 => 256,
+                size => size as u32,
+            })
+            .collect();
+        for size in [16, 32, 48, 256] {
+            assert!(sizes.contains(&size), "icon.ico lacks {}x{}", size, size);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Ctrl+C Handling
+//!
+//! Interrupting passman (at a password prompt, halfway through an import,
+//! while `passman open` waits to clear the clipboard) exits with status 130
+//! after doing what the interrupted code would have done on its way out:
+//!
+//! - the terminal mode captured at startup is restored, so echo is back on
+//!   after a prompt that turned it off and the shell isn't left in raw mode
+//! - the clipboard is cleared if it still holds something passman copied
+//! - temp files of unfinished vault writes are deleted; the vault itself is
+//!   only replaced by the final rename, so it keeps its previous contents
+//!
+//! Secrets on the interrupted thread's stack can't be reached from here.
+//! They are never written anywhere and go away with the process, which
+//! exits right after cleanup instead of unwinding.
+//!
+//! Manual check for the echo case (needs a real terminal):
+//! 1. `stty -a | grep -o '[-]*echo '` shows `echo`
+//! 2. `passman list`, press Ctrl+C at the master password prompt
+//! 3. `echo $?` prints 130, and typed characters are visible again
+//! 4. Repeat at `passman add x`'s entry password prompt, and with
+//!    `kill -INT` on a running `passman tui` (which reads Ctrl+C as a key)
+
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, Sender};
+use crate::secure_clipboard;
+use crate::vault::VaultManager;
+
+/// Exit status for a process ended by SIGINT (128 + 2)
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Where the next Ctrl+C goes instead of the cleanup below, see [`redirect`]
+static REDIRECT: Mutex<Option<Sender<()>>> = Mutex::new(None);
+
+/// Remember the terminal mode and install the handler. Call once, before
+/// anything prompts or changes the terminal.
+pub fn install() {
+    terminal::capture();
+    if let Err(e) = ctrlc::set_handler(on_interrupt) {
+        log::warn!("Failed to install Ctrl+C handler: {}", e);
+    }
+}
+
+/// Deliver the next Ctrl+C to the returned receiver instead of exiting, for
+/// a command that finishes its own cleanup (the clipboard countdown) and
+/// then exits with [`INTERRUPTED_EXIT_CODE`]. A second Ctrl+C, or one after
+/// the receiver is dropped, exits as usual.
+pub fn redirect() -> Receiver<()> {
+    let (sender, receiver) = mpsc::channel();
+    *REDIRECT.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(sender);
+    receiver
+}
+
+fn on_interrupt() {
+    let redirect = REDIRECT.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+    if redirect.is_some_and(|sender| sender.send(()).is_ok()) {
+        return;
+    }
+    terminal::restore();
+    match secure_clipboard::clear_if_holding_copy() {
+        Ok(true) => log::debug!("Clipboard cleared on interrupt"),
+        Ok(false) => {}
+        Err(e) => log::warn!("Failed to clear clipboard on interrupt: {}", e),
+    }
+    let removed = VaultManager::remove_pending_temp_files();
+    if removed > 0 {
+        log::debug!("Removed {} temp file(s) on interrupt", removed);
+    }
+    // The prompt line was left unfinished
+    eprintln!();
+    std::process::exit(INTERRUPTED_EXIT_CODE);
+}
+
+#[cfg(unix)]
+mod terminal {
+    use std::sync::OnceLock;
+
+    static ORIGINAL: OnceLock<libc::termios> = OnceLock::new();
+
+    pub fn capture() {
+        // SAFETY: tcgetattr only writes the termios struct it is given
+        unsafe {
+            if libc::isatty(libc::STDIN_FILENO) != 1 {
+                return;
+            }
+            let mut termios: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) == 0 {
+                let _ = ORIGINAL.set(termios);
+            }
+        }
+    }
+
+    pub fn restore() {
+        if let Some(termios) = ORIGINAL.get() {
+            // SAFETY: restores a mode previously read from the same descriptor
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, termios);
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+mod terminal {
+    use std::sync::OnceLock;
+    use windows::Win32::System::Console::{GetConsoleMode, GetStdHandle, SetConsoleMode, CONSOLE_MODE, STD_INPUT_HANDLE};
+
+    static ORIGINAL: OnceLock<u32> = OnceLock::new();
+
+    pub fn capture() {
+        // SAFETY: reads the mode of this process's own input handle
+        unsafe {
+            let Ok(handle) = GetStdHandle(STD_INPUT_HANDLE) else { return };
+            let mut mode = CONSOLE_MODE::default();
+            if GetConsoleMode(handle, &mut mode).is_ok() {
+                let _ = ORIGINAL.set(mode.0);
+            }
+        }
+    }
+
+    pub fn restore() {
+        if let Some(mode) = ORIGINAL.get() {
+            // SAFETY: restores a mode previously read from the same handle
+            unsafe {
+                if let Ok(handle) = GetStdHandle(STD_INPUT_HANDLE) {
+                    let _ = SetConsoleMode(handle, CONSOLE_MODE(*mode));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod terminal {
+    pub fn capture() {}
+    pub fn restore() {}
+}
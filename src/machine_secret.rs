@@ -0,0 +1,66 @@
+//! Machine Secret
+//!
+//! A random secret kept in the OS keyring (Keychain, Credential Manager,
+//! Secret Service), generated on first use. It seals what this machine
+//! remembers about a vault between sessions, such as the lock screen
+//! summary. Builds without the `keyring` feature, and machines where the
+//! keyring can't be reached, have no secret, and those features stay off.
+
+use std::sync::OnceLock;
+use zeroize::Zeroizing;
+
+#[cfg(feature = "keyring")]
+const SERVICE: &str = "passman";
+#[cfg(feature = "keyring")]
+const ACCOUNT: &str = "machine-secret";
+
+static SECRET: OnceLock<Option<Zeroizing<Vec<u8>>>> = OnceLock::new();
+
+/// The machine secret, looked up once per run
+pub fn get() -> Option<&'static [u8]> {
+    SECRET.get_or_init(load).as_ref().map(|secret| secret.as_slice())
+}
+
+#[cfg(feature = "keyring")]
+fn load() -> Option<Zeroizing<Vec<u8>>> {
+    let entry = match keyring::Entry::new(SERVICE, ACCOUNT) {
+        Ok(entry) => entry,
+        Err(e) => {
+            log::info!("OS keyring unavailable: {}", e);
+            return None;
+        }
+    };
+    match entry.get_password() {
+        Ok(encoded) => {
+            let encoded = Zeroizing::new(encoded);
+            let secret: Option<Vec<u8>> = (0..encoded.len())
+                .step_by(2)
+                .map(|i| encoded.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+                .collect();
+            if secret.is_none() {
+                log::warn!("Ignoring an unreadable machine secret in the OS keyring");
+            }
+            secret.map(Zeroizing::new)
+        }
+        Err(keyring::Error::NoEntry) => {
+            let secret = passman_core::lock_summary::new_machine_secret();
+            let encoded = Zeroizing::new(secret.iter().map(|byte| format!("{:02x}", byte)).collect::<String>());
+            match entry.set_password(&encoded) {
+                Ok(()) => Some(secret),
+                Err(e) => {
+                    log::info!("Could not store the machine secret in the OS keyring: {}", e);
+                    None
+                }
+            }
+        }
+        Err(e) => {
+            log::info!("OS keyring unavailable: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+fn load() -> Option<Zeroizing<Vec<u8>>> {
+    None
+}
@@ -2,34 +2,32 @@
 // Instead, we detach from console when running GUI mode on Windows
 
 mod cli;
-mod crypto;
-mod vault;
-mod model;
 mod utils;
 mod gui;
-mod health;
-mod import_export;
 mod secure_clipboard;
 mod session;
-mod error;
-mod config;
 mod logging;
-mod core;
-mod secure_types;
+mod tui;
+mod rpc;
+mod interrupt;
+mod machine_secret;
+
+// Core modules live in the passman-core crate; importing them here keeps
+// `crate::vault`-style paths working throughout the frontends
+use passman_core::{bulk, changes, config, copy_sequence, crypto, diff, git_credential, health, import_export, lock_summary, model, net, paste, sample, sealed, search, secure_types, share, status, sync, update, url_match, vault};
 
 use eframe::egui;
-use cli::{Cli, Commands, TransferCommands, ConfigCommands};
-use model::Entry;
+use cli::{Cli, Commands, AddArgs, ClientArgs, ClientCommands, GitCredentialCommands, TransferCommands, ConfigCommands, VaultCommands, ShareArgs, ShareCommands, SyncCommands, TemplateCommands, BackupArgs, BackupCommands, ChangesArgs, ChangesCommands};
+use copy_sequence::CopyStep;
+use crypto::CipherKind;
+use model::{Entry, EntryTemplate, GenerationPrefs};
+use sealed::{OpenedEntries, SealedSecrets};
 use vault::VaultManager;
 use utils::*;
 use clap::Parser;
 use std::error::Error;
 use zeroize::Zeroizing;
-
-// Re-export commonly used types
-pub use error::{PassmanError, PassmanResult};
-pub use config::Config;
-pub use core::{PassmanCore, EntryBuilder};
+use passman_core::PassmanCore;
 
 
 fn main() -> Result<(), eframe::Error> {
@@ -38,15 +36,23 @@ fn main() -> Result<(), eframe::Error> {
         eprintln!("Warning: Failed to initialize logging: {}", e);
     }
     log::info!("Passman starting...");
+    interrupt::install();
     
-    // Check if CLI arguments are provided
-    let args: Vec<String> = std::env::args().collect();
-    
-    if args.len() > 1 {
-        // Run CLI mode - console stays attached for I/O
-        log::debug!("Running in CLI mode");
-        run_cli();
-        return Ok(());
+    // Check if CLI arguments are provided; `passman --demo` is the GUI on
+    // a vault of made-up entries
+    let mut demo = false;
+    if std::env::args_os().len() > 1 {
+        let cli = Cli::parse();
+        demo = cli.demo;
+        if !demo {
+            // Run CLI mode - console stays attached for I/O
+            log::debug!("Running in CLI mode");
+            if let Some(recovery) = config::take_config_recovery() {
+                eprintln!("⚠ {}", recovery.message());
+            }
+            run_cli(cli);
+            return Ok(());
+        }
     }
 
     // GUI mode - detach from console on Windows so no console window appears
@@ -59,41 +65,108 @@ fn main() -> Result<(), eframe::Error> {
     }
 
     // Run GUI mode
+    gui::crash::install_panic_hook();
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([800.0, 600.0])
             .with_min_inner_size([600.0, 400.0])
-            .with_title("Passman - Password Manager")
-            .with_icon(eframe::icon_data::from_png_bytes(&[]).unwrap_or_default()),
+            .with_title(gui::window::title(None))
+            .with_app_id(gui::window::APP_ID)
+            .with_icon(gui::window::icon(true)),
         ..Default::default()
     };
 
     eframe::run_native(
         "Passman",
         options,
-        Box::new(|cc| Ok(Box::new(gui::PassmanApp::new(cc)))),
+        Box::new(move |cc| Ok(Box::new(if demo { gui::PassmanApp::new_demo(cc) } else { gui::PassmanApp::new(cc) }))),
     )
 }
 
-fn run_cli() {
-    let cli = Cli::parse();
-    let vault_file = cli.vault.as_deref();    let result = match cli.command {
-        Commands::Init { description: _ } => handle_init(vault_file),
-        Commands::Add { id, .. } => handle_add(&id, vault_file),
-        Commands::Get { id, copy, show } => handle_get(&id, vault_file, copy, show),
-        Commands::List { search, verbose, .. } => handle_list(vault_file, search.as_deref(), verbose),
-        Commands::Edit { id } => handle_edit(&id, vault_file),
-        Commands::Remove { id, force } => handle_remove(&id, vault_file, force),
-        Commands::Check { password, all } => handle_check(password.as_deref(), all, vault_file),
-        Commands::Vaults => handle_vaults(),
-        Commands::Generate { length, symbols, no_ambiguous, memorable } => {
-            handle_generate(length, symbols, no_ambiguous, memorable)
+fn run_cli(cli: Cli) {
+    if cli.offline {
+        net::force_offline();
+    }
+    if cli.strict_permissions {
+        VaultManager::force_strict_permissions();
+    }
+    if let Some(path) = &cli.password_file {
+        set_password_file(path.into());
+    }
+    let (vault_path, vault_source) = config::resolve_vault_path(cli.vault.as_deref());
+    log::debug!("Using vault {} (from {})", vault_path, vault_source);
+    // Only --demo goes without a subcommand, and that opens the GUI instead
+    let Some(command) = cli.command else {
+        use clap::CommandFactory;
+        Cli::command().error(clap::error::ErrorKind::MissingSubcommand, "a subcommand is required (or --demo on its own)").exit();
+    };
+    let result = match command {
+        Commands::Init { description: _, cipher } => handle_init(cipher.as_deref(), &vault_path),
+        Commands::Add(add_args) => handle_add(add_args, &vault_path),
+        Commands::Get { id, field, .. } if !field.is_empty() => handle_get_fields(&id, &field, &vault_path),
+        Commands::Get { id, links: true, .. } => handle_get_links(&id, &vault_path),
+        Commands::Get { id, copy_next: true, no_wait, .. } => handle_copy_next(&id, &vault_path, no_wait),
+        Commands::Get { id, copy, show, spell, no_wait, .. } => handle_get(&id, &vault_path, copy, show, spell, no_wait, cli.verbose),
+        Commands::List { search, saved, verbose, no_notes, include_trash, archived, output, .. } => {
+            resolve_saved_search(saved.as_deref(), search)
+                .map(|query| match archived {
+                    true => Some(format!("{} {}", search::ARCHIVED_FILTER, query.unwrap_or_default())),
+                    false => query,
+                })
+                .and_then(|search| handle_list(&vault_path, search.as_deref(), verbose, no_notes, include_trash, &output))
+        }
+        Commands::Open { id } => handle_open(&id, &vault_path),
+        Commands::Edit { id, expires, copy_sequence } => handle_edit(&id, expires.as_deref(), &copy_sequence, &vault_path),
+        Commands::Duplicate { id, new_id, generate } => handle_duplicate(&id, new_id.as_deref(), generate, &vault_path),
+        Commands::Rotate { id, same_rules, length } => handle_rotate(&id, same_rules, length, &vault_path),
+        Commands::Replace { field, find, replace, regex, dry_run, yes } => {
+            handle_replace(&field, &find, &replace, regex, dry_run, yes, &vault_path)
+        }
+        Commands::Remove { id, force } => handle_remove(&id, &vault_path, force),
+        Commands::Archive { id, undo } => handle_archive(&id, undo, &vault_path),
+        Commands::Link { id, other_id, remove } => handle_link(&id, &other_id, remove, &vault_path),
+        Commands::Seal { id } => handle_seal(&id, &vault_path),
+        Commands::Unseal { id } => handle_unseal(&id, &vault_path),
+        Commands::Check { expiring: Some(days), .. } => handle_check_expiring(days, &vault_path),
+        Commands::Check { password, all, output, include_trash, breaches, fail_below, .. } => {
+            handle_check(password.as_deref(), all, &output, include_trash, breaches, fail_below, &vault_path)
+        }
+        Commands::Vaults { page } => handle_vaults(&vault_path, vault_source, page),
+        Commands::Vault(vault_cmd) => handle_vault(vault_cmd, &vault_path),
+        Commands::Doctor => handle_doctor(&vault_path),
+        Commands::Generate { length, symbols, no_ambiguous, memorable, pronounceable, syllables, exclude, leading_alpha, max, no_wait } => {
+            let rules = PasswordConfig {
+                include_symbols: symbols,
+                exclude_ambiguous: no_ambiguous,
+                excluded_chars: exclude.unwrap_or_default(),
+                require_leading_alpha: leading_alpha,
+                max_length: max,
+                ..Default::default()
+            };
+            let mode = if memorable {
+                GenerateMode::Memorable
+            } else if pronounceable {
+                GenerateMode::Pronounceable(syllables.unwrap_or(config::get_config().password.pronounceable_syllables))
+            } else {
+                GenerateMode::Random
+            };
+            handle_generate(length, &rules, mode, no_wait)
         },
-        Commands::Transfer(transfer_cmd) => handle_transfer(transfer_cmd, vault_file),
-        Commands::Config(config_cmd) => handle_config(config_cmd),
-        Commands::Backup { output } => handle_backup(vault_file, output.as_deref()),
-        Commands::Health { verbose, issues_only } => handle_health(vault_file, verbose, issues_only),
-        Commands::ChangePassword => handle_change_password(vault_file),
+        Commands::Template(template_cmd) => handle_template(template_cmd),
+        Commands::Share(share_args) => handle_share(share_args, &vault_path),
+        Commands::Sync(sync_cmd) => handle_sync(sync_cmd, &vault_path),
+        Commands::Transfer(transfer_cmd) => handle_transfer(transfer_cmd, &vault_path),
+        Commands::Config(config_cmd) => handle_config(config_cmd, &vault_path, vault_source),
+        Commands::Diff { other, show_secrets, output } => handle_diff(&vault_path, &other, show_secrets, &output),
+        Commands::Changes(changes_args) => handle_changes(changes_args, &vault_path),
+        Commands::Backup(backup_args) => handle_backup(backup_args, &vault_path),
+        Commands::Health { verbose, issues_only } => handle_health(&vault_path, verbose, issues_only),
+        Commands::ChangePassword => handle_change_password(&vault_path),
+        Commands::Tui => tui::run(&vault_path),
+        Commands::Serve { socket, token } => handle_serve(socket.as_deref(), token, &vault_path),
+        Commands::Client(client_args) => handle_client(client_args),
+        Commands::GitCredential { action, socket } => handle_git_credential(action, socket.as_deref(), &vault_path),
+        Commands::Version { check } => handle_version(check),
     };
 
     if let Err(e) = result {
@@ -102,181 +175,631 @@ fn run_cli() {
     }
 }
 
-fn handle_init(vault_file: Option<&str>) -> Result<(), Box<dyn Error>> {
-    if VaultManager::exists(vault_file) {
+/// Prompt for the master password and unlock the vault through the core API.
+/// The password is returned for the save that follows any edit.
+fn open_vault(vault_path: &str) -> Result<(PassmanCore, Zeroizing<String>), Box<dyn Error>> {
+    warn_if_substituted(vault_path);
+    warn_if_readable_by_others(vault_path);
+    let master_password = read_master_password("Enter master password: ")?;
+    let mut core = PassmanCore::with_vault_path(vault_path);
+    core.unlock(&master_password)?;
+    if let Some(strength) = core.master_password_warning(&master_password) {
+        eprintln!("⚠ Your master password is {} — change it with 'passman change-password'", strength.to_string().to_lowercase());
+    }
+    if let Some(risk) = core.sync_folder_warning() {
+        print_sync_folder_warning(&risk);
+    }
+    Ok((core, master_password))
+}
+
+/// Warn on stderr, before the master password is typed, if the vault file
+/// isn't the one last unlocked at this path
+fn warn_if_substituted(vault_path: &str) {
+    if let Some(warning) = VaultManager::identity(Some(vault_path)).ok().and_then(|identity| identity.warning()) {
+        eprintln!("⚠ WARNING: {}", warning);
+    }
+}
+
+/// Warn on stderr, before the master password is typed, if other users
+/// can read the vault file. Unlocking refuses it under strict permissions.
+fn warn_if_readable_by_others(vault_path: &str) {
+    if let Some(loose) = VaultManager::loose_permissions(Some(vault_path)) {
+        eprintln!("⚠ {}", loose.message());
+        eprintln!("  Fix: {}", loose.remedy());
+    }
+}
+
+/// Explain the risk of keeping a vault in a cloud sync folder, on stderr
+fn print_sync_folder_warning(risk: &vault::SyncFolderRisk) {
+    eprintln!("⚠ {}", risk.message());
+    for copy in &risk.conflicted_copies {
+        eprintln!("    {}", copy);
+    }
+    if !risk.conflicted_copies.is_empty() {
+        eprintln!("  Merge them into the vault: 'passman vault find-conflicts'");
+    }
+    eprintln!("  Safer: keep the vault outside the synced folder and share it with 'passman sync export <dir>'.");
+    if !config::get_config().backup.auto_backup {
+        eprintln!("  Automatic backups are off: 'passman config set auto_backup true'");
+    }
+    eprintln!("  Don't warn about this vault again: 'passman config set sync_folder_warning false'");
+}
+
+/// Print a typed password's strength and any suggestions for improving it
+fn print_strength(core: &PassmanCore, password: &str) {
+    let (strength, suggestions) = core.analyze_password(password);
+    println!("Password strength: {}", strength);
+    print_suggestions("Suggestions:", &suggestions);
+}
+
+/// List strength suggestions under `heading`, as many as `ui.suggestion_detail` asks for
+fn print_suggestions(heading: &str, suggestions: &[StrengthSuggestion]) {
+    let shown = config::get_config().ui.suggestion_detail.select(suggestions);
+    if !shown.is_empty() {
+        println!("{}", heading);
+        for suggestion in shown {
+            println!("  • {}", suggestion);
+        }
+    }
+}
+
+fn handle_init(cipher: Option<&str>, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    let mut core = PassmanCore::with_vault_path(vault_path);
+    if core.vault_exists() {
         return Err("Vault already exists! Remove vault file to reset.".into());
     }
+    let cipher: CipherKind = match cipher {
+        Some(name) => name.parse()?,
+        None => core.config().security.cipher,
+    };
 
-    let master_password = read_password_secure("Create a master password: ")?;
-    let confirm_password = read_password_secure("Confirm master password: ")?;
+    let master_password = read_master_password("Create a master password: ")?;
+    let confirm_password = read_master_password("Confirm master password: ")?;
 
     if master_password.as_str() != confirm_password.as_str() {
         return Err("Passwords do not match!".into());
     }
 
-    if master_password.len() < 8 {
-        return Err("Master password must be at least 8 characters long!".into());
+    core.init_vault_with_cipher(&master_password, cipher)?;
+    println!("✓ Vault initialized successfully! ({})", cipher);
+    if let Some(risk) = core.sync_folder_warning() {
+        print_sync_folder_warning(&risk);
     }
-
-    VaultManager::init(&master_password, vault_file)?;
-    println!("✓ Vault initialized successfully!");
     Ok(())
 }
 
-fn handle_add(id: &str, vault_file: Option<&str>) -> Result<(), Box<dyn Error>> {
-    let master_password = read_password_secure("Enter master password: ")?;
-    let mut vault = VaultManager::load(&master_password, vault_file)?;
+fn handle_add(args: AddArgs, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    if args.batch_stdin {
+        return handle_add_batch(args.on_conflict.as_deref().unwrap_or("skip"), args.strict, vault_path);
+    }
+    // clap waives `requires = "batch_stdin"` once an ID is given, since the
+    // two conflict, so the batch-only flags are checked here
+    if args.on_conflict.is_some() || args.strict {
+        use clap::CommandFactory;
+        let flag = if args.strict { "--strict" } else { "--on-conflict" };
+        Cli::command().error(clap::error::ErrorKind::MissingRequiredArgument, format!("{} needs --batch-stdin", flag)).exit();
+    }
+    let expires_at = args.expires.as_deref().map(parse_expiry_date).transpose()?;
+    let copy_sequence = args.copy_sequence.iter().map(|spec| CopyStep::parse(spec)).collect::<Result<Vec<_>, _>>()?;
+    let template = match args.template.as_deref() {
+        Some(name) => Some(
+            config::get_config().templates.get(name).cloned()
+                .ok_or_else(|| format!("Template '{}' not found", name))?
+        ),
+        None => None,
+    };
+    
+    let (mut core, _) = open_vault(vault_path)?;
 
-    if vault.get_entry(id).is_some() {
+    let id = match args.id {
+        Some(id) => id,
+        None => propose_entry_id(&core, args.url.as_deref(), args.username.as_deref())?,
+    };
+    let id = id.as_str();
+    if core.get_entry(id).is_some() {
         return Err(format!("Entry '{}' already exists!", id).into());
     }
 
-    println!("Adding new entry for '{}'", id);
-    let username = read_line("Username: ")?;
+    // With --username only a password that was neither given nor generated is prompted for
+    let interactive = args.username.is_none();
+    let username = match args.username {
+        Some(username) => username,
+        None => {
+            println!("Adding new entry for '{}'", id);
+            match template.as_ref().filter(|t| !t.username.is_empty()) {
+                Some(template) => {
+                    let default = EntryTemplate::fill(&template.username, id);
+                    let input = read_line_optional(&format!("Username [{}]: ", default))?;
+                    if input.is_empty() { default } else { input }
+                }
+                None => read_line("Username: ")?,
+            }
+        }
+    };
     
-    let password_choice = read_line_optional("Generate password? (y/N): ")?;
-    let password = if password_choice.to_lowercase() == "y" || password_choice.to_lowercase() == "yes" {
-        let generated = generate_password(16);
+    let generation = template.as_ref().and_then(|t| t.generation.clone());
+    let generate = args.generate || (interactive && args.password.is_none() && {
+        let password_choice = read_line_optional("Generate password? (y/N): ")?;
+        password_choice.to_lowercase() == "y" || password_choice.to_lowercase() == "yes"
+    });
+    // Rules of a generated password, kept with the entry for the next rotation
+    let mut generated_with = None;
+    let password = if let Some(password) = args.password {
+        password
+    } else if generate {
+        let prefs = generation.unwrap_or_else(|| GenerationPrefs::random(args.length, PasswordConfig::default()));
+        let generated = prefs.generate()?;
+        generated_with = Some(prefs);
         println!("Generated password: {}", generated);
-        let (strength, _) = analyze_password_strength(&generated);
+        let (strength, _) = core.analyze_password(&generated);
         println!("Password strength: {}", strength);
         generated
     } else {
         let pwd = read_password_secure("Password: ")?;
-        let (strength, suggestions) = analyze_password_strength(&pwd);
-        println!("Password strength: {}", strength);
-        if !suggestions.is_empty() {
-            println!("Suggestions:");
-            for suggestion in suggestions {
-                println!("  • {}", suggestion);
-            }
-        }
+        print_strength(&core, &pwd);
         pwd.to_string()
     };
 
-    let note_input = read_line_optional("Note (optional): ")?;
-    let note = if note_input.is_empty() { None } else { Some(note_input) };
+    let note = match args.note {
+        Some(note) => Some(note),
+        None if interactive => Some(read_line_optional("Note (optional): ")?),
+        None => None,
+    }.filter(|note| !note.is_empty());
 
-    let entry = Entry::new(username, password, note);
-    vault.add_entry(id.to_string(), entry);
-
-    VaultManager::save(&vault, &master_password, vault_file)?;
+    let mut entry = core.create_entry(username, password, note);
+    entry.generation = generated_with;
+    entry.url = args.url.filter(|url| !url.trim().is_empty());
+    entry.tags = args.tags.map(|tags| tags.split(',').map(|tag| tag.trim().to_string()).filter(|tag| !tag.is_empty()).collect())
+        .unwrap_or_default();
+    if let Some(template) = &template {
+        template.apply(id, &mut entry);
+    }
+    entry.expires_at = expires_at;
+    entry.copy_sequence = copy_sequence;
+    core.add_entry(id, entry)?;
+    core.save()?;
     println!("✓ Entry '{}' added successfully!", id);
     Ok(())
 }
 
-fn handle_get(id: &str, vault_file: Option<&str>, copy: bool, show: bool) -> Result<(), Box<dyn Error>> {
-    let master_password = read_password_secure("Enter master password: ")?;
-    let vault = VaultManager::load(&master_password, vault_file)?;
+/// Add the entries of a JSON stream on stdin in one batch, saved once.
+/// Stdin carries the entries, so the master password is never prompted for.
+fn handle_add_batch(on_conflict: &str, strict: bool, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    use passman_core::batch::OpOutcome;
 
-    match vault.get_entry(id) {
-        Some(entry) => {
-            println!("\n--- {} ---", id);
-            println!("Username: {}", entry.username);
-            
-            if show {
-                println!("Password: {}", entry.password_str());
-            } else {
-                println!("Password: {}", "*".repeat(entry.password_str().len().min(16)));
+    let policy: import_export::ConflictPolicy = on_conflict.parse()?;
+    if provided_master_password()?.is_none() {
+        return Err(format!("--batch-stdin reads entries from stdin; give the master password with --password-file or {}", PASSWORD_ENV).into());
+    }
+
+    let (rows, errors) = import_export::parse_entry_stream(std::io::stdin().lock())?;
+    for error in &errors {
+        eprintln!("✗ {}", error);
+    }
+    if strict && !errors.is_empty() {
+        return Err(format!("{} malformed line(s), nothing was added", errors.len()).into());
+    }
+    let (lines, rows): (Vec<usize>, Vec<_>) = rows.into_iter().map(|(line, id, entry)| (line, (id, entry))).unzip();
+
+    let (mut core, _) = open_vault(vault_path)?;
+    let result = core.apply_batch(import_export::ImportExportManager::import_ops(rows, policy))?;
+    let (mut added, mut replaced, mut skipped) = (0, 0, 0);
+    for (line, (id, outcome)) in lines.iter().zip(&result.outcomes) {
+        match outcome {
+            OpOutcome::Applied => {
+                added += 1;
+                println!("line {}: ✓ added '{}'", line, id);
             }
-            
-            if let Some(note) = &entry.note {
-                println!("Note: {}", note);
+            OpOutcome::Replaced => {
+                replaced += 1;
+                println!("line {}: ✓ replaced '{}'", line, id);
             }
-            
-            if copy {
-                copy_to_clipboard(entry.password_str())?;
-                println!("✓ Password copied to clipboard!");
-            } else if !show {
-                let copy_choice = read_line_optional("\nCopy password to clipboard? (y/N): ")?;
-                if copy_choice.to_lowercase() == "y" || copy_choice.to_lowercase() == "yes" {
-                    copy_to_clipboard(entry.password_str())?;
-                    println!("✓ Password copied to clipboard!");
-                }
+            OpOutcome::Renamed { to } => {
+                added += 1;
+                println!("line {}: ✓ '{}' exists, added as '{}'", line, id, to);
+            }
+            OpOutcome::Skipped => {
+                skipped += 1;
+                println!("line {}: ⚠ skipped '{}', it already exists", line, id);
             }
+            OpOutcome::Failed { reason } => println!("line {}: ✗ {}", line, reason),
         }
-        None => {
-            return Err(format!("Entry '{}' not found!", id).into());
+    }
+    if let Some((id, reason)) = result.failures().next() {
+        return Err(format!("Adding '{}' failed, nothing was added: {}", id, reason).into());
+    }
+
+    println!("✓ Batch completed: {} added, {} replaced, {} skipped, {} malformed line(s)", added, replaced, skipped, errors.len());
+    Ok(())
+}
+
+/// ID for `passman add` without one: derived from the URL or an email
+/// username, confirmed at the prompt or replaced by a typed one
+fn propose_entry_id(core: &PassmanCore, url: Option<&str>, username: Option<&str>) -> Result<String, Box<dyn Error>> {
+    let vault = core.vault().ok_or("Vault is locked")?;
+    let Some(suggested) = url_match::suggest_entry_id(vault, url, username) else {
+        return Err("Give an entry ID, or --url to derive one from".into());
+    };
+    let answer = read_line_optional(&format!("Entry ID [{}]: ", suggested))?;
+    Ok(if answer.is_empty() { suggested } else { answer })
+}
+
+fn handle_get(id: &str, vault_path: &str, copy: bool, show: bool, spell: bool, no_wait: bool, verbose: bool) -> Result<(), Box<dyn Error>> {
+    let (mut core, _) = open_vault(vault_path)?;
+    let entry = core.get_entry(id).ok_or_else(|| format!("Entry '{}' not found!", id))?;
+    let secrets = open_entry(id, entry)?;
+
+    println!("\n--- {} ---", id);
+    println!("Username: {}", entry.username);
+    
+    if spell {
+        println!("Password:\n{}", spell_out(&secrets.password).as_str().trim_end());
+    } else if show {
+        println!("Password: {}", secrets.password.as_str());
+    } else {
+        println!("Password: {}", password_mask('*', config::get_config().ui.mask_length));
+    }
+    let show = show || spell;
+    
+    if let Some(note) = &secrets.note {
+        println!("Note: {}", note.as_str());
+    }
+    if let Some(expires_at) = entry.expires_at {
+        println!("Expires: {}", expires_at.format("%Y-%m-%d"));
+    }
+    if !entry.linked_ids.is_empty() {
+        println!("Links: {}", entry.linked_ids.join(", "));
+    }
+    if !entry.attachments.is_empty() {
+        let attachments: Vec<String> = entry.attachments.iter()
+            .map(|attachment| format!("{} ({})", attachment.name, format_size(attachment.content.len() as u64)))
+            .collect();
+        println!("Attachments: {}", attachments.join(", "));
+    }
+    if verbose {
+        let by = |who: &Option<String>| who.as_deref().map(|who| format!(" by {}", who)).unwrap_or_default();
+        println!("Created: {}{}", entry.created_at.format("%Y-%m-%d %H:%M"), by(&entry.created_by));
+        println!("Modified: {}{}", entry.modified_at.format("%Y-%m-%d %H:%M"), by(&entry.modified_by));
+    }
+    
+    let copy = copy || (!show && {
+        let copy_choice = read_line_optional("\nCopy password to clipboard? (y/N): ")?;
+        copy_choice.to_lowercase() == "y" || copy_choice.to_lowercase() == "yes"
+    });
+    let password = secrets.password;
+
+    // Usage counts are best-effort, and recorded before the clipboard wait
+    // so that Ctrl+C during it doesn't lose them
+    if show || copy {
+        if let Err(e) = core.record_use(id).and_then(|()| core.save()) {
+            eprintln!("⚠ Could not record use of '{}': {}", id, e);
         }
     }
+    if copy {
+        copy_and_clear(&password, no_wait)?;
+    }
     Ok(())
 }
 
-fn handle_list(vault_file: Option<&str>, search: Option<&str>, verbose: bool) -> Result<(), Box<dyn Error>> {
-    let master_password = read_password_secure("Enter master password: ")?;
-    let vault = VaultManager::load(&master_password, vault_file)?;
+/// The entries `id` links to, flagging any that are gone or in the trash,
+/// and the entries linking to it
+fn handle_get_links(id: &str, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    let (core, _) = open_vault(vault_path)?;
+    let vault = core.vault().ok_or("Vault is locked")?;
+    let entry = vault.get_entry(id).ok_or_else(|| format!("Entry '{}' not found!", id))?;
+    let linked_from = vault.linked_from(id);
+    if entry.linked_ids.is_empty() && linked_from.is_empty() {
+        println!("'{}' has no links. Add one with 'passman link \"{}\" <other-id>'", id, id);
+        return Ok(());
+    }
 
-    if vault.is_empty() {
-        println!("No entries found. Use 'passman add <id>' to add entries.");
+    for linked_id in &entry.linked_ids {
+        let state = match vault.get_entry(linked_id) {
+            None => " ⚠ no longer exists",
+            Some(linked) if linked.is_trashed() => " ⚠ in the trash",
+            Some(_) => "",
+        };
+        println!("→ {}{}", linked_id, state);
+    }
+    for other in linked_from {
+        println!("← {}", other);
+    }
+    Ok(())
+}
+
+/// Copy the next item of an entry's copy sequence. The position is kept
+/// in a state file for a minute, so running this again copies the one
+/// after. Only the password is held and cleared like `get --copy`; the
+/// other items are left on the clipboard.
+fn handle_copy_next(id: &str, vault_path: &str, no_wait: bool) -> Result<(), Box<dyn Error>> {
+    use copy_sequence::SequenceCursor;
+
+    let (mut core, _) = open_vault(vault_path)?;
+    let entry = core.get_entry(id).ok_or_else(|| format!("Entry '{}' not found!", id))?;
+    let steps = entry.copy_sequence.clone();
+    let now = chrono::Utc::now().timestamp();
+    let key = SequenceCursor::key(vault_path, id);
+    let (index, next) = SequenceCursor::step(SequenceCursor::load(now).as_ref(), &key, steps.len(), now)
+        .ok_or_else(|| format!("Entry '{}' has no copy sequence; set one with 'passman edit \"{}\" --copy-sequence ...'", id, id))?;
+    let step = &steps[index];
+    let value = match step.value(entry) {
+        Some(value) => value,
+        None => open_entry(id, entry)?.password,
+    };
+    if let Err(e) = SequenceCursor::store(next.as_ref()) {
+        eprintln!("⚠ Could not remember the copy sequence position: {}", e);
+    }
+
+    let position = format!("{}/{}: {}", index + 1, steps.len(), step.label());
+    if !step.is_secret() {
+        secure_clipboard::SecureClipboard::new().copy_username(&value)?;
+        println!("✓ Copied {}", position);
+        if next.is_some() {
+            println!("  Run again within {}s for the next item.", copy_sequence::SEQUENCE_TIMEOUT_SECS);
+        }
         return Ok(());
     }
 
-    let mut entries: Vec<_> = vault.list_entries();
-    entries.sort();
-    
-    // Filter by search term if provided
-    let filtered_entries: Vec<_> = if let Some(pattern) = search {
-        let pattern_lower = pattern.to_lowercase();
-        entries.into_iter()
-            .filter(|id| {
-                let id_lower = id.to_lowercase();
-                if id_lower.contains(&pattern_lower) {
-                    return true;
-                }
-                if let Some(entry) = vault.get_entry(id) {
-                    if entry.username.to_lowercase().contains(&pattern_lower) {
-                        return true;
-                    }
-                    if let Some(note) = &entry.note {
-                        if note.to_lowercase().contains(&pattern_lower) {
-                            return true;
-                        }
-                    }
-                }
-                false
-            })
-            .collect()
+    println!("Copying {}", position);
+    if let Err(e) = core.record_use(id).and_then(|()| core.save()) {
+        eprintln!("⚠ Could not record use of '{}': {}", id, e);
+    }
+    copy_and_clear(&value, no_wait)
+}
+
+/// Copy a password, then stay until `security.clipboard_timeout_secs`
+/// passes and clear it, since no auto-clear thread outlives the process.
+/// Ctrl+C clears at once and exits. `no_wait` skips the wait and leaves
+/// clearing to the user.
+fn copy_and_clear(password: &str, no_wait: bool) -> Result<(), Box<dyn Error>> {
+    use secure_clipboard::{hold_then_clear, HoldEnd, SecureClipboard};
+    use std::time::Duration;
+
+    let timeout = config::get_config().security.clipboard_timeout_secs;
+    let clipboard = SecureClipboard::with_timeout(timeout);
+    let stop = interrupt::redirect();
+    clipboard.copy(password, false)?;
+    println!("✓ Password copied to clipboard!");
+    if timeout == 0 {
+        return Ok(());
+    }
+    if no_wait {
+        eprintln!("⚠ The clipboard won't be cleared automatically; clearing it is up to you.");
+        return Ok(());
+    }
+
+    // Redraw the countdown in place on a terminal, print it once otherwise
+    let interactive = atty::is(atty::Stream::Stderr);
+    let mut printed = false;
+    let end = hold_then_clear(&clipboard, Duration::from_secs(timeout), &stop, |left| {
+        if interactive || !printed {
+            eprint!("\rClipboard will clear in {}s (Ctrl+C to clear now and exit)… ", left);
+            let _ = std::io::Write::flush(&mut std::io::stderr());
+            printed = true;
+        }
+    })?;
+    eprintln!();
+    println!("✓ Clipboard cleared");
+    if end == HoldEnd::Interrupted {
+        std::process::exit(interrupt::INTERRUPTED_EXIT_CODE);
+    }
+    Ok(())
+}
+
+/// `passman get --field`: print the chosen fields one per line and nothing
+/// else, for scripts. Missing values print as empty lines.
+fn handle_get_fields(id: &str, fields: &[String], vault_path: &str) -> Result<(), Box<dyn Error>> {
+    const FIELDS: &[&str] = &["username", "password", "url", "note", "tags"];
+    if let Some(unknown) = fields.iter().find(|field| !FIELDS.contains(&field.as_str())) {
+        return Err(format!("Unknown field '{}'. Use: {}", unknown, FIELDS.join(", ")).into());
+    }
+    let (mut core, _) = open_vault(vault_path)?;
+    let entry = core.get_entry(id).ok_or_else(|| format!("Entry '{}' not found!", id))?;
+    // A high-security entry's PIN is only asked for when its secrets are
+    let secrets = match fields.iter().any(|field| matches!(field.as_str(), "password" | "note")) {
+        true => open_entry(id, entry)?,
+        false => SealedSecrets { password: Zeroizing::default(), note: None },
+    };
+
+    for field in fields {
+        match field.as_str() {
+            "username" => println!("{}", entry.username),
+            "password" => println!("{}", secrets.password.as_str()),
+            "url" => println!("{}", entry.url.as_deref().unwrap_or_default()),
+            "note" => println!("{}", secrets.note.as_deref().map(String::as_str).unwrap_or_default()),
+            _ => println!("{}", entry.tags.join(", ")),
+        }
+    }
+
+    if fields.iter().any(|field| field == "password") {
+        if let Err(e) = core.record_use(id).and_then(|()| core.save()) {
+            eprintln!("⚠ Could not record use of '{}': {}", id, e);
+        }
+    }
+    Ok(())
+}
+
+/// Two-stage login: open the site with the username on the clipboard,
+/// then swap in the password on Enter or after `ui.open_copy_delay_secs`
+fn handle_open(id: &str, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    use secure_clipboard::SecureClipboard;
+    use std::time::Duration;
+
+    let (mut core, _) = open_vault(vault_path)?;
+    let entry = core.get_entry(id).ok_or_else(|| format!("Entry '{}' not found!", id))?;
+    let url = site_url(entry.url.as_deref().unwrap_or_default())?;
+    let username = entry.username.clone();
+    let password = open_entry(id, entry)?.password;
+    let config = config::get_config();
+    let delay = config.ui.open_copy_delay_secs;
+    let clipboard = SecureClipboard::with_timeout(config.security.clipboard_timeout_secs);
+    drop(config);
+
+    open::that(&url).map_err(|e| format!("Could not open {}: {}", url, e))?;
+    clipboard.copy_username(&username)?;
+    println!("✓ Opened {}", url);
+    if delay > 0 {
+        println!("[1/2] Username copied. Press Enter for the password (or wait {}s)...", delay);
     } else {
-        entries
+        println!("[1/2] Username copied. Press Enter for the password...");
+    }
+    wait_for_enter((delay > 0).then(|| Duration::from_secs(delay)));
+
+    clipboard.copy(&password, false)?;
+    if let Err(e) = core.record_use(id).and_then(|()| core.save()) {
+        eprintln!("⚠ Could not record use of '{}': {}", id, e);
+    }
+    let timeout = clipboard.get_timeout();
+    if timeout == 0 {
+        println!("[2/2] Password copied.");
+        return Ok(());
+    }
+    // The clipboard is cleared by this process, so stay until then
+    println!("[2/2] Password copied. Clearing the clipboard in {}s (Enter to clear now)...", timeout);
+    wait_for_enter(Some(Duration::from_secs(timeout)));
+    clipboard.clear_now()?;
+    println!("✓ Clipboard cleared");
+    Ok(())
+}
+
+/// Block until Enter is pressed or `timeout` passes; true if Enter was pressed
+fn wait_for_enter(timeout: Option<std::time::Duration>) -> bool {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let mut line = String::new();
+        let _ = std::io::stdin().read_line(&mut line);
+        let _ = sender.send(());
+    });
+    match timeout {
+        Some(timeout) => receiver.recv_timeout(timeout).is_ok(),
+        None => receiver.recv().is_ok(),
+    }
+}
+
+/// The query for `list --saved <name>`, or `search` when no name is given
+fn resolve_saved_search(name: Option<&str>, search: Option<String>) -> Result<Option<String>, Box<dyn Error>> {
+    let Some(name) = name else {
+        return Ok(search);
+    };
+    let config = config::get_config();
+    match config.ui.saved_searches.get(name) {
+        Some(query) => Ok(Some(query.clone())),
+        None if config.ui.saved_searches.is_empty() => {
+            Err(format!("No saved search '{}'. Pin searches in the GUI with ☆ next to the search box.", name).into())
+        }
+        None => {
+            let names: Vec<&str> = config.ui.saved_searches.keys().map(String::as_str).collect();
+            Err(format!("No saved search '{}'. Saved searches: {}", name, names.join(", ")).into())
+        }
+    }
+}
+
+fn handle_list(vault_path: &str, search: Option<&str>, verbose: bool, no_notes: bool, include_trash: bool, output: &str) -> Result<(), Box<dyn Error>> {
+    use search::SearchScope;
+
+    let json = match output.to_lowercase().as_str() {
+        "text" => false,
+        "json" => true,
+        other => return Err(format!("Unsupported output format: {}", other).into()),
+    };
+    let (core, _) = open_vault(vault_path)?;
+
+    // Filter by search term if provided
+    let include_notes = !no_notes && config::get_config().ui.search_notes;
+    let scope = if include_trash { SearchScope::IncludeTrash } else { SearchScope::ActiveOnly };
+    let filtered_entries = core.search_entries_with(search.unwrap_or(""), include_notes, scope);
+
+    if json {
+        return print_list_json(&filtered_entries);
+    }
+
+    if core.is_empty() {
+        println!("No entries found. Use 'passman add <id>' to add entries.");
+        return Ok(());
+    }
+
+    // Point at the trash when it holds matches that were left out
+    let in_trash = match (search, scope) {
+        (Some(pattern), SearchScope::ActiveOnly) => core
+            .search_entries_with(pattern, include_notes, SearchScope::IncludeTrash)
+            .len() - filtered_entries.len(),
+        _ => 0,
+    };
+    let trash_hint = || {
+        if in_trash > 0 {
+            println!("({} matching {} in trash; add --include-trash to list them)", in_trash, if in_trash == 1 { "entry" } else { "entries" });
+        }
     };
 
     if filtered_entries.is_empty() {
         println!("No entries match your search criteria.");
+        trash_hint();
         return Ok(());
     }
 
     println!("\nStored entries ({} found):", filtered_entries.len());
     println!("{}", "-".repeat(50));
     
-    for (i, id) in filtered_entries.iter().enumerate() {
-        let entry = vault.get_entry(id).unwrap();
+    for (i, (id, entry)) in filtered_entries.iter().enumerate() {
         if verbose {
             println!("{}. {}", i + 1, id);
             println!("   Username: {}", entry.username);
-            println!("   Password: {}", "*".repeat(entry.password_str().len().min(12)));
+            println!("   Password: {}", password_mask('*', config::get_config().ui.mask_length));
             if let Some(note) = &entry.note {
                 println!("   Note: {}", note);
+            } else if entry.is_sealed() {
+                println!("   🛡 High security");
             }
-            let (strength, _) = analyze_password_strength(entry.password_str());
-            println!("   Strength: {}", strength);
+            println!("   Strength: {}", entry.password_strength());
             println!();
         } else {
-            println!("{}. {} ({})", i + 1, id, entry.username);
+            let trashed = if entry.is_trashed() { " [trash]" } else { "" };
+            let archived = if entry.archived { " [archived]" } else { "" };
+            println!("{}. {} ({}){}{}", i + 1, id, entry.username, trashed, archived);
         }
     }
+    trash_hint();
     Ok(())
 }
 
-fn handle_remove(id: &str, vault_file: Option<&str>, force: bool) -> Result<(), Box<dyn Error>> {
-    let master_password = read_password_secure("Enter master password: ")?;
-    let mut vault = VaultManager::load(&master_password, vault_file)?;
+/// Print listed entries as a JSON array; passwords, TOTP secrets and notes are left out
+fn print_list_json(entries: &[(String, Entry)]) -> Result<(), Box<dyn Error>> {
+    let entries: Vec<serde_json::Value> = entries.iter()
+        .map(|(id, entry)| serde_json::json!({
+            "id": id,
+            "username": entry.username,
+            "url": entry.url,
+            "tags": entry.tags,
+            "created_at": entry.created_at.to_rfc3339(),
+            "modified_at": entry.modified_at.to_rfc3339(),
+            "expires_at": entry.expires_at.map(|at| at.to_rfc3339()),
+            "trashed": entry.is_trashed(),
+            "archived": entry.archived,
+        }))
+        .collect();
+    println!("{}", serde_json::to_string_pretty(&entries)?);
+    Ok(())
+}
 
-    if vault.get_entry(id).is_none() {
-        return Err(format!("Entry '{}' not found!", id).into());
-    }
+fn handle_remove(id: &str, vault_path: &str, force: bool) -> Result<(), Box<dyn Error>> {
+    let (mut core, _) = open_vault(vault_path)?;
 
-    // Confirm deletion unless force flag is set
-    if !force {
+    let entry = core.get_entry(id).ok_or_else(|| format!("Entry '{}' not found!", id))?;
+    let protected_tag = config::get_config().security.protected_tag.clone();
+
+    if entry.has_tag(&protected_tag) {
+        // Protected entries need --force and the id typed back, every time
+        if !force {
+            return Err(format!(
+                "Entry '{}' is tagged '{}'; rerun with --force to delete it", id, protected_tag
+            ).into());
+        }
+        let typed = read_line_optional(&format!("Type '{}' to confirm deletion: ", id))?;
+        if !Entry::confirms_id(&typed, id) {
+            println!("Deletion cancelled.");
+            return Ok(());
+        }
+    } else if !force {
         let confirm = read_line_optional(&format!("Are you sure you want to delete '{}'? (y/N): ", id))?;
         if confirm.to_lowercase() != "y" && confirm.to_lowercase() != "yes" {
             println!("Deletion cancelled.");
@@ -284,56 +807,101 @@ fn handle_remove(id: &str, vault_file: Option<&str>, force: bool) -> Result<(),
         }
     }
 
-    match vault.remove_entry(id) {
-        Some(_) => {
-            VaultManager::save(&vault, &master_password, vault_file)?;
-            println!("✓ Entry '{}' removed successfully!", id);
-        }
-        None => {
-            return Err(format!("Entry '{}' not found!", id).into());
-        }
+    core.remove_entry(id)?;
+    core.save()?;
+    println!("✓ Entry '{}' removed successfully!", id);
+    Ok(())
+}
+
+fn handle_archive(id: &str, undo: bool, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    let (mut core, _) = open_vault(vault_path)?;
+    let (done, state) = if undo { ("Unarchived", "isn't archived") } else { ("Archived", "is already archived") };
+    if !core.set_archived(id, !undo)? {
+        println!("Entry '{}' {}", id, state);
+        return Ok(());
     }
+    core.save()?;
+    println!("✓ {} '{}'", done, id);
     Ok(())
 }
 
-fn handle_check(password: Option<&str>, all: bool, vault_file: Option<&str>) -> Result<(), Box<dyn Error>> {
-    if all {
-        // Check all passwords in vault
-        let master_password = read_password_secure("Enter master password: ")?;
-        let vault = VaultManager::load(&master_password, vault_file)?;
-        
-        if vault.is_empty() {
-            println!("No entries in vault.");
-            return Ok(());
-        }
+fn handle_link(id: &str, other_id: &str, remove: bool, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    let (mut core, _) = open_vault(vault_path)?;
+    if !core.set_link(id, other_id, !remove)? {
+        let state = if remove { "isn't linked to" } else { "is already linked to" };
+        println!("Entry '{}' {} '{}'", id, state, other_id);
+        return Ok(());
+    }
+    core.save()?;
+    match remove {
+        true => println!("✓ Unlinked '{}' from '{}'", id, other_id),
+        false => println!("✓ Linked '{}' → '{}'", id, other_id),
+    }
+    Ok(())
+}
 
-        println!("\nPassword Strength Analysis:");
-        println!("{}", "=".repeat(60));
-        
-        let mut weak_count = 0;
-        let mut entries: Vec<_> = vault.list_entries();
-        entries.sort();
+fn handle_seal(id: &str, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    let (mut core, _) = open_vault(vault_path)?;
+    let mut entry = core.get_entry(id).cloned().ok_or_else(|| format!("Entry '{}' not found!", id))?;
+    if entry.is_sealed() {
+        println!("Entry '{}' is already high security", id);
+        return Ok(());
+    }
+    let pin = read_password_secure("New PIN: ")?;
+    if *read_password_secure("Confirm PIN: ")? != *pin {
+        return Err("PINs don't match".into());
+    }
+    entry.seal(&pin)?;
+    entry.update();
+    core.update_entry(id, entry)?;
+    core.save()?;
+    println!("✓ '{}' is now high security; its password and note need the PIN", id);
+    Ok(())
+}
 
-        for id in entries {
-            let entry = vault.get_entry(id).unwrap();
-            let (strength, suggestions) = analyze_password_strength(entry.password_str());
-            
-            let status_icon = if suggestions.is_empty() { "✓" } else { "⚠" };
-            println!("{} {} - {}", status_icon, id, strength);
-            
-            if !suggestions.is_empty() {
-                weak_count += 1;
-                for suggestion in &suggestions {
-                    println!("    • {}", suggestion);
-                }
-            }
+fn handle_unseal(id: &str, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    let (mut core, _) = open_vault(vault_path)?;
+    let mut entry = core.get_entry(id).cloned().ok_or_else(|| format!("Entry '{}' not found!", id))?;
+    if !entry.is_sealed() {
+        println!("Entry '{}' isn't high security", id);
+        return Ok(());
+    }
+    entry.unseal(&read_password_secure(&format!("PIN for '{}': ", id))?)?;
+    entry.update();
+    core.update_entry(id, entry)?;
+    core.save()?;
+    println!("✓ '{}' is no longer high security", id);
+    Ok(())
+}
+
+/// An entry's password and note, asking for the PIN if it is high security
+fn open_entry(id: &str, entry: &Entry) -> Result<SealedSecrets, Box<dyn Error>> {
+    if entry.is_sealed() {
+        let pin = read_password_secure(&format!("PIN for '{}': ", id))?;
+        return Ok(entry.open_sealed(&pin)?);
+    }
+    Ok(SealedSecrets {
+        password: Zeroizing::new(entry.password_str().reveal().to_string()),
+        note: entry.note.clone().map(Zeroizing::new),
+    })
+}
+
+fn handle_check(password: Option<&str>, all: bool, output: &str, include_trash: bool, breaches: bool, fail_below: Option<u8>, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    if all {
+        // Audit all passwords in vault, scored exactly like the GUI dashboard
+        let (core, _) = open_vault(vault_path)?;
+        let scope = if include_trash { health::AnalysisScope::IncludeTrash } else { health::AnalysisScope::ActiveOnly };
+        let (mut reports, summary) = core.analyze_health_with(scope).ok_or("Vault is locked")?;
+        reports.sort_by(|a, b| a.entry_id.cmp(&b.entry_id));
+        
+        match output.to_lowercase().as_str() {
+            "text" => print_check_table(&reports, &summary, breaches),
+            "json" => print_check_json(&core, &reports, &summary)?,
+            other => return Err(format!("Unsupported output format: {}", other).into()),
         }
         
-        println!("{}", "=".repeat(60));
-        if weak_count > 0 {
-            println!("⚠ {} password(s) need improvement", weak_count);
-        } else {
-            println!("✓ All passwords are strong!");
+        if let Some(threshold) = fail_below.filter(|threshold| summary.score < *threshold) {
+            return Err(format!("Health score {} is below the required {}", summary.score, threshold).into());
         }
     } else {
         let pwd = match password {
@@ -345,12 +913,12 @@ fn handle_check(password: Option<&str>, all: bool, vault_file: Option<&str>) ->
         
         println!("\nPassword Analysis:");
         println!("Strength: {}", strength);
+        if health::PasswordHealthAnalyzer::new().is_banned(&pwd) {
+            println!("✗ {}", health::BANNED_ISSUE);
+        }
         
         if !suggestions.is_empty() {
-            println!("\nSuggestions for improvement:");
-            for suggestion in suggestions {
-                println!("  • {}", suggestion);
-            }
+            print_suggestions("\nSuggestions for improvement:", &suggestions);
         } else {
             println!("✓ This is a strong password!");
         }
@@ -359,241 +927,1276 @@ fn handle_check(password: Option<&str>, all: bool, vault_file: Option<&str>) ->
     Ok(())
 }
 
-fn handle_vaults() -> Result<(), Box<dyn Error>> {
-    use std::fs;
-    
-    println!("Available vault files:");
-    
-    let current_dir = std::env::current_dir()?;
-    let entries = fs::read_dir(&current_dir)?;
-    
-    let mut vault_files = Vec::new();
-    for entry in entries {
-        let entry = entry?;
-        let path = entry.path();
-        if let Some(file_name) = path.file_name() {
-            let file_name_str = file_name.to_string_lossy();
-            if file_name_str.ends_with(".dat") || file_name_str == "vault.dat" {
-                vault_files.push(file_name_str.to_string());
+/// List entries that expired or expire within `days`, soonest first, for
+/// cron-driven reminders; nothing is printed when none do
+fn handle_check_expiring(days: i64, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    let (core, _) = open_vault(vault_path)?;
+    let now = chrono::Utc::now();
+    let mut expiring: Vec<(String, i64, chrono::DateTime<chrono::Utc>)> = core.list_entries_with_data()
+        .into_iter()
+        .filter(|(_, entry)| !entry.is_trashed() && !entry.archived && entry.expires_within(days, now))
+        .filter_map(|(id, entry)| Some((id, entry.days_until_expiry(now)?, entry.expires_at?)))
+        .collect();
+    expiring.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+
+    for (id, left, expires_at) in expiring {
+        let when = match left {
+            0 => "today".to_string(),
+            l if l < 0 => format!("{} day(s) ago", -l),
+            l => format!("in {} day(s)", l),
+        };
+        let icon = if left < 0 { "✗" } else { "⌛" };
+        println!("{} {} - {} {} ({})", icon, id, if left < 0 { "expired" } else { "expires" }, when, expires_at.format("%Y-%m-%d"));
+    }
+    Ok(())
+}
+
+/// Print one audit row per entry (strength, age, reuse and optionally
+/// breaches) followed by the overall health score
+fn print_check_table(reports: &[health::HealthReport], summary: &health::HealthSummary, breaches: bool) {
+    if reports.is_empty() {
+        println!("No entries in vault.");
+        return;
+    }
+
+    println!("\nPassword Audit:");
+    println!("{}", "=".repeat(72));
+    let breach_header = if breaches { format!(" {:>8}", "Breaches") } else { String::new() };
+    println!("  {:<28} {:<10} {:>8} {:>7}{}", "Entry", "Strength", "Age (d)", "Reused", breach_header);
+
+    for report in reports {
+        let (icon, issues) = match &report.health {
+            health::PasswordHealth::Critical { issues } => ("✗", issues.as_slice()),
+            health::PasswordHealth::Warning { issues } => ("⚠", issues.as_slice()),
+            health::PasswordHealth::Good | health::PasswordHealth::Excellent => ("✓", &[][..]),
+        };
+        let breach_column = if breaches { format!(" {:>8}", report.breach_count) } else { String::new() };
+        println!(
+            "{} {:<28} {:<10} {:>8} {:>7}{}",
+            icon, report.entry_id, report.strength.to_string(), report.age_days, report.reused_with, breach_column,
+        );
+        for issue in issues {
+            println!("    • {}", issue);
+        }
+    }
+
+    println!("{}", "=".repeat(72));
+    println!(
+        "Health score: {}/100 ({} critical, {} warning, {} good, {} excellent)",
+        summary.score, summary.critical, summary.warning, summary.good, summary.excellent,
+    );
+}
+
+/// Print the health reports and the vault's stored score history as JSON
+fn print_check_json(core: &PassmanCore, reports: &[health::HealthReport], summary: &health::HealthSummary) -> Result<(), Box<dyn Error>> {
+    // Read-only: the current analysis is not added to the stored history
+    let history = core.vault().map(|vault| vault.metadata.health_history.clone()).unwrap_or_default();
+    let report = serde_json::json!({
+        "summary": summary,
+        "entries": reports,
+        "history": history,
+    });
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+/// Vault files listed per page by `passman vaults`
+const VAULTS_PAGE_SIZE: usize = 20;
+
+fn handle_vaults(vault_path: &str, vault_source: config::VaultPathSource, page: usize) -> Result<(), Box<dyn Error>> {
+    println!("Active vault: {} (from {})", vault_path, vault_source);
+    println!();
+    
+    let dirs = VaultManager::vault_dirs(vault_path);
+    let scan = VaultManager::list_vaults(&dirs);
+    for warning in &scan.warnings {
+        eprintln!("⚠ Skipped {}", warning);
+    }
+    
+    if scan.vaults.is_empty() {
+        let searched: Vec<String> = dirs.iter().map(|dir| dir.display().to_string()).collect();
+        println!("No vault files found in {}.", searched.join(", "));
+        println!("Use 'passman init' to create a new vault.");
+        return Ok(());
+    }
+    
+    let pages = scan.vaults.len().div_ceil(VAULTS_PAGE_SIZE);
+    let page = page.clamp(1, pages);
+    let start = (page - 1) * VAULTS_PAGE_SIZE;
+    println!("{:<4} {:<50} {:>7} {:>10} {:>16}", "#", "Vault", "Format", "Size", "Entries");
+    println!("{}", "-".repeat(91));
+    for (i, vault) in scan.vaults.iter().enumerate().skip(start).take(VAULTS_PAGE_SIZE) {
+        // Counts are only known with security.store_plain_metadata on
+        let entries = vault.stats
+            .map(|stats| if stats.weak > 0 { format!("{} ({} weak)", stats.entries, stats.weak) } else { stats.entries.to_string() })
+            .unwrap_or_else(|| "-".to_string());
+        println!("{:<4} {:<50} {:>7} {:>10} {:>16}", i + 1, vault.path, format!("v{}", vault.version), format_size(vault.size), entries);
+    }
+    if pages > 1 {
+        println!();
+        println!("Page {} of {} ({} vaults)", page, pages, scan.vaults.len());
+        if page < pages {
+            println!("Use 'passman vaults --page {}' for more.", page + 1);
+        }
+    }
+    
+    Ok(())
+}
+
+fn handle_vault(cmd: VaultCommands, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    match cmd {
+        VaultCommands::Info => handle_vault_info(vault_path),
+        VaultCommands::Status { output, stale_after } => handle_vault_status(vault_path, &output, stale_after),
+        VaultCommands::Calibrate { target_ms, apply } => handle_calibrate(target_ms, apply, vault_path),
+        VaultCommands::Repair => handle_vault_repair(vault_path),
+        VaultCommands::Rekey { cipher } => handle_vault_rekey(cipher.as_deref(), vault_path),
+        VaultCommands::FindConflicts { yes } => handle_find_conflicts(vault_path, yes),
+        VaultCommands::Housekeeping { dry_run } => handle_housekeeping(vault_path, dry_run),
+        VaultCommands::Delete { backups, force } => handle_vault_delete(vault_path, backups, force),
+    }
+}
+
+/// Check the vault file without unlocking it, printing a line per check
+/// and how to fix what isn't right
+fn handle_doctor(vault_path: &str) -> Result<(), Box<dyn Error>> {
+    println!("Checking {}", vault_path);
+    if !VaultManager::exists(Some(vault_path)) {
+        println!("  ✗ Vault file: not found (create it with 'passman init')");
+        return Ok(());
+    }
+    let mut problems = 0;
+    let mut problem = |check: &str, message: String, fix: String| {
+        problems += 1;
+        println!("  ⚠ {}: {}", check, message);
+        println!("      Fix: {}", fix);
+    };
+
+    match (VaultManager::format_version(Some(vault_path)), VaultManager::cipher(Some(vault_path))) {
+        (Ok(Some(version)), Ok(cipher)) => println!("  ✓ Vault file: format v{}, {}", version, cipher),
+        (Ok(None), _) => problem("Vault file", "legacy (v1) format".to_string(), "unlock and save it once to upgrade".to_string()),
+        (Err(e), _) => problem("Vault file", e.to_string(), "check the path and that you own the file".to_string()),
+        (_, Err(e)) => problem("Vault file", e.to_string(), "restore a backup with 'passman backup restore'".to_string()),
+    }
+
+    match VaultManager::loose_permissions(Some(vault_path)) {
+        Some(loose) => problem("Permissions", loose.message(), loose.remedy()),
+        None => println!("  ✓ Permissions: only you can read the vault file"),
+    }
+    let loose_backups = VaultManager::loose_backup_permissions(Some(vault_path));
+    for loose in &loose_backups {
+        problem("Backup permissions", loose.message(), loose.remedy());
+    }
+    if loose_backups.is_empty() {
+        println!("  ✓ Backup permissions: only you can read the backups");
+    }
+
+    match VaultManager::salt_fallbacks(Some(vault_path)) {
+        Ok(fallbacks) if fallbacks.is_empty() => println!("  ✓ Salt: canonical"),
+        Ok(_) => problem("Salt", "stored in a non-canonical form".to_string(), "passman vault repair".to_string()),
+        Err(e) => problem("Salt", e.to_string(), "restore a backup with 'passman backup restore'".to_string()),
+    }
+
+    match VaultManager::sync_folder_risk(Some(vault_path)) {
+        Some(risk) if !risk.conflicted_copies.is_empty() => problem("Sync folder", risk.message(), "passman vault find-conflicts".to_string()),
+        Some(risk) => problem("Sync folder", risk.message(), "keep the vault outside the synced folder and share it with 'passman sync export <dir>'".to_string()),
+        None => println!("  ✓ Sync folder: not in a synced folder"),
+    }
+
+    match problems {
+        0 => println!("✓ No problems found"),
+        n => println!("{} problem{} found", n, if n == 1 { "" } else { "s" }),
+    }
+    Ok(())
+}
+
+fn handle_vault_info(vault_path: &str) -> Result<(), Box<dyn Error>> {
+    if !VaultManager::exists(Some(vault_path)) {
+        return Err(format!("Vault not found: {}", vault_path).into());
+    }
+    let identity = VaultManager::identity(Some(vault_path))?;
+
+    println!("Vault:       {}", vault_path);
+    match VaultManager::format_version(Some(vault_path))? {
+        Some(version) => println!("Format:      v{}", version),
+        None => println!("Format:      legacy (v1)"),
+    }
+    println!("Size:        {} bytes", std::fs::metadata(vault_path)?.len());
+    println!("Cipher:      {}", VaultManager::cipher(Some(vault_path))?);
+    println!("KDF:         {}", VaultManager::kdf_params(Some(vault_path))?);
+    println!("Fingerprint: {}", identity.fingerprint);
+    match &identity.pinned {
+        None => println!("             (not unlocked on this machine yet)"),
+        Some(_) if !identity.is_substituted() => println!("             ✓ matches the vault previously opened here"),
+        Some(_) => eprintln!("⚠ {}", identity.warning().unwrap_or_default()),
+    }
+    Ok(())
+}
+
+/// Exit codes of `passman vault status`, as monitoring plugins use them
+const STATUS_EXIT_WARNINGS: i32 = 1;
+const STATUS_EXIT_ERRORS: i32 = 2;
+
+/// Check the vault without prompting: the master password is only taken
+/// from PASSMAN_PASSWORD or --password-file. Exits with
+/// [`STATUS_EXIT_WARNINGS`] or [`STATUS_EXIT_ERRORS`] when the checks
+/// aren't all clean.
+fn handle_vault_status(vault_path: &str, output: &str, stale_after: i64) -> Result<(), Box<dyn Error>> {
+    let output = output.to_lowercase();
+    if !matches!(output.as_str(), "text" | "json") {
+        return Err(format!("Unsupported output format: {}", output).into());
+    }
+    let master_password = provided_master_password()?;
+    let limits = config::get_config().housekeeping_limits();
+    let report = status::VaultStatus::check(vault_path, master_password.as_ref(), &limits, stale_after, chrono::Utc::now());
+    // Exiting below skips destructors
+    drop(master_password);
+
+    match output.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+        _ => print_vault_status(&report),
+    }
+    let code = match report.status {
+        status::StatusLevel::Ok => return Ok(()),
+        status::StatusLevel::Warnings => STATUS_EXIT_WARNINGS,
+        status::StatusLevel::Errors => STATUS_EXIT_ERRORS,
+    };
+    std::process::exit(code);
+}
+
+fn print_vault_status(report: &status::VaultStatus) {
+    let not_checked = "not checked (no master password given)";
+    println!("Vault:         {}", report.path);
+    if let Some(size) = report.size {
+        println!("Size:          {}", format_size(size));
+    }
+    match (report.format_version, &report.cipher) {
+        (Some(version), Some(cipher)) => println!("Format:        v{}, {}", version, cipher),
+        (Some(version), None) => println!("Format:        v{}", version),
+        (None, _) if report.size.is_some() => println!("Format:        legacy (v1)"),
+        (None, _) => {}
+    }
+    if let Some(modified) = report.last_modified {
+        println!("Last modified: {}", modified.format("%Y-%m-%d %H:%M:%S UTC"));
+    }
+    match report.hmac_verified {
+        Some(true) => println!("HMAC:          ✓ verified"),
+        Some(false) => println!("HMAC:          ✗ failed"),
+        None => println!("HMAC:          {}", not_checked),
+    }
+    match (report.entries, &report.housekeeping) {
+        (Some(entries), Some(housekeeping)) => println!(
+            "Entries:       {} ({} in the trash, {} due for purge, {} archived)",
+            entries, housekeeping.trashed, housekeeping.due_for_purge, housekeeping.archived
+        ),
+        (Some(entries), None) => println!("Entries:       {}", entries),
+        (None, _) => println!("Entries:       {}", not_checked),
+    }
+    match &report.newest_backup {
+        Some(backup) => {
+            let age = backup.age_days.map(|days| format!(", {} day{} old", days, if days == 1 { "" } else { "s" })).unwrap_or_default();
+            let verified = match backup.verified {
+                Some(true) => "✓ verified",
+                Some(false) => "✗ does not decrypt",
+                None => "? never verified",
+            };
+            println!("Newest backup: {} ({}{})", backup.name, verified, age);
+        }
+        None => println!("Newest backup: none"),
+    }
+    for warning in &report.warnings {
+        println!("⚠ {}", warning);
+    }
+    for error in &report.errors {
+        println!("✗ {}", error);
+    }
+    match report.status {
+        status::StatusLevel::Ok => println!("✓ OK"),
+        status::StatusLevel::Warnings => println!("Status: warnings"),
+        status::StatusLevel::Errors => println!("Status: errors"),
+    }
+}
+
+fn handle_vault_repair(vault_path: &str) -> Result<(), Box<dyn Error>> {
+    if !VaultManager::exists(Some(vault_path)) {
+        return Err(format!("Vault not found: {}", vault_path).into());
+    }
+    let needed = VaultManager::salt_fallbacks(Some(vault_path))?;
+    println!("Salt checks for {}:", vault_path);
+    for fallback in vault::SaltFallback::ALL {
+        let mark = if needed.contains(&fallback) { "needed" } else { "ok" };
+        println!("  {:<7} {}", mark, fallback);
+    }
+    if needed.is_empty() {
+        println!("✓ Salt is canonical; nothing to repair");
+        return Ok(());
+    }
+
+    let master_password = read_master_password("Enter master password: ")?;
+    let repair = VaultManager::repair_salt(&master_password, Some(vault_path))?;
+    println!("✓ Salt rewritten in canonical form; the master password is unchanged");
+    for fallback in &repair.fallbacks {
+        println!("  Fixed: {}", fallback);
+    }
+    if let Some(backup) = &repair.backup {
+        println!("  Previous file backed up to {}", backup);
+    }
+    Ok(())
+}
+
+/// Re-encrypt the vault with a fresh salt, the configured KDF parameters
+/// and optionally another cipher
+fn handle_vault_rekey(cipher: Option<&str>, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    if !VaultManager::exists(Some(vault_path)) {
+        return Err(format!("Vault not found: {}", vault_path).into());
+    }
+    let cipher = cipher.map(str::parse::<CipherKind>).transpose()?;
+    let before = VaultManager::cipher(Some(vault_path))?;
+
+    let master_password = read_master_password("Enter master password: ")?;
+    let key = VaultManager::rekey(&master_password, cipher, Some(vault_path))?;
+    if key.cipher() == before {
+        println!("✓ Vault re-keyed ({}); the master password is unchanged", key.cipher());
+    } else {
+        println!("✓ Vault re-encrypted with {} (was {}); the master password is unchanged", key.cipher(), before);
+    }
+    println!("  KDF: {}", key.kdf());
+    if let Some(backup) = VaultManager::list_backups(Some(vault_path)).first() {
+        println!("  Previous file backed up to {}", backup);
+    }
+    Ok(())
+}
+
+/// Merge conflicted sync copies of the vault into it, one at a time after
+/// showing what each would change. The copies are unlocked with the
+/// vault's master password; one that doesn't open with it is skipped.
+/// Print what housekeeping prunes, and prune it unless `dry_run`
+fn handle_housekeeping(vault_path: &str, dry_run: bool) -> Result<(), Box<dyn Error>> {
+    let (mut core, _) = open_vault(vault_path)?;
+    let plan = core.housekeeping_plan().ok_or("Vault is locked")?;
+    let retention = core.config().trash.retention_days;
+    if retention == 0 {
+        println!("Trash retention: keep until emptied (trash.retention_days = 0)");
+    } else {
+        println!("Trash retention: {} days", retention);
+    }
+    if plan.is_empty() {
+        println!("✓ Nothing to prune");
+        return Ok(());
+    }
+    
+    println!("{} from the trash:", if dry_run { "Would delete" } else { "Deleting" });
+    for (id, trashed_at) in &plan.purged_trash {
+        println!("  - {} (trashed {})", id, trashed_at.format("%Y-%m-%d"));
+    }
+    if dry_run {
+        return Ok(());
+    }
+    core.save()?;
+    println!("✓ Housekeeping {}", plan.summary());
+    Ok(())
+}
+
+fn handle_find_conflicts(vault_path: &str, yes: bool) -> Result<(), Box<dyn Error>> {
+    if !VaultManager::exists(Some(vault_path)) {
+        return Err(format!("Vault not found: {}", vault_path).into());
+    }
+    let copies = VaultManager::conflicted_copies(Some(vault_path));
+    if copies.is_empty() {
+        println!("✓ No conflicted copies of {}", vault_path);
+        return Ok(());
+    }
+    println!("Found {} conflicted cop{} of {}:", copies.len(), if copies.len() == 1 { "y" } else { "ies" }, vault_path);
+    for copy_path in &copies {
+        println!("  {}", copy_path);
+    }
+
+    let (mut core, master_password) = open_vault(vault_path)?;
+    for copy_path in &copies {
+        println!();
+        let copy = match std::fs::read(copy_path).map_err(Box::<dyn Error>::from)
+            .and_then(|bytes| VaultManager::load_from_bytes(&master_password, &bytes))
+        {
+            Ok(copy) => copy,
+            Err(e) => {
+                eprintln!("⚠ Skipped {}: {} (was it saved under another master password?)", copy_path, e);
+                continue;
+            }
+        };
+        let diff = diff::VaultDiff::compare(core.vault().ok_or("Vault is locked")?, &copy);
+        println!("{}", copy_path);
+        for id in &diff.only_in_b {
+            println!("  + {}", id);
+        }
+        for entry in diff.merge_updates() {
+            println!("  ~ {} (newer in the copy)", entry.id);
+        }
+        match diff.merge_summary() {
+            Some(summary) => println!("  {}.", summary),
+            None => println!("  Everything in it is already in the vault."),
+        }
+        if !yes {
+            let answer = read_line_optional("Merge it and move it aside? (y/N): ")?;
+            if !matches!(answer.to_lowercase().as_str(), "y" | "yes") {
+                println!("  Skipped");
+                continue;
+            }
+        }
+        let resolution = core.resolve_conflicted_copy(copy_path, &copy)?;
+        println!("✓ Merged; the copy was moved to {}", resolution.archived);
+        println!("  Previous vault backed up to {}", resolution.backup);
+    }
+    Ok(())
+}
+
+fn handle_vault_delete(vault_path: &str, backups: bool, force: bool) -> Result<(), Box<dyn Error>> {
+    if !VaultManager::exists(Some(vault_path)) {
+        return Err(format!("Vault not found: {}", vault_path).into());
+    }
+    let backup_count = VaultManager::list_backups(Some(vault_path)).len();
+    
+    if !force {
+        println!("⚠ This permanently deletes '{}'.", vault_path);
+        if backups {
+            println!("  Its {} backup(s) will be deleted too.", backup_count);
+        } else if backup_count > 0 {
+            println!("  Its {} backup(s) are kept (add --backups to delete them).", backup_count);
+        }
+        let file_name = std::path::Path::new(vault_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| vault_path.to_string());
+        let typed = read_line_optional(&format!("Type '{}' to confirm: ", file_name))?;
+        if typed.trim() != file_name && typed.trim() != vault_path {
+            println!("Deletion cancelled.");
+            return Ok(());
+        }
+    }
+    
+    let removed = VaultManager::delete(Some(vault_path), backups)?;
+    println!("✓ Deleted '{}' ({} file(s) removed)", vault_path, removed.len());
+    if config::forget_vault(vault_path)? {
+        println!("  general.default_vault was reset to the default.");
+    }
+    Ok(())
+}
+
+fn handle_calibrate(target_ms: u64, apply: bool, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    use config::{get_config, get_config_mut, save_config};
+    
+    if target_ms == 0 {
+        return Err("Target time must be greater than zero".into());
+    }
+    
+    let (memory_kb, parallelism) = {
+        let config = get_config();
+        (config.security.argon2_memory_kb, config.security.argon2_parallelism)
+    };
+    
+    println!("⏱ Benchmarking Argon2id (target: {} ms)...", target_ms);
+    let calibration = crypto::calibrate_kdf(target_ms, memory_kb, parallelism)?;
+    
+    println!("\nSuggested parameters:");
+    println!("  argon2_memory_kb:   {}", calibration.params.memory_kb);
+    println!("  argon2_time_cost:   {}", calibration.params.time_cost);
+    println!("  argon2_parallelism: {}", calibration.params.parallelism);
+    println!("  Measured unlock time: {} ms", calibration.measured_ms);
+    
+    if VaultManager::exists(Some(vault_path)) {
+        if let Ok(current) = VaultManager::kdf_params(Some(vault_path)) {
+            println!("\nCurrent vault uses: {}", current);
+        }
+    }
+    
+    if apply {
+        let mut config = get_config_mut();
+        config.security.argon2_memory_kb = calibration.params.memory_kb;
+        config.security.argon2_time_cost = calibration.params.time_cost;
+        config.security.argon2_parallelism = calibration.params.parallelism;
+        drop(config); // Release write lock before saving
+        save_config()?;
+        
+        println!("\n✓ Parameters saved to configuration.");
+        println!("  New vaults use them immediately; existing vaults adopt them on 'passman change-password'.");
+    } else {
+        println!("\nRun with --apply to save these parameters.");
+    }
+    
+    Ok(())
+}
+
+fn handle_edit(id: &str, expires: Option<&str>, copy_sequence: &[String], vault_path: &str) -> Result<(), Box<dyn Error>> {
+    let parse_expiry = |input: &str| match input {
+        "none" | "-" => Ok(None),
+        date => parse_expiry_date(date).map(Some),
+    };
+    let expires_flag = expires.map(parse_expiry).transpose()?;
+    let copy_sequence = match copy_sequence {
+        [] => None,
+        [none] if none == "none" => Some(Vec::new()),
+        specs => Some(specs.iter().map(|spec| CopyStep::parse(spec)).collect::<Result<Vec<_>, _>>()?),
+    };
+    let (mut core, _) = open_vault(vault_path)?;
+
+    let mut entry = core.get_entry(id).cloned()
+        .ok_or_else(|| format!("Entry '{}' not found!", id))?;
+
+    println!("\nEditing entry '{}' (press Enter to keep current value)", id);
+    println!("{}", "-".repeat(50));
+
+    // Edit username
+    println!("Current username: {}", entry.username);
+    let new_username = read_line_optional("New username: ")?;
+    if !new_username.is_empty() {
+        entry.username = new_username;
+    }
+
+    // Edit password
+    println!("Current password: {}", password_mask('*', config::get_config().ui.mask_length));
+    let password_choice = read_line_optional("Change password? (y/N/g for generate): ")?;
+    let new_password = match password_choice.to_lowercase().as_str() {
+        "y" | "yes" => {
+            let pwd = read_password_secure("New password: ")?;
+            print_strength(&core, &pwd);
+            Some(pwd.to_string())
+        }
+        "g" | "gen" | "generate" => {
+            // Reuse the rules this site was generated with, if any
+            let prefs = match &entry.generation {
+                Some(prefs) => {
+                    println!("Using saved generation rules ({})", prefs.describe());
+                    prefs.clone()
+                }
+                None => {
+                    let len_str = read_line_optional("Password length (default 16): ")?;
+                    let length: usize = len_str.parse().unwrap_or(16);
+                    GenerationPrefs::random(length, PasswordConfig::default())
+                }
+            };
+            let generated = prefs.generate()?;
+            entry.generation = Some(prefs);
+            println!("Generated password: {}", generated);
+            let (strength, _) = core.analyze_password(&generated);
+            println!("Password strength: {}", strength);
+            Some(generated)
+        }
+        _ => None,
+    };
+    if let Some(password) = new_password {
+        entry.password = password.into();
+        entry.holds_master_password = false;
+    }
+
+    // Edit note
+    let current_note = entry.note.clone().unwrap_or_default();
+    if !current_note.is_empty() {
+        println!("Current note: {}", current_note);
+    }
+    let new_note = read_line_optional("New note (or '-' to remove): ")?;
+    match new_note.as_str() {
+        "" => {}
+        "-" => entry.note = None,
+        _ => entry.note = Some(new_note),
+    }
+
+    // Edit expiry date
+    match expires_flag {
+        Some(expires_at) => entry.expires_at = expires_at,
+        None => {
+            if let Some(expires_at) = entry.expires_at {
+                println!("Current expiry: {}", expires_at.format("%Y-%m-%d"));
+            }
+            let new_expiry = read_line_optional("New expiry date (YYYY-MM-DD, or '-' to remove): ")?;
+            if !new_expiry.is_empty() {
+                entry.expires_at = parse_expiry(&new_expiry)?;
+            }
+        }
+    }
+
+    if let Some(copy_sequence) = copy_sequence {
+        entry.copy_sequence = copy_sequence;
+    }
+
+    // URL, tags, TOTP secret and creation date are carried over untouched
+    entry.update();
+    core.update_entry(id, entry)?;
+    core.save()?;
+
+    println!("\n✓ Entry '{}' updated successfully!", id);
+    Ok(())
+}
+
+/// Which generator `passman generate` uses
+#[derive(Clone, Copy)]
+enum GenerateMode {
+    Random,
+    Memorable,
+    Pronounceable(usize),
+}
+
+fn handle_duplicate(id: &str, new_id: Option<&str>, generate: bool, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    let (mut core, _) = open_vault(vault_path)?;
+    let new_id = core.duplicate_entry(id, new_id)?;
+
+    if generate {
+        let mut entry = core.get_entry(&new_id).cloned().ok_or("Copy not found")?;
+        // Same site, same rules
+        let prefs = entry.generation.clone().unwrap_or_else(|| config::get_config().password.generation_defaults());
+        entry.password = prefs.generate()?.into();
+        entry.generation = Some(prefs);
+        core.update_entry(&new_id, entry)?;
+    }
+    core.save()?;
+
+    println!("✓ Copied '{}' to '{}'", id, new_id);
+    if !generate {
+        println!("It has no password yet; set one with 'passman edit \"{}\"'", new_id);
+    }
+    Ok(())
+}
+
+/// Give an entry a newly generated password. With `same_rules` it is made
+/// the way the last one was; otherwise with the configured defaults (and
+/// `length`), which become the entry's rules.
+fn handle_rotate(id: &str, same_rules: bool, length: Option<usize>, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    let (mut core, _) = open_vault(vault_path)?;
+    let mut entry = core.get_entry(id).cloned().ok_or_else(|| format!("Entry '{}' not found!", id))?;
+    if entry.is_sealed() {
+        return Err(format!("'{}' is high security; unseal it before rotating its password", id).into());
+    }
+
+    let prefs = if same_rules {
+        entry.generation.clone().ok_or_else(|| {
+            format!("'{}' has no saved generation rules; rotate it once without --same-rules", id)
+        })?
+    } else {
+        let mut prefs = config::get_config().password.generation_defaults();
+        prefs.length = length.unwrap_or(prefs.length);
+        prefs
+    };
+    entry.password = prefs.generate()?.into();
+    entry.holds_master_password = false;
+    entry.generation = Some(prefs.clone());
+    entry.update();
+    core.update_entry(id, entry)?;
+    core.save()?;
+
+    println!("✓ New password for '{}' ({})", id, prefs.describe());
+    println!("Copy it with 'passman get \"{}\" --copy'", id);
+    Ok(())
+}
+
+/// Find-and-replace across one field of every entry, shown entry by entry
+/// before anything is changed; saved once at the end
+fn handle_replace(field: &str, find: &str, replace: &str, regex: bool, dry_run: bool, yes: bool, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    let field = bulk::BulkField::parse(field)?;
+    let replace = bulk::BulkReplace::new(field, find, replace, regex)?;
+    let (mut core, _) = open_vault(vault_path)?;
+
+    let changes = replace.preview(core.vault().ok_or("Vault is locked")?);
+    if changes.is_empty() {
+        println!("No entries have a {} matching '{}'.", field.as_str(), find);
+        return Ok(());
+    }
+    for change in &changes {
+        println!("{}:\n  - {}\n  + {}", change.id, change.before, change.after);
+    }
+    println!("\n{} entr{} would change.", changes.len(), if changes.len() == 1 { "y" } else { "ies" });
+    if dry_run {
+        return Ok(());
+    }
+    if !yes {
+        let confirm = read_line_optional(&format!("Update the {} of these entries? (y/N): ", field.as_str()))?;
+        if !matches!(confirm.to_lowercase().as_str(), "y" | "yes") {
+            println!("Nothing changed.");
+            return Ok(());
+        }
+    }
+
+    let applied = replace.apply(core.vault_mut().ok_or("Vault is locked")?);
+    core.save()?;
+    println!("✓ Updated {} entr{}", applied.len(), if applied.len() == 1 { "y" } else { "ies" });
+    Ok(())
+}
+
+fn handle_generate(length: usize, rules: &PasswordConfig, mode: GenerateMode, no_wait: bool) -> Result<(), Box<dyn Error>> {
+    let password = match mode {
+        GenerateMode::Random => generate_password_with_config(length, rules)?,
+        GenerateMode::Memorable => generate_memorable_password(4),
+        GenerateMode::Pronounceable(syllables) => generate_pronounceable_password(syllables),
+    };
+
+    println!("\nGenerated Password: {}", password);
+    if let GenerateMode::Pronounceable(syllables) = mode {
+        println!("Entropy: ~{:.0} bits", pronounceable_entropy_bits(syllables));
+    }
+    
+    let (strength, suggestions) = analyze_password_strength(&password);
+    println!("Strength: {}", strength);
+    print_suggestions("Note:", &suggestions);
+
+    let copy_choice = read_line_optional("\nCopy to clipboard? (y/N): ")?;
+    if copy_choice.to_lowercase() == "y" || copy_choice.to_lowercase() == "yes" {
+        copy_and_clear(&password, no_wait)?;
+    }
+
+    Ok(())
+}
+
+
+fn handle_share(args: ShareArgs, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    match args.command {
+        Some(ShareCommands::Import { file }) => handle_share_import(&file, vault_path),
+        None => {
+            let id = args.id.ok_or("Specify an entry to share, or use 'share import <file>'")?;
+            handle_share_export(&id, args.out.as_deref(), args.passphrase, args.expires_days, vault_path)
+        }
+    }
+}
+
+fn handle_share_export(
+    id: &str,
+    output: Option<&str>,
+    custom_passphrase: bool,
+    expires_days: i64,
+    vault_path: &str,
+) -> Result<(), Box<dyn Error>> {
+    use share::{ShareManager, SHARE_EXTENSION};
+    
+    let (core, _) = open_vault(vault_path)?;
+    let vault = core.vault().ok_or("Vault is locked")?;
+    
+    if vault.get_entry(id).is_none() {
+        return Err(format!("Entry '{}' not found!", id).into());
+    }
+    
+    let passphrase = if custom_passphrase {
+        let passphrase = read_password_secure("Bundle passphrase: ")?;
+        let confirm = read_password_secure("Confirm bundle passphrase: ")?;
+        if passphrase.as_str() != confirm.as_str() {
+            return Err("Passphrases do not match!".into());
+        }
+        passphrase
+    } else {
+        Zeroizing::new(ShareManager::generate_passphrase())
+    };
+    
+    let output_path = output
+        .map(|path| path.to_string())
+        .unwrap_or_else(|| format!("{}.{}", id, SHARE_EXTENSION));
+    
+    ShareManager::export_entry(vault, id, &passphrase, expires_days, &output_path)?;
+    
+    println!("✓ Share bundle for '{}' written to {}", id, output_path);
+    println!("  Expires in {} day(s).", expires_days);
+    if !custom_passphrase {
+        println!("\nPassphrase (shown once): {}", passphrase.as_str());
+    }
+    println!("⚠ Send the passphrase over a different channel than the bundle.");
+    Ok(())
+}
+
+fn handle_share_import(file: &str, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    use share::ShareManager;
+    
+    let passphrase = read_password_secure("Bundle passphrase: ")?;
+    let shared = ShareManager::read_bundle(file, &passphrase)?;
+    println!("✓ Bundle decrypted: '{}' (expires {})", shared.id, shared.expires_at.format("%Y-%m-%d"));
+    
+    let (mut core, _) = open_vault(vault_path)?;
+    
+    let mut target_id = shared.id.clone();
+    if core.get_entry(&target_id).is_some() {
+        let choice = read_line_optional(&format!(
+            "Entry '{}' already exists. [o]verwrite, [r]ename, [c]ancel (default: rename): ",
+            target_id
+        ))?;
+        match choice.to_lowercase().as_str() {
+            "o" | "overwrite" => {}
+            "c" | "cancel" => {
+                println!("Import cancelled.");
+                return Ok(());
+            }
+            _ => target_id = ShareManager::unique_id(core.vault().ok_or("Vault is locked")?, &shared.id),
+        }
+    }
+    
+    if core.get_entry(&target_id).is_some() {
+        core.update_entry(&target_id, shared.entry)?;
+    } else {
+        core.add_entry(target_id.clone(), shared.entry)?;
+    }
+    core.save()?;
+    
+    println!("✓ Shared entry imported as '{}'", target_id);
+    Ok(())
+}
+
+fn handle_template(cmd: TemplateCommands) -> Result<(), Box<dyn Error>> {
+    use config::{get_config, get_config_mut, save_config};
+    
+    match cmd {
+        TemplateCommands::Add { name, username, tags, url, note, length, exclude, leading_alpha, max } => {
+            let has_rules = length.is_some() || exclude.is_some() || leading_alpha || max.is_some();
+            let generation = has_rules.then(|| GenerationPrefs::random(
+                length.unwrap_or(get_config().password.default_length),
+                PasswordConfig {
+                    excluded_chars: exclude.unwrap_or_default(),
+                    require_leading_alpha: leading_alpha,
+                    max_length: max,
+                    ..Default::default()
+                },
+            ));
+            
+            let template = EntryTemplate {
+                username: username.unwrap_or_default(),
+                tags: tags
+                    .map(|t| t.split(',').map(|tag| tag.trim().to_string()).filter(|tag| !tag.is_empty()).collect())
+                    .unwrap_or_default(),
+                url: url.unwrap_or_default(),
+                note: note.unwrap_or_default(),
+                generation,
+            };
+            
+            get_config_mut().templates.insert(name.clone(), template);
+            save_config()?;
+            println!("✓ Template '{}' saved", name);
+        }
+        TemplateCommands::List => {
+            let config = get_config();
+            if config.templates.is_empty() {
+                println!("No templates. Create one with 'passman template add <name>'.");
+                return Ok(());
+            }
+            
+            println!("Templates:");
+            for (name, template) in &config.templates {
+                println!("  {}", name);
+                if !template.username.is_empty() {
+                    println!("    username: {}", template.username);
+                }
+                if !template.tags.is_empty() {
+                    println!("    tags: {}", template.tags.join(", "));
+                }
+                if !template.url.is_empty() {
+                    println!("    url: {}", template.url);
+                }
+                if let Some(prefs) = &template.generation {
+                    println!("    generate: {} characters", prefs.length);
+                }
+            }
+        }
+        TemplateCommands::Remove { name } => {
+            if get_config_mut().templates.remove(&name).is_none() {
+                return Err(format!("Template '{}' not found", name).into());
+            }
+            save_config()?;
+            println!("✓ Template '{}' removed", name);
+        }
+    }
+    Ok(())
+}
+
+fn handle_sync(cmd: SyncCommands, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    use sync::SyncManager;
+    
+    let (mut core, master_password) = open_vault(vault_path)?;
+    let vault = core.vault_mut().ok_or("Vault is locked")?;
+    
+    match cmd {
+        SyncCommands::Export { dir } => {
+            let report = SyncManager::export(vault, &master_password, Some(vault_path), &dir)?;
+            println!("✓ Synced {} entries to {}", vault.len(), dir);
+            println!("  {} written, {} unchanged, {} removed", report.written, report.unchanged, report.removed);
+        }
+        SyncCommands::Import { dir } => {
+            let report = SyncManager::import(vault, &master_password, Some(vault_path), &dir)?;
+            println!("✓ Merged sync directory {}", dir);
+            println!("  {} added, {} updated, {} unchanged", report.added, report.updated, report.unchanged);
+            if !report.conflicts.is_empty() {
+                println!("⚠ {} conflict(s) — remote versions kept as:", report.conflicts.len());
+                for id in &report.conflicts {
+                    println!("  - {}", id);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Ask before a plaintext export goes into a sync folder or somewhere
+/// other users can read it, pointing at the encrypted format instead
+fn confirm_export_destination(output: &str) -> Result<(), Box<dyn Error>> {
+    let Some(risk) = import_export::export_risk(output) else {
+        return Ok(());
+    };
+    eprintln!("⚠ {}", risk.message());
+    eprintln!("  '--format encrypted' writes a copy that opens only with the master password.");
+    let answer = read_line_optional("Write the plaintext export anyway? (y/N): ")?;
+    if !matches!(answer.to_lowercase().as_str(), "y" | "yes") {
+        return Err("Export cancelled".into());
+    }
+    Ok(())
+}
+
+/// `transfer export --include-high-security`: confirm, then open the
+/// high-security entries with one PIN. Those it doesn't open are left out.
+fn open_high_security(core: &PassmanCore) -> Result<OpenedEntries, Box<dyn Error>> {
+    let vault = core.vault().ok_or("Vault is locked")?;
+    if !vault.iter().any(|(_, entry)| entry.is_sealed()) {
+        return Ok(OpenedEntries::new());
+    }
+    let answer = read_line_optional("High-security passwords and notes will be written in plaintext. Continue? (y/N): ")?;
+    if !matches!(answer.to_lowercase().as_str(), "y" | "yes") {
+        return Err("Export cancelled".into());
+    }
+    let (opened, failed) = sealed::open_all(vault, &read_password_secure("PIN: ")?);
+    if !failed.is_empty() {
+        eprintln!("⚠ The PIN didn't open: {}", failed.join(", "));
+    }
+    Ok(opened)
+}
+
+fn handle_transfer(cmd: TransferCommands, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    match cmd {
+        TransferCommands::Export { output, format, include_high_security, yes } => {
+            let format = format.to_lowercase();
+            if !matches!(format.as_str(), "json" | "csv" | "keepass-xml" | "encrypted") {
+                return Err(format!("Unsupported export format: {}. Use 'json', 'csv', 'keepass-xml' or 'encrypted'.", format).into());
+            }
+            if format != "encrypted" && !yes {
+                confirm_export_destination(&output)?;
+            }
+            let (core, _) = open_vault(vault_path)?;
+            if format == "encrypted" {
+                core.export_encrypted(&output)?;
+                println!("✓ Vault exported to '{}'; it opens with the master password", output);
+                return Ok(());
+            }
+            let opened = if include_high_security { open_high_security(&core)? } else { OpenedEntries::new() };
+            
+            match format.as_str() {
+                "json" => core.export_json(&output, &opened)?,
+                "csv" => core.export_csv(&output, &opened)?,
+                _ => core.export_keepass_xml(&output, &opened)?,
+            }
+            
+            println!("✓ Vault exported to '{}' successfully!", output);
+            println!("⚠ Warning: Exported file contains unencrypted passwords. Handle with care!");
+        }
+        TransferCommands::Import { input, format, merge, strict, profile, keep_ids, max_field_chars, max_note_chars } => {
+            let mut profile = match profile {
+                Some(name) => {
+                    let mut profile = config::get_config().import_profiles.get(&name).cloned()
+                        .ok_or_else(|| format!("Import profile '{}' not found", name))?;
+                    if let Some(format) = format {
+                        profile.format = format.to_lowercase();
+                    }
+                    profile
+                }
+                None => import_export::ImportProfile::new(&format.unwrap_or_default().to_lowercase()),
+            };
+            if keep_ids {
+                profile.normalize_ids = false;
+            }
+            profile.max_field_chars = max_field_chars.unwrap_or(profile.max_field_chars);
+            profile.max_note_chars = max_note_chars.unwrap_or(profile.max_note_chars);
+            let master_password = read_master_password("Enter master password: ")?;
+            let mut core = PassmanCore::with_vault_path(vault_path);
+            
+            // Importing creates the vault if it does not exist yet
+            let report = core.import_profile(&input, &profile, &master_password, merge, strict, &mut |done, total| {
+                eprint!("\rDecrypting {}/{}", done, total);
+            });
+            if profile.format == "pass" {
+                eprintln!();
+            }
+            print_import_report(&report?);
+        }
+    }
+    
+    Ok(())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn handle_serve(_socket: Option<&str>, _token: bool, _vault_path: &str) -> Result<(), Box<dyn Error>> {
+    Err("passman serve needs unix domain sockets or Windows named pipes".into())
+}
+
+#[cfg(any(unix, windows))]
+fn handle_serve(socket: Option<&str>, token: bool, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    use std::path::PathBuf;
+    use std::time::Instant;
+
+    if !std::path::Path::new(vault_path).exists() {
+        return Err(format!("Vault '{}' not found. Run 'passman init' first.", vault_path).into());
+    }
+    let path = socket.map(PathBuf::from).unwrap_or_else(rpc::default_socket_path);
+    let listener = rpc::transport::bind(&path)?;
+
+    let token = token.then(|| Zeroizing::new(uuid::Uuid::new_v4().simple().to_string()));
+    let idle_secs = config::get_config().security.lock_timeout_secs;
+    let server = rpc::Server::new(vault_path, token.clone(), idle_secs, Instant::now());
+
+    println!("Serving {} on {} (locked; unlock with 'passman client unlock')", vault_path, path.display());
+    if let Some(token) = &token {
+        println!("Token: {}", token.as_str());
+    }
+    log_security!("rpc: serving {} on {}", vault_path, path.display());
+    rpc::transport::serve(listener, server)?;
+    Ok(())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn handle_client(_args: ClientArgs) -> Result<(), Box<dyn Error>> {
+    Err("passman client needs unix domain sockets or Windows named pipes".into())
+}
+
+#[cfg(any(unix, windows))]
+fn handle_client(args: ClientArgs) -> Result<(), Box<dyn Error>> {
+    use serde_json::{json, Map, Value};
+    use std::path::PathBuf;
+
+    let path = args.socket.map(PathBuf::from).unwrap_or_else(rpc::default_socket_path);
+    let token = args.token.map(Zeroizing::new)
+        .or_else(|| std::env::var("PASSMAN_RPC_TOKEN").ok().map(Zeroizing::new));
+    let call = |method: &str, params: Value| {
+        let params = params.as_object().cloned().unwrap_or_else(Map::new);
+        rpc::transport::call(&path, method, params, token.as_ref().map(|t| t.as_str()))
+            .map_err(|e| e.message)
+    };
+
+    match args.command {
+        ClientCommands::Status => {
+            let status = call("status", json!({}))?;
+            if status["unlocked"] == json!(true) {
+                println!("Unlocked: {} ({} entries)", status["vault"].as_str().unwrap_or_default(), status["entries"]);
+                if let Some(secs) = status["locks_in_secs"].as_u64() {
+                    println!("Locks in {}s if idle", secs);
+                }
+            } else {
+                println!("Locked: {}", status["vault"].as_str().unwrap_or_default());
             }
         }
-    }
-    
-    if vault_files.is_empty() {
-        println!("No vault files found in current directory.");
-        println!("Use 'passman init' to create a new vault.");
-    } else {
-        vault_files.sort();
-        for (i, file) in vault_files.iter().enumerate() {
-            println!("{}. {}", i + 1, file);
+        ClientCommands::Unlock => {
+            let password = read_master_password("Enter master password: ")?;
+            call("unlock", json!({ "password": password.as_str() }))?;
+            println!("✓ Vault unlocked");
+        }
+        ClientCommands::Lock => {
+            call("lock", json!({}))?;
+            println!("✓ Vault locked");
+        }
+        ClientCommands::Get { id, field } => {
+            let result = call("get", json!({ "id": id, "fields": field }))?;
+            for name in &field {
+                match &result[name.as_str()] {
+                    Value::String(value) => println!("{}", value),
+                    Value::Null => println!(),
+                    Value::Array(tags) => println!("{}", tags.iter().filter_map(Value::as_str).collect::<Vec<_>>().join(", ")),
+                    other => println!("{}", other),
+                }
+            }
         }
     }
-    
     Ok(())
 }
 
-fn handle_edit(id: &str, vault_file: Option<&str>) -> Result<(), Box<dyn Error>> {
-    let master_password = read_password_secure("Enter master password: ")?;
-    let mut vault = VaultManager::load(&master_password, vault_file)?;
+/// `passman git-credential get|store|erase`. git writes the credential to
+/// stdin and parses stdout, so nothing here prompts: `get` asks a running
+/// `passman serve` first, and otherwise the master password must come from
+/// `PASSMAN_PASSWORD` or `--password-file`.
+fn handle_git_credential(action: GitCredentialCommands, socket: Option<&str>, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    use git_credential::{CredentialRequest, GIT_TAG};
+    use logging::safe_log_id;
 
-    let entry = match vault.get_entry(id) {
-        Some(e) => e.clone(),
-        None => return Err(format!("Entry '{}' not found!", id).into()),
-    };
+    let input = Zeroizing::new(std::io::read_to_string(std::io::stdin())?);
+    let request = CredentialRequest::parse(&input)?;
 
-    println!("\nEditing entry '{}' (press Enter to keep current value)", id);
-    println!("{}", "-".repeat(50));
+    if matches!(action, GitCredentialCommands::Get) {
+        if let Some(reply) = git_credential_from_server(&request, socket) {
+            // No reply when nothing matched: git then asks the user
+            if let Some(reply) = reply {
+                print!("{}", reply.as_str());
+            }
+            return Ok(());
+        }
+    }
 
-    // Edit username
-    println!("Current username: {}", entry.username);
-    let new_username = read_line_optional("New username: ")?;
-    let username = if new_username.is_empty() { entry.username.clone() } else { new_username };
+    let master_password = provided_master_password()?.ok_or_else(|| {
+        format!("git-credential can't prompt; run 'passman serve' and unlock it, or set {} or --password-file", PASSWORD_ENV)
+    })?;
+    let mut core = PassmanCore::with_vault_path(vault_path);
+    core.unlock(&master_password)?;
+    let vault = core.vault().ok_or("Vault is locked")?;
+    let found = request.find(vault).map(|(id, entry)| (id.to_string(), entry.clone()));
+    let new_id = request.new_entry_id(vault);
 
-    // Edit password
-    println!("Current password: {}", "*".repeat(entry.password_str().len().min(16)));
-    let password_choice = read_line_optional("Change password? (y/N/g for generate): ")?;
-    let password = match password_choice.to_lowercase().as_str() {
-        "y" | "yes" => {
-            let pwd = read_password_secure("New password: ")?;
-            let (strength, suggestions) = analyze_password_strength(&pwd);
-            println!("Password strength: {}", strength);
-            if !suggestions.is_empty() {
-                println!("Suggestions:");
-                for suggestion in suggestions {
-                    println!("  • {}", suggestion);
+    match action {
+        GitCredentialCommands::Get => {
+            let Some((id, entry)) = found else {
+                return Ok(());
+            };
+            print!("{}", git_credential::reply(&entry)?.as_str());
+            log_security!("git-credential: get '{}' for {}", safe_log_id(&id), request.host);
+            // Usage counts are best-effort, as with `passman get`
+            if let Err(e) = core.record_use(&id).and_then(|()| core.save()) {
+                log::warn!("Could not record use of '{}': {}", safe_log_id(&id), e);
+            }
+        }
+        GitCredentialCommands::Store => {
+            let username = request.username.clone().ok_or("git sent no username to store")?;
+            let password = request.password.as_ref().ok_or("git sent no password to store")?;
+            match found {
+                // git stores after every successful use; only a new password is saved
+                Some((_, entry)) if entry.password_str() == password.as_str() => return Ok(()),
+                Some((id, mut entry)) => {
+                    entry.password = password.as_str().into();
+                    entry.holds_master_password = false;
+                    entry.update();
+                    core.update_entry(&id, entry)?;
+                    log_security!("git-credential: updated '{}' for {}", safe_log_id(&id), request.host);
+                }
+                None => {
+                    let mut entry = Entry::new(username, password.to_string(), None);
+                    entry.url = Some(request.url());
+                    entry.tags = vec![GIT_TAG.to_string()];
+                    core.add_entry(new_id.clone(), entry)?;
+                    log_security!("git-credential: added '{}' for {}", safe_log_id(&new_id), request.host);
                 }
             }
-            pwd.to_string()
+            core.save()?;
         }
-        "g" | "gen" | "generate" => {
-            let len_str = read_line_optional("Password length (default 16): ")?;
-            let len: usize = len_str.parse().unwrap_or(16);
-            let generated = generate_password(len);
-            println!("Generated password: {}", generated);
-            let (strength, _) = analyze_password_strength(&generated);
-            println!("Password strength: {}", strength);
-            generated
+        GitCredentialCommands::Erase => {
+            let Some((id, entry)) = found else {
+                return Ok(());
+            };
+            // Leave an entry whose password has changed since git used it
+            if request.password.as_ref().is_some_and(|password| entry.password_str() != password.as_str()) {
+                return Ok(());
+            }
+            // To the trash rather than gone, as git erases after any rejected login
+            core.vault_mut().ok_or("Vault is locked")?.trash_entry(&id, chrono::Utc::now());
+            core.save()?;
+            log_security!("git-credential: erased '{}' for {}", safe_log_id(&id), request.host);
         }
-        _ => entry.password_str().to_string(),
-    };
-
-    // Edit note
-    let current_note = entry.note.clone().unwrap_or_default();
-    if !current_note.is_empty() {
-        println!("Current note: {}", current_note);
     }
-    let new_note = read_line_optional("New note (or '-' to remove): ")?;
-    let note = match new_note.as_str() {
-        "" => entry.note.clone(),
-        "-" => None,
-        _ => Some(new_note),
-    };
-
-    // Create updated entry and save (add_entry with insert replaces existing)
-    let updated_entry = Entry::new(username, password, note);
-    vault.add_entry(id.to_string(), updated_entry);
-    VaultManager::save(&vault, &master_password, vault_file)?;
-
-    println!("\n✓ Entry '{}' updated successfully!", id);
     Ok(())
 }
 
-fn handle_generate(length: usize, symbols: bool, no_ambiguous: bool, memorable: bool) -> Result<(), Box<dyn Error>> {
-    let password = if memorable {
-        generate_memorable_password(4)
-    } else {
-        generate_password_with_options(length, symbols, !no_ambiguous)
+/// Answer `get` from a running, unlocked `passman serve`. `None` if there
+/// is no such server; `Some(None)` if it has no matching entry.
+#[cfg(any(unix, windows))]
+fn git_credential_from_server(request: &git_credential::CredentialRequest, socket: Option<&str>) -> Option<Option<Zeroizing<String>>> {
+    use serde_json::{json, Value};
+
+    let path = socket.map(std::path::PathBuf::from).unwrap_or_else(rpc::default_socket_path);
+    // Pipe names aren't files; on Windows a failed connect says the same
+    if cfg!(unix) && !path.exists() {
+        return None;
+    }
+    let token = std::env::var("PASSMAN_RPC_TOKEN").ok().map(Zeroizing::new);
+    let call = |method: &str, params: Value| {
+        let params = params.as_object().cloned().unwrap_or_default();
+        rpc::transport::call(&path, method, params, token.as_ref().map(|t| t.as_str())).ok()
     };
 
-    println!("\nGenerated Password: {}", password);
-    
-    let (strength, suggestions) = analyze_password_strength(&password);
-    println!("Strength: {}", strength);
-    
-    if !suggestions.is_empty() {
-        println!("Note:");
-        for suggestion in suggestions {
-            println!("  • {}", suggestion);
+    // `list` has no secrets; rebuild enough of each entry to match locally
+    let listed = call("list", json!({}))?;
+    let mut vault = model::Vault::new();
+    for item in listed.as_array()? {
+        let (Some(id), Some(username)) = (item["id"].as_str(), item["username"].as_str()) else {
+            continue;
+        };
+        let mut entry = Entry::new(username.to_string(), String::new(), None);
+        entry.url = item["url"].as_str().map(str::to_string);
+        entry.tags = item["tags"].as_array().into_iter().flatten().filter_map(Value::as_str).map(str::to_string).collect();
+        if let Some(modified) = item["modified_at"].as_str().and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok()) {
+            entry.modified_at = modified.with_timezone(&chrono::Utc);
         }
+        vault.add_entry(id.to_string(), entry);
     }
+    let Some((id, _)) = request.find(&vault) else {
+        return Some(None);
+    };
 
-    let copy_choice = read_line_optional("\nCopy to clipboard? (y/N): ")?;
-    if copy_choice.to_lowercase() == "y" || copy_choice.to_lowercase() == "yes" {
-        copy_to_clipboard(&password)?;
-        println!("✓ Password copied to clipboard!");
-    }
+    let fields = call("get", json!({ "id": id, "fields": ["username", "password"] }))?;
+    let entry = Entry::new(fields["username"].as_str()?.to_string(), fields["password"].as_str()?.to_string(), None);
+    Some(git_credential::reply(&entry).ok())
+}
 
-    Ok(())
+#[cfg(not(any(unix, windows)))]
+fn git_credential_from_server(_request: &git_credential::CredentialRequest, _socket: Option<&str>) -> Option<Option<Zeroizing<String>>> {
+    None
 }
 
-fn generate_password_with_options(length: usize, include_symbols: bool, include_ambiguous: bool) -> String {
-    use rand::seq::SliceRandom;
-    
-    let mut charset: Vec<char> = Vec::new();
-    
-    // Lowercase
-    charset.extend('a'..='z');
-    // Uppercase
-    charset.extend('A'..='Z');
-    // Digits
-    charset.extend('0'..='9');
-    
-    if include_symbols {
-        charset.extend("!@#$%^&*()_+-=[]{}|;:,.<>?".chars());
+fn handle_version(check: bool) -> Result<(), Box<dyn Error>> {
+    let current = env!("CARGO_PKG_VERSION");
+    println!("passman {}", current);
+    if !check {
+        return Ok(());
     }
-    
-    if !include_ambiguous {
-        // Remove ambiguous characters
-        let ambiguous = ['0', 'O', 'o', '1', 'l', 'I', '|'];
-        charset.retain(|c| !ambiguous.contains(c));
+    if !cfg!(feature = "update-check") {
+        return Err("This build has no update check (build with --features update-check)".into());
     }
     
-    let mut rng = rand::thread_rng();
-    (0..length)
-        .map(|_| *charset.choose(&mut rng).unwrap())
-        .collect()
-}
-
-fn generate_memorable_password(word_count: usize) -> String {
-    use rand::seq::SliceRandom;
-    use rand::Rng;
-    
-    let words = vec![
-        "apple", "banana", "cherry", "dragon", "eagle", "falcon", "garden", "harbor",
-        "island", "jungle", "knight", "lemon", "mountain", "nebula", "ocean", "phoenix",
-        "quartz", "river", "sunset", "thunder", "umbrella", "valley", "winter", "xenon",
-        "yellow", "zenith", "anchor", "bridge", "castle", "diamond", "empire", "forest",
-        "glacier", "horizon", "ivory", "jasmine", "kingdom", "lantern", "marble", "neptune",
-        "orchid", "palace", "quantum", "rainbow", "silver", "tornado", "universe", "volcano",
-    ];
-    
-    let mut rng = rand::thread_rng();
-    let mut result = Vec::new();
-    
-    for _ in 0..word_count {
-        let word = words.choose(&mut rng).unwrap();
-        // Capitalize first letter
-        let capitalized: String = word.chars().enumerate()
-            .map(|(i, c)| if i == 0 { c.to_uppercase().next().unwrap() } else { c })
-            .collect();
-        result.push(capitalized);
+    let manifest = update::check_now(&net::NetPolicy::current())?;
+    match manifest.notice_for(current) {
+        Some(notice) if notice.security => {
+            println!("⚠ Passman {} is available and fixes security issues in this version.", notice.latest);
+            println!("  Release notes: {}", notice.notes_url);
+        }
+        Some(notice) => {
+            println!("Passman {} is available.", notice.latest);
+            println!("  Release notes: {}", notice.notes_url);
+        }
+        None => println!("✓ You are on the latest release ({}).", manifest.version),
     }
-    
-    // Add a random number
-    let num: u16 = rng.gen_range(10..100);
-    result.push(num.to_string());
-    
-    // Add a random symbol
-    let symbols = ['!', '@', '#', '$', '%', '&', '*'];
-    let symbol = symbols.choose(&mut rng).unwrap();
-    
-    result.join("-") + &symbol.to_string()
+    Ok(())
 }
 
-fn handle_transfer(cmd: TransferCommands, vault_file: Option<&str>) -> Result<(), Box<dyn Error>> {
-    use import_export::ImportExportManager;
+/// Summary of an import, with one table row per malformed record
+fn print_import_report(report: &import_export::ImportReport) {
+    for id in &report.skipped {
+        println!("⚠ Skipping existing entry: {}", id);
+    }
+    for (id, new_id) in &report.renamed {
+        println!("⚠ '{}' already existed; imported as '{}'", id, new_id);
+    }
     
-    match cmd {
-        TransferCommands::Export { output, format } => {
-            let master_password = read_password_secure("Enter master password: ")?;
-            let vault = VaultManager::load(&master_password, vault_file)?;
-            
-            match format.to_lowercase().as_str() {
-                "json" => {
-                    ImportExportManager::export_json(&vault, &output)?;
-                }
-                "csv" => {
-                    ImportExportManager::export_csv(&vault, &output)?;
-                }
-                _ => return Err(format!("Unsupported export format: {}. Use 'json' or 'csv'.", format).into()),
-            }
-            
-            println!("✓ Vault exported to '{}' successfully!", output);
-            println!("⚠ Warning: Exported file contains unencrypted passwords. Handle with care!");
-        }
-        TransferCommands::Import { input, format, merge } => {
-            let master_password = read_password_secure("Enter master password: ")?;
-            
-            // The import functions handle vault creation/loading internally
-            match format.to_lowercase().as_str() {
-                "json" => {
-                    ImportExportManager::import_json(&input, &master_password, vault_file, merge)?;
-                }
-                "csv" => {
-                    ImportExportManager::import_csv(&input, &master_password, vault_file, merge)?;
-                }
-                "chrome" | "firefox" => {
-                    ImportExportManager::import_browser(&input, &master_password, vault_file, &format.to_lowercase(), merge)?;
-                }
-                _ => return Err(format!("Unsupported import format: {}. Use 'json', 'csv', 'chrome', or 'firefox'.", format).into()),
-            }
+    println!("✓ Import completed:");
+    println!("  - Imported: {} entries", report.imported);
+    if !report.skipped.is_empty() {
+        println!("  - Skipped: {} existing entries", report.skipped.len());
+    }
+    if !report.replaced.is_empty() {
+        println!("  - Replaced: {} existing entries", report.replaced.len());
+    }
+    if !report.moved_to_attachments.is_empty() {
+        println!("  - Oversize fields moved to attachments: {} entries", report.moved_to_attachments.len());
+        for (id, fields) in &report.moved_to_attachments {
+            println!("      {} ({})", id, fields.join(", "));
         }
     }
+    if report.errors.is_empty() {
+        return;
+    }
     
-    Ok(())
+    println!("  - Not imported: {} malformed rows", report.errors.len());
+    println!();
+    println!("{:<12} Reason", "Row");
+    println!("{}", "-".repeat(50));
+    for error in &report.errors {
+        let row = match error.line {
+            Some(line) => format!("line {}", line),
+            None => format!("entry {}", error.row),
+        };
+        println!("{:<12} {}", row, error.reason);
+    }
+    println!();
+    println!("Fix these rows and import the file again with --merge to add them.");
 }
 
-fn handle_config(cmd: ConfigCommands) -> Result<(), Box<dyn Error>> {
+fn handle_config(cmd: ConfigCommands, vault_path: &str, vault_source: config::VaultPathSource) -> Result<(), Box<dyn Error>> {
     use config::{get_config, get_config_mut, save_config, reload_config, Config as AppConfig};
     
     match cmd {
@@ -604,6 +2207,10 @@ fn handle_config(cmd: ConfigCommands) -> Result<(), Box<dyn Error>> {
             
             println!("\n[General]");
             println!("  default_vault: {}", config.general.default_vault);
+            println!("  effective vault: {} (from {})", vault_path, vault_source);
+            println!("  autosave: {}", config.general.autosave);
+            println!("  check_updates: {}", config.general.check_updates);
+            println!("  identity: {}", if config.general.identity.is_empty() { "(not set)" } else { &config.general.identity });
             
             println!("\n[Security]");
             println!("  lock_timeout_secs: {} ({})", 
@@ -613,6 +2220,17 @@ fn handle_config(cmd: ConfigCommands) -> Result<(), Box<dyn Error>> {
             println!("  clear_clipboard_on_lock: {}", config.security.clear_clipboard_on_lock);
             println!("  max_failed_attempts: {}", config.security.max_failed_attempts);
             println!("  min_password_length: {}", config.security.min_password_length);
+            println!("  argon2_memory_kb: {}", config.security.argon2_memory_kb);
+            println!("  argon2_time_cost: {}", config.security.argon2_time_cost);
+            println!("  argon2_parallelism: {}", config.security.argon2_parallelism);
+            println!("  cipher: {}", config.security.cipher);
+            println!("  lock_timeout_behavior: {}", config.security.lock_timeout_behavior);
+            println!("  protected_tag: {}", config.security.protected_tag);
+            println!("  suppress_master_strength_warning: {}", config.security.suppress_master_strength_warning);
+            println!("  reveal_requires_password: {}", config.security.reveal_requires_password);
+            println!("  strict_permissions: {}", config.security.strict_permissions);
+            println!("  store_plain_metadata: {}", config.security.store_plain_metadata);
+            println!("  quiet_sync_folder_vaults: {}", config.security.quiet_sync_folder_vaults.join(", "));
             
             println!("\n[Password Generation]");
             println!("  default_length: {}", config.password.default_length);
@@ -621,16 +2239,57 @@ fn handle_config(cmd: ConfigCommands) -> Result<(), Box<dyn Error>> {
             println!("  include_numbers: {}", config.password.include_numbers);
             println!("  include_symbols: {}", config.password.include_symbols);
             println!("  exclude_ambiguous: {}", config.password.exclude_ambiguous);
+            println!("  pronounceable_syllables: {}", config.password.pronounceable_syllables);
             
             println!("\n[UI]");
             println!("  theme: {}", config.ui.theme);
             println!("  show_password_strength: {}", config.ui.show_password_strength);
+            println!("  search_notes: {}", config.ui.search_notes);
+            println!("  default_sort: {}", config.ui.default_sort);
+            println!("  group_by_site: {}", config.ui.group_by_site);
+            println!("  clipboard_command: {}", if config.ui.clipboard_command.is_empty() { "(OSC 52)" } else { &config.ui.clipboard_command });
+            println!("  mask_length: {}", config.ui.mask_length);
+            println!("  hold_to_reveal: {}", config.ui.hold_to_reveal);
+            println!("  reveal_timeout_secs: {}", config.ui.reveal_timeout_secs);
+            println!("  open_copy_delay_secs: {}", config.ui.open_copy_delay_secs);
+            println!("  animations: {}", config.ui.animations);
+            println!("  suggestion_detail: {}", config.ui.suggestion_detail);
+            for (name, query) in &config.ui.saved_searches {
+                println!("  saved search {}: {}", name, query);
+            }
             
             println!("\n[Backup]");
             println!("  auto_backup: {}", config.backup.auto_backup);
             println!("  max_backups: {}", config.backup.max_backups);
+            
+            println!("\n[Health]");
+            println!("  banned_password_file: {}", if config.health.banned_password_file.is_empty() { "(none)" } else { &config.health.banned_password_file });
+            
+            println!("\n[Trash]");
+            println!("  retention_days: {}", if config.trash.retention_days == 0 { "0 (keep)".to_string() } else { config.trash.retention_days.to_string() });
+            
+            println!("\n[Network]");
+            println!("  offline: {}", config.network.offline);
+            println!("  proxy: {}", if config.network.proxy.is_empty() { "(from environment)" } else { &config.network.proxy });
+            println!("  timeout_secs: {}", config.network.timeout_secs);
+            println!("  retries: {}", config.network.retries);
+            
+            if !config.import_profiles.is_empty() {
+                println!("\n[Import Profiles]");
+                for (name, profile) in &config.import_profiles {
+                    println!("  {}: {} (on conflict: {})", name, profile.format, profile.conflict.as_str());
+                }
+            }
         }
         ConfigCommands::Set { key, value } => {
+            // Per vault: applies to the active one
+            if matches!(key.to_lowercase().as_str(), "security.sync_folder_warning" | "sync_folder_warning") {
+                let enabled = value.parse().map_err(|_| format!("Invalid boolean: {}", value))?;
+                VaultManager::set_sync_folder_warning(Some(vault_path), enabled)?;
+                println!("✓ Sync folder warning {} for {}", if enabled { "on" } else { "off" }, vault_path);
+                return Ok(());
+            }
+            
             let mut config = get_config_mut();
             
             match key.to_lowercase().as_str() {
@@ -642,6 +2301,31 @@ fn handle_config(cmd: ConfigCommands) -> Result<(), Box<dyn Error>> {
                     config.security.clipboard_timeout_secs = value.parse()
                         .map_err(|_| format!("Invalid number: {}", value))?;
                 }
+                "security.lock_timeout_behavior" | "lock_timeout_behavior" => {
+                    config.security.lock_timeout_behavior = value.parse()?;
+                }
+                "security.cipher" | "cipher" => {
+                    config.security.cipher = value.parse()?;
+                }
+                "security.protected_tag" | "protected_tag" => {
+                    config.security.protected_tag = value.trim().to_string();
+                }
+                "security.suppress_master_strength_warning" | "suppress_master_strength_warning" => {
+                    config.security.suppress_master_strength_warning = value.parse()
+                        .map_err(|_| format!("Invalid boolean: {}", value))?;
+                }
+                "security.reveal_requires_password" | "reveal_requires_password" => {
+                    config.security.reveal_requires_password = value.parse()
+                        .map_err(|_| format!("Invalid boolean: {}", value))?;
+                }
+                "security.strict_permissions" | "strict_permissions" => {
+                    config.security.strict_permissions = value.parse()
+                        .map_err(|_| format!("Invalid boolean: {}", value))?;
+                }
+                "security.store_plain_metadata" | "store_plain_metadata" => {
+                    config.security.store_plain_metadata = value.parse()
+                        .map_err(|_| format!("Invalid boolean: {}", value))?;
+                }
                 "security.max_failed_attempts" | "max_attempts" => {
                     config.security.max_failed_attempts = value.parse()
                         .map_err(|_| format!("Invalid number: {}", value))?;
@@ -662,9 +2346,58 @@ fn handle_config(cmd: ConfigCommands) -> Result<(), Box<dyn Error>> {
                     config.password.exclude_ambiguous = value.parse()
                         .map_err(|_| format!("Invalid boolean: {}", value))?;
                 }
+                "password.pronounceable_syllables" | "syllables" => {
+                    config.password.pronounceable_syllables = value.parse()
+                        .ok()
+                        .filter(|count| (1..=24).contains(count))
+                        .ok_or("Invalid syllable count (1-24)")?;
+                }
                 "ui.theme" | "theme" => {
                     config.ui.theme = value.clone();
                 }
+                "ui.search_notes" | "search_notes" => {
+                    config.ui.search_notes = value.parse()
+                        .map_err(|_| format!("Invalid boolean: {}", value))?;
+                }
+                "ui.default_sort" | "default_sort" => {
+                    let sort = model::EntrySort::parse(&value)
+                        .ok_or("Invalid sort order. Use: name, created, modified, recent, frequent")?;
+                    config.ui.default_sort = sort.as_str().to_string();
+                }
+                "ui.group_by_site" | "group_by_site" => {
+                    config.ui.group_by_site = value.parse()
+                        .map_err(|_| format!("Invalid boolean: {}", value))?;
+                }
+                "ui.clipboard_command" | "clipboard_command" => {
+                    config.ui.clipboard_command = value.trim().to_string();
+                }
+                "ui.mask_length" | "mask_length" => {
+                    config.ui.mask_length = value.parse()
+                        .ok()
+                        .filter(|length| (1..=64).contains(length))
+                        .ok_or("Invalid mask length (1-64)")?;
+                }
+                "ui.hold_to_reveal" | "hold_to_reveal" => {
+                    config.ui.hold_to_reveal = value.parse()
+                        .map_err(|_| format!("Invalid boolean: {}", value))?;
+                }
+                "ui.reveal_timeout_secs" | "reveal_timeout" => {
+                    config.ui.reveal_timeout_secs = value.parse()
+                        .map_err(|_| format!("Invalid number: {}", value))?;
+                }
+                "ui.open_copy_delay_secs" | "open_copy_delay" => {
+                    config.ui.open_copy_delay_secs = value.parse()
+                        .map_err(|_| format!("Invalid number: {}", value))?;
+                }
+                "ui.animations" | "animations" => {
+                    if !["full", "reduced", "off", "system"].contains(&value.as_str()) {
+                        return Err("Invalid animations setting. Use: full, reduced, off, system".into());
+                    }
+                    config.ui.animations = value.clone();
+                }
+                "ui.suggestion_detail" | "suggestion_detail" => {
+                    config.ui.suggestion_detail = value.parse()?;
+                }
                 "backup.auto_backup" | "auto_backup" => {
                     config.backup.auto_backup = value.parse()
                         .map_err(|_| format!("Invalid boolean: {}", value))?;
@@ -673,9 +2406,48 @@ fn handle_config(cmd: ConfigCommands) -> Result<(), Box<dyn Error>> {
                     config.backup.max_backups = value.parse()
                         .map_err(|_| format!("Invalid number: {}", value))?;
                 }
+                "health.banned_password_file" | "banned_password_file" => {
+                    if !value.is_empty() && !std::path::Path::new(&value).is_file() {
+                        return Err(format!("Banned password file not found: {}", value).into());
+                    }
+                    config.health.banned_password_file = value.clone();
+                }
+                "trash.retention_days" | "retention_days" => {
+                    config.trash.retention_days = value.parse()
+                        .map_err(|_| format!("Invalid number: {}", value))?;
+                }
                 "general.default_vault" | "default_vault" => {
                     config.general.default_vault = value.clone();
                 }
+                "general.autosave" | "autosave" => {
+                    config.general.autosave = value.parse()
+                        .map_err(|_| format!("Invalid boolean: {}", value))?;
+                }
+                "general.check_updates" | "check_updates" => {
+                    config.general.check_updates = value.parse()
+                        .map_err(|_| format!("Invalid boolean: {}", value))?;
+                }
+                "general.identity" | "identity" => {
+                    config.general.identity = value.trim().to_string();
+                }
+                "network.offline" | "offline" => {
+                    config.network.offline = value.parse()
+                        .map_err(|_| format!("Invalid boolean: {}", value))?;
+                }
+                "network.proxy" | "proxy" => {
+                    if !value.is_empty() {
+                        net::Target::parse(&value).map_err(|e| format!("Invalid proxy URL: {}", e))?;
+                    }
+                    config.network.proxy = value.clone();
+                }
+                "network.timeout_secs" | "timeout_secs" => {
+                    config.network.timeout_secs = value.parse()
+                        .map_err(|_| format!("Invalid number: {}", value))?;
+                }
+                "network.retries" | "retries" => {
+                    config.network.retries = value.parse()
+                        .map_err(|_| format!("Invalid number: {}", value))?;
+                }
                 _ => {
                     return Err(format!("Unknown configuration key: {}", key).into());
                 }
@@ -683,6 +2455,10 @@ fn handle_config(cmd: ConfigCommands) -> Result<(), Box<dyn Error>> {
             
             drop(config); // Release write lock before saving
             save_config()?;
+            if key.to_lowercase().ends_with("store_plain_metadata") && !get_config().security.store_plain_metadata {
+                // Turning badges off also removes what was stored for them
+                VaultManager::forget_all_stats(vault_path);
+            }
             println!("✓ Configuration updated: {} = {}", key, value);
         }
         ConfigCommands::Reset => {
@@ -716,40 +2492,292 @@ fn format_duration(secs: u64) -> String {
     }
 }
 
-fn handle_backup(vault_file: Option<&str>, output: Option<&str>) -> Result<(), Box<dyn Error>> {
+fn handle_diff(vault_path: &str, other: &str, show_secrets: bool, output: &str) -> Result<(), Box<dyn Error>> {
+    use diff::VaultDiff;
+    
+    let json = match output.to_lowercase().as_str() {
+        "text" => false,
+        "json" => true,
+        other => return Err(format!("Unsupported output format: {}", other).into()),
+    };
+    let password_a = read_master_password(&format!("Master password for {}: ", vault_path))?;
+    let mut core_a = PassmanCore::with_vault_path(vault_path);
+    core_a.unlock(&password_a)?;
+    
+    // The other file may use a different password; Enter reuses the first
+    let password_b = read_password_secure(&format!("Master password for {} (Enter to reuse): ", other))?;
+    let password_b = if password_b.is_empty() { password_a } else { password_b };
+    let mut core_b = PassmanCore::with_vault_path(other);
+    core_b.unlock(&password_b)?;
+    
+    let (Some(vault_a), Some(vault_b)) = (core_a.vault(), core_b.vault()) else {
+        return Err("Vault is locked".into());
+    };
+    let mut diff = VaultDiff::compare(vault_a, vault_b);
+    if !show_secrets {
+        diff.redact_secrets();
+    }
+    
+    if json {
+        let report = serde_json::json!({
+            "a": vault_path,
+            "b": other,
+            "diff": diff,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    
+    println!("\nA: {}", vault_path);
+    println!("B: {}", other);
+    println!("{}", "-".repeat(50));
+    
+    if diff.is_empty() {
+        println!("✓ No differences ({} entries)", diff.unchanged);
+        return Ok(());
+    }
+    
+    if !diff.only_in_a.is_empty() {
+        println!("\nOnly in A ({}):", diff.only_in_a.len());
+        for id in &diff.only_in_a {
+            println!("  - {}", id);
+        }
+    }
+    if !diff.only_in_b.is_empty() {
+        println!("\nOnly in B ({}):", diff.only_in_b.len());
+        for id in &diff.only_in_b {
+            println!("  + {}", id);
+        }
+    }
+    if !diff.modified.is_empty() {
+        println!("\nModified ({}):", diff.modified.len());
+        for entry in &diff.modified {
+            println!("  ~ {}", entry.id);
+            for change in &entry.changes {
+                if change.secret && !show_secrets {
+                    println!("      {}: changed", change.field);
+                } else {
+                    println!(
+                        "      {}: {} → {}",
+                        change.field,
+                        change.a.as_deref().unwrap_or("(none)"),
+                        change.b.as_deref().unwrap_or("(none)")
+                    );
+                }
+            }
+        }
+    }
+    
+    println!(
+        "\n{} only in A, {} only in B, {} modified, {} unchanged",
+        diff.only_in_a.len(), diff.only_in_b.len(), diff.modified.len(), diff.unchanged
+    );
+    Ok(())
+}
+
+fn handle_changes(args: ChangesArgs, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    if let Some(ChangesCommands::Mark) = args.command {
+        let (core, _) = open_vault(vault_path)?;
+        let marker = core.mark_changes()?;
+        println!("✓ Marked {} entries as of {}", core.entry_count(), marker.marked_at.to_rfc3339());
+        return Ok(());
+    }
+    
+    let json = match args.output.to_lowercase().as_str() {
+        "text" => false,
+        "json" => true,
+        other => return Err(format!("Unsupported output format: {}", other).into()),
+    };
+    let since = changes::Since::parse(&args.since)?;
+    let (core, _) = open_vault(vault_path)?;
+    if args.include_secrets && !args.yes {
+        let answer = read_line_optional("Changed passwords and TOTP secrets will be printed in plaintext. Continue? (y/N): ")?;
+        if !matches!(answer.to_lowercase().as_str(), "y" | "yes") {
+            return Err("Cancelled".into());
+        }
+    }
+    let (since, entries) = core.changes(since, args.include_secrets)?;
+    if args.include_secrets {
+        log_security!("changes: printed values of {} changed entries of {}", entries.len(), vault_path);
+    }
+    
+    if json {
+        let report = serde_json::json!({
+            "since": since,
+            "changes": entries,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    
+    if entries.is_empty() {
+        println!("No entries changed since {}", since.to_rfc3339());
+        return Ok(());
+    }
+    println!("Changed since {} ({}):", since.to_rfc3339(), entries.len());
+    for change in &entries {
+        let fields = match &change.fields {
+            Some(fields) if fields.is_empty() => "no tracked fields".to_string(),
+            Some(fields) => fields.join(", "),
+            None => "fields unknown".to_string(),
+        };
+        println!("  {} {} ({})", if change.added { "+" } else { "~" }, change.id, fields);
+        for (field, value) in change.values.iter().flatten() {
+            println!("      {}: {}", field, value.as_deref().unwrap_or("(unset)"));
+        }
+    }
+    if entries.iter().any(|change| change.fields.is_none()) {
+        println!("\nFields are compared with the last 'passman changes mark' taken with this master password; there is none.");
+    }
+    Ok(())
+}
+
+fn handle_backup(args: BackupArgs, vault_path: &str) -> Result<(), Box<dyn Error>> {
+    match args.command {
+        Some(BackupCommands::List) => return handle_backup_list(vault_path),
+        Some(BackupCommands::Restore { file, force, merge }) => return handle_backup_restore(vault_path, &file, force, merge),
+        None => {}
+    }
+    
     // Verify vault exists and password is correct
-    let master_password = read_password_secure("Enter master password: ")?;
-    let _ = VaultManager::load(&master_password, vault_file)?;
+    let (core, master_password) = open_vault(vault_path)?;
     
     // Create backup - if custom output provided, copy to that path
-    let backup_path = if let Some(custom_path) = output {
-        // Use custom path
-        let vault_path = vault_file.unwrap_or("vault.dat");
-        std::fs::copy(vault_path, custom_path)?;
+    let backup_path = if let Some(custom_path) = args.output.as_deref() {
+        std::fs::copy(core.vault_path(), custom_path)?;
+        vault::VaultManager::load(&master_password, Some(custom_path))
+            .map_err(|e| format!("Backup {} failed verification: {}", custom_path, e))?;
         custom_path.to_string()
     } else {
         // Use default timestamped backup
-        VaultManager::create_backup(vault_file)?
+        core.create_backup(&master_password)?
     };
     
-    println!("✓ Backup created: {}", backup_path);
+    println!("✓ Backup created and verified: {}", backup_path);
+    Ok(())
+}
+
+/// Show the recorded verification of each backup; nothing is decrypted
+fn handle_backup_list(vault_path: &str) -> Result<(), Box<dyn Error>> {
+    let backups = vault::VaultManager::backup_infos(Some(vault_path));
+    if backups.is_empty() {
+        println!("No backups found for {}. Create one with 'passman backup'.", vault_path);
+        return Ok(());
+    }
+    
+    println!("{:<2} {:<40} {:<19} {:>10} {:>8}", "", "File", "Created (UTC)", "Size", "Entries");
+    for backup in &backups {
+        let marker = match backup.verified {
+            Some(true) => "✓",
+            Some(false) => "✗",
+            None => "?",
+        };
+        let name = std::path::Path::new(&backup.path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| backup.path.clone());
+        let created = backup.created
+            .map(|created| created.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let entries = backup.entries.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string());
+        println!("{:<2} {:<40} {:<19} {:>10} {:>8}", marker, name, created, format_size(backup.size), entries);
+    }
+    println!("\n✓ verified  ✗ failed or changed since verification  ? never checked");
+    Ok(())
+}
+
+fn handle_backup_restore(vault_path: &str, file: &str, force: bool, merge: bool) -> Result<(), Box<dyn Error>> {
+    if !std::path::Path::new(file).exists() {
+        return Err(format!("Backup not found: {}", file).into());
+    }
+    let (mut core, master_password) = open_vault(vault_path)?;
+    
+    // A backup that doesn't decrypt can't be compared, only force-restored
+    let diff = match core.compare_backup(&master_password, file) {
+        Ok(diff) => Some(diff),
+        Err(e) if force => {
+            println!("⚠ Cannot compare with {}: {}", file, e);
+            None
+        }
+        Err(e) if merge => return Err(e.into()),
+        Err(e) => return Err(format!("{}. Use --force to restore it anyway.", e).into()),
+    };
+    
+    let merge = match diff.as_ref().and_then(diff::VaultDiff::restore_warning) {
+        Some(warning) if !merge => {
+            println!("⚠ {}.", warning);
+            loop {
+                let choice = read_line_optional("Restore anyway, merge (keep the newer copy of each entry), list entries, or cancel? (r/m/l/N): ")?;
+                match choice.to_lowercase().as_str() {
+                    "r" | "restore" => break false,
+                    "m" | "merge" => break true,
+                    "l" | "list" => print_restore_changes(diff.as_ref().ok_or("No comparison available")?),
+                    _ => {
+                        println!("Restore cancelled.");
+                        return Ok(());
+                    }
+                }
+            }
+        }
+        _ => {
+            let verb = if merge { "Merge" } else { "Replace" };
+            let confirm = read_line_optional(&format!("{} {} with {}? (y/N): ", verb, vault_path, file))?;
+            if !matches!(confirm.to_lowercase().as_str(), "y" | "yes") {
+                println!("Restore cancelled.");
+                return Ok(());
+            }
+            merge
+        }
+    };
+    
+    if merge {
+        let safety = core.merge_backup(&master_password, file)?;
+        println!("✓ Merged {} into the vault (previous vault saved as {})", file, safety);
+        return Ok(());
+    }
+    
+    let safety = core.restore_backup(&master_password, file, force).map_err(|e| {
+        if force { e.to_string() } else { format!("{}. Use --force to restore it anyway.", e) }
+    })?;
+    println!("✓ Restored {} (previous vault saved as {})", file, safety);
+    if !core.is_authenticated() {
+        println!("⚠ The restored vault does not open with the current master password.");
+    }
     Ok(())
 }
 
-fn handle_health(vault_file: Option<&str>, verbose: bool, issues_only: bool) -> Result<(), Box<dyn Error>> {
-    use health::{PasswordHealthAnalyzer, PasswordHealth};
+/// Per-entry effect of restoring a backup, with the live vault as A
+fn print_restore_changes(diff: &diff::VaultDiff) {
+    let date = |at: chrono::DateTime<chrono::Utc>| at.format("%Y-%m-%d %H:%M").to_string();
+    for id in &diff.only_in_a {
+        println!("  - {} (added after the backup; removed)", id);
+    }
+    for entry in diff.newer_in_a() {
+        let fields: Vec<&str> = entry.changes.iter().map(|change| change.field).collect();
+        println!(
+            "  ~ {} (changed {}, backup copy from {}; {} reverted)",
+            entry.id, date(entry.a_modified_at), date(entry.b_modified_at), fields.join(", "),
+        );
+    }
+    for entry in diff.newer_in_b() {
+        println!("  ↑ {} (backup copy is newer; restored)", entry.id);
+    }
+    for id in &diff.only_in_b {
+        println!("  + {} (deleted after the backup; brought back)", id);
+    }
+}
+
+fn handle_health(vault_path: &str, verbose: bool, issues_only: bool) -> Result<(), Box<dyn Error>> {
+    use health::PasswordHealth;
     
-    let master_password = read_password_secure("Enter master password: ")?;
-    let vault = VaultManager::load(&master_password, vault_file)?;
+    let (core, _) = open_vault(vault_path)?;
     
-    if vault.is_empty() {
+    if core.is_empty() {
         println!("No entries in vault to analyze.");
         return Ok(());
     }
     
-    let analyzer = PasswordHealthAnalyzer::new();
-    let reports = analyzer.analyze_vault(&vault);
-    let summary = analyzer.generate_summary(&reports);
+    let (reports, summary) = core.analyze_health().ok_or("Vault is locked")?;
     
     // Print header
     println!("\n🔐 Password Health Report");
@@ -810,6 +2838,9 @@ fn handle_health(vault_file: Option<&str>, verbose: bool, issues_only: bool) ->
                     println!("   → {}", rec);
                 }
             }
+            for suggestion in config::get_config().ui.suggestion_detail.select(&report.suggestions) {
+                println!("     • {}", suggestion);
+            }
             println!();
         }
     }
@@ -829,15 +2860,15 @@ fn handle_health(vault_file: Option<&str>, verbose: bool, issues_only: bool) ->
     Ok(())
 }
 
-fn handle_change_password(vault_file: Option<&str>) -> Result<(), Box<dyn Error>> {
+fn handle_change_password(vault_path: &str) -> Result<(), Box<dyn Error>> {
     println!("🔐 Change Master Password");
     println!("{}", "-".repeat(40));
     
     // Verify current password
-    let current_password = read_password_secure("Enter current master password: ")?;
-    
-    // Load vault to verify password
-    let _ = VaultManager::load(&current_password, vault_file)?;
+    warn_if_substituted(vault_path);
+    let current_password = read_master_password("Enter current master password: ")?;
+    let mut core = PassmanCore::with_vault_path(vault_path);
+    core.unlock(&current_password)?;
     println!("✓ Current password verified");
     
     // Get new password
@@ -848,19 +2879,12 @@ fn handle_change_password(vault_file: Option<&str>) -> Result<(), Box<dyn Error>
         return Err("New passwords do not match!".into());
     }
     
-    if new_password.len() < 8 {
-        return Err("New password must be at least 8 characters long!".into());
-    }
-    
     // Check password strength
-    let (strength, suggestions) = analyze_password_strength(&new_password);
+    let (strength, suggestions) = core.analyze_password(&new_password);
     println!("\nNew password strength: {:?}", strength);
     
     if !suggestions.is_empty() {
-        println!("Suggestions for improvement:");
-        for suggestion in &suggestions {
-            println!("  • {}", suggestion);
-        }
+        print_suggestions("Suggestions for improvement:", &suggestions);
         
         let proceed = read_line_optional("\nProceed anyway? (y/N): ")?;
         if proceed.to_lowercase() != "y" && proceed.to_lowercase() != "yes" {
@@ -871,11 +2895,11 @@ fn handle_change_password(vault_file: Option<&str>) -> Result<(), Box<dyn Error>
     
     // Create backup before changing
     println!("\nCreating backup before password change...");
-    let backup_path = VaultManager::create_backup(vault_file)?;
+    let backup_path = core.create_backup(&current_password)?;
     println!("✓ Backup created: {}", backup_path);
     
-    // Change the password
-    VaultManager::change_password(&current_password, &new_password, vault_file)?;
+    // Change the password; the core rejects passwords below the configured minimum
+    core.change_password(&current_password, &new_password)?;
     
     println!("\n✓ Master password changed successfully!");
     println!("⚠ Make sure to remember your new password - it cannot be recovered!");
@@ -883,5 +2907,3 @@ fn handle_change_password(vault_file: Option<&str>) -> Result<(), Box<dyn Error>
     Ok(())
 }
 
-
-
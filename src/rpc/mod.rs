@@ -0,0 +1,438 @@
+//! Local JSON-RPC API
+//!
+//! `passman serve`: JSON-RPC 2.0 over a unix domain socket (a named pipe
+//! on Windows) for scripts and launchers. One JSON request per line, one
+//! response per line. Dispatch lives here; each platform's transport and
+//! peer checks live in `socket` or `pipe`, re-exported as `transport`.
+//!
+//! Methods: `status`, `unlock {password}`, `lock`, `list`,
+//! `get {id, fields}`, `search {query}` and `generate {length, symbols,
+//! no_ambiguous, exclude, memorable, words}`. When the server was started
+//! with a token, every request carries it as `params.token`.
+
+#[cfg(unix)]
+pub mod socket;
+#[cfg(windows)]
+pub mod pipe;
+
+#[cfg(unix)]
+pub use socket as transport;
+#[cfg(windows)]
+pub use pipe as transport;
+
+use std::io::{self, BufRead, Read, Write};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use passman_core::PassmanCore;
+use serde_json::{json, Map, Value};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+use crate::logging::safe_log_id;
+use crate::utils::{generate_memorable_password, generate_password_with_config, PasswordConfig};
+use crate::{log_security, log_session};
+
+/// Requests longer than this are refused without being parsed
+pub const MAX_REQUEST_BYTES: u64 = 64 * 1024;
+
+/// Fields `get` returns when none are asked for
+const DEFAULT_FIELDS: &[&str] = &["username", "password"];
+const ENTRY_FIELDS: &[&str] = &["username", "password", "url", "note", "tags"];
+
+// Standard JSON-RPC 2.0 codes, then passman's own
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const VAULT_LOCKED: i64 = -32001;
+const UNAUTHORIZED: i64 = -32002;
+const NOT_FOUND: i64 = -32003;
+const UNLOCK_FAILED: i64 = -32004;
+
+/// Default socket location: the per-user runtime directory where there is one
+#[cfg(not(windows))]
+pub fn default_socket_path() -> PathBuf {
+    dirs::runtime_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("passman.sock")
+}
+
+/// Default pipe name. Pipe names are machine-wide, so it carries the user
+/// name; the pipe's DACL is what keeps other users out.
+#[cfg(windows)]
+pub fn default_socket_path() -> PathBuf {
+    let user = std::env::var("USERNAME").unwrap_or_default();
+    PathBuf::from(format!(r"\\.\pipe\passman-{}", user))
+}
+
+/// A JSON-RPC error object
+#[derive(Debug, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+/// Vault state behind the socket: starts locked, locks again when idle
+pub struct Server {
+    core: PassmanCore,
+    token: Option<Zeroizing<String>>,
+    idle_timeout: Option<Duration>,
+    last_activity: Instant,
+}
+
+impl Server {
+    /// `idle_timeout_secs` of 0 disables the idle lock
+    pub fn new(vault_path: &str, token: Option<Zeroizing<String>>, idle_timeout_secs: u64, now: Instant) -> Self {
+        Self {
+            core: PassmanCore::with_vault_path(vault_path),
+            token,
+            idle_timeout: (idle_timeout_secs > 0).then(|| Duration::from_secs(idle_timeout_secs)),
+            last_activity: now,
+        }
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.core.is_authenticated()
+    }
+
+    /// Lock the vault once it has been idle for the timeout
+    pub fn tick(&mut self, now: Instant) {
+        let Some(timeout) = self.idle_timeout else { return };
+        if self.is_unlocked() && now.duration_since(self.last_activity) >= timeout {
+            self.core.lock();
+            log_security!("rpc: vault locked after {}s idle", timeout.as_secs());
+        }
+    }
+
+    /// Answer one request line; `None` for notifications, which get no reply
+    pub fn handle_line(&mut self, line: &str, peer: &str, now: Instant) -> Option<Value> {
+        self.tick(now);
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => return Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, format!("Parse error: {}", e)))),
+        };
+        let id = request.get("id").cloned();
+        let result = self.dispatch(&request, peer, now);
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => error_response(id, error),
+        })
+    }
+
+    fn dispatch(&mut self, request: &Value, peer: &str, now: Instant) -> Result<Value, RpcError> {
+        if request.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+            return Err(RpcError::new(INVALID_REQUEST, "Invalid request: expected jsonrpc \"2.0\""));
+        }
+        let method = request.get("method").and_then(Value::as_str)
+            .ok_or_else(|| RpcError::new(INVALID_REQUEST, "Invalid request: missing method"))?;
+        let empty = Map::new();
+        let params = match request.get("params") {
+            None | Some(Value::Null) => &empty,
+            Some(Value::Object(params)) => params,
+            Some(_) => return Err(RpcError::new(INVALID_PARAMS, "params must be an object")),
+        };
+
+        self.authorize(params, method, peer)?;
+
+        match method {
+            "status" => Ok(self.status(now)),
+            "unlock" => self.unlock(params, peer, now),
+            "lock" => {
+                if self.is_unlocked() {
+                    self.core.lock();
+                    log_security!("rpc: vault locked by {}", peer);
+                }
+                Ok(json!(true))
+            }
+            "list" => {
+                self.touch(now)?;
+                log_session!("rpc: list by {}", peer);
+                Ok(self.list())
+            }
+            "get" => {
+                self.touch(now)?;
+                self.get(params, peer)
+            }
+            "search" => {
+                self.touch(now)?;
+                let query = str_param(params, "query")?;
+                log_session!("rpc: search by {}", peer);
+                let ids: Vec<String> = self.core.search_entries(query).into_iter().map(|(id, _)| id).collect();
+                Ok(json!(ids))
+            }
+            "generate" => generate(params),
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        }
+    }
+
+    fn authorize(&self, params: &Map<String, Value>, method: &str, peer: &str) -> Result<(), RpcError> {
+        let Some(expected) = &self.token else { return Ok(()) };
+        let given = params.get("token").and_then(Value::as_str).unwrap_or_default();
+        if bool::from(given.as_bytes().ct_eq(expected.as_bytes())) {
+            Ok(())
+        } else {
+            log_security!("rpc: rejected '{}' from {}: bad or missing token", safe_log_id(method), peer);
+            Err(RpcError::new(UNAUTHORIZED, "Unauthorized: bad or missing token"))
+        }
+    }
+
+    /// Count a vault request as activity, refusing it while locked
+    fn touch(&mut self, now: Instant) -> Result<(), RpcError> {
+        if !self.is_unlocked() {
+            return Err(RpcError::new(VAULT_LOCKED, "Vault is locked"));
+        }
+        self.last_activity = now;
+        Ok(())
+    }
+
+    fn status(&self, now: Instant) -> Value {
+        let unlocked = self.is_unlocked();
+        let locks_in = self.idle_timeout.filter(|_| unlocked)
+            .map(|timeout| timeout.saturating_sub(now.duration_since(self.last_activity)).as_secs());
+        json!({
+            "unlocked": unlocked,
+            "vault": self.core.vault_path(),
+            "entries": unlocked.then(|| self.core.entry_count()),
+            "locks_in_secs": locks_in,
+        })
+    }
+
+    fn unlock(&mut self, params: &Map<String, Value>, peer: &str, now: Instant) -> Result<Value, RpcError> {
+        let password = Zeroizing::new(str_param(params, "password")?.to_string());
+        match self.core.unlock(&password) {
+            Ok(()) => {
+                self.last_activity = now;
+                log_security!("rpc: vault unlocked by {}", peer);
+                Ok(json!(true))
+            }
+            Err(e) => {
+                log_security!("rpc: failed unlock from {}", peer);
+                Err(RpcError::new(UNLOCK_FAILED, format!("Unlock failed: {}", e)))
+            }
+        }
+    }
+
+    fn list(&self) -> Value {
+        let entries: Vec<Value> = self.core.list_entries_with_data()
+            .into_iter()
+            .filter(|(_, entry)| !entry.is_trashed() && !entry.archived)
+            .map(|(id, entry)| json!({
+                "id": id,
+                "username": entry.username,
+                "url": entry.url,
+                "tags": entry.tags,
+                "modified_at": entry.modified_at.to_rfc3339(),
+            }))
+            .collect();
+        Value::Array(entries)
+    }
+
+    fn get(&mut self, params: &Map<String, Value>, peer: &str) -> Result<Value, RpcError> {
+        let id = str_param(params, "id")?;
+        let fields: Vec<&str> = match params.get("fields") {
+            None | Some(Value::Null) => DEFAULT_FIELDS.to_vec(),
+            Some(Value::Array(fields)) => fields.iter()
+                .map(|f| f.as_str().filter(|f| ENTRY_FIELDS.contains(f)).ok_or_else(|| {
+                    RpcError::new(INVALID_PARAMS, format!("Unknown field {}; expected one of {}", f, ENTRY_FIELDS.join(", ")))
+                }))
+                .collect::<Result<_, _>>()?,
+            Some(_) => return Err(RpcError::new(INVALID_PARAMS, "fields must be an array of strings")),
+        };
+        let entry = self.core.get_entry(id)
+            .filter(|entry| !entry.is_trashed())
+            .ok_or_else(|| RpcError::new(NOT_FOUND, format!("Entry '{}' not found", id)))?;
+        if entry.is_sealed() && fields.iter().any(|field| matches!(*field, "password" | "note")) {
+            return Err(RpcError::new(INVALID_PARAMS, format!("Entry '{}' is high security; its password and note need a PIN", id)));
+        }
+
+        let mut result = Map::new();
+        for field in &fields {
+            let value = match *field {
+                "username" => json!(entry.username),
+                "password" => json!(entry.password_str().reveal()),
+                "url" => json!(entry.url),
+                "note" => json!(entry.note),
+                _ => json!(entry.tags),
+            };
+            result.insert(field.to_string(), value);
+        }
+        log_security!("rpc: get '{}' [{}] by {}", safe_log_id(id), fields.join(","), peer);
+
+        // Usage counts are best-effort, as with `passman get`
+        if fields.contains(&"password") {
+            let id = id.to_string();
+            if let Err(e) = self.core.record_use(&id).and_then(|()| self.core.save()) {
+                log::warn!("rpc: could not record use of '{}': {}", safe_log_id(&id), e);
+            }
+        }
+        Ok(Value::Object(result))
+    }
+}
+
+/// The shared `Server`. A thread that panicked while holding it may have
+/// left the vault half-updated, so that stops the server instead.
+fn lock_server(server: &Mutex<Server>) -> io::Result<MutexGuard<'_, Server>> {
+    server.lock().map_err(|_| io::Error::other("a client thread panicked while handling a request"))
+}
+
+/// Answer request lines from one connected, already vetted client until it
+/// hangs up. The server is held for one request at a time, so an idle
+/// client doesn't hold up the others.
+fn serve_lines(mut reader: impl BufRead, mut writer: impl Write, server: &Mutex<Server>, peer: &str) -> io::Result<()> {
+    loop {
+        let mut line = Zeroizing::new(String::new());
+        let read = (&mut reader).take(MAX_REQUEST_BYTES).read_line(&mut line)?;
+        if read == 0 {
+            return Ok(());
+        }
+        if !line.ends_with('\n') && read as u64 == MAX_REQUEST_BYTES {
+            log_security!("rpc: dropped oversized request from {}", peer);
+            return Ok(());
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = lock_server(server)?.handle_line(&line, peer, Instant::now());
+        if let Some(response) = response {
+            let response = Zeroizing::new(format!("{}\n", response));
+            writer.write_all(response.as_bytes())?;
+        }
+    }
+}
+
+fn generate(params: &Map<String, Value>) -> Result<Value, RpcError> {
+    let flag = |name: &str| params.get(name).and_then(Value::as_bool);
+    let number = |name: &str, default: u64| match params.get(name) {
+        None | Some(Value::Null) => Ok(default),
+        Some(value) => value.as_u64().ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("{} must be a positive integer", name))),
+    };
+
+    let password = if flag("memorable").unwrap_or(false) {
+        generate_memorable_password(number("words", 4)?.clamp(1, 32) as usize)
+    } else {
+        let defaults = PasswordConfig::default();
+        let rules = PasswordConfig {
+            include_symbols: flag("symbols").unwrap_or(defaults.include_symbols),
+            exclude_ambiguous: flag("no_ambiguous").unwrap_or(defaults.exclude_ambiguous),
+            excluded_chars: params.get("exclude").and_then(Value::as_str).unwrap_or_default().to_string(),
+            ..defaults
+        };
+        let length = number("length", 16)?.min(1024) as usize;
+        generate_password_with_config(length, &rules).map_err(|e| RpcError::new(INVALID_PARAMS, e))?
+    };
+    Ok(json!(password))
+}
+
+fn str_param<'a>(params: &'a Map<String, Value>, name: &str) -> Result<&'a str, RpcError> {
+    params.get(name).and_then(Value::as_str)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Missing string parameter '{}'", name)))
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": error.code, "message": error.message } })
+}
+
+/// Build a request line for `method`, adding the token when there is one
+pub fn request_line(id: u64, method: &str, mut params: Map<String, Value>, token: Option<&str>) -> Zeroizing<String> {
+    if let Some(token) = token {
+        params.insert("token".into(), json!(token));
+    }
+    let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+    Zeroizing::new(format!("{}\n", request))
+}
+
+/// Pull the result out of a response, turning an error object into its message
+pub fn response_result(response: Value) -> Result<Value, RpcError> {
+    if let Some(error) = response.get("error") {
+        return Err(RpcError::new(
+            error.get("code").and_then(Value::as_i64).unwrap_or(INVALID_REQUEST),
+            error.get("message").and_then(Value::as_str).unwrap_or("Unknown error"),
+        ));
+    }
+    response.get("result").cloned().ok_or_else(|| RpcError::new(INVALID_REQUEST, "Response has no result"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use passman_core::model::Entry;
+
+    fn call(server: &mut Server, method: &str, params: Value, now: Instant) -> Result<Value, RpcError> {
+        let params = params.as_object().cloned().unwrap_or_default();
+        let line = request_line(1, method, params, Some("t0ken"));
+        response_result(server.handle_line(&line, "test", now).unwrap())
+    }
+
+    #[test]
+    fn test_rpc_token_protocol_errors_and_locked_vault() {
+        let now = Instant::now();
+        let mut server = Server::new("missing.dat", Some(Zeroizing::new("t0ken".into())), 60, now);
+
+        let unauthorized = server.handle_line(&request_line(1, "status", Map::new(), Some("wrong")), "test", now).unwrap();
+        assert_eq!(response_result(unauthorized).unwrap_err().code, UNAUTHORIZED);
+        let untokened = server.handle_line(&request_line(2, "status", Map::new(), None), "test", now).unwrap();
+        assert_eq!(response_result(untokened).unwrap_err().code, UNAUTHORIZED);
+
+        let parse = server.handle_line("{not json", "test", now).unwrap();
+        assert_eq!(response_result(parse).unwrap_err().code, PARSE_ERROR);
+        assert_eq!(call(&mut server, "frobnicate", json!({}), now).unwrap_err().code, METHOD_NOT_FOUND);
+        assert!(server.handle_line(r#"{"jsonrpc":"2.0","method":"lock","params":{"token":"t0ken"}}"#, "test", now).is_none());
+
+        assert_eq!(call(&mut server, "status", json!({}), now).unwrap()["unlocked"], json!(false));
+        assert_eq!(call(&mut server, "get", json!({"id": "github"}), now).unwrap_err().code, VAULT_LOCKED);
+        assert_eq!(call(&mut server, "list", json!({}), now).unwrap_err().code, VAULT_LOCKED);
+
+        // Generating touches no vault data, so it works while locked
+        let password = call(&mut server, "generate", json!({"length": 24, "symbols": false}), now).unwrap();
+        assert_eq!(password.as_str().unwrap().len(), 24);
+        assert!(password.as_str().unwrap().chars().all(char::is_alphanumeric));
+        assert_eq!(call(&mut server, "generate", json!({"length": "long"}), now).unwrap_err().code, INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_rpc_unlock_get_and_idle_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("rpc.dat").to_string_lossy().into_owned();
+        let master = Zeroizing::new("correct horse battery staple".to_string());
+        let mut core = PassmanCore::with_vault_path(&vault_path);
+        core.init_vault(&master).unwrap();
+        let mut entry = Entry::new("octocat".into(), "hunter2".into(), Some("2FA on".into()));
+        entry.url = Some("https://github.com".into());
+        core.add_entry("github", entry).unwrap();
+        core.save().unwrap();
+
+        let start = Instant::now();
+        let mut server = Server::new(&vault_path, Some(Zeroizing::new("t0ken".into())), 60, start);
+        assert_eq!(call(&mut server, "unlock", json!({"password": "nope"}), start).unwrap_err().code, UNLOCK_FAILED);
+        assert_eq!(call(&mut server, "unlock", json!({"password": master.as_str()}), start).unwrap(), json!(true));
+
+        let got = call(&mut server, "get", json!({"id": "github"}), start).unwrap();
+        assert_eq!(got, json!({"username": "octocat", "password": "hunter2"}));
+        let got = call(&mut server, "get", json!({"id": "github", "fields": ["url", "note"]}), start).unwrap();
+        assert_eq!(got, json!({"url": "https://github.com", "note": "2FA on"}));
+        assert_eq!(call(&mut server, "get", json!({"id": "github", "fields": ["totp"]}), start).unwrap_err().code, INVALID_PARAMS);
+        assert_eq!(call(&mut server, "get", json!({"id": "gitlab"}), start).unwrap_err().code, NOT_FOUND);
+
+        let listed = call(&mut server, "list", json!({}), start).unwrap();
+        assert_eq!(listed[0]["id"], json!("github"));
+        assert!(listed[0].get("password").is_none());
+        assert_eq!(call(&mut server, "search", json!({"query": "octo"}), start).unwrap(), json!(["github"]));
+
+        // Each vault request pushes the idle lock back; status doesn't
+        let later = start + Duration::from_secs(50);
+        call(&mut server, "list", json!({}), later).unwrap();
+        let status = call(&mut server, "status", json!({}), later + Duration::from_secs(30)).unwrap();
+        assert_eq!((status["unlocked"].clone(), status["locks_in_secs"].clone()), (json!(true), json!(30)));
+        assert_eq!(call(&mut server, "get", json!({"id": "github"}), later + Duration::from_secs(61)).unwrap_err().code, VAULT_LOCKED);
+        assert!(!server.is_unlocked());
+    }
+}
@@ -0,0 +1,333 @@
+//! Windows Named Pipe Transport
+//!
+//! The counterpart of `socket`: a local-only pipe whose DACL admits our own
+//! user and nobody else. Each caller's process token is checked against our
+//! user before anything is read, and clients check the server the same way,
+//! since pipe names go to whoever creates them first. Each connection gets
+//! its own thread; requests take turns on the `Server`.
+
+use std::ffi::OsStr;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::windows::ffi::OsStrExt;
+use std::os::windows::fs::OpenOptionsExt;
+use std::os::windows::io::{AsRawHandle, FromRawHandle, OwnedHandle, RawHandle};
+use std::path::Path;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::{Map, Value};
+use windows::core::{PCWSTR, PWSTR};
+use windows::Win32::Foundation::{LocalFree, ERROR_PIPE_BUSY, ERROR_PIPE_CONNECTED, FALSE, HANDLE, HLOCAL, PSID};
+use windows::Win32::Security::Authorization::{ConvertSidToStringSidW, ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1};
+use windows::Win32::Security::{EqualSid, GetTokenInformation, TokenUser, PSECURITY_DESCRIPTOR, SECURITY_ATTRIBUTES, TOKEN_QUERY, TOKEN_USER};
+use windows::Win32::Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX, SECURITY_IDENTIFICATION};
+use windows::Win32::System::Pipes::{
+    ConnectNamedPipe, CreateNamedPipeW, GetNamedPipeClientProcessId, GetNamedPipeServerProcessId, PeekNamedPipe, WaitNamedPipeW,
+    PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+};
+use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcess, OpenProcessToken, PROCESS_QUERY_LIMITED_INFORMATION};
+use zeroize::Zeroizing;
+
+use super::{lock_server, request_line, response_result, serve_lines, RpcError, Server, MAX_REQUEST_BYTES};
+use crate::log_security;
+
+/// How long a connected client may sit between requests
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// Pipes have no read timeout; reads wait for data in steps this long
+const READ_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The pipe's first instance, waiting for a client
+#[derive(Debug)]
+pub struct PipeListener {
+    name: Vec<u16>,
+    instance: File,
+}
+
+/// Create the pipe `path` (`\\.\pipe\<name>`), refusing if a server,
+/// ours or another user's, already holds that name
+pub fn bind(path: &Path) -> io::Result<PipeListener> {
+    let name = wide(path.as_os_str());
+    let instance = create_instance(&name, true).map_err(|e| {
+        if e.kind() == io::ErrorKind::PermissionDenied {
+            io::Error::new(io::ErrorKind::AddrInUse, format!("A server is already listening on {}", path.display()))
+        } else {
+            e
+        }
+    })?;
+    Ok(PipeListener { name, instance })
+}
+
+/// Accept clients until the listener fails, each served on its own thread,
+/// locking the vault on the idle timeout in between
+pub fn serve(listener: PipeListener, server: Server) -> io::Result<()> {
+    let server = Arc::new(Mutex::new(server));
+    let (stopped, accept_error) = mpsc::channel();
+    let accepting = Arc::clone(&server);
+    // Waiting for a client blocks, so that happens on its own thread
+    std::thread::spawn(move || {
+        let _ = stopped.send(accept(listener, &accepting));
+    });
+    loop {
+        match accept_error.recv_timeout(POLL_INTERVAL) {
+            Ok(e) => return Err(e),
+            Err(mpsc::RecvTimeoutError::Timeout) => lock_server(&server)?.tick(Instant::now()),
+            Err(mpsc::RecvTimeoutError::Disconnected) => return Err(io::Error::other("the pipe listener thread panicked")),
+        }
+    }
+}
+
+/// Hand each connected instance to a thread of its own and put a fresh
+/// instance in its place; returns the error that stopped it
+fn accept(mut listener: PipeListener, server: &Arc<Mutex<Server>>) -> io::Error {
+    loop {
+        // SAFETY: blocks on a pipe instance we own until a client connects
+        let connected = unsafe { ConnectNamedPipe(handle_of(&listener.instance), None) };
+        // A client that came in before we started waiting is reported as
+        // an error, but is connected all the same
+        if let Err(e) = connected.or_else(|e| if e.code() == ERROR_PIPE_CONNECTED.to_hresult() { Ok(()) } else { Err(e) }) {
+            return e.into();
+        }
+        let next = match create_instance(&listener.name, false) {
+            Ok(next) => next,
+            Err(e) => return e,
+        };
+        let pipe = std::mem::replace(&mut listener.instance, next);
+        let server = Arc::clone(server);
+        std::thread::spawn(move || {
+            if let Err(e) = handle_client(pipe, &server) {
+                log::warn!("rpc: client error: {}", e);
+            }
+        });
+    }
+}
+
+/// Serve one connection: callers running as another user are dropped
+/// before anything is read
+pub fn handle_client(pipe: File, server: &Mutex<Server>) -> io::Result<()> {
+    let mut pid = 0;
+    // SAFETY: asks about the client of a connected instance we own
+    unsafe { GetNamedPipeClientProcessId(handle_of(&pipe), &mut pid)? };
+    if !same_user(pid)? {
+        log_security!("rpc: refused connection from pid {}", pid);
+        return Ok(());
+    }
+    let writer = pipe.try_clone()?;
+    serve_lines(BufReader::new(TimedPipe(pipe)), writer, server, &format!("pid {}", pid))
+}
+
+/// Send one request to the server at `path` and wait for its result
+pub fn call(path: &Path, method: &str, params: Map<String, Value>, token: Option<&str>) -> Result<Value, RpcError> {
+    let io_error = |e: io::Error| RpcError { code: 0, message: format!("{}: {}", path.display(), e) };
+    let mut pipe = open(path).map_err(io_error)?;
+    let mut pid = 0;
+    // SAFETY: asks about the server end of a pipe we just opened
+    unsafe { GetNamedPipeServerProcessId(handle_of(&pipe), &mut pid) }.map_err(|e| io_error(e.into()))?;
+    // Don't hand a token or the master password to a pipe another user made
+    if !same_user(pid).map_err(io_error)? {
+        return Err(io_error(io::Error::new(io::ErrorKind::PermissionDenied, "the pipe belongs to another user")));
+    }
+    pipe.write_all(request_line(1, method, params, token).as_bytes()).map_err(io_error)?;
+
+    let mut line = Zeroizing::new(String::new());
+    BufReader::new(TimedPipe(pipe)).read_line(&mut line).map_err(io_error)?;
+    let response = serde_json::from_str(&line)
+        .map_err(|e| RpcError { code: 0, message: format!("Bad response from server: {}", e) })?;
+    response_result(response)
+}
+
+/// Open the client end, waiting a while if every instance is busy
+fn open(path: &Path) -> io::Result<File> {
+    let deadline = Instant::now() + CLIENT_TIMEOUT;
+    loop {
+        // Identification only: the server may see who we are, not act as us
+        let opened = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .security_qos_flags(SECURITY_IDENTIFICATION.0)
+            .open(path);
+        match opened {
+            Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY.0 as i32) && Instant::now() < deadline => {
+                let name = wide(path.as_os_str());
+                // SAFETY: `name` is NUL-terminated and outlives the call
+                let _ = unsafe { WaitNamedPipeW(PCWSTR(name.as_ptr()), POLL_INTERVAL.as_millis() as u32) };
+            }
+            opened => return opened,
+        }
+    }
+}
+
+fn create_instance(name: &[u16], first: bool) -> io::Result<File> {
+    let security = OwnerOnly::new(&UserSid::of_current_process()?)?;
+    let attributes = SECURITY_ATTRIBUTES {
+        nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+        lpSecurityDescriptor: security.0 .0,
+        bInheritHandle: FALSE,
+    };
+    let mut open_mode = PIPE_ACCESS_DUPLEX;
+    if first {
+        open_mode |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+    }
+    let buffer_size = MAX_REQUEST_BYTES as u32;
+    // SAFETY: `name` is NUL-terminated; `attributes` and the descriptor it
+    // points to live until the call returns
+    let handle = unsafe {
+        CreateNamedPipeW(
+            PCWSTR(name.as_ptr()),
+            open_mode,
+            PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+            PIPE_UNLIMITED_INSTANCES,
+            buffer_size,
+            buffer_size,
+            0,
+            Some(&attributes),
+        )
+    };
+    if handle.is_invalid() {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: a fresh handle nothing else owns
+    Ok(unsafe { File::from_raw_handle(handle.0 as RawHandle) })
+}
+
+/// Whether process `pid` runs as our user. One we may not even query
+/// (another user's, or an elevated one) doesn't.
+fn same_user(pid: u32) -> io::Result<bool> {
+    let own = UserSid::of_current_process()?;
+    Ok(UserSid::of_process(pid).is_ok_and(|user| user == own))
+}
+
+fn handle_of(handle: &impl AsRawHandle) -> HANDLE {
+    HANDLE(handle.as_raw_handle() as isize)
+}
+
+fn wide(s: &OsStr) -> Vec<u16> {
+    s.encode_wide().chain(std::iter::once(0)).collect()
+}
+
+/// A pipe end whose reads give up after `CLIENT_TIMEOUT` without data, as
+/// a socket's read timeout would
+struct TimedPipe(File);
+
+impl Read for TimedPipe {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let deadline = Instant::now() + CLIENT_TIMEOUT;
+        loop {
+            let mut available = 0;
+            // SAFETY: only counts the bytes waiting in a pipe we own
+            let peeked = unsafe { PeekNamedPipe(handle_of(&self.0), None, 0, None, Some(&mut available), None) };
+            // A closed pipe fails the peek; the read then reports the end
+            if peeked.is_err() || available > 0 {
+                return self.0.read(buf);
+            }
+            if Instant::now() >= deadline {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "timed out waiting for the other end"));
+            }
+            std::thread::sleep(READ_POLL_INTERVAL);
+        }
+    }
+}
+
+/// A process's user SID, kept in the `TOKEN_USER` buffer it came in
+struct UserSid(Vec<usize>);
+
+impl UserSid {
+    fn of_current_process() -> io::Result<Self> {
+        // SAFETY: our own process's pseudo handle needs no closing
+        Self::of(unsafe { GetCurrentProcess() })
+    }
+
+    fn of_process(pid: u32) -> io::Result<Self> {
+        // SAFETY: the new handle is owned, and closed, by `process`
+        let process = unsafe { OwnedHandle::from_raw_handle(OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, pid)?.0 as RawHandle) };
+        Self::of(handle_of(&process))
+    }
+
+    fn of(process: HANDLE) -> io::Result<Self> {
+        let mut token = HANDLE::default();
+        // SAFETY: the token handle is owned, and closed, by `token`
+        let token = unsafe {
+            OpenProcessToken(process, TOKEN_QUERY, &mut token)?;
+            OwnedHandle::from_raw_handle(token.0 as RawHandle)
+        };
+        let mut len = 0;
+        // SAFETY: the first call only reports the size; the second fills a
+        // buffer of that size, aligned for the pointer inside TOKEN_USER
+        unsafe {
+            let _ = GetTokenInformation(handle_of(&token), TokenUser, None, 0, &mut len);
+            let mut buffer = vec![0usize; (len as usize).div_ceil(std::mem::size_of::<usize>())];
+            GetTokenInformation(handle_of(&token), TokenUser, Some(buffer.as_mut_ptr().cast()), len, &mut len)?;
+            Ok(Self(buffer))
+        }
+    }
+
+    fn sid(&self) -> PSID {
+        // SAFETY: the buffer holds a TOKEN_USER whose SID points into it
+        unsafe { (*self.0.as_ptr().cast::<TOKEN_USER>()).User.Sid }
+    }
+
+    /// The SID in `S-1-5-…` form, as SDDL wants it
+    fn sddl(&self) -> io::Result<String> {
+        let mut string = PWSTR::null();
+        // SAFETY: the string is allocated by the call and freed below
+        unsafe {
+            ConvertSidToStringSidW(self.sid(), &mut string)?;
+            let sddl = string.to_string();
+            let _ = LocalFree(HLOCAL(string.0.cast()));
+            sddl.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        }
+    }
+}
+
+impl PartialEq for UserSid {
+    fn eq(&self, other: &Self) -> bool {
+        // SAFETY: both SIDs live in their buffers for the call
+        unsafe { EqualSid(self.sid(), other.sid()).is_ok() }
+    }
+}
+
+/// A security descriptor whose DACL grants `user` full access and nobody
+/// else anything; freed on drop
+struct OwnerOnly(PSECURITY_DESCRIPTOR);
+
+impl OwnerOnly {
+    fn new(user: &UserSid) -> io::Result<Self> {
+        let sddl = wide(OsStr::new(&format!("D:P(A;;GA;;;{})", user.sddl()?)));
+        let mut descriptor = PSECURITY_DESCRIPTOR::default();
+        // SAFETY: `sddl` is NUL-terminated; the descriptor is freed on drop
+        unsafe { ConvertStringSecurityDescriptorToSecurityDescriptorW(PCWSTR(sddl.as_ptr()), SDDL_REVISION_1, &mut descriptor, None)? };
+        Ok(Self(descriptor))
+    }
+}
+
+impl Drop for OwnerOnly {
+    fn drop(&mut self) {
+        // SAFETY: allocated by ConvertStringSecurityDescriptorToSecurityDescriptorW
+        let _ = unsafe { LocalFree(HLOCAL(self.0 .0)) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_pipe_round_trip_and_single_server() {
+        let path = std::path::PathBuf::from(format!(r"\\.\pipe\passman-test-{}", uuid::Uuid::new_v4().simple()));
+        let listener = bind(&path).unwrap();
+        assert_eq!(bind(&path).unwrap_err().kind(), io::ErrorKind::AddrInUse);
+
+        let server = Server::new("missing.dat", Some(Zeroizing::new("t0ken".into())), 0, Instant::now());
+        std::thread::spawn(move || serve(listener, server));
+
+        // A client that connects and then says nothing doesn't keep others waiting
+        let idle = open(&path).unwrap();
+        let status = call(&path, "status", Map::new(), Some("t0ken")).unwrap();
+        assert_eq!(status["unlocked"], json!(false));
+        let error = call(&path, "status", Map::new(), Some("wrong")).unwrap_err();
+        assert_eq!(error.code, crate::rpc::UNAUTHORIZED);
+        drop(idle);
+    }
+}
@@ -0,0 +1,155 @@
+//! Unix Socket Transport
+//!
+//! Binds the socket owner-only, checks each caller's peer credentials
+//! against our own UID and feeds request lines to the `Server`. Each
+//! connection gets its own thread; requests take turns on the `Server`.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::{Map, Value};
+use zeroize::Zeroizing;
+
+use super::{lock_server, request_line, response_result, serve_lines, RpcError, Server};
+use crate::log_security;
+
+/// How long a connected client may sit between requests
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Bind `path`, replacing a stale socket left by a server that died but
+/// refusing to touch a live one or anything that isn't a socket
+pub fn bind(path: &Path) -> io::Result<UnixListener> {
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} exists and is not a socket", path.display())));
+        }
+        if UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(io::ErrorKind::AddrInUse, format!("A server is already listening on {}", path.display())));
+        }
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+/// Accept clients until the listener fails, each served on its own thread,
+/// locking the vault on the idle timeout in between
+pub fn serve(listener: UnixListener, server: Server) -> io::Result<()> {
+    listener.set_nonblocking(true)?;
+    let server = Arc::new(Mutex::new(server));
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let server = Arc::clone(&server);
+                std::thread::spawn(move || {
+                    if let Err(e) = handle_client(stream, &server) {
+                        log::warn!("rpc: client error: {}", e);
+                    }
+                });
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                lock_server(&server)?.tick(Instant::now());
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Serve one connection: callers running as another user are dropped
+/// before anything is read
+pub fn handle_client(stream: UnixStream, server: &Mutex<Server>) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    let uid = peer_uid(&stream)?;
+    let own_uid = unsafe { libc::geteuid() };
+    if uid != own_uid {
+        log_security!("rpc: refused connection from uid {}", uid);
+        return Ok(());
+    }
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    let writer = stream.try_clone()?;
+    serve_lines(BufReader::new(stream), writer, server, &format!("uid {}", uid))
+}
+
+/// Send one request to the server at `path` and wait for its result
+pub fn call(path: &Path, method: &str, params: Map<String, Value>, token: Option<&str>) -> Result<Value, RpcError> {
+    let io_error = |e: io::Error| RpcError { code: 0, message: format!("{}: {}", path.display(), e) };
+    let mut stream = UnixStream::connect(path).map_err(io_error)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT)).map_err(io_error)?;
+    stream.write_all(request_line(1, method, params, token).as_bytes()).map_err(io_error)?;
+
+    let mut line = Zeroizing::new(String::new());
+    BufReader::new(stream).read_line(&mut line).map_err(io_error)?;
+    let response = serde_json::from_str(&line)
+        .map_err(|e| RpcError { code: 0, message: format!("Bad response from server: {}", e) })?;
+    response_result(response)
+}
+
+#[cfg(target_os = "linux")]
+fn peer_uid(stream: &UnixStream) -> io::Result<u32> {
+    let mut cred: libc::ucred = unsafe { std::mem::zeroed() };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    let rc = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    if rc != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(cred.uid)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn peer_uid(stream: &UnixStream) -> io::Result<u32> {
+    let mut uid: libc::uid_t = 0;
+    let mut gid: libc::gid_t = 0;
+    if unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(uid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_socket_round_trip_and_stale_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("passman.sock");
+
+        // A socket nobody listens on is stale and gets replaced
+        drop(UnixListener::bind(&path).unwrap());
+        let listener = bind(&path).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(bind(&path).unwrap_err().kind(), io::ErrorKind::AddrInUse);
+
+        let server = Server::new("missing.dat", Some(Zeroizing::new("t0ken".into())), 0, Instant::now());
+        std::thread::spawn(move || serve(listener, server));
+
+        // A client that connects and then says nothing doesn't keep others waiting
+        let idle = UnixStream::connect(&path).unwrap();
+        let status = call(&path, "status", Map::new(), Some("t0ken")).unwrap();
+        assert_eq!(status["unlocked"], json!(false));
+        let error = call(&path, "status", Map::new(), Some("wrong")).unwrap_err();
+        assert_eq!(error.code, crate::rpc::UNAUTHORIZED);
+        drop(idle);
+
+        let file = dir.path().join("not-a-socket");
+        std::fs::write(&file, "").unwrap();
+        assert_eq!(bind(&file).unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+    }
+}
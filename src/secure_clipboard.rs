@@ -10,12 +10,18 @@
 
 use clipboard::{ClipboardProvider, ClipboardContext};
 use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
 
 /// Default clipboard clear timeout in seconds
 const DEFAULT_CLEAR_TIMEOUT_SECS: u64 = 30;
 
+/// Whether the clipboard holds something copied by any `SecureClipboard`
+/// that hasn't been cleared yet, for [`clear_if_holding_copy`]
+static HOLDS_COPY: AtomicBool = AtomicBool::new(false);
+
 /// Windows clipboard format for excluding from history
 /// CLIPBOARD_FORMAT_EXCLUDE_FROM_HISTORY = "ExcludeClipboardContentFromMonitorProcessing"
 #[cfg(target_os = "windows")]
@@ -70,6 +76,8 @@ pub enum ClipboardError {
     SetError(String),
     /// Failed to clear clipboard
     ClearError(String),
+    /// Failed to read clipboard content
+    GetError(String),
     /// Clipboard is currently locked
     Locked,
 }
@@ -80,6 +88,7 @@ impl std::fmt::Display for ClipboardError {
             ClipboardError::AccessError(msg) => write!(f, "Clipboard access error: {}", msg),
             ClipboardError::SetError(msg) => write!(f, "Failed to set clipboard: {}", msg),
             ClipboardError::ClearError(msg) => write!(f, "Failed to clear clipboard: {}", msg),
+            ClipboardError::GetError(msg) => write!(f, "Failed to read clipboard: {}", msg),
             ClipboardError::Locked => write!(f, "Clipboard is locked by another operation"),
         }
     }
@@ -177,6 +186,7 @@ impl SecureClipboard {
         if let Ok(mut id) = self.content_id.lock() {
             *id = Some(content_id.clone());
         }
+        HOLDS_COPY.store(true, Ordering::SeqCst);
 
         // Schedule auto-clear if requested
         if auto_clear && self.clear_timeout_secs > 0 {
@@ -302,6 +312,7 @@ impl SecureClipboard {
                 if let Ok(mut ctx) = ClipboardProvider::new() as Result<ClipboardContext, _> {
                     // Clear by setting empty content
                     let _ = ctx.set_contents(String::new());
+                    HOLDS_COPY.store(false, Ordering::SeqCst);
                     log::debug!("Clipboard auto-cleared after {}s timeout", timeout);
                 }
 
@@ -327,11 +338,23 @@ impl SecureClipboard {
         if let Ok(mut id) = self.content_id.lock() {
             *id = None;
         }
+        HOLDS_COPY.store(false, Ordering::SeqCst);
 
         log::debug!("Clipboard cleared immediately");
         Ok(())
     }
 
+    /// Read the clipboard's text and clear it, for pasted credentials that
+    /// shouldn't stay on the clipboard once taken in
+    pub fn take_text(&self) -> ClipboardResult<Zeroizing<String>> {
+        let mut ctx: ClipboardContext = ClipboardProvider::new()
+            .map_err(|e| ClipboardError::AccessError(e.to_string()))?;
+        let text = Zeroizing::new(ctx.get_contents()
+            .map_err(|e| ClipboardError::GetError(e.to_string()))?);
+        self.clear_now()?;
+        Ok(text)
+    }
+
     /// Check if a clear operation is pending
     pub fn is_clear_pending(&self) -> bool {
         self.clear_pending.load(Ordering::SeqCst)
@@ -367,6 +390,53 @@ impl Clone for SecureClipboard {
     }
 }
 
+/// Where [`hold_then_clear`] clears; the system clipboard outside tests
+pub trait ClipboardSink {
+    fn set(&self, text: &str) -> ClipboardResult<()>;
+    fn clear(&self) -> ClipboardResult<()>;
+}
+
+impl ClipboardSink for SecureClipboard {
+    fn set(&self, text: &str) -> ClipboardResult<()> {
+        self.copy(text, false)
+    }
+
+    fn clear(&self) -> ClipboardResult<()> {
+        self.clear_now()
+    }
+}
+
+/// Why [`hold_then_clear`] stopped waiting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoldEnd {
+    TimedOut,
+    Interrupted,
+}
+
+/// Block for `timeout`, then clear the clipboard. For commands that would
+/// otherwise exit before an auto-clear thread gets to run. `tick` is called
+/// about once a second with the whole seconds left; a message on `stop`
+/// (Ctrl+C) clears right away.
+pub fn hold_then_clear(sink: &impl ClipboardSink, timeout: Duration, stop: &Receiver<()>, mut tick: impl FnMut(u64)) -> ClipboardResult<HoldEnd> {
+    let deadline = Instant::now() + timeout;
+    let end = loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            break HoldEnd::TimedOut;
+        }
+        tick(left.as_millis().div_ceil(1000) as u64);
+        let step = left.min(Duration::from_secs(1));
+        match stop.recv_timeout(step) {
+            Ok(()) => break HoldEnd::Interrupted,
+            Err(RecvTimeoutError::Timeout) => {}
+            // Nobody can interrupt; just wait
+            Err(RecvTimeoutError::Disconnected) => thread::sleep(step),
+        }
+    };
+    sink.clear()?;
+    Ok(end)
+}
+
 /// Global clipboard instance for easy access
 static CLIPBOARD: std::sync::OnceLock<Mutex<SecureClipboard>> = std::sync::OnceLock::new();
 
@@ -399,6 +469,17 @@ pub fn clear_clipboard() -> ClipboardResult<()> {
         .clear_now()
 }
 
+/// Clear the clipboard if passman copied something to it that hasn't been
+/// cleared yet. Doesn't take the global instance's lock, so it is safe to
+/// call from a signal handler thread. Returns whether it cleared anything.
+pub fn clear_if_holding_copy() -> ClipboardResult<bool> {
+    if !HOLDS_COPY.load(Ordering::SeqCst) {
+        return Ok(false);
+    }
+    SecureClipboard::new().clear_now()?;
+    Ok(true)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -416,6 +497,55 @@ mod tests {
         assert_eq!(clipboard.get_timeout(), 60);
     }
 
+    /// In-memory clipboard recording what was done to it
+    #[derive(Default)]
+    struct MockClipboard {
+        contents: Mutex<Option<String>>,
+    }
+
+    impl ClipboardSink for MockClipboard {
+        fn set(&self, text: &str) -> ClipboardResult<()> {
+            *self.contents.lock().unwrap() = Some(text.to_string());
+            Ok(())
+        }
+
+        fn clear(&self) -> ClipboardResult<()> {
+            *self.contents.lock().unwrap() = None;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_hold_clears_on_timeout() {
+        let clipboard = MockClipboard::default();
+        clipboard.set("hunter2").unwrap();
+        let (_stop, stopped) = std::sync::mpsc::channel();
+        let mut ticks = Vec::new();
+
+        let end = hold_then_clear(&clipboard, Duration::from_millis(1500), &stopped, |left| ticks.push(left)).unwrap();
+        assert_eq!(end, HoldEnd::TimedOut);
+        assert_eq!(ticks.first(), Some(&2));
+        assert_eq!(*clipboard.contents.lock().unwrap(), None);
+    }
+
+    #[test]
+    fn test_hold_clears_on_interrupt() {
+        let clipboard = MockClipboard::default();
+        clipboard.set("hunter2").unwrap();
+        let (stop, stopped) = std::sync::mpsc::channel();
+        let interrupter = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            stop.send(()).unwrap();
+        });
+
+        let started = Instant::now();
+        let end = hold_then_clear(&clipboard, Duration::from_secs(60), &stopped, |_| {}).unwrap();
+        interrupter.join().unwrap();
+        assert_eq!(end, HoldEnd::Interrupted);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(*clipboard.contents.lock().unwrap(), None);
+    }
+
     #[test]
     fn test_disable_clipboard() {
         let mut clipboard = SecureClipboard::new();
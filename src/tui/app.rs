@@ -0,0 +1,607 @@
+//! TUI State
+//!
+//! Everything the terminal UI knows, and how key presses change it. Vault
+//! access goes through `PassmanCore`; searching goes through the same
+//! `SearchIndex` the GUI uses.
+
+use std::time::{Duration, Instant};
+
+use passman_core::config::{get_config, LockTimeoutBehavior};
+use passman_core::search::{SearchIndex, SearchScope};
+use passman_core::{Entry, EntryBuilder, PassmanCore};
+use zeroize::Zeroizing;
+
+/// A key press, decoded by the terminal backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Ctrl(char),
+    Enter,
+    Esc,
+    Backspace,
+    Tab,
+    BackTab,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Delete,
+}
+
+/// Form field labels, in tab order
+pub const FORM_FIELDS: [&str; 6] = ["Id", "Username", "Password", "URL", "Tags", "Note"];
+const PASSWORD_FIELD: usize = 2;
+
+/// Add/edit form
+pub struct Form {
+    /// Id of the entry being edited; `None` when adding
+    pub editing: Option<String>,
+    pub values: Vec<Zeroizing<String>>,
+    pub focus: usize,
+    pub show_password: bool,
+}
+
+impl Form {
+    fn add() -> Self {
+        Self {
+            editing: None,
+            values: FORM_FIELDS.iter().map(|_| Zeroizing::new(String::new())).collect(),
+            focus: 0,
+            show_password: false,
+        }
+    }
+
+    fn edit(id: &str, entry: &Entry) -> Self {
+        let values = [
+            id.to_string(),
+            entry.username.clone(),
+            entry.password_str().reveal().to_string(),
+            entry.url.clone().unwrap_or_default(),
+            entry.tags.join(", "),
+            entry.note.clone().unwrap_or_default(),
+        ];
+        Self {
+            editing: Some(id.to_string()),
+            values: values.into_iter().map(Zeroizing::new).collect(),
+            focus: 1,
+            show_password: false,
+        }
+    }
+
+    fn value(&self, field: usize) -> &str {
+        self.values[field].trim()
+    }
+}
+
+pub enum Mode {
+    /// Browsing the list
+    Normal,
+    /// Typing into the filter
+    Search,
+    /// Full-screen detail view on narrow terminals
+    Detail,
+    Form(Form),
+    /// Waiting for `y`, or the retyped id of a protected entry
+    ConfirmDelete { id: String, protected: bool, typed: String },
+    Locked { input: Zeroizing<String>, unlocking: bool },
+    Help,
+}
+
+/// Side effects the run loop performs on the real terminal
+#[derive(Debug, PartialEq)]
+pub enum Effect {
+    Copy(Zeroizing<String>),
+    ClearClipboard,
+}
+
+pub struct App {
+    core: PassmanCore,
+    index: SearchIndex,
+    include_notes: bool,
+    pub query: String,
+    /// Ids matching the filter, sorted
+    pub visible: Vec<String>,
+    pub selected: usize,
+    pub revealed: bool,
+    pub mode: Mode,
+    pub status: Option<String>,
+    /// First `d` of `dd` was pressed
+    pending_delete: bool,
+    last_activity: Instant,
+    lock_timeout: Option<Duration>,
+    lenient_lock: bool,
+    clipboard_timeout: Option<Duration>,
+    clipboard_clear_at: Option<Instant>,
+    effects: Vec<Effect>,
+    /// Copies or reveals not yet saved; written with the next save, on lock or on exit
+    usage_pending: bool,
+    pub quit: bool,
+}
+
+impl App {
+    pub fn new(core: PassmanCore, now: Instant) -> Self {
+        let config = get_config();
+        let seconds = |secs: u64| (secs > 0).then(|| Duration::from_secs(secs));
+        let mut app = Self {
+            core,
+            index: SearchIndex::new(),
+            include_notes: config.ui.search_notes,
+            query: String::new(),
+            visible: Vec::new(),
+            selected: 0,
+            revealed: false,
+            mode: Mode::Normal,
+            status: None,
+            pending_delete: false,
+            last_activity: now,
+            lock_timeout: seconds(config.security.lock_timeout_secs),
+            lenient_lock: config.security.lock_timeout_behavior == LockTimeoutBehavior::Lenient,
+            clipboard_timeout: seconds(config.security.clipboard_timeout_secs),
+            clipboard_clear_at: None,
+            effects: Vec::new(),
+            usage_pending: false,
+            quit: false,
+        };
+        app.refresh();
+        app
+    }
+
+    pub fn vault_path(&self) -> &str {
+        self.core.vault_path()
+    }
+
+    pub fn entry_count(&self) -> usize {
+        self.core.entry_count()
+    }
+
+    pub fn selected_id(&self) -> Option<&str> {
+        self.visible.get(self.selected).map(String::as_str)
+    }
+
+    pub fn selected_entry(&self) -> Option<&Entry> {
+        self.core.get_entry(self.selected_id()?)
+    }
+
+    pub fn username_of(&self, id: &str) -> Option<&str> {
+        self.core.get_entry(id).map(|entry| entry.username.as_str())
+    }
+
+    pub fn take_effects(&mut self) -> Vec<Effect> {
+        std::mem::take(&mut self.effects)
+    }
+
+    /// Re-sync the search index and the visible list with the vault
+    fn refresh(&mut self) {
+        let Some(vault) = self.core.vault() else {
+            self.visible.clear();
+            self.selected = 0;
+            return;
+        };
+        self.index.sync(vault.iter());
+        let query = self.index.prepare(&self.query, self.include_notes, SearchScope::ActiveOnly);
+        let mut ids: Vec<String> = vault.iter()
+            .filter(|(id, _)| self.index.matches(id, &query))
+            .map(|(id, _)| id.to_string())
+            .collect();
+        ids.sort();
+        self.visible = ids;
+        self.selected = self.selected.min(self.visible.len().saturating_sub(1));
+    }
+
+    fn select(&mut self, index: usize) {
+        let index = index.min(self.visible.len().saturating_sub(1));
+        if index != self.selected {
+            self.revealed = false;
+        }
+        self.selected = index;
+    }
+
+    fn save(&mut self) -> bool {
+        match self.core.save() {
+            Ok(()) => {
+                self.usage_pending = false;
+                true
+            }
+            Err(e) => {
+                self.status = Some(format!("Save failed: {}", e));
+                false
+            }
+        }
+    }
+
+    fn copy(&mut self, text: String, what: &str, now: Instant) {
+        self.effects.push(Effect::Copy(Zeroizing::new(text)));
+        self.clipboard_clear_at = self.clipboard_timeout.map(|timeout| now + timeout);
+        self.status = Some(match self.clipboard_timeout {
+            Some(timeout) => format!("{} copied (clears in {}s)", what, timeout.as_secs()),
+            None => format!("{} copied", what),
+        });
+    }
+
+    /// Count a copy or reveal of the selected entry's password
+    fn record_use(&mut self) {
+        if let Some(id) = self.selected_id().map(str::to_string) {
+            self.usage_pending |= self.core.record_use(&id).is_ok();
+        }
+    }
+
+    /// Write usage counts that haven't been saved yet
+    pub fn save_usage(&mut self) {
+        if self.usage_pending {
+            self.save();
+        }
+    }
+
+    /// Forget the decrypted vault and ask for the master password again
+    pub fn lock(&mut self) {
+        self.save_usage();
+        self.core.lock();
+        self.index = SearchIndex::new();
+        self.refresh();
+        self.revealed = false;
+        self.pending_delete = false;
+        self.mode = Mode::Locked { input: Zeroizing::new(String::new()), unlocking: false };
+    }
+
+    /// Whether an unlock was requested and should run after the next draw
+    pub fn unlock_pending(&self) -> bool {
+        matches!(self.mode, Mode::Locked { unlocking: true, .. })
+    }
+
+    /// Run the (slow) key derivation for a pending unlock
+    pub fn finish_unlock(&mut self, now: Instant) {
+        let Mode::Locked { input, .. } = std::mem::replace(&mut self.mode, Mode::Normal) else {
+            return;
+        };
+        match self.core.unlock(&input) {
+            Ok(()) => {
+                self.status = None;
+                self.last_activity = now;
+                self.refresh();
+            }
+            Err(e) => {
+                self.status = Some(e.to_string());
+                self.mode = Mode::Locked { input: Zeroizing::new(String::new()), unlocking: false };
+            }
+        }
+    }
+
+    /// Time-based work: auto-lock and clipboard clearing
+    pub fn tick(&mut self, now: Instant) {
+        if self.clipboard_clear_at.is_some_and(|at| now >= at) {
+            self.clipboard_clear_at = None;
+            self.effects.push(Effect::ClearClipboard);
+        }
+
+        let typing = matches!(self.mode, Mode::Form(_));
+        let paused = self.lenient_lock && typing;
+        if matches!(self.mode, Mode::Locked { .. }) || paused {
+            return;
+        }
+        if self.lock_timeout.is_some_and(|timeout| now.duration_since(self.last_activity) >= timeout) {
+            self.lock();
+            self.status = Some("Locked after inactivity".to_string());
+        }
+    }
+
+    pub fn handle_key(&mut self, key: Key, now: Instant) {
+        self.last_activity = now;
+        if key != Key::Char('d') {
+            self.pending_delete = false;
+        }
+
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Normal => self.normal_key(key, false, now),
+            Mode::Detail => self.normal_key(key, true, now),
+            Mode::Search => self.search_key(key),
+            Mode::Form(form) => self.form_key(form, key),
+            Mode::ConfirmDelete { id, protected, typed } => self.confirm_key(id, protected, typed, key),
+            Mode::Locked { input, unlocking } => self.locked_key(input, unlocking, key),
+            Mode::Help => {
+                if matches!(key, Key::Char('q') | Key::Ctrl('c')) {
+                    self.quit = true;
+                }
+            }
+        }
+    }
+
+    fn normal_key(&mut self, key: Key, detail: bool, now: Instant) {
+        if detail {
+            self.mode = Mode::Detail;
+        }
+        match key {
+            Key::Char('q') | Key::Ctrl('c') => self.quit = true,
+            Key::Char('j') | Key::Down => self.select(self.selected + 1),
+            Key::Char('k') | Key::Up => self.select(self.selected.saturating_sub(1)),
+            Key::Char('g') | Key::Home => self.select(0),
+            Key::Char('G') | Key::End => self.select(usize::MAX),
+            Key::PageDown | Key::Ctrl('d') => self.select(self.selected + 10),
+            Key::PageUp | Key::Ctrl('u') => self.select(self.selected.saturating_sub(10)),
+            Key::Char('/') => {
+                self.mode = Mode::Search;
+                self.status = None;
+            }
+            Key::Enter | Key::Char('l') if self.selected_id().is_some() => self.mode = Mode::Detail,
+            Key::Esc | Key::Char('h') if detail => self.mode = Mode::Normal,
+            Key::Esc if !self.query.is_empty() => {
+                self.query.clear();
+                self.refresh();
+            }
+            Key::Char('r') => {
+                self.revealed = !self.revealed;
+                if self.revealed {
+                    self.record_use();
+                }
+            }
+            Key::Char('y') | Key::Char('e') if self.selected_entry().is_some_and(Entry::is_sealed) => {
+                self.status = Some("High-security entry: needs its PIN, use the GUI or passman get".to_string());
+            }
+            Key::Char('y') => {
+                if let Some(password) = self.selected_entry().map(|e| e.password_str().reveal().to_string()) {
+                    self.copy(password, "Password", now);
+                    self.record_use();
+                }
+            }
+            Key::Char('u') => {
+                if let Some(username) = self.selected_entry().map(|e| e.username.clone()) {
+                    self.copy(username, "Username", now);
+                }
+            }
+            Key::Char('a') => self.mode = Mode::Form(Form::add()),
+            Key::Char('e') => {
+                if let (Some(id), Some(entry)) = (self.selected_id(), self.selected_entry()) {
+                    self.mode = Mode::Form(Form::edit(id, entry));
+                }
+            }
+            Key::Char('d') if self.pending_delete => {
+                self.pending_delete = false;
+                if let (Some(id), Some(entry)) = (self.selected_id(), self.selected_entry()) {
+                    let protected = entry.has_tag(&get_config().security.protected_tag);
+                    self.mode = Mode::ConfirmDelete { id: id.to_string(), protected, typed: String::new() };
+                }
+            }
+            Key::Char('d') => self.pending_delete = true,
+            Key::Ctrl('l') => self.lock(),
+            Key::Char('?') => self.mode = Mode::Help,
+            _ => {}
+        }
+    }
+
+    fn search_key(&mut self, key: Key) {
+        self.mode = Mode::Search;
+        match key {
+            Key::Ctrl('c') => self.quit = true,
+            Key::Enter => self.mode = Mode::Normal,
+            Key::Esc => {
+                self.query.clear();
+                self.mode = Mode::Normal;
+            }
+            Key::Up => self.select(self.selected.saturating_sub(1)),
+            Key::Down => self.select(self.selected + 1),
+            Key::Backspace => {
+                self.query.pop();
+            }
+            Key::Char(c) => self.query.push(c),
+            _ => return,
+        }
+        self.refresh();
+    }
+
+    fn form_key(&mut self, mut form: Form, key: Key) {
+        match key {
+            Key::Esc => return,
+            Key::Enter => match self.submit(&form) {
+                Ok(message) => {
+                    self.status = Some(message);
+                    return;
+                }
+                Err(message) => self.status = Some(message),
+            },
+            Key::Tab | Key::Down => form.focus = (form.focus + 1) % FORM_FIELDS.len(),
+            Key::BackTab | Key::Up => form.focus = (form.focus + FORM_FIELDS.len() - 1) % FORM_FIELDS.len(),
+            Key::Ctrl('g') => {
+                let length = get_config().password.default_length;
+                *form.values[PASSWORD_FIELD] = self.core.generate_password(length);
+                form.focus = PASSWORD_FIELD;
+            }
+            Key::Ctrl('r') => form.show_password = !form.show_password,
+            Key::Ctrl('u') => *form.values[form.focus] = String::new(),
+            Key::Backspace => {
+                form.values[form.focus].pop();
+            }
+            Key::Char(c) => form.values[form.focus].push(c),
+            _ => {}
+        }
+        self.mode = Mode::Form(form);
+    }
+
+    /// Validate and store a form; returns the status message
+    fn submit(&mut self, form: &Form) -> Result<String, String> {
+        let id = form.value(0).to_string();
+        if id.is_empty() || form.value(1).is_empty() || form.values[PASSWORD_FIELD].is_empty() {
+            return Err("Id, username and password are required".to_string());
+        }
+        let renamed = form.editing.as_deref().is_some_and(|original| original != id);
+        if (form.editing.is_none() || renamed) && self.core.get_entry(&id).is_some() {
+            return Err(format!("Entry '{}' already exists", id));
+        }
+
+        let tags: Vec<String> = form.value(4).split(',')
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        let url = Some(form.value(3).to_string()).filter(|url| !url.is_empty());
+        let note = Some(form.value(5).to_string()).filter(|note| !note.is_empty());
+
+        let result = match &form.editing {
+            None => {
+                let mut builder = EntryBuilder::new(form.value(1))
+                    .password(form.values[PASSWORD_FIELD].as_str())
+                    .tags(tags);
+                if let Some(url) = url {
+                    builder = builder.url(url);
+                }
+                if let Some(note) = note {
+                    builder = builder.note(note);
+                }
+                self.core.add_entry(id.clone(), builder.build())
+            }
+            Some(original) => {
+                let Some(mut entry) = self.core.get_entry(original).cloned() else {
+                    return Err(format!("Entry '{}' no longer exists", original));
+                };
+                entry.username = form.value(1).to_string();
+                if entry.password_str() != form.values[PASSWORD_FIELD].as_str() {
+                    entry.password = form.values[PASSWORD_FIELD].to_string().into();
+                    entry.holds_master_password = false;
+                }
+                entry.url = url;
+                entry.tags = tags;
+                entry.note = note;
+                entry.update();
+                if renamed {
+                    self.core.rename_entry(original, id.clone())
+                        .and_then(|_| self.core.update_entry(&id, entry))
+                } else {
+                    self.core.update_entry(original, entry)
+                }
+            }
+        };
+        result.map_err(|e| e.to_string())?;
+
+        if !self.save() {
+            return Err(self.status.take().unwrap_or_default());
+        }
+        self.refresh();
+        if let Some(position) = self.visible.iter().position(|visible| *visible == id) {
+            self.select(position);
+        }
+        Ok(match form.editing {
+            None => format!("Added '{}'", id),
+            Some(_) => format!("Saved '{}'", id),
+        })
+    }
+
+    fn confirm_key(&mut self, id: String, protected: bool, mut typed: String, key: Key) {
+        let confirmed = match (protected, key) {
+            (true, Key::Enter) => Entry::confirms_id(&typed, &id),
+            (true, Key::Backspace) => {
+                typed.pop();
+                self.mode = Mode::ConfirmDelete { id, protected, typed };
+                return;
+            }
+            (true, Key::Char(c)) => {
+                typed.push(c);
+                self.mode = Mode::ConfirmDelete { id, protected, typed };
+                return;
+            }
+            (false, Key::Char('y' | 'Y')) => true,
+            _ => false,
+        };
+
+        if !confirmed {
+            self.status = Some("Deletion cancelled".to_string());
+            return;
+        }
+        if let Err(e) = self.core.remove_entry(&id) {
+            self.status = Some(e.to_string());
+            return;
+        }
+        if self.save() {
+            self.status = Some(format!("Deleted '{}'", id));
+        }
+        self.revealed = false;
+        self.refresh();
+    }
+
+    fn locked_key(&mut self, mut input: Zeroizing<String>, unlocking: bool, key: Key) {
+        match key {
+            Key::Ctrl('c') | Key::Esc => self.quit = true,
+            Key::Enter if !input.is_empty() => {
+                self.status = None;
+                self.mode = Mode::Locked { input, unlocking: true };
+                return;
+            }
+            Key::Backspace => {
+                input.pop();
+            }
+            Key::Char(c) => input.push(c),
+            _ => {}
+        }
+        self.mode = Mode::Locked { input, unlocking };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app_with(ids: &[&str]) -> App {
+        let mut core = PassmanCore::with_vault("unused.dat", passman_core::Vault::new());
+        for id in ids {
+            core.add_entry(*id, EntryBuilder::new(format!("{}-user", id)).password("pw").build()).unwrap();
+        }
+        App::new(core, Instant::now())
+    }
+
+    fn press(app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            app.handle_key(Key::Char(c), Instant::now());
+        }
+    }
+
+    #[test]
+    fn test_navigation_and_search() {
+        let mut app = app_with(&["bank", "github", "mail"]);
+        press(&mut app, "jj");
+        assert_eq!(app.selected_id(), Some("mail"));
+        press(&mut app, "k");
+        assert_eq!(app.selected_id(), Some("github"));
+
+        press(&mut app, "/git");
+        assert_eq!(app.visible, vec!["github"]);
+        app.handle_key(Key::Esc, Instant::now());
+        assert_eq!(app.visible.len(), 3);
+    }
+
+    #[test]
+    fn test_dd_needs_confirmation() {
+        let mut app = app_with(&["bank", "github"]);
+        press(&mut app, "dj");
+        assert!(matches!(app.mode, Mode::Normal));
+        press(&mut app, "dd");
+        assert!(matches!(app.mode, Mode::ConfirmDelete { ref id, .. } if id == "github"));
+        press(&mut app, "n");
+        assert_eq!(app.entry_count(), 2);
+    }
+
+    #[test]
+    fn test_auto_lock_clears_vault() {
+        let mut app = app_with(&["bank"]);
+        app.lock_timeout = Some(Duration::from_secs(60));
+        let start = Instant::now();
+        app.handle_key(Key::Char('j'), start);
+        app.tick(start + Duration::from_secs(30));
+        assert!(matches!(app.mode, Mode::Normal));
+        app.tick(start + Duration::from_secs(61));
+        assert!(matches!(app.mode, Mode::Locked { .. }));
+        assert!(app.visible.is_empty() && !app.core.is_authenticated());
+    }
+
+    #[test]
+    fn test_copy_and_reveal_count_as_uses() {
+        let mut app = app_with(&["bank", "github"]);
+        press(&mut app, "jyr");
+        assert_eq!(app.selected_entry().unwrap().use_count, 2);
+        // Hiding again is not a use
+        press(&mut app, "r");
+        assert_eq!(app.selected_entry().unwrap().use_count, 2);
+        assert!(app.selected_entry().unwrap().last_used_at.is_some());
+        assert_eq!(app.core.get_entry("bank").unwrap().use_count, 0);
+        assert!(app.usage_pending);
+    }
+}
@@ -0,0 +1,123 @@
+//! Terminal UI
+//!
+//! `passman tui`: a full-screen, keyboard-driven vault browser for sessions
+//! where only a terminal is available (SSH, consoles). State and key
+//! handling live in `app`, layout in `view` (ratatui widgets), terminal I/O
+//! in `terminal` (crossterm, so Windows consoles work too).
+
+mod app;
+mod terminal;
+mod view;
+
+use std::error::Error;
+
+pub fn run(vault_path: &str) -> Result<(), Box<dyn Error>> {
+    use std::time::{Duration, Instant};
+    use passman_core::PassmanCore;
+    use crate::utils::read_master_password;
+    use app::{App, Effect};
+    use terminal::Terminal;
+
+    crate::warn_if_substituted(vault_path);
+    let master_password = read_master_password("Enter master password: ")?;
+    let mut core = PassmanCore::with_vault_path(vault_path);
+    core.unlock(&master_password)?;
+    let warning = core.master_password_warning(&master_password);
+    let sync_warning = core.sync_folder_warning();
+    // Saves use the key derived at unlock; the password itself isn't needed again
+    drop(master_password);
+
+    let clipboard_command = crate::config::get_config().ui.clipboard_command.clone();
+    let mut app = App::new(core, Instant::now());
+    app.status = warning.map(|strength| {
+        format!("Your master password is {} — change it with 'passman change-password'", strength.to_string().to_lowercase())
+    }).or_else(|| sync_warning.map(|risk| risk.message()));
+    let mut terminal = Terminal::enter()?;
+
+    while !app.quit {
+        terminal.draw(|frame| view::render(frame, &app))?;
+
+        // Unlock after "Unlocking…" is on screen; key derivation blocks
+        if app.unlock_pending() {
+            app.finish_unlock(Instant::now());
+            continue;
+        }
+
+        if let Some(key) = terminal.read_key(Duration::from_millis(250))? {
+            app.handle_key(key, Instant::now());
+        }
+        app.tick(Instant::now());
+
+        for effect in app.take_effects() {
+            let text = match &effect {
+                Effect::Copy(text) => text.as_str(),
+                Effect::ClearClipboard => "",
+            };
+            if clipboard_command.is_empty() {
+                terminal.write_raw(osc52(text).as_bytes())?;
+            } else if let Err(e) = pipe_to_command(&clipboard_command, text) {
+                app.status = Some(format!("Clipboard command failed: {}", e));
+            }
+        }
+    }
+    app.save_usage();
+    Ok(())
+}
+
+/// OSC 52 "set clipboard" escape; the terminal emulator on the far end of
+/// the SSH connection puts `text` on the local clipboard
+fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64(text.as_bytes()))
+}
+
+/// Run `command` through the shell (`cmd` on Windows) with `text` on its stdin
+fn pipe_to_command(command: &str, text: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+    let mut child = Command::new(shell)
+        .arg(flag)
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    child.wait()?;
+    Ok(())
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"hunter2!"), "aHVudGVyMiE=");
+    }
+}
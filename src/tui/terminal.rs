@@ -0,0 +1,114 @@
+//! Terminal Backend
+//!
+//! ratatui over crossterm: raw mode on the alternate screen, key events
+//! decoded into [`Key`], and raw writes for OSC 52. crossterm drives Unix
+//! terminals (including over SSH) and Windows consoles alike.
+
+use std::io::{self, Stdout, Write};
+use std::time::Duration;
+
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::crossterm::{cursor, execute};
+use ratatui::Frame;
+
+use super::app::Key;
+
+/// Raw-mode terminal on the alternate screen; restored on drop
+pub struct Terminal {
+    inner: ratatui::Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl Terminal {
+    pub fn enter() -> io::Result<Self> {
+        enable_raw_mode()?;
+        let inner = ratatui::Terminal::new(CrosstermBackend::new(io::stdout()))
+            .inspect_err(|_| { let _ = disable_raw_mode(); })?;
+        // From here on, dropping `terminal` undoes everything
+        let mut terminal = Self { inner };
+        execute!(terminal.inner.backend_mut(), EnterAlternateScreen, cursor::Hide)?;
+        terminal.inner.clear()?;
+        Ok(terminal)
+    }
+
+    /// Redraw the screen; ratatui only writes the cells that changed
+    pub fn draw(&mut self, render: impl FnOnce(&mut Frame)) -> io::Result<()> {
+        self.inner.draw(render).map(|_| ())
+    }
+
+    /// Wait up to `timeout` for a key press. Resizes are picked up by the
+    /// next draw.
+    pub fn read_key(&mut self, timeout: Duration) -> io::Result<Option<Key>> {
+        if !event::poll(timeout)? {
+            return Ok(None);
+        }
+        match event::read()? {
+            Event::Key(event) => Ok(key_of(event)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Write a raw escape sequence (used for OSC 52 clipboard requests)
+    pub fn write_raw(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let backend = self.inner.backend_mut();
+        backend.write_all(bytes)?;
+        backend.flush()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = execute!(self.inner.backend_mut(), LeaveAlternateScreen, cursor::Show);
+        let _ = disable_raw_mode();
+    }
+}
+
+/// The app's key for a crossterm key event. Releases are dropped; Windows
+/// consoles report them alongside presses.
+fn key_of(event: KeyEvent) -> Option<Key> {
+    if event.kind == KeyEventKind::Release {
+        return None;
+    }
+    let key = match event.code {
+        KeyCode::Char(c) if event.modifiers.contains(KeyModifiers::CONTROL) => Key::Ctrl(c.to_ascii_lowercase()),
+        KeyCode::Char(c) => Key::Char(c),
+        KeyCode::Enter => Key::Enter,
+        KeyCode::Esc => Key::Esc,
+        KeyCode::Backspace => Key::Backspace,
+        KeyCode::Tab => Key::Tab,
+        KeyCode::BackTab => Key::BackTab,
+        KeyCode::Up => Key::Up,
+        KeyCode::Down => Key::Down,
+        KeyCode::Left => Key::Left,
+        KeyCode::Right => Key::Right,
+        KeyCode::Home => Key::Home,
+        KeyCode::End => Key::End,
+        KeyCode::PageUp => Key::PageUp,
+        KeyCode::PageDown => Key::PageDown,
+        KeyCode::Delete => Key::Delete,
+        _ => return None,
+    };
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::crossterm::event::KeyEventState;
+
+    #[test]
+    fn test_key_events() {
+        let press = |code, modifiers| KeyEvent::new(code, modifiers);
+        assert_eq!(key_of(press(KeyCode::Char('j'), KeyModifiers::NONE)), Some(Key::Char('j')));
+        assert_eq!(key_of(press(KeyCode::Char('G'), KeyModifiers::SHIFT)), Some(Key::Char('G')));
+        assert_eq!(key_of(press(KeyCode::Char('c'), KeyModifiers::CONTROL)), Some(Key::Ctrl('c')));
+        assert_eq!(key_of(press(KeyCode::Char('L'), KeyModifiers::CONTROL | KeyModifiers::SHIFT)), Some(Key::Ctrl('l')));
+        assert_eq!(key_of(press(KeyCode::Char('é'), KeyModifiers::NONE)), Some(Key::Char('é')));
+        assert_eq!(key_of(press(KeyCode::BackTab, KeyModifiers::SHIFT)), Some(Key::BackTab));
+        assert_eq!(key_of(press(KeyCode::F(1), KeyModifiers::NONE)), None);
+
+        let release = KeyEvent::new_with_kind_and_state(KeyCode::Enter, KeyModifiers::NONE, KeyEventKind::Release, KeyEventState::NONE);
+        assert_eq!(key_of(release), None);
+    }
+}
@@ -0,0 +1,264 @@
+//! TUI Layout
+//!
+//! Draws the app state with ratatui widgets. Wide terminals get a list pane
+//! and a detail pane side by side; narrow ones show one pane at a time.
+
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::Frame;
+
+use super::app::{App, Form, Mode, FORM_FIELDS};
+use crate::utils::{format_age, password_mask};
+
+/// Terminals narrower than this show the list and details one at a time
+const TWO_PANE_MIN_WIDTH: u16 = 72;
+
+const BOLD: Style = Style::new().add_modifier(Modifier::BOLD);
+const DIM: Style = Style::new().add_modifier(Modifier::DIM);
+const SELECTED: Style = Style::new().add_modifier(Modifier::REVERSED);
+const ACCENT: Style = Style::new().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+const WARNING: Style = Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+const DANGER: Style = Style::new().fg(Color::Red).add_modifier(Modifier::BOLD);
+
+pub fn render(frame: &mut Frame, app: &App) {
+    let [header_area, body, footer_area] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Fill(1),
+        Constraint::Length(1),
+    ]).areas(frame.area());
+
+    frame.render_widget(header(app), header_area);
+    match &app.mode {
+        Mode::Locked { input, unlocking } => locked(frame, body, input.chars().count(), *unlocking),
+        Mode::Form(form) => frame.render_widget(form_view(form), body),
+        Mode::Help => frame.render_widget(help(), body),
+        Mode::Detail if body.width < TWO_PANE_MIN_WIDTH => frame.render_widget(detail(app), body),
+        _ if body.width < TWO_PANE_MIN_WIDTH => list(frame, body, app),
+        _ => {
+            let left_width = (body.width * 2 / 5).clamp(24, 40);
+            let [left, right] = Layout::horizontal([Constraint::Length(left_width), Constraint::Fill(1)]).areas(body);
+            list(frame, left, app);
+            frame.render_widget(detail(app).block(Block::new().borders(Borders::LEFT).border_style(DIM)), right);
+        }
+    }
+    frame.render_widget(footer(app), footer_area);
+}
+
+fn header(app: &App) -> Line<'_> {
+    let mut line = Line::from(vec![
+        Span::styled(" passman ", SELECTED),
+        Span::styled(format!(" {} ", app.vault_path()), DIM),
+    ]);
+    if !matches!(app.mode, Mode::Locked { .. }) {
+        line.push_span(Span::styled(format!(" {} entries", app.entry_count()), DIM));
+    }
+    if !app.query.is_empty() || matches!(app.mode, Mode::Search) {
+        line.push_span(Span::styled("  /", ACCENT));
+        line.push_span(Span::styled(app.query.as_str(), BOLD));
+        if matches!(app.mode, Mode::Search) {
+            line.push_span(Span::styled("_", ACCENT));
+        }
+    }
+    line
+}
+
+fn footer(app: &App) -> Line<'_> {
+    match &app.mode {
+        Mode::ConfirmDelete { id, protected: true, typed } => Line::from(vec![
+            Span::styled(format!(" Protected entry: type '{}' and Enter to delete: ", id), DANGER),
+            Span::styled(typed.as_str(), BOLD),
+        ]),
+        Mode::ConfirmDelete { id, .. } => Line::styled(format!(" Delete '{}'? (y/N) ", id), DANGER),
+        _ => match &app.status {
+            Some(status) => Line::styled(format!(" {}", status), WARNING),
+            None => Line::styled(hints(&app.mode), DIM),
+        },
+    }
+}
+
+fn hints(mode: &Mode) -> &'static str {
+    match mode {
+        Mode::Search => " type to filter  ↑/↓ move  Enter keep  Esc clear",
+        Mode::Form(_) => " Tab/↑/↓ field  ^G generate  ^R show password  ^U clear  Enter save  Esc cancel",
+        Mode::Locked { .. } => " Enter unlock  Esc quit",
+        Mode::Help => " any key to go back",
+        _ => " j/k move  / search  Enter open  r reveal  y copy  a add  e edit  dd delete  ? help  q quit",
+    }
+}
+
+/// The entry list; ratatui scrolls it to keep the selection on screen
+fn list(frame: &mut Frame, area: Rect, app: &App) {
+    if app.visible.is_empty() {
+        let message = if app.query.is_empty() { "No entries. Press 'a' to add one." } else { "No matches." };
+        frame.render_widget(Line::styled(format!(" {}", message), DIM), area);
+        return;
+    }
+
+    let items = app.visible.iter().map(|id| {
+        let username = app.username_of(id).unwrap_or_default();
+        ListItem::new(Line::from(vec![
+            Span::styled(format!(" {} ", id), BOLD),
+            Span::styled(format!(" {}", username), DIM),
+        ]))
+    });
+    let mut state = ListState::default().with_selected(Some(app.selected));
+    frame.render_stateful_widget(List::new(items).highlight_style(SELECTED), area, &mut state);
+}
+
+fn detail(app: &App) -> Paragraph<'_> {
+    let (Some(id), Some(entry)) = (app.selected_id(), app.selected_entry()) else {
+        return Paragraph::new(Line::styled(" Nothing selected", DIM));
+    };
+
+    let password = if entry.is_sealed() {
+        "🛡 high security: open it in the GUI or with passman get".to_string()
+    } else if app.revealed {
+        entry.password_str().reveal().to_string()
+    } else {
+        password_mask('•', crate::config::get_config().ui.mask_length)
+    };
+    let field = |label: &str, value: String, style: Style| {
+        Line::from(vec![Span::styled(format!(" {:<10}", label), DIM), Span::styled(value, style)])
+    };
+
+    let mut lines = vec![
+        Line::styled(format!(" {}", id), ACCENT),
+        Line::default(),
+        field("Username", entry.username.clone(), Style::new()),
+        field("Password", password, if app.revealed { WARNING } else { Style::new() }),
+    ];
+    if let Some(url) = &entry.url {
+        lines.push(field("URL", url.clone(), Style::new()));
+    }
+    if !entry.tags.is_empty() {
+        lines.push(field("Tags", entry.tags.join(", "), Style::new()));
+    }
+    lines.push(field("Modified", entry.modified_at.format("%Y-%m-%d %H:%M").to_string(), DIM));
+    if let Some(last_used) = entry.last_used_at {
+        lines.push(field("Last used", format_age(last_used, chrono::Utc::now()), DIM));
+    }
+    if let Some(note) = entry.note.as_deref().filter(|note| !note.is_empty()) {
+        lines.push(Line::default());
+        lines.extend(note.lines().map(|line| Line::raw(format!(" {}", line))));
+    }
+    Paragraph::new(lines).wrap(Wrap { trim: false })
+}
+
+fn form_view(form: &Form) -> Paragraph<'_> {
+    let title = match &form.editing {
+        Some(id) => format!(" Edit '{}'", id),
+        None => " New entry".to_string(),
+    };
+    let mut lines = vec![Line::styled(title, ACCENT), Line::default()];
+    for (i, label) in FORM_FIELDS.iter().enumerate() {
+        let value = form.values[i].as_str();
+        let shown = if *label == "Password" && !form.show_password {
+            "•".repeat(value.chars().count())
+        } else {
+            value.to_string()
+        };
+        let focused = i == form.focus;
+        let mut line = Line::from(vec![
+            Span::styled(if focused { " > " } else { "   " }, ACCENT),
+            Span::styled(format!("{:<10}", label), if focused { BOLD } else { DIM }),
+            Span::raw(shown),
+        ]);
+        if focused {
+            line.push_span(Span::styled("_", ACCENT));
+        }
+        lines.push(line);
+    }
+    Paragraph::new(lines)
+}
+
+fn locked(frame: &mut Frame, area: Rect, typed: usize, unlocking: bool) {
+    let prompt = if unlocking {
+        Line::styled("   Unlocking…", DIM)
+    } else {
+        Line::from(vec![
+            Span::styled("   Master password: ", DIM),
+            Span::raw("•".repeat(typed)),
+            Span::styled("_", ACCENT),
+        ])
+    };
+    let [_, area] = Layout::vertical([Constraint::Length(area.height / 3), Constraint::Fill(1)]).areas(area);
+    frame.render_widget(Paragraph::new(vec![Line::styled("   Vault locked", ACCENT), Line::default(), prompt]), area);
+}
+
+fn help() -> Paragraph<'static> {
+    let keys = [
+        ("j / k, ↓ / ↑", "move selection"),
+        ("g / G", "first / last entry"),
+        ("/", "filter (id, username, URL, tags, notes)"),
+        ("Enter / l", "open details (narrow terminals)"),
+        ("Esc / h", "back, or clear the filter"),
+        ("r", "reveal / hide password"),
+        ("y / u", "copy password / username"),
+        ("a / e", "add / edit entry"),
+        ("dd", "delete entry (asks first)"),
+        ("Ctrl+L", "lock now"),
+        ("q", "quit"),
+    ];
+    let mut lines = vec![Line::styled(" Keys", ACCENT), Line::default()];
+    lines.extend(keys.iter().map(|(key, action)| {
+        Line::from(vec![Span::styled(format!("   {:<16}", key), BOLD), Span::raw(*action)])
+    }));
+    Paragraph::new(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use passman_core::{EntryBuilder, PassmanCore, Vault};
+    use ratatui::backend::TestBackend;
+    use ratatui::buffer::Buffer;
+    use std::time::Instant;
+
+    fn draw(app: &App, width: u16, height: u16) -> Buffer {
+        let mut terminal = ratatui::Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|frame| render(frame, app)).unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    fn row(buffer: &Buffer, y: u16) -> String {
+        (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect()
+    }
+
+    fn app_with(ids: &[&str]) -> App {
+        let mut core = PassmanCore::with_vault("unused.dat", Vault::new());
+        for id in ids {
+            core.add_entry(*id, EntryBuilder::new("octocat").password("pw").build()).unwrap();
+        }
+        App::new(core, Instant::now())
+    }
+
+    #[test]
+    fn test_layout_adapts_to_width() {
+        let app = app_with(&["github"]);
+
+        let wide = draw(&app, 100, 10);
+        assert!(row(&wide, 1).contains('│'));
+        assert!(row(&wide, 1).contains("github"));
+
+        let narrow = draw(&app, 40, 10);
+        assert!(!row(&narrow, 1).contains('│'));
+        assert!(row(&narrow, 1).contains("github"));
+
+        // Tiny terminals must not panic
+        draw(&app, 3, 1);
+    }
+
+    #[test]
+    fn test_selection_stays_on_screen() {
+        let ids: Vec<String> = (0..20).map(|i| format!("site{:02}", i)).collect();
+        let mut app = app_with(&ids.iter().map(String::as_str).collect::<Vec<_>>());
+        app.selected = 15;
+
+        // Header and footer leave 4 list rows; the selection is the last of them
+        let buffer = draw(&app, 40, 6);
+        assert!(row(&buffer, 4).contains("site15"));
+        assert!(buffer[(1, 4)].style().add_modifier.contains(Modifier::REVERSED));
+    }
+}
@@ -0,0 +1,785 @@
+//! End-to-end tests of the `passman` command line.
+//!
+//! Each test runs the real binary against a vault, config and data
+//! directory inside its own temp dir, supplying the master password through
+//! `PASSMAN_PASSWORD`, `--password-file` or piped stdin.
+
+use std::fs;
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use serde_json::Value;
+use tempfile::TempDir;
+
+const MASTER: &str = "Correct-Horse-42-battery";
+
+/// A vault with its own config and data directories
+struct Sandbox {
+    dir: TempDir,
+}
+
+impl Sandbox {
+    fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        // Cheap key derivation keeps each command fast
+        let config_dir = dir.path().join("config").join("passman");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(
+            config_dir.join("passman.toml"),
+            "[security]\nargon2_memory_kb = 8192\nargon2_time_cost = 1\nargon2_parallelism = 1\n",
+        ).unwrap();
+        Self { dir }
+    }
+
+    fn vault(&self) -> PathBuf {
+        self.dir.path().join("vault.dat")
+    }
+
+    /// `passman --vault <sandbox vault>` with no master password source
+    fn cmd(&self) -> Command {
+        self.sandboxed(Command::cargo_bin("passman").unwrap())
+    }
+
+    /// `cmd()` run through `sh` under umask 000, as on a machine where new
+    /// files are readable by everyone unless a program says otherwise
+    #[cfg(unix)]
+    fn relaxed_umask(&self) -> Command {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "umask 000 && exec \"$0\" \"$@\""]).arg(assert_cmd::cargo::cargo_bin("passman"));
+        self.sandboxed(cmd)
+    }
+
+    fn sandboxed(&self, mut cmd: Command) -> Command {
+        cmd.env("HOME", self.dir.path())
+            .env("XDG_CONFIG_HOME", self.dir.path().join("config"))
+            .env("XDG_DATA_HOME", self.dir.path().join("data"))
+            .env_remove("PASSMAN_VAULT")
+            .env_remove("PASSMAN_PASSWORD")
+            .arg("--offline")
+            .arg("--vault")
+            .arg(self.vault());
+        cmd
+    }
+
+    /// `cmd()` unlocking with the master password from the environment
+    fn unlocked(&self) -> Command {
+        let mut cmd = self.cmd();
+        cmd.env("PASSMAN_PASSWORD", MASTER);
+        cmd
+    }
+
+    fn init(&self) {
+        self.cmd().arg("init").write_stdin(format!("{0}\n{0}\n", MASTER)).assert().success();
+    }
+
+    fn add(&self, id: &str, username: &str, password: &str, extra: &[&str]) {
+        self.unlocked()
+            .args(["add", id, "--username", username, "--password", password])
+            .args(extra)
+            .assert()
+            .success();
+    }
+
+    fn stdout(&self, cmd: &mut Command) -> String {
+        String::from_utf8(cmd.assert().success().get_output().stdout.clone()).unwrap()
+    }
+
+    fn list_json(&self) -> Vec<Value> {
+        let out = self.stdout(self.unlocked().args(["list", "--output", "json"]));
+        serde_json::from_str::<Value>(&out).unwrap().as_array().unwrap().clone()
+    }
+}
+
+#[test]
+fn test_init_with_piped_password() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    assert!(sandbox.vault().exists());
+
+    // A second init refuses to overwrite the vault
+    sandbox.cmd().arg("init").write_stdin(format!("{0}\n{0}\n", MASTER)).assert().code(1);
+
+    let mismatched = Sandbox::new();
+    mismatched.cmd().arg("init").write_stdin(format!("{}\nsomething-else\n", MASTER)).assert().code(1);
+    assert!(!mismatched.vault().exists());
+}
+
+#[test]
+fn test_add_with_flags_and_get_fields() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("github", "ann", "s3cret-Value", &["--url", "https://github.com", "--tags", "work, dev", "--note", "2FA on"]);
+
+    let out = sandbox.stdout(sandbox.unlocked().args(["get", "github", "--field", "username", "--field", "password", "--field", "tags"]));
+    assert_eq!(out, "ann\ns3cret-Value\nwork, dev\n");
+    let out = sandbox.stdout(sandbox.unlocked().args(["get", "github", "-f", "url", "-f", "note"]));
+    assert_eq!(out, "https://github.com\n2FA on\n");
+
+    // Adding the same id again fails without touching the entry
+    sandbox.unlocked().args(["add", "github", "--username", "bob", "--password", "x"]).assert().code(1);
+    sandbox.unlocked().args(["get", "github", "--field", "secret"]).assert().code(1);
+}
+
+#[test]
+fn test_get_spell_names_look_alike_characters() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("wifi", "home", "Ol0-xyz1", &[]);
+
+    let out = sandbox.stdout(sandbox.unlocked().args(["get", "wifi", "--spell"]));
+    assert!(out.contains("Password:\nOl0-  xyz1\n^^^      ^\n  O = capital o\n  l = lowercase L\n  0 = zero\n  1 = one\n"), "{}", out);
+    assert!(!out.contains("Copy password"));
+    sandbox.unlocked().args(["get", "wifi", "--spell", "--copy"]).assert().code(2);
+}
+
+#[test]
+fn test_add_without_id_proposes_one_from_url() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    let add = || {
+        let mut cmd = sandbox.unlocked();
+        cmd.args(["add", "--url", "https://github.com/login", "--username", "ann", "--password", "pw"]);
+        cmd
+    };
+    add().write_stdin("\n").assert().success();
+    add().write_stdin("github work\n").assert().success();
+
+    let ids: Vec<String> = sandbox.list_json().iter().map(|entry| entry["id"].as_str().unwrap().to_string()).collect();
+    assert_eq!(ids, vec!["github", "github work"]);
+
+    // Nothing to derive an ID from
+    sandbox.unlocked().args(["add", "--username", "ann", "--password", "pw"]).assert().code(1);
+}
+
+#[test]
+fn test_add_batch_stdin_saves_once_and_reports_each_line() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("github", "ann", "old-pw", &[]);
+    let stream = concat!(
+        "{\"id\": \"github\", \"username\": \"bob\", \"password\": \"new-pw\"}\n",
+        "{\"id\": \"mail\", \"username\": \"me\", \"password\": \"pw\", \"tags\": [\"home\"]}\n",
+        "not json\n",
+        "{\"id\": \"bank\", \"username\": \"ann\", \"password\": \"pw\", \"note\": \"joint\"}\n",
+    );
+
+    // Stdin holds the entries, so there is no prompting for the master password
+    sandbox.cmd().args(["add", "--batch-stdin"]).write_stdin(stream).assert().code(1);
+    sandbox.unlocked().args(["add", "--batch-stdin", "--strict"]).write_stdin(stream).assert().code(1);
+    assert_eq!(sandbox.list_json().len(), 1);
+
+    let output = sandbox.unlocked().args(["add", "--batch-stdin"]).write_stdin(stream).assert().success().get_output().clone();
+    let out = String::from_utf8(output.stdout).unwrap();
+    assert!(out.contains("line 1: ⚠ skipped 'github'") && out.contains("line 4: ✓ added 'bank'"), "{}", out);
+    assert!(out.contains("2 added, 0 replaced, 1 skipped, 1 malformed line(s)"), "{}", out);
+    assert!(String::from_utf8(output.stderr).unwrap().contains("line 3:"));
+    assert_eq!(sandbox.stdout(sandbox.unlocked().args(["get", "mail", "-f", "tags"])), "home\n");
+
+    let out = sandbox.stdout(sandbox.unlocked().args(["add", "--batch-stdin", "--on-conflict", "suffix"]).write_stdin(stream));
+    assert!(out.contains("line 1: ✓ 'github' exists, added as 'github (copy)'"), "{}", out);
+    sandbox.unlocked().args(["add", "--batch-stdin", "--on-conflict", "overwrite"]).write_stdin(stream).assert().success();
+    assert_eq!(sandbox.stdout(sandbox.unlocked().args(["get", "github", "-f", "username"])), "bob\n");
+    sandbox.unlocked().args(["add", "github", "--on-conflict", "overwrite"]).assert().code(2);
+    sandbox.unlocked().args(["add", "github", "--strict"]).assert().code(2);
+}
+
+#[test]
+fn test_add_generates_password() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.unlocked().args(["add", "mail", "--username", "ann", "--generate", "--length", "24"]).assert().success();
+
+    let out = sandbox.stdout(sandbox.unlocked().args(["get", "mail", "--field", "password"]));
+    assert_eq!(out.trim_end().chars().count(), 24);
+}
+
+#[test]
+fn test_list_json_is_machine_readable() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    assert!(sandbox.list_json().is_empty());
+
+    sandbox.add("github", "ann", "pw-one", &["--tags", "work"]);
+    sandbox.add("bank", "ann.b", "pw-two", &[]);
+
+    let entries = sandbox.list_json();
+    let ids: Vec<&str> = entries.iter().map(|entry| entry["id"].as_str().unwrap()).collect();
+    assert_eq!(ids, vec!["bank", "github"]);
+    assert_eq!(entries[1]["tags"], serde_json::json!(["work"]));
+    assert!(entries.iter().all(|entry| entry.get("password").is_none()));
+
+    sandbox.unlocked().args(["list", "--output", "yaml"]).assert().code(1);
+}
+
+#[test]
+fn test_edit_keeps_created_at_and_tags() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("github", "ann", "pw-one", &["--tags", "work,dev"]);
+    let before = sandbox.list_json().remove(0);
+
+    // New username; keep the password, note and expiry
+    sandbox.unlocked().args(["edit", "github"]).write_stdin("bob\nn\n\n\n").assert().success();
+
+    let after = sandbox.list_json().remove(0);
+    assert_eq!(after["username"], "bob");
+    assert_eq!(after["created_at"], before["created_at"]);
+    assert_eq!(after["tags"], before["tags"]);
+    let out = sandbox.stdout(sandbox.unlocked().args(["get", "github", "--field", "password"]));
+    assert_eq!(out, "pw-one\n");
+}
+
+#[test]
+fn test_remove_force() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("github", "ann", "pw-one", &[]);
+
+    sandbox.unlocked().args(["rm", "github", "--force"]).assert().success();
+    sandbox.unlocked().args(["get", "github", "--field", "username"]).assert().code(1);
+    sandbox.unlocked().args(["rm", "github", "--force"]).assert().code(1);
+}
+
+#[test]
+fn test_transfer_round_trip() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("github", "ann", "pw-one", &["--url", "https://github.com"]);
+    sandbox.add("bank", "ann.b", "pw-two", &[]);
+
+    let export = sandbox.dir.path().join("export.json");
+    sandbox.unlocked().args(["transfer", "export", "--format", "json", "--output"]).arg(&export).assert().success();
+
+    // Importing into a vault that doesn't exist yet creates it
+    let other = Sandbox::new();
+    other.unlocked().args(["transfer", "import", "--format", "json", "--input"]).arg(&export).assert().success();
+
+    let out = other.stdout(other.unlocked().args(["get", "github", "--field", "password", "--field", "url"]));
+    assert_eq!(out, "pw-one\nhttps://github.com\n");
+    assert_eq!(other.list_json().len(), 2);
+}
+
+#[test]
+fn test_export_to_a_sync_folder_asks_and_writes_private_files() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("github", "ann", "pw-one", &[]);
+    let dropbox = sandbox.dir.path().join("Dropbox");
+    fs::create_dir(&dropbox).unwrap();
+    let export = dropbox.join("export.json");
+
+    let output = sandbox.unlocked().args(["transfer", "export", "--output"]).arg(&export).write_stdin("n\n").assert().code(1).get_output().clone();
+    assert!(String::from_utf8(output.stderr).unwrap().contains("is in a Dropbox folder"));
+    assert!(!export.exists());
+
+    sandbox.unlocked().args(["transfer", "export", "--yes", "--output"]).arg(&export).assert().success();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&export).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    // An encrypted export needs no confirmation and opens as a vault
+    let copy = dropbox.join("copy.dat");
+    sandbox.unlocked().args(["transfer", "export", "--format", "encrypted", "--output"]).arg(&copy).assert().success();
+    assert!(!String::from_utf8_lossy(&fs::read(&copy).unwrap()).contains("pw-one"));
+    sandbox.unlocked().args(["rm", "github", "--force"]).assert().success();
+    fs::copy(&copy, sandbox.vault()).unwrap();
+    let out = sandbox.stdout(sandbox.unlocked().args(["get", "github", "--field", "password"]));
+    assert_eq!(out, "pw-one\n");
+}
+
+#[test]
+fn test_keepass_xml_export_imports_back() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("work/github", "ann", "pw&<one>", &["--url", "https://github.com"]);
+    sandbox.add("bank", "ann.b", "pw-two", &[]);
+
+    let export = sandbox.dir.path().join("export.xml");
+    sandbox.unlocked().args(["transfer", "export", "--format", "keepass-xml", "--output"]).arg(&export).assert().success();
+    let xml = std::fs::read_to_string(&export).unwrap();
+    assert!(xml.contains("<Name>work</Name>") && xml.contains("pw&amp;&lt;one&gt;"), "{}", xml);
+
+    let other = Sandbox::new();
+    other.unlocked().args(["transfer", "import", "--format", "keepass", "--input"]).arg(&export).assert().success();
+    let out = other.stdout(other.unlocked().args(["get", "work/github", "--field", "password", "--field", "url"]));
+    assert_eq!(out, "pw&<one>\nhttps://github.com\n");
+    assert_eq!(other.list_json().len(), 2);
+}
+
+#[test]
+fn test_import_moves_oversize_fields_into_attachments() {
+    let sandbox = Sandbox::new();
+    let csv = sandbox.dir.path().join("big.csv");
+    fs::write(&csv, format!("id,username,password,note\nbig,ann,pw-one,{}\nsmall,bob,pw-two,short\n", "x".repeat(500))).unwrap();
+
+    let out = sandbox.stdout(sandbox.unlocked().args(["transfer", "import", "--format", "csv", "--max-note-chars", "100", "--input"]).arg(&csv));
+    assert!(out.contains("Imported: 2 entries") && out.contains("moved to attachments: 1 entries") && out.contains("big (note)"), "{}", out);
+
+    let out = sandbox.stdout(sandbox.unlocked().args(["get", "big", "--show"]));
+    assert!(out.contains("Note: Moved to the attachment note.txt (500 characters)") && out.contains("Attachments: note.txt (500 B)"), "{}", out);
+    assert!(!sandbox.stdout(sandbox.unlocked().args(["get", "small", "--show"])).contains("Attachments"));
+}
+
+#[test]
+fn test_change_password() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("github", "ann", "pw-one", &[]);
+
+    let new_password = "Brand-New-Master-77";
+    sandbox.unlocked()
+        .arg("change-password")
+        .write_stdin(format!("{0}\n{0}\ny\n", new_password))
+        .assert()
+        .success();
+
+    sandbox.unlocked().args(["get", "github", "--field", "username"]).assert().code(1);
+    let out = sandbox.stdout(sandbox.cmd().env("PASSMAN_PASSWORD", new_password).args(["get", "github", "--field", "username"]));
+    assert_eq!(out, "ann\n");
+}
+
+#[test]
+fn test_password_file() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("github", "ann", "pw-one", &[]);
+
+    let file = sandbox.dir.path().join("master.txt");
+    fs::write(&file, format!("{}\n", MASTER)).unwrap();
+    let out = sandbox.stdout(sandbox.cmd().arg("--password-file").arg(&file).args(["get", "github", "--field", "username"]));
+    assert_eq!(out, "ann\n");
+
+    let missing = sandbox.dir.path().join("missing.txt");
+    sandbox.cmd().arg("--password-file").arg(&missing).args(["get", "github", "--field", "username"]).assert().code(1);
+}
+
+#[test]
+fn test_wrong_password_fails_cleanly() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("github", "ann", "pw-one", &[]);
+
+    for args in [
+        vec!["get", "github", "--field", "password"],
+        vec!["list", "--output", "json"],
+        vec!["rm", "github", "--force"],
+    ] {
+        let output = sandbox.cmd().env("PASSMAN_PASSWORD", "wrong-password-1A").args(&args).assert().code(1).get_output().clone();
+        assert!(output.stdout.is_empty(), "{:?} wrote to stdout: {}", args, String::from_utf8_lossy(&output.stdout));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Error:"));
+    }
+
+    // Piped input: the prompt goes to stderr, leaving stdout empty too
+    let output = sandbox.cmd().args(["get", "github", "--field", "password"]).write_stdin("wrong-password-1A\n").assert().code(1).get_output().clone();
+    assert!(output.stdout.is_empty());
+
+    // The entry survived the failed removal
+    let out = sandbox.stdout(sandbox.unlocked().args(["get", "github", "--field", "username"]));
+    assert_eq!(out, "ann\n");
+}
+
+#[test]
+fn test_demo_takes_no_other_arguments() {
+    // Rejected by the parser, before any window could open
+    let sandbox = Sandbox::new();
+    let output = sandbox.cmd().arg("--demo").assert().code(2).get_output().stderr.clone();
+    assert!(String::from_utf8_lossy(&output).contains("--demo"));
+    assert!(!sandbox.vault().exists());
+}
+
+#[test]
+fn test_git_credential_store_get_erase() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    let socket = sandbox.dir.path().join("no-server.sock");
+    let git = |action: &str, input: &str| {
+        let mut cmd = sandbox.unlocked();
+        cmd.args(["git-credential", "--socket"]).arg(&socket).arg(action).write_stdin(input.to_string());
+        cmd
+    };
+
+    git("store", "protocol=https\nhost=github.com\nusername=ann\npassword=ghp_one\n\n").assert().success();
+    let entries = sandbox.list_json();
+    assert_eq!(entries[0]["id"], "github.com");
+    assert_eq!(entries[0]["tags"], serde_json::json!(["git"]));
+
+    let out = sandbox.stdout(&mut git("get", "protocol=https\nhost=github.com\n\n"));
+    assert_eq!(out, "username=ann\npassword=ghp_one\n");
+
+    // A rotated token updates the same entry
+    git("store", "protocol=https\nhost=github.com\nusername=ann\npassword=ghp_two\n").assert().success();
+    assert_eq!(sandbox.list_json().len(), 1);
+    let out = sandbox.stdout(&mut git("get", "url=https://ann@github.com/org/repo.git\n"));
+    assert_eq!(out, "username=ann\npassword=ghp_two\n");
+
+    // Unknown hosts get no answer, so git prompts instead
+    assert_eq!(sandbox.stdout(&mut git("get", "protocol=https\nhost=gitlab.com\n")), "");
+
+    // Erasing a stale password leaves the entry; the current one trashes it
+    git("erase", "protocol=https\nhost=github.com\nusername=ann\npassword=ghp_one\n").assert().success();
+    assert_eq!(sandbox.stdout(&mut git("get", "protocol=https\nhost=github.com\n")), "username=ann\npassword=ghp_two\n");
+    git("erase", "protocol=https\nhost=github.com\nusername=ann\npassword=ghp_two\n").assert().success();
+    assert_eq!(sandbox.stdout(&mut git("get", "protocol=https\nhost=github.com\n")), "");
+}
+
+#[test]
+fn test_git_credential_never_prompts() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("github", "ann", "pw-one", &["--url", "https://github.com"]);
+
+    // No password source and no server: fail on stderr, nothing on stdout
+    let output = sandbox.cmd()
+        .args(["git-credential", "--socket"]).arg(sandbox.dir.path().join("no-server.sock")).arg("get")
+        .write_stdin("protocol=https\nhost=github.com\n")
+        .assert()
+        .code(1)
+        .get_output()
+        .clone();
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("can't prompt"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_interrupt_at_prompt_exits_130() {
+    use std::io::Read;
+    use std::process::Stdio;
+
+    let sandbox = Sandbox::new();
+    sandbox.init();
+
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("passman"))
+        .env("HOME", sandbox.dir.path())
+        .env("XDG_CONFIG_HOME", sandbox.dir.path().join("config"))
+        .env("XDG_DATA_HOME", sandbox.dir.path().join("data"))
+        .env_remove("PASSMAN_VAULT")
+        .env_remove("PASSMAN_PASSWORD")
+        .arg("--offline")
+        .arg("--vault")
+        .arg(sandbox.vault())
+        .arg("list")
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // The prompt is printed after the handler is installed
+    let mut stderr = child.stderr.take().unwrap();
+    let mut seen = Vec::new();
+    let mut buf = [0u8; 64];
+    while !String::from_utf8_lossy(&seen).contains("master password") {
+        let n = stderr.read(&mut buf).unwrap();
+        assert!(n > 0, "exited before prompting: {}", String::from_utf8_lossy(&seen));
+        seen.extend_from_slice(&buf[..n]);
+    }
+
+    unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGINT) };
+    assert_eq!(child.wait().unwrap().code(), Some(130));
+    assert!(!sandbox.dir.path().join("vault.dat.tmp").exists());
+}
+
+#[test]
+fn test_archive_hides_entry_until_unarchived() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("old-job", "ann", "pw-one", &[]);
+    sandbox.add("current", "ann", "pw-two", &[]);
+
+    let out = sandbox.stdout(sandbox.unlocked().args(["archive", "old-job"]));
+    assert!(out.contains("Archived 'old-job'"));
+    let ids = |entries: Vec<Value>| entries.iter().map(|entry| entry["id"].as_str().unwrap().to_string()).collect::<Vec<_>>();
+    assert_eq!(ids(sandbox.list_json()), vec!["current"]);
+
+    let out = sandbox.stdout(sandbox.unlocked().args(["list", "--archived", "--output", "json"]));
+    let archived: Vec<Value> = serde_json::from_str(&out).unwrap();
+    assert_eq!(ids(archived.clone()), vec!["old-job"]);
+    assert_eq!(archived[0]["archived"], true);
+
+    sandbox.unlocked().args(["archive", "old-job", "--undo"]).assert().success();
+    assert_eq!(ids(sandbox.list_json()), vec!["current", "old-job"]);
+}
+
+#[test]
+fn test_links_are_listed_both_ways_and_flag_removed_entries() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("mail", "ann", "pw-one", &[]);
+    sandbox.add("bank", "ann", "pw-two", &[]);
+
+    let out = sandbox.stdout(sandbox.unlocked().args(["link", "bank", "mail"]));
+    assert!(out.contains("Linked 'bank' → 'mail'"));
+    sandbox.unlocked().args(["link", "bank", "nowhere"]).assert().failure();
+    assert_eq!(sandbox.stdout(sandbox.unlocked().args(["get", "bank", "--links"])), "→ mail\n");
+    assert_eq!(sandbox.stdout(sandbox.unlocked().args(["get", "mail", "--links"])), "← bank\n");
+
+    sandbox.unlocked().args(["rm", "mail", "--force"]).assert().success();
+    let out = sandbox.stdout(sandbox.unlocked().args(["get", "bank", "--links"]));
+    assert!(out.starts_with("→ mail ⚠"), "{}", out);
+
+    sandbox.unlocked().args(["link", "bank", "mail", "--remove"]).assert().success();
+    assert!(sandbox.stdout(sandbox.unlocked().args(["get", "bank", "--links"])).contains("has no links"));
+}
+
+#[test]
+fn test_sealed_entry_needs_its_pin() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("bank", "ann", "pw-bank", &["--note", "seed words"]);
+
+    sandbox.unlocked().args(["seal", "bank"]).write_stdin("2468\n2469\n").assert().code(1);
+    sandbox.unlocked().args(["seal", "bank"]).write_stdin("2468\n2468\n").assert().success();
+
+    let get = || {
+        let mut cmd = sandbox.unlocked();
+        cmd.args(["get", "bank", "--field", "password", "--field", "note"]);
+        cmd
+    };
+    get().write_stdin("1111\n").assert().code(1);
+    assert_eq!(sandbox.stdout(get().write_stdin("2468\n")), "pw-bank\nseed words\n");
+
+    // Left out of exports unless confirmed and opened
+    let export = sandbox.dir.path().join("export.json");
+    sandbox.unlocked().args(["transfer", "export", "--output"]).arg(&export).assert().success();
+    assert!(!std::fs::read_to_string(&export).unwrap().contains("pw-bank"));
+    sandbox.unlocked().args(["transfer", "export", "--include-high-security", "--output"]).arg(&export)
+        .write_stdin("y\n2468\n").assert().success();
+    assert!(std::fs::read_to_string(&export).unwrap().contains("pw-bank"));
+
+    sandbox.unlocked().args(["unseal", "bank"]).write_stdin("2468\n").assert().success();
+    let out = sandbox.stdout(sandbox.unlocked().args(["get", "bank", "--field", "password"]));
+    assert_eq!(out, "pw-bank\n");
+}
+
+#[test]
+fn test_copy_sequence_is_set_and_checked() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("portal", "ann", "pw", &["--copy-sequence", "company=ACME", "--copy-sequence", "username", "--copy-sequence", "password"]);
+    sandbox.add("mail", "ann", "pw", &[]);
+
+    // Steps are checked before anything is written
+    let output = sandbox.unlocked().args(["add", "bank", "--username", "ann", "--password", "pw", "--copy-sequence", "pin"])
+        .assert().failure().get_output().stderr.clone();
+    assert!(String::from_utf8_lossy(&output).contains("Unknown copy step 'pin'"));
+    let output = sandbox.unlocked().args(["get", "mail", "--copy-next"]).assert().failure().get_output().stderr.clone();
+    assert!(String::from_utf8_lossy(&output).contains("has no copy sequence"));
+
+    let export = sandbox.dir.path().join("export.json");
+    sandbox.unlocked().args(["transfer", "export", "--output"]).arg(&export).assert().success();
+    let exported = fs::read_to_string(&export).unwrap();
+    assert!(exported.contains("\"copy_sequence\"") && exported.contains("ACME"));
+}
+
+#[test]
+fn test_changes_lists_fields_changed_since_the_mark() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("github", "octo", "hunter2", &[]);
+    sandbox.add("mail", "ann", "pw", &[]);
+
+    let output = sandbox.unlocked().arg("changes").assert().failure().get_output().stderr.clone();
+    assert!(String::from_utf8_lossy(&output).contains("passman changes mark"));
+    let out = sandbox.stdout(sandbox.unlocked().args(["changes", "mark"]));
+    assert!(out.contains("Marked 2 entries"));
+    assert!(fs::metadata(format!("{}.changes.json", sandbox.vault().display())).is_ok());
+    let out = sandbox.stdout(sandbox.unlocked().arg("changes"));
+    assert!(out.contains("No entries changed"));
+
+    sandbox.add("bank", "ann", "s3cret", &[]);
+    sandbox.unlocked().args(["replace", "--field", "username", "--find", "octo", "--replace", "octocat", "--yes"]).assert().success();
+    let out = sandbox.stdout(sandbox.unlocked().args(["changes", "--since", "last-export", "--output", "json"]));
+    let report: Value = serde_json::from_str(&out).unwrap();
+    let changes = report["changes"].as_array().unwrap();
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0]["id"], "bank");
+    assert_eq!(changes[0]["added"], true);
+    assert_eq!(changes[1]["id"], "github");
+    assert_eq!(changes[1]["fields"], serde_json::json!(["username"]));
+    assert!(!out.contains("s3cret") && changes[1].get("values").is_none());
+
+    let out = sandbox.stdout(sandbox.unlocked().args(["changes", "--include-secrets", "--yes"]));
+    assert!(out.contains("+ bank (username, password)") && out.contains("password: s3cret"));
+}
+
+#[test]
+fn test_check_flags_passwords_on_the_banned_list() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("intranet", "ann", "ACME-Spring-2024", &[]);
+    sandbox.add("mail", "ann", "Tr0ub4dor&3-staple-Horse", &[]);
+    let banned = sandbox.dir.path().join("banned.txt");
+    fs::write(&banned, "# leaked company passwords\nacme-spring-2024\n").unwrap();
+    sandbox.cmd().args(["config", "set", "health.banned_password_file"]).arg(&banned).assert().success();
+
+    let out = sandbox.stdout(sandbox.unlocked().args(["check", "--all"]));
+    let flagged: Vec<&str> = out.lines().filter(|line| line.contains("matches banned list")).collect();
+    assert_eq!(flagged.len(), 1);
+    assert!(out.contains("✗ intranet"));
+    let out = sandbox.stdout(sandbox.cmd().args(["check", "Acme-Spring-2024"]));
+    assert!(out.contains("Password matches banned list"));
+}
+
+#[test]
+fn test_check_shows_as_many_suggestions_as_configured() {
+    let sandbox = Sandbox::new();
+    let out = sandbox.stdout(sandbox.cmd().args(["check", "qwerty"]));
+    assert!(out.contains("Avoid common passwords such as \"qwerty\"") && out.contains("Add numbers"));
+
+    sandbox.cmd().args(["config", "set", "ui.suggestion_detail", "brief"]).assert().success();
+    let out = sandbox.stdout(sandbox.cmd().args(["check", "qwerty"]));
+    let bullets: Vec<&str> = out.lines().filter(|line| line.trim_start().starts_with('•')).collect();
+    assert_eq!(bullets, ["  • Avoid common passwords such as \"qwerty\""]);
+
+    sandbox.cmd().args(["config", "set", "ui.suggestion_detail", "off"]).assert().success();
+    let out = sandbox.stdout(sandbox.cmd().args(["check", "qwerty"]));
+    assert!(out.contains("Strength: Very Weak") && !out.contains('•'));
+    sandbox.cmd().args(["config", "set", "ui.suggestion_detail", "loud"]).assert().failure();
+}
+
+#[test]
+fn test_vault_repair_rewrites_a_non_canonical_salt() {
+    let sandbox = Sandbox::new();
+    fs::write(sandbox.vault(), include_bytes!("../passman-core/tests/fixtures/vault_salt/combined.bin")).unwrap();
+    let repair = || {
+        let mut cmd = sandbox.cmd();
+        cmd.env("PASSMAN_PASSWORD", "correct horse").args(["vault", "repair"]);
+        cmd
+    };
+
+    let out = sandbox.stdout(&mut repair());
+    assert!(out.contains("needed  removed '=' padding") && out.contains("ok      dropped a dangling last character"));
+    assert!(out.contains("Fixed: read the URL-safe base64 alphabet") && out.contains("backed up to"));
+
+    let out = sandbox.stdout(&mut repair());
+    assert!(out.contains("nothing to repair"));
+    let out = sandbox.stdout(sandbox.cmd().env("PASSMAN_PASSWORD", "correct horse").args(["get", "github", "--field", "username"]));
+    assert_eq!(out, "octo\n");
+}
+
+#[test]
+fn test_vault_info_warns_when_file_is_swapped() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    let out = sandbox.stdout(sandbox.cmd().args(["vault", "info"]));
+    assert!(out.contains("Fingerprint:") && out.contains("not unlocked on this machine yet"));
+
+    sandbox.list_json();
+    let out = sandbox.stdout(sandbox.cmd().args(["vault", "info"]));
+    assert!(out.contains("matches the vault previously opened here"));
+
+    let other = Sandbox::new();
+    other.init();
+    fs::copy(other.vault(), sandbox.vault()).unwrap();
+    let output = sandbox.unlocked().args(["list"]).assert().success().get_output().stderr.clone();
+    assert!(String::from_utf8(output).unwrap().contains("not the one previously opened on this machine"));
+}
+
+#[test]
+fn test_vault_status_reports_and_exits_for_monitoring() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.add("github", "octo", "pw-one", &[]);
+    let status = |cmd: &mut Command, code: i32| -> Value {
+        let out = cmd.args(["vault", "status", "--output", "json"]).assert().code(code).get_output().stdout.clone();
+        serde_json::from_slice(&out).unwrap()
+    };
+
+    // No backup yet is a warning
+    let report = status(&mut sandbox.unlocked(), 1);
+    assert_eq!(report["status"], "warnings");
+    assert_eq!(report["hmac_verified"], true);
+    assert_eq!(report["entries"], 1);
+    assert_eq!(report["format_version"], 5);
+    assert!(report["newest_backup"].is_null());
+
+    sandbox.unlocked().arg("backup").assert().success();
+    let report = status(&mut sandbox.unlocked(), 0);
+    assert_eq!(report["status"], "ok");
+    assert_eq!(report["newest_backup"]["verified"], true);
+    assert_eq!(report["housekeeping"]["trashed"], 0);
+
+    // Never prompts: without a password the checks needing one are skipped
+    let report = status(&mut sandbox.cmd(), 1);
+    assert!(report["hmac_verified"].is_null());
+
+    let mut bytes = fs::read(sandbox.vault()).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xFF;
+    fs::write(sandbox.vault(), bytes).unwrap();
+    let report = status(&mut sandbox.unlocked(), 2);
+    assert_eq!(report["status"], "errors");
+    assert_eq!(report["hmac_verified"], false);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_vault_permissions_under_a_relaxed_umask() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let sandbox = Sandbox::new();
+    let mode = |path: &PathBuf| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+    sandbox.relaxed_umask().arg("init").write_stdin(format!("{0}\n{0}\n", MASTER)).assert().success();
+    assert_eq!(mode(&sandbox.vault()), 0o600);
+    sandbox.relaxed_umask().env("PASSMAN_PASSWORD", MASTER).args(["add", "github", "--username", "ann", "--password", "pw"]).assert().success();
+    assert_eq!(mode(&sandbox.vault()), 0o600);
+    let out = sandbox.stdout(sandbox.cmd().arg("doctor"));
+    assert!(out.contains("✓ Permissions") && out.contains("No problems found"), "{}", out);
+
+    // Readable by others: a warning with the fix, or a refusal when strict
+    fs::set_permissions(sandbox.vault(), fs::Permissions::from_mode(0o644)).unwrap();
+    let output = sandbox.unlocked().args(["list"]).assert().success().get_output().stderr.clone();
+    let stderr = String::from_utf8(output).unwrap();
+    assert!(stderr.contains("can be read by other users") && stderr.contains("chmod 600"), "{}", stderr);
+    let output = sandbox.unlocked().args(["--strict-permissions", "list"]).assert().code(1).get_output().stderr.clone();
+    assert!(String::from_utf8(output).unwrap().contains("Refusing to open the vault"));
+
+    let out = sandbox.stdout(sandbox.cmd().arg("doctor"));
+    assert!(out.contains("⚠ Permissions") && out.contains(&format!("Fix: chmod 600 '{}'", sandbox.vault().display())), "{}", out);
+    assert!(out.contains("1 problem found"), "{}", out);
+}
+
+#[test]
+fn test_init_with_cipher_and_rekey_to_another() {
+    let sandbox = Sandbox::new();
+    sandbox.cmd().args(["init", "--cipher", "xchacha20"]).write_stdin(format!("{0}\n{0}\n", MASTER)).assert().success();
+    sandbox.add("github", "ann", "s3cret-Value", &[]);
+    let out = sandbox.stdout(sandbox.cmd().args(["vault", "info"]));
+    assert!(out.contains("Cipher:      xchacha20-poly1305"), "{}", out);
+
+    let out = sandbox.stdout(sandbox.unlocked().args(["vault", "rekey", "--cipher", "aes-256-gcm"]));
+    assert!(out.contains("re-encrypted with aes-256-gcm (was xchacha20-poly1305)"), "{}", out);
+    let out = sandbox.stdout(sandbox.cmd().args(["vault", "info"]));
+    assert!(out.contains("Cipher:      aes-256-gcm"), "{}", out);
+    let out = sandbox.stdout(sandbox.unlocked().args(["get", "github", "--field", "password"]));
+    assert_eq!(out, "s3cret-Value\n");
+
+    sandbox.unlocked().args(["vault", "rekey", "--cipher", "rot13"]).assert().code(1);
+    let unknown = Sandbox::new();
+    unknown.cmd().args(["init", "--cipher", "rot13"]).write_stdin(format!("{0}\n{0}\n", MASTER)).assert().code(1);
+    assert!(!unknown.vault().exists());
+}
+
+#[test]
+fn test_rotate_with_same_rules_reuses_the_last_generation() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.unlocked().args(["add", "bank", "--username", "ann", "--generate", "--length", "11"]).assert().success();
+    sandbox.add("mail", "ann", "typed", &[]);
+
+    let before = sandbox.stdout(sandbox.unlocked().args(["get", "bank", "--field", "password"]));
+    let out = sandbox.stdout(sandbox.unlocked().args(["rotate", "bank", "--same-rules"]));
+    assert!(out.contains("11 characters, random"), "{}", out);
+    let after = sandbox.stdout(sandbox.unlocked().args(["get", "bank", "--field", "password"]));
+    assert_ne!(before, after);
+    assert_eq!(after.trim().len(), 11);
+
+    // A typed password has no rules to repeat until it is rotated once
+    let output = sandbox.unlocked().args(["rotate", "mail", "--same-rules"]).assert().failure().get_output().stderr.clone();
+    assert!(String::from_utf8_lossy(&output).contains("no saved generation rules"));
+    sandbox.unlocked().args(["rotate", "mail", "--length", "14"]).assert().success();
+    let out = sandbox.stdout(sandbox.unlocked().args(["rotate", "mail", "--same-rules"]));
+    assert!(out.contains("14 characters, random"), "{}", out);
+}